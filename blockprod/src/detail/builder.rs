@@ -165,6 +165,8 @@ impl PerpetualBlockBuilder {
                                 )
                     }
                 }
+                chainstate::ChainstateEvent::BlockConnected(_, _)
+                | chainstate::ChainstateEvent::BlockDisconnected(_, _) => {}
             }
         });
 
@@ -191,6 +193,7 @@ impl PerpetualBlockBuilder {
                         )
                 }
             }
+            MempoolEvent::TransactionAdded(_) => {}
         });
 
         self.mempool_handle
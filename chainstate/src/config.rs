@@ -15,6 +15,10 @@
 
 use std::time::Duration;
 
+use common::{
+    chain::Block,
+    primitives::{BlockHeight, Id},
+};
 use utils::make_config_setting;
 
 const DEFAULT_MIN_IMPORT_BUFFER_SIZE: usize = 1 << 22; // 4 MB
@@ -31,7 +35,38 @@ make_config_setting!(
     )
 );
 make_config_setting!(TxIndexEnabled, bool, false);
+make_config_setting!(AddressIndexEnabled, bool, false);
+make_config_setting!(SpentIndexEnabled, bool, false);
+make_config_setting!(BlockFiltersEnabled, bool, false);
+make_config_setting!(HeadersOnly, bool, false);
 make_config_setting!(MaxTipAge, Duration, Duration::from_secs(60 * 60 * 24));
+make_config_setting!(PruneModeSetting, PruneMode, PruneMode::Disabled);
+make_config_setting!(AssumedValidBlock, Option<(BlockHeight, Id<Block>)>, None);
+make_config_setting!(ReindexChainstate, bool, false);
+
+/// Minimum number of blocks below the tip whose data is kept when pruning by size.
+pub const MIN_BLOCKS_TO_KEEP_WHEN_PRUNING: u64 = 288;
+
+/// Defines which old blocks have their bodies and undo data deleted.
+///
+/// Block indexes and the UTXO set are always kept, so the chain can still be followed and
+/// validated, but reorgs deeper than the pruned height are impossible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PruneMode {
+    /// Keep all the blocks.
+    Disabled,
+    /// Keep the data of the given number of blocks below the tip.
+    Depth(u64),
+    /// Keep the total size of the stored blocks under the given number of bytes, while retaining
+    /// at least `MIN_BLOCKS_TO_KEEP_WHEN_PRUNING` blocks.
+    Size(u64),
+}
+
+impl PruneMode {
+    pub fn is_enabled(&self) -> bool {
+        *self != PruneMode::Disabled
+    }
+}
 
 /// The chainstate subsystem configuration.
 #[derive(Debug, Clone, Default)]
@@ -44,9 +79,25 @@ pub struct ChainstateConfig {
     /// (see bootstrap import function for more information)
     pub min_max_bootstrap_import_buffer_sizes: MinMaxBootstrapImportBufferSizes,
     pub tx_index_enabled: TxIndexEnabled,
+    /// Whether the outputs and spends are indexed by destination.
+    pub address_index_enabled: AddressIndexEnabled,
+    /// Whether the inputs that spend each output are indexed.
+    pub spent_index_enabled: SpentIndexEnabled,
+    /// Whether compact filters and filter headers are built for main chain blocks.
+    pub block_filters_enabled: BlockFiltersEnabled,
+    /// Whether only the block headers are validated and kept, without the block data and the
+    /// utxo set.
+    pub headers_only: HeadersOnly,
     /// The initial block download is finished if the difference between the current time and the
     /// tip time is less than this value.
     pub max_tip_age: MaxTipAge,
+    /// Whether and how old blocks are pruned.
+    pub prune_mode: PruneModeSetting,
+    /// Overrides the assumed valid block of the chain config.
+    pub assumed_valid_block: AssumedValidBlock,
+    /// Whether the utxo set and the other data derived from the blocks are dropped and rebuilt
+    /// from the stored blocks on startup.
+    pub reindex_chainstate: ReindexChainstate,
 }
 
 impl ChainstateConfig {
@@ -55,6 +106,11 @@ impl ChainstateConfig {
         Self::default()
     }
 
+    pub fn with_max_db_commit_attempts(mut self, max_db_commit_attempts: usize) -> Self {
+        self.max_db_commit_attempts = max_db_commit_attempts.into();
+        self
+    }
+
     pub fn with_max_orphan_blocks(mut self, max_orphan_blocks: usize) -> Self {
         self.max_orphan_blocks = max_orphan_blocks.into();
         self
@@ -72,4 +128,39 @@ impl ChainstateConfig {
         self.tx_index_enabled = tx_index_enabled.into();
         self
     }
+
+    pub fn with_whether_address_index_enabled(mut self, address_index_enabled: bool) -> Self {
+        self.address_index_enabled = address_index_enabled.into();
+        self
+    }
+
+    pub fn with_whether_spent_index_enabled(mut self, spent_index_enabled: bool) -> Self {
+        self.spent_index_enabled = spent_index_enabled.into();
+        self
+    }
+
+    pub fn with_whether_block_filters_enabled(mut self, block_filters_enabled: bool) -> Self {
+        self.block_filters_enabled = block_filters_enabled.into();
+        self
+    }
+
+    pub fn with_headers_only(mut self, headers_only: bool) -> Self {
+        self.headers_only = headers_only.into();
+        self
+    }
+
+    pub fn with_prune_mode(mut self, prune_mode: PruneMode) -> Self {
+        self.prune_mode = prune_mode.into();
+        self
+    }
+
+    pub fn with_assumed_valid_block(mut self, height: BlockHeight, block_id: Id<Block>) -> Self {
+        self.assumed_valid_block = Some((height, block_id)).into();
+        self
+    }
+
+    pub fn with_reindex_chainstate(mut self, reindex_chainstate: bool) -> Self {
+        self.reindex_chainstate = reindex_chainstate.into();
+        self
+    }
 }
@@ -15,12 +15,12 @@
 
 use super::{
     transaction_verifier::{
-        error::{ConnectTransactionError, TokensError},
+        error::{AddressIndexError, ConnectTransactionError, SpentIndexError, TokensError},
         storage::TransactionVerifierStorageError,
     },
     BlockSizeError, CheckBlockError, CheckBlockTransactionsError, OrphanCheckError, TxIndexError,
 };
-use crate::BlockError;
+use crate::{BlockError, ChainstateError};
 use chainstate_types::GetAncestorError;
 use consensus::{ConsensusPoWError, ConsensusVerificationError};
 
@@ -31,6 +31,18 @@ pub trait BanScore {
     fn ban_score(&self) -> u32;
 }
 
+impl BanScore for ChainstateError {
+    fn ban_score(&self) -> u32 {
+        match self {
+            ChainstateError::FailedToInitializeChainstate(_) => 0,
+            ChainstateError::ProcessBlockError(err) => err.ban_score(),
+            ChainstateError::FailedToReadProperty(_) => 0,
+            ChainstateError::BootstrapError(_) => 0,
+            ChainstateError::UtxoSnapshotError(_) => 0,
+        }
+    }
+}
+
 impl BanScore for BlockError {
     fn ban_score(&self) -> u32 {
         match self {
@@ -48,7 +60,29 @@ impl BanScore for BlockError {
             BlockError::BlockProofCalculationError(_) => 100,
             BlockError::TransactionVerifierError(err) => err.ban_score(),
             BlockError::TxIndexConfigError => 0,
+            BlockError::AddressIndexConfigError => 0,
+            BlockError::SpentIndexConfigError => 0,
+            BlockError::BlockFiltersConfigError => 0,
+            BlockError::MissingBlockFilterHeader(_) => 0,
+            BlockError::HeadersOnlyConfigError => 0,
+            BlockError::BlockDataInHeadersOnlyMode => 0,
+            BlockError::HeadersOnlyModeDisabled => 0,
             BlockError::TxIndexConstructionError(_) => 100,
+            BlockError::InvalidBlockAlreadyProcessed(_) => 100,
+            BlockError::PrevBlockInvalid(_) => 100,
+            BlockError::BlockNotFound(_) => 0,
+            BlockError::ReorgBeyondPrunedHeight(_, _) => 0,
+            BlockError::TxIndexWithPruning => 0,
+            BlockError::UtxoSnapshotIntoNonEmptyChainstate => 0,
+            BlockError::UtxoSnapshotWithTxIndex => 0,
+            BlockError::UtxoSnapshotWithAddressIndex => 0,
+            BlockError::UtxoSnapshotWithSpentIndex => 0,
+            BlockError::UtxoSnapshotWithBlockFilters => 0,
+            BlockError::UtxoSnapshotWithoutBlocks => 0,
+            BlockError::UtxoSnapshotHistoryMismatch(_, _, _) => 0,
+            BlockError::UtxoSnapshotHistoryInvalid(_) => 0,
+            BlockError::ReindexWithoutBlocks => 0,
+            BlockError::VerifyChainDepthTooLarge(_, _) => 0,
         }
     }
 }
@@ -76,7 +110,7 @@ impl BanScore for ConnectTransactionError {
             ConnectTransactionError::MissingCoinOutputToStake => 100,
             ConnectTransactionError::AttemptToPrintMoney(_, _) => 100,
             ConnectTransactionError::TxFeeTotalCalcFailed(_, _) => 100,
-            ConnectTransactionError::SignatureVerificationFailed(_) => 100,
+            ConnectTransactionError::SignatureVerificationFailed(_, _, _) => 100,
             ConnectTransactionError::BlockHeightArithmeticError => 100,
             ConnectTransactionError::BlockTimestampArithmeticError => 100,
             // Even though this is an invariant error, it stems from a block reward that doesn't exist
@@ -92,6 +126,8 @@ impl BanScore for ConnectTransactionError {
             ConnectTransactionError::UtxoError(err) => err.ban_score(),
             ConnectTransactionError::TokensError(err) => err.ban_score(),
             ConnectTransactionError::TxIndexError(err) => err.ban_score(),
+            ConnectTransactionError::AddressIndexError(err) => err.ban_score(),
+            ConnectTransactionError::SpentIndexError(err) => err.ban_score(),
             ConnectTransactionError::InvariantErrorHeaderCouldNotBeLoadedFromHeight(_, _) => 100,
             ConnectTransactionError::BlockIndexCouldNotBeLoaded(_) => 100,
             ConnectTransactionError::TransactionVerifierError(err) => err.ban_score(),
@@ -118,6 +154,8 @@ impl BanScore for TransactionVerifierStorageError {
             TransactionVerifierStorageError::TxIndexError(err) => err.ban_score(),
             TransactionVerifierStorageError::UtxoBlockUndoError(_) => 100,
             TransactionVerifierStorageError::TransactionIndexDisabled => 0,
+            TransactionVerifierStorageError::AddressIndexDisabled => 0,
+            TransactionVerifierStorageError::SpentIndexDisabled => 0,
             TransactionVerifierStorageError::PoSAccountingError(err) => err.ban_score(),
             TransactionVerifierStorageError::AccountingBlockUndoError(_) => 100,
         }
@@ -182,6 +220,28 @@ impl BanScore for CheckBlockError {
     }
 }
 
+// The utxo set is checked before the address index is updated, so these are invariant errors
+impl BanScore for AddressIndexError {
+    fn ban_score(&self) -> u32 {
+        match self {
+            AddressIndexError::OutputAlreadyPresent(_) => 0,
+            AddressIndexError::MissingOutput(_) => 0,
+            AddressIndexError::OutputErased(_) => 0,
+            AddressIndexError::AlreadySpent(_) => 0,
+            AddressIndexError::InvariantBrokenAlreadyUnspent(_) => 0,
+        }
+    }
+}
+
+// The utxo set is checked before the spent-output index is updated, so this is an invariant error
+impl BanScore for SpentIndexError {
+    fn ban_score(&self) -> u32 {
+        match self {
+            SpentIndexError::AlreadySpent(_) => 0,
+        }
+    }
+}
+
 impl BanScore for TokensError {
     fn ban_score(&self) -> u32 {
         match self {
@@ -273,6 +333,7 @@ impl BanScore for utxo::Error {
             utxo::Error::MissingBlockRewardUndo(_) => 0,
             utxo::Error::InvalidBlockRewardOutputType(_) => 100,
             utxo::Error::DBError(_) => 0,
+            utxo::Error::MissingUtxoSetHash(_) => 0,
         }
     }
 }
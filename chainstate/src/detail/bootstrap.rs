@@ -13,12 +13,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::io::{BufRead, Write};
+//! Bootstrap files, which hold blocks exported from one node to be imported into another.
+//!
+//! A file starts with the chain magic bytes and the format version, followed by a header with the
+//! number of blocks and their height range. Each block is prefixed with its length, and the file
+//! ends with the hash of everything before it, so that truncated or corrupted files are detected.
+//!
+//! Files of the first version have no header: they are a sequence of blocks, each prefixed with
+//! the chain magic bytes. They can still be imported.
+
+use std::io::{BufRead, Read, Write};
+use std::ops::RangeInclusive;
 
 use chainstate_storage::BlockchainStorageRead;
 use chainstate_types::{BlockIndex, PropertyQueryError};
-use common::{chain::Block, primitives::id::WithId};
-use serialization::{Decode, Encode};
+use common::{
+    chain::Block,
+    primitives::{
+        id::{DefaultHashAlgoStream, WithId},
+        BlockHeight, H256,
+    },
+};
+use crypto::hash::StreamHasher;
+use logging::log;
+use serialization::{Decode, DecodeAll, Encode};
+use utils::ensure;
 
 use crate::{BlockError, ChainstateConfig};
 
@@ -27,6 +46,15 @@ use super::{
     tx_verification_strategy::TransactionVerificationStrategy,
 };
 
+/// The version of the bootstrap file format, increased on incompatible changes
+pub const BOOTSTRAP_FORMAT_VERSION: u32 = 2;
+
+/// The files of the first version are recognized by the version tag of their first block
+const LEGACY_BLOCK_VERSION_TAG: u8 = 1;
+
+/// The number of imported blocks between the progress reports
+const IMPORT_PROGRESS_INTERVAL: u64 = 1000;
+
 #[derive(thiserror::Error, Debug, Eq, PartialEq)]
 pub enum BootstrapError {
     #[error("File error: {0}")]
@@ -37,6 +65,16 @@ pub enum BootstrapError {
     BlockProcessing(#[from] BlockError),
     #[error("Block import error: {0}")]
     FailedToReadProperty(#[from] PropertyQueryError),
+    #[error("The bootstrap file was created for a different chain")]
+    WrongMagicBytes,
+    #[error("Unsupported bootstrap file format version {0}")]
+    UnsupportedVersion(u32),
+    #[error(
+        "A record of {0} bytes in the bootstrap file is larger than the import buffer of {1} bytes"
+    )]
+    RecordTooLarge(usize, usize),
+    #[error("The bootstrap file is corrupted, its hash is {0} instead of {1}")]
+    CorruptedContent(H256, H256),
 }
 
 impl From<std::io::Error> for BootstrapError {
@@ -45,12 +83,164 @@ impl From<std::io::Error> for BootstrapError {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+struct BootstrapFileHeader {
+    block_count: u64,
+    /// The lowest and the highest heights of the blocks in the file, zero if there are none
+    start_height: BlockHeight,
+    end_height: BlockHeight,
+}
+
+/// Feeds everything that's written to the hasher
+struct HashingWriter<W> {
+    inner: W,
+    hasher: DefaultHashAlgoStream,
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.write(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Feeds everything that's read to the hasher
+struct HashingReader<R> {
+    inner: R,
+    hasher: DefaultHashAlgoStream,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.hasher.write(&buf[..read]);
+        Ok(read)
+    }
+}
+
+fn write_record(writer: &mut impl Write, data: &[u8]) -> Result<(), BootstrapError> {
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(data)?;
+    Ok(())
+}
+
+/// Read a length-prefixed record into the buffer, which is reused between the records
+fn read_record(
+    reader: &mut impl Read,
+    buffer: &mut Vec<u8>,
+    max_record_size: usize,
+) -> Result<(), BootstrapError> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len) as usize;
+    ensure!(
+        len <= max_record_size,
+        BootstrapError::RecordTooLarge(len, max_record_size)
+    );
+
+    buffer.resize(len, 0);
+    reader.read_exact(buffer)?;
+    Ok(())
+}
+
+/// Import the blocks of a bootstrap file, in the order in which they were written.
+///
+/// The blocks are processed as they are read, so the hash of the file is only checked after
+/// the last one. The largest accepted block is bounded by the max import buffer size.
 pub fn import_bootstrap_stream<P, S: std::io::Read>(
     expected_magic_bytes: &[u8],
     file_reader: &mut std::io::BufReader<S>,
     process_block_func: &mut P,
     chainstate_config: &ChainstateConfig,
 ) -> Result<(), BootstrapError>
+where
+    P: FnMut(WithId<Block>) -> Result<Option<BlockIndex>, BlockError>,
+{
+    // An empty file has no blocks
+    if file_reader.fill_buf()?.is_empty() {
+        return Ok(());
+    }
+
+    let mut magic_bytes = vec![0u8; expected_magic_bytes.len()];
+    file_reader.read_exact(&mut magic_bytes)?;
+    ensure!(
+        magic_bytes == expected_magic_bytes,
+        BootstrapError::WrongMagicBytes
+    );
+
+    if file_reader.fill_buf()?.first() == Some(&LEGACY_BLOCK_VERSION_TAG) {
+        return import_legacy_bootstrap_stream(
+            expected_magic_bytes,
+            file_reader,
+            process_block_func,
+            chainstate_config,
+        );
+    }
+
+    let (_, max_record_size) = *chainstate_config.min_max_bootstrap_import_buffer_sizes;
+    let mut reader = HashingReader {
+        inner: file_reader,
+        hasher: DefaultHashAlgoStream::new(),
+    };
+    reader.hasher.write(&magic_bytes);
+
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    ensure!(
+        version == BOOTSTRAP_FORMAT_VERSION,
+        BootstrapError::UnsupportedVersion(version)
+    );
+
+    let mut buffer = Vec::new();
+    read_record(&mut reader, &mut buffer, max_record_size)?;
+    let header = BootstrapFileHeader::decode_all(&mut buffer.as_slice())?;
+    log::info!(
+        "Importing {} blocks with heights from {} to {} from a bootstrap file",
+        header.block_count,
+        header.start_height,
+        header.end_height
+    );
+
+    for imported in 1..=header.block_count {
+        read_record(&mut reader, &mut buffer, max_record_size)?;
+        let block = Block::decode_all(&mut buffer.as_slice())?;
+        process_block_func(block.into())?;
+
+        if imported % IMPORT_PROGRESS_INTERVAL == 0 || imported == header.block_count {
+            log::info!(
+                "Imported {} of {} blocks from the bootstrap file",
+                imported,
+                header.block_count
+            );
+        }
+    }
+
+    let content_hash: H256 = reader.hasher.finalize().into();
+    let mut stored_hash = [0u8; 32];
+    reader.inner.read_exact(&mut stored_hash)?;
+    let stored_hash = H256::from(stored_hash);
+    ensure!(
+        content_hash == stored_hash,
+        BootstrapError::CorruptedContent(content_hash, stored_hash)
+    );
+
+    Ok(())
+}
+
+/// Import a file of the first version, whose blocks are found by looking for the magic bytes.
+/// The magic bytes at the start of the file have already been consumed.
+fn import_legacy_bootstrap_stream<P, S: std::io::Read>(
+    expected_magic_bytes: &[u8],
+    file_reader: &mut std::io::BufReader<S>,
+    process_block_func: &mut P,
+    chainstate_config: &ChainstateConfig,
+) -> Result<(), BootstrapError>
 where
     P: FnMut(WithId<Block>) -> Result<Option<BlockIndex>, BlockError>,
 {
@@ -60,30 +250,31 @@ where
     let (min_buffer_size, max_buffer_size) =
         *chainstate_config.min_max_bootstrap_import_buffer_sizes;
 
-    // It's more reasonable to use a VeqDeque, but it's incompatible with the windows() method which is needed to search for magic bytes
-    // There's a performance hit behind this, but we don't care. Anyone is free to optimize this.
-    let mut buffer_queue = Vec::<u8>::new();
+    // The processed data is only removed from the front of the buffer when it's refilled,
+    // so that the remaining data isn't moved after every block
+    let mut buffer_queue = expected_magic_bytes.to_vec();
+    let mut start = 0;
 
     loop {
-        if buffer_queue.len() < min_buffer_size + expected_magic_bytes.len() {
+        if buffer_queue.len() - start < min_buffer_size + expected_magic_bytes.len() {
+            buffer_queue.drain(..start);
+            start = 0;
             fill_buffer(&mut buffer_queue, file_reader, max_buffer_size)?;
         }
 
         // locate magic bytes to recognize the start of a block
-        let current_pos = buffer_queue
+        let current_pos = buffer_queue[start..]
             .windows(expected_magic_bytes.len())
             .position(|window| window == expected_magic_bytes);
 
         // read the block after the magic bytes
-        let block = match current_pos {
-            Some(v) => Block::decode(&mut &buffer_queue[v + expected_magic_bytes.len()..])?,
+        let block_start = match current_pos {
+            Some(v) => start + v + expected_magic_bytes.len(),
             None => break,
         };
-        let block_len = block.encoded_size();
+        let block = Block::decode(&mut &buffer_queue[block_start..])?;
+        start = block_start + block.encoded_size();
         process_block_func(block.into())?;
-
-        // consume the buffer from the front
-        buffer_queue = buffer_queue[expected_magic_bytes.len() + block_len..].to_vec();
     }
 
     Ok(())
@@ -109,6 +300,7 @@ fn fill_buffer<S: std::io::Read>(
     Ok(())
 }
 
+/// Write the blocks with heights in the given range to a bootstrap file, parents first
 pub fn export_bootstrap_stream<
     'a,
     S: BlockchainStorageRead,
@@ -118,6 +310,7 @@ pub fn export_bootstrap_stream<
     magic_bytes: &[u8],
     writer: &mut std::io::BufWriter<Box<dyn std::io::Write + 'a + Send>>,
     include_orphans: bool,
+    heights: RangeInclusive<BlockHeight>,
     query_interface: &ChainstateQuery<'a, S, O, V>,
 ) -> Result<(), BootstrapError>
 where
@@ -128,12 +321,39 @@ where
         query_interface.get_mainchain_blocks_list()?
     };
 
+    let mut blocks = Vec::new();
     for block_id in blocks_list {
-        writer.write_all(magic_bytes)?;
+        let block_index = query_interface
+            .get_block_index(&block_id)?
+            .ok_or(PropertyQueryError::BlockNotFound(block_id))?;
+        if heights.contains(&block_index.block_height()) {
+            blocks.push((block_id, block_index.block_height()));
+        }
+    }
+
+    let header = BootstrapFileHeader {
+        block_count: blocks.len() as u64,
+        start_height: blocks.iter().map(|(_, height)| *height).min().unwrap_or(BlockHeight::zero()),
+        end_height: blocks.iter().map(|(_, height)| *height).max().unwrap_or(BlockHeight::zero()),
+    };
+
+    let mut writer = HashingWriter {
+        inner: writer,
+        hasher: DefaultHashAlgoStream::new(),
+    };
+    writer.write_all(magic_bytes)?;
+    writer.write_all(&BOOTSTRAP_FORMAT_VERSION.to_le_bytes())?;
+    write_record(&mut writer, &header.encode())?;
+
+    for (block_id, _) in blocks {
         let block = query_interface
             .get_block(block_id)?
             .ok_or(PropertyQueryError::BlockNotFound(block_id))?;
-        writer.write_all(&block.encode())?;
+        write_record(&mut writer, &block.encode())?;
     }
+
+    let content_hash: H256 = writer.hasher.finalize().into();
+    writer.inner.write_all(content_hash.as_bytes())?;
+    writer.inner.flush()?;
     Ok(())
 }
@@ -13,23 +13,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::BTreeSet, convert::TryInto};
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    convert::TryInto,
+};
 
 use chainstate_storage::{BlockchainStorageRead, BlockchainStorageWrite, TransactionRw};
 use chainstate_types::{
-    block_index_ancestor_getter, get_skip_height, BlockIndex, BlockIndexHandle, GenBlockIndex,
-    GetAncestorError, PropertyQueryError,
+    block_index_ancestor_getter, get_skip_height, AddressHistoryEntry, BlockIndex,
+    BlockIndexHandle, BlockStatus, ChainTip, ChainTipStatus, GenBlockIndex, GetAncestorError,
+    OutputSpendInfo, PropertyQueryError, UtxoSetInfo,
 };
 use common::{
     chain::{
         block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
             calculate_tx_merkle_root, calculate_witness_merkle_root, BlockHeader, BlockReward,
         },
         tokens::TokenAuxiliaryData,
-        tokens::{get_tokens_issuance_count, OutputValue, TokenId},
-        Block, ChainConfig, GenBlock, GenBlockId, OutPointSourceId, Transaction,
+        tokens::{get_tokens_issuance_count, CoinOrTokenId, OutputValue, TokenData, TokenId},
+        Block, ChainConfig, Destination, GenBlock, GenBlockId, OutPoint, OutPointSourceId,
+        Transaction,
     },
-    primitives::{id::WithId, BlockDistance, BlockHeight, Id, Idable},
+    primitives::{id::WithId, Amount, BlockDistance, BlockHeight, Id, Idable},
     time_getter::TimeGetterFn,
     Uint256,
 };
@@ -37,9 +43,11 @@ use consensus::TransactionIndexHandle;
 use logging::log;
 use tx_verifier::transaction_verifier::{config::TransactionVerifierConfig, TransactionVerifier};
 use utils::{ensure, tap_error_log::LogError};
-use utxo::{UtxosDB, UtxosView};
+use utxo::{UtxoSetHash, UtxosDB, UtxosView};
 
-use crate::{BlockError, BlockSource, ChainstateConfig};
+use crate::{
+    BlockError, BlockSource, ChainstateConfig, PruneMode, MIN_BLOCKS_TO_KEEP_WHEN_PRUNING,
+};
 
 use self::tx_verifier_storage::gen_block_index_getter;
 
@@ -49,10 +57,23 @@ use super::{
     tokens::check_tokens_data,
     transaction_verifier::{error::TokensError, flush::flush_to_storage},
     tx_verification_strategy::TransactionVerificationStrategy,
-    BlockSizeError, CheckBlockError, CheckBlockTransactionsError, OrphanCheckError,
+    utxo_snapshot::UtxoSnapshot,
+    BlockSizeError, CheckBlockError, CheckBlockTransactionsError, OrphanCheckError, ReorgError,
 };
 
 mod tx_verifier_storage;
+mod verify_chain;
+
+/// The token issued by the transaction that created the output
+fn issuance_token_id(
+    issuance_token_ids: &BTreeMap<Id<Transaction>, TokenId>,
+    outpoint: &OutPoint,
+) -> Option<TokenId> {
+    match outpoint.tx_id() {
+        OutPointSourceId::Transaction(tx_id) => issuance_token_ids.get(&tx_id).copied(),
+        OutPointSourceId::BlockReward(_) => None,
+    }
+}
 
 pub struct ChainstateRef<'a, S, O, V> {
     chain_config: &'a ChainConfig,
@@ -204,6 +225,88 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         self.db_tx.get_mainchain_tx_index(tx_id).map_err(PropertyQueryError::from)
     }
 
+    fn get_address_index_entries(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, PropertyQueryError> {
+        ensure!(
+            *self.chainstate_config.address_index_enabled,
+            PropertyQueryError::AddressIndexDisabled
+        );
+        let entries = self.db_tx.get_address_index_entries(destination)?;
+        Ok(entries
+            .into_iter()
+            .map(|((coin_or_token_id, outpoint), info)| AddressHistoryEntry {
+                coin_or_token_id,
+                outpoint,
+                info,
+            })
+            .collect())
+    }
+
+    /// Total amounts of coins and tokens in the unspent outputs of the destination
+    pub fn get_address_balance(
+        &self,
+        destination: &Destination,
+    ) -> Result<BTreeMap<CoinOrTokenId, Amount>, PropertyQueryError> {
+        let mut balance = BTreeMap::<CoinOrTokenId, Amount>::new();
+        for entry in self.get_address_index_entries(destination)? {
+            if entry.info.is_spent() {
+                continue;
+            }
+            let total = balance.entry(entry.coin_or_token_id).or_insert(Amount::ZERO);
+            *total =
+                (*total + entry.info.amount()).ok_or(PropertyQueryError::AddressBalanceOverflow)?;
+        }
+        Ok(balance)
+    }
+
+    /// All the outputs ever sent to the destination in the main chain, oldest first
+    pub fn get_address_history(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, PropertyQueryError> {
+        let mut history = self.get_address_index_entries(destination)?;
+        history.sort_by_key(|entry| entry.info.block_height());
+        Ok(history)
+    }
+
+    /// The input that spent the output in the main chain, if any
+    pub fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, PropertyQueryError> {
+        ensure!(
+            *self.chainstate_config.spent_index_enabled,
+            PropertyQueryError::SpentIndexDisabled
+        );
+        self.db_tx.get_output_spend_info(outpoint).map_err(PropertyQueryError::from)
+    }
+
+    /// The compact filter of a main chain block
+    pub fn get_block_filter(
+        &self,
+        block_id: &Id<Block>,
+    ) -> Result<Option<BlockFilter>, PropertyQueryError> {
+        ensure!(
+            *self.chainstate_config.block_filters_enabled,
+            PropertyQueryError::BlockFiltersDisabled
+        );
+        self.db_tx.get_block_filter(block_id).map_err(PropertyQueryError::from)
+    }
+
+    /// The filter header of a main chain block
+    pub fn get_block_filter_header(
+        &self,
+        block_id: &Id<Block>,
+    ) -> Result<Option<BlockFilterHeader>, PropertyQueryError> {
+        ensure!(
+            *self.chainstate_config.block_filters_enabled,
+            PropertyQueryError::BlockFiltersDisabled
+        );
+        self.db_tx.get_block_filter_header(block_id).map_err(PropertyQueryError::from)
+    }
+
     pub fn get_mainchain_tx_by_position(
         &self,
         tx_index: &common::chain::TxMainChainPosition,
@@ -297,6 +400,30 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         }
     }
 
+    /// Height of the last main chain block whose body and undo data were pruned
+    pub fn get_pruned_height(&self) -> Result<Option<BlockHeight>, PropertyQueryError> {
+        self.db_tx.get_pruned_height().map_err(PropertyQueryError::from)
+    }
+
+    /// The block of the loaded utxo snapshot, if its history is not validated yet
+    pub fn get_utxo_snapshot_block(&self) -> Result<Option<Id<Block>>, PropertyQueryError> {
+        self.db_tx.get_utxo_snapshot_block().map_err(PropertyQueryError::from)
+    }
+
+    /// The entries of the in-memory chainstate used to validate the snapshot history, as saved
+    /// at the last checkpoint
+    pub fn get_utxo_snapshot_validation_entries(
+        &self,
+    ) -> Result<chainstate_storage::UtxoSnapshotValidationEntries, PropertyQueryError> {
+        self.db_tx
+            .get_utxo_snapshot_validation_entries()
+            .map_err(PropertyQueryError::from)
+    }
+
+    pub fn get_invalid_utxo_snapshot_block(&self) -> Result<Option<Id<Block>>, PropertyQueryError> {
+        self.db_tx.get_invalid_utxo_snapshot_block().map_err(PropertyQueryError::from)
+    }
+
     pub fn get_best_block_index(&self) -> Result<Option<GenBlockIndex>, PropertyQueryError> {
         self.get_gen_block_index(&self.get_best_block_id().log_err()?)
     }
@@ -390,6 +517,29 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         Ok(())
     }
 
+    /// Reject blocks that are already known to be invalid or that build on top of an invalid block
+    pub fn check_not_known_invalid(&self, header: &BlockHeader) -> Result<(), BlockError> {
+        let block_id = header.block_id();
+        if let Some(block_index) = self.db_tx.get_block_index(&block_id).log_err()? {
+            ensure!(
+                !block_index.status().is_failed(),
+                BlockError::InvalidBlockAlreadyProcessed(block_id)
+            );
+        }
+
+        if let GenBlockId::Block(prev_block_id) = header.prev_block_id().classify(self.chain_config)
+        {
+            if let Some(prev_block_index) = self.db_tx.get_block_index(&prev_block_id).log_err()? {
+                ensure!(
+                    !prev_block_index.status().is_failed(),
+                    BlockError::PrevBlockInvalid(prev_block_id)
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn check_block_header(&self, header: &BlockHeader) -> Result<(), CheckBlockError> {
         self.check_header_size(header).log_err()?;
 
@@ -645,12 +795,11 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         Ok(())
     }
 
-    fn get_block_proof(&self, block: &Block) -> Result<Uint256, BlockError> {
-        block
-            .header()
+    fn get_block_proof(&self, header: &BlockHeader) -> Result<Uint256, BlockError> {
+        header
             .consensus_data()
             .get_block_proof()
-            .ok_or_else(|| BlockError::BlockProofCalculationError(block.get_id()))
+            .ok_or_else(|| BlockError::BlockProofCalculationError(header.block_id()))
     }
 
     pub fn get_mainchain_blocks_list(&self) -> Result<Vec<Id<Block>>, PropertyQueryError> {
@@ -678,6 +827,153 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         Ok(result)
     }
 
+    /// Collect the indexes of all the stored blocks that descend from the given block
+    pub fn get_descendants(
+        &self,
+        block_index: &BlockIndex,
+    ) -> Result<Vec<BlockIndex>, PropertyQueryError> {
+        let mut result = Vec::new();
+        let mut to_visit = VecDeque::from([*block_index.block_id()]);
+        while let Some(id) = to_visit.pop_front() {
+            for child_id in self.db_tx.get_block_children(&id.into()).log_err()? {
+                let child = self
+                    .get_block_index(&child_id)
+                    .log_err()?
+                    .ok_or(PropertyQueryError::BlockNotFound(child_id))
+                    .log_err()?;
+                to_visit.push_back(child_id);
+                result.push(child);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Find the stored block with the highest chain trust that isn't known to be invalid,
+    /// if its chain trust is higher than the one of the current tip
+    pub fn get_best_valid_candidate(&self) -> Result<Option<BlockIndex>, PropertyQueryError> {
+        let best_block_index = self
+            .get_best_block_index()
+            .log_err()?
+            .expect("Best block index not present in the database");
+
+        // The descendants of an invalid block are invalid too, so the best candidate of each
+        // branch is found by going back from its tip past the invalid blocks
+        let mut result: Option<BlockIndex> = None;
+        for leaf_id in self.db_tx.get_block_tree_leaves().log_err()? {
+            let mut id = leaf_id;
+            let candidate = loop {
+                let block_index = self
+                    .get_block_index(&id)
+                    .log_err()?
+                    .ok_or(PropertyQueryError::BlockNotFound(id))
+                    .log_err()?;
+                if block_index.chain_trust() <= best_block_index.chain_trust() {
+                    break None;
+                }
+                if !block_index.status().is_failed() {
+                    break Some(block_index);
+                }
+                match block_index.prev_block_id().classify(self.chain_config) {
+                    GenBlockId::Block(prev_block_id) => id = prev_block_id,
+                    GenBlockId::Genesis(_) => break None,
+                }
+            };
+            let candidate = match candidate {
+                Some(candidate) => candidate,
+                None => continue,
+            };
+            let is_better = match &result {
+                Some(best) => candidate.chain_trust() > best.chain_trust(),
+                None => true,
+            };
+            if is_better {
+                result = Some(candidate);
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns true if the first block is the second one or one of its ancestors
+    fn is_ancestor_of(
+        &self,
+        block_index: &BlockIndex,
+        descendant_index: &BlockIndex,
+    ) -> Result<bool, PropertyQueryError> {
+        if block_index.block_height() > descendant_index.block_height() {
+            return Ok(false);
+        }
+        let ancestor = self
+            .get_ancestor(
+                &GenBlockIndex::Block(descendant_index.clone()),
+                block_index.block_height(),
+            )
+            .log_err()?;
+        Ok(ancestor.block_id() == Id::<GenBlock>::from(*block_index.block_id()))
+    }
+
+    /// Check whether the signatures of a block can be skipped because it's an ancestor of the
+    /// assumed valid block, when connecting the chain that ends with `new_tip_index`.
+    ///
+    /// The assumed valid block has to be in the block index, so its header has been checked, and
+    /// has to be in the new chain or to descend from its tip. The signatures are checked as long as
+    /// the assumed valid block is unknown, as nothing proves that the block is its ancestor.
+    fn is_assumed_valid(
+        &self,
+        block_index: &BlockIndex,
+        new_tip_index: &BlockIndex,
+    ) -> Result<bool, PropertyQueryError> {
+        let (assumed_valid_height, assumed_valid_block_id) =
+            match (*self.chainstate_config.assumed_valid_block)
+                .or_else(|| self.chain_config.assumed_valid_block())
+            {
+                Some(assumed_valid_block) => assumed_valid_block,
+                None => return Ok(false),
+            };
+        if block_index.block_height() > assumed_valid_height {
+            return Ok(false);
+        }
+
+        let assumed_valid_block_index =
+            match self.get_block_index(&assumed_valid_block_id).log_err()? {
+                Some(block_index) => block_index,
+                None => return Ok(false),
+            };
+
+        if assumed_valid_block_index.block_height() != assumed_valid_height {
+            log::warn!(
+                "Assumed valid block {} is at height {}, not {}, checking all the signatures",
+                assumed_valid_block_id,
+                assumed_valid_block_index.block_height(),
+                assumed_valid_height,
+            );
+            return Ok(false);
+        }
+        if assumed_valid_block_index.status().is_failed() {
+            log::warn!(
+                "Assumed valid block {} is invalid, checking all the signatures",
+                assumed_valid_block_id
+            );
+            return Ok(false);
+        }
+
+        let in_new_chain = self.is_ancestor_of(&assumed_valid_block_index, new_tip_index)?
+            || self.is_ancestor_of(new_tip_index, &assumed_valid_block_index)?;
+        if !in_new_chain {
+            log::warn!(
+                "Assumed valid block {} is not in the best chain, checking all the signatures",
+                assumed_valid_block_id
+            );
+            return Ok(false);
+        }
+
+        log::info!(
+            "Skipping the signature checks of block {}, an ancestor of the assumed valid block {}",
+            block_index.block_id(),
+            assumed_valid_block_id
+        );
+        Ok(true)
+    }
+
     pub fn get_block_id_tree_as_list(&self) -> Result<Vec<Id<Block>>, PropertyQueryError> {
         let block_tree_map = self.db_tx.get_block_tree_by_height().log_err()?;
         let result = block_tree_map
@@ -686,6 +982,147 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
             .collect();
         Ok(result)
     }
+
+    /// Summarize the utxo set at the tip of the main chain
+    pub fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, PropertyQueryError> {
+        let best_block_index = self
+            .get_best_block_index()
+            .log_err()?
+            .expect("Best block index not present in the database");
+        let best_block_id = best_block_index.block_id();
+        let set_hash = self
+            .db_tx
+            .get_utxo_set_hash(&best_block_id)
+            .log_err()?
+            .ok_or(PropertyQueryError::UtxoSetHashNotFound(best_block_id))?;
+
+        let utxos = self.db_tx.get_utxo_set().log_err()?;
+        let issuance_token_ids = self.db_tx.get_all_token_ids().log_err()?;
+
+        let mut coin_amount = Amount::ZERO;
+        let mut token_amounts = BTreeMap::<TokenId, Amount>::new();
+        for (outpoint, utxo) in &utxos {
+            let (token_id, amount) = match utxo.output().value() {
+                OutputValue::Coin(amount) => {
+                    coin_amount =
+                        (coin_amount + *amount).ok_or(PropertyQueryError::UtxoSetAmountOverflow)?;
+                    continue;
+                }
+                OutputValue::Token(token_data) => match &**token_data {
+                    TokenData::TokenTransfer(transfer) => (transfer.token_id, transfer.amount),
+                    TokenData::TokenIssuance(issuance) => {
+                        match issuance_token_id(&issuance_token_ids, outpoint) {
+                            Some(token_id) => (token_id, issuance.amount_to_issue),
+                            None => continue,
+                        }
+                    }
+                    TokenData::NftIssuance(_) => {
+                        match issuance_token_id(&issuance_token_ids, outpoint) {
+                            Some(token_id) => (token_id, Amount::from_atoms(1)),
+                            None => continue,
+                        }
+                    }
+                },
+            };
+            let total = token_amounts.entry(token_id).or_insert(Amount::ZERO);
+            *total = (*total + amount).ok_or(PropertyQueryError::UtxoSetAmountOverflow)?;
+        }
+
+        Ok(UtxoSetInfo {
+            best_block_id,
+            best_block_height: best_block_index.block_height(),
+            utxo_count: utxos.len() as u64,
+            coin_amount,
+            token_amounts,
+            set_hash: set_hash.digest(),
+        })
+    }
+
+    /// Collect the state at the tip of the main chain into a utxo snapshot
+    pub fn create_utxo_snapshot(&self) -> Result<UtxoSnapshot, PropertyQueryError> {
+        let headers = self
+            .get_mainchain_blocks_list()
+            .log_err()?
+            .into_iter()
+            .map(|block_id| {
+                self.get_block_index(&block_id)?
+                    .map(BlockIndex::into_block_header)
+                    .ok_or(PropertyQueryError::BlockNotFound(block_id))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .log_err()?;
+
+        Ok(UtxoSnapshot::new(
+            headers,
+            self.db_tx.get_utxo_set().log_err()?,
+            self.db_tx.get_accounting_data().log_err()?,
+            self.db_tx.get_all_token_aux_data().log_err()?,
+            self.db_tx.get_all_token_ids().log_err()?,
+        ))
+    }
+
+    /// Collect the leaves of the block tree, along with the tip of the main chain
+    pub fn get_chain_tips(&self) -> Result<Vec<ChainTip>, PropertyQueryError> {
+        let best_block_index = self
+            .get_best_block_index()
+            .log_err()?
+            .expect("Best block index not present in the database");
+        let block_tree_map = self.db_tx.get_block_tree_by_height().log_err()?;
+
+        let mut block_indexes = Vec::new();
+        let mut parents = BTreeSet::new();
+        for id in block_tree_map.into_values().flatten() {
+            let block_index = self
+                .get_block_index(&id)
+                .log_err()?
+                .ok_or(PropertyQueryError::BlockNotFound(id))
+                .log_err()?;
+            parents.insert(*block_index.prev_block_id());
+            block_indexes.push(block_index);
+        }
+
+        let mut result = vec![ChainTip {
+            block_id: best_block_index.block_id(),
+            height: best_block_index.block_height(),
+            branch_length: BlockDistance::new(0),
+            chain_trust: *best_block_index.chain_trust(),
+            status: ChainTipStatus::Active,
+        }];
+
+        for block_index in block_indexes {
+            let block_id: Id<GenBlock> = (*block_index.block_id()).into();
+            if parents.contains(&block_id) || block_id == best_block_index.block_id() {
+                continue;
+            }
+
+            let fork_point = self
+                .last_common_ancestor(
+                    &GenBlockIndex::Block(block_index.clone()),
+                    &best_block_index,
+                )
+                .log_err()?;
+            let branch_length = (block_index.block_height() - fork_point.block_height())
+                .expect("The fork point cannot be higher than the tip");
+
+            let status = if block_index.status().is_failed() {
+                ChainTipStatus::Invalid
+            } else if block_index.status().has_valid_transactions() {
+                ChainTipStatus::ValidFork
+            } else {
+                ChainTipStatus::HeadersOnly
+            };
+
+            result.push(ChainTip {
+                block_id,
+                height: block_index.block_height(),
+                branch_length,
+                chain_trust: *block_index.chain_trust(),
+                status,
+            });
+        }
+
+        Ok(result)
+    }
 }
 
 impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificationStrategy>
@@ -716,6 +1153,22 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         to_disconnect: &BlockIndex,
         last_to_remain_connected: &Id<GenBlock>,
     ) -> Result<(), BlockError> {
+        // The undo data of pruned blocks is gone, so they can't be disconnected
+        if let Some(pruned_height) =
+            self.get_pruned_height().map_err(BlockError::BestBlockLoadError).log_err()?
+        {
+            let fork_height = self
+                .get_gen_block_index(last_to_remain_connected)
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?
+                .expect("Inconsistent DB")
+                .block_height();
+            ensure!(
+                fork_height >= pruned_height,
+                BlockError::ReorgBeyondPrunedHeight(fork_height, pruned_height)
+            );
+        }
+
         let mut to_disconnect = GenBlockIndex::Block(to_disconnect.clone());
         while to_disconnect.block_id() != *last_to_remain_connected {
             let to_disconnect_block = match to_disconnect {
@@ -731,7 +1184,7 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         &mut self,
         best_block_id: &Id<GenBlock>,
         new_block_index: &BlockIndex,
-    ) -> Result<(), BlockError> {
+    ) -> Result<(), ReorgError> {
         let new_chain = self
             .get_new_chain(new_block_index)
             .map_err(|e| {
@@ -763,7 +1216,13 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
 
         // Connect the new chain
         for block_index in new_chain {
-            self.connect_tip(&block_index).log_err()?;
+            let skip_signature_verification = self
+                .is_assumed_valid(&block_index, new_block_index)
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?;
+            self.connect_tip(&block_index, skip_signature_verification)
+                .map_err(|err| ReorgError::ConnectTipFailed(*block_index.block_id(), err))
+                .log_err()?;
         }
 
         Ok(())
@@ -773,9 +1232,13 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         &mut self,
         block_index: &BlockIndex,
         block: &WithId<Block>,
+        skip_signature_verification: bool,
     ) -> Result<(), BlockError> {
         let verifier_config = TransactionVerifierConfig {
             tx_index_enabled: *self.chainstate_config.tx_index_enabled,
+            address_index_enabled: *self.chainstate_config.address_index_enabled,
+            spent_index_enabled: *self.chainstate_config.spent_index_enabled,
+            skip_signature_verification,
         };
         let connected_txs = self
             .tx_verification_strategy
@@ -797,9 +1260,10 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
     }
 
     fn disconnect_transactions(&mut self, block: &WithId<Block>) -> Result<(), BlockError> {
-        let verifier_config = TransactionVerifierConfig {
-            tx_index_enabled: *self.chainstate_config.tx_index_enabled,
-        };
+        let verifier_config =
+            TransactionVerifierConfig::new(*self.chainstate_config.tx_index_enabled)
+                .with_address_index_enabled(*self.chainstate_config.address_index_enabled)
+                .with_spent_index_enabled(*self.chainstate_config.spent_index_enabled);
         let cached_inputs = self.tx_verification_strategy.disconnect_block(
             TransactionVerifier::new,
             &*self,
@@ -810,23 +1274,75 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         let cached_inputs = cached_inputs.consume()?;
         flush_to_storage(self, cached_inputs)?;
 
+        // The utxo set hashes are only kept for the blocks of the main chain
+        self.db_tx.del_utxo_set_hash(&block.get_id().into()).log_err()?;
+
+        Ok(())
+    }
+
+    fn connect_block_filter(
+        &mut self,
+        block_index: &BlockIndex,
+        block: &WithId<Block>,
+    ) -> Result<(), BlockError> {
+        if !*self.chainstate_config.block_filters_enabled {
+            return Ok(());
+        }
+
+        let prev_header = match block_index.prev_block_id().classify(self.chain_config) {
+            GenBlockId::Genesis(_) => BlockFilterHeader::zero(),
+            GenBlockId::Block(prev_block_id) => self
+                .db_tx
+                .get_block_filter_header(&prev_block_id)
+                .log_err()?
+                .ok_or(BlockError::MissingBlockFilterHeader(prev_block_id))
+                .log_err()?,
+        };
+        let filter = BlockFilter::from_block(block);
+        let header = BlockFilterHeader::new(&filter, &prev_header);
+
+        self.db_tx.set_block_filter(block_index.block_id(), &filter).log_err()?;
+        self.db_tx.set_block_filter_header(block_index.block_id(), &header).log_err()?;
+        Ok(())
+    }
+
+    fn disconnect_block_filter(&mut self, block_id: &Id<Block>) -> Result<(), BlockError> {
+        if !*self.chainstate_config.block_filters_enabled {
+            return Ok(());
+        }
+
+        self.db_tx.del_block_filter(block_id).log_err()?;
+        self.db_tx.del_block_filter_header(block_id).log_err()?;
         Ok(())
     }
 
     // Connect new block
-    fn connect_tip(&mut self, new_tip_block_index: &BlockIndex) -> Result<(), BlockError> {
+    fn connect_tip(
+        &mut self,
+        new_tip_block_index: &BlockIndex,
+        skip_signature_verification: bool,
+    ) -> Result<(), BlockError> {
         let best_block_id =
             self.get_best_block_id().map_err(BlockError::BestBlockLoadError).log_err()?;
         utils::ensure!(
             &best_block_id == new_tip_block_index.prev_block_id(),
             BlockError::InvariantErrorInvalidTip,
         );
-        let block = self
+        let block: WithId<Block> = self
             .get_block_from_index(new_tip_block_index)
             .log_err()?
-            .expect("Inconsistent DB");
+            .expect("Inconsistent DB")
+            .into();
+
+        self.connect_transactions(new_tip_block_index, &block, skip_signature_verification)
+            .log_err()?;
+        self.connect_block_filter(new_tip_block_index, &block).log_err()?;
 
-        self.connect_transactions(new_tip_block_index, &block.into()).log_err()?;
+        if !new_tip_block_index.status().has_valid_transactions() {
+            let mut status = new_tip_block_index.status();
+            status.set_valid_transactions();
+            self.set_block_status(new_tip_block_index.clone(), status).log_err()?;
+        }
 
         self.db_tx
             .set_block_id_at_height(
@@ -866,6 +1382,7 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         let block = self.get_block_from_index(&block_index).log_err()?.expect("Inconsistent DB");
         // Disconnect transactions
         self.disconnect_transactions(&block.into()).log_err()?;
+        self.disconnect_block_filter(&best_block_id).log_err()?;
         self.db_tx.set_best_block_id(block_index.prev_block_id()).log_err()?;
         // Disconnect block
         self.db_tx.del_block_id_at_height(&block_index.block_height()).log_err()?;
@@ -880,7 +1397,7 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         &mut self,
         new_block_index: BlockIndex,
         best_block_id: Id<GenBlock>,
-    ) -> Result<Option<BlockIndex>, BlockError> {
+    ) -> Result<Option<BlockIndex>, ReorgError> {
         // Chain trust is higher than the best block
         let current_best_block_index = self
             .get_gen_block_index(&best_block_id)
@@ -890,16 +1407,22 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
 
         if new_block_index.chain_trust() > current_best_block_index.chain_trust() {
             self.reorganize(&best_block_id, &new_block_index).log_err()?;
+            // Reload the index as its status has been updated while connecting
+            let new_block_index = self
+                .get_block_index(new_block_index.block_id())
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?
+                .expect("Inconsistent DB");
             return Ok(Some(new_block_index));
         }
 
         Ok(None)
     }
 
-    fn add_to_block_index(&mut self, block: &WithId<Block>) -> Result<BlockIndex, BlockError> {
+    fn add_to_block_index(&mut self, header: &BlockHeader) -> Result<BlockIndex, BlockError> {
         if let Some(bi) = self
             .db_tx
-            .get_block_index(&block.get_id())
+            .get_block_index(&header.block_id())
             .map_err(BlockError::from)
             .log_err()?
         {
@@ -907,7 +1430,7 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         }
 
         let prev_block_index = self
-            .get_gen_block_index(&block.prev_block_id())
+            .get_gen_block_index(header.prev_block_id())
             .map_err(BlockError::BestBlockLoadError)
             .log_err()?
             .ok_or(BlockError::PrevBlockNotFound)
@@ -918,22 +1441,22 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
 
         let some_ancestor = {
             let skip_ht = get_skip_height(height);
-            let err = |_| panic!("Ancestor retrieval failed for block: {}", block.get_id());
+            let err = |_| panic!("Ancestor retrieval failed for block: {}", header.block_id());
             self.get_ancestor(&prev_block_index, skip_ht).unwrap_or_else(err).block_id()
         };
 
         // Set Time Max
-        let time_max = std::cmp::max(prev_block_index.chain_timestamps_max(), block.timestamp());
+        let time_max = std::cmp::max(prev_block_index.chain_timestamps_max(), header.timestamp());
 
         // Set Chain Trust
         let chain_trust =
-            *prev_block_index.chain_trust() + self.get_block_proof(block).log_err()?;
-        let block_index = BlockIndex::new(block, chain_trust, some_ancestor, height, time_max);
+            *prev_block_index.chain_trust() + self.get_block_proof(header).log_err()?;
+        let block_index = BlockIndex::new(header, chain_trust, some_ancestor, height, time_max);
         Ok(block_index)
     }
 
     pub fn accept_block(&mut self, block: &WithId<Block>) -> Result<BlockIndex, BlockError> {
-        let block_index = self.add_to_block_index(block).log_err()?;
+        let block_index = self.add_to_block_index(block.header()).log_err()?;
         if (self.db_tx.get_block(block.get_id()).map_err(BlockError::from).log_err()?).is_some() {
             return Err(BlockError::BlockAlreadyExists(block.get_id()));
         }
@@ -944,6 +1467,324 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocksMut, V: TransactionVerificati
         Ok(block_index)
     }
 
+    /// Add a header to the block index without the block data
+    pub fn accept_header(&mut self, header: &BlockHeader) -> Result<BlockIndex, BlockError> {
+        let block_index = self.add_to_block_index(header).log_err()?;
+        self.check_block_index(&block_index).log_err()?;
+        self.db_tx.set_block_index(&block_index).map_err(BlockError::from).log_err()?;
+        Ok(block_index)
+    }
+
+    /// Make the header chain with the most trust the main chain.
+    ///
+    /// There is no block data to connect, so only the main chain heights and the best block
+    /// are updated.
+    pub fn activate_best_header_chain(
+        &mut self,
+        new_block_index: BlockIndex,
+    ) -> Result<Option<BlockIndex>, BlockError> {
+        let best_block_id =
+            self.get_best_block_id().map_err(BlockError::BestBlockLoadError).log_err()?;
+        let current_best_block_index = self
+            .get_gen_block_index(&best_block_id)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .expect("Inconsistent DB");
+        if new_block_index.chain_trust() <= current_best_block_index.chain_trust() {
+            return Ok(None);
+        }
+
+        let new_chain = self
+            .get_new_chain(&new_block_index)
+            .map_err(|e| {
+                BlockError::InvariantErrorFailedToFindNewChainPath(
+                    *new_block_index.block_id(),
+                    best_block_id,
+                    e,
+                )
+            })
+            .log_err()?;
+        let common_ancestor_id = *new_chain
+            .first()
+            .expect("There is at least one header to connect")
+            .prev_block_id();
+
+        let mut to_disconnect = current_best_block_index;
+        while to_disconnect.block_id() != common_ancestor_id {
+            self.db_tx.del_block_id_at_height(&to_disconnect.block_height()).log_err()?;
+            to_disconnect = match to_disconnect {
+                GenBlockIndex::Genesis(_) => panic!("Attempt to disconnect genesis"),
+                GenBlockIndex::Block(block_index) => self
+                    .get_previous_block_index(&block_index)
+                    .map_err(BlockError::BestBlockLoadError)
+                    .log_err()?,
+            };
+        }
+
+        for block_index in &new_chain {
+            self.db_tx
+                .set_block_id_at_height(
+                    &block_index.block_height(),
+                    &(*block_index.block_id()).into(),
+                )
+                .log_err()?;
+        }
+        self.db_tx.set_best_block_id(&(*new_block_index.block_id()).into()).log_err()?;
+
+        Ok(Some(new_block_index))
+    }
+
+    /// Delete the bodies and undo data of the main chain blocks that are no longer needed
+    /// according to the prune mode
+    pub fn prune_blocks(&mut self) -> Result<(), BlockError> {
+        let prune_mode = *self.chainstate_config.prune_mode;
+        if !prune_mode.is_enabled() {
+            return Ok(());
+        }
+
+        let tip_height = self
+            .get_best_block_index()
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .expect("Best block index not present in the database")
+            .block_height();
+        let initial_pruned_height = self
+            .get_pruned_height()
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .unwrap_or_else(BlockHeight::zero);
+
+        let mut pruned_height = initial_pruned_height;
+        loop {
+            let depth = u64::from(tip_height).saturating_sub(pruned_height.into());
+            let should_prune = match prune_mode {
+                PruneMode::Disabled => false,
+                PruneMode::Depth(keep) => depth > keep,
+                PruneMode::Size(max_size) => {
+                    depth > MIN_BLOCKS_TO_KEEP_WHEN_PRUNING
+                        && self.db_tx.get_stored_blocks_size().log_err()? > max_size
+                }
+            };
+            if !should_prune {
+                break;
+            }
+
+            pruned_height = pruned_height.next_height();
+            let block_id = self
+                .get_block_id_by_height(&pruned_height)
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?
+                .expect("Inconsistent DB")
+                .classify(self.chain_config)
+                .chain_block_id()
+                .expect("Genesis cannot be above the pruned height");
+            self.db_tx.del_block(block_id).log_err()?;
+            self.db_tx.del_undo_data(block_id).log_err()?;
+            self.db_tx.del_accounting_undo_data(block_id).log_err()?;
+        }
+
+        if pruned_height != initial_pruned_height {
+            log::debug!("Pruned blocks up to height {}", pruned_height);
+            self.db_tx.set_pruned_height(&pruned_height).log_err()?;
+        }
+        Ok(())
+    }
+
+    /// Replace the state of a chainstate without blocks with the one from the snapshot.
+    /// The blocks up to the snapshot block are only known by their headers, as if they were pruned.
+    /// Returns the block index of the new tip.
+    pub fn load_utxo_snapshot(
+        &mut self,
+        snapshot: &UtxoSnapshot,
+    ) -> Result<BlockIndex, BlockError> {
+        let best_block_id =
+            self.get_best_block_id().map_err(BlockError::BestBlockLoadError).log_err()?;
+        ensure!(
+            best_block_id == self.chain_config.genesis_block_id(),
+            BlockError::UtxoSnapshotIntoNonEmptyChainstate
+        );
+        ensure!(
+            !*self.chainstate_config.tx_index_enabled,
+            BlockError::UtxoSnapshotWithTxIndex
+        );
+        ensure!(
+            !*self.chainstate_config.address_index_enabled,
+            BlockError::UtxoSnapshotWithAddressIndex
+        );
+        ensure!(
+            !*self.chainstate_config.spent_index_enabled,
+            BlockError::UtxoSnapshotWithSpentIndex
+        );
+        ensure!(
+            !*self.chainstate_config.block_filters_enabled,
+            BlockError::UtxoSnapshotWithBlockFilters
+        );
+        ensure!(
+            !*self.chainstate_config.headers_only,
+            BlockError::BlockDataInHeadersOnlyMode
+        );
+
+        let mut tip_block_index = None;
+        for header in snapshot.headers() {
+            self.check_not_known_invalid(header).log_err()?;
+            self.check_block_header(header).log_err()?;
+
+            let mut block_index = self.add_to_block_index(header).log_err()?;
+            // The snapshot hash is pinned in the chain config, so the blocks are assumed valid
+            let mut status = block_index.status();
+            status.set_valid_transactions();
+            block_index.set_status(status);
+
+            self.db_tx.set_block_index(&block_index).log_err()?;
+            self.db_tx
+                .set_block_id_at_height(
+                    &block_index.block_height(),
+                    &(*block_index.block_id()).into(),
+                )
+                .log_err()?;
+            tip_block_index = Some(block_index);
+        }
+        let tip_block_index = tip_block_index.ok_or(BlockError::UtxoSnapshotWithoutBlocks)?;
+        let tip_block_id = (*tip_block_index.block_id()).into();
+
+        // Genesis outputs that are still unspent are a part of the snapshot
+        for outpoint in self.db_tx.get_utxo_set().log_err()?.keys() {
+            self.db_tx.del_utxo(outpoint).log_err()?;
+        }
+        for (outpoint, utxo) in snapshot.utxos() {
+            self.db_tx.set_utxo(outpoint, utxo.clone()).log_err()?;
+        }
+        let set_hash = UtxoSetHash::from_utxos(snapshot.utxos());
+        self.db_tx.set_utxo_set_hash(&tip_block_id, &set_hash).log_err()?;
+        self.db_tx.set_best_block_for_utxos(&tip_block_id).log_err()?;
+
+        let accounting_data = snapshot.accounting_data();
+        for (pool_id, pool_data) in &accounting_data.pool_data {
+            self.db_tx.set_pool_data(*pool_id, pool_data).log_err()?;
+        }
+        for (pool_id, balance) in &accounting_data.pool_balances {
+            self.db_tx.set_pool_balance(*pool_id, *balance).log_err()?;
+        }
+        for ((pool_id, delegation_id), share) in &accounting_data.pool_delegation_shares {
+            self.db_tx
+                .set_pool_delegation_share(*pool_id, *delegation_id, *share)
+                .log_err()?;
+        }
+        for (delegation_id, balance) in &accounting_data.delegation_balances {
+            self.db_tx.set_delegation_balance(*delegation_id, *balance).log_err()?;
+        }
+        for (delegation_id, delegation_data) in &accounting_data.delegation_data {
+            self.db_tx.set_delegation_data(*delegation_id, delegation_data).log_err()?;
+        }
+
+        for (token_id, aux_data) in snapshot.token_aux_data() {
+            self.db_tx.set_token_aux_data(token_id, aux_data).log_err()?;
+        }
+        for (tx_id, token_id) in snapshot.token_ids() {
+            self.db_tx.set_token_id(tx_id, token_id).log_err()?;
+        }
+
+        self.db_tx.set_best_block_id(&tip_block_id).log_err()?;
+        self.db_tx.set_pruned_height(&tip_block_index.block_height()).log_err()?;
+        self.db_tx.set_utxo_snapshot_block(tip_block_index.block_id()).log_err()?;
+
+        Ok(tip_block_index)
+    }
+
+    fn set_block_status(
+        &mut self,
+        mut block_index: BlockIndex,
+        status: BlockStatus,
+    ) -> Result<(), BlockError> {
+        block_index.set_status(status);
+        self.db_tx.set_block_index(&block_index).map_err(BlockError::from).log_err()
+    }
+
+    /// Disconnect the main chain down to the parent of the given block, if the block is in it
+    pub fn disconnect_block_and_descendants(
+        &mut self,
+        block_index: &BlockIndex,
+    ) -> Result<(), BlockError> {
+        let block_id = (*block_index.block_id()).into();
+        if !self.is_block_in_main_chain(&block_id).map_err(BlockError::BestBlockLoadError)? {
+            return Ok(());
+        }
+
+        let best_block_id = self
+            .get_best_block_id()
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .classify(self.chain_config)
+            .chain_block_id()
+            .expect("Genesis cannot be the tip if a block is in the main chain");
+        let mainchain_tip = self
+            .get_block_index(&best_block_id)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .expect("Can't get block index. Inconsistent DB");
+        self.disconnect_until(&mainchain_tip, block_index.prev_block_id()).log_err()
+    }
+
+    /// Mark the block as failed and all of its descendants as failed children
+    pub fn set_block_invalid(&mut self, block_id: &Id<Block>) -> Result<(), BlockError> {
+        let block_index = self
+            .get_block_index(block_id)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .ok_or(BlockError::BlockNotFound(*block_id))
+            .log_err()?;
+        log::warn!("Marking block {} as invalid", block_id);
+
+        let descendants =
+            self.get_descendants(&block_index).map_err(BlockError::BestBlockLoadError)?;
+        for descendant in descendants {
+            let mut status = descendant.status();
+            status.set_failed_child();
+            self.set_block_status(descendant, status).log_err()?;
+        }
+
+        let mut status = block_index.status();
+        status.set_failed();
+        self.set_block_status(block_index, status).log_err()
+    }
+
+    /// Clear the failure flags of the block, its ancestors and its descendants
+    pub fn reset_block_failure_flags(&mut self, block_id: &Id<Block>) -> Result<(), BlockError> {
+        let block_index = self
+            .get_block_index(block_id)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .ok_or(BlockError::BlockNotFound(*block_id))
+            .log_err()?;
+
+        let descendants =
+            self.get_descendants(&block_index).map_err(BlockError::BestBlockLoadError)?;
+        for descendant in descendants {
+            if descendant.status().is_failed() {
+                let mut status = descendant.status();
+                status.reset_failure_flags();
+                self.set_block_status(descendant, status).log_err()?;
+            }
+        }
+
+        // Ancestors of a block that isn't failed can't be failed either
+        let mut current = GenBlockIndex::Block(block_index);
+        while let GenBlockIndex::Block(block_index) = current {
+            if !block_index.status().is_failed() {
+                break;
+            }
+            current = self
+                .get_previous_block_index(&block_index)
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?;
+            let mut status = block_index.status();
+            status.reset_failure_flags();
+            self.set_block_status(block_index, status).log_err()?;
+        }
+
+        Ok(())
+    }
+
     /// Mark new block as an orphan
     fn new_orphan_block(&mut self, block: WithId<Block>) -> Result<(), OrphanCheckError> {
         match self.orphan_blocks.add_block(block) {
@@ -25,11 +25,13 @@ use crate::detail::{
     tx_verification_strategy::TransactionVerificationStrategy,
 };
 use chainstate_storage::{BlockchainStorageRead, BlockchainStorageWrite};
-use chainstate_types::{storage_result, GenBlockIndex};
+use chainstate_types::{
+    storage_result, AddressIndexKey, AddressOutputInfo, GenBlockIndex, OutputSpendInfo,
+};
 use common::{
     chain::{
         tokens::{TokenAuxiliaryData, TokenId},
-        Block, ChainConfig, GenBlock, GenBlockId, OutPointSourceId, Transaction,
+        Block, ChainConfig, GenBlock, GenBlockId, OutPoint, OutPointSourceId, Transaction,
     },
     primitives::{Amount, Id},
 };
@@ -38,7 +40,9 @@ use pos_accounting::{
     PoSAccountingDB, PoSAccountingDeltaData, PoSAccountingView, PoolData, PoolId,
 };
 use tx_verifier::transaction_verifier::TransactionSource;
-use utxo::{ConsumedUtxoCache, FlushableUtxoView, UtxosBlockUndo, UtxosDB, UtxosStorageRead};
+use utxo::{
+    ConsumedUtxoCache, FlushableUtxoView, UtxoSetHash, UtxosBlockUndo, UtxosDB, UtxosStorageRead,
+};
 
 impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationStrategy>
     TransactionVerifierStorageRef for ChainstateRef<'a, S, O, V>
@@ -83,6 +87,24 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
             .get_accounting_undo(id)
             .map_err(TransactionVerifierStorageError::from)
     }
+
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError> {
+        self.db_tx
+            .get_address_index_entry(key)
+            .map_err(TransactionVerifierStorageError::from)
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError> {
+        self.db_tx
+            .get_output_spend_info(outpoint)
+            .map_err(TransactionVerifierStorageError::from)
+    }
 }
 
 // TODO: this function is a duplicate of one in chainstate-types; the cause for this is that BlockchainStorageRead causes a circular dependencies
@@ -120,6 +142,13 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
     ) -> Result<Option<UtxosBlockUndo>, storage_result::Error> {
         self.db_tx.get_undo_data(id)
     }
+
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<UtxoSetHash>, storage_result::Error> {
+        self.db_tx.get_utxo_set_hash(block_id)
+    }
 }
 
 impl<'a, S: BlockchainStorageWrite, O: OrphanBlocks, V: TransactionVerificationStrategy>
@@ -255,6 +284,44 @@ impl<'a, S: BlockchainStorageWrite, O: OrphanBlocks, V: TransactionVerificationS
             }
         }
     }
+
+    fn set_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+        info: &AddressOutputInfo,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        self.db_tx
+            .set_address_index_entry(key, info)
+            .map_err(TransactionVerifierStorageError::from)
+    }
+
+    fn del_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        self.db_tx
+            .del_address_index_entry(key)
+            .map_err(TransactionVerifierStorageError::from)
+    }
+
+    fn set_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+        info: &OutputSpendInfo,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        self.db_tx
+            .set_output_spend_info(outpoint, info)
+            .map_err(TransactionVerifierStorageError::from)
+    }
+
+    fn del_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        self.db_tx
+            .del_output_spend_info(outpoint)
+            .map_err(TransactionVerifierStorageError::from)
+    }
 }
 
 impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationStrategy>
@@ -0,0 +1,293 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeSet;
+
+use chainstate_storage::BlockchainStorageRead;
+use chainstate_types::BlockIndex;
+use common::{
+    chain::{
+        signature::Signable, Block, OutPoint, OutPointSourceId, OutputPurpose, TxInput, TxOutput,
+    },
+    primitives::{id::WithId, BlockHeight, Idable},
+};
+use logging::log;
+use pos_accounting::{make_pool_id, PoSAccountingData, PoSAccountingView, PoolId};
+use tx_verifier::transaction_verifier::{config::TransactionVerifierConfig, TransactionVerifier};
+use utils::tap_error_log::LogError;
+use utxo::UtxosStorageRead;
+
+use crate::{
+    detail::{
+        orphan_blocks::OrphanBlocks, transaction_verifier::flush::flush_to_storage,
+        tx_verification_strategy::TransactionVerificationStrategy, ChainInconsistency,
+    },
+    BlockError, ConnectTransactionError,
+};
+
+use super::ChainstateRef;
+
+impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationStrategy>
+    ChainstateRef<'a, S, O, V>
+{
+    /// Check the last `depth` blocks of the main chain against their indexes, then disconnect
+    /// and reconnect them and compare the rebuilt utxos and PoS accounting data with the
+    /// stored ones.
+    ///
+    /// The blocks are disconnected and reconnected in a transaction verifier on top of this
+    /// view of the database, so the changes are kept in memory and nothing is written. Pruned
+    /// blocks are skipped, as their undo data is gone. Returns the first inconsistency found,
+    /// if any.
+    pub fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, BlockError> {
+        let best_height = self
+            .get_best_block_index()
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .expect("Best block index not present in the database")
+            .block_height();
+        let lowest_height = self
+            .get_pruned_height()
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .map_or(BlockHeight::new(1), |pruned_height| {
+                pruned_height.next_height()
+            });
+        let lowest_height = std::cmp::max(
+            lowest_height,
+            BlockHeight::new(u64::from(best_height).saturating_sub(depth as u64) + 1),
+        );
+
+        let mut blocks = Vec::new();
+        let mut height = lowest_height;
+        while height <= best_height {
+            match self.load_main_chain_block(height)? {
+                Ok(block) => blocks.push(block),
+                Err(inconsistency) => return Ok(Some(inconsistency)),
+            }
+            height = height.next_height();
+        }
+        log::info!(
+            "Verifying the last {} blocks of the main chain",
+            blocks.len()
+        );
+
+        // The indexes are not compared, so there's no need to rebuild them
+        let verifier_config = TransactionVerifierConfig::new(false);
+        let mut verifier =
+            TransactionVerifier::new(self, self.chain_config, verifier_config.clone());
+
+        for (_, block) in blocks.iter().rev() {
+            let disconnected = self
+                .tx_verification_strategy
+                .disconnect_block(
+                    TransactionVerifier::new,
+                    &verifier,
+                    self.chain_config,
+                    verifier_config.clone(),
+                    block,
+                )
+                .and_then(|block_verifier| Ok(block_verifier.consume()?));
+            let result =
+                disconnected.and_then(|consumed| Ok(flush_to_storage(&mut verifier, consumed)?));
+            if let Err(err) = result {
+                return Ok(Some(ChainInconsistency::DisconnectFailed(
+                    block.get_id(),
+                    err,
+                )));
+            }
+        }
+        for (block_index, block) in &blocks {
+            let connected = self
+                .tx_verification_strategy
+                .connect_block(
+                    TransactionVerifier::new,
+                    self,
+                    &verifier,
+                    self.chain_config,
+                    verifier_config.clone(),
+                    block_index,
+                    block,
+                )
+                .and_then(|block_verifier| Ok(block_verifier.consume()?));
+            let result =
+                connected.and_then(|consumed| Ok(flush_to_storage(&mut verifier, consumed)?));
+            if let Err(err) = result {
+                return Ok(Some(ChainInconsistency::ReconnectFailed(
+                    block.get_id(),
+                    err,
+                )));
+            }
+        }
+
+        // Only the utxos created or spent by the blocks can differ
+        for outpoint in touched_outpoints(&blocks) {
+            let rebuilt = verifier.get_utxo(&outpoint).log_err()?;
+            if rebuilt != self.db_tx.get_utxo(&outpoint).log_err()? {
+                return Ok(Some(ChainInconsistency::UtxoMismatch(outpoint)));
+            }
+        }
+
+        let stored_accounting_data = self.db_tx.get_accounting_data().log_err()?;
+        let rebuilt_accounting_data =
+            read_accounting_data(&verifier, &stored_accounting_data, created_pools(&blocks))
+                .map_err(ConnectTransactionError::PoSAccountingError)
+                .log_err()?;
+        if rebuilt_accounting_data != stored_accounting_data {
+            return Ok(Some(ChainInconsistency::AccountingDataMismatch));
+        }
+
+        Ok(None)
+    }
+
+    // Load the main chain block at the given height and check it against its index
+    fn load_main_chain_block(
+        &self,
+        height: BlockHeight,
+    ) -> Result<Result<(BlockIndex, WithId<Block>), ChainInconsistency>, BlockError> {
+        let block_id = match self
+            .get_block_id_by_height(&height)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .and_then(|id| id.classify(self.chain_config).chain_block_id())
+        {
+            Some(block_id) => block_id,
+            None => return Ok(Err(ChainInconsistency::MainChainHeightMissing(height))),
+        };
+
+        let block_index =
+            match self.get_block_index(&block_id).map_err(BlockError::BestBlockLoadError)? {
+                Some(block_index) => block_index,
+                None => return Ok(Err(ChainInconsistency::BlockIndexMissing(block_id))),
+            };
+        if block_index.block_height() != height {
+            return Ok(Err(ChainInconsistency::BlockHeightMismatch(
+                block_id,
+                height,
+                block_index.block_height(),
+            )));
+        }
+
+        let block: WithId<Block> =
+            match self.get_block(block_id).map_err(BlockError::BestBlockLoadError)? {
+                Some(block) => block.into(),
+                None => return Ok(Err(ChainInconsistency::BlockMissing(block_id))),
+            };
+        if block.get_id() != block_id || block.header() != block_index.block_header() {
+            return Ok(Err(ChainInconsistency::BlockIndexMismatch(block_id)));
+        }
+        if let Err(err) = self.check_block(&block) {
+            return Ok(Err(ChainInconsistency::BlockCheckFailed(block_id, err)));
+        }
+
+        Ok(Ok((block_index, block)))
+    }
+}
+
+/// The outputs created and spent by the blocks, in order
+fn touched_outpoints(blocks: &[(BlockIndex, WithId<Block>)]) -> BTreeSet<OutPoint> {
+    fn add(
+        outpoints: &mut BTreeSet<OutPoint>,
+        source_id: OutPointSourceId,
+        inputs: &[TxInput],
+        outputs: &[TxOutput],
+    ) {
+        outpoints.extend(inputs.iter().map(|input| input.outpoint().clone()));
+        outpoints
+            .extend((0..outputs.len()).map(|index| OutPoint::new(source_id.clone(), index as u32)));
+    }
+
+    let mut outpoints = BTreeSet::new();
+    for (_, block) in blocks {
+        let reward = block.block_reward_transactable();
+        add(
+            &mut outpoints,
+            OutPointSourceId::BlockReward(block.get_id().into()),
+            reward.inputs().unwrap_or_default(),
+            reward.outputs().unwrap_or_default(),
+        );
+        for tx in block.transactions() {
+            let tx = tx.transaction();
+            add(
+                &mut outpoints,
+                tx.get_id().into(),
+                tx.inputs(),
+                tx.outputs(),
+            );
+        }
+    }
+    outpoints
+}
+
+/// The pools created by the transactions of the blocks
+fn created_pools(blocks: &[(BlockIndex, WithId<Block>)]) -> impl Iterator<Item = PoolId> + '_ {
+    blocks.iter().flat_map(|(_, block)| block.transactions()).filter_map(|tx| {
+        let tx = tx.transaction();
+        let creates_pool = tx
+            .outputs()
+            .iter()
+            .any(|output| matches!(output.purpose(), OutputPurpose::StakePool(_)));
+        tx.inputs()
+            .first()
+            .filter(|_| creates_pool)
+            .map(|input| make_pool_id(input.outpoint()))
+    })
+}
+
+/// Read the entries of the stored accounting data and of the created pools from the view
+fn read_accounting_data(
+    view: &impl PoSAccountingView,
+    stored: &PoSAccountingData,
+    created_pools: impl Iterator<Item = PoolId>,
+) -> Result<PoSAccountingData, pos_accounting::Error> {
+    let mut data = PoSAccountingData::new();
+
+    let pool_ids: BTreeSet<PoolId> = stored
+        .pool_data
+        .keys()
+        .chain(stored.pool_balances.keys())
+        .chain(stored.pool_delegation_shares.keys().map(|(pool_id, _)| pool_id))
+        .copied()
+        .chain(created_pools)
+        .collect();
+    for pool_id in pool_ids {
+        if let Some(pool_data) = view.get_pool_data(pool_id)? {
+            data.pool_data.insert(pool_id, pool_data);
+        }
+        if let Some(balance) = view.get_pool_balance(pool_id)? {
+            data.pool_balances.insert(pool_id, balance);
+        }
+        for (delegation_id, share) in view.get_pool_delegations_shares(pool_id)?.unwrap_or_default()
+        {
+            data.pool_delegation_shares.insert((pool_id, delegation_id), share);
+        }
+    }
+
+    let delegation_ids: BTreeSet<_> = stored
+        .delegation_data
+        .keys()
+        .chain(stored.delegation_balances.keys())
+        .copied()
+        .collect();
+    for delegation_id in delegation_ids {
+        if let Some(delegation_data) = view.get_delegation_data(delegation_id)? {
+            data.delegation_data.insert(delegation_id, delegation_data);
+        }
+        if let Some(balance) = view.get_delegation_balance(delegation_id)? {
+            data.delegation_balances.insert(delegation_id, balance);
+        }
+    }
+
+    Ok(data)
+}
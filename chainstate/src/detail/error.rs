@@ -22,8 +22,8 @@ use super::{
 };
 use chainstate_types::PropertyQueryError;
 use common::{
-    chain::{Block, GenBlock, Transaction},
-    primitives::{BlockDistance, Id},
+    chain::{Block, GenBlock, OutPoint, Transaction},
+    primitives::{BlockDistance, BlockHeight, Id, H256},
 };
 use consensus::ConsensusVerificationError;
 use thiserror::Error;
@@ -57,8 +57,99 @@ pub enum BlockError {
     TransactionVerifierError(#[from] TransactionVerifierStorageError),
     #[error("Changing tx index state is not implemented for existing DB")]
     TxIndexConfigError,
+    #[error("Changing address index state is not implemented for existing DB")]
+    AddressIndexConfigError,
+    #[error("Changing spent-output index state is not implemented for existing DB")]
+    SpentIndexConfigError,
+    #[error("Changing block filters state is not implemented for existing DB")]
+    BlockFiltersConfigError,
+    #[error("The filter header of the previous block {0} is missing")]
+    MissingBlockFilterHeader(Id<Block>),
+    #[error("Changing headers-only state is not implemented for existing DB")]
+    HeadersOnlyConfigError,
+    #[error("Block data cannot be processed by a headers-only chainstate")]
+    BlockDataInHeadersOnlyMode,
+    #[error("Standalone headers can only be processed by a headers-only chainstate")]
+    HeadersOnlyModeDisabled,
     #[error("Transaction index construction error: {0}")]
     TxIndexConstructionError(#[from] TxIndexError),
+    #[error("Block {0} has already been found to be invalid")]
+    InvalidBlockAlreadyProcessed(Id<Block>),
+    #[error("The previous block {0} is invalid")]
+    PrevBlockInvalid(Id<Block>),
+    #[error("Block {0} not found")]
+    BlockNotFound(Id<Block>),
+    #[error("Cannot disconnect blocks down to height {0}, blocks up to height {1} are pruned")]
+    ReorgBeyondPrunedHeight(BlockHeight, BlockHeight),
+    #[error("Transaction index cannot be enabled together with block pruning")]
+    TxIndexWithPruning,
+    #[error("Utxo snapshots can only be loaded into a chainstate without blocks")]
+    UtxoSnapshotIntoNonEmptyChainstate,
+    #[error("Utxo snapshots cannot be loaded with the transaction index enabled")]
+    UtxoSnapshotWithTxIndex,
+    #[error("Utxo snapshots cannot be loaded with the address index enabled")]
+    UtxoSnapshotWithAddressIndex,
+    #[error("Utxo snapshots cannot be loaded with the spent-output index enabled")]
+    UtxoSnapshotWithSpentIndex,
+    #[error("Utxo snapshots cannot be loaded with block filters enabled")]
+    UtxoSnapshotWithBlockFilters,
+    #[error("The utxo snapshot has no blocks")]
+    UtxoSnapshotWithoutBlocks,
+    #[error(
+        "The state from the history of the utxo snapshot at block {0} has hash {1} instead of {2}"
+    )]
+    UtxoSnapshotHistoryMismatch(Id<Block>, H256, H256),
+    #[error("The history of the utxo snapshot at block {0} is invalid, no blocks are processed")]
+    UtxoSnapshotHistoryInvalid(Id<Block>),
+    #[error(
+        "The chainstate cannot be reindexed because some of the main chain blocks are missing"
+    )]
+    ReindexWithoutBlocks,
+    #[error("Cannot verify the last {0} blocks, at most {1} blocks can be verified at once")]
+    VerifyChainDepthTooLarge(usize, usize),
+}
+
+/// Errors that can occur while switching the main chain to a new tip
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ReorgError {
+    #[error("Failed to connect block {0}: {1}")]
+    ConnectTipFailed(Id<Block>, BlockError),
+    #[error("{0}")]
+    OtherError(#[from] BlockError),
+}
+
+impl From<ReorgError> for BlockError {
+    fn from(err: ReorgError) -> Self {
+        match err {
+            ReorgError::ConnectTipFailed(_, err) => err,
+            ReorgError::OtherError(err) => err,
+        }
+    }
+}
+
+/// The first difference found between the stored chainstate and the one rebuilt by `verify_chain`
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum ChainInconsistency {
+    #[error("No main chain block is stored at height {0}")]
+    MainChainHeightMissing(BlockHeight),
+    #[error("The index of main chain block {0} is missing")]
+    BlockIndexMissing(Id<Block>),
+    #[error("Main chain block {0} is at height {1}, but its index says {2}")]
+    BlockHeightMismatch(Id<Block>, BlockHeight, BlockHeight),
+    #[error("The data of main chain block {0} is missing")]
+    BlockMissing(Id<Block>),
+    #[error("The data of block {0} doesn't match its index")]
+    BlockIndexMismatch(Id<Block>),
+    #[error("Block {0} failed the checks: {1}")]
+    BlockCheckFailed(Id<Block>, CheckBlockError),
+    #[error("Failed to disconnect block {0}: {1}")]
+    DisconnectFailed(Id<Block>, BlockError),
+    #[error("Failed to reconnect block {0}: {1}")]
+    ReconnectFailed(Id<Block>, BlockError),
+    #[error("The rebuilt utxo set differs from the stored one at output {0:?}")]
+    UtxoMismatch(OutPoint),
+    #[error("The rebuilt PoS accounting data differs from the stored one")]
+    AccountingDataMismatch,
 }
 
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
@@ -137,6 +228,8 @@ pub enum InitializationError {
     Block1Missing,
     #[error("Genesis mismatch: {0} according to configuration, {1} inferred from storage")]
     GenesisMismatch(Id<GenBlock>, Id<GenBlock>),
+    #[error("The history of the utxo snapshot at block {0} is invalid, the chainstate has to be synced again without the snapshot")]
+    UtxoSnapshotHistoryInvalid(Id<Block>),
 }
 
 impl From<OrphanAddError> for Result<(), OrphanCheckError> {
@@ -23,7 +23,9 @@ mod chainstateref;
 mod error;
 mod median_time;
 mod orphan_blocks;
+mod reindex;
 pub mod tx_verification_strategy;
+pub mod utxo_snapshot;
 
 pub use self::error::*;
 pub use self::median_time::calculate_median_time_past;
@@ -34,22 +36,24 @@ pub use error::{
 };
 // TODO: ConnectTransactionError used in unit tests to check block processing results. We have to find more appropriate place for this error.
 pub use transaction_verifier::{
-    error::{ConnectTransactionError, TokensError, TxIndexError},
+    error::{
+        AddressIndexError, ConnectTransactionError, SpentIndexError, TokensError, TxIndexError,
+    },
     storage::TransactionVerifierStorageError,
+    timelock_check::check_timelock,
 };
 use tx_verifier::transaction_verifier;
 
 use std::sync::Arc;
 
-use itertools::Itertools;
-
 use chainstate_storage::{BlockchainStorage, BlockchainStorageRead, Transactional};
-use chainstate_types::{BlockIndex, GenBlockIndex, PropertyQueryError};
+use chainstate_types::{AddressOutputInfo, BlockIndex, GenBlockIndex, PropertyQueryError};
 use common::{
     chain::{
         block::{timestamp::BlockTimestamp, BlockHeader},
         config::ChainConfig,
-        Block,
+        tokens::{CoinOrTokenId, OutputValue, TokenData},
+        Block, GenBlock, GenBlockId, OutPoint,
     },
     primitives::{id::WithId, BlockDistance, BlockHeight, Id, Idable},
     time_getter::TimeGetter,
@@ -62,9 +66,11 @@ use utils::{
 use utxo::UtxosDB;
 
 use self::{
+    ban_score::BanScore,
     orphan_blocks::{OrphanBlocksRef, OrphanBlocksRefMut},
     query::ChainstateQuery,
     tx_verification_strategy::TransactionVerificationStrategy,
+    utxo_snapshot::{UtxoSnapshot, UtxoSnapshotHistoryOutcome, UtxoSnapshotHistoryValidator},
 };
 use crate::{detail::orphan_blocks::OrphanBlocksPool, ChainstateConfig, ChainstateEvent};
 
@@ -75,6 +81,9 @@ type ChainstateEventHandler = EventHandler<ChainstateEvent>;
 // TODO: move this to some configuration, maybe p2p?
 pub const HEADER_LIMIT: BlockDistance = BlockDistance::new(2000);
 
+/// The maximum number of blocks `verify_chain` rolls back, all their changes are kept in memory
+pub const MAX_VERIFY_CHAIN_DEPTH: usize = 1000;
+
 pub type OrphanErrorHandler = dyn Fn(&BlockError) + Send + Sync;
 
 #[must_use]
@@ -88,6 +97,13 @@ pub struct Chainstate<S, V> {
     events_controller: EventsController<ChainstateEvent>,
     time_getter: TimeGetter,
     is_initial_block_download_finished: bool,
+    utxo_snapshot_validator: Option<Box<UtxoSnapshotHistoryValidator>>,
+    /// Set once the history of the loaded utxo snapshot turns out to be invalid, no more blocks
+    /// are processed then
+    invalid_utxo_snapshot_block: Option<Id<Block>>,
+    /// The main chain tip as of the last broadcast event, used to find the blocks that were
+    /// connected and disconnected since
+    events_tip: Id<GenBlock>,
 }
 
 #[derive(Copy, Clone, Eq, Debug, PartialEq)]
@@ -152,6 +168,11 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
     ) -> Result<Self, crate::ChainstateError> {
         use crate::ChainstateError;
 
+        utils::ensure!(
+            !(*chainstate_config.tx_index_enabled && chainstate_config.prune_mode.is_enabled()),
+            ChainstateError::ProcessBlockError(BlockError::TxIndexWithPruning)
+        );
+
         let best_block_id = chainstate_storage
             .get_best_block_id()
             .map_err(|e| ChainstateError::FailedToInitializeChainstate(e.into()))
@@ -169,6 +190,16 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         chainstate
             .process_tx_index_enabled_flag()
             .map_err(crate::ChainstateError::from)?;
+        chainstate
+            .process_address_index_enabled_flag()
+            .map_err(crate::ChainstateError::from)?;
+        chainstate
+            .process_spent_index_enabled_flag()
+            .map_err(crate::ChainstateError::from)?;
+        chainstate
+            .process_block_filters_enabled_flag()
+            .map_err(crate::ChainstateError::from)?;
+        chainstate.process_headers_only_flag().map_err(crate::ChainstateError::from)?;
 
         if best_block_id.is_none() {
             chainstate
@@ -177,8 +208,25 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
                 .log_err()?;
         } else {
             chainstate.check_genesis().map_err(crate::ChainstateError::from)?;
+            chainstate.check_utxo_snapshot_history().map_err(crate::ChainstateError::from)?;
+            if *chainstate.chainstate_config.reindex_chainstate {
+                chainstate
+                    .start_reindex()
+                    .map_err(crate::ChainstateError::ProcessBlockError)
+                    .log_err()?;
+            }
+            chainstate
+                .continue_reindex()
+                .map_err(crate::ChainstateError::ProcessBlockError)
+                .log_err()?;
+            chainstate.start_utxo_snapshot_validation()?;
         }
 
+        chainstate.events_tip = chainstate
+            .query()
+            .and_then(|query| query.get_best_block_id())
+            .map_err(crate::ChainstateError::FailedToReadProperty)?;
+
         Ok(chainstate)
     }
 
@@ -191,6 +239,7 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         time_getter: TimeGetter,
     ) -> Self {
         let orphan_blocks = OrphanBlocksPool::new(*chainstate_config.max_orphan_blocks);
+        let events_tip = chain_config.genesis_block_id();
         Self {
             chain_config,
             chainstate_config,
@@ -201,6 +250,18 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
             events_controller: EventsController::new(),
             time_getter,
             is_initial_block_download_finished: false,
+            utxo_snapshot_validator: None,
+            invalid_utxo_snapshot_block: None,
+            events_tip,
+        }
+    }
+
+    /// The chain of a utxo snapshot with an invalid history can't be followed
+    fn check_utxo_snapshot_history(&self) -> Result<(), InitializationError> {
+        let dbtx = self.make_db_tx_ro()?;
+        match dbtx.get_invalid_utxo_snapshot_block()? {
+            Some(block_id) => Err(InitializationError::UtxoSnapshotHistoryInvalid(block_id)),
+            None => Ok(()),
         }
     }
 
@@ -213,14 +274,15 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
             return Ok(());
         }
 
-        // Look up the parent of block 1 to figure out the genesis ID according to storage
+        // Look up the parent of block 1 to figure out the genesis ID according to storage.
+        // The block index is used because the block itself may be pruned.
         let block1_id = dbtx
             .get_block_id_by_height(&BlockHeight::new(1))?
             .ok_or(InitializationError::Block1Missing)?;
-        let block1 = dbtx
-            .get_block(Id::new(block1_id.get()))?
+        let block1_index = dbtx
+            .get_block_index(&Id::new(block1_id.get()))?
             .ok_or(InitializationError::Block1Missing)?;
-        let stored_genesis_id = block1.prev_block_id();
+        let stored_genesis_id = *block1_index.prev_block_id();
 
         // Check storage genesis ID matches chain config genesis ID
         utils::ensure!(
@@ -260,12 +322,165 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
                 .log_err()?;
         }
 
-        db_tx.commit().expect("Set tx indexing failed");
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+
+        Ok(())
+    }
+
+    /// Whether no blocks other than genesis have been connected yet
+    fn has_no_connected_blocks(
+        &self,
+        db_tx: &impl BlockchainStorageRead,
+    ) -> Result<bool, BlockError> {
+        match db_tx.get_best_block_id().map_err(BlockError::StorageError)? {
+            Some(best_block_id) => Ok(best_block_id == self.chain_config.genesis_block_id()),
+            None => Ok(true),
+        }
+    }
+
+    /// Check that address index state is consistent between DB and config.
+    fn process_address_index_enabled_flag(&mut self) -> Result<(), BlockError> {
+        use chainstate_storage::{BlockchainStorageWrite, TransactionRw};
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+
+        let address_index_enabled = db_tx
+            .get_is_address_index_enabled()
+            .map_err(BlockError::StorageError)
+            .log_err()?;
+
+        if let Some(address_index_enabled) = address_index_enabled {
+            // TODO: Allow changing state (creating new or deleting existing index).
+            utils::ensure!(
+                *self.chainstate_config.address_index_enabled == address_index_enabled,
+                BlockError::AddressIndexConfigError
+            );
+        } else {
+            // The database may be older than the flag, the index can't be built for its blocks
+            utils::ensure!(
+                !*self.chainstate_config.address_index_enabled
+                    || self.has_no_connected_blocks(&db_tx)?,
+                BlockError::AddressIndexConfigError
+            );
+            db_tx
+                .set_is_address_index_enabled(*self.chainstate_config.address_index_enabled)
+                .map_err(BlockError::StorageError)
+                .log_err()?;
+        }
+
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+
+        Ok(())
+    }
+
+    fn process_spent_index_enabled_flag(&mut self) -> Result<(), BlockError> {
+        use chainstate_storage::{BlockchainStorageWrite, TransactionRw};
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+
+        let spent_index_enabled =
+            db_tx.get_is_spent_index_enabled().map_err(BlockError::StorageError).log_err()?;
+
+        if let Some(spent_index_enabled) = spent_index_enabled {
+            // TODO: Allow changing state (creating new or deleting existing index).
+            utils::ensure!(
+                *self.chainstate_config.spent_index_enabled == spent_index_enabled,
+                BlockError::SpentIndexConfigError
+            );
+        } else {
+            // The database may be older than the flag, the index can't be built for its blocks
+            utils::ensure!(
+                !*self.chainstate_config.spent_index_enabled
+                    || self.has_no_connected_blocks(&db_tx)?,
+                BlockError::SpentIndexConfigError
+            );
+            db_tx
+                .set_is_spent_index_enabled(*self.chainstate_config.spent_index_enabled)
+                .map_err(BlockError::StorageError)
+                .log_err()?;
+        }
+
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+
+        Ok(())
+    }
+
+    fn process_block_filters_enabled_flag(&mut self) -> Result<(), BlockError> {
+        use chainstate_storage::{BlockchainStorageWrite, TransactionRw};
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+
+        let block_filters_enabled = db_tx
+            .get_is_block_filters_enabled()
+            .map_err(BlockError::StorageError)
+            .log_err()?;
+
+        if let Some(block_filters_enabled) = block_filters_enabled {
+            // TODO: Allow changing state (building or deleting the filters of existing blocks).
+            utils::ensure!(
+                *self.chainstate_config.block_filters_enabled == block_filters_enabled,
+                BlockError::BlockFiltersConfigError
+            );
+        } else {
+            // The database may be older than the flag, the filters can't be built for its blocks
+            utils::ensure!(
+                !*self.chainstate_config.block_filters_enabled
+                    || self.has_no_connected_blocks(&db_tx)?,
+                BlockError::BlockFiltersConfigError
+            );
+            db_tx
+                .set_is_block_filters_enabled(*self.chainstate_config.block_filters_enabled)
+                .map_err(BlockError::StorageError)
+                .log_err()?;
+        }
+
+        db_tx.commit().map_err(BlockError::from).log_err()?;
 
         Ok(())
     }
 
-    fn broadcast_new_tip_event(&self, new_block_index: &Option<BlockIndex>) {
+    fn process_headers_only_flag(&mut self) -> Result<(), BlockError> {
+        use chainstate_storage::{BlockchainStorageWrite, TransactionRw};
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+
+        let headers_only =
+            db_tx.get_is_headers_only().map_err(BlockError::StorageError).log_err()?;
+
+        if let Some(headers_only) = headers_only {
+            // The block data and the utxo set of a headers-only chainstate are missing, and a
+            // full chainstate can't be turned into a headers-only one without deleting them
+            utils::ensure!(
+                *self.chainstate_config.headers_only == headers_only,
+                BlockError::HeadersOnlyConfigError
+            );
+        } else {
+            db_tx
+                .set_is_headers_only(*self.chainstate_config.headers_only)
+                .map_err(BlockError::StorageError)
+                .log_err()?;
+        }
+
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+
+        Ok(())
+    }
+
+    fn broadcast_new_tip_event(&mut self, new_block_index: &Option<BlockIndex>) {
+        self.broadcast_block_events();
+
         match new_block_index {
             Some(ref new_block_index) => {
                 let new_height = new_block_index.block_height();
@@ -276,21 +491,125 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         }
     }
 
+    /// Broadcast the blocks disconnected from the main chain since the last broadcast, starting
+    /// from the old tip, followed by the connected blocks, starting from the fork point
+    fn broadcast_block_events(&mut self) {
+        let best_block_id = match self.query().and_then(|query| query.get_best_block_id()).log_err()
+        {
+            Ok(best_block_id) => best_block_id,
+            Err(_) => return,
+        };
+        if best_block_id == self.events_tip {
+            return;
+        }
+
+        // Loading the blocks is only worth it if somebody listens, and there are no blocks
+        // in the headers-only mode
+        if !self.events_controller.subscribers().is_empty() && !*self.chainstate_config.headers_only
+        {
+            match self.main_chain_changes(&best_block_id).log_err() {
+                Ok((disconnected, connected)) => {
+                    for (block, height) in disconnected {
+                        self.events_controller
+                            .broadcast(ChainstateEvent::BlockDisconnected(Arc::new(block), height))
+                    }
+                    for (block, height) in connected {
+                        self.events_controller
+                            .broadcast(ChainstateEvent::BlockConnected(Arc::new(block), height))
+                    }
+                }
+                Err(e) => log::error!("Failed to load the blocks of the main chain change: {e}"),
+            }
+        }
+
+        self.events_tip = best_block_id;
+    }
+
+    /// Returns the blocks that left the main chain since `self.events_tip`, from the old tip
+    /// down, and the blocks that joined it, from the fork point up to `new_tip_id`. Blocks
+    /// without data, e.g. pruned ones, are skipped.
+    #[allow(clippy::type_complexity)]
+    fn main_chain_changes(
+        &self,
+        new_tip_id: &Id<GenBlock>,
+    ) -> Result<(Vec<(Block, BlockHeight)>, Vec<(Block, BlockHeight)>), PropertyQueryError> {
+        let query = self.query()?;
+        let get_index = |id: &Id<GenBlock>| {
+            query
+                .get_gen_block_index(id)?
+                .ok_or(PropertyQueryError::PrevBlockIndexNotFound(*id))
+        };
+        let get_block = |index: &GenBlockIndex| match index.block_id().classify(&self.chain_config)
+        {
+            GenBlockId::Block(id) => query
+                .get_block(id)
+                .map(|block| block.map(|block| (block, index.block_height()))),
+            GenBlockId::Genesis(_) => Ok(None),
+        };
+
+        let mut old_index = get_index(&self.events_tip)?;
+        let mut new_index = get_index(new_tip_id)?;
+        let mut disconnected = Vec::new();
+        let mut connected = Vec::new();
+        while old_index.block_id() != new_index.block_id() {
+            if old_index.block_height() >= new_index.block_height() {
+                disconnected.extend(get_block(&old_index)?);
+                let prev_id = old_index.prev_block_id().expect("genesis is a common ancestor");
+                old_index = get_index(&prev_id)?;
+            } else {
+                connected.extend(get_block(&new_index)?);
+                let prev_id = new_index.prev_block_id().expect("genesis is a common ancestor");
+                new_index = get_index(&prev_id)?;
+            }
+        }
+        connected.reverse();
+
+        Ok((disconnected, connected))
+    }
+
     /// returns the new block index, which is the new tip, if any
+    ///
+    /// The chains of orphans are walked iteratively, so that a long chain doesn't grow the stack.
     fn process_orphans(&mut self, last_processed_block: &Id<Block>) -> Option<BlockIndex> {
-        let orphans = self.orphan_blocks.take_all_children_of(&(*last_processed_block).into());
-        let (block_indexes, block_errors): (Vec<Option<BlockIndex>>, Vec<BlockError>) = orphans
-            .into_iter()
-            .map(|blk| self.process_block(blk, BlockSource::Local))
-            .partition_result();
-
-        block_errors.into_iter().for_each(|e| match &self.custom_orphan_error_hook {
-            Some(handler) => handler(&e),
-            None => logging::log::error!("Failed to process a chain of orphan blocks: {}", e),
-        });
+        let mut result = None;
+        let mut parents = vec![*last_processed_block];
+
+        while let Some(parent) = parents.pop() {
+            for orphan in self.orphan_blocks.take_all_children_of(&parent.into()) {
+                let orphan_id = orphan.get_id();
+                let orphan_result = match self.utxo_snapshot_history_block_height(&orphan) {
+                    Ok(Some(block_height)) => {
+                        self.submit_utxo_snapshot_history_block(
+                            orphan,
+                            block_height,
+                            BlockSource::Local,
+                        );
+                        Ok(None)
+                    }
+                    Ok(None) => self
+                        .attempt_to_accept_block(orphan, BlockSource::Local, 0)
+                        .inspect(|_| parents.push(orphan_id)),
+                    Err(e) => Err(e),
+                };
+
+                match orphan_result {
+                    Ok(new_tip) => {
+                        self.update_after_new_tip(&new_tip);
+                        // since we process the blocks in order, the last one is the best tip
+                        result = new_tip.or(result);
+                    }
+                    Err(e) => match &self.custom_orphan_error_hook {
+                        Some(handler) => handler(&e),
+                        None => logging::log::error!(
+                            "Failed to process a chain of orphan blocks: {}",
+                            e
+                        ),
+                    },
+                }
+            }
+        }
 
-        // since we processed the blocks in order, the last one is the best tip
-        block_indexes.into_iter().flatten().rev().next()
+        result
     }
 
     fn process_db_commit_error(
@@ -300,15 +619,15 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         block_source: BlockSource,
         attempt_number: usize,
     ) -> Result<Option<BlockIndex>, BlockError> {
-        if attempt_number >= *self.chainstate_config.max_db_commit_attempts {
+        // The attempts are numbered from zero
+        if attempt_number + 1 >= *self.chainstate_config.max_db_commit_attempts {
             Err(BlockError::DatabaseCommitError(
                 block.get_id(),
                 *self.chainstate_config.max_db_commit_attempts,
                 db_error,
             ))
         } else {
-            // TODO: test reattempts using mocks of the database that emulate failure
-            self.attempt_to_process_block(block, block_source, attempt_number + 1)
+            self.attempt_to_accept_block(block, block_source, attempt_number + 1)
         }
     }
 
@@ -317,6 +636,42 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         block: WithId<Block>,
         block_source: BlockSource,
         attempt_number: usize,
+    ) -> Result<Option<BlockIndex>, BlockError> {
+        let block_id = block.get_id();
+        let result = self.attempt_to_accept_block(block, block_source, attempt_number)?;
+
+        let new_block_index_after_orphans = self.process_orphans(&block_id);
+        let result = match new_block_index_after_orphans {
+            Some(result_from_orphan) => Some(result_from_orphan),
+            None => result,
+        };
+
+        self.update_after_new_tip(&result);
+
+        Ok(result)
+    }
+
+    fn update_after_new_tip(&mut self, new_tip: &Option<BlockIndex>) {
+        self.broadcast_new_tip_event(new_tip);
+
+        if let Some(ref bi) = new_tip {
+            log::info!(
+                "New tip in chainstate {} with height {}",
+                bi.block_id(),
+                bi.block_height()
+            );
+
+            self.is_initial_block_download_finished = self.is_fresh_block(&bi.block_timestamp());
+        }
+    }
+
+    /// Validate the block and connect it if it's on the best chain, without processing the
+    /// orphans that it may have
+    fn attempt_to_accept_block(
+        &mut self,
+        block: WithId<Block>,
+        block_source: BlockSource,
+        attempt_number: usize,
     ) -> Result<Option<BlockIndex>, BlockError> {
         log::info!("Processing block: {}", block.get_id());
 
@@ -324,6 +679,8 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
 
         let block = chainstate_ref.check_legitimate_orphan(block_source, block).log_err()?;
 
+        chainstate_ref.check_not_known_invalid(block.header()).log_err()?;
+
         let best_block_id = chainstate_ref
             .get_best_block_id()
             .map_err(BlockError::BestBlockLoadError)
@@ -335,7 +692,21 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
             .log_err()?;
 
         let block_index = chainstate_ref.accept_block(&block).log_err()?;
-        let result = chainstate_ref.activate_best_chain(block_index, best_block_id).log_err()?;
+        let result = match chainstate_ref.activate_best_chain(block_index, best_block_id) {
+            Ok(result) => result,
+            Err(ReorgError::ConnectTipFailed(invalid_block_id, err)) => {
+                // Changes made so far are discarded; the failure is recorded separately
+                drop(chainstate_ref);
+                if err.ban_score() > 0 {
+                    self.record_invalid_block(&block, &invalid_block_id).log_err()?;
+                }
+                return Err(err);
+            }
+            Err(ReorgError::OtherError(err)) => return Err(err),
+        };
+        if result.is_some() {
+            chainstate_ref.prune_blocks().log_err()?;
+        }
         let db_commit_result = chainstate_ref.commit_db_tx().log_err();
         match db_commit_result {
             Ok(_) => {}
@@ -346,24 +717,134 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
             }
         }
 
-        let new_block_index_after_orphans = self.process_orphans(&block.get_id());
-        let result = match new_block_index_after_orphans {
-            Some(result_from_orphan) => Some(result_from_orphan),
-            None => result,
-        };
+        Ok(result)
+    }
 
-        self.broadcast_new_tip_event(&result);
+    /// Store the block that was being processed and mark the block that failed to connect,
+    /// which is either the same block or one of its ancestors, as invalid
+    fn record_invalid_block(
+        &mut self,
+        block: &WithId<Block>,
+        invalid_block_id: &Id<Block>,
+    ) -> Result<(), BlockError> {
+        let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+        chainstate_ref.accept_block(block).log_err()?;
+        chainstate_ref.set_block_invalid(invalid_block_id).log_err()?;
+        chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()
+    }
 
-        if let Some(ref bi) = result {
-            log::info!(
-                "New tip in chainstate {} with height {}",
-                bi.block_id(),
-                bi.block_height()
-            );
+    /// Switch to the valid block with the most chain trust, if it's better than the current tip.
+    /// Blocks that fail to connect on the way are marked invalid and the next best one is tried.
+    /// Returns the block index of the new tip, if the tip changed.
+    fn activate_best_valid_chain(&mut self) -> Result<Option<BlockIndex>, BlockError> {
+        let mut result = None;
+        loop {
+            let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+            let candidate = chainstate_ref
+                .get_best_valid_candidate()
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?;
+            let candidate = match candidate {
+                Some(candidate) => candidate,
+                None => return Ok(result),
+            };
+
+            let best_block_id = chainstate_ref
+                .get_best_block_id()
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?;
 
-            self.is_initial_block_download_finished = self.is_fresh_block(&bi.block_timestamp());
+            match chainstate_ref.activate_best_chain(candidate, best_block_id) {
+                Ok(new_tip) => {
+                    chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+                    result = new_tip.or(result);
+                }
+                Err(ReorgError::ConnectTipFailed(invalid_block_id, err)) if err.ban_score() > 0 => {
+                    drop(chainstate_ref);
+                    let mut chainstate_ref =
+                        self.make_db_tx().map_err(BlockError::from).log_err()?;
+                    chainstate_ref.set_block_invalid(&invalid_block_id).log_err()?;
+                    chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+                }
+                Err(err) => return Err(err.into()),
+            }
         }
+    }
+
+    /// Mark the block and all of its descendants as invalid. If the block is in the main chain,
+    /// it's disconnected and the best remaining valid chain is activated.
+    pub fn invalidate_block(&mut self, block_id: &Id<Block>) -> Result<(), BlockError> {
+        log::info!("Invalidating block: {}", block_id);
+
+        let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+        let block_index = chainstate_ref
+            .get_block_index(block_id)
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?
+            .ok_or(BlockError::BlockNotFound(*block_id))
+            .log_err()?;
+        let was_in_main_chain = chainstate_ref
+            .is_block_in_main_chain(&(*block_id).into())
+            .map_err(BlockError::BestBlockLoadError)
+            .log_err()?;
+        chainstate_ref.disconnect_block_and_descendants(&block_index).log_err()?;
+        chainstate_ref.set_block_invalid(block_id).log_err()?;
+        chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+
+        let new_tip = match self.activate_best_valid_chain().log_err()? {
+            Some(new_tip) => Some(new_tip),
+            // The tip has moved back to the parent of the invalidated block
+            None if was_in_main_chain => {
+                match block_index.prev_block_id().classify(&self.chain_config) {
+                    GenBlockId::Block(id) => self
+                        .query()
+                        .and_then(|query| query.get_block_index(&id))
+                        .map_err(BlockError::BestBlockLoadError)
+                        .log_err()?,
+                    GenBlockId::Genesis(_) => None,
+                }
+            }
+            None => None,
+        };
+        self.broadcast_new_tip_event(&new_tip);
 
+        Ok(())
+    }
+
+    /// Clear the invalid status of the block, its ancestors and descendants, and switch to
+    /// the best valid chain, which may now include the block.
+    pub fn reconsider_block(&mut self, block_id: &Id<Block>) -> Result<(), BlockError> {
+        log::info!("Reconsidering block: {}", block_id);
+
+        let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+        chainstate_ref.reset_block_failure_flags(block_id).log_err()?;
+        chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+
+        let new_tip = self.activate_best_valid_chain().log_err()?;
+        self.broadcast_new_tip_event(&new_tip);
+
+        Ok(())
+    }
+
+    /// Disconnect and reconnect the last `depth` blocks of the main chain in memory, on top of
+    /// a read-only database transaction, and return the first inconsistency found
+    pub fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, BlockError> {
+        utils::ensure!(
+            !*self.chainstate_config.headers_only,
+            BlockError::BlockDataInHeadersOnlyMode
+        );
+        utils::ensure!(
+            depth <= MAX_VERIFY_CHAIN_DEPTH,
+            BlockError::VerifyChainDepthTooLarge(depth, MAX_VERIFY_CHAIN_DEPTH)
+        );
+
+        let chainstate_ref = self.make_db_tx_ro().map_err(BlockError::from).log_err()?;
+        let result = chainstate_ref.verify_chain(depth).log_err()?;
+
+        match &result {
+            Some(inconsistency) => log::warn!("Chain verification failed: {}", inconsistency),
+            None => log::info!("Chain verification finished without errors"),
+        }
         Ok(result)
     }
 
@@ -373,12 +854,176 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         block: WithId<Block>,
         block_source: BlockSource,
     ) -> Result<Option<BlockIndex>, BlockError> {
+        utils::ensure!(
+            !*self.chainstate_config.headers_only,
+            BlockError::BlockDataInHeadersOnlyMode
+        );
+        self.update_utxo_snapshot_validation();
+        if let Some(block_id) = self.invalid_utxo_snapshot_block {
+            return Err(BlockError::UtxoSnapshotHistoryInvalid(block_id));
+        }
+        if let Some(block_height) = self.utxo_snapshot_history_block_height(&block)? {
+            self.submit_utxo_snapshot_history_block(block, block_height, block_source);
+            return Ok(None);
+        }
         self.attempt_to_process_block(block, block_source, 0)
     }
 
+    /// Replace the state of a chainstate without blocks with the one from the snapshot,
+    /// and start validating the history of the snapshot
+    pub fn import_utxo_snapshot(
+        &mut self,
+        snapshot: &UtxoSnapshot,
+    ) -> Result<(), crate::ChainstateError> {
+        let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+        let tip_block_index = chainstate_ref.load_utxo_snapshot(snapshot).log_err()?;
+        chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+
+        log::info!(
+            "Loaded utxo snapshot at block {} with height {}",
+            tip_block_index.block_id(),
+            tip_block_index.block_height()
+        );
+
+        self.start_utxo_snapshot_validation()?;
+        // The blocks below the snapshot are not available, so only the new tip is announced
+        self.events_tip = (*tip_block_index.block_id()).into();
+        self.broadcast_new_tip_event(&Some(tip_block_index));
+        Ok(())
+    }
+
+    /// Start validating the history of the loaded utxo snapshot, if there is one
+    fn start_utxo_snapshot_validation(&mut self) -> Result<(), crate::ChainstateError> {
+        let chainstate_ref = self.make_db_tx_ro().map_err(BlockError::from)?;
+        let block_id = match chainstate_ref.get_utxo_snapshot_block()? {
+            Some(block_id) => block_id,
+            None => return Ok(()),
+        };
+        let block_height = chainstate_ref
+            .get_block_index(&block_id)?
+            .ok_or(PropertyQueryError::BlockNotFound(block_id))?
+            .block_height();
+        let checkpoint = chainstate_ref.get_utxo_snapshot_validation_entries()?;
+        drop(chainstate_ref);
+
+        let expected_hash = match self.chain_config.utxo_snapshot_hashes().get(&block_id) {
+            Some(hash) => *hash,
+            None => {
+                log::warn!(
+                    "The hash of the utxo snapshot at block {} is not pinned anymore, its history cannot be validated",
+                    block_id
+                );
+                return Ok(());
+            }
+        };
+
+        let validator = UtxoSnapshotHistoryValidator::new(
+            Arc::clone(&self.chain_config),
+            self.time_getter.clone(),
+            block_id,
+            block_height,
+            expected_hash,
+            checkpoint,
+            self.chainstate_storage.utxo_snapshot_validation_handle(),
+        )?;
+        self.utxo_snapshot_validator = Some(Box::new(validator));
+        Ok(())
+    }
+
+    /// The blocks up to the loaded utxo snapshot block are already known by their headers,
+    /// they are only used to validate the history of the snapshot. Returns the height of such
+    /// a block.
+    fn utxo_snapshot_history_block_height(
+        &self,
+        block: &WithId<Block>,
+    ) -> Result<Option<BlockHeight>, BlockError> {
+        let validator = match &self.utxo_snapshot_validator {
+            Some(validator) => validator,
+            None => return Ok(None),
+        };
+        let block_index = self
+            .make_db_tx_ro()?
+            .get_block_index(&block.get_id())
+            .map_err(BlockError::BestBlockLoadError)?;
+        Ok(block_index
+            .map(|block_index| block_index.block_height())
+            .filter(|block_height| *block_height <= validator.block_height()))
+    }
+
+    fn submit_utxo_snapshot_history_block(
+        &self,
+        block: WithId<Block>,
+        block_height: BlockHeight,
+        block_source: BlockSource,
+    ) {
+        let validator = self
+            .utxo_snapshot_validator
+            .as_ref()
+            .expect("Only called while the snapshot is validated");
+        validator.submit_block(block, block_height, block_source);
+    }
+
+    /// Stop following the history validation once it has ended
+    fn update_utxo_snapshot_validation(&mut self) {
+        let outcome = match &self.utxo_snapshot_validator {
+            Some(validator) => validator.outcome(),
+            None => return,
+        };
+        match outcome {
+            Some(UtxoSnapshotHistoryOutcome::Valid) => {
+                self.utxo_snapshot_validator = None;
+            }
+            Some(UtxoSnapshotHistoryOutcome::Invalid(block_id)) => {
+                self.utxo_snapshot_validator = None;
+                self.invalid_utxo_snapshot_block = Some(block_id);
+            }
+            None => {}
+        }
+    }
+
+    /// The headers of the next blocks needed to validate the history of the loaded utxo
+    /// snapshot, at most `max_count` of them
+    pub fn get_utxo_snapshot_history_headers(
+        &self,
+        max_count: usize,
+    ) -> Result<Vec<BlockHeader>, PropertyQueryError> {
+        let validator = match &self.utxo_snapshot_validator {
+            Some(validator) if validator.outcome().is_none() => validator,
+            Some(_) | None => return Ok(Vec::new()),
+        };
+        let first_height = validator.queued_height().next_height();
+        let chainstate_ref = self.make_db_tx_ro()?;
+        let mut headers = Vec::new();
+        let mut height = first_height;
+        while height <= validator.block_height() && headers.len() < max_count {
+            let header = chainstate_ref
+                .get_header_from_height(&height)?
+                .ok_or(PropertyQueryError::BlockForHeightNotFound(height))?;
+            headers.push(header);
+            height = height.next_height();
+        }
+        Ok(headers)
+    }
+
     /// Initialize chainstate with genesis block
     pub fn process_genesis(&mut self) -> Result<(), BlockError> {
-        use chainstate_storage::{BlockchainStorageWrite, TransactionRw};
+        use chainstate_storage::TransactionRw;
+
+        // Initialize storage with given info
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+        self.write_genesis_data(&mut db_tx)?;
+
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+        Ok(())
+    }
+
+    /// Write the main chain entry, the indexes and the utxo set of the genesis block
+    fn write_genesis_data(&self, db_tx: &mut TxRw<'_, S>) -> Result<(), BlockError> {
+        use chainstate_storage::BlockchainStorageWrite;
 
         // Gather information about genesis.
         let genesis = self.chain_config.genesis_block();
@@ -387,12 +1032,6 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         let genesis_index = common::chain::TxMainChainIndex::new(genesis_id.into(), utxo_count)
             .expect("Genesis not constructed correctly");
 
-        // Initialize storage with given info
-        let mut db_tx = self
-            .chainstate_storage
-            .transaction_rw(None)
-            .map_err(BlockError::from)
-            .log_err()?;
         db_tx
             .set_best_block_id(&genesis_id)
             .map_err(BlockError::StorageError)
@@ -409,10 +1048,36 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
                 .log_err()?;
         }
 
+        if *self.chainstate_config.address_index_enabled {
+            for (idx, output) in genesis.utxos().iter().enumerate() {
+                let destination = match output.purpose().destination() {
+                    Some(destination) => destination.clone(),
+                    None => continue,
+                };
+                let (coin_or_token_id, amount) = match output.value() {
+                    OutputValue::Coin(amount) => (CoinOrTokenId::Coin, *amount),
+                    OutputValue::Token(token_data) => match &**token_data {
+                        TokenData::TokenTransfer(transfer) => {
+                            (CoinOrTokenId::TokenId(transfer.token_id), transfer.amount)
+                        }
+                        // Tokens can't be issued in genesis
+                        TokenData::TokenIssuance(_) | TokenData::NftIssuance(_) => continue,
+                    },
+                };
+                let outpoint = OutPoint::new(genesis_id.into(), idx as u32);
+                db_tx
+                    .set_address_index_entry(
+                        &(destination, coin_or_token_id, outpoint),
+                        &AddressOutputInfo::new(amount, BlockHeight::zero()),
+                    )
+                    .map_err(BlockError::StorageError)
+                    .log_err()?;
+            }
+        }
+
         // initialize the utxo-set by adding genesis outputs to it
-        UtxosDB::initialize_db(&mut db_tx, &self.chain_config);
+        UtxosDB::initialize_db(db_tx, &self.chain_config);
 
-        db_tx.commit().expect("Genesis database initialization failed");
         Ok(())
     }
 
@@ -420,13 +1085,54 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         &self,
         block: WithId<Block>,
     ) -> Result<WithId<Block>, BlockError> {
+        // The blocks of the utxo snapshot history are checked in full when they are validated
+        if self.utxo_snapshot_history_block_height(&block)?.is_some() {
+            return Ok(block);
+        }
         let chainstate_ref = self.make_db_tx_ro().map_err(BlockError::from)?;
         chainstate_ref.check_block(&block).log_err()?;
         Ok(block)
     }
 
+    /// Validate a header and add it to the block index of a headers-only chainstate.
+    ///
+    /// The header chain with the most trust becomes the main chain; returns the new tip if it
+    /// has changed.
+    pub fn process_header(
+        &mut self,
+        header: BlockHeader,
+    ) -> Result<Option<BlockIndex>, BlockError> {
+        utils::ensure!(
+            *self.chainstate_config.headers_only,
+            BlockError::HeadersOnlyModeDisabled
+        );
+        log::info!("Processing header: {}", header.get_id());
+
+        let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+        chainstate_ref.check_not_known_invalid(&header).log_err()?;
+        chainstate_ref.check_block_header(&header).log_err()?;
+        let block_index = chainstate_ref.accept_header(&header).log_err()?;
+        let result = chainstate_ref.activate_best_header_chain(block_index).log_err()?;
+        chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+
+        self.broadcast_new_tip_event(&result);
+
+        if let Some(ref bi) = result {
+            log::info!(
+                "New header tip in chainstate {} with height {}",
+                bi.block_id(),
+                bi.block_height()
+            );
+
+            self.is_initial_block_download_finished = self.is_fresh_block(&bi.block_timestamp());
+        }
+
+        Ok(result)
+    }
+
     pub fn preliminary_header_check(&self, block: BlockHeader) -> Result<(), BlockError> {
         let chainstate_ref = self.make_db_tx_ro().map_err(BlockError::from)?;
+        chainstate_ref.check_not_known_invalid(&block).log_err()?;
         chainstate_ref.check_block_header(&block).log_err()?;
         Ok(())
     }
@@ -447,6 +1153,10 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V>
         &self.events_controller
     }
 
+    pub fn storage_backup_handle(&self) -> Box<dyn chainstate_storage::BlockchainStorageBackup> {
+        self.chainstate_storage.backup_handle()
+    }
+
     pub fn is_initial_block_download(&self) -> Result<bool, PropertyQueryError> {
         if self.is_initial_block_download_finished {
             return Ok(false);
@@ -13,18 +13,27 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
+
 use chainstate_storage::BlockchainStorageRead;
-use chainstate_types::{BlockIndex, GenBlockIndex, Locator, PropertyQueryError};
+use chainstate_types::{
+    AddressHistoryEntry, BlockIndex, ChainTip, GenBlockIndex, Locator, OutputSpendInfo,
+    PropertyQueryError, UtxoSetInfo,
+};
 use common::{
     chain::{
-        block::{BlockHeader, BlockReward},
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            tx_inclusion_proof::TxInclusionProof,
+            BlockHeader, BlockReward,
+        },
         tokens::{
-            OutputValue, RPCFungibleTokenInfo, RPCNonFungibleTokenInfo, RPCTokenInfo,
-            TokenAuxiliaryData, TokenData, TokenId,
+            CoinOrTokenId, OutputValue, RPCFungibleTokenInfo, RPCNonFungibleTokenInfo,
+            RPCTokenInfo, TokenAuxiliaryData, TokenData, TokenId,
         },
-        Block, GenBlock, OutPointSourceId, Transaction, TxMainChainIndex,
+        Block, Destination, GenBlock, OutPoint, OutPointSourceId, Transaction, TxMainChainIndex,
     },
-    primitives::{BlockDistance, BlockHeight, Id, Idable},
+    primitives::{Amount, BlockDistance, BlockHeight, Id, Idable},
 };
 
 use super::{
@@ -131,6 +140,10 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
         self.chainstate_ref.get_block_height_in_main_chain(id)
     }
 
+    pub fn is_block_in_main_chain(&self, id: &Id<GenBlock>) -> Result<bool, PropertyQueryError> {
+        self.chainstate_ref.is_block_in_main_chain(id)
+    }
+
     pub fn get_headers(&self, locator: Locator) -> Result<Vec<BlockHeader>, PropertyQueryError> {
         // use genesis block if no common ancestor with better block height is found
         let mut best = BlockHeight::new(0);
@@ -258,4 +271,60 @@ impl<'a, S: BlockchainStorageRead, O: OrphanBlocks, V: TransactionVerificationSt
     pub fn get_block_id_tree_as_list(&self) -> Result<Vec<Id<Block>>, PropertyQueryError> {
         self.chainstate_ref.get_block_id_tree_as_list()
     }
+
+    pub fn get_chain_tips(&self) -> Result<Vec<ChainTip>, PropertyQueryError> {
+        self.chainstate_ref.get_chain_tips()
+    }
+
+    pub fn get_pruned_height(&self) -> Result<Option<BlockHeight>, PropertyQueryError> {
+        self.chainstate_ref.get_pruned_height()
+    }
+
+    pub fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, PropertyQueryError> {
+        self.chainstate_ref.get_utxo_set_info()
+    }
+
+    pub fn get_address_balance(
+        &self,
+        destination: &Destination,
+    ) -> Result<BTreeMap<CoinOrTokenId, Amount>, PropertyQueryError> {
+        self.chainstate_ref.get_address_balance(destination)
+    }
+
+    pub fn get_address_history(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, PropertyQueryError> {
+        self.chainstate_ref.get_address_history(destination)
+    }
+
+    pub fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, PropertyQueryError> {
+        self.chainstate_ref.get_output_spend_info(outpoint)
+    }
+
+    pub fn get_block_filter(
+        &self,
+        block_id: &Id<Block>,
+    ) -> Result<Option<BlockFilter>, PropertyQueryError> {
+        self.chainstate_ref.get_block_filter(block_id)
+    }
+
+    pub fn get_block_filter_header(
+        &self,
+        block_id: &Id<Block>,
+    ) -> Result<Option<BlockFilterHeader>, PropertyQueryError> {
+        self.chainstate_ref.get_block_filter_header(block_id)
+    }
+
+    pub fn get_tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: &Id<Transaction>,
+    ) -> Result<Option<TxInclusionProof>, PropertyQueryError> {
+        let block = self.chainstate_ref.get_block(block_id)?;
+        Ok(block.and_then(|block| TxInclusionProof::from_block(&block, tx_id)))
+    }
 }
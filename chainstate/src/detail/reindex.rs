@@ -0,0 +1,252 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rebuilding the chainstate from the stored blocks.
+//!
+//! The data derived from connecting the blocks (the utxo set, the undo data, the PoS accounting
+//! data, the token data and the indexes) is dropped, and the blocks of the stored main chain are
+//! then reconnected one by one on top of genesis. The blocks themselves and their indexes are
+//! kept. The tip being reconnected is stored, so an interrupted reindex continues on the next
+//! startup.
+//!
+//! The derived data is dropped in batches, each in its own transaction, after the main chain is
+//! reset to genesis. A stored target with genesis as the tip therefore means that dropping the
+//! data may not be finished, and it is resumed before the blocks are reconnected.
+
+use chainstate_storage::{
+    BlockchainStorage, BlockchainStorageRead, BlockchainStorageWrite, TransactionRw,
+};
+use common::{
+    chain::{Block, GenBlock, GenBlockId},
+    primitives::Id,
+};
+use logging::log;
+use utils::{ensure, tap_error_log::LogError};
+
+use crate::{BlockError, TransactionVerificationStrategy};
+
+use super::{Chainstate, TxRw};
+
+/// How often the progress of the reindex is logged, in blocks
+const REINDEX_PROGRESS_LOG_INTERVAL: usize = 1000;
+
+/// The maximum number of derived data entries dropped in one database transaction
+const DERIVED_DATA_DELETION_BATCH_SIZE: usize = 10_000;
+
+impl<S: BlockchainStorage, V: TransactionVerificationStrategy> Chainstate<S, V> {
+    /// Reset the main chain to genesis and drop the data derived from the blocks, remembering the
+    /// current tip so that `continue_reindex` can reconnect the blocks up to it.
+    pub(super) fn start_reindex(&mut self) -> Result<(), BlockError> {
+        ensure!(
+            !*self.chainstate_config.headers_only,
+            BlockError::BlockDataInHeadersOnlyMode
+        );
+
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+
+        // If a previous reindex was interrupted, its target is still the tip to reconnect
+        let reindex_target =
+            match db_tx.get_reindex_target().map_err(BlockError::StorageError).log_err()? {
+                Some(reindex_target) => Some(reindex_target),
+                None => self.set_reindex_target(&mut db_tx)?,
+            };
+
+        match reindex_target {
+            Some(_) => {
+                db_tx
+                    .set_best_block_id(&self.chain_config.genesis_block_id())
+                    .map_err(BlockError::StorageError)
+                    .log_err()?;
+                db_tx.commit().map_err(BlockError::from).log_err()?;
+                self.drop_derived_data()
+            }
+            // Only the data of genesis is there, which is dropped and written again at once
+            None => {
+                log::info!("Dropping the chainstate data derived from the blocks");
+                db_tx.del_derived_data(usize::MAX).map_err(BlockError::StorageError).log_err()?;
+                self.write_genesis_data(&mut db_tx)?;
+                db_tx.commit().map_err(BlockError::from).log_err()
+            }
+        }
+    }
+
+    /// Store the current tip as the target of the reindex, unless it is genesis; fails if some of
+    /// the blocks are not stored
+    fn set_reindex_target(&self, db_tx: &mut TxRw<'_, S>) -> Result<Option<Id<Block>>, BlockError> {
+        let pruned_height =
+            db_tx.get_pruned_height().map_err(BlockError::StorageError).log_err()?;
+        let utxo_snapshot_block =
+            db_tx.get_utxo_snapshot_block().map_err(BlockError::StorageError).log_err()?;
+        ensure!(
+            pruned_height.is_none() && utxo_snapshot_block.is_none(),
+            BlockError::ReindexWithoutBlocks
+        );
+
+        let best_block_id = db_tx
+            .get_best_block_id()
+            .map_err(BlockError::StorageError)
+            .log_err()?
+            .expect("Best block must be set");
+        match best_block_id.classify(&self.chain_config) {
+            GenBlockId::Block(best_block_id) => {
+                db_tx
+                    .set_reindex_target(&best_block_id)
+                    .map_err(BlockError::StorageError)
+                    .log_err()?;
+                Ok(Some(best_block_id))
+            }
+            GenBlockId::Genesis(_) => Ok(None),
+        }
+    }
+
+    /// Drop the data derived from the blocks in batches, writing the data of genesis along with
+    /// the last batch
+    fn drop_derived_data(&mut self) -> Result<(), BlockError> {
+        log::info!("Dropping the chainstate data derived from the blocks");
+
+        loop {
+            let mut db_tx = self
+                .chainstate_storage
+                .transaction_rw(None)
+                .map_err(BlockError::from)
+                .log_err()?;
+            let finished = db_tx
+                .del_derived_data(DERIVED_DATA_DELETION_BATCH_SIZE)
+                .map_err(BlockError::StorageError)
+                .log_err()?;
+            if finished {
+                self.write_genesis_data(&mut db_tx)?;
+            }
+            db_tx.commit().map_err(BlockError::from).log_err()?;
+
+            if finished {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Reconnect the stored main chain blocks up to the target of an unfinished reindex, if
+    /// there is one, after dropping the rest of the derived data if that was interrupted.
+    pub(super) fn continue_reindex(&mut self) -> Result<(), BlockError> {
+        if self.is_dropping_derived_data()? {
+            self.drop_derived_data()?;
+        }
+
+        let block_ids = match self.blocks_to_reindex()? {
+            Some(block_ids) => block_ids,
+            None => return Ok(()),
+        };
+
+        log::info!(
+            "Reindexing the chainstate, {} blocks to connect",
+            block_ids.len()
+        );
+
+        for (connected, block_id) in block_ids.iter().enumerate() {
+            let mut chainstate_ref = self.make_db_tx().map_err(BlockError::from).log_err()?;
+            let block_index = chainstate_ref
+                .get_block_index(block_id)
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?
+                .ok_or(BlockError::BlockNotFound(*block_id))?;
+            let best_block_id = chainstate_ref
+                .get_best_block_id()
+                .map_err(BlockError::BestBlockLoadError)
+                .log_err()?;
+            chainstate_ref
+                .activate_best_chain(block_index, best_block_id)
+                .map_err(BlockError::from)
+                .log_err()?;
+            chainstate_ref.commit_db_tx().map_err(BlockError::from).log_err()?;
+
+            if (connected + 1) % REINDEX_PROGRESS_LOG_INTERVAL == 0 {
+                log::info!(
+                    "Reindexing the chainstate, {}/{} blocks connected",
+                    connected + 1,
+                    block_ids.len()
+                );
+            }
+        }
+
+        let mut db_tx = self
+            .chainstate_storage
+            .transaction_rw(None)
+            .map_err(BlockError::from)
+            .log_err()?;
+        db_tx.del_reindex_target().map_err(BlockError::StorageError).log_err()?;
+        db_tx.commit().map_err(BlockError::from).log_err()?;
+
+        log::info!("Reindexing the chainstate finished");
+
+        Ok(())
+    }
+
+    /// Whether the reindex may have been interrupted while dropping the derived data, which
+    /// happens after the tip is reset to genesis and before any block is reconnected
+    fn is_dropping_derived_data(&self) -> Result<bool, BlockError> {
+        let db_tx = self.chainstate_storage.transaction_ro().map_err(BlockError::from).log_err()?;
+        if db_tx
+            .get_reindex_target()
+            .map_err(BlockError::StorageError)
+            .log_err()?
+            .is_none()
+        {
+            return Ok(false);
+        }
+        let best_block_id =
+            db_tx.get_best_block_id().map_err(BlockError::StorageError).log_err()?;
+        Ok(best_block_id == Some(self.chain_config.genesis_block_id()))
+    }
+
+    /// The ids of the blocks between the current tip and the reindex target, in connection order
+    fn blocks_to_reindex(&self) -> Result<Option<Vec<Id<Block>>>, BlockError> {
+        let db_tx = self.chainstate_storage.transaction_ro().map_err(BlockError::from).log_err()?;
+
+        let target_id =
+            match db_tx.get_reindex_target().map_err(BlockError::StorageError).log_err()? {
+                Some(target_id) => target_id,
+                None => return Ok(None),
+            };
+        let best_block_id = db_tx
+            .get_best_block_id()
+            .map_err(BlockError::StorageError)
+            .log_err()?
+            .expect("Best block must be set");
+
+        // The blocks are reconnected along the target chain, so the tip is always its ancestor
+        let mut block_ids = Vec::new();
+        let mut current_id: Id<GenBlock> = target_id.into();
+        while current_id != best_block_id {
+            let block_id = match current_id.classify(&self.chain_config) {
+                GenBlockId::Block(block_id) => block_id,
+                GenBlockId::Genesis(_) => break,
+            };
+            let block_index = db_tx
+                .get_block_index(&block_id)
+                .map_err(BlockError::StorageError)
+                .log_err()?
+                .ok_or(BlockError::BlockNotFound(block_id))?;
+            block_ids.push(block_id);
+            current_id = *block_index.prev_block_id();
+        }
+        block_ids.reverse();
+
+        Ok(Some(block_ids))
+    }
+}
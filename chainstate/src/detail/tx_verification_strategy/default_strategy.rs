@@ -79,6 +79,7 @@ impl TransactionVerificationStrategy for DefaultTransactionVerificationStrategy
         let block_reward_tx_index = construct_reward_tx_indices(&verifier_config, block)?;
 
         let mut tx_verifier = tx_verifier_maker(storage_backend, chain_config, verifier_config);
+        tx_verifier.defer_signature_checks();
 
         let reward_fees = tx_verifier
             .connect_transactable(
@@ -111,6 +112,8 @@ impl TransactionVerificationStrategy for DefaultTransactionVerificationStrategy
             })
             .log_err()?;
 
+        tx_verifier.verify_deferred_signatures(block).log_err()?;
+
         tx_verifier
             .check_block_reward(block, Fee(total_fees), Subsidy(block_subsidy))
             .log_err()?;
@@ -0,0 +1,456 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utxo set snapshots, which let a fresh node start from a recent block instead of genesis.
+//!
+//! A snapshot contains the headers of the main chain and the state derived from its blocks: the
+//! utxo set, the PoS accounting data and the token data. It can only be loaded if its content
+//! hash is pinned in the chain config. The blocks below the snapshot are then treated as pruned,
+//! and their history is validated separately as they are received.
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::sync::{mpsc, Arc, Mutex};
+
+use chainstate_storage::UtxoSnapshotValidationStorage;
+use chainstate_types::PropertyQueryError;
+use common::{
+    chain::{
+        block::BlockHeader,
+        tokens::{TokenAuxiliaryData, TokenId},
+        Block, ChainConfig, GenBlock, OutPoint, Transaction,
+    },
+    primitives::{id, id::WithId, BlockHeight, Id, Idable, H256},
+    time_getter::TimeGetter,
+};
+use logging::log;
+use pos_accounting::PoSAccountingData;
+use serialization::{Decode, Encode};
+use utils::ensure;
+use utxo::Utxo;
+
+use crate::{
+    BlockError, BlockSource, ChainstateConfig, ChainstateError,
+    DefaultTransactionVerificationStrategy, PruneMode, MIN_BLOCKS_TO_KEEP_WHEN_PRUNING,
+};
+
+use super::Chainstate;
+
+/// The version of the snapshot file format, increased on incompatible changes
+pub const UTXO_SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// The changes made by the history validation are saved to the chainstate storage after this
+/// many blocks, so the validation doesn't start over from genesis after a restart
+const VALIDATION_CHECKPOINT_INTERVAL: usize = 1000;
+
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+pub enum UtxoSnapshotError {
+    #[error("File error: {0}")]
+    File(String),
+    #[error("Deserialization error: {0}")]
+    Deserialization(#[from] serialization::Error),
+    #[error("The snapshot was created for a different chain")]
+    WrongMagicBytes,
+    #[error("Unsupported snapshot format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("The snapshot is corrupted, its content hash is {0} instead of {1}")]
+    CorruptedContent(H256, H256),
+    #[error("The snapshot has no block headers")]
+    NoHeaders,
+    #[error("No snapshot hash is pinned in the chain config for block {0}")]
+    HashNotPinned(Id<Block>),
+    #[error("The snapshot hash {1} for block {0} doesn't match the pinned hash {2}")]
+    PinnedHashMismatch(Id<Block>, H256, H256),
+    #[error("Snapshot import error: {0}")]
+    BlockProcessing(#[from] BlockError),
+    #[error("Property read error: {0}")]
+    FailedToReadProperty(#[from] PropertyQueryError),
+    #[error("Failed to start the history validation thread: {0}")]
+    ValidationThread(String),
+}
+
+impl From<std::io::Error> for UtxoSnapshotError {
+    fn from(error: std::io::Error) -> Self {
+        Self::File(error.to_string())
+    }
+}
+
+/// The state of the chain at a given block
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct UtxoSnapshot {
+    /// The headers of the main chain blocks after genesis, the last one is the snapshot block
+    headers: Vec<BlockHeader>,
+    utxos: BTreeMap<OutPoint, Utxo>,
+    accounting_data: PoSAccountingData,
+    token_aux_data: BTreeMap<TokenId, TokenAuxiliaryData>,
+    token_ids: BTreeMap<Id<Transaction>, TokenId>,
+}
+
+impl UtxoSnapshot {
+    pub fn new(
+        headers: Vec<BlockHeader>,
+        utxos: BTreeMap<OutPoint, Utxo>,
+        accounting_data: PoSAccountingData,
+        token_aux_data: BTreeMap<TokenId, TokenAuxiliaryData>,
+        token_ids: BTreeMap<Id<Transaction>, TokenId>,
+    ) -> Self {
+        Self {
+            headers,
+            utxos,
+            accounting_data,
+            token_aux_data,
+            token_ids,
+        }
+    }
+
+    /// The block at which the snapshot was taken, `None` if the snapshot is taken at genesis
+    pub fn block_id(&self) -> Option<Id<Block>> {
+        self.headers.last().map(|header| header.get_id())
+    }
+
+    pub fn headers(&self) -> &[BlockHeader] {
+        &self.headers
+    }
+
+    pub fn utxos(&self) -> &BTreeMap<OutPoint, Utxo> {
+        &self.utxos
+    }
+
+    pub fn accounting_data(&self) -> &PoSAccountingData {
+        &self.accounting_data
+    }
+
+    pub fn token_aux_data(&self) -> &BTreeMap<TokenId, TokenAuxiliaryData> {
+        &self.token_aux_data
+    }
+
+    pub fn token_ids(&self) -> &BTreeMap<Id<Transaction>, TokenId> {
+        &self.token_ids
+    }
+
+    /// The hash that commits to the whole content of the snapshot
+    pub fn content_hash(&self) -> H256 {
+        id::hash_encoded(self)
+    }
+}
+
+/// Write the snapshot, prefixed with the chain magic bytes, the format version and the content
+/// hash. Returns the content hash.
+pub fn export_utxo_snapshot_stream<'a>(
+    magic_bytes: &[u8],
+    writer: &mut std::io::BufWriter<Box<dyn Write + 'a + Send>>,
+    snapshot: &UtxoSnapshot,
+) -> Result<H256, UtxoSnapshotError> {
+    let content_hash = snapshot.content_hash();
+    writer.write_all(magic_bytes)?;
+    writer.write_all(&UTXO_SNAPSHOT_FORMAT_VERSION.encode())?;
+    writer.write_all(&content_hash.encode())?;
+    writer.write_all(&snapshot.encode())?;
+    writer.flush()?;
+    Ok(content_hash)
+}
+
+/// Read a snapshot written with `export_utxo_snapshot_stream` and check that its content hash
+/// is the one pinned in the chain config
+pub fn import_utxo_snapshot_stream<'a>(
+    chain_config: &ChainConfig,
+    reader: &mut std::io::BufReader<Box<dyn Read + 'a + Send>>,
+) -> Result<UtxoSnapshot, UtxoSnapshotError> {
+    let mut magic_bytes = [0u8; 4];
+    reader.read_exact(&mut magic_bytes)?;
+    ensure!(
+        &magic_bytes == chain_config.magic_bytes(),
+        UtxoSnapshotError::WrongMagicBytes
+    );
+
+    // The snapshot is decoded straight from the reader without buffering the file, but the
+    // decoded snapshot itself is held in memory
+    let mut input = parity_scale_codec::IoReader(reader);
+    let version = u32::decode(&mut input)?;
+    ensure!(
+        version == UTXO_SNAPSHOT_FORMAT_VERSION,
+        UtxoSnapshotError::UnsupportedVersion(version)
+    );
+
+    let stored_hash = H256::decode(&mut input)?;
+    let snapshot = UtxoSnapshot::decode(&mut input)?;
+    let content_hash = snapshot.content_hash();
+    ensure!(
+        content_hash == stored_hash,
+        UtxoSnapshotError::CorruptedContent(content_hash, stored_hash)
+    );
+
+    let block_id = snapshot.block_id().ok_or(UtxoSnapshotError::NoHeaders)?;
+    let pinned_hash = chain_config
+        .utxo_snapshot_hashes()
+        .get(&block_id)
+        .ok_or(UtxoSnapshotError::HashNotPinned(block_id))?;
+    ensure!(
+        &content_hash == pinned_hash,
+        UtxoSnapshotError::PinnedHashMismatch(block_id, content_hash, *pinned_hash)
+    );
+
+    Ok(snapshot)
+}
+
+/// How the validation of the history of a snapshot has ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UtxoSnapshotHistoryOutcome {
+    Valid,
+    Invalid(Id<Block>),
+}
+
+/// The progress of the history validation, shared with the validation thread
+struct ValidationProgress {
+    /// The height of the last validated block of the history
+    validated_height: BlockHeight,
+    /// The height of the last block sent to the validation thread
+    queued_height: BlockHeight,
+    outcome: Option<UtxoSnapshotHistoryOutcome>,
+}
+
+type HistoryChainstate =
+    Chainstate<chainstate_storage::inmemory::Store, DefaultTransactionVerificationStrategy>;
+
+/// Validates the history of a loaded snapshot. Its blocks are connected to a separate in-memory
+/// chainstate on a thread of its own, and once the snapshot block is reached, the resulting state
+/// must have the same content hash as the snapshot.
+///
+/// The changes made to the in-memory chainstate are periodically saved to the chainstate storage
+/// as a checkpoint, from which the validation continues after a restart.
+pub struct UtxoSnapshotHistoryValidator {
+    block_height: BlockHeight,
+    progress: Arc<Mutex<ValidationProgress>>,
+    block_sender: Option<mpsc::Sender<(WithId<Block>, BlockSource)>>,
+    thread: Option<std::thread::JoinHandle<()>>,
+}
+
+impl UtxoSnapshotHistoryValidator {
+    pub fn new(
+        chain_config: Arc<ChainConfig>,
+        time_getter: TimeGetter,
+        block_id: Id<Block>,
+        block_height: BlockHeight,
+        expected_hash: H256,
+        checkpoint: chainstate_storage::UtxoSnapshotValidationEntries,
+        validation_storage: Box<dyn UtxoSnapshotValidationStorage>,
+    ) -> Result<Self, ChainstateError> {
+        // Only the state is needed at the end, so the old blocks are pruned to save memory
+        let chainstate_config = ChainstateConfig::new()
+            .with_prune_mode(PruneMode::Depth(MIN_BLOCKS_TO_KEEP_WHEN_PRUNING));
+        let (backend, journal) = chainstate_storage::inmemory::InMemory::new_journaled();
+        let storage = if checkpoint.is_empty() {
+            chainstate_storage::inmemory::Store::new(backend).map_err(BlockError::from)?
+        } else {
+            let storage =
+                chainstate_storage::inmemory::Store::from_raw_entries(backend, checkpoint)
+                    .map_err(BlockError::from)?;
+            // The restored entries are already saved
+            let _ = journal.take();
+            storage
+        };
+        let chainstate = Chainstate::new(
+            chain_config,
+            chainstate_config,
+            storage,
+            DefaultTransactionVerificationStrategy::new(),
+            None,
+            time_getter,
+        )?;
+
+        let validated_height = best_block_height(&chainstate)?;
+        let progress = Arc::new(Mutex::new(ValidationProgress {
+            validated_height,
+            queued_height: validated_height,
+            outcome: None,
+        }));
+
+        log::info!(
+            "Validating the history of the utxo snapshot at block {} with height {}",
+            block_id,
+            block_height
+        );
+
+        let (block_sender, block_receiver) = mpsc::channel();
+        let worker = ValidationWorker {
+            block_id,
+            expected_hash,
+            chainstate,
+            journal,
+            validation_storage,
+            progress: Arc::clone(&progress),
+            blocks_since_checkpoint: 0,
+        };
+        let thread = std::thread::Builder::new()
+            .name("utxo-snapshot-validator".into())
+            .spawn(move || worker.run(block_receiver))
+            .map_err(|e| UtxoSnapshotError::ValidationThread(e.to_string()))?;
+
+        Ok(Self {
+            block_height,
+            progress,
+            block_sender: Some(block_sender),
+            thread: Some(thread),
+        })
+    }
+
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    /// The height after which the blocks of the history are still needed
+    pub fn queued_height(&self) -> BlockHeight {
+        self.progress.lock().expect("lock to be alive").queued_height
+    }
+
+    /// How the validation has ended, `None` while it's still in progress
+    pub fn outcome(&self) -> Option<UtxoSnapshotHistoryOutcome> {
+        self.progress.lock().expect("lock to be alive").outcome
+    }
+
+    /// Queue a block from the history of the snapshot to be validated
+    pub fn submit_block(
+        &self,
+        block: WithId<Block>,
+        block_height: BlockHeight,
+        source: BlockSource,
+    ) {
+        {
+            let mut progress = self.progress.lock().expect("lock to be alive");
+            progress.queued_height = std::cmp::max(progress.queued_height, block_height);
+        }
+        let sender = self.block_sender.as_ref().expect("Only taken on drop");
+        if sender.send((block, source)).is_err() {
+            log::warn!("The utxo snapshot history validation thread has stopped");
+        }
+    }
+}
+
+impl Drop for UtxoSnapshotHistoryValidator {
+    fn drop(&mut self) {
+        // Closing the channel makes the thread save a checkpoint and exit
+        self.block_sender = None;
+        if let Some(thread) = self.thread.take() {
+            if thread.join().is_err() {
+                log::error!("The utxo snapshot history validation thread panicked");
+            }
+        }
+    }
+}
+
+fn best_block_height(chainstate: &HistoryChainstate) -> Result<BlockHeight, PropertyQueryError> {
+    let best_block_index = chainstate
+        .query()?
+        .get_best_block_index()?
+        .ok_or(PropertyQueryError::BestBlockIndexNotFound)?;
+    Ok(best_block_index.block_height())
+}
+
+struct ValidationWorker {
+    block_id: Id<Block>,
+    expected_hash: H256,
+    chainstate: HistoryChainstate,
+    journal: chainstate_storage::inmemory::JournalHandle,
+    validation_storage: Box<dyn UtxoSnapshotValidationStorage>,
+    progress: Arc<Mutex<ValidationProgress>>,
+    blocks_since_checkpoint: usize,
+}
+
+impl ValidationWorker {
+    fn run(mut self, block_receiver: mpsc::Receiver<(WithId<Block>, BlockSource)>) {
+        while let Ok((block, source)) = block_receiver.recv() {
+            match self.process_block(block, source) {
+                Ok(None) => {}
+                Ok(Some(outcome)) => {
+                    self.progress.lock().expect("lock to be alive").outcome = Some(outcome);
+                    return;
+                }
+                Err(e) => {
+                    log::error!("Failed to validate a block of the utxo snapshot history: {e}");
+                    // The blocks after the last validated one are requested again
+                    let mut progress = self.progress.lock().expect("lock to be alive");
+                    progress.queued_height = progress.validated_height;
+                }
+            }
+        }
+
+        if let Err(e) = self.save_checkpoint() {
+            log::error!("Failed to save the utxo snapshot history validation checkpoint: {e}");
+        }
+    }
+
+    /// Connect a block from the history of the snapshot.
+    /// Returns the outcome once the whole history up to the snapshot block is validated.
+    fn process_block(
+        &mut self,
+        block: WithId<Block>,
+        source: BlockSource,
+    ) -> Result<Option<UtxoSnapshotHistoryOutcome>, BlockError> {
+        self.chainstate.process_block(block, source)?;
+        self.blocks_since_checkpoint += 1;
+
+        let validated_height =
+            best_block_height(&self.chainstate).map_err(BlockError::BestBlockLoadError)?;
+        self.progress.lock().expect("lock to be alive").validated_height = validated_height;
+
+        let chainstate_ref = self.chainstate.make_db_tx_ro()?;
+        let best_block_id =
+            chainstate_ref.get_best_block_id().map_err(BlockError::BestBlockLoadError)?;
+        if best_block_id != Id::<GenBlock>::from(self.block_id) {
+            drop(chainstate_ref);
+            if self.blocks_since_checkpoint >= VALIDATION_CHECKPOINT_INTERVAL {
+                self.save_checkpoint()?;
+            }
+            return Ok(None);
+        }
+
+        let content_hash = chainstate_ref
+            .create_utxo_snapshot()
+            .map_err(BlockError::BestBlockLoadError)?
+            .content_hash();
+        if content_hash != self.expected_hash {
+            log::error!(
+                "CRITICAL: {}, the node stops processing blocks",
+                BlockError::UtxoSnapshotHistoryMismatch(
+                    self.block_id,
+                    content_hash,
+                    self.expected_hash
+                )
+            );
+            self.validation_storage.invalidate_utxo_snapshot(&self.block_id)?;
+            return Ok(Some(UtxoSnapshotHistoryOutcome::Invalid(self.block_id)));
+        }
+
+        log::info!(
+            "The history of the utxo snapshot at block {} is valid",
+            self.block_id
+        );
+        self.validation_storage.finish_utxo_snapshot_validation()?;
+        Ok(Some(UtxoSnapshotHistoryOutcome::Valid))
+    }
+
+    /// Save the entries of the in-memory chainstate changed since the last checkpoint
+    fn save_checkpoint(&mut self) -> Result<(), BlockError> {
+        let changes: Vec<_> = self
+            .journal
+            .take()
+            .into_iter()
+            .map(|((idx, key), value)| ((idx.get() as u32, key), value))
+            .collect();
+        self.validation_storage.save_utxo_snapshot_validation_changes(&changes)?;
+        self.blocks_since_checkpoint = 0;
+        Ok(())
+    }
+}
@@ -13,23 +13,31 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::detail::BlockSource;
-use crate::ChainstateConfig;
-use chainstate_types::{BlockIndex, GenBlockIndex};
-use common::chain::tokens::TokenAuxiliaryData;
+use crate::{ChainInconsistency, ChainstateConfig};
+use chainstate_types::{
+    AddressHistoryEntry, BlockIndex, ChainTip, GenBlockIndex, OutputSpendInfo, UtxoSetInfo,
+};
+use common::chain::tokens::{CoinOrTokenId, TokenAuxiliaryData};
 use common::chain::TxInput;
 use common::chain::{
-    block::{timestamp::BlockTimestamp, Block, BlockHeader, BlockReward, GenBlock},
+    block::{
+        block_filter::{BlockFilter, BlockFilterHeader},
+        timestamp::BlockTimestamp,
+        tx_inclusion_proof::TxInclusionProof,
+        Block, BlockHeader, BlockReward, GenBlock,
+    },
     tokens::{RPCTokenInfo, TokenId},
     ChainConfig, OutPointSourceId, TxMainChainIndex,
 };
-use common::chain::{OutPoint, Transaction};
-use common::primitives::{Amount, BlockHeight, Id};
+use common::chain::{Destination, OutPoint, Transaction};
+use common::primitives::{Amount, BlockHeight, Id, H256};
 use utils::eventhandler::EventHandler;
 
 use crate::{ChainstateError, ChainstateEvent};
+use chainstate_storage::BlockchainStorageBackup;
 use chainstate_types::Locator;
 use utxo::Utxo;
 
@@ -42,6 +50,12 @@ pub trait ChainstateInterface: Send {
     ) -> Result<Option<BlockIndex>, ChainstateError>;
     fn preliminary_block_check(&self, block: Block) -> Result<Block, ChainstateError>;
     fn preliminary_header_check(&self, header: BlockHeader) -> Result<(), ChainstateError>;
+    /// Validates a header and adds it to a headers-only chainstate; returns the new tip if the
+    /// header chain with the most trust has changed
+    fn process_header(
+        &mut self,
+        header: BlockHeader,
+    ) -> Result<Option<BlockIndex>, ChainstateError>;
     fn get_best_block_id(&self) -> Result<Id<GenBlock>, ChainstateError>;
     fn is_block_in_main_chain(&self, block_id: &Id<Block>) -> Result<bool, ChainstateError>;
     fn get_block_height_in_main_chain(
@@ -140,23 +154,113 @@ pub trait ChainstateInterface: Send {
     /// Returns a list of all blocks in the block tree, including orphans. The length cannot be predicted before the call
     fn get_block_id_tree_as_list(&self) -> Result<Vec<Id<Block>>, ChainstateError>;
 
+    /// Returns the leaves of the block tree, including the tip of the main chain
+    fn get_chain_tips(&self) -> Result<Vec<ChainTip>, ChainstateError>;
+
+    /// Returns the height of the last main chain block that was pruned, if any
+    fn get_pruned_height(&self) -> Result<Option<BlockHeight>, ChainstateError>;
+
+    /// Returns the summary of the utxo set at the tip of the main chain, along with its hash
+    fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, ChainstateError>;
+
+    /// Returns the total amounts of coins and tokens in the unspent outputs of the destination,
+    /// requires the address index
+    fn get_address_balance(
+        &self,
+        destination: &Destination,
+    ) -> Result<BTreeMap<CoinOrTokenId, Amount>, ChainstateError>;
+
+    /// Returns all the outputs sent to the destination in the main chain along with their
+    /// spending, oldest first; requires the address index
+    fn get_address_history(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, ChainstateError>;
+
+    /// Returns the input that spent the output in the main chain, if it is spent;
+    /// requires the spent-output index
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, ChainstateError>;
+
+    /// Returns the compact filter of a main chain block; requires block filters
+    fn get_block_filter(&self, block_id: Id<Block>)
+        -> Result<Option<BlockFilter>, ChainstateError>;
+
+    /// Returns the filter header of a main chain block; requires block filters
+    fn get_block_filter_header(
+        &self,
+        block_id: Id<Block>,
+    ) -> Result<Option<BlockFilterHeader>, ChainstateError>;
+
+    /// Returns the merkle proof that a transaction is in a block, None if the block data is not
+    /// available or the transaction is not in the block
+    fn get_tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: Id<Transaction>,
+    ) -> Result<Option<TxInclusionProof>, ChainstateError>;
+
     /// Imports a bootstrap file exported with export_bootstrap_stream
     fn import_bootstrap_stream<'a>(
         &mut self,
         reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
     ) -> Result<(), ChainstateError>;
 
-    /// Writes the blocks of the blockchain into a stream that's meant to go to a file.
-    /// The blocks in the stream can be used to resync the blockchain in another node
+    /// Writes the blocks of the blockchain with heights in the given range into a stream that's
+    /// meant to go to a file. The blocks in the stream can be used to resync the blockchain in
+    /// another node
     fn export_bootstrap_stream<'a>(
         &self,
         writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
         include_orphans: bool,
+        heights: std::ops::RangeInclusive<BlockHeight>,
+    ) -> Result<(), ChainstateError>;
+
+    /// Writes the utxo set, the accounting data and the token data at the current tip into a
+    /// stream that's meant to go to a file. Returns the content hash of the snapshot,
+    /// which has to be pinned in the chain config of the nodes that load it
+    fn export_utxo_snapshot_stream<'a>(
+        &self,
+        writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
+    ) -> Result<H256, ChainstateError>;
+
+    /// Loads a utxo snapshot exported with export_utxo_snapshot_stream into a chainstate
+    /// without blocks. The history of the snapshot is validated as its blocks are processed
+    fn import_utxo_snapshot_stream<'a>(
+        &mut self,
+        reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
     ) -> Result<(), ChainstateError>;
 
+    /// Returns the headers of the next blocks needed to validate the history of the loaded utxo
+    /// snapshot, at most `max_count` of them. Empty if no history is being validated
+    fn get_utxo_snapshot_history_headers(
+        &self,
+        max_count: usize,
+    ) -> Result<Vec<BlockHeader>, ChainstateError>;
+
     /// Returns the UTXO for a specified OutPoint
     fn utxo(&self, outpoint: &OutPoint) -> Result<Option<Utxo>, ChainstateError>;
 
     /// Returns true if the initial block download isn't finished yet.
     fn is_initial_block_download(&self) -> Result<bool, ChainstateError>;
+
+    /// Marks the block and all of its descendants as invalid, disconnecting them from
+    /// the main chain if necessary
+    fn invalidate_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError>;
+
+    /// Removes the invalid status from the block, its ancestors and descendants, allowing them
+    /// to become a part of the main chain again
+    fn reconsider_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError>;
+
+    /// Disconnect and reconnect the last `depth` blocks of the main chain in memory, and compare
+    /// the rebuilt utxo set and PoS accounting data with the stored ones. At most
+    /// `MAX_VERIFY_CHAIN_DEPTH` blocks can be verified. Returns the first inconsistency found,
+    /// if any.
+    fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, ChainstateError>;
+
+    /// Get a handle to the chainstate storage for writing backups. The backup is written from
+    /// a read-only transaction, so the chainstate keeps working while it is written.
+    fn storage_backup_handle(&self) -> Box<dyn BlockchainStorageBackup>;
 }
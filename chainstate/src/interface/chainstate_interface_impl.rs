@@ -13,19 +13,26 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
 use crate::detail::bootstrap::export_bootstrap_stream;
 use crate::detail::bootstrap::import_bootstrap_stream;
 use crate::detail::calculate_median_time_past;
 use crate::detail::tx_verification_strategy::TransactionVerificationStrategy;
-use chainstate_storage::BlockchainStorage;
-use chainstate_types::{BlockIndex, GenBlockIndex};
-use common::chain::block::BlockReward;
+use crate::detail::utxo_snapshot::{export_utxo_snapshot_stream, import_utxo_snapshot_stream};
+use chainstate_storage::{BlockchainStorage, BlockchainStorageBackup};
+use chainstate_types::{
+    AddressHistoryEntry, BlockIndex, ChainTip, GenBlockIndex, OutputSpendInfo, UtxoSetInfo,
+};
+use common::chain::block::{
+    block_filter::{BlockFilter, BlockFilterHeader},
+    tx_inclusion_proof::TxInclusionProof,
+    BlockReward,
+};
 use common::chain::config::ChainConfig;
 use common::chain::tokens::OutputValue;
-use common::chain::tokens::TokenAuxiliaryData;
-use common::chain::{OutPoint, TxInput};
+use common::chain::tokens::{CoinOrTokenId, TokenAuxiliaryData};
+use common::chain::{Destination, OutPoint, TxInput};
 use common::chain::{OutPointSourceId, Transaction, TxMainChainIndex};
 use common::primitives::Amount;
 
@@ -35,16 +42,16 @@ use common::{
         block::{Block, BlockHeader, GenBlock},
         tokens::{RPCTokenInfo, TokenId},
     },
-    primitives::{id::WithId, BlockHeight, Id},
+    primitives::{id::WithId, BlockHeight, Id, H256},
 };
 use utils::eventhandler::EventHandler;
 use utxo::{Utxo, UtxosView};
 
-use crate::ChainstateConfig;
 use crate::{
     detail::{self, BlockSource},
     ChainstateError, ChainstateEvent, ChainstateInterface, Locator,
 };
+use crate::{ChainInconsistency, ChainstateConfig};
 
 pub struct ChainstateInterfaceImpl<S, V> {
     chainstate: detail::Chainstate<S, V>,
@@ -79,6 +86,15 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
             .map_err(ChainstateError::ProcessBlockError)
     }
 
+    fn process_header(
+        &mut self,
+        header: BlockHeader,
+    ) -> Result<Option<BlockIndex>, ChainstateError> {
+        self.chainstate
+            .process_header(header)
+            .map_err(ChainstateError::ProcessBlockError)
+    }
+
     fn preliminary_block_check(&self, block: Block) -> Result<Block, ChainstateError> {
         let block = self
             .chainstate
@@ -99,9 +115,8 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
         self.chainstate
             .query()
             .map_err(ChainstateError::from)?
-            .get_block_height_in_main_chain(&(*block_id).into())
+            .is_block_in_main_chain(&(*block_id).into())
             .map_err(ChainstateError::FailedToReadProperty)
-            .map(|ht| ht.is_some())
     }
 
     fn get_block_height_in_main_chain(
@@ -385,6 +400,97 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
             .map_err(ChainstateError::FailedToReadProperty)
     }
 
+    fn get_chain_tips(&self) -> Result<Vec<ChainTip>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_chain_tips()
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_pruned_height(&self) -> Result<Option<BlockHeight>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_pruned_height()
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_utxo_set_info()
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_address_balance(
+        &self,
+        destination: &Destination,
+    ) -> Result<BTreeMap<CoinOrTokenId, Amount>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_address_balance(destination)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_address_history(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_address_history(destination)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_output_spend_info(outpoint)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_block_filter(
+        &self,
+        block_id: Id<Block>,
+    ) -> Result<Option<BlockFilter>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_block_filter(&block_id)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_block_filter_header(
+        &self,
+        block_id: Id<Block>,
+    ) -> Result<Option<BlockFilterHeader>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_block_filter_header(&block_id)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
+    fn get_tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: Id<Transaction>,
+    ) -> Result<Option<TxInclusionProof>, ChainstateError> {
+        self.chainstate
+            .query()
+            .map_err(ChainstateError::from)?
+            .get_tx_inclusion_proof(block_id, &tx_id)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
     fn import_bootstrap_stream<'a>(
         &mut self,
         reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
@@ -413,6 +519,7 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
         &self,
         writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
         include_orphans: bool,
+        heights: std::ops::RangeInclusive<BlockHeight>,
     ) -> Result<(), ChainstateError> {
         let magic_bytes = self.chainstate.chain_config().magic_bytes();
         let mut writer = writer;
@@ -420,11 +527,45 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
             magic_bytes,
             &mut writer,
             include_orphans,
+            heights,
             &self.chainstate.query().map_err(ChainstateError::from)?,
         )?;
         Ok(())
     }
 
+    fn export_utxo_snapshot_stream<'a>(
+        &self,
+        writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
+    ) -> Result<H256, ChainstateError> {
+        let magic_bytes = self.chainstate.chain_config().magic_bytes();
+        let snapshot = self
+            .chainstate
+            .make_db_tx_ro()
+            .map_err(PropertyQueryError::from)?
+            .create_utxo_snapshot()?;
+        let mut writer = writer;
+        let content_hash = export_utxo_snapshot_stream(magic_bytes, &mut writer, &snapshot)?;
+        Ok(content_hash)
+    }
+
+    fn import_utxo_snapshot_stream<'a>(
+        &mut self,
+        reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
+    ) -> Result<(), ChainstateError> {
+        let mut reader = reader;
+        let snapshot = import_utxo_snapshot_stream(self.chainstate.chain_config(), &mut reader)?;
+        self.chainstate.import_utxo_snapshot(&snapshot)
+    }
+
+    fn get_utxo_snapshot_history_headers(
+        &self,
+        max_count: usize,
+    ) -> Result<Vec<BlockHeader>, ChainstateError> {
+        self.chainstate
+            .get_utxo_snapshot_history_headers(max_count)
+            .map_err(ChainstateError::FailedToReadProperty)
+    }
+
     fn utxo(&self, outpoint: &OutPoint) -> Result<Option<Utxo>, ChainstateError> {
         let chainstate_ref = self
             .chainstate
@@ -437,4 +578,24 @@ impl<S: BlockchainStorage, V: TransactionVerificationStrategy> ChainstateInterfa
     fn is_initial_block_download(&self) -> Result<bool, ChainstateError> {
         self.chainstate.is_initial_block_download().map_err(ChainstateError::from)
     }
+
+    fn invalidate_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError> {
+        self.chainstate
+            .invalidate_block(block_id)
+            .map_err(ChainstateError::ProcessBlockError)
+    }
+
+    fn reconsider_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError> {
+        self.chainstate
+            .reconsider_block(block_id)
+            .map_err(ChainstateError::ProcessBlockError)
+    }
+
+    fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, ChainstateError> {
+        self.chainstate.verify_chain(depth).map_err(ChainstateError::ProcessBlockError)
+    }
+
+    fn storage_backup_handle(&self) -> Box<dyn BlockchainStorageBackup> {
+        self.chainstate.storage_backup_handle()
+    }
 }
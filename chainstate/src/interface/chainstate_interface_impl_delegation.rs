@@ -14,35 +14,44 @@
 // limitations under the License.
 
 use std::{
+    collections::BTreeMap,
     ops::{Deref, DerefMut},
     sync::Arc,
 };
 
+use chainstate_storage::BlockchainStorageBackup;
 use chainstate_types::Locator;
-use chainstate_types::{BlockIndex, GenBlockIndex};
+use chainstate_types::{
+    AddressHistoryEntry, BlockIndex, ChainTip, GenBlockIndex, OutputSpendInfo, UtxoSetInfo,
+};
 use common::chain::TxInput;
 use common::chain::{
-    block::{timestamp::BlockTimestamp, BlockReward},
+    block::{
+        block_filter::{BlockFilter, BlockFilterHeader},
+        timestamp::BlockTimestamp,
+        tx_inclusion_proof::TxInclusionProof,
+        BlockReward,
+    },
     config::ChainConfig,
     tokens::TokenAuxiliaryData,
     OutPointSourceId, TxMainChainIndex,
 };
-use common::chain::{OutPoint, Transaction};
+use common::chain::{tokens::CoinOrTokenId, Destination, OutPoint, Transaction};
 use common::{
     chain::{
         block::BlockHeader,
         tokens::{RPCTokenInfo, TokenId},
         Block, GenBlock,
     },
-    primitives::{BlockHeight, Id},
+    primitives::{Amount, BlockHeight, Id, H256},
 };
 use utils::eventhandler::EventHandler;
 use utxo::Utxo;
 
-use crate::ChainstateConfig;
 use crate::{
     chainstate_interface::ChainstateInterface, BlockSource, ChainstateError, ChainstateEvent,
 };
+use crate::{ChainInconsistency, ChainstateConfig};
 
 impl<
         T: Deref<Target = dyn ChainstateInterface> + DerefMut<Target = dyn ChainstateInterface> + Send,
@@ -68,6 +77,13 @@ impl<
         self.deref().preliminary_header_check(header)
     }
 
+    fn process_header(
+        &mut self,
+        header: BlockHeader,
+    ) -> Result<Option<BlockIndex>, ChainstateError> {
+        self.deref_mut().process_header(header)
+    }
+
     fn get_best_block_id(&self) -> Result<Id<GenBlock>, ChainstateError> {
         self.deref().get_best_block_id()
     }
@@ -235,6 +251,61 @@ impl<
         self.deref().get_block_id_tree_as_list()
     }
 
+    fn get_chain_tips(&self) -> Result<Vec<ChainTip>, ChainstateError> {
+        self.deref().get_chain_tips()
+    }
+
+    fn get_pruned_height(&self) -> Result<Option<BlockHeight>, ChainstateError> {
+        self.deref().get_pruned_height()
+    }
+
+    fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, ChainstateError> {
+        self.deref().get_utxo_set_info()
+    }
+
+    fn get_address_balance(
+        &self,
+        destination: &Destination,
+    ) -> Result<BTreeMap<CoinOrTokenId, Amount>, ChainstateError> {
+        self.deref().get_address_balance(destination)
+    }
+
+    fn get_address_history(
+        &self,
+        destination: &Destination,
+    ) -> Result<Vec<AddressHistoryEntry>, ChainstateError> {
+        self.deref().get_address_history(destination)
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, ChainstateError> {
+        self.deref().get_output_spend_info(outpoint)
+    }
+
+    fn get_block_filter(
+        &self,
+        block_id: Id<Block>,
+    ) -> Result<Option<BlockFilter>, ChainstateError> {
+        self.deref().get_block_filter(block_id)
+    }
+
+    fn get_block_filter_header(
+        &self,
+        block_id: Id<Block>,
+    ) -> Result<Option<BlockFilterHeader>, ChainstateError> {
+        self.deref().get_block_filter_header(block_id)
+    }
+
+    fn get_tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: Id<Transaction>,
+    ) -> Result<Option<TxInclusionProof>, ChainstateError> {
+        self.deref().get_tx_inclusion_proof(block_id, tx_id)
+    }
+
     fn import_bootstrap_stream<'a>(
         &mut self,
         reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
@@ -246,8 +317,30 @@ impl<
         &self,
         writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
         include_orphans: bool,
+        heights: std::ops::RangeInclusive<BlockHeight>,
     ) -> Result<(), ChainstateError> {
-        self.deref().export_bootstrap_stream(writer, include_orphans)
+        self.deref().export_bootstrap_stream(writer, include_orphans, heights)
+    }
+
+    fn export_utxo_snapshot_stream<'a>(
+        &self,
+        writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
+    ) -> Result<H256, ChainstateError> {
+        self.deref().export_utxo_snapshot_stream(writer)
+    }
+
+    fn import_utxo_snapshot_stream<'a>(
+        &mut self,
+        reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
+    ) -> Result<(), ChainstateError> {
+        self.deref_mut().import_utxo_snapshot_stream(reader)
+    }
+
+    fn get_utxo_snapshot_history_headers(
+        &self,
+        max_count: usize,
+    ) -> Result<Vec<BlockHeader>, ChainstateError> {
+        self.deref().get_utxo_snapshot_history_headers(max_count)
     }
 
     fn utxo(&self, outpoint: &OutPoint) -> Result<Option<Utxo>, ChainstateError> {
@@ -257,6 +350,22 @@ impl<
     fn is_initial_block_download(&self) -> Result<bool, ChainstateError> {
         self.deref().is_initial_block_download()
     }
+
+    fn invalidate_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError> {
+        self.deref_mut().invalidate_block(block_id)
+    }
+
+    fn reconsider_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError> {
+        self.deref_mut().reconsider_block(block_id)
+    }
+
+    fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, ChainstateError> {
+        self.deref().verify_chain(depth)
+    }
+
+    fn storage_backup_handle(&self) -> Box<dyn BlockchainStorageBackup> {
+        self.deref().storage_backup_handle()
+    }
 }
 
 #[cfg(test)]
@@ -307,7 +416,14 @@ mod tests {
                 max_orphan_blocks: 0.into(),
                 min_max_bootstrap_import_buffer_sizes: Default::default(),
                 tx_index_enabled: Default::default(),
+                address_index_enabled: Default::default(),
+                spent_index_enabled: Default::default(),
+                block_filters_enabled: Default::default(),
+                headers_only: Default::default(),
                 max_tip_age: Default::default(),
+                prune_mode: Default::default(),
+                assumed_valid_block: Default::default(),
+                reindex_chainstate: Default::default(),
             };
             let chainstate_storage = Store::new_empty().unwrap();
 
@@ -13,29 +13,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::sync::Arc;
+use std::{collections::BTreeMap, sync::Arc};
 
-use common::chain::block::BlockReward;
-use common::chain::OutPoint;
+use common::chain::block::{
+    block_filter::{BlockFilter, BlockFilterHeader},
+    tx_inclusion_proof::TxInclusionProof,
+    BlockReward,
+};
 use common::chain::OutPointSourceId;
 use common::chain::Transaction;
 use common::chain::TxInput;
 use common::chain::TxMainChainIndex;
+use common::chain::{Destination, OutPoint};
 use common::primitives::Amount;
 use common::{
     chain::{
         block::{Block, BlockHeader, GenBlock},
         tokens::{RPCTokenInfo, TokenId},
     },
-    primitives::{BlockHeight, Id},
+    primitives::{BlockHeight, Id, H256},
 };
 
-use crate::ChainstateConfig;
 use crate::{detail::BlockSource, ChainstateError, ChainstateEvent, Locator};
+use crate::{ChainInconsistency, ChainstateConfig};
+use chainstate_storage::BlockchainStorageBackup;
 use chainstate_types::BlockIndex;
 use chainstate_types::GenBlockIndex;
+use chainstate_types::{AddressHistoryEntry, ChainTip, OutputSpendInfo, UtxoSetInfo};
 use common::chain::block::timestamp::BlockTimestamp;
-use common::chain::tokens::TokenAuxiliaryData;
+use common::chain::tokens::{CoinOrTokenId, TokenAuxiliaryData};
 use common::chain::ChainConfig;
 use utils::eventhandler::EventHandler;
 use utxo::Utxo;
@@ -50,6 +56,7 @@ mockall::mock! {
         fn process_block(&mut self, block: Block, source: BlockSource) -> Result<Option<BlockIndex>, ChainstateError>;
         fn preliminary_block_check(&self, block: Block) -> Result<Block, ChainstateError>;
         fn preliminary_header_check(&self, header: BlockHeader) -> Result<(), ChainstateError>;
+        fn process_header(&mut self, header: BlockHeader) -> Result<Option<BlockIndex>, ChainstateError>;
         fn get_best_block_id(&self) -> Result<Id<GenBlock>, ChainstateError>;
         fn get_best_block_height(&self) -> Result<BlockHeight, ChainstateError>;
         fn get_best_block_header(&self) -> Result<BlockHeader, ChainstateError>;
@@ -122,6 +129,31 @@ mockall::mock! {
         ) -> Result<Vec<Option<Amount>>, ChainstateError>;
         fn get_mainchain_blocks_list(&self) -> Result<Vec<Id<Block>>, ChainstateError>;
         fn get_block_id_tree_as_list(&self) -> Result<Vec<Id<Block>>, ChainstateError>;
+        fn get_chain_tips(&self) -> Result<Vec<ChainTip>, ChainstateError>;
+        fn get_pruned_height(&self) -> Result<Option<BlockHeight>, ChainstateError>;
+        fn get_utxo_set_info(&self) -> Result<UtxoSetInfo, ChainstateError>;
+        fn get_address_balance(
+            &self,
+            destination: &Destination,
+        ) -> Result<BTreeMap<CoinOrTokenId, Amount>, ChainstateError>;
+        fn get_address_history(
+            &self,
+            destination: &Destination,
+        ) -> Result<Vec<AddressHistoryEntry>, ChainstateError>;
+        fn get_output_spend_info(
+            &self,
+            outpoint: &OutPoint,
+        ) -> Result<Option<OutputSpendInfo>, ChainstateError>;
+        fn get_block_filter(&self, block_id: Id<Block>) -> Result<Option<BlockFilter>, ChainstateError>;
+        fn get_block_filter_header(
+            &self,
+            block_id: Id<Block>,
+        ) -> Result<Option<BlockFilterHeader>, ChainstateError>;
+        fn get_tx_inclusion_proof(
+            &self,
+            block_id: Id<Block>,
+            tx_id: Id<Transaction>,
+        ) -> Result<Option<TxInclusionProof>, ChainstateError>;
         fn import_bootstrap_stream<'a>(
             &'a mut self,
             reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
@@ -130,8 +162,25 @@ mockall::mock! {
             &'a self,
             writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
             include_orphans: bool,
+            heights: std::ops::RangeInclusive<BlockHeight>,
         ) -> Result<(), ChainstateError>;
+        fn export_utxo_snapshot_stream<'a>(
+            &'a self,
+            writer: std::io::BufWriter<Box<dyn std::io::Write + Send + 'a>>,
+        ) -> Result<H256, ChainstateError>;
+        fn import_utxo_snapshot_stream<'a>(
+            &'a mut self,
+            reader: std::io::BufReader<Box<dyn std::io::Read + Send + 'a>>,
+        ) -> Result<(), ChainstateError>;
+        fn get_utxo_snapshot_history_headers(
+            &self,
+            max_count: usize,
+        ) -> Result<Vec<BlockHeader>, ChainstateError>;
         fn utxo(&self, outpoint: &OutPoint) -> Result<Option<Utxo>, ChainstateError>;
         fn is_initial_block_download(&self) -> Result<bool, ChainstateError>;
+        fn invalidate_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError>;
+        fn reconsider_block(&mut self, block_id: &Id<Block>) -> Result<(), ChainstateError>;
+        fn verify_chain(&self, depth: usize) -> Result<Option<ChainInconsistency>, ChainstateError>;
+        fn storage_backup_handle(&self) -> Box<dyn BlockchainStorageBackup>;
     }
 }
@@ -14,8 +14,9 @@
 // limitations under the License.
 
 mod interface;
-use detail::bootstrap::BootstrapError;
+pub use detail::bootstrap::{BootstrapError, BOOTSTRAP_FORMAT_VERSION};
 pub use detail::tx_verification_strategy::*;
+pub use detail::utxo_snapshot::{UtxoSnapshotError, UTXO_SNAPSHOT_FORMAT_VERSION};
 pub use interface::chainstate_interface;
 use interface::chainstate_interface_impl;
 pub use interface::chainstate_interface_impl_delegation;
@@ -23,12 +24,13 @@ pub use interface::chainstate_interface_impl_delegation;
 pub mod rpc;
 
 pub use crate::{
-    config::ChainstateConfig,
+    config::{ChainstateConfig, PruneMode, MIN_BLOCKS_TO_KEEP_WHEN_PRUNING},
     detail::{
-        ban_score, calculate_median_time_past, is_rfc3986_valid_symbol, BlockError, BlockSource,
-        CheckBlockError, CheckBlockTransactionsError, ConnectTransactionError, InitializationError,
-        Locator, OrphanCheckError, TokensError, TransactionVerifierStorageError, TxIndexError,
-        HEADER_LIMIT,
+        ban_score, calculate_median_time_past, check_timelock, is_rfc3986_valid_symbol,
+        AddressIndexError, BlockError, BlockSource, ChainInconsistency, CheckBlockError,
+        CheckBlockTransactionsError, ConnectTransactionError, InitializationError, Locator,
+        OrphanCheckError, SpentIndexError, TokensError, TransactionVerifierStorageError,
+        TxIndexError, HEADER_LIMIT, MAX_VERIFY_CHAIN_DEPTH,
     },
 };
 
@@ -51,6 +53,11 @@ use detail::Chainstate;
 #[derive(Debug, Clone)]
 pub enum ChainstateEvent {
     NewTip(Id<Block>, BlockHeight),
+    /// A block joined the main chain. Sent before the `NewTip` event of the new tip.
+    BlockConnected(Arc<Block>, BlockHeight),
+    /// A block left the main chain, e.g. in a reorg. The blocks are disconnected from the tip
+    /// down, before the blocks of the new chain are connected.
+    BlockDisconnected(Arc<Block>, BlockHeight),
 }
 
 #[derive(thiserror::Error, Debug, PartialEq, Eq)]
@@ -63,6 +70,8 @@ pub enum ChainstateError {
     FailedToReadProperty(#[from] PropertyQueryError),
     #[error("Block import error {0}")]
     BootstrapError(#[from] BootstrapError),
+    #[error("Utxo snapshot error: {0}")]
+    UtxoSnapshotError(#[from] UtxoSnapshotError),
 }
 
 impl subsystem::Subsystem for Box<dyn ChainstateInterface> {}
@@ -15,16 +15,163 @@
 
 //! Chainstate subsystem RPC handler
 
-use std::io::{Read, Write};
+use std::{
+    collections::BTreeMap,
+    io::{Read, Write},
+};
 
 use crate::{Block, BlockSource, ChainstateError, GenBlock};
+use chainstate_types::{AddressHistoryEntry, ChainTip, OutputSpendInfo, UtxoSetInfo};
 use common::{
-    chain::tokens::{RPCTokenInfo, TokenId},
-    primitives::{BlockHeight, Id},
+    chain::{
+        tokens::{CoinOrTokenId, RPCTokenInfo, TokenId},
+        Destination, OutPoint, OutPointSourceId, Spender, Transaction,
+    },
+    primitives::{Amount, BlockHeight, Id, H256},
 };
 use serialization::{Decode, Encode};
 use subsystem::subsystem::CallError;
 
+/// A leaf of the block tree, as reported by the `chain_tips` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcChainTip {
+    pub block_id: Id<GenBlock>,
+    pub height: BlockHeight,
+    /// Number of blocks between the tip and the main chain, zero for the active tip
+    pub branch_length: i64,
+    /// Big-endian hex encoding of the chain trust
+    pub chain_trust: String,
+    /// One of `active`, `valid-fork`, `headers-only` or `invalid`
+    pub status: String,
+}
+
+impl From<ChainTip> for RpcChainTip {
+    fn from(tip: ChainTip) -> Self {
+        Self {
+            block_id: tip.block_id,
+            height: tip.height,
+            branch_length: tip.branch_length.into(),
+            chain_trust: hex::encode(tip.chain_trust.to_be_bytes()),
+            status: tip.status.to_string(),
+        }
+    }
+}
+
+/// Summary of the utxo set, as reported by the `utxo_set_info` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcUtxoSetInfo {
+    pub best_block_id: Id<GenBlock>,
+    pub best_block_height: BlockHeight,
+    pub utxo_count: u64,
+    pub coin_amount: Amount,
+    pub token_amounts: BTreeMap<TokenId, Amount>,
+    /// Rolling hash of the utxo set, compare it to check that two nodes have the same utxos
+    pub set_hash: H256,
+}
+
+impl From<UtxoSetInfo> for RpcUtxoSetInfo {
+    fn from(info: UtxoSetInfo) -> Self {
+        Self {
+            best_block_id: info.best_block_id,
+            best_block_height: info.best_block_height,
+            utxo_count: info.utxo_count,
+            coin_amount: info.coin_amount,
+            token_amounts: info.token_amounts,
+            set_hash: info.set_hash,
+        }
+    }
+}
+
+/// Unspent amounts of an address, as reported by the `address_balance` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcAddressBalance {
+    pub coin_amount: Amount,
+    pub token_amounts: BTreeMap<TokenId, Amount>,
+}
+
+impl From<BTreeMap<CoinOrTokenId, Amount>> for RpcAddressBalance {
+    fn from(balance: BTreeMap<CoinOrTokenId, Amount>) -> Self {
+        let mut coin_amount = Amount::ZERO;
+        let mut token_amounts = BTreeMap::new();
+        for (coin_or_token_id, amount) in balance {
+            match coin_or_token_id {
+                CoinOrTokenId::Coin => coin_amount = amount,
+                CoinOrTokenId::TokenId(token_id) => {
+                    token_amounts.insert(token_id, amount);
+                }
+            }
+        }
+        Self {
+            coin_amount,
+            token_amounts,
+        }
+    }
+}
+
+/// An output sent to an address, as reported by the `address_history` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcAddressOutput {
+    /// Id of the transaction, or of the block for block rewards, that created the output
+    pub source_id: H256,
+    pub output_index: u32,
+    /// Null for coins
+    pub token_id: Option<TokenId>,
+    pub amount: Amount,
+    pub block_height: BlockHeight,
+    /// Id of the spending transaction, or of the block for block reward inputs, null if unspent
+    pub spent_by: Option<H256>,
+    pub spent_at_height: Option<BlockHeight>,
+}
+
+impl From<AddressHistoryEntry> for RpcAddressOutput {
+    fn from(entry: AddressHistoryEntry) -> Self {
+        let source_id = match entry.outpoint.tx_id() {
+            OutPointSourceId::Transaction(id) => id.get(),
+            OutPointSourceId::BlockReward(id) => id.get(),
+        };
+        let token_id = match entry.coin_or_token_id {
+            CoinOrTokenId::Coin => None,
+            CoinOrTokenId::TokenId(token_id) => Some(token_id),
+        };
+        let spent_by = entry.info.spend().map(|spend| spender_id(spend.spender()));
+        Self {
+            source_id,
+            output_index: entry.outpoint.output_index(),
+            token_id,
+            amount: entry.info.amount(),
+            block_height: entry.info.block_height(),
+            spent_by,
+            spent_at_height: entry.info.spend().map(|spend| spend.block_height()),
+        }
+    }
+}
+
+/// The input that spent an output, as reported by the `output_spender` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcOutputSpend {
+    /// Id of the spending transaction, or of the block for block reward inputs
+    pub spent_by: H256,
+    pub input_index: u32,
+    pub block_id: Id<Block>,
+}
+
+impl From<OutputSpendInfo> for RpcOutputSpend {
+    fn from(info: OutputSpendInfo) -> Self {
+        Self {
+            spent_by: spender_id(info.spender()),
+            input_index: info.input_index(),
+            block_id: info.block_id(),
+        }
+    }
+}
+
+fn spender_id(spender: &Spender) -> H256 {
+    match spender {
+        Spender::RegularInput(id) => id.get(),
+        Spender::BlockInput(id) => id.get(),
+    }
+}
+
 #[rpc::rpc(server, namespace = "chainstate")]
 trait ChainstateRpc {
     /// Get the best block ID
@@ -58,17 +205,81 @@ trait ChainstateRpc {
     #[method(name = "token_info")]
     async fn token_info(&self, token_id: TokenId) -> rpc::Result<Option<RPCTokenInfo>>;
 
-    /// Write blocks to disk
+    /// Write blocks to disk, optionally only the ones with heights in the given inclusive range
     #[method(name = "export_bootstrap_file")]
     async fn export_bootstrap_file(
         &self,
         file_path: &std::path::Path,
         include_orphans: bool,
+        start_height: Option<BlockHeight>,
+        end_height: Option<BlockHeight>,
     ) -> rpc::Result<()>;
 
     /// Reads blocks from disk
     #[method(name = "import_bootstrap_file")]
     async fn import_bootstrap_file(&self, file_path: &std::path::Path) -> rpc::Result<()>;
+
+    /// Write the utxo set at the current tip to disk, returns the content hash of the snapshot
+    #[method(name = "export_utxo_snapshot_file")]
+    async fn export_utxo_snapshot_file(&self, file_path: &std::path::Path) -> rpc::Result<H256>;
+
+    /// Load a utxo snapshot into a node without blocks, the snapshot hash must be pinned
+    /// in the chain config
+    #[method(name = "import_utxo_snapshot_file")]
+    async fn import_utxo_snapshot_file(&self, file_path: &std::path::Path) -> rpc::Result<()>;
+
+    /// Mark a block and its descendants as invalid, reorganizing away from it if necessary
+    #[method(name = "invalidate_block")]
+    async fn invalidate_block(&self, block_id: Id<Block>) -> rpc::Result<()>;
+
+    /// Remove the invalid status from a block and its descendants, and reconsider them
+    /// for the main chain
+    #[method(name = "reconsider_block")]
+    async fn reconsider_block(&self, block_id: Id<Block>) -> rpc::Result<()>;
+
+    /// Disconnect and reconnect the last `depth` blocks of the main chain in memory, at most
+    /// `MAX_VERIFY_CHAIN_DEPTH` of them, and check the rebuilt utxo set and PoS accounting data against the stored ones.
+    /// Returns the description of the first inconsistency found, null if there is none.
+    #[method(name = "verify_chain")]
+    async fn verify_chain(&self, depth: usize) -> rpc::Result<Option<String>>;
+
+    /// List the leaves of the block tree, along with the tip of the main chain
+    #[method(name = "chain_tips")]
+    async fn chain_tips(&self) -> rpc::Result<Vec<RpcChainTip>>;
+
+    /// Height of the last main chain block whose data was pruned, null if nothing was pruned
+    #[method(name = "pruned_height")]
+    async fn pruned_height(&self) -> rpc::Result<Option<BlockHeight>>;
+
+    /// Number of utxos, total amounts of coins and tokens, and the hash of the utxo set
+    /// at the best block
+    #[method(name = "utxo_set_info")]
+    async fn utxo_set_info(&self) -> rpc::Result<RpcUtxoSetInfo>;
+
+    /// Unspent amounts of coins and tokens of a hex-encoded destination,
+    /// requires the address index
+    #[method(name = "address_balance")]
+    async fn address_balance(&self, destination_hex: String) -> rpc::Result<RpcAddressBalance>;
+
+    /// Outputs sent to a hex-encoded destination in the main chain along with their spending,
+    /// oldest first; requires the address index
+    #[method(name = "address_history")]
+    async fn address_history(&self, destination_hex: String) -> rpc::Result<Vec<RpcAddressOutput>>;
+
+    /// The input that spent a hex-encoded outpoint in the main chain, null if it is not spent;
+    /// requires the spent-output index
+    #[method(name = "output_spender")]
+    async fn output_spender(&self, outpoint_hex: String) -> rpc::Result<Option<RpcOutputSpend>>;
+
+    /// Returns a hex-encoded proof that the transaction is in the block, which can be checked
+    /// against the transaction merkle root of the block header; null if the block data is not
+    /// available or the transaction is not in the block
+    #[method(name = "tx_inclusion_proof")]
+    async fn tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: Id<Transaction>,
+    ) -> rpc::Result<Option<String>>;
 }
 
 #[async_trait::async_trait]
@@ -115,14 +326,18 @@ impl ChainstateRpcServer for super::ChainstateHandle {
         &self,
         file_path: &std::path::Path,
         include_orphans: bool,
+        start_height: Option<BlockHeight>,
+        end_height: Option<BlockHeight>,
     ) -> rpc::Result<()> {
         // TODO: test this function in functional tests
         let file_obj = std::fs::File::create(file_path).map_err(rpc::Error::to_call_error)?;
         let writer: std::io::BufWriter<Box<dyn Write + Send>> =
             std::io::BufWriter::new(Box::new(file_obj));
+        let heights =
+            start_height.unwrap_or(BlockHeight::zero())..=end_height.unwrap_or(BlockHeight::max());
 
         handle_error(
-            self.call(move |this| this.export_bootstrap_stream(writer, include_orphans))
+            self.call(move |this| this.export_bootstrap_stream(writer, include_orphans, heights))
                 .await,
         )?;
 
@@ -139,6 +354,88 @@ impl ChainstateRpcServer for super::ChainstateHandle {
 
         Ok(())
     }
+
+    async fn export_utxo_snapshot_file(&self, file_path: &std::path::Path) -> rpc::Result<H256> {
+        let file_obj = std::fs::File::create(file_path).map_err(rpc::Error::to_call_error)?;
+        let writer: std::io::BufWriter<Box<dyn Write + Send>> =
+            std::io::BufWriter::new(Box::new(file_obj));
+
+        handle_error(self.call(move |this| this.export_utxo_snapshot_stream(writer)).await)
+    }
+
+    async fn import_utxo_snapshot_file(&self, file_path: &std::path::Path) -> rpc::Result<()> {
+        let file_obj = std::fs::File::open(file_path).map_err(rpc::Error::to_call_error)?;
+        let reader: std::io::BufReader<Box<dyn Read + Send>> =
+            std::io::BufReader::new(Box::new(file_obj));
+
+        handle_error(self.call_mut(move |this| this.import_utxo_snapshot_stream(reader)).await)
+    }
+
+    async fn invalidate_block(&self, block_id: Id<Block>) -> rpc::Result<()> {
+        handle_error(self.call_mut(move |this| this.invalidate_block(&block_id)).await)
+    }
+
+    async fn reconsider_block(&self, block_id: Id<Block>) -> rpc::Result<()> {
+        handle_error(self.call_mut(move |this| this.reconsider_block(&block_id)).await)
+    }
+
+    async fn verify_chain(&self, depth: usize) -> rpc::Result<Option<String>> {
+        let inconsistency = handle_error(self.call(move |this| this.verify_chain(depth)).await)?;
+        Ok(inconsistency.map(|inconsistency| inconsistency.to_string()))
+    }
+
+    async fn chain_tips(&self) -> rpc::Result<Vec<RpcChainTip>> {
+        let tips = handle_error(self.call(|this| this.get_chain_tips()).await)?;
+        Ok(tips.into_iter().map(RpcChainTip::from).collect())
+    }
+
+    async fn pruned_height(&self) -> rpc::Result<Option<BlockHeight>> {
+        handle_error(self.call(|this| this.get_pruned_height()).await)
+    }
+
+    async fn utxo_set_info(&self) -> rpc::Result<RpcUtxoSetInfo> {
+        let info = handle_error(self.call(|this| this.get_utxo_set_info()).await)?;
+        Ok(info.into())
+    }
+
+    async fn address_balance(&self, destination_hex: String) -> rpc::Result<RpcAddressBalance> {
+        let destination = decode_destination(&destination_hex)?;
+        let balance =
+            handle_error(self.call(move |this| this.get_address_balance(&destination)).await)?;
+        Ok(balance.into())
+    }
+
+    async fn address_history(&self, destination_hex: String) -> rpc::Result<Vec<RpcAddressOutput>> {
+        let destination = decode_destination(&destination_hex)?;
+        let history =
+            handle_error(self.call(move |this| this.get_address_history(&destination)).await)?;
+        Ok(history.into_iter().map(RpcAddressOutput::from).collect())
+    }
+
+    async fn output_spender(&self, outpoint_hex: String) -> rpc::Result<Option<RpcOutputSpend>> {
+        let outpoint_data = hex::decode(outpoint_hex).map_err(rpc::Error::to_call_error)?;
+        let outpoint =
+            OutPoint::decode(&mut &outpoint_data[..]).map_err(rpc::Error::to_call_error)?;
+        let info =
+            handle_error(self.call(move |this| this.get_output_spend_info(&outpoint)).await)?;
+        Ok(info.map(RpcOutputSpend::from))
+    }
+
+    async fn tx_inclusion_proof(
+        &self,
+        block_id: Id<Block>,
+        tx_id: Id<Transaction>,
+    ) -> rpc::Result<Option<String>> {
+        let proof = handle_error(
+            self.call(move |this| this.get_tx_inclusion_proof(block_id, tx_id)).await,
+        )?;
+        Ok(proof.map(|proof| hex::encode(proof.encode())))
+    }
+}
+
+fn decode_destination(destination_hex: &str) -> rpc::Result<Destination> {
+    let destination_data = hex::decode(destination_hex).map_err(rpc::Error::to_call_error)?;
+    Destination::decode(&mut &destination_data[..]).map_err(rpc::Error::to_call_error)
 }
 
 fn handle_error<T>(e: Result<Result<T, ChainstateError>, CallError>) -> rpc::Result<T> {
@@ -205,6 +502,27 @@ mod test {
 
             let res: rpc::Result<Value> = rpc.call("chainstate_block_id_at_height", [1u32]).await;
             assert!(matches!(res, Ok(Value::Null)));
+
+            let res: rpc::Result<Vec<RpcChainTip>> =
+                rpc.call("chainstate_chain_tips", [(); 0]).await;
+            let tips = res.unwrap();
+            assert_eq!(tips.len(), 1);
+            assert_eq!(
+                serde_json::to_value(tips[0].block_id).unwrap(),
+                Value::String(genesis_hash.clone())
+            );
+            assert_eq!(tips[0].height, BlockHeight::zero());
+            assert_eq!(tips[0].branch_length, 0);
+            assert_eq!(tips[0].status, "active");
+
+            let res: rpc::Result<RpcUtxoSetInfo> =
+                rpc.call("chainstate_utxo_set_info", [(); 0]).await;
+            let info = res.unwrap();
+            assert_eq!(
+                serde_json::to_value(info.best_block_id).unwrap(),
+                Value::String(genesis_hash)
+            );
+            assert_eq!(info.best_block_height, BlockHeight::zero());
         })
         .await
     }
@@ -0,0 +1,253 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Upgrades of the database contents to the current storage version
+//!
+//! Each time the layout or the meaning of the data in the maps of the schema changes, the storage
+//! version is increased and a migration step is added to convert databases written with the
+//! previous version. The steps are applied in order when the database is opened, each one in its
+//! own write transaction together with the version bump, so an interrupted upgrade continues from
+//! the last completed step.
+
+use chainstate_types::{BlockIndex, BlockStatus};
+use common::{
+    chain::{
+        block::{timestamp::BlockTimestamp, BlockHeader},
+        Block, GenBlock,
+    },
+    primitives::{BlockHeight, Id},
+    Uint256,
+};
+use logging::log;
+use serialization::{Decode, DecodeAll};
+use std::collections::BTreeSet;
+use utxo::{UtxoSetHash, UtxosStorageWrite};
+
+use crate::{
+    schema as db, BlockchainStorageRead, BlockchainStorageWrite, TransactionRw, Transactional,
+};
+
+use super::{well_known, Store, StoreTxRw};
+
+/// The storage version of the databases written by this code
+pub const CURRENT_STORAGE_VERSION: u32 = 6;
+
+/// A step that upgrades the database contents from `from_version` to `from_version + 1`
+pub(super) struct Migration<B: storage::Backend> {
+    pub from_version: u32,
+    pub description: &'static str,
+    pub migrate: fn(&mut StoreTxRw<'_, B>) -> crate::Result<()>,
+}
+
+/// The migration steps, ordered by version
+pub(super) fn migrations<B: storage::Backend>() -> Vec<Migration<B>> {
+    vec![
+        Migration {
+            from_version: 1,
+            description: "add the validation status to the block indexes",
+            migrate: add_block_index_status,
+        },
+        Migration {
+            from_version: 2,
+            description: "compute the utxo set hash of the tip",
+            migrate: store_tip_utxo_set_hash,
+        },
+        Migration {
+            from_version: 3,
+            description: "compute the total size of the stored blocks",
+            migrate: store_blocks_size,
+        },
+        Migration {
+            from_version: 4,
+            description: "split the utxo snapshot validation checkpoint into entries",
+            migrate: split_utxo_snapshot_validation_state,
+        },
+        Migration {
+            from_version: 5,
+            description: "link the block indexes to their children",
+            migrate: link_block_indexes,
+        },
+    ]
+}
+
+/// Bring the database to the current storage version.
+///
+/// A new database is marked with the current version, and a database with a newer version than
+/// the current one is refused.
+pub(super) fn migrate<B: storage::Backend>(
+    store: &Store<B>,
+    migrations: &[Migration<B>],
+    current_version: u32,
+) -> crate::Result<()> {
+    let version = store.transaction_ro()?.get_storage_version()?;
+
+    if version == 0 {
+        let mut db_tx = store.transaction_rw(None)?;
+        db_tx.set_storage_version(current_version)?;
+        return db_tx.commit();
+    }
+
+    if version > current_version {
+        return Err(crate::Error::UnsupportedStorageVersion(
+            version,
+            current_version,
+        ));
+    }
+
+    for migration in migrations.iter().filter(|m| m.from_version >= version) {
+        log::info!(
+            "Upgrading the chainstate database from version {} to {}: {}",
+            migration.from_version,
+            migration.from_version + 1,
+            migration.description,
+        );
+        let mut db_tx = store.transaction_rw(None)?;
+        (migration.migrate)(&mut db_tx)?;
+        db_tx.set_storage_version(migration.from_version + 1)?;
+        db_tx.commit()?;
+    }
+
+    Ok(())
+}
+
+/// Block index as stored by version 1 databases, before the validation status was added
+#[derive(Decode)]
+struct BlockIndexV1 {
+    _block_id: Id<Block>,
+    block_header: BlockHeader,
+    some_ancestor: Id<GenBlock>,
+    chain_trust: Uint256,
+    height: BlockHeight,
+    time_max: BlockTimestamp,
+}
+
+// The block indexes of version 1 databases have no validation status. All of them have a valid
+// header, and the blocks on the main chain have been connected. The indexes are converted one by
+// one, only their keys are kept in memory.
+fn add_block_index_status<B: storage::Backend>(db_tx: &mut StoreTxRw<'_, B>) -> crate::Result<()> {
+    let block_ids: Vec<Id<Block>> =
+        db_tx.0.get::<db::DBBlockIndex, _>().prefix_iter_keys(&())?.collect();
+
+    for block_id in block_ids {
+        let encoded = db_tx
+            .0
+            .get::<db::DBBlockIndex, _>()
+            .get_raw(block_id)?
+            .expect("block index to exist")
+            .into_owned();
+        let old = BlockIndexV1::decode_all(&mut encoded.as_slice())
+            .expect("version 1 block index to be encoded correctly");
+
+        let mut status = BlockStatus::new();
+        if db_tx.get_block_id_by_height(&old.height)? == Some(block_id.into()) {
+            status.set_valid_transactions();
+        }
+        let mut block_index = BlockIndex::new(
+            &old.block_header,
+            old.chain_trust,
+            old.some_ancestor,
+            old.height,
+            old.time_max,
+        );
+        block_index.set_status(status);
+        db_tx.set_block_index(&block_index)?;
+    }
+    Ok(())
+}
+
+// Databases before version 3 don't have the hash of the utxo set, which the hashes after each block are
+// derived from. Only the hash of the tip is needed, the others are derived from it when blocks are
+// connected or disconnected.
+fn store_tip_utxo_set_hash<B: storage::Backend>(db_tx: &mut StoreTxRw<'_, B>) -> crate::Result<()> {
+    if let Some(best_block_id) = db_tx.get_best_block_id()? {
+        // The utxos are hashed one at a time, the utxo set may not fit in memory
+        let set_hash = db_tx.0.get::<db::DBUtxo, _>().prefix_iter_decoded(&())?.fold(
+            UtxoSetHash::new(),
+            |mut set_hash, (outpoint, utxo)| {
+                set_hash.add_utxo(&outpoint, &utxo);
+                set_hash
+            },
+        );
+        db_tx.set_utxo_set_hash(&best_block_id, &set_hash)?;
+    }
+    Ok(())
+}
+
+// Databases before version 4 don't keep the total size of the stored blocks, which pruning by size
+// relies on. It's computed once from the encoded blocks, then kept up to date as blocks are added
+// and removed.
+fn store_blocks_size<B: storage::Backend>(db_tx: &mut StoreTxRw<'_, B>) -> crate::Result<()> {
+    let blocks_size = db_tx
+        .0
+        .get::<db::DBBlock, _>()
+        .prefix_iter(&())?
+        .map(|(_id, block)| block.bytes().len() as u64)
+        .sum();
+    db_tx.write_value::<well_known::StoredBlocksSize>(&blocks_size)
+}
+
+/// Key of the checkpoint of the utxo snapshot history validation in version 4 databases
+const UTXO_SNAPSHOT_VALIDATION_STATE_V4: &[u8] = b"UtxoSnapshotValidationState";
+
+// Version 4 databases save the whole database of the utxo snapshot history validation as a single
+// value, in the portable dump format. It's converted to the entries that are now saved as they
+// change.
+fn split_utxo_snapshot_validation_state<B: storage::Backend>(
+    db_tx: &mut StoreTxRw<'_, B>,
+) -> crate::Result<()> {
+    let state =
+        db_tx
+            .0
+            .get::<db::DBValue, _>()
+            .get(UTXO_SNAPSHOT_VALIDATION_STATE_V4)?
+            .map(|state| {
+                Vec::<u8>::decode_all(&mut state.decode().as_slice())
+                    .expect("db values to be encoded correctly")
+            });
+    let state = match state {
+        Some(state) => state,
+        None => return Ok(()),
+    };
+
+    let contents = storage::raw::read_dump::<db::Schema>(&mut state.as_slice())?;
+    for (idx, map) in contents {
+        for (key, value) in map {
+            db_tx.set_utxo_snapshot_validation_entry(&(idx.as_usize() as u32, key), &value)?;
+        }
+    }
+    db_tx.0.get_mut::<db::DBValue, _>().del(UTXO_SNAPSHOT_VALIDATION_STATE_V4)?;
+    Ok(())
+}
+
+// Databases before version 6 don't link the blocks to their children, so finding the descendants
+// of a block or the tips of the block tree requires a scan of all the block indexes. The links
+// and the leaves are built once from the block indexes, only their ids are kept in memory.
+fn link_block_indexes<B: storage::Backend>(db_tx: &mut StoreTxRw<'_, B>) -> crate::Result<()> {
+    let links: Vec<(Id<GenBlock>, Id<Block>)> = db_tx
+        .0
+        .get::<db::DBBlockIndex, _>()
+        .prefix_iter_decoded(&())?
+        .map(|(block_id, block_index)| (*block_index.prev_block_id(), block_id))
+        .collect();
+
+    let parents: BTreeSet<Id<GenBlock>> = links.iter().map(|(parent_id, _)| *parent_id).collect();
+    for (parent_id, block_id) in links {
+        db_tx.write::<db::DBBlockChildren, _, _, _>((parent_id, block_id), ())?;
+        if !parents.contains(&block_id.into()) {
+            db_tx.write::<db::DBBlockLeaves, _, _, _>(block_id, ())?;
+        }
+    }
+    Ok(())
+}
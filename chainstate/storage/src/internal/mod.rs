@@ -13,26 +13,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod migration;
 pub mod utxo_db;
 
-use chainstate_types::BlockIndex;
+pub use migration::CURRENT_STORAGE_VERSION;
+
+use chainstate_types::{AddressIndexKey, AddressOutputInfo, BlockIndex, OutputSpendInfo};
 use common::{
     chain::{
-        block::BlockReward,
-        tokens::{TokenAuxiliaryData, TokenId},
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            BlockReward,
+        },
+        tokens::{CoinOrTokenId, TokenAuxiliaryData, TokenId},
         transaction::{Transaction, TxMainChainIndex, TxMainChainPosition},
-        Block, GenBlock, OutPoint, OutPointSourceId,
+        Block, Destination, GenBlock, OutPoint, OutPointSourceId,
     },
     primitives::{Amount, BlockHeight, Id, Idable, H256},
 };
 use pos_accounting::{
-    AccountingBlockUndo, DelegationData, DelegationId, PoSAccountingStorageRead,
+    AccountingBlockUndo, DelegationData, DelegationId, PoSAccountingData, PoSAccountingStorageRead,
     PoSAccountingStorageWrite, PoolData, PoolId,
 };
 use serialization::{Codec, Decode, DecodeAll, Encode, EncodeLike};
 use std::collections::BTreeMap;
 use storage::schema;
-use utxo::{Utxo, UtxosBlockUndo, UtxosStorageRead, UtxosStorageWrite};
+use utxo::{Utxo, UtxoSetHash, UtxosBlockUndo, UtxosStorageRead, UtxosStorageWrite};
 
 use crate::{
     schema::{self as db, Schema},
@@ -40,7 +46,7 @@ use crate::{
 };
 
 mod well_known {
-    use super::{Codec, GenBlock, Id};
+    use super::{Block, BlockHeight, Codec, GenBlock, Id};
 
     /// Pre-defined database keys
     pub trait Entry {
@@ -64,70 +70,101 @@ mod well_known {
     declare_entry!(BestBlockId: Id<GenBlock>);
     declare_entry!(UtxosBestBlockId: Id<GenBlock>);
     declare_entry!(TxIndexEnabled: bool);
+    declare_entry!(PrunedHeight: BlockHeight);
+    declare_entry!(StoredBlocksSize: u64);
+    declare_entry!(UtxoSnapshotBlock: Id<Block>);
+    declare_entry!(AddressIndexEnabled: bool);
+    declare_entry!(SpentIndexEnabled: bool);
+    declare_entry!(BlockFiltersEnabled: bool);
+    declare_entry!(HeadersOnly: bool);
+    declare_entry!(ReindexTarget: Id<Block>);
+    declare_entry!(UtxoSnapshotHistoryInvalid: Id<Block>);
 }
 
 /// Store for blockchain data, parametrized over the backend B
 pub struct Store<B: storage::Backend>(storage::Storage<B, Schema>);
 
 impl<B: storage::Backend> Store<B> {
-    /// Create a new chainstate storage
+    /// Create a new chainstate storage, upgrading the database contents to the current storage
+    /// version if needed
     pub fn new(backend: B) -> crate::Result<Self> {
-        let mut storage = Self(storage::Storage::new(backend).map_err(crate::Error::from)?);
-        storage.set_storage_version(1)?;
+        Self::new_with_migrations(
+            backend,
+            &migration::migrations(),
+            migration::CURRENT_STORAGE_VERSION,
+        )
+    }
+
+    fn new_with_migrations(
+        backend: B,
+        migrations: &[migration::Migration<B>],
+        current_version: u32,
+    ) -> crate::Result<Self> {
+        let storage = Self(storage::Storage::new(backend).map_err(crate::Error::from)?);
+        migration::migrate(&storage, migrations, current_version)?;
         Ok(storage)
     }
 
+    /// Create a chainstate storage with the contents of a portable dump, the database has to be
+    /// empty
+    pub fn from_dump(backend: B, reader: &mut impl std::io::Read) -> crate::Result<Self> {
+        let contents = storage::raw::read_dump(reader).map_err(crate::Error::from)?;
+        Self::from_raw(backend, &contents)
+    }
+
+    /// Create a chainstate storage with given raw contents, the database has to be empty
+    pub fn from_raw(
+        backend: B,
+        contents: &storage::raw::StorageContents<Schema>,
+    ) -> crate::Result<Self> {
+        let storage = storage::Storage::new(backend).map_err(crate::Error::from)?;
+        storage.restore_raw(contents).map_err(crate::Error::from)?;
+        let storage = Self(storage);
+        migration::migrate(
+            &storage,
+            &migration::migrations(),
+            migration::CURRENT_STORAGE_VERSION,
+        )?;
+        Ok(storage)
+    }
+
+    /// Create a chainstate storage with given raw entries, keyed by the map index and the key,
+    /// the database has to be empty
+    pub fn from_raw_entries(
+        backend: B,
+        entries: impl IntoIterator<Item = (crate::UtxoSnapshotValidationKey, Vec<u8>)>,
+    ) -> crate::Result<Self> {
+        let mut contents = storage::raw::StorageContents::<Schema>::new();
+        for ((idx, key), value) in entries {
+            let idx = storage::raw::DbIndex::from_usize(idx as usize).ok_or_else(|| {
+                crate::Error::Storage(storage::error::Recoverable::Io(
+                    std::io::ErrorKind::InvalidData,
+                    format!("Unknown database map index {idx}"),
+                ))
+            })?;
+            contents.entry(idx).or_default().insert(key, value);
+        }
+        Self::from_raw(backend, &contents)
+    }
+
     /// Dump raw database contents
     pub fn dump_raw(&self) -> crate::Result<storage::raw::StorageContents<Schema>> {
         self.0.dump_raw().map_err(crate::Error::from)
     }
 
+    /// Write the database contents in the portable dump format
+    pub fn write_dump(&self, writer: &mut impl std::io::Write) -> crate::Result<()> {
+        storage::raw::write_dump(&self.dump_raw()?, writer).map_err(crate::Error::from)
+    }
+
     /// Collect and return all utxos from the storage
-    #[allow(clippy::let_and_return)]
     pub fn read_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>> {
-        let db = self.transaction_ro()?;
-        let map = db.0.get::<db::DBUtxo, _>();
-        let res = map.prefix_iter_decoded(&())?.collect::<BTreeMap<_, _>>();
-
-        Ok(res)
+        self.transaction_ro()?.get_utxo_set()
     }
 
     /// Collect and return all accounting data from storage
-    pub fn read_accounting_data(&self) -> crate::Result<pos_accounting::PoSAccountingData> {
-        let db = self.transaction_ro()?;
-
-        let pool_data =
-            db.0.get::<db::DBAccountingPoolData, _>()
-                .prefix_iter_decoded(&())?
-                .collect::<BTreeMap<_, _>>();
-
-        let pool_balances =
-            db.0.get::<db::DBAccountingPoolBalances, _>()
-                .prefix_iter_decoded(&())?
-                .collect::<BTreeMap<_, _>>();
-
-        let delegation_data =
-            db.0.get::<db::DBAccountingDelegationData, _>()
-                .prefix_iter_decoded(&())?
-                .collect::<BTreeMap<_, _>>();
-
-        let delegation_balances =
-            db.0.get::<db::DBAccountingDelegationBalances, _>()
-                .prefix_iter_decoded(&())?
-                .collect::<BTreeMap<_, _>>();
-
-        let pool_delegation_shares =
-            db.0.get::<db::DBAccountingPoolDelegationShares, _>()
-                .prefix_iter_decoded(&())?
-                .collect::<BTreeMap<_, _>>();
-
-        Ok(pos_accounting::PoSAccountingData {
-            pool_data,
-            pool_balances,
-            pool_delegation_shares,
-            delegation_balances,
-            delegation_data,
-        })
+    pub fn read_accounting_data(&self) -> crate::Result<PoSAccountingData> {
+        self.transaction_ro()?.get_accounting_data()
     }
 }
 
@@ -163,7 +200,51 @@ impl<'tx, B: storage::Backend + 'tx> Transactional<'tx> for Store<B> {
     }
 }
 
-impl<B: storage::Backend + 'static> BlockchainStorage for Store<B> {}
+impl<B: storage::Backend + 'static> BlockchainStorage for Store<B> {
+    fn backup_handle(&self) -> Box<dyn crate::BlockchainStorageBackup> {
+        Box::new(self.clone())
+    }
+
+    fn utxo_snapshot_validation_handle(&self) -> Box<dyn crate::UtxoSnapshotValidationStorage> {
+        Box::new(self.clone())
+    }
+}
+
+impl<B: storage::Backend + 'static> crate::UtxoSnapshotValidationStorage for Store<B> {
+    fn save_utxo_snapshot_validation_changes(
+        &self,
+        changes: &[(crate::UtxoSnapshotValidationKey, Option<Vec<u8>>)],
+    ) -> crate::Result<()> {
+        let mut db_tx = self.transaction_rw(None)?;
+        for (key, value) in changes {
+            match value {
+                Some(value) => db_tx.set_utxo_snapshot_validation_entry(key, value)?,
+                None => db_tx.del_utxo_snapshot_validation_entry(key)?,
+            }
+        }
+        db_tx.commit()
+    }
+
+    fn finish_utxo_snapshot_validation(&self) -> crate::Result<()> {
+        let mut db_tx = self.transaction_rw(None)?;
+        db_tx.del_utxo_snapshot_validation_entries()?;
+        db_tx.del_utxo_snapshot_block()?;
+        db_tx.commit()
+    }
+
+    fn invalidate_utxo_snapshot(&self, block_id: &Id<Block>) -> crate::Result<()> {
+        let mut db_tx = self.transaction_rw(None)?;
+        db_tx.del_utxo_snapshot_validation_entries()?;
+        db_tx.set_invalid_utxo_snapshot_block(block_id)?;
+        db_tx.commit()
+    }
+}
+
+impl<B: storage::Backend + 'static> crate::BlockchainStorageBackup for Store<B> {
+    fn backup(&self, path: &std::path::Path, format: storage::BackupFormat) -> crate::Result<()> {
+        self.0.backup(path, format).map_err(crate::Error::from)
+    }
+}
 
 macro_rules! delegate_to_transaction {
     ($($(#[size=$s:expr])? fn $func:ident $args:tt -> $ret:ty;)*) => {
@@ -218,11 +299,46 @@ impl<B: storage::Backend> BlockchainStorageRead for Store<B> {
         fn get_block_tree_by_height(
             &self,
         ) -> crate::Result<BTreeMap<BlockHeight, Vec<Id<Block>>>>;
+        fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>>;
+        fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>>;
 
         fn get_accounting_undo(
             &self,
             id: Id<Block>,
         ) -> crate::Result<Option<AccountingBlockUndo>>;
+        fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>>;
+        fn get_stored_blocks_size(&self) -> crate::Result<u64>;
+        fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>>;
+        fn get_accounting_data(&self) -> crate::Result<PoSAccountingData>;
+        fn get_all_token_aux_data(&self) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>>;
+        fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>>;
+        fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+
+        fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_address_index_entry(
+            &self,
+            key: &AddressIndexKey,
+        ) -> crate::Result<Option<AddressOutputInfo>>;
+        fn get_address_index_entries(
+            &self,
+            destination: &Destination,
+        ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>>;
+
+        fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_output_spend_info(&self, outpoint: &OutPoint) -> crate::Result<Option<OutputSpendInfo>>;
+
+        fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_is_headers_only(&self) -> crate::Result<Option<bool>>;
+        fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_utxo_snapshot_validation_entries(
+            &self,
+        ) -> crate::Result<crate::UtxoSnapshotValidationEntries>;
+        fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>>;
+        fn get_block_filter_header(
+            &self,
+            block_id: &Id<Block>,
+        ) -> crate::Result<Option<BlockFilterHeader>>;
     }
 }
 
@@ -231,6 +347,7 @@ impl<B: storage::Backend> UtxosStorageRead for Store<B> {
         fn get_utxo(&self, outpoint: &OutPoint) -> crate::Result<Option<Utxo>>;
         fn get_best_block_for_utxos(&self) -> crate::Result<Option<Id<GenBlock>>>;
         fn get_undo_data(&self, id: Id<Block>) -> crate::Result<Option<UtxosBlockUndo>>;
+        fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> crate::Result<Option<UtxoSetHash>>;
     }
 }
 
@@ -290,6 +407,48 @@ impl<B: storage::Backend> BlockchainStorageWrite for Store<B> {
             undo: &AccountingBlockUndo,
         ) -> crate::Result<()>;
         fn del_accounting_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+
+        fn set_pruned_height(&mut self, height: &BlockHeight) -> crate::Result<()>;
+        fn set_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_utxo_snapshot_block(&mut self) -> crate::Result<()>;
+
+        fn set_is_address_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_address_index_entry(
+            &mut self,
+            key: &AddressIndexKey,
+            info: &AddressOutputInfo,
+        ) -> crate::Result<()>;
+        fn del_address_index_entry(&mut self, key: &AddressIndexKey) -> crate::Result<()>;
+
+        fn set_is_spent_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_output_spend_info(
+            &mut self,
+            outpoint: &OutPoint,
+            info: &OutputSpendInfo,
+        ) -> crate::Result<()>;
+        fn del_output_spend_info(&mut self, outpoint: &OutPoint) -> crate::Result<()>;
+
+        fn set_is_block_filters_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_is_headers_only(&mut self, headers_only: bool) -> crate::Result<()>;
+        fn set_reindex_target(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_reindex_target(&mut self) -> crate::Result<()>;
+        fn set_utxo_snapshot_validation_entry(
+            &mut self,
+            key: &crate::UtxoSnapshotValidationKey,
+            value: &[u8],
+        ) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entry(&mut self, key: &crate::UtxoSnapshotValidationKey) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entries(&mut self) -> crate::Result<()>;
+        fn set_invalid_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_derived_data(&mut self, max_count: usize) -> crate::Result<bool>;
+        fn set_block_filter(&mut self, block_id: &Id<Block>, filter: &BlockFilter) -> crate::Result<()>;
+        fn set_block_filter_header(
+            &mut self,
+            block_id: &Id<Block>,
+            header: &BlockFilterHeader,
+        ) -> crate::Result<()>;
+        fn del_block_filter(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
+        fn del_block_filter_header(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
     }
 }
 
@@ -300,6 +459,8 @@ impl<B: storage::Backend> UtxosStorageWrite for Store<B> {
         fn set_best_block_for_utxos(&mut self, block_id: &Id<GenBlock>) -> crate::Result<()>;
         fn set_undo_data(&mut self, id: Id<Block>, undo: &UtxosBlockUndo) -> crate::Result<()>;
         fn del_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+        fn set_utxo_set_hash(&mut self, block_id: &Id<GenBlock>, hash: &UtxoSetHash) -> crate::Result<()>;
+        fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> crate::Result<()>;
     }
 }
 
@@ -460,12 +621,134 @@ macro_rules! impl_read_ops {
                 Ok(result)
             }
 
+            fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>> {
+                let map = self.0.get::<db::DBBlockChildren, _>();
+                let children = map.prefix_iter_keys(&(*id,))?;
+                Ok(children.map(|(_parent, child)| child).collect())
+            }
+
+            fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>> {
+                let map = self.0.get::<db::DBBlockLeaves, _>();
+                let leaves = map.prefix_iter_keys(&())?;
+                Ok(leaves.collect())
+            }
+
             fn get_accounting_undo(
                 &self,
                 id: Id<Block>,
             ) -> crate::Result<Option<AccountingBlockUndo>> {
                 self.read::<db::DBAccountingBlockUndo, _, _>(id)
             }
+
+            fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>> {
+                self.read_value::<well_known::PrunedHeight>()
+            }
+
+            fn get_stored_blocks_size(&self) -> crate::Result<u64> {
+                self.read_value::<well_known::StoredBlocksSize>().map(|v| v.unwrap_or_default())
+            }
+
+            fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>> {
+                self.read_all::<db::DBUtxo, _>()
+            }
+
+            fn get_accounting_data(&self) -> crate::Result<PoSAccountingData> {
+                Ok(PoSAccountingData {
+                    pool_data: self.read_all::<db::DBAccountingPoolData, _>()?,
+                    pool_balances: self.read_all::<db::DBAccountingPoolBalances, _>()?,
+                    pool_delegation_shares: self
+                        .read_all::<db::DBAccountingPoolDelegationShares, _>()?,
+                    delegation_balances: self
+                        .read_all::<db::DBAccountingDelegationBalances, _>()?,
+                    delegation_data: self.read_all::<db::DBAccountingDelegationData, _>()?,
+                })
+            }
+
+            fn get_all_token_aux_data(
+                &self,
+            ) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>> {
+                self.read_all::<db::DBTokensAuxData, _>()
+            }
+
+            fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>> {
+                self.read_all::<db::DBIssuanceTxVsTokenId, _>()
+            }
+
+            fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>> {
+                self.read_value::<well_known::UtxoSnapshotBlock>()
+            }
+
+            fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>> {
+                self.read_value::<well_known::AddressIndexEnabled>()
+            }
+
+            fn get_address_index_entry(
+                &self,
+                key: &AddressIndexKey,
+            ) -> crate::Result<Option<AddressOutputInfo>> {
+                self.read::<db::DBAddressIndex, _, _>(key)
+            }
+
+            fn get_address_index_entries(
+                &self,
+                destination: &Destination,
+            ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>> {
+                let map = self.0.get::<db::DBAddressIndex, _>();
+                let items = map.prefix_iter_decoded(&(destination.clone(),))?;
+                Ok(items
+                    .map(|((_destination, coin_or_token, outpoint), info)| {
+                        ((coin_or_token, outpoint), info)
+                    })
+                    .collect())
+            }
+
+            fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>> {
+                self.read_value::<well_known::SpentIndexEnabled>()
+            }
+
+            fn get_output_spend_info(
+                &self,
+                outpoint: &OutPoint,
+            ) -> crate::Result<Option<OutputSpendInfo>> {
+                self.read::<db::DBSpentIndex, _, _>(outpoint)
+            }
+
+            fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>> {
+                self.read_value::<well_known::BlockFiltersEnabled>()
+            }
+
+            fn get_is_headers_only(&self) -> crate::Result<Option<bool>> {
+                self.read_value::<well_known::HeadersOnly>()
+            }
+
+            fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>> {
+                self.read_value::<well_known::ReindexTarget>()
+            }
+
+            fn get_utxo_snapshot_validation_entries(
+                &self,
+            ) -> crate::Result<crate::UtxoSnapshotValidationEntries> {
+                self.0
+                    .get::<db::DBUtxoSnapshotValidation, _>()
+                    .prefix_iter_decoded(&())
+                    .map(Iterator::collect)
+                    .map_err(crate::Error::from)
+            }
+
+            fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>> {
+                self.read_value::<well_known::UtxoSnapshotHistoryInvalid>()
+            }
+
+            fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>> {
+                self.read::<db::DBBlockFilter, _, _>(block_id)
+            }
+
+            fn get_block_filter_header(
+                &self,
+                block_id: &Id<Block>,
+            ) -> crate::Result<Option<BlockFilterHeader>> {
+                self.read::<db::DBBlockFilterHeader, _, _>(block_id)
+            }
         }
 
         impl<'st, B: storage::Backend> UtxosStorageRead for $TxType<'st, B> {
@@ -480,6 +763,13 @@ macro_rules! impl_read_ops {
             fn get_undo_data(&self, id: Id<Block>) -> crate::Result<Option<UtxosBlockUndo>> {
                 self.read::<db::DBUtxosBlockUndo, _, _>(id)
             }
+
+            fn get_utxo_set_hash(
+                &self,
+                block_id: &Id<GenBlock>,
+            ) -> crate::Result<Option<UtxoSetHash>> {
+                self.read::<db::DBUtxoSetHash, _, _>(block_id)
+            }
         }
 
         impl<'st, B: storage::Backend> PoSAccountingStorageRead for $TxType<'st, B> {
@@ -548,6 +838,18 @@ macro_rules! impl_read_ops {
                 map.get(key).map_err(crate::Error::from).map(|x| x.map(|x| x.decode()))
             }
 
+            // Read and decode all the entries of a map
+            fn read_all<DbMap, I>(&self) -> crate::Result<BTreeMap<DbMap::Key, DbMap::Value>>
+            where
+                DbMap: schema::DbMap,
+                Schema: schema::HasDbMap<DbMap, I>,
+                DbMap::Key: Ord + storage::HasPrefix<()>,
+            {
+                let map = self.0.get::<DbMap, I>();
+                let items = map.prefix_iter_decoded(&())?;
+                Ok(items.collect())
+            }
+
             // Read a value for a well-known entry
             fn read_value<E: well_known::Entry>(&self) -> crate::Result<Option<E::Value>> {
                 self.read::<db::DBValue, _, _>(E::KEY).map(|x| {
@@ -574,14 +876,24 @@ impl<'st, B: storage::Backend> BlockchainStorageWrite for StoreTxRw<'st, B> {
     }
 
     fn add_block(&mut self, block: &Block) -> crate::Result<()> {
-        self.write::<db::DBBlock, _, _, _>(block.get_id(), block)
+        let old_size = self.stored_block_size(block.get_id())?;
+        let new_size = block.encoded_size() as u64;
+        self.write::<db::DBBlock, _, _, _>(block.get_id(), block)?;
+        self.update_stored_blocks_size(old_size, new_size)
     }
 
     fn del_block(&mut self, id: Id<Block>) -> crate::Result<()> {
-        self.0.get_mut::<db::DBBlock, _>().del(id).map_err(Into::into)
+        let old_size = self.stored_block_size(id)?;
+        self.0.get_mut::<db::DBBlock, _>().del(id)?;
+        self.update_stored_blocks_size(old_size, 0)
     }
 
     fn set_block_index(&mut self, block_index: &BlockIndex) -> crate::Result<()> {
+        // Only the presence is checked, old databases store indexes in a different format
+        let is_new = self.0.get::<db::DBBlockIndex, _>().get_raw(block_index.block_id())?.is_none();
+        if is_new {
+            self.link_block_index(block_index)?;
+        }
         self.write::<db::DBBlockIndex, _, _, _>(block_index.block_id(), block_index)
     }
 
@@ -651,6 +963,144 @@ impl<'st, B: storage::Backend> BlockchainStorageWrite for StoreTxRw<'st, B> {
     fn del_accounting_undo_data(&mut self, id: Id<Block>) -> crate::Result<()> {
         self.0.get_mut::<db::DBAccountingBlockUndo, _>().del(id).map_err(Into::into)
     }
+
+    fn set_pruned_height(&mut self, height: &BlockHeight) -> crate::Result<()> {
+        self.write_value::<well_known::PrunedHeight>(height)
+    }
+
+    fn set_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()> {
+        self.write_value::<well_known::UtxoSnapshotBlock>(id)
+    }
+
+    fn del_utxo_snapshot_block(&mut self) -> crate::Result<()> {
+        self.del_value::<well_known::UtxoSnapshotBlock>()
+    }
+
+    fn set_is_address_index_enabled(&mut self, enabled: bool) -> crate::Result<()> {
+        self.write_value::<well_known::AddressIndexEnabled>(&enabled)
+    }
+
+    fn set_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+        info: &AddressOutputInfo,
+    ) -> crate::Result<()> {
+        self.write::<db::DBAddressIndex, _, _, _>(key, info)
+    }
+
+    fn del_address_index_entry(&mut self, key: &AddressIndexKey) -> crate::Result<()> {
+        self.0.get_mut::<db::DBAddressIndex, _>().del(key).map_err(Into::into)
+    }
+
+    fn set_is_spent_index_enabled(&mut self, enabled: bool) -> crate::Result<()> {
+        self.write_value::<well_known::SpentIndexEnabled>(&enabled)
+    }
+
+    fn set_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+        info: &OutputSpendInfo,
+    ) -> crate::Result<()> {
+        self.write::<db::DBSpentIndex, _, _, _>(outpoint, info)
+    }
+
+    fn del_output_spend_info(&mut self, outpoint: &OutPoint) -> crate::Result<()> {
+        self.0.get_mut::<db::DBSpentIndex, _>().del(outpoint).map_err(Into::into)
+    }
+
+    fn set_is_block_filters_enabled(&mut self, enabled: bool) -> crate::Result<()> {
+        self.write_value::<well_known::BlockFiltersEnabled>(&enabled)
+    }
+
+    fn set_is_headers_only(&mut self, headers_only: bool) -> crate::Result<()> {
+        self.write_value::<well_known::HeadersOnly>(&headers_only)
+    }
+
+    fn set_reindex_target(&mut self, id: &Id<Block>) -> crate::Result<()> {
+        self.write_value::<well_known::ReindexTarget>(id)
+    }
+
+    fn del_reindex_target(&mut self) -> crate::Result<()> {
+        self.del_value::<well_known::ReindexTarget>()
+    }
+
+    fn set_utxo_snapshot_validation_entry(
+        &mut self,
+        key: &crate::UtxoSnapshotValidationKey,
+        value: &[u8],
+    ) -> crate::Result<()> {
+        self.write::<db::DBUtxoSnapshotValidation, _, _, _>(key, value)
+    }
+
+    fn del_utxo_snapshot_validation_entry(
+        &mut self,
+        key: &crate::UtxoSnapshotValidationKey,
+    ) -> crate::Result<()> {
+        self.0.get_mut::<db::DBUtxoSnapshotValidation, _>().del(key).map_err(Into::into)
+    }
+
+    fn del_utxo_snapshot_validation_entries(&mut self) -> crate::Result<()> {
+        self.clear::<db::DBUtxoSnapshotValidation, _>(usize::MAX).map(|_| ())
+    }
+
+    fn set_invalid_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()> {
+        self.write_value::<well_known::UtxoSnapshotHistoryInvalid>(id)
+    }
+
+    fn del_derived_data(&mut self, max_count: usize) -> crate::Result<bool> {
+        let clears: [fn(&mut Self, usize) -> crate::Result<usize>; 17] = [
+            Self::clear::<db::DBBlockByHeight, _>,
+            Self::clear::<db::DBTxIndex, _>,
+            Self::clear::<db::DBUtxo, _>,
+            Self::clear::<db::DBUtxosBlockUndo, _>,
+            Self::clear::<db::DBUtxoSetHash, _>,
+            Self::clear::<db::DBTokensAuxData, _>,
+            Self::clear::<db::DBIssuanceTxVsTokenId, _>,
+            Self::clear::<db::DBAddressIndex, _>,
+            Self::clear::<db::DBSpentIndex, _>,
+            Self::clear::<db::DBBlockFilter, _>,
+            Self::clear::<db::DBBlockFilterHeader, _>,
+            Self::clear::<db::DBAccountingBlockUndo, _>,
+            Self::clear::<db::DBAccountingPoolData, _>,
+            Self::clear::<db::DBAccountingPoolBalances, _>,
+            Self::clear::<db::DBAccountingDelegationData, _>,
+            Self::clear::<db::DBAccountingDelegationBalances, _>,
+            Self::clear::<db::DBAccountingPoolDelegationShares, _>,
+        ];
+
+        let mut remaining = max_count;
+        for clear in clears {
+            remaining -= clear(self, remaining)?;
+            if remaining == 0 {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    fn set_block_filter(
+        &mut self,
+        block_id: &Id<Block>,
+        filter: &BlockFilter,
+    ) -> crate::Result<()> {
+        self.write::<db::DBBlockFilter, _, _, _>(block_id, filter)
+    }
+
+    fn set_block_filter_header(
+        &mut self,
+        block_id: &Id<Block>,
+        header: &BlockFilterHeader,
+    ) -> crate::Result<()> {
+        self.write::<db::DBBlockFilterHeader, _, _, _>(block_id, header)
+    }
+
+    fn del_block_filter(&mut self, block_id: &Id<Block>) -> crate::Result<()> {
+        self.0.get_mut::<db::DBBlockFilter, _>().del(block_id).map_err(Into::into)
+    }
+
+    fn del_block_filter_header(&mut self, block_id: &Id<Block>) -> crate::Result<()> {
+        self.0.get_mut::<db::DBBlockFilterHeader, _>().del(block_id).map_err(Into::into)
+    }
 }
 
 impl<'st, B: storage::Backend> UtxosStorageWrite for StoreTxRw<'st, B> {
@@ -673,6 +1123,18 @@ impl<'st, B: storage::Backend> UtxosStorageWrite for StoreTxRw<'st, B> {
     fn del_undo_data(&mut self, id: Id<Block>) -> crate::Result<()> {
         self.0.get_mut::<db::DBUtxosBlockUndo, _>().del(id).map_err(Into::into)
     }
+
+    fn set_utxo_set_hash(
+        &mut self,
+        block_id: &Id<GenBlock>,
+        hash: &UtxoSetHash,
+    ) -> crate::Result<()> {
+        self.write::<db::DBUtxoSetHash, _, _, _>(block_id, hash)
+    }
+
+    fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> crate::Result<()> {
+        self.0.get_mut::<db::DBUtxoSetHash, _>().del(block_id).map_err(Into::into)
+    }
 }
 
 impl<'st, B: storage::Backend> PoSAccountingStorageWrite for StoreTxRw<'st, B> {
@@ -765,6 +1227,56 @@ impl<'st, B: storage::Backend> StoreTxRw<'st, B> {
     fn write_value<E: well_known::Entry>(&mut self, val: &E::Value) -> crate::Result<()> {
         self.write::<db::DBValue, _, _, _>(E::KEY, val.encode())
     }
+
+    // Remove the value of a well-known entry
+    fn del_value<E: well_known::Entry>(&mut self) -> crate::Result<()> {
+        self.0.get_mut::<db::DBValue, _>().del(E::KEY).map_err(Into::into)
+    }
+
+    // Remove up to `max_count` entries of a map, returns the number of removed entries. Only the
+    // keys are read, the values can be large.
+    fn clear<DbMap, I>(&mut self, max_count: usize) -> crate::Result<usize>
+    where
+        DbMap: schema::DbMap,
+        Schema: schema::HasDbMap<DbMap, I>,
+        DbMap::Key: storage::HasPrefix<()>,
+    {
+        let keys: Vec<DbMap::Key> =
+            self.0.get::<DbMap, I>().prefix_iter_keys(&())?.take(max_count).collect();
+        let mut map = self.0.get_mut::<DbMap, I>();
+        for key in &keys {
+            map.del(key)?;
+        }
+        Ok(keys.len())
+    }
+
+    // Add the block to the children of its parent, the block is a leaf of the block tree
+    // and its parent isn't anymore. Genesis is never stored as a leaf, so removing the
+    // parent is a no-op for its children.
+    fn link_block_index(&mut self, block_index: &BlockIndex) -> crate::Result<()> {
+        let parent_id = *block_index.prev_block_id();
+        let block_id = *block_index.block_id();
+        self.write::<db::DBBlockChildren, _, _, _>((parent_id, block_id), ())?;
+        self.write::<db::DBBlockLeaves, _, _, _>(block_id, ())?;
+        self.0
+            .get_mut::<db::DBBlockLeaves, _>()
+            .del(Id::<Block>::new(parent_id.get()))?;
+        Ok(())
+    }
+
+    // Size of the encoded block in the database, zero if it's not there
+    fn stored_block_size(&self, id: Id<Block>) -> crate::Result<u64> {
+        let map = self.0.get::<db::DBBlock, _>();
+        let block = map.get(id)?;
+        Ok(block.map_or(0, |block| block.bytes().len() as u64))
+    }
+
+    // Replace the size of a stored block in the running total
+    fn update_stored_blocks_size(&mut self, old_size: u64, new_size: u64) -> crate::Result<()> {
+        let total = self.read_value::<well_known::StoredBlocksSize>()?.unwrap_or_default();
+        let total = total.saturating_sub(old_size) + new_size;
+        self.write_value::<well_known::StoredBlocksSize>(&total)
+    }
 }
 
 impl<'st, B: storage::Backend> crate::TransactionRo for StoreTxRo<'st, B> {
@@ -14,6 +14,7 @@
 // limitations under the License.
 
 use super::*;
+use chainstate_types::BlockStatus;
 use common::chain::tokens::OutputValue;
 use common::chain::transaction::signed_transaction::SignedTransaction;
 use common::chain::{Destination, OutputPurpose, TxOutput};
@@ -21,6 +22,7 @@ use common::primitives::{Amount, H256};
 use crypto::key::{KeyKind, PrivateKey};
 use crypto::random::{CryptoRng, Rng};
 use rstest::rstest;
+use std::collections::BTreeSet;
 use test_utils::random::{make_seedable_rng, Seed};
 use utxo::{UtxosBlockRewardUndo, UtxosBlockUndo, UtxosTxUndoWithSources};
 
@@ -32,7 +34,10 @@ fn test_storage_get_default_version_in_tx() {
         let store = TestStore::new_empty().unwrap();
         let vtx = store.transaction_ro().unwrap().get_storage_version().unwrap();
         let vst = store.get_storage_version().unwrap();
-        assert_eq!(vtx, 1, "Default storage version wrong");
+        assert_eq!(
+            vtx, CURRENT_STORAGE_VERSION,
+            "Default storage version wrong"
+        );
         assert_eq!(vtx, vst, "Transaction and non-transaction inconsistency");
     })
 }
@@ -72,7 +77,7 @@ fn test_storage_manipulation() {
     let mut store = TestStore::new_empty().unwrap();
 
     // Storage version manipulation
-    assert_eq!(store.get_storage_version(), Ok(1));
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
     assert_eq!(store.set_storage_version(2), Ok(()));
     assert_eq!(store.get_storage_version(), Ok(2));
 
@@ -89,9 +94,24 @@ fn test_storage_manipulation() {
     assert_eq!(&store.get_block(block0.get_id()).unwrap().unwrap(), &block0);
     assert_eq!(store.del_block(block1.get_id()), Ok(()));
     assert_eq!(store.get_block(block1.get_id()), Ok(None));
+    assert_eq!(
+        store.get_stored_blocks_size(),
+        Ok(block0.encoded_size() as u64)
+    );
     assert_eq!(store.add_block(&block1), Ok(()));
     assert_eq!(&store.get_block(block0.get_id()).unwrap().unwrap(), &block0);
 
+    // The size of the stored blocks is tracked, adding the same block twice doesn't change it
+    let blocks_size = (block0.encoded_size() + block1.encoded_size()) as u64;
+    assert_eq!(store.get_stored_blocks_size(), Ok(blocks_size));
+    assert_eq!(store.add_block(&block1), Ok(()));
+    assert_eq!(store.get_stored_blocks_size(), Ok(blocks_size));
+
+    // Pruned height manipulation
+    assert_eq!(store.get_pruned_height(), Ok(None));
+    assert_eq!(store.set_pruned_height(&BlockHeight::new(1)), Ok(()));
+    assert_eq!(store.get_pruned_height(), Ok(Some(BlockHeight::new(1))));
+
     // Test the transaction extraction from a block
     let enc_tx0 = tx0.encode();
     let enc_block0 = block0.encode();
@@ -344,3 +364,290 @@ fn undo_test(#[case] seed: Seed) {
     assert_eq!(store.set_undo_data(id1, &block_undo1), Ok(()));
     assert_eq!(store.get_undo_data(id1).unwrap().unwrap(), block_undo1);
 }
+
+// Each step checks that the previous one has been applied, then records its own version
+fn record_migration(
+    db_tx: &mut StoreTxRw<'_, storage::inmemory::InMemory>,
+    version: u32,
+) -> crate::Result<()> {
+    let previous = db_tx.get_pruned_height()?;
+    assert_eq!(previous, Some(BlockHeight::new(version as u64 - 1)));
+    db_tx.set_pruned_height(&BlockHeight::new(version as u64))
+}
+
+fn test_migrations() -> Vec<migration::Migration<storage::inmemory::InMemory>> {
+    vec![
+        migration::Migration {
+            from_version: 1,
+            description: "first",
+            migrate: |db_tx| record_migration(db_tx, 1),
+        },
+        migration::Migration {
+            from_version: 2,
+            description: "second",
+            migrate: |db_tx| record_migration(db_tx, 2),
+        },
+        migration::Migration {
+            from_version: 3,
+            description: "third",
+            migrate: |db_tx| record_migration(db_tx, 3),
+        },
+    ]
+}
+
+#[test]
+fn migrations_are_applied_in_order() {
+    utils::concurrency::model(|| {
+        let mut store = TestStore::new_empty().unwrap();
+        store.set_storage_version(2).unwrap();
+        store.set_pruned_height(&BlockHeight::new(1)).unwrap();
+
+        migration::migrate(&store, &test_migrations(), 4).unwrap();
+        assert_eq!(store.get_storage_version(), Ok(4));
+        assert_eq!(store.get_pruned_height(), Ok(Some(BlockHeight::new(3))));
+
+        // Nothing left to do
+        migration::migrate(&store, &test_migrations(), 4).unwrap();
+        assert_eq!(store.get_pruned_height(), Ok(Some(BlockHeight::new(3))));
+
+        // A database written by a newer version is refused
+        assert_eq!(
+            migration::migrate(&store, &test_migrations()[..2], 3),
+            Err(crate::Error::UnsupportedStorageVersion(4, 3))
+        );
+    })
+}
+
+#[test]
+fn migrations_cover_all_versions() {
+    let migrations = migration::migrations::<storage::inmemory::InMemory>();
+    let versions: Vec<u32> = migrations.iter().map(|m| m.from_version).collect();
+    assert_eq!(versions, (1..CURRENT_STORAGE_VERSION).collect::<Vec<_>>());
+}
+
+// Block index encoded as in version 1 databases, without the validation status
+struct BlockIndexV1Bytes(Vec<u8>);
+
+impl Encode for BlockIndexV1Bytes {
+    fn encode_to<T: serialization::Output + ?Sized>(&self, dest: &mut T) {
+        dest.write(&self.0)
+    }
+}
+
+impl serialization::EncodeLike<BlockIndex> for BlockIndexV1Bytes {}
+
+#[test]
+#[cfg(not(loom))]
+fn block_index_status_migration() {
+    use common::{
+        chain::block::{timestamp::BlockTimestamp, BlockReward, ConsensusData},
+        Uint256,
+    };
+
+    let make_block_index = |timestamp| {
+        let block = Block::new(
+            vec![],
+            Id::new(H256::default()),
+            BlockTimestamp::from_int_seconds(timestamp),
+            ConsensusData::None,
+            BlockReward::new(Vec::new()),
+        )
+        .unwrap();
+        BlockIndex::new(
+            block.header(),
+            Uint256::from_u64(1),
+            Id::new(H256::default()),
+            BlockHeight::new(1),
+            block.timestamp(),
+        )
+    };
+    let mainchain_index = make_block_index(12);
+    let stale_index = make_block_index(34);
+
+    let store = TestStore::new_empty().unwrap();
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    for block_index in [&mainchain_index, &stale_index] {
+        let mut encoded = block_index.encode();
+        encoded.truncate(encoded.len() - BlockStatus::new().encoded_size());
+        db_tx
+            .0
+            .get_mut::<db::DBBlockIndex, _>()
+            .put(block_index.block_id(), BlockIndexV1Bytes(encoded))
+            .unwrap();
+    }
+    db_tx
+        .set_block_id_at_height(&BlockHeight::new(1), &(*mainchain_index.block_id()).into())
+        .unwrap();
+    db_tx.set_storage_version(1).unwrap();
+    db_tx.commit().unwrap();
+
+    migration::migrate(&store, &migration::migrations(), CURRENT_STORAGE_VERSION).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+
+    // The block on the main chain has been connected, the other one only has a valid header
+    let mainchain_status =
+        store.get_block_index(mainchain_index.block_id()).unwrap().unwrap().status();
+    assert!(mainchain_status.has_valid_transactions());
+    let stale_status = store.get_block_index(stale_index.block_id()).unwrap().unwrap().status();
+    assert_eq!(stale_status, BlockStatus::new());
+    assert!(stale_status.has_valid_header());
+}
+
+#[test]
+#[cfg(not(loom))]
+fn stored_blocks_size_migration() {
+    use common::chain::block::{timestamp::BlockTimestamp, BlockReward, ConsensusData};
+
+    let blocks: Vec<Block> = (0..3)
+        .map(|timestamp| {
+            Block::new(
+                vec![],
+                Id::new(H256::default()),
+                BlockTimestamp::from_int_seconds(timestamp),
+                ConsensusData::None,
+                BlockReward::new(Vec::new()),
+            )
+            .unwrap()
+        })
+        .collect();
+    let blocks_size: u64 = blocks.iter().map(|block| block.encoded_size() as u64).sum();
+
+    // Version 3 databases have the blocks but not their total size
+    let store = TestStore::new_empty().unwrap();
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    for block in &blocks {
+        db_tx.add_block(block).unwrap();
+    }
+    db_tx.del_value::<well_known::StoredBlocksSize>().unwrap();
+    db_tx.set_storage_version(3).unwrap();
+    db_tx.commit().unwrap();
+    assert_eq!(store.get_stored_blocks_size(), Ok(0));
+
+    migration::migrate(&store, &migration::migrations(), CURRENT_STORAGE_VERSION).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    assert_eq!(store.get_stored_blocks_size(), Ok(blocks_size));
+}
+
+#[test]
+fn utxo_snapshot_validation_state_migration() {
+    let checkpoint = TestStore::new_empty().unwrap();
+    let mut dump = Vec::new();
+    checkpoint.write_dump(&mut dump).unwrap();
+    let expected: BTreeMap<_, _> = checkpoint
+        .dump_raw()
+        .unwrap()
+        .into_iter()
+        .flat_map(|(idx, map)| {
+            map.into_iter().map(move |(key, value)| ((idx.as_usize() as u32, key), value))
+        })
+        .collect();
+    assert!(!expected.is_empty());
+
+    // Version 4 databases save the whole checkpoint as a single value
+    let store = TestStore::new_empty().unwrap();
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    db_tx
+        .write::<db::DBValue, _, _, _>(b"UtxoSnapshotValidationState".as_slice(), dump.encode())
+        .unwrap();
+    db_tx.set_storage_version(4).unwrap();
+    db_tx.commit().unwrap();
+
+    migration::migrate(&store, &migration::migrations(), CURRENT_STORAGE_VERSION).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    assert_eq!(store.get_utxo_snapshot_validation_entries(), Ok(expected));
+    let db_tx = store.transaction_ro().unwrap();
+    assert_eq!(
+        db_tx
+            .0
+            .get::<db::DBValue, _>()
+            .get(b"UtxoSnapshotValidationState".as_slice())
+            .unwrap(),
+        None
+    );
+}
+
+// Block tree with two branches: genesis <- a <- b and a <- c
+#[cfg(not(loom))]
+fn make_block_tree() -> Vec<BlockIndex> {
+    use common::{
+        chain::block::{timestamp::BlockTimestamp, BlockReward, ConsensusData},
+        Uint256,
+    };
+
+    let make_block_index = |prev_block_id: Id<GenBlock>, timestamp, height| {
+        let block = Block::new(
+            vec![],
+            prev_block_id,
+            BlockTimestamp::from_int_seconds(timestamp),
+            ConsensusData::None,
+            BlockReward::new(Vec::new()),
+        )
+        .unwrap();
+        BlockIndex::new(
+            block.header(),
+            Uint256::from_u64(height),
+            prev_block_id,
+            BlockHeight::new(height),
+            block.timestamp(),
+        )
+    };
+    let a = make_block_index(Id::new(H256::default()), 1, 1);
+    let b = make_block_index((*a.block_id()).into(), 2, 2);
+    let c = make_block_index((*a.block_id()).into(), 3, 2);
+    vec![a, b, c]
+}
+
+#[test]
+#[cfg(not(loom))]
+fn block_tree_links() {
+    let store = TestStore::new_empty().unwrap();
+    let genesis_id = Id::new(H256::default());
+    let block_tree = make_block_tree();
+    let [a, b, c] = [0, 1, 2].map(|i| *block_tree[i].block_id());
+
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    db_tx.set_block_index(&block_tree[0]).unwrap();
+    assert_eq!(db_tx.get_block_children(&genesis_id), Ok(vec![a]));
+    assert_eq!(db_tx.get_block_tree_leaves(), Ok(vec![a]));
+
+    for block_index in &block_tree[1..] {
+        db_tx.set_block_index(block_index).unwrap();
+    }
+    // Updating an index doesn't change the links
+    db_tx.set_block_index(&block_tree[0]).unwrap();
+    db_tx.commit().unwrap();
+
+    let children: BTreeSet<_> = store.get_block_children(&a.into()).unwrap().into_iter().collect();
+    assert_eq!(children, BTreeSet::from([b, c]));
+    assert_eq!(store.get_block_children(&b.into()), Ok(vec![]));
+    let leaves: BTreeSet<_> = store.get_block_tree_leaves().unwrap().into_iter().collect();
+    assert_eq!(leaves, BTreeSet::from([b, c]));
+}
+
+#[test]
+#[cfg(not(loom))]
+fn block_tree_links_migration() {
+    let genesis_id = Id::new(H256::default());
+    let block_tree = make_block_tree();
+    let [a, b, c] = [0, 1, 2].map(|i| *block_tree[i].block_id());
+
+    // Version 5 databases have the block indexes but no links between them
+    let store = TestStore::new_empty().unwrap();
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    for block_index in &block_tree {
+        db_tx.set_block_index(block_index).unwrap();
+    }
+    db_tx.clear::<db::DBBlockChildren, _>(usize::MAX).unwrap();
+    db_tx.clear::<db::DBBlockLeaves, _>(usize::MAX).unwrap();
+    db_tx.set_storage_version(5).unwrap();
+    db_tx.commit().unwrap();
+    assert_eq!(store.get_block_tree_leaves(), Ok(vec![]));
+
+    migration::migrate(&store, &migration::migrations(), CURRENT_STORAGE_VERSION).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    assert_eq!(store.get_block_children(&genesis_id), Ok(vec![a]));
+    let children: BTreeSet<_> = store.get_block_children(&a.into()).unwrap().into_iter().collect();
+    assert_eq!(children, BTreeSet::from([b, c]));
+    let leaves: BTreeSet<_> = store.get_block_tree_leaves().unwrap().into_iter().collect();
+    assert_eq!(leaves, BTreeSet::from([b, c]));
+}
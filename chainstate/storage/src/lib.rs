@@ -23,23 +23,35 @@ pub mod schema;
 
 use std::collections::BTreeMap;
 
-pub use internal::{utxo_db, Store};
-
-use chainstate_types::BlockIndex;
-use common::chain::block::BlockReward;
-use common::chain::tokens::{TokenAuxiliaryData, TokenId};
+pub use internal::{utxo_db, Store, CURRENT_STORAGE_VERSION};
+pub use storage::BackupFormat;
+
+use chainstate_types::{AddressIndexKey, AddressOutputInfo, BlockIndex, OutputSpendInfo};
+use common::chain::block::{
+    block_filter::{BlockFilter, BlockFilterHeader},
+    BlockReward,
+};
+use common::chain::tokens::{CoinOrTokenId, TokenAuxiliaryData, TokenId};
 use common::chain::transaction::{Transaction, TxMainChainIndex, TxMainChainPosition};
-use common::chain::{Block, GenBlock, OutPointSourceId};
+use common::chain::{Block, Destination, GenBlock, OutPoint, OutPointSourceId};
 use common::primitives::{BlockHeight, Id};
-use pos_accounting::{AccountingBlockUndo, PoSAccountingStorageRead, PoSAccountingStorageWrite};
-use utxo::{UtxosStorageRead, UtxosStorageWrite};
+use pos_accounting::{
+    AccountingBlockUndo, PoSAccountingData, PoSAccountingStorageRead, PoSAccountingStorageWrite,
+};
+use utxo::{Utxo, UtxosStorageRead, UtxosStorageWrite};
 
 /// Possibly failing result of blockchain storage query
 pub type Result<T> = chainstate_types::storage_result::Result<T>;
 pub type Error = chainstate_types::storage_result::Error;
 
+/// The index of a map and a key in it, for the entries of the in-memory chainstate that validates
+/// the history of a utxo snapshot
+pub type UtxoSnapshotValidationKey = (u32, Vec<u8>);
+pub type UtxoSnapshotValidationEntries = BTreeMap<UtxoSnapshotValidationKey, Vec<u8>>;
+
 pub mod inmemory {
     pub type Store = super::Store<storage::inmemory::InMemory>;
+    pub use storage::inmemory::{InMemory, JournalHandle};
 }
 
 /// Queries on persistent blockchain data
@@ -83,8 +95,78 @@ pub trait BlockchainStorageRead: UtxosStorageRead + PoSAccountingStorageRead {
     /// Get block tree as height vs ids
     fn get_block_tree_by_height(&self) -> crate::Result<BTreeMap<BlockHeight, Vec<Id<Block>>>>;
 
+    /// Get the ids of the stored blocks whose parent is the given block
+    fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>>;
+
+    /// Get the ids of the stored blocks without children, the tips of the block tree
+    fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>>;
+
     /// Get accounting undo for specific block
     fn get_accounting_undo(&self, id: Id<Block>) -> crate::Result<Option<AccountingBlockUndo>>;
+
+    /// Get the height of the last mainchain block whose body and undo data were pruned
+    fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>>;
+
+    /// Get the total encoded size of the stored blocks
+    fn get_stored_blocks_size(&self) -> crate::Result<u64>;
+
+    /// Get the whole utxo set
+    fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>>;
+
+    /// Get all the accounting data
+    fn get_accounting_data(&self) -> crate::Result<PoSAccountingData>;
+
+    /// Get the auxiliary data of all the tokens
+    fn get_all_token_aux_data(&self) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>>;
+
+    /// Get all the token ids by the ids of their issuance txs
+    fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>>;
+
+    /// Get the block of the loaded utxo snapshot whose history is not validated yet
+    fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+
+    fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>>;
+
+    /// Get an output recorded in the address index
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> crate::Result<Option<AddressOutputInfo>>;
+
+    /// Get all the outputs recorded in the address index for given destination
+    fn get_address_index_entries(
+        &self,
+        destination: &Destination,
+    ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>>;
+
+    fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>>;
+
+    /// Get the input that spent given output, if spent-output indexing is enabled
+    fn get_output_spend_info(&self, outpoint: &OutPoint) -> crate::Result<Option<OutputSpendInfo>>;
+
+    fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>>;
+
+    /// Whether the chainstate keeps only the block headers
+    fn get_is_headers_only(&self) -> crate::Result<Option<bool>>;
+
+    /// Get the tip of the stored main chain that an unfinished reindex is reconnecting
+    fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>>;
+
+    /// Get the saved state of the utxo snapshot history validation, the raw entries of its
+    /// database by the index of their key-value map and their key
+    fn get_utxo_snapshot_validation_entries(&self) -> crate::Result<UtxoSnapshotValidationEntries>;
+
+    /// Get the utxo snapshot block whose history was found to be invalid
+    fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+
+    /// Get the compact filter of a main chain block, if block filters are enabled
+    fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>>;
+
+    /// Get the filter header of a main chain block, if block filters are enabled
+    fn get_block_filter_header(
+        &self,
+        block_id: &Id<Block>,
+    ) -> crate::Result<Option<BlockFilterHeader>>;
 }
 
 /// Modifying operations on persistent blockchain data
@@ -98,6 +180,7 @@ pub trait BlockchainStorageWrite:
     fn set_best_block_id(&mut self, id: &Id<GenBlock>) -> crate::Result<()>;
 
     // Set the block index
+    /// Set the block index. A new block is linked to its parent in the block tree.
     fn set_block_index(&mut self, block_index: &BlockIndex) -> crate::Result<()>;
 
     /// Add a new block into the database
@@ -158,6 +241,97 @@ pub trait BlockchainStorageWrite:
 
     // Remove accounting block undo data for specific block
     fn del_accounting_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+
+    /// Set the height of the last mainchain block whose body and undo data were pruned
+    fn set_pruned_height(&mut self, height: &BlockHeight) -> crate::Result<()>;
+
+    /// Set the block of the loaded utxo snapshot whose history is not validated yet
+    fn set_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+
+    /// Forget the utxo snapshot block once its history is validated
+    fn del_utxo_snapshot_block(&mut self) -> crate::Result<()>;
+
+    /// Change address indexing state flag
+    fn set_is_address_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+
+    /// Set an output recorded in the address index
+    fn set_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+        info: &AddressOutputInfo,
+    ) -> crate::Result<()>;
+
+    /// Remove an output from the address index
+    fn del_address_index_entry(&mut self, key: &AddressIndexKey) -> crate::Result<()>;
+
+    /// Change spent-output indexing state flag
+    fn set_is_spent_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+
+    /// Set the input that spent given output
+    fn set_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+        info: &OutputSpendInfo,
+    ) -> crate::Result<()>;
+
+    /// Remove the spending of given output from the spent-output index
+    fn del_output_spend_info(&mut self, outpoint: &OutPoint) -> crate::Result<()>;
+
+    /// Change block filters state flag
+    fn set_is_block_filters_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+
+    /// Change headers-only state flag
+    fn set_is_headers_only(&mut self, headers_only: bool) -> crate::Result<()>;
+
+    /// Set the tip of the stored main chain that a reindex reconnects
+    fn set_reindex_target(&mut self, id: &Id<Block>) -> crate::Result<()>;
+
+    /// Forget the reindex target once the reindex is finished
+    fn del_reindex_target(&mut self) -> crate::Result<()>;
+
+    /// Save an entry of the database of the utxo snapshot history validation
+    fn set_utxo_snapshot_validation_entry(
+        &mut self,
+        key: &UtxoSnapshotValidationKey,
+        value: &[u8],
+    ) -> crate::Result<()>;
+
+    /// Remove an entry of the database of the utxo snapshot history validation
+    fn del_utxo_snapshot_validation_entry(
+        &mut self,
+        key: &UtxoSnapshotValidationKey,
+    ) -> crate::Result<()>;
+
+    /// Forget the utxo snapshot history validation state once the validation is over
+    fn del_utxo_snapshot_validation_entries(&mut self) -> crate::Result<()>;
+
+    /// Record that the history of the given utxo snapshot block is invalid
+    fn set_invalid_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+
+    /// Remove the main chain heights and everything derived from connecting the blocks: the
+    /// utxo set and its hashes, the undo data, the PoS accounting data, the token data and the
+    /// indexes. The blocks and the block indexes are kept.
+    ///
+    /// At most `max_count` entries are removed, so that the work can be split into several
+    /// transactions. Returns true once there is nothing left to remove.
+    fn del_derived_data(&mut self, max_count: usize) -> crate::Result<bool>;
+
+    /// Set the compact filter of a block
+    fn set_block_filter(&mut self, block_id: &Id<Block>, filter: &BlockFilter)
+        -> crate::Result<()>;
+
+    /// Set the filter header of a block
+    fn set_block_filter_header(
+        &mut self,
+        block_id: &Id<Block>,
+        header: &BlockFilterHeader,
+    ) -> crate::Result<()>;
+
+    /// Remove the compact filter of a block
+    fn del_block_filter(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
+
+    /// Remove the filter header of a block
+    fn del_block_filter_header(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
 }
 
 /// Marker trait for types where read/write operations are run in a transaction
@@ -193,4 +367,35 @@ pub trait Transactional<'t> {
     fn transaction_rw<'s: 't>(&'s self, size: Option<usize>) -> crate::Result<Self::TransactionRw>;
 }
 
-pub trait BlockchainStorage: BlockchainStorageWrite + for<'tx> Transactional<'tx> + Send {}
+pub trait BlockchainStorage: BlockchainStorageWrite + for<'tx> Transactional<'tx> + Send {
+    /// Get a handle to the storage which can write backups independently of this object
+    fn backup_handle(&self) -> Box<dyn BlockchainStorageBackup>;
+
+    /// Get a handle to the storage which can save the state of the utxo snapshot history
+    /// validation independently of this object
+    fn utxo_snapshot_validation_handle(&self) -> Box<dyn UtxoSnapshotValidationStorage>;
+}
+
+/// Saving the state of the utxo snapshot history validation, which runs on its own thread.
+/// Each method writes in its own transaction.
+pub trait UtxoSnapshotValidationStorage: Send {
+    /// Apply the changes of the validation database since the last checkpoint, `None` values
+    /// are removed entries
+    fn save_utxo_snapshot_validation_changes(
+        &self,
+        changes: &[(UtxoSnapshotValidationKey, Option<Vec<u8>>)],
+    ) -> crate::Result<()>;
+
+    /// Forget the loaded utxo snapshot and the validation state once the history is validated
+    fn finish_utxo_snapshot_validation(&self) -> crate::Result<()>;
+
+    /// Record that the history of the given utxo snapshot block is invalid, the validation state
+    /// is of no use anymore
+    fn invalidate_utxo_snapshot(&self, block_id: &Id<Block>) -> crate::Result<()>;
+}
+
+/// Writing backups of the blockchain storage
+pub trait BlockchainStorageBackup: Send {
+    /// Write a consistent backup of the whole storage to given path, which must not exist yet
+    fn backup(&self, path: &std::path::Path, format: BackupFormat) -> crate::Result<()>;
+}
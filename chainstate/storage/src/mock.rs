@@ -17,21 +17,24 @@
 
 use std::collections::BTreeMap;
 
-use chainstate_types::BlockIndex;
-use common::chain::tokens::{TokenAuxiliaryData, TokenId};
+use chainstate_types::{AddressIndexKey, AddressOutputInfo, BlockIndex, OutputSpendInfo};
+use common::chain::tokens::{CoinOrTokenId, TokenAuxiliaryData, TokenId};
 use common::{
     chain::{
-        block::BlockReward,
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            BlockReward,
+        },
         transaction::{OutPointSourceId, Transaction, TxMainChainIndex, TxMainChainPosition},
-        Block, GenBlock, OutPoint,
+        Block, Destination, GenBlock, OutPoint,
     },
     primitives::{Amount, BlockHeight, Id},
 };
 use pos_accounting::{
-    AccountingBlockUndo, DelegationData, DelegationId, PoSAccountingStorageRead,
+    AccountingBlockUndo, DelegationData, DelegationId, PoSAccountingData, PoSAccountingStorageRead,
     PoSAccountingStorageWrite, PoolData, PoolId,
 };
-use utxo::{Utxo, UtxosBlockUndo, UtxosStorageRead, UtxosStorageWrite};
+use utxo::{Utxo, UtxoSetHash, UtxosBlockUndo, UtxosStorageRead, UtxosStorageWrite};
 
 mockall::mock! {
     /// A mock object for blockchain storage
@@ -68,14 +71,49 @@ mockall::mock! {
         fn get_block_tree_by_height(
             &self,
         ) -> crate::Result<BTreeMap<BlockHeight, Vec<Id<Block>>>>;
+        fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>>;
+        fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>>;
 
         fn get_accounting_undo(&self, id: Id<Block>) -> crate::Result<Option<AccountingBlockUndo>>;
+        fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>>;
+        fn get_stored_blocks_size(&self) -> crate::Result<u64>;
+        fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>>;
+        fn get_accounting_data(&self) -> crate::Result<PoSAccountingData>;
+        fn get_all_token_aux_data(&self) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>>;
+        fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>>;
+        fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_address_index_entry(
+            &self,
+            key: &AddressIndexKey,
+        ) -> crate::Result<Option<AddressOutputInfo>>;
+        fn get_address_index_entries(
+            &self,
+            destination: &Destination,
+        ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>>;
+
+        fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_output_spend_info(&self, outpoint: &OutPoint) -> crate::Result<Option<OutputSpendInfo>>;
+
+        fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_is_headers_only(&self) -> crate::Result<Option<bool>>;
+        fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_utxo_snapshot_validation_entries(
+            &self,
+        ) -> crate::Result<crate::UtxoSnapshotValidationEntries>;
+        fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>>;
+        fn get_block_filter_header(
+            &self,
+            block_id: &Id<Block>,
+        ) -> crate::Result<Option<BlockFilterHeader>>;
     }
 
     impl UtxosStorageRead for Store {
         fn get_utxo(&self, outpoint: &OutPoint) -> crate::Result<Option<Utxo>>;
         fn get_best_block_for_utxos(&self) -> crate::Result<Option<Id<GenBlock>>>;
         fn get_undo_data(&self, id: Id<Block>) -> crate::Result<Option<UtxosBlockUndo>>;
+        fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> crate::Result<Option<UtxoSetHash>>;
     }
 
     impl PoSAccountingStorageRead for Store {
@@ -123,6 +161,46 @@ mockall::mock! {
 
         fn set_accounting_undo_data(&mut self, id: Id<Block>, undo: &AccountingBlockUndo) -> crate::Result<()>;
         fn del_accounting_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+        fn set_pruned_height(&mut self, height: &BlockHeight) -> crate::Result<()>;
+        fn set_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_utxo_snapshot_block(&mut self) -> crate::Result<()>;
+        fn set_is_address_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_address_index_entry(
+            &mut self,
+            key: &AddressIndexKey,
+            info: &AddressOutputInfo,
+        ) -> crate::Result<()>;
+        fn del_address_index_entry(&mut self, key: &AddressIndexKey) -> crate::Result<()>;
+
+        fn set_is_spent_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_output_spend_info(
+            &mut self,
+            outpoint: &OutPoint,
+            info: &OutputSpendInfo,
+        ) -> crate::Result<()>;
+        fn del_output_spend_info(&mut self, outpoint: &OutPoint) -> crate::Result<()>;
+
+        fn set_is_block_filters_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_is_headers_only(&mut self, headers_only: bool) -> crate::Result<()>;
+        fn set_reindex_target(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_reindex_target(&mut self) -> crate::Result<()>;
+        fn set_utxo_snapshot_validation_entry(
+            &mut self,
+            key: &crate::UtxoSnapshotValidationKey,
+            value: &[u8],
+        ) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entry(&mut self, key: &crate::UtxoSnapshotValidationKey) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entries(&mut self) -> crate::Result<()>;
+        fn set_invalid_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_derived_data(&mut self, max_count: usize) -> crate::Result<bool>;
+        fn set_block_filter(&mut self, block_id: &Id<Block>, filter: &BlockFilter) -> crate::Result<()>;
+        fn set_block_filter_header(
+            &mut self,
+            block_id: &Id<Block>,
+            header: &BlockFilterHeader,
+        ) -> crate::Result<()>;
+        fn del_block_filter(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
+        fn del_block_filter_header(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
     }
 
     impl UtxosStorageWrite for Store {
@@ -133,6 +211,8 @@ mockall::mock! {
 
         fn set_undo_data(&mut self, id: Id<Block>, undo: &UtxosBlockUndo) -> crate::Result<()>;
         fn del_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+        fn set_utxo_set_hash(&mut self, block_id: &Id<GenBlock>, hash: &UtxoSetHash) -> crate::Result<()>;
+        fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> crate::Result<()>;
     }
 
     impl PoSAccountingStorageWrite for Store {
@@ -186,7 +266,12 @@ mockall::mock! {
         fn transaction_rw<'st>(&'st self, size: Option<usize>) -> crate::Result<MockStoreTxRw> where 'st: 'tx;
     }
 
-    impl crate::BlockchainStorage for Store {}
+    impl crate::BlockchainStorage for Store {
+        fn backup_handle(&self) -> Box<dyn crate::BlockchainStorageBackup>;
+        fn utxo_snapshot_validation_handle(
+            &self,
+        ) -> Box<dyn crate::UtxoSnapshotValidationStorage>;
+    }
 }
 
 mockall::mock! {
@@ -221,14 +306,49 @@ mockall::mock! {
         fn get_block_tree_by_height(
             &self,
         ) -> crate::Result<BTreeMap<BlockHeight, Vec<Id<Block>>>>;
+        fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>>;
+        fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>>;
 
         fn get_accounting_undo(&self, id: Id<Block>) -> crate::Result<Option<AccountingBlockUndo>>;
+        fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>>;
+        fn get_stored_blocks_size(&self) -> crate::Result<u64>;
+        fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>>;
+        fn get_accounting_data(&self) -> crate::Result<PoSAccountingData>;
+        fn get_all_token_aux_data(&self) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>>;
+        fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>>;
+        fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_address_index_entry(
+            &self,
+            key: &AddressIndexKey,
+        ) -> crate::Result<Option<AddressOutputInfo>>;
+        fn get_address_index_entries(
+            &self,
+            destination: &Destination,
+        ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>>;
+
+        fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_output_spend_info(&self, outpoint: &OutPoint) -> crate::Result<Option<OutputSpendInfo>>;
+
+        fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_is_headers_only(&self) -> crate::Result<Option<bool>>;
+        fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_utxo_snapshot_validation_entries(
+            &self,
+        ) -> crate::Result<crate::UtxoSnapshotValidationEntries>;
+        fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>>;
+        fn get_block_filter_header(
+            &self,
+            block_id: &Id<Block>,
+        ) -> crate::Result<Option<BlockFilterHeader>>;
     }
 
     impl crate::UtxosStorageRead for StoreTxRo {
         fn get_utxo(&self, outpoint: &OutPoint) -> crate::Result<Option<Utxo>>;
         fn get_best_block_for_utxos(&self) -> crate::Result<Option<Id<GenBlock>>>;
         fn get_undo_data(&self, id: Id<Block>) -> crate::Result<Option<UtxosBlockUndo>>;
+        fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> crate::Result<Option<UtxoSetHash>>;
     }
 
     impl PoSAccountingStorageRead for StoreTxRo {
@@ -287,14 +407,49 @@ mockall::mock! {
         fn get_block_tree_by_height(
             &self,
         ) -> crate::Result<BTreeMap<BlockHeight, Vec<Id<Block>>>>;
+        fn get_block_children(&self, id: &Id<GenBlock>) -> crate::Result<Vec<Id<Block>>>;
+        fn get_block_tree_leaves(&self) -> crate::Result<Vec<Id<Block>>>;
 
         fn get_accounting_undo(&self, id: Id<Block>) -> crate::Result<Option<AccountingBlockUndo>>;
+        fn get_pruned_height(&self) -> crate::Result<Option<BlockHeight>>;
+        fn get_stored_blocks_size(&self) -> crate::Result<u64>;
+        fn get_utxo_set(&self) -> crate::Result<BTreeMap<OutPoint, Utxo>>;
+        fn get_accounting_data(&self) -> crate::Result<PoSAccountingData>;
+        fn get_all_token_aux_data(&self) -> crate::Result<BTreeMap<TokenId, TokenAuxiliaryData>>;
+        fn get_all_token_ids(&self) -> crate::Result<BTreeMap<Id<Transaction>, TokenId>>;
+        fn get_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_is_address_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_address_index_entry(
+            &self,
+            key: &AddressIndexKey,
+        ) -> crate::Result<Option<AddressOutputInfo>>;
+        fn get_address_index_entries(
+            &self,
+            destination: &Destination,
+        ) -> crate::Result<BTreeMap<(CoinOrTokenId, OutPoint), AddressOutputInfo>>;
+
+        fn get_is_spent_index_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_output_spend_info(&self, outpoint: &OutPoint) -> crate::Result<Option<OutputSpendInfo>>;
+
+        fn get_is_block_filters_enabled(&self) -> crate::Result<Option<bool>>;
+        fn get_is_headers_only(&self) -> crate::Result<Option<bool>>;
+        fn get_reindex_target(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_utxo_snapshot_validation_entries(
+            &self,
+        ) -> crate::Result<crate::UtxoSnapshotValidationEntries>;
+        fn get_invalid_utxo_snapshot_block(&self) -> crate::Result<Option<Id<Block>>>;
+        fn get_block_filter(&self, block_id: &Id<Block>) -> crate::Result<Option<BlockFilter>>;
+        fn get_block_filter_header(
+            &self,
+            block_id: &Id<Block>,
+        ) -> crate::Result<Option<BlockFilterHeader>>;
     }
 
     impl UtxosStorageRead for StoreTxRw {
         fn get_utxo(&self, outpoint: &OutPoint) -> crate::Result<Option<Utxo>>;
         fn get_best_block_for_utxos(&self) -> crate::Result<Option<Id<GenBlock>>>;
         fn get_undo_data(&self, id: Id<Block>) -> crate::Result<Option<UtxosBlockUndo>>;
+        fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> crate::Result<Option<UtxoSetHash>>;
     }
 
     impl PoSAccountingStorageRead for StoreTxRw {
@@ -343,6 +498,46 @@ mockall::mock! {
 
         fn set_accounting_undo_data(&mut self, id: Id<Block>, undo: &AccountingBlockUndo) -> crate::Result<()>;
         fn del_accounting_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+        fn set_pruned_height(&mut self, height: &BlockHeight) -> crate::Result<()>;
+        fn set_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_utxo_snapshot_block(&mut self) -> crate::Result<()>;
+        fn set_is_address_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_address_index_entry(
+            &mut self,
+            key: &AddressIndexKey,
+            info: &AddressOutputInfo,
+        ) -> crate::Result<()>;
+        fn del_address_index_entry(&mut self, key: &AddressIndexKey) -> crate::Result<()>;
+
+        fn set_is_spent_index_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_output_spend_info(
+            &mut self,
+            outpoint: &OutPoint,
+            info: &OutputSpendInfo,
+        ) -> crate::Result<()>;
+        fn del_output_spend_info(&mut self, outpoint: &OutPoint) -> crate::Result<()>;
+
+        fn set_is_block_filters_enabled(&mut self, enabled: bool) -> crate::Result<()>;
+        fn set_is_headers_only(&mut self, headers_only: bool) -> crate::Result<()>;
+        fn set_reindex_target(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_reindex_target(&mut self) -> crate::Result<()>;
+        fn set_utxo_snapshot_validation_entry(
+            &mut self,
+            key: &crate::UtxoSnapshotValidationKey,
+            value: &[u8],
+        ) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entry(&mut self, key: &crate::UtxoSnapshotValidationKey) -> crate::Result<()>;
+        fn del_utxo_snapshot_validation_entries(&mut self) -> crate::Result<()>;
+        fn set_invalid_utxo_snapshot_block(&mut self, id: &Id<Block>) -> crate::Result<()>;
+        fn del_derived_data(&mut self, max_count: usize) -> crate::Result<bool>;
+        fn set_block_filter(&mut self, block_id: &Id<Block>, filter: &BlockFilter) -> crate::Result<()>;
+        fn set_block_filter_header(
+            &mut self,
+            block_id: &Id<Block>,
+            header: &BlockFilterHeader,
+        ) -> crate::Result<()>;
+        fn del_block_filter(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
+        fn del_block_filter_header(&mut self, block_id: &Id<Block>) -> crate::Result<()>;
     }
 
     impl UtxosStorageWrite for StoreTxRw {
@@ -353,6 +548,8 @@ mockall::mock! {
 
         fn set_undo_data(&mut self, id: Id<Block>, undo: &UtxosBlockUndo) -> crate::Result<()>;
         fn del_undo_data(&mut self, id: Id<Block>) -> crate::Result<()>;
+        fn set_utxo_set_hash(&mut self, block_id: &Id<GenBlock>, hash: &UtxoSetHash) -> crate::Result<()>;
+        fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> crate::Result<()>;
     }
 
     impl PoSAccountingStorageWrite for StoreTxRw {
@@ -15,16 +15,17 @@
 
 //! Chainstate database schema
 
-use chainstate_types::BlockIndex;
+use chainstate_types::{AddressIndexKey, AddressOutputInfo, BlockIndex, OutputSpendInfo};
 use common::{
     chain::{
+        block::block_filter::{BlockFilter, BlockFilterHeader},
         tokens::{TokenAuxiliaryData, TokenId},
         Block, GenBlock, OutPoint, OutPointSourceId, Transaction, TxMainChainIndex,
     },
     primitives::{Amount, BlockHeight, Id},
 };
 use pos_accounting::{AccountingBlockUndo, DelegationData, DelegationId, PoolData, PoolId};
-use utxo::{Utxo, UtxosBlockUndo};
+use utxo::{Utxo, UtxoSetHash, UtxosBlockUndo};
 
 storage::decl_schema! {
     /// Database schema for blockchain storage
@@ -35,6 +36,10 @@ storage::decl_schema! {
         pub DBBlock: Map<Id<Block>, Block>,
         /// Store tag for blocks indexes.
         pub DBBlockIndex: Map<Id<Block>, BlockIndex>,
+        /// Links from the blocks to their children, keyed by the parent and the child
+        pub DBBlockChildren: Map<(Id<GenBlock>, Id<Block>), ()>,
+        /// The blocks without children
+        pub DBBlockLeaves: Map<Id<Block>, ()>,
         /// Storage for transaction indices.
         pub DBTxIndex: Map<OutPointSourceId, TxMainChainIndex>,
         /// Storage for block IDs indexed by block height.
@@ -43,10 +48,23 @@ storage::decl_schema! {
         pub DBUtxo: Map<OutPoint, Utxo>,
         /// Store for utxo BlockUndo
         pub DBUtxosBlockUndo: Map<Id<Block>, UtxosBlockUndo>,
+        /// Store for the rolling hash of the utxo set, by the block it was computed at
+        pub DBUtxoSetHash: Map<Id<GenBlock>, UtxoSetHash>,
         /// Store for token's info; created on issuance
         pub DBTokensAuxData: Map<TokenId, TokenAuxiliaryData>,
         /// Store of issuance tx id vs token id
         pub DBIssuanceTxVsTokenId: Map<Id<Transaction>, TokenId>,
+        /// Storage for the outputs and spends of each destination, if address indexing is enabled
+        pub DBAddressIndex: Map<AddressIndexKey, AddressOutputInfo>,
+        /// Storage for the inputs that spent each output, if spent-output indexing is enabled
+        pub DBSpentIndex: Map<OutPoint, OutputSpendInfo>,
+        /// Storage for the compact filters of main chain blocks, if block filters are enabled
+        pub DBBlockFilter: Map<Id<Block>, BlockFilter>,
+        /// Storage for the filter header chain, if block filters are enabled
+        pub DBBlockFilterHeader: Map<Id<Block>, BlockFilterHeader>,
+        /// Storage for the state of the utxo snapshot history validation, the raw entries of its
+        /// database by the index of their key-value map and their key
+        pub DBUtxoSnapshotValidation: Map<(u32, Vec<u8>), Vec<u8>>,
 
         /// Store for accounting BlockUndo
         pub DBAccountingBlockUndo: Map<Id<Block>, AccountingBlockUndo>,
@@ -0,0 +1,168 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Upgrades of the storage version, run against the persistent backends
+
+use std::path::Path;
+
+use chainstate_storage::{
+    schema::Schema, BlockchainStorageRead, BlockchainStorageWrite, Store, TransactionRw,
+    Transactional, CURRENT_STORAGE_VERSION,
+};
+use common::{
+    chain::{config::create_unit_test_config, GenBlock},
+    primitives::{BlockHeight, Id, Idable},
+};
+use utxo::{UtxoSetHash, UtxosDB, UtxosStorageRead, UtxosStorageWrite};
+
+fn make_lmdb(path: &Path) -> storage_lmdb::Lmdb {
+    storage_lmdb::Lmdb::new(
+        path.to_path_buf(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+}
+
+fn make_sqlite(path: &Path) -> storage_sqlite::Sqlite {
+    storage_sqlite::Sqlite::new(path.join("chainstate.sqlite"))
+}
+
+// Write a version 1 database: the genesis utxos without the utxo set hash
+fn write_version_1<B: storage::Backend>(backend: B) -> Id<GenBlock> {
+    let chain_config = create_unit_test_config();
+    let genesis_id = chain_config.genesis_block_id();
+    let store = Store::new(backend).unwrap();
+    let mut db_tx = store.transaction_rw(None).unwrap();
+    db_tx.set_best_block_id(&genesis_id).unwrap();
+    UtxosDB::initialize_db(&mut db_tx, &chain_config);
+    db_tx.del_utxo_set_hash(&genesis_id).unwrap();
+    db_tx.set_storage_version(1).unwrap();
+    db_tx.commit().unwrap();
+    genesis_id
+}
+
+fn upgrade_from_version_1<B: storage::Backend>(make_backend: impl Fn() -> B) {
+    let genesis_id = write_version_1(make_backend());
+
+    let store = Store::new(make_backend()).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    let expected_hash = UtxoSetHash::from_utxos(&store.read_utxo_set().unwrap());
+    let db_tx = store.transaction_ro().unwrap();
+    assert_eq!(
+        db_tx.get_utxo_set_hash(&genesis_id),
+        Ok(Some(expected_hash))
+    );
+}
+
+// Dump of a version 1 database written by the node before the storage versions were introduced,
+// on the unit test chain config. It has a main chain of 5 blocks and a fork of 2 blocks on top of
+// the block at height 2.
+const VERSION_1_WITH_BLOCKS: &[u8] = include_bytes!("version_1_blocks.dump");
+
+fn upgrade_from_version_1_with_blocks<B: storage::Backend>(make_backend: impl Fn() -> B) {
+    // Put the contents in place as they are, without upgrading them
+    let contents = storage::raw::read_dump::<Schema>(&mut &VERSION_1_WITH_BLOCKS[..]).unwrap();
+    let storage = storage::Storage::<_, Schema>::new(make_backend()).unwrap();
+    storage.restore_raw(&contents).unwrap();
+    drop(storage);
+
+    let store = Store::new(make_backend()).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    let db_tx = store.transaction_ro().unwrap();
+
+    let best_block_id = db_tx.get_best_block_id().unwrap().unwrap();
+    let expected_hash = UtxoSetHash::from_utxos(&db_tx.get_utxo_set().unwrap());
+    assert_eq!(
+        db_tx.get_utxo_set_hash(&best_block_id),
+        Ok(Some(expected_hash))
+    );
+
+    let block_tree = db_tx.get_block_tree_by_height().unwrap();
+    assert_eq!(block_tree.values().flatten().count(), 7);
+    for (height, block_ids) in block_tree {
+        for block_id in block_ids {
+            let block_index = db_tx.get_block_index(&block_id).unwrap().unwrap();
+            let block = db_tx.get_block(block_id).unwrap().unwrap();
+            assert_eq!(block_index.block_id(), &block.get_id());
+            assert_eq!(block_index.block_header(), block.header());
+            assert_eq!(block_index.block_height(), height);
+
+            let on_main_chain = db_tx.get_block_id_by_height(&height) == Ok(Some(block_id.into()));
+            assert!(block_index.status().has_valid_header());
+            assert_eq!(block_index.status().has_valid_transactions(), on_main_chain);
+        }
+    }
+    assert_eq!(
+        db_tx.get_block_id_by_height(&BlockHeight::new(5)),
+        Ok(Some(best_block_id))
+    );
+}
+
+fn refuse_newer_version<B: storage::Backend>(make_backend: impl Fn() -> B) {
+    let mut store = Store::new(make_backend()).unwrap();
+    store.set_storage_version(CURRENT_STORAGE_VERSION + 1).unwrap();
+    drop(store);
+
+    assert_eq!(
+        Store::new(make_backend()).err(),
+        Some(chainstate_storage::Error::UnsupportedStorageVersion(
+            CURRENT_STORAGE_VERSION + 1,
+            CURRENT_STORAGE_VERSION,
+        ))
+    );
+}
+
+fn reopen_current_version<B: storage::Backend>(make_backend: impl Fn() -> B) {
+    let store = Store::new(make_backend()).unwrap();
+    let contents = store.dump_raw().unwrap();
+    drop(store);
+
+    let store = Store::new(make_backend()).unwrap();
+    assert_eq!(store.get_storage_version(), Ok(CURRENT_STORAGE_VERSION));
+    assert_eq!(store.dump_raw().unwrap(), contents);
+}
+
+#[test]
+fn migrations_lmdb() {
+    let test_root = test_utils::test_root!("migrations").unwrap();
+
+    let dir = test_root.fresh_test_dir("upgrade_from_version_1");
+    upgrade_from_version_1(|| make_lmdb(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("upgrade_from_version_1_with_blocks");
+    upgrade_from_version_1_with_blocks(|| make_lmdb(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("refuse_newer_version");
+    refuse_newer_version(|| make_lmdb(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("reopen_current_version");
+    reopen_current_version(|| make_lmdb(dir.as_ref()));
+
+    test_root.delete();
+}
+
+#[test]
+fn migrations_sqlite() {
+    let test_root = test_utils::test_root!("migrations").unwrap();
+
+    let dir = test_root.fresh_test_dir("upgrade_from_version_1");
+    upgrade_from_version_1(|| make_sqlite(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("upgrade_from_version_1_with_blocks");
+    upgrade_from_version_1_with_blocks(|| make_sqlite(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("refuse_newer_version");
+    refuse_newer_version(|| make_sqlite(dir.as_ref()));
+    let dir = test_root.fresh_test_dir("reopen_current_version");
+    reopen_current_version(|| make_sqlite(dir.as_ref()));
+
+    test_root.delete();
+}
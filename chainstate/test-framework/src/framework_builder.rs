@@ -68,7 +68,14 @@ impl TestFrameworkBuilder {
             max_orphan_blocks: Default::default(),
             min_max_bootstrap_import_buffer_sizes: Default::default(),
             tx_index_enabled: rng.gen::<bool>().into(),
+            address_index_enabled: rng.gen::<bool>().into(),
+            spent_index_enabled: rng.gen::<bool>().into(),
+            block_filters_enabled: rng.gen::<bool>().into(),
+            headers_only: Default::default(),
             max_tip_age: Default::default(),
+            prune_mode: Default::default(),
+            assumed_valid_block: Default::default(),
+            reindex_chainstate: Default::default(),
         };
         let chainstate_storage = TestStore::new_empty().unwrap();
         let time_getter = None;
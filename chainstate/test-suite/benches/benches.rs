@@ -0,0 +1,127 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chainstate::BlockSource;
+use chainstate_test_framework::{TestFramework, TransactionBuilder};
+use common::{
+    chain::{
+        signature::{
+            inputsig::{InputWitness, StandardInputSignature},
+            sighashtype::SigHashType,
+        },
+        signed_transaction::SignedTransaction,
+        tokens::OutputValue,
+        Block, Destination, OutPointSourceId, OutputPurpose, TxInput, TxOutput,
+    },
+    primitives::{Amount, Idable},
+};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use crypto::key::{KeyKind, PrivateKey};
+use test_utils::random::{make_seedable_rng, Seed};
+
+const TX_COUNT: usize = 50;
+const INPUTS_PER_TX: usize = 10;
+
+// Create a test framework with a block that has `TX_COUNT * INPUTS_PER_TX` outputs locked with
+// a public key, and a block that spends them, ready to be connected
+fn prepare_block_with_signatures() -> (TestFramework, Block) {
+    let mut rng = make_seedable_rng(Seed::from_entropy());
+    let mut tf = TestFramework::builder(&mut rng).build();
+
+    let (private_key, public_key) = PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+    let destination = Destination::PublicKey(public_key);
+
+    let funding_tx = (0..TX_COUNT * INPUTS_PER_TX)
+        .fold(
+            TransactionBuilder::new().add_input(
+                TxInput::new(
+                    OutPointSourceId::BlockReward(tf.genesis().get_id().into()),
+                    0,
+                ),
+                InputWitness::NoSignature(None),
+            ),
+            |builder, _| {
+                builder.add_output(TxOutput::new(
+                    OutputValue::Coin(Amount::from_atoms(100)),
+                    OutputPurpose::Transfer(destination.clone()),
+                ))
+            },
+        )
+        .build();
+    let funding_tx_id = funding_tx.transaction().get_id();
+    tf.make_block_builder().add_transaction(funding_tx).build_and_process().unwrap();
+
+    let transactions = (0..TX_COUNT)
+        .map(|tx_num| {
+            let tx = (0..INPUTS_PER_TX)
+                .fold(TransactionBuilder::new(), |builder, input_num| {
+                    let output_index = (tx_num * INPUTS_PER_TX + input_num) as u32;
+                    builder.add_input(
+                        TxInput::new(OutPointSourceId::Transaction(funding_tx_id), output_index),
+                        InputWitness::NoSignature(None),
+                    )
+                })
+                .add_anyone_can_spend_output(100)
+                .build()
+                .transaction()
+                .clone();
+            let witnesses = (0..INPUTS_PER_TX)
+                .map(|input_num| {
+                    let sig = StandardInputSignature::produce_signature_for_input(
+                        &private_key,
+                        SigHashType::try_from(SigHashType::ALL).unwrap(),
+                        destination.clone(),
+                        &tx,
+                        input_num,
+                    )
+                    .unwrap();
+                    InputWitness::Standard(sig)
+                })
+                .collect();
+            SignedTransaction::new(tx, witnesses).unwrap()
+        })
+        .collect();
+    let block = tf.make_block_builder().with_transactions(transactions).build();
+
+    (tf, block)
+}
+
+// Connect a block full of signed inputs with a varying number of signature checking threads
+fn connect_block_with_signatures(c: &mut Criterion) {
+    let mut group = c.benchmark_group("connect_block_with_signatures");
+    group.sample_size(10);
+
+    let max_threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let thread_counts = std::iter::successors(Some(1), |n| Some(n * 2))
+        .take_while(|n| *n < max_threads)
+        .chain(std::iter::once(max_threads));
+
+    for threads in thread_counts {
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter_batched(
+                prepare_block_with_signatures,
+                |(mut tf, block)| {
+                    pool.install(|| tf.process_block(block, BlockSource::Local).unwrap());
+                },
+                BatchSize::PerIteration,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, connect_block_with_signatures);
+criterion_main!(benches);
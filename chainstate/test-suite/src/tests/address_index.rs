@@ -0,0 +1,232 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::helpers::storage_without_value;
+use chainstate::{BlockError, ChainstateConfig, ChainstateError, PropertyQueryError};
+use chainstate_storage::{BlockchainStorageRead, Transactional};
+use chainstate_test_framework::{empty_witness, TestFramework, TestStore, TransactionBuilder};
+use common::{
+    chain::{
+        tokens::{CoinOrTokenId, OutputValue},
+        Destination, GenBlock, OutPoint, OutPointSourceId, OutputPurpose, Spender, TxInput,
+        TxOutput,
+    },
+    primitives::{Amount, BlockHeight, Id, Idable},
+};
+use crypto::key::{KeyKind, PrivateKey};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn address_index_config() -> ChainstateConfig {
+    ChainstateConfig::new().with_whether_address_index_enabled(true)
+}
+
+// The unspent part of the index must match the utxo set that is actually stored
+fn check_anyone_can_spend_index(tf: &TestFramework) {
+    let utxos = tf.storage.read_utxo_set().unwrap();
+    let history = tf.chainstate.get_address_history(&Destination::AnyoneCanSpend).unwrap();
+
+    let unspent: Vec<_> = history.iter().filter(|entry| !entry.info.is_spent()).collect();
+    assert_eq!(
+        unspent.len(),
+        utxos
+            .values()
+            .filter(|utxo| {
+                utxo.output().purpose().destination() == Some(&Destination::AnyoneCanSpend)
+            })
+            .count()
+    );
+    for entry in &unspent {
+        let utxo = utxos.get(&entry.outpoint).unwrap();
+        assert_eq!(
+            utxo.output().value().coin_amount(),
+            Some(entry.info.amount())
+        );
+    }
+
+    let balance = tf.chainstate.get_address_balance(&Destination::AnyoneCanSpend).unwrap();
+    let coin_amount =
+        unspent.iter().map(|entry| entry.info.amount()).sum::<Option<Amount>>().unwrap();
+    assert_eq!(
+        balance.get(&CoinOrTokenId::Coin).copied().unwrap_or(Amount::ZERO),
+        coin_amount
+    );
+}
+
+// Outputs sent to an address show up in its balance and history, and disappear after a reorg
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn balance_and_history(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(address_index_config())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        let genesis_outpoint = OutPoint::new(OutPointSourceId::BlockReward(genesis_id), 0);
+
+        let (_, public_key) = PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+        let destination = Destination::PublicKey(public_key);
+        assert!(tf.chainstate.get_address_balance(&destination).unwrap().is_empty());
+        assert!(tf.chainstate.get_address_history(&destination).unwrap().is_empty());
+
+        let tx = TransactionBuilder::new()
+            .add_input(
+                TxInput::new(genesis_outpoint.tx_id(), genesis_outpoint.output_index()),
+                empty_witness(&mut rng),
+            )
+            .add_output(TxOutput::new(
+                OutputValue::Coin(Amount::from_atoms(1000)),
+                OutputPurpose::Transfer(destination.clone()),
+            ))
+            .add_output(TxOutput::new(
+                OutputValue::Coin(Amount::from_atoms(500)),
+                OutputPurpose::Transfer(destination.clone()),
+            ))
+            .build();
+        let tx_id = tx.transaction().get_id();
+        tf.make_block_builder().add_transaction(tx).build_and_process().unwrap();
+
+        let balance = tf.chainstate.get_address_balance(&destination).unwrap();
+        assert_eq!(
+            balance.get(&CoinOrTokenId::Coin),
+            Some(&Amount::from_atoms(1500))
+        );
+        let history = tf.chainstate.get_address_history(&destination).unwrap();
+        assert_eq!(history.len(), 2);
+        for (index, entry) in history.iter().enumerate() {
+            assert_eq!(
+                entry.outpoint,
+                OutPoint::new(OutPointSourceId::Transaction(tx_id), index as u32)
+            );
+            assert_eq!(entry.info.block_height(), BlockHeight::new(1));
+            assert!(!entry.info.is_spent());
+        }
+
+        // The premine output is now recorded as spent
+        let premine = tf
+            .chainstate
+            .get_address_history(&Destination::AnyoneCanSpend)
+            .unwrap()
+            .into_iter()
+            .find(|entry| entry.outpoint == genesis_outpoint)
+            .unwrap();
+        assert_eq!(premine.info.block_height(), BlockHeight::new(0));
+        let spend = premine.info.spend().unwrap();
+        assert_eq!(spend.spender(), &Spender::RegularInput(tx_id));
+        assert_eq!(spend.block_height(), BlockHeight::new(1));
+        check_anyone_can_spend_index(&tf);
+
+        // Reorg away from the block with the transfer
+        tf.create_chain(&genesis_id, 2, &mut rng).unwrap();
+        assert!(tf.chainstate.get_address_balance(&destination).unwrap().is_empty());
+        assert!(tf.chainstate.get_address_history(&destination).unwrap().is_empty());
+        check_anyone_can_spend_index(&tf);
+    });
+}
+
+// The index follows the main chain through a series of reorgs
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn index_follows_reorgs(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(address_index_config())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        check_anyone_can_spend_index(&tf);
+
+        let a_tip = tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        check_anyone_can_spend_index(&tf);
+
+        // reorg to the b branch
+        tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        check_anyone_can_spend_index(&tf);
+
+        // and back to the a branch
+        tf.create_chain(&a_tip, 2, &mut rng).unwrap();
+        check_anyone_can_spend_index(&tf);
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn queries_fail_when_disabled(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new().with_whether_address_index_enabled(false),
+            )
+            .build();
+
+        assert_eq!(
+            tf.chainstate.get_address_balance(&Destination::AnyoneCanSpend),
+            Err(ChainstateError::FailedToReadProperty(
+                PropertyQueryError::AddressIndexDisabled
+            ))
+        );
+        assert_eq!(
+            tf.chainstate.get_address_history(&Destination::AnyoneCanSpend),
+            Err(ChainstateError::FailedToReadProperty(
+                PropertyQueryError::AddressIndexDisabled
+            ))
+        );
+    });
+}
+
+// A database from before the address index has no index flag; the index can't be enabled once
+// blocks are connected, as their outputs are missing from it
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn enable_on_database_without_flag(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        drop(tf);
+
+        let storage = storage_without_value(&storage, "AddressIndexEnabled");
+        let result = TestFramework::builder(&mut rng)
+            .with_chainstate_config(address_index_config())
+            .with_storage(storage.clone())
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(ChainstateError::ProcessBlockError(
+                BlockError::AddressIndexConfigError
+            ))
+        );
+
+        TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        assert_eq!(
+            storage.transaction_ro().unwrap().get_is_address_index_enabled(),
+            Ok(Some(false))
+        );
+    });
+}
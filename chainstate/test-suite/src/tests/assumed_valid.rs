@@ -0,0 +1,206 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chainstate::{
+    BlockError, BlockSource, ChainstateConfig, ChainstateError, ConnectTransactionError,
+};
+use chainstate_test_framework::{TestFramework, TransactionBuilder};
+use common::{
+    chain::{
+        signature::inputsig::InputWitness, tokens::OutputValue, Block, Destination, GenBlock,
+        OutPointSourceId, OutputPurpose, TxInput, TxOutput,
+    },
+    primitives::{Amount, BlockHeight, Id, Idable},
+};
+use crypto::key::{KeyKind, PrivateKey};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, CryptoRng, Rng, Seed};
+
+// Make a main chain of 3 blocks and a fork of 4 blocks from genesis. The first block of the fork
+// spends an output without providing the signature.
+fn make_blocks(
+    tf: &mut TestFramework,
+    rng: &mut (impl Rng + CryptoRng),
+) -> (Vec<Block>, Vec<Block>) {
+    let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+    let (_, public_key) = PrivateKey::new_from_rng(rng, KeyKind::RistrettoSchnorr);
+
+    tf.create_chain(&genesis_id, 3, rng).unwrap();
+    let main_chain = (1..=3).map(|i| tf.block(*tf.index_at(i).block_id())).collect();
+
+    let tx_1 = TransactionBuilder::new()
+        .add_input(
+            TxInput::new(OutPointSourceId::BlockReward(genesis_id), 0),
+            InputWitness::NoSignature(None),
+        )
+        .add_output(TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(100)),
+            OutputPurpose::Transfer(Destination::PublicKey(public_key)),
+        ))
+        .build();
+    let tx_2 = TransactionBuilder::new()
+        .add_input(
+            TxInput::new(
+                OutPointSourceId::Transaction(tx_1.transaction().get_id()),
+                0,
+            ),
+            InputWitness::NoSignature(None),
+        )
+        .add_output(TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(100)),
+            OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+        ))
+        .build();
+
+    let mut fork = Vec::new();
+    let mut parent_id = genesis_id;
+    for i in 0..4 {
+        let transactions = if i == 0 {
+            vec![tx_1.clone(), tx_2.clone()]
+        } else {
+            Vec::new()
+        };
+        let block = tf
+            .make_block_builder()
+            .with_parent(parent_id)
+            .with_transactions(transactions)
+            .build();
+        parent_id = block.get_id().into();
+        fork.push(block);
+    }
+
+    (main_chain, fork)
+}
+
+// The fork is connected only if the block with the missing signature is an ancestor of the
+// assumed valid block, which can also be the new tip itself
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy(), None, false)]
+#[case(Seed::from_entropy(), Some(2), true)]
+#[case(Seed::from_entropy(), Some(0), true)]
+#[case(Seed::from_entropy(), Some(3), true)]
+fn skip_signatures_of_assumed_valid_ancestors(
+    #[case] seed: Seed,
+    #[case] assumed_valid_fork_block: Option<usize>,
+    #[case] expect_reorg: bool,
+) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let (main_chain, fork) =
+            make_blocks(&mut TestFramework::builder(&mut rng).build(), &mut rng);
+
+        let mut chainstate_config = ChainstateConfig::new();
+        if let Some(idx) = assumed_valid_fork_block {
+            chainstate_config = chainstate_config
+                .with_assumed_valid_block(BlockHeight::new(idx as u64 + 1), fork[idx].get_id());
+        }
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(chainstate_config)
+            .build();
+
+        for block in main_chain.iter().chain(fork[..3].iter()) {
+            tf.process_block(block.clone(), BlockSource::Local).unwrap();
+        }
+        let main_tip_id: Id<GenBlock> = main_chain[2].get_id().into();
+        assert_eq!(tf.best_block_id(), main_tip_id);
+
+        let result = tf.process_block(fork[3].clone(), BlockSource::Local);
+        if expect_reorg {
+            assert!(result.is_ok());
+            assert_eq!(tf.best_block_id(), Id::<GenBlock>::from(fork[3].get_id()));
+        } else {
+            assert!(matches!(
+                result.unwrap_err(),
+                ChainstateError::ProcessBlockError(BlockError::StateUpdateFailed(
+                    ConnectTransactionError::SignatureVerificationFailed(_, 0, _)
+                ))
+            ));
+            assert_eq!(tf.best_block_id(), main_tip_id);
+        }
+    });
+}
+
+// An assumed valid block that isn't in the best chain is ignored, even if the connected blocks
+// are its ancestors
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn assumed_valid_block_not_in_best_chain(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let (main_chain, fork) = make_blocks(&mut tf, &mut rng);
+        // A sibling of the third fork block, with a different timestamp
+        tf.progress_time_seconds_since_epoch(1);
+        let side_block = tf.make_block_builder().with_parent(fork[1].get_id().into()).build();
+
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new()
+                    .with_assumed_valid_block(BlockHeight::new(3), side_block.get_id()),
+            )
+            .build();
+        for block in main_chain.iter().chain(fork[..3].iter()) {
+            tf.process_block(block.clone(), BlockSource::Local).unwrap();
+        }
+        tf.process_block(side_block, BlockSource::Local).unwrap();
+
+        assert!(matches!(
+            tf.process_block(fork[3].clone(), BlockSource::Local).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::StateUpdateFailed(
+                ConnectTransactionError::SignatureVerificationFailed(_, 0, _)
+            ))
+        ));
+        assert_eq!(
+            tf.best_block_id(),
+            Id::<GenBlock>::from(main_chain[2].get_id())
+        );
+    });
+}
+
+// Syncing a linear chain, the blocks are connected one by one before the assumed valid block is
+// known. Nothing proves that they are its ancestors, so their signatures are checked.
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy(), 1)]
+#[case(Seed::from_entropy(), 3)]
+fn linear_sync_before_assumed_valid_block(
+    #[case] seed: Seed,
+    #[case] assumed_valid_fork_block: usize,
+) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let (_main_chain, fork) =
+            make_blocks(&mut TestFramework::builder(&mut rng).build(), &mut rng);
+
+        let height = BlockHeight::new(assumed_valid_fork_block as u64 + 1);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new()
+                    .with_assumed_valid_block(height, fork[assumed_valid_fork_block].get_id()),
+            )
+            .build();
+        assert!(matches!(
+            tf.process_block(fork[0].clone(), BlockSource::Peer).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::StateUpdateFailed(
+                ConnectTransactionError::SignatureVerificationFailed(_, 0, _)
+            ))
+        ));
+        assert_eq!(
+            tf.best_block_id(),
+            Id::<GenBlock>::from(tf.genesis().get_id())
+        );
+    });
+}
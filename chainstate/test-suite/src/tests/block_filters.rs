@@ -0,0 +1,214 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::helpers::storage_without_value;
+use chainstate::{BlockError, ChainstateConfig, ChainstateError, PropertyQueryError};
+use chainstate_storage::{
+    BlockchainStorageRead, BlockchainStorageWrite, TransactionRw, Transactional,
+};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{
+        block::block_filter::{BlockFilter, BlockFilterHeader},
+        Block, GenBlock, GenBlockId,
+    },
+    primitives::{Id, Idable},
+};
+use rstest::rstest;
+use serialization::Encode;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn main_chain_blocks(tf: &TestFramework) -> Vec<Block> {
+    let chain_config = tf.chainstate.get_chain_config();
+    let best_height: u64 = tf.chainstate.get_best_block_height().unwrap().into();
+    (1..=best_height)
+        .map(|height| match tf.block_id(height).classify(&chain_config) {
+            GenBlockId::Genesis(_) => unreachable!(),
+            GenBlockId::Block(id) => tf.block(id),
+        })
+        .collect()
+}
+
+// Every main chain block has a filter matching its destinations and spent outpoints, and the
+// filter headers form a chain starting from the zero header
+fn check_filters(tf: &TestFramework) {
+    let mut prev_header = BlockFilterHeader::zero();
+    for block in main_chain_blocks(tf) {
+        let block_id = block.get_id();
+        let filter = tf.chainstate.get_block_filter(block_id).unwrap().unwrap();
+        assert_eq!(filter, BlockFilter::from_block(&block));
+
+        for tx in block.transactions() {
+            for output in tx.outputs() {
+                if let Some(destination) = output.purpose().destination() {
+                    assert!(filter.matches_any(&block_id, [destination.encode().as_slice()]));
+                }
+            }
+            for input in tx.inputs() {
+                assert!(filter.matches_any(&block_id, [input.outpoint().encode().as_slice()]));
+            }
+        }
+
+        let header = tf.chainstate.get_block_filter_header(block_id).unwrap().unwrap();
+        assert_eq!(header, BlockFilterHeader::new(&filter, &prev_header));
+        prev_header = header;
+    }
+}
+
+// The filters of the disconnected blocks are removed
+fn check_disconnected(tf: &TestFramework, blocks: &[Block]) {
+    for block in blocks {
+        assert_eq!(tf.chainstate.get_block_filter(block.get_id()), Ok(None));
+        assert_eq!(
+            tf.chainstate.get_block_filter_header(block.get_id()),
+            Ok(None)
+        );
+    }
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn filters_follow_reorgs(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new().with_whether_block_filters_enabled(true),
+            )
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        check_filters(&tf);
+
+        let a_tip = tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        check_filters(&tf);
+        let a_blocks = main_chain_blocks(&tf);
+
+        // reorg to the b branch
+        tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        check_filters(&tf);
+        check_disconnected(&tf, &a_blocks);
+        let b_blocks = main_chain_blocks(&tf);
+
+        // and back to the a branch
+        tf.create_chain(&a_tip, 2, &mut rng).unwrap();
+        check_filters(&tf);
+        check_disconnected(&tf, &b_blocks);
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn queries_fail_when_disabled(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new().with_whether_block_filters_enabled(false),
+            )
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 1, &mut rng).unwrap();
+        let block_id = main_chain_blocks(&tf)[0].get_id();
+
+        assert_eq!(
+            tf.chainstate.get_block_filter(block_id),
+            Err(ChainstateError::FailedToReadProperty(
+                PropertyQueryError::BlockFiltersDisabled
+            ))
+        );
+        assert_eq!(
+            tf.chainstate.get_block_filter_header(block_id),
+            Err(ChainstateError::FailedToReadProperty(
+                PropertyQueryError::BlockFiltersDisabled
+            ))
+        );
+    });
+}
+
+// A database from before the block filters has no filters flag; the filters can't be enabled
+// once blocks are connected, as the filters of these blocks are missing
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn enable_on_database_without_flag(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        drop(tf);
+
+        let storage = storage_without_value(&storage, "BlockFiltersEnabled");
+        let result = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new().with_whether_block_filters_enabled(true),
+            )
+            .with_storage(storage.clone())
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(ChainstateError::ProcessBlockError(
+                BlockError::BlockFiltersConfigError
+            ))
+        );
+
+        TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        assert_eq!(
+            storage.transaction_ro().unwrap().get_is_block_filters_enabled(),
+            Ok(Some(false))
+        );
+    });
+}
+
+// A block can't be connected on top of a block without a filter header
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn missing_previous_filter_header(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(
+                ChainstateConfig::new().with_whether_block_filters_enabled(true),
+            )
+            .with_storage(storage.clone())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 2, &mut rng).unwrap();
+        let tip_id = main_chain_blocks(&tf)[1].get_id();
+
+        let mut db_tx = storage.transaction_rw(None).unwrap();
+        db_tx.del_block_filter_header(&tip_id).unwrap();
+        db_tx.commit().unwrap();
+
+        assert_eq!(
+            tf.create_chain(&tip_id.into(), 1, &mut rng),
+            Err(ChainstateError::ProcessBlockError(
+                BlockError::MissingBlockFilterHeader(tip_id)
+            ))
+        );
+        assert_eq!(tf.best_block_id(), Id::<GenBlock>::from(tip_id));
+    });
+}
@@ -18,9 +18,10 @@ use std::collections::BTreeSet;
 use std::io::BufWriter;
 
 use chainstate::chainstate_interface::ChainstateInterface;
-use chainstate::ChainstateConfig;
+use chainstate::{BootstrapError, ChainstateConfig, ChainstateError};
 use chainstate_test_framework::TestFramework;
 use common::chain::Block;
+use common::primitives::BlockHeight;
 use common::primitives::Id;
 use common::primitives::Idable;
 use rstest::rstest;
@@ -185,7 +186,13 @@ fn bootstrap_tests(#[case] seed: Seed) {
             let writer: BufWriter<Box<dyn std::io::Write + Send>> =
                 std::io::BufWriter::new(Box::new(&mut write_buffer));
 
-            tf1.chainstate.export_bootstrap_stream(writer, with_orphans).unwrap();
+            tf1.chainstate
+                .export_bootstrap_stream(
+                    writer,
+                    with_orphans,
+                    BlockHeight::zero()..=BlockHeight::max(),
+                )
+                .unwrap();
 
             write_buffer
         };
@@ -274,3 +281,131 @@ fn bootstrap_tests(#[case] seed: Seed) {
         }
     });
 }
+
+fn export_bootstrap<C: ChainstateInterface>(
+    chainstate: &C,
+    heights: std::ops::RangeInclusive<BlockHeight>,
+) -> Vec<u8> {
+    let mut write_buffer = Vec::new();
+    let writer: BufWriter<Box<dyn std::io::Write + Send>> =
+        std::io::BufWriter::new(Box::new(&mut write_buffer));
+    chainstate.export_bootstrap_stream(writer, false, heights).unwrap();
+    write_buffer
+}
+
+fn import_bootstrap<C: ChainstateInterface>(
+    chainstate: &mut C,
+    data: &[u8],
+) -> Result<(), ChainstateError> {
+    let reader: std::io::BufReader<Box<dyn std::io::Read + Send>> =
+        std::io::BufReader::new(Box::new(data));
+    chainstate.import_bootstrap_stream(reader)
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn bootstrap_height_ranges(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = TestFramework::builder(&mut rng).build();
+        let genesis_id = tf1.genesis().get_id();
+        tf1.create_chain(&genesis_id.into(), 10, &mut rng).unwrap();
+
+        let first_part = export_bootstrap(&tf1.chainstate, BlockHeight::zero()..=4.into());
+        let second_part = export_bootstrap(&tf1.chainstate, 5.into()..=BlockHeight::max());
+        let empty = export_bootstrap(&tf1.chainstate, 11.into()..=20.into());
+
+        let mut tf2 = TestFramework::builder(&mut rng).build();
+
+        import_bootstrap(&mut tf2.chainstate, &first_part).unwrap();
+        assert_eq!(tf2.best_block_index().block_height(), 4.into());
+        import_bootstrap(&mut tf2.chainstate, &second_part).unwrap();
+        import_bootstrap(&mut tf2.chainstate, &empty).unwrap();
+        assert_eq!(
+            tf2.chainstate.get_mainchain_blocks_list().unwrap(),
+            tf1.chainstate.get_mainchain_blocks_list().unwrap(),
+        );
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn bootstrap_bad_files(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = TestFramework::builder(&mut rng).build();
+        let genesis_id = tf1.genesis().get_id();
+        tf1.create_chain(&genesis_id.into(), 5, &mut rng).unwrap();
+        let file = export_bootstrap(&tf1.chainstate, BlockHeight::zero()..=BlockHeight::max());
+
+        let mut tf2 = TestFramework::builder(&mut rng).build();
+
+        let mut wrong_magic = file.clone();
+        wrong_magic[0] ^= 1;
+        assert_eq!(
+            import_bootstrap(&mut tf2.chainstate, &wrong_magic),
+            Err(ChainstateError::BootstrapError(
+                BootstrapError::WrongMagicBytes
+            ))
+        );
+
+        let mut wrong_version = file.clone();
+        wrong_version[4..8].copy_from_slice(&3u32.to_le_bytes());
+        assert_eq!(
+            import_bootstrap(&mut tf2.chainstate, &wrong_version),
+            Err(ChainstateError::BootstrapError(
+                BootstrapError::UnsupportedVersion(3)
+            ))
+        );
+
+        let truncated = &file[..file.len() - 1];
+        assert!(matches!(
+            import_bootstrap(&mut tf2.chainstate, truncated),
+            Err(ChainstateError::BootstrapError(BootstrapError::File(_)))
+        ));
+
+        let mut corrupted_hash = file.clone();
+        *corrupted_hash.last_mut().unwrap() ^= 1;
+        let mut tf3 = TestFramework::builder(&mut rng).build();
+        assert!(matches!(
+            import_bootstrap(&mut tf3.chainstate, &corrupted_hash),
+            Err(ChainstateError::BootstrapError(
+                BootstrapError::CorruptedContent(_, _)
+            ))
+        ));
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn bootstrap_legacy_format(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = TestFramework::builder(&mut rng).build();
+        let genesis_id = tf1.genesis().get_id();
+        tf1.create_chain(&genesis_id.into(), 10, &mut rng).unwrap();
+
+        // The first version of the format is a sequence of blocks prefixed with the magic bytes
+        let magic_bytes = tf1.chainstate.get_chain_config().magic_bytes().to_vec();
+        let legacy_file: Vec<u8> = tf1
+            .chainstate
+            .get_mainchain_blocks_list()
+            .unwrap()
+            .into_iter()
+            .flat_map(|id| {
+                let block = tf1.chainstate.get_block(id).unwrap().unwrap();
+                magic_bytes.iter().copied().chain(block.encode())
+            })
+            .collect();
+
+        let mut tf2 = TestFramework::builder(&mut rng).build();
+        import_bootstrap(&mut tf2.chainstate, &legacy_file).unwrap();
+        assert_eq!(
+            tf2.chainstate.get_mainchain_blocks_list().unwrap(),
+            tf1.chainstate.get_mainchain_blocks_list().unwrap(),
+        );
+    });
+}
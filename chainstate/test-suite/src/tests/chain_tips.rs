@@ -0,0 +1,97 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate_test_framework::TestFramework;
+use chainstate_types::ChainTipStatus;
+use common::{
+    chain::GenBlock,
+    primitives::{BlockDistance, Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn chain_tips(tf: &TestFramework) -> Vec<(Id<GenBlock>, ChainTipStatus, BlockDistance)> {
+    let mut tips: Vec<_> = tf
+        .chainstate
+        .get_chain_tips()
+        .unwrap()
+        .into_iter()
+        .map(|tip| (tip.block_id, tip.status, tip.branch_length))
+        .collect();
+    tips.sort_by_key(|(id, _, _)| *id);
+    tips
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn only_genesis(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        assert_eq!(
+            chain_tips(&tf),
+            vec![(genesis_id, ChainTipStatus::Active, BlockDistance::new(0))]
+        );
+    });
+}
+
+// genesis -> c1
+//         -> a1 -> a2 -> a3 (main)
+//               -> b2
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn tips_of_all_kinds(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        // c1 is connected first and then reorged out, so it has been fully validated
+        let c1 = tf.create_chain(&genesis_id, 1, &mut rng).unwrap();
+        let a3 = tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        assert_eq!(tf.best_block_id(), a3);
+        let a1: Id<GenBlock> = (*tf.index_at(2).block_id()).into();
+        let a2: Id<GenBlock> = (*tf.index_at(3).block_id()).into();
+
+        // b2 doesn't have more chain trust than the main chain, so it's never connected
+        let b2 = tf.create_chain(&a1, 1, &mut rng).unwrap();
+        assert_eq!(tf.best_block_id(), a3);
+
+        let mut expected = vec![
+            (a3, ChainTipStatus::Active, BlockDistance::new(0)),
+            (c1, ChainTipStatus::ValidFork, BlockDistance::new(1)),
+            (b2, ChainTipStatus::HeadersOnly, BlockDistance::new(1)),
+        ];
+        expected.sort_by_key(|(id, _, _)| *id);
+        assert_eq!(chain_tips(&tf), expected);
+
+        let a3_block_id = *tf.index_at(4).block_id();
+        tf.chainstate.invalidate_block(&a3_block_id).unwrap();
+        assert_eq!(tf.best_block_id(), a2);
+
+        let mut expected = vec![
+            (a2, ChainTipStatus::Active, BlockDistance::new(0)),
+            (a3, ChainTipStatus::Invalid, BlockDistance::new(1)),
+            (c1, ChainTipStatus::ValidFork, BlockDistance::new(1)),
+            (b2, ChainTipStatus::HeadersOnly, BlockDistance::new(1)),
+        ];
+        expected.sort_by_key(|(id, _, _)| *id);
+        assert_eq!(chain_tips(&tf), expected);
+    });
+}
@@ -551,6 +551,10 @@ fn reorg_store_coin_no_tx_index(#[case] seed: Seed, #[case] tx_index_enabled: bo
         {
             let config = chainstate::ChainstateConfig {
                 tx_index_enabled: tx_index_enabled.into(),
+                address_index_enabled: Default::default(),
+                spent_index_enabled: Default::default(),
+                block_filters_enabled: Default::default(),
+                headers_only: Default::default(),
                 ..Default::default()
             };
 
@@ -693,10 +697,17 @@ fn reorg_store_coin_no_tx_index(#[case] seed: Seed, #[case] tx_index_enabled: bo
 
             let config_new = chainstate::ChainstateConfig {
                 tx_index_enabled: (!tx_index_enabled).into(),
+                address_index_enabled: Default::default(),
+                spent_index_enabled: Default::default(),
+                block_filters_enabled: Default::default(),
+                headers_only: Default::default(),
                 max_db_commit_attempts: Default::default(),
                 max_orphan_blocks: Default::default(),
                 min_max_bootstrap_import_buffer_sizes: Default::default(),
                 max_tip_age: Default::default(),
+                prune_mode: Default::default(),
+                assumed_valid_block: Default::default(),
+                reindex_chainstate: Default::default(),
             };
 
             let tf_build_error = TestFramework::builder(&mut rng)
@@ -214,6 +214,74 @@ fn custom_orphan_error_hook(#[case] seed: Seed) {
     });
 }
 
+// A reorg disconnects the blocks of the old chain from the tip down and connects the blocks of the
+// new chain from the fork point up, and only then announces the new tip.
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reorg_block_events(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id = tf.genesis().get_id();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let events_ = Arc::clone(&events);
+        tf.chainstate.subscribe_to_events(Arc::new(move |event: ChainstateEvent| {
+            let event = match event {
+                ChainstateEvent::NewTip(id, height) => ("tip", id, height),
+                ChainstateEvent::BlockConnected(block, height) => {
+                    ("connected", block.get_id(), height)
+                }
+                ChainstateEvent::BlockDisconnected(block, height) => {
+                    ("disconnected", block.get_id(), height)
+                }
+            };
+            events_.lock().unwrap().push(event);
+        }));
+
+        let a1 = tf.make_block_builder().add_test_transaction_from_best_block(&mut rng).build();
+        tf.process_block(a1.clone(), BlockSource::Local).unwrap();
+        let a2 = tf.make_block_builder().add_test_transaction_from_best_block(&mut rng).build();
+        tf.process_block(a2.clone(), BlockSource::Local).unwrap();
+        tf.chainstate.wait_for_all_events();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("connected", a1.get_id(), BlockHeight::new(1)),
+                ("tip", a1.get_id(), BlockHeight::new(1)),
+                ("connected", a2.get_id(), BlockHeight::new(2)),
+                ("tip", a2.get_id(), BlockHeight::new(2)),
+            ]
+        );
+        events.lock().unwrap().clear();
+
+        // The new chain only becomes the main chain with its third block
+        tf.progress_time_seconds_since_epoch(1);
+        let b1 = tf.make_block_builder().with_parent(genesis_id.into()).build();
+        tf.process_block(b1.clone(), BlockSource::Local).unwrap();
+        let b2 = tf.make_block_builder().with_parent(b1.get_id().into()).build();
+        tf.process_block(b2.clone(), BlockSource::Local).unwrap();
+        tf.chainstate.wait_for_all_events();
+        assert!(events.lock().unwrap().is_empty());
+
+        let b3 = tf.make_block_builder().with_parent(b2.get_id().into()).build();
+        tf.process_block(b3.clone(), BlockSource::Local).unwrap();
+        tf.chainstate.wait_for_all_events();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("disconnected", a2.get_id(), BlockHeight::new(2)),
+                ("disconnected", a1.get_id(), BlockHeight::new(1)),
+                ("connected", b1.get_id(), BlockHeight::new(1)),
+                ("connected", b2.get_id(), BlockHeight::new(2)),
+                ("connected", b3.get_id(), BlockHeight::new(3)),
+                ("tip", b3.get_id(), BlockHeight::new(3)),
+            ]
+        );
+    });
+}
+
 // Subscribes to events N times emulating different subscribers.
 fn subscribe(chainstate: &mut TestChainstate, n: usize) -> EventList {
     let events = Arc::new(Mutex::new(Vec::new()));
@@ -224,6 +292,7 @@ fn subscribe(chainstate: &mut TestChainstate, n: usize) -> EventList {
             ChainstateEvent::NewTip(block_id, block_height) => {
                 events_.lock().unwrap().push((block_id, block_height));
             }
+            ChainstateEvent::BlockConnected(_, _) | ChainstateEvent::BlockDisconnected(_, _) => {}
         });
         chainstate.subscribe_to_events(handler);
     }
@@ -0,0 +1,113 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate::{BlockError, BlockSource, ChainstateConfig, ChainstateError};
+use chainstate_test_framework::TestFramework;
+use common::{
+    chain::{GenBlock, GenBlockId},
+    primitives::{BlockHeight, Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+
+// Feed the headers of the main chain of a full chainstate to a headers-only one, which should end
+// up with the same main chain
+fn sync_headers(full_tf: &TestFramework, light_tf: &mut TestFramework) {
+    let chain_config = full_tf.chainstate.get_chain_config();
+    let best_height: u64 = full_tf.chainstate.get_best_block_height().unwrap().into();
+    for height in 1..=best_height {
+        let block_id = match full_tf.block_id(height).classify(&chain_config) {
+            GenBlockId::Genesis(_) => unreachable!(),
+            GenBlockId::Block(id) => id,
+        };
+        let header = full_tf.block(block_id).header().clone();
+        match light_tf.chainstate.process_header(header) {
+            Ok(_) | Err(ChainstateError::ProcessBlockError(BlockError::BlockAlreadyExists(_))) => {}
+            Err(err) => panic!("Processing header failed: {err}"),
+        }
+    }
+
+    assert_eq!(light_tf.best_block_id(), full_tf.best_block_id());
+    for height in 0..=best_height {
+        assert_eq!(light_tf.block_id(height), full_tf.block_id(height));
+    }
+    assert_eq!(
+        light_tf.chainstate.get_locator().unwrap(),
+        full_tf.chainstate.get_locator().unwrap()
+    );
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn headers_follow_the_chain_with_most_trust(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut full_tf = TestFramework::builder(&mut rng).build();
+        let mut light_tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new().with_headers_only(true))
+            .build();
+        let genesis_id: Id<GenBlock> = full_tf.genesis().get_id().into();
+
+        let a_tip = full_tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        sync_headers(&full_tf, &mut light_tf);
+
+        // reorg to the b branch
+        full_tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        sync_headers(&full_tf, &mut light_tf);
+
+        // and back to the a branch
+        full_tf.create_chain(&a_tip, 2, &mut rng).unwrap();
+        sync_headers(&full_tf, &mut light_tf);
+
+        // The block data is never stored
+        let tip_id = light_tf.best_block_id().classify(&light_tf.chainstate.get_chain_config());
+        let tip_id = tip_id.chain_block_id().unwrap();
+        assert_eq!(light_tf.chainstate.get_block(tip_id), Ok(None));
+        assert_eq!(
+            light_tf.chainstate.get_best_block_height(),
+            Ok(BlockHeight::new(5))
+        );
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn modes_are_exclusive(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut full_tf = TestFramework::builder(&mut rng).build();
+        let mut light_tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new().with_headers_only(true))
+            .build();
+
+        let block = full_tf.make_block_builder().build();
+        assert_eq!(
+            light_tf.process_block(block.clone(), BlockSource::Peer).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::BlockDataInHeadersOnlyMode)
+        );
+        assert_eq!(
+            full_tf.chainstate.process_header(block.header().clone()).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::HeadersOnlyModeDisabled)
+        );
+
+        light_tf.chainstate.process_header(block.header().clone()).unwrap();
+        assert_eq!(
+            light_tf.chainstate.process_header(block.header().clone()).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::BlockAlreadyExists(block.get_id()))
+        );
+    });
+}
@@ -19,7 +19,9 @@ use ::tx_verifier::transaction_verifier::storage::{
     TransactionVerifierStorageError, TransactionVerifierStorageRef,
 };
 use chainstate_storage::{inmemory::Store, BlockchainStorageRead};
-use chainstate_types::{storage_result, GenBlockIndex};
+use chainstate_types::{
+    storage_result, AddressIndexKey, AddressOutputInfo, GenBlockIndex, OutputSpendInfo,
+};
 use common::{
     chain::{
         tokens::{TokenAuxiliaryData, TokenId},
@@ -94,6 +96,24 @@ impl TransactionVerifierStorageRef for InMemoryStorageWrapper {
             .get_accounting_undo(id)
             .map_err(TransactionVerifierStorageError::from)
     }
+
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError> {
+        self.storage
+            .get_address_index_entry(key)
+            .map_err(TransactionVerifierStorageError::from)
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &common::chain::OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError> {
+        self.storage
+            .get_output_spend_info(outpoint)
+            .map_err(TransactionVerifierStorageError::from)
+    }
 }
 
 impl UtxosStorageRead for InMemoryStorageWrapper {
@@ -116,6 +136,13 @@ impl UtxosStorageRead for InMemoryStorageWrapper {
     ) -> Result<Option<utxo::UtxosBlockUndo>, chainstate_types::storage_result::Error> {
         self.storage.get_undo_data(id)
     }
+
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<utxo::UtxoSetHash>, chainstate_types::storage_result::Error> {
+        self.storage.get_utxo_set_hash(block_id)
+    }
 }
 
 impl PoSAccountingView for InMemoryStorageWrapper {
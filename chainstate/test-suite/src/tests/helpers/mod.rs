@@ -13,7 +13,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use chainstate_test_framework::{anyonecanspend_address, TestFramework, TransactionBuilder};
+use chainstate_test_framework::{
+    anyonecanspend_address, TestFramework, TestStore, TransactionBuilder,
+};
 use common::{
     chain::{
         block::timestamp::BlockTimestamp, signature::inputsig::InputWitness,
@@ -22,9 +24,19 @@ use common::{
     },
     primitives::{Amount, BlockDistance, Id, Idable},
 };
+use serialization::Encode;
 
 pub mod in_memory_storage_wrapper;
 
+/// A copy of the storage without the given well-known value, like a database written before the
+/// value was introduced
+pub fn storage_without_value(storage: &TestStore, key: &str) -> TestStore {
+    let mut contents = storage.dump_raw().unwrap();
+    let (_, values) = contents.iter_mut().find(|(idx, _)| idx.name() == "DBValue").unwrap();
+    values.remove(&key.as_bytes().encode()).unwrap();
+    TestStore::from_raw(Default::default(), &contents).unwrap()
+}
+
 /// Adds a block with the locked output and returns input corresponding to this output.
 pub fn add_block_with_locked_output(
     tf: &mut TestFramework,
@@ -50,16 +50,29 @@ fn genesis_check_ok(num_blocks: u64, rng: &mut (impl Rng + CryptoRng)) {
         tf.storage.clone()
     };
 
-    // Check that tx_index_enabled state is same as used in the storage.
-    // Could be removed once tx re-index is implemented.
+    // Check that the indexing states are same as used in the storage.
+    // Could be removed once re-indexing is implemented.
     let tx_index_enabled =
         storage.transaction_ro().unwrap().get_is_mainchain_tx_index_enabled().unwrap();
+    let address_index_enabled =
+        storage.transaction_ro().unwrap().get_is_address_index_enabled().unwrap();
+    let spent_index_enabled =
+        storage.transaction_ro().unwrap().get_is_spent_index_enabled().unwrap();
+    let block_filters_enabled =
+        storage.transaction_ro().unwrap().get_is_block_filters_enabled().unwrap();
     let chainstate_config = ChainstateConfig {
         max_db_commit_attempts: Default::default(),
         max_orphan_blocks: Default::default(),
         min_max_bootstrap_import_buffer_sizes: Default::default(),
         tx_index_enabled: tx_index_enabled.map(Into::into).unwrap_or_default(),
+        address_index_enabled: address_index_enabled.map(Into::into).unwrap_or_default(),
+        spent_index_enabled: spent_index_enabled.map(Into::into).unwrap_or_default(),
+        block_filters_enabled: block_filters_enabled.map(Into::into).unwrap_or_default(),
+        headers_only: Default::default(),
         max_tip_age: Default::default(),
+        prune_mode: Default::default(),
+        assumed_valid_block: Default::default(),
+        reindex_chainstate: Default::default(),
     };
 
     // Initialize a different test framework with given storage.
@@ -110,16 +123,29 @@ fn genesis_check_err(num_blocks: u64, rng: &mut (impl Rng + CryptoRng)) {
         tf.storage
     };
 
-    // Check that tx_index_enabled state is same as used in the storage.
-    // Could be removed once tx re-index is implemented.
+    // Check that the indexing states are same as used in the storage.
+    // Could be removed once re-indexing is implemented.
     let tx_index_enabled =
         storage.transaction_ro().unwrap().get_is_mainchain_tx_index_enabled().unwrap();
+    let address_index_enabled =
+        storage.transaction_ro().unwrap().get_is_address_index_enabled().unwrap();
+    let spent_index_enabled =
+        storage.transaction_ro().unwrap().get_is_spent_index_enabled().unwrap();
+    let block_filters_enabled =
+        storage.transaction_ro().unwrap().get_is_block_filters_enabled().unwrap();
     let chainstate_config = ChainstateConfig {
         max_db_commit_attempts: Default::default(),
         max_orphan_blocks: Default::default(),
         min_max_bootstrap_import_buffer_sizes: Default::default(),
         tx_index_enabled: tx_index_enabled.map(Into::into).unwrap_or_default(),
+        address_index_enabled: address_index_enabled.map(Into::into).unwrap_or_default(),
+        spent_index_enabled: spent_index_enabled.map(Into::into).unwrap_or_default(),
+        block_filters_enabled: block_filters_enabled.map(Into::into).unwrap_or_default(),
+        headers_only: Default::default(),
         max_tip_age: Default::default(),
+        prune_mode: Default::default(),
+        assumed_valid_block: Default::default(),
+        reindex_chainstate: Default::default(),
     };
 
     // Start another chain with different genesis using the previous storage
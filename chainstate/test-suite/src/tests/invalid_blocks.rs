@@ -0,0 +1,115 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate::{BlockError, BlockSource, ChainstateError};
+use chainstate_test_framework::TestFramework;
+use chainstate_types::{BlockStatus, GenBlockIndex};
+use common::{
+    chain::{Block, GenBlock},
+    primitives::{Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn block_status(tf: &TestFramework, id: &Id<Block>) -> BlockStatus {
+    match tf.block_index(&(*id).into()) {
+        GenBlockIndex::Block(block_index) => block_index.status(),
+        GenBlockIndex::Genesis(..) => panic!("genesis has no status"),
+    }
+}
+
+// Invalidate a block in the middle of the main chain, then reconsider it
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn invalidate_and_reconsider_main_chain_block(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        let tip_id = tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+        let ids: Vec<Id<Block>> = (1..=5).map(|i| *tf.index_at(i).block_id()).collect();
+
+        tf.chainstate.invalidate_block(&ids[2]).unwrap();
+        assert_eq!(tf.best_block_id(), Id::<GenBlock>::from(ids[1]));
+        assert!(block_status(&tf, &ids[2]).is_failed_itself());
+        assert!(block_status(&tf, &ids[3]).is_failed_child());
+        assert!(block_status(&tf, &ids[4]).is_failed_child());
+        assert!(!block_status(&tf, &ids[1]).is_failed());
+
+        // Known invalid blocks and their descendants are rejected without being connected
+        let invalid_block = tf.block(ids[2]);
+        assert_eq!(
+            tf.process_block(invalid_block, BlockSource::Local).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::InvalidBlockAlreadyProcessed(ids[2]))
+        );
+        assert_eq!(
+            tf.create_chain(&tip_id, 1, &mut rng).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::PrevBlockInvalid(ids[4]))
+        );
+
+        tf.chainstate.reconsider_block(&ids[2]).unwrap();
+        assert_eq!(tf.best_block_id(), tip_id);
+        for id in &ids {
+            assert!(!block_status(&tf, id).is_failed());
+        }
+    });
+}
+
+// Invalidating a block switches the node to the best chain that doesn't contain it
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn invalidate_block_switches_to_fork(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        let main_tip_id = tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        let fork_start_id: Id<GenBlock> = (*tf.index_at(1).block_id()).into();
+        let fork_tip_id = tf.create_chain(&fork_start_id, 2, &mut rng).unwrap();
+        assert_eq!(tf.best_block_id(), main_tip_id);
+
+        let invalidated_id = *tf.index_at(2).block_id();
+        tf.chainstate.invalidate_block(&invalidated_id).unwrap();
+        assert_eq!(tf.best_block_id(), fork_tip_id);
+
+        tf.chainstate.reconsider_block(&invalidated_id).unwrap();
+        assert_eq!(tf.best_block_id(), main_tip_id);
+    });
+}
+
+// Invalidating the only block on top of genesis makes genesis the tip again
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn invalidate_block_on_top_of_genesis(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        tf.create_chain(&genesis_id, 1, &mut rng).unwrap();
+        let block_id = *tf.index_at(1).block_id();
+
+        tf.chainstate.invalidate_block(&block_id).unwrap();
+        assert_eq!(tf.best_block_id(), genesis_id);
+
+        tf.chainstate.reconsider_block(&block_id).unwrap();
+        assert_eq!(tf.best_block_id(), Id::<GenBlock>::from(block_id));
+    });
+}
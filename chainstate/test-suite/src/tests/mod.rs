@@ -31,13 +31,19 @@ use rstest::rstest;
 use serialization::Encode;
 use test_utils::random::{make_seedable_rng, Seed};
 
+mod address_index;
+mod assumed_valid;
+mod block_filters;
 mod bootstrap;
+mod chain_tips;
 mod chainstate_storage_tests;
 mod double_spend_tests;
 mod events_tests;
 mod fungible_tokens;
+mod headers_only;
 mod homomorphism;
 mod initialization;
+mod invalid_blocks;
 mod mempool_output_timelock;
 mod nft_burn;
 mod nft_issuance;
@@ -46,13 +52,21 @@ mod nft_transfer;
 mod output_timelock;
 mod pos_accounting_reorg;
 mod processing_tests;
+mod pruning;
+mod reindex;
 mod reorgs_tests;
 mod signature_tests;
+mod spent_index;
 mod stake_pool_tests;
+mod storage_faults;
 mod syncing_tests;
+mod tx_inclusion_proofs;
 mod tx_verification_simulation;
 mod tx_verifier_among_threads;
 mod tx_verifier_disconnect;
+mod utxo_set_hash;
+mod utxo_snapshot;
+mod verify_chain;
 
 mod helpers;
 
@@ -0,0 +1,147 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use chainstate::{
+    BlockError, ChainstateConfig, ChainstateError, PruneMode, MIN_BLOCKS_TO_KEEP_WHEN_PRUNING,
+};
+use chainstate_storage::{BlockchainStorageRead, Transactional};
+use chainstate_test_framework::TestFramework;
+use common::{
+    chain::{Block, GenBlock},
+    primitives::{BlockHeight, Id, Idable},
+};
+use rstest::rstest;
+use serialization::Encode;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn pruning_config(keep: u64) -> ChainstateConfig {
+    ChainstateConfig::new()
+        .with_whether_tx_index_enabled(false)
+        .with_prune_mode(PruneMode::Depth(keep))
+}
+
+// Blocks deeper than the configured depth lose their bodies, while the indexes are kept
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn prune_by_depth(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(pruning_config(3))
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        tf.create_chain(&genesis_id, 2, &mut rng).unwrap();
+        assert_eq!(tf.chainstate.get_pruned_height().unwrap(), None);
+
+        tf.create_chain(&tf.best_block_id(), 8, &mut rng).unwrap();
+        assert_eq!(
+            tf.chainstate.get_pruned_height().unwrap(),
+            Some(BlockHeight::new(7))
+        );
+
+        let ids: Vec<Id<Block>> = (1..=10).map(|i| *tf.index_at(i).block_id()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            assert!(tf.chainstate.get_block_index(id).unwrap().is_some());
+            assert_eq!(tf.chainstate.get_block(*id).unwrap().is_some(), i >= 7);
+        }
+
+        // The size of the remaining blocks is tracked in the storage
+        let expected_size: u64 =
+            ids[7..].iter().map(|id| tf.block(*id).encoded_size() as u64).sum();
+        let db_tx = tf.storage.transaction_ro().unwrap();
+        assert_eq!(db_tx.get_stored_blocks_size().unwrap(), expected_size);
+    });
+}
+
+// With size based pruning the minimum number of blocks is always kept
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn prune_by_size_keeps_minimum(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let config = ChainstateConfig::new()
+            .with_whether_tx_index_enabled(false)
+            .with_prune_mode(PruneMode::Size(0));
+        let mut tf = TestFramework::builder(&mut rng).with_chainstate_config(config).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        let blocks = MIN_BLOCKS_TO_KEEP_WHEN_PRUNING as usize + 5;
+        tf.create_chain(&genesis_id, blocks, &mut rng).unwrap();
+        assert_eq!(
+            tf.chainstate.get_pruned_height().unwrap(),
+            Some(BlockHeight::new(5))
+        );
+        assert!(tf.chainstate.get_block(*tf.index_at(5).block_id()).unwrap().is_none());
+        assert!(tf.chainstate.get_block(*tf.index_at(6).block_id()).unwrap().is_some());
+    });
+}
+
+// Reorgs that don't go below the pruned height work as usual, deeper ones are rejected
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reorg_with_pruned_blocks(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(pruning_config(3))
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        // Start a fork while its base is still available, the test blocks spend its outputs
+        tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+        let deep_fork_tip_id = tf.create_chain(&tf.block_id(4), 1, &mut rng).unwrap();
+
+        let main_tip_id = tf.create_chain(&tf.best_block_id(), 5, &mut rng).unwrap();
+        assert_eq!(
+            tf.chainstate.get_pruned_height().unwrap(),
+            Some(BlockHeight::new(7))
+        );
+
+        // The fork point is below the pruned height
+        assert_eq!(
+            tf.create_chain(&deep_fork_tip_id, 6, &mut rng).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::ReorgBeyondPrunedHeight(
+                BlockHeight::new(4),
+                BlockHeight::new(7)
+            ))
+        );
+        assert_eq!(tf.best_block_id(), main_tip_id);
+
+        // The fork point is above the pruned height
+        let fork_tip_id = tf.create_chain(&tf.block_id(8), 3, &mut rng).unwrap();
+        assert_eq!(tf.best_block_id(), fork_tip_id);
+    });
+}
+
+// Pruning cannot be combined with the transaction index
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn tx_index_with_pruning(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let config = pruning_config(3).with_whether_tx_index_enabled(true);
+        let result = TestFramework::builder(&mut rng).with_chainstate_config(config).try_build();
+        assert_eq!(
+            result.err(),
+            Some(ChainstateError::ProcessBlockError(
+                BlockError::TxIndexWithPruning
+            ))
+        );
+    });
+}
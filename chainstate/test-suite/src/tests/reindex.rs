@@ -0,0 +1,195 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate::{BlockError, BlockSource, ChainstateConfig, ChainstateError, PruneMode};
+use chainstate_storage::{
+    BlockchainStorageRead, BlockchainStorageWrite, TransactionRw, Transactional,
+};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{GenBlock, GenBlockId},
+    primitives::{Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+use utxo::UtxosStorageWrite;
+
+fn reindex_config() -> ChainstateConfig {
+    ChainstateConfig::new()
+        .with_whether_tx_index_enabled(true)
+        .with_whether_address_index_enabled(true)
+}
+
+// The state rebuilt from the blocks is the same as the one built while processing them
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reindex_rebuilds_corrupted_state(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_storage(storage.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        // A stale branch, whose blocks are kept but not reconnected
+        tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        tf.create_chain(&genesis_id, 6, &mut rng).unwrap();
+        drop(tf);
+        let expected = storage.dump_raw();
+
+        let mut db_tx = storage.transaction_rw(None).unwrap();
+        for outpoint in db_tx.get_utxo_set().unwrap().keys() {
+            db_tx.del_utxo(outpoint).unwrap();
+        }
+        db_tx.commit().unwrap();
+        assert_ne!(storage.dump_raw(), expected);
+
+        let tf = TestFramework::builder(&mut rng)
+            .with_storage(storage.clone())
+            .with_chainstate_config(reindex_config().with_reindex_chainstate(true))
+            .build();
+        assert_eq!(tf.chainstate.get_best_block_height().unwrap(), 6.into());
+        drop(tf);
+        assert_eq!(storage.dump_raw(), expected);
+    });
+}
+
+// A reindex that was interrupted continues on the next startup, without the flag
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reindex_resumes_after_interruption(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage1 = TestStore::new_empty().unwrap();
+        let mut tf1 = TestFramework::builder(&mut rng)
+            .with_storage(storage1.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        let tip_id = tf1.create_chain(&genesis_id, 8, &mut rng).unwrap();
+        let block_ids: Vec<_> = (1..=8)
+            .map(
+                |height| match tf1.block_id(height).classify(&tf1.chainstate.get_chain_config()) {
+                    GenBlockId::Genesis(_) => unreachable!(),
+                    GenBlockId::Block(id) => id,
+                },
+            )
+            .collect();
+
+        // The first blocks are connected, the rest are only stored
+        let storage2 = TestStore::new_empty().unwrap();
+        let mut tf2 = TestFramework::builder(&mut rng)
+            .with_storage(storage2.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        for block_id in &block_ids[..3] {
+            tf2.process_block(tf1.block(*block_id), BlockSource::Local).unwrap();
+        }
+        drop(tf2);
+
+        let mut db_tx = storage2.transaction_rw(None).unwrap();
+        for block_id in &block_ids[3..] {
+            let block_index = storage1.transaction_ro().unwrap().get_block_index(block_id);
+            db_tx.set_block_index(&block_index.unwrap().unwrap()).unwrap();
+            db_tx.add_block(&tf1.block(*block_id)).unwrap();
+        }
+        db_tx.set_reindex_target(&block_ids[7]).unwrap();
+        db_tx.commit().unwrap();
+        drop(tf1);
+
+        let tf2 = TestFramework::builder(&mut rng)
+            .with_storage(storage2.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        assert_eq!(tf2.best_block_id(), tip_id);
+        drop(tf2);
+        assert_eq!(storage2.dump_raw(), storage1.dump_raw());
+    });
+}
+
+// A reindex that was interrupted while dropping the derived data finishes dropping it on the
+// next startup, then reconnects the blocks
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reindex_resumes_dropping_derived_data(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_storage(storage.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        let tip_id = tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+        let tip_id = match tip_id.classify(&tf.chainstate.get_chain_config()) {
+            GenBlockId::Genesis(_) => unreachable!(),
+            GenBlockId::Block(id) => id,
+        };
+        drop(tf);
+        let expected = storage.dump_raw();
+
+        // The state left by a reindex that dropped only a batch of the derived data
+        let mut db_tx = storage.transaction_rw(None).unwrap();
+        db_tx.set_reindex_target(&tip_id).unwrap();
+        db_tx.set_best_block_id(&genesis_id).unwrap();
+        assert!(!db_tx.del_derived_data(3).unwrap());
+        db_tx.commit().unwrap();
+
+        let tf = TestFramework::builder(&mut rng)
+            .with_storage(storage.clone())
+            .with_chainstate_config(reindex_config())
+            .build();
+        assert_eq!(tf.best_block_id(), tip_id);
+        drop(tf);
+        assert_eq!(storage.dump_raw(), expected);
+    });
+}
+
+// The chain can't be rebuilt once some of its blocks are pruned
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reindex_with_pruned_blocks(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let config = ChainstateConfig::new()
+            .with_whether_tx_index_enabled(false)
+            .with_prune_mode(PruneMode::Depth(2));
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_storage(storage.clone())
+            .with_chainstate_config(config.clone())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+        drop(tf);
+
+        let result = TestFramework::builder(&mut rng)
+            .with_storage(storage)
+            .with_chainstate_config(config.with_reindex_chainstate(true))
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(ChainstateError::ProcessBlockError(
+                BlockError::ReindexWithoutBlocks
+            ))
+        );
+    });
+}
@@ -191,7 +191,9 @@ fn check_fork_that_double_spends(tf: &mut TestFramework, rng: &mut impl Rng) {
     //                         +-- 0x67fd…6419 (H:3,P:4)
     // > H - Height, M - main chain, B - block
     //
-    // Reject a chain with a double spend, even if it is longer
+    // Reject a chain with a double spend, even if it is longer.
+    // The parent descends from a block that has already failed to connect, so the new block is
+    // rejected without trying to connect it again.
     //
     let block = tf.block(*tf.block_indexes.last().unwrap().block_id());
     let spend_from = *tf.index_at(6).block_id();
@@ -201,9 +203,7 @@ fn check_fork_that_double_spends(tf: &mut TestFramework, rng: &mut impl Rng) {
             .add_double_spend_transaction(block.get_id().into(), spend_from, rng)
             .build_and_process()
             .unwrap_err(),
-        ChainstateError::ProcessBlockError(BlockError::StateUpdateFailed(
-            ConnectTransactionError::MissingOutputOrSpent
-        ))
+        ChainstateError::ProcessBlockError(BlockError::PrevBlockInvalid(block.get_id()))
     );
 }
 
@@ -386,6 +386,7 @@ fn subscribe_to_events(tf: &mut TestFramework, events: &EventList) {
                 events.lock().unwrap().push((block_id, block_height));
                 assert!(!events.lock().unwrap().is_empty());
             }
+            ChainstateEvent::BlockConnected(_, _) | ChainstateEvent::BlockDisconnected(_, _) => {}
         },
     );
     tf.chainstate.subscribe_to_events(subscribe_func);
@@ -13,6 +13,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use chainstate::{BlockError, ChainstateError, ConnectTransactionError};
 use common::chain::signed_transaction::SignedTransaction;
 use common::primitives::Idable;
 use common::{
@@ -20,6 +21,7 @@ use common::{
         signature::{
             inputsig::{InputWitness, StandardInputSignature},
             sighashtype::SigHashType,
+            TransactionSigError,
         },
         tokens::OutputValue,
         Destination, OutPointSourceId, OutputPurpose, TxInput, TxOutput,
@@ -31,7 +33,7 @@ use crypto::key::{KeyKind, PrivateKey};
 use chainstate_test_framework::TestFramework;
 use chainstate_test_framework::TransactionBuilder;
 use rstest::rstest;
-use test_utils::random::Seed;
+use test_utils::random::{Rng, Seed};
 
 #[rstest]
 #[trace]
@@ -97,3 +99,98 @@ fn signed_tx(#[case] seed: Seed) {
             .unwrap();
     });
 }
+
+// The signatures of a block are checked in parallel, a failure is reported for the exact input
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn invalid_signature_reports_input(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = test_utils::random::make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+
+        let (private_key, public_key) =
+            PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+        let (wrong_private_key, wrong_public_key) =
+            PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+        let destination = Destination::PublicKey(public_key);
+
+        let inputs_count = rng.gen_range(1..10);
+        let bad_input = rng.gen_range(0..inputs_count);
+
+        let tx_1 = (0..inputs_count)
+            .fold(
+                TransactionBuilder::new().add_input(
+                    TxInput::new(
+                        OutPointSourceId::BlockReward(
+                            tf.chainstate.get_chain_config().genesis_block_id(),
+                        ),
+                        0,
+                    ),
+                    InputWitness::NoSignature(None),
+                ),
+                |builder, _| {
+                    builder.add_output(TxOutput::new(
+                        OutputValue::Coin(Amount::from_atoms(100)),
+                        OutputPurpose::Transfer(destination.clone()),
+                    ))
+                },
+            )
+            .build();
+        let tx_1_id = tx_1.transaction().get_id();
+
+        let tx_2 = {
+            let tx = (0..inputs_count)
+                .fold(TransactionBuilder::new(), |builder, idx| {
+                    builder.add_input(
+                        TxInput::new(OutPointSourceId::Transaction(tx_1_id), idx as u32),
+                        InputWitness::NoSignature(None),
+                    )
+                })
+                .add_output(TxOutput::new(
+                    OutputValue::Coin(Amount::from_atoms(100)),
+                    OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+                ))
+                .build()
+                .transaction()
+                .clone();
+            let witnesses = (0..inputs_count)
+                .map(|idx| {
+                    let (key, key_destination) = if idx == bad_input {
+                        (
+                            &wrong_private_key,
+                            Destination::PublicKey(wrong_public_key.clone()),
+                        )
+                    } else {
+                        (&private_key, destination.clone())
+                    };
+                    let sig = StandardInputSignature::produce_signature_for_input(
+                        key,
+                        SigHashType::try_from(SigHashType::ALL).unwrap(),
+                        key_destination,
+                        &tx,
+                        idx,
+                    )
+                    .unwrap();
+                    InputWitness::Standard(sig)
+                })
+                .collect();
+            SignedTransaction::new(tx, witnesses).expect("invalid witness count")
+        };
+        let tx_2_id = tx_2.transaction().get_id();
+
+        assert_eq!(
+            tf.make_block_builder()
+                .with_transactions(vec![tx_1, tx_2])
+                .build_and_process()
+                .unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::StateUpdateFailed(
+                ConnectTransactionError::SignatureVerificationFailed(
+                    tx_2_id.into(),
+                    bad_input,
+                    TransactionSigError::SignatureVerificationFailed
+                )
+            ))
+        );
+    });
+}
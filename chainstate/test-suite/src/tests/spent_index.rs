@@ -0,0 +1,171 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::helpers::storage_without_value;
+use chainstate::{BlockError, ChainstateConfig, ChainstateError, PropertyQueryError};
+use chainstate_storage::{BlockchainStorageRead, Transactional};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{Block, GenBlock, GenBlockId, OutPoint, OutPointSourceId, Spender},
+    primitives::{Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+
+fn main_chain_blocks(tf: &TestFramework) -> Vec<Block> {
+    let chain_config = tf.chainstate.get_chain_config();
+    let best_height: u64 = tf.chainstate.get_best_block_height().unwrap().into();
+    (1..=best_height)
+        .map(|height| match tf.block_id(height).classify(&chain_config) {
+            GenBlockId::Genesis(_) => unreachable!(),
+            GenBlockId::Block(id) => tf.block(id),
+        })
+        .collect()
+}
+
+// Every input of the main chain is recorded as the spender of its output, while the outputs of
+// the tip are unspent
+fn check_spent_index(tf: &TestFramework) {
+    let blocks = main_chain_blocks(tf);
+    for block in &blocks {
+        for tx in block.transactions() {
+            let tx_id = tx.transaction().get_id();
+            for (index, input) in tx.inputs().iter().enumerate() {
+                let info = tf.chainstate.get_output_spend_info(input.outpoint()).unwrap().unwrap();
+                assert_eq!(info.spender(), &Spender::RegularInput(tx_id));
+                assert_eq!(info.input_index(), index as u32);
+                assert_eq!(info.block_id(), block.get_id());
+            }
+        }
+    }
+
+    if let Some(tip) = blocks.last() {
+        for tx in tip.transactions() {
+            let tx_id = tx.transaction().get_id();
+            for index in 0..tx.outputs().len() {
+                let outpoint = OutPoint::new(OutPointSourceId::Transaction(tx_id), index as u32);
+                assert_eq!(tf.chainstate.get_output_spend_info(&outpoint), Ok(None));
+            }
+        }
+    }
+}
+
+// The spends of the disconnected blocks are no longer attributed to their transactions
+fn check_disconnected(tf: &TestFramework, blocks: &[Block]) {
+    for block in blocks {
+        for tx in block.transactions() {
+            let tx_id = tx.transaction().get_id();
+            for input in tx.inputs() {
+                let info = tf.chainstate.get_output_spend_info(input.outpoint()).unwrap();
+                assert_ne!(
+                    info.map(|info| info.spender().clone()),
+                    Some(Spender::RegularInput(tx_id))
+                );
+            }
+        }
+    }
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn spends_follow_reorgs(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new().with_whether_spent_index_enabled(true))
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        check_spent_index(&tf);
+
+        let a_tip = tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        check_spent_index(&tf);
+        let a_blocks = main_chain_blocks(&tf);
+
+        // reorg to the b branch
+        tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        check_spent_index(&tf);
+        check_disconnected(&tf, &a_blocks);
+        let b_blocks = main_chain_blocks(&tf);
+
+        // and back to the a branch
+        tf.create_chain(&a_tip, 2, &mut rng).unwrap();
+        check_spent_index(&tf);
+        check_disconnected(&tf, &b_blocks);
+    });
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn query_fails_when_disabled(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new().with_whether_spent_index_enabled(false))
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+
+        assert_eq!(
+            tf.chainstate.get_output_spend_info(&OutPoint::new(
+                OutPointSourceId::BlockReward(genesis_id),
+                0
+            )),
+            Err(ChainstateError::FailedToReadProperty(
+                PropertyQueryError::SpentIndexDisabled
+            ))
+        );
+    });
+}
+
+// A database from before the spent-output index has no index flag; the index can't be enabled
+// once blocks are connected, as their spends are missing from it
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn enable_on_database_without_flag(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        drop(tf);
+
+        let storage = storage_without_value(&storage, "SpentIndexEnabled");
+        let result = TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new().with_whether_spent_index_enabled(true))
+            .with_storage(storage.clone())
+            .try_build();
+        assert_eq!(
+            result.err(),
+            Some(ChainstateError::ProcessBlockError(
+                BlockError::SpentIndexConfigError
+            ))
+        );
+
+        TestFramework::builder(&mut rng)
+            .with_chainstate_config(ChainstateConfig::new())
+            .with_storage(storage.clone())
+            .build();
+        assert_eq!(
+            storage.transaction_ro().unwrap().get_is_spent_index_enabled(),
+            Ok(Some(false))
+        );
+    });
+}
@@ -0,0 +1,310 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Chainstate behavior when the storage fails, using a storage backend that injects faults
+
+use std::sync::Arc;
+
+use chainstate::{
+    chainstate_interface::ChainstateInterface, BlockError, BlockSource, ChainstateConfig,
+    ChainstateError, DefaultTransactionVerificationStrategy,
+};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{Block, ChainConfig},
+    primitives::Idable,
+    time_getter::TimeGetter,
+};
+use crypto::random::Rng;
+use rstest::rstest;
+use storage_failing::{CommitFault, Failing, FaultInjector};
+use storage_inmemory::InMemory;
+use test_utils::random::{make_seedable_rng, Seed};
+
+type FailingStore = chainstate_storage::Store<Failing<InMemory>>;
+
+const MAX_DB_COMMIT_ATTEMPTS: usize = 3;
+
+fn chainstate_config() -> ChainstateConfig {
+    ChainstateConfig::new()
+        .with_max_db_commit_attempts(MAX_DB_COMMIT_ATTEMPTS)
+        .with_whether_tx_index_enabled(true)
+}
+
+/// Create a chain of blocks in a chainstate with fault-free storage, whose contents serve as the
+/// reference for what the chainstate with faults should end up with
+fn make_reference_chain(
+    rng: &mut (impl Rng + crypto::random::CryptoRng),
+    num_blocks: usize,
+) -> (Arc<ChainConfig>, TestStore, Vec<Block>) {
+    let storage = TestStore::new_empty().unwrap();
+    let mut tf = TestFramework::builder(rng)
+        .with_storage(storage.clone())
+        .with_chainstate_config(chainstate_config())
+        .build();
+
+    let blocks = (0..num_blocks)
+        .map(|_| {
+            let block = tf.make_block_builder().add_test_transaction_from_best_block(rng).build();
+            tf.process_block(block.clone(), BlockSource::Local).unwrap();
+            block
+        })
+        .collect();
+
+    (tf.chainstate.get_chain_config(), storage, blocks)
+}
+
+fn make_failing_store() -> (FaultInjector, FailingStore) {
+    let faults = FaultInjector::new();
+    let store = FailingStore::new(Failing::new(InMemory::new(), faults.clone())).unwrap();
+    (faults, store)
+}
+
+fn make_chainstate(
+    chain_config: &Arc<ChainConfig>,
+    chainstate_config: ChainstateConfig,
+    store: &FailingStore,
+) -> Result<Box<dyn ChainstateInterface>, ChainstateError> {
+    chainstate::make_chainstate(
+        Arc::clone(chain_config),
+        chainstate_config,
+        store.clone(),
+        DefaultTransactionVerificationStrategy::new(),
+        None,
+        TimeGetter::default(),
+    )
+}
+
+/// Create a chainstate over fault-injecting storage and process the given blocks
+fn make_populated_store(
+    chain_config: &Arc<ChainConfig>,
+    blocks: &[Block],
+) -> (FaultInjector, FailingStore) {
+    let (faults, store) = make_failing_store();
+    let mut chainstate = make_chainstate(chain_config, chainstate_config(), &store).unwrap();
+    for block in blocks {
+        chainstate.process_block(block.clone(), BlockSource::Local).unwrap();
+    }
+    (faults, store)
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn commit_retried_after_failure(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let num_blocks = rng.gen_range(2..8);
+    let (chain_config, reference, blocks) = make_reference_chain(&mut rng, num_blocks);
+
+    let (faults, store) = make_failing_store();
+    let mut chainstate = make_chainstate(&chain_config, chainstate_config(), &store).unwrap();
+    for block in &blocks {
+        // Every attempt but the last one fails
+        for _ in 1..MAX_DB_COMMIT_ATTEMPTS {
+            faults.push_commit_fault(CommitFault::Fail);
+        }
+        chainstate.process_block(block.clone(), BlockSource::Local).unwrap();
+        assert_eq!(faults.pending_commit_faults(), 0);
+    }
+
+    assert_eq!(store.dump_raw().unwrap(), reference.dump_raw().unwrap());
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn commit_attempts_exhausted(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let num_blocks = rng.gen_range(2..8);
+    let (chain_config, reference, blocks) = make_reference_chain(&mut rng, num_blocks);
+    let failing_block_idx = rng.gen_range(0..num_blocks);
+
+    let (faults, store) = make_populated_store(&chain_config, &blocks[..failing_block_idx]);
+    let mut chainstate = make_chainstate(&chain_config, chainstate_config(), &store).unwrap();
+    let contents_before = store.dump_raw().unwrap();
+
+    let failing_block = blocks[failing_block_idx].clone();
+    let failing_block_id = failing_block.get_id();
+    for _ in 0..MAX_DB_COMMIT_ATTEMPTS {
+        faults.push_commit_fault(CommitFault::Fail);
+    }
+    let result = chainstate.process_block(failing_block, BlockSource::Local);
+    assert!(matches!(
+        result,
+        Err(ChainstateError::ProcessBlockError(BlockError::DatabaseCommitError(
+            id,
+            MAX_DB_COMMIT_ATTEMPTS,
+            _,
+        ))) if id == failing_block_id
+    ));
+    assert_eq!(faults.pending_commit_faults(), 0);
+    assert_eq!(store.dump_raw().unwrap(), contents_before);
+
+    // Once the storage works again, the chain can be completed
+    for block in &blocks[failing_block_idx..] {
+        chainstate.process_block(block.clone(), BlockSource::Local).unwrap();
+    }
+    assert_eq!(store.dump_raw().unwrap(), reference.dump_raw().unwrap());
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy(), 1)]
+#[case(Seed::from_entropy(), 2)]
+#[case(Seed::from_entropy(), 5)]
+fn commit_attempts_counted(#[case] seed: Seed, #[case] max_attempts: usize) {
+    let mut rng = make_seedable_rng(seed);
+    let (chain_config, _, blocks) = make_reference_chain(&mut rng, 2);
+    let config = chainstate_config().with_max_db_commit_attempts(max_attempts);
+    let (faults, store) = make_failing_store();
+    let mut chainstate = make_chainstate(&chain_config, config, &store).unwrap();
+
+    // The first commit counts as an attempt, the block is committed exactly `max_attempts` times
+    for _ in 0..max_attempts {
+        faults.push_commit_fault(CommitFault::Fail);
+    }
+    let commits_before = faults.commit_count();
+    let result = chainstate.process_block(blocks[0].clone(), BlockSource::Local);
+    assert!(matches!(
+        result,
+        Err(ChainstateError::ProcessBlockError(
+            BlockError::DatabaseCommitError(_, attempts, _)
+        )) if attempts == max_attempts
+    ));
+    assert_eq!(faults.commit_count() - commits_before, max_attempts as u64);
+
+    // The last attempt succeeds
+    for _ in 1..max_attempts {
+        faults.push_commit_fault(CommitFault::Fail);
+    }
+    let commits_before = faults.commit_count();
+    chainstate.process_block(blocks[0].clone(), BlockSource::Local).unwrap();
+    assert_eq!(faults.commit_count() - commits_before, max_attempts as u64);
+    assert_eq!(faults.pending_commit_faults(), 0);
+}
+
+// Only crashes that happen before any write is persisted are exercised here. The chainstate relies
+// on the storage backends committing atomically when processing blocks, a partially persisted
+// commit of a block is not recoverable. See `crash_during_reindex_recovered_by_reindex` for the
+// partial commits that are.
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn crash_during_commit_is_retried(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let num_blocks = rng.gen_range(2..8);
+    let (chain_config, reference, blocks) = make_reference_chain(&mut rng, num_blocks);
+    let failing_block_idx = rng.gen_range(0..num_blocks);
+
+    let (faults, store) = make_populated_store(&chain_config, &blocks[..failing_block_idx]);
+
+    // Crash with no retries allowed, the block has to be processed again after a restart
+    let config = chainstate_config().with_max_db_commit_attempts(1);
+    let mut chainstate = make_chainstate(&chain_config, config, &store).unwrap();
+    let contents_before = store.dump_raw().unwrap();
+    faults.push_commit_fault(CommitFault::Crash {
+        persisted_writes: 0,
+    });
+    let result = chainstate.process_block(blocks[failing_block_idx].clone(), BlockSource::Local);
+    assert!(matches!(
+        result,
+        Err(ChainstateError::ProcessBlockError(
+            BlockError::DatabaseCommitError(_, 1, _)
+        ))
+    ));
+    assert_eq!(store.dump_raw().unwrap(), contents_before);
+    drop(chainstate);
+
+    // Restart and try again, this time with retries
+    let mut chainstate = make_chainstate(&chain_config, chainstate_config(), &store).unwrap();
+    faults.push_commit_fault(CommitFault::Crash {
+        persisted_writes: 0,
+    });
+    for block in &blocks[failing_block_idx..] {
+        chainstate.process_block(block.clone(), BlockSource::Local).unwrap();
+    }
+    assert_eq!(faults.pending_commit_faults(), 0);
+    assert_eq!(store.dump_raw().unwrap(), reference.dump_raw().unwrap());
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn interrupted_reindex_resumes(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let num_blocks = rng.gen_range(2..8);
+    let (chain_config, reference, blocks) = make_reference_chain(&mut rng, num_blocks);
+    let expected = reference.dump_raw().unwrap();
+
+    let (faults, store) = make_populated_store(&chain_config, &blocks);
+    let reindex_config = chainstate_config().with_reindex_chainstate(true);
+
+    // A complete reindex leaves the storage as it was. Count the commits made while starting up.
+    let commits_before = faults.commit_count();
+    make_chainstate(&chain_config, reindex_config.clone(), &store).unwrap();
+    let reindex_commits = faults.commit_count() - commits_before;
+    assert_eq!(store.dump_raw().unwrap(), expected);
+
+    // Reindex again, this time with one of the commits failing
+    let failing_commit = rng.gen_range(0..reindex_commits);
+    for _ in 0..failing_commit {
+        faults.push_commit_fault(CommitFault::Pass);
+    }
+    faults.push_commit_fault(CommitFault::Fail);
+    let result = make_chainstate(&chain_config, reindex_config, &store);
+    assert!(result.is_err());
+    assert_eq!(faults.pending_commit_faults(), 0);
+
+    // The reindex is completed on restart
+    let chainstate = make_chainstate(&chain_config, chainstate_config(), &store).unwrap();
+    assert_eq!(
+        chainstate.get_best_block_id().unwrap(),
+        blocks.last().unwrap().get_id()
+    );
+    assert_eq!(store.dump_raw().unwrap(), expected);
+}
+
+// A reindex only writes derived data, so a crash in any of its commits, whatever part of the commit
+// has been persisted, is recovered by reindexing again
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn crash_during_reindex_recovered_by_reindex(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let num_blocks = rng.gen_range(2..8);
+    let (chain_config, reference, blocks) = make_reference_chain(&mut rng, num_blocks);
+    let expected = reference.dump_raw().unwrap();
+    let reindex_config = chainstate_config().with_reindex_chainstate(true);
+
+    let (faults, store) = make_populated_store(&chain_config, &blocks);
+    let commits_before = faults.commit_count();
+    make_chainstate(&chain_config, reindex_config.clone(), &store).unwrap();
+    let reindex_commits = faults.commit_count() - commits_before;
+
+    for crashing_commit in 0..reindex_commits {
+        let (faults, store) = make_populated_store(&chain_config, &blocks);
+        for _ in 0..crashing_commit {
+            faults.push_commit_fault(CommitFault::Pass);
+        }
+        let persisted_writes = rng.gen_range(1..20);
+        faults.push_commit_fault(CommitFault::Crash { persisted_writes });
+        let result = make_chainstate(&chain_config, reindex_config.clone(), &store);
+        assert!(result.is_err());
+        assert_eq!(faults.pending_commit_faults(), 0);
+
+        make_chainstate(&chain_config, reindex_config.clone(), &store).unwrap();
+        assert_eq!(store.dump_raw().unwrap(), expected);
+    }
+}
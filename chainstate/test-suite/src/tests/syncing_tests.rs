@@ -343,7 +343,14 @@ fn initial_block_download(#[case] seed: Seed) {
                 max_orphan_blocks: Default::default(),
                 min_max_bootstrap_import_buffer_sizes: Default::default(),
                 tx_index_enabled: Default::default(),
+                address_index_enabled: Default::default(),
+                spent_index_enabled: Default::default(),
+                block_filters_enabled: Default::default(),
+                headers_only: Default::default(),
                 max_tip_age: Duration::from_secs(1).into(),
+                prune_mode: Default::default(),
+                assumed_valid_block: Default::default(),
+                reindex_chainstate: Default::default(),
             })
             .build();
 
@@ -0,0 +1,77 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate_test_framework::TestFramework;
+use common::{
+    chain::{block::tx_inclusion_proof::TxInclusionProof, GenBlock, GenBlockId},
+    primitives::{Id, Idable, H256},
+};
+use rstest::rstest;
+use serialization::{Decode, Encode};
+use test_utils::random::{make_seedable_rng, Seed};
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn proofs_verify_against_headers(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+
+        let chain_config = tf.chainstate.get_chain_config();
+        let mut prev_block_id = None;
+        for height in 1..=5u64 {
+            let block_id = match tf.block_id(height).classify(&chain_config) {
+                GenBlockId::Genesis(_) => unreachable!(),
+                GenBlockId::Block(id) => id,
+            };
+            let header = tf.chainstate.get_block_index(&block_id).unwrap().unwrap();
+            let header = header.block_header();
+
+            for tx in tf.block(block_id).transactions() {
+                let tx_id = tx.transaction().get_id();
+                let proof = tf.chainstate.get_tx_inclusion_proof(block_id, tx_id).unwrap().unwrap();
+
+                // The proof is checked with the header alone, after a round trip through
+                // its encoding
+                let proof = TxInclusionProof::decode(&mut proof.encode().as_slice()).unwrap();
+                assert!(proof.verify_with_header(tx, header));
+                assert!(proof.verify(tx, &header.tx_merkle_root()));
+
+                // The transaction is not in the previous block
+                if let Some(prev_block_id) = prev_block_id {
+                    assert_eq!(
+                        tf.chainstate.get_tx_inclusion_proof(prev_block_id, tx_id),
+                        Ok(None)
+                    );
+                }
+            }
+            prev_block_id = Some(block_id);
+        }
+
+        let unknown_block_id = Id::new(H256::random_using(&mut rng));
+        let unknown_tx_id = Id::new(H256::random_using(&mut rng));
+        assert_eq!(
+            tf.chainstate.get_tx_inclusion_proof(unknown_block_id, unknown_tx_id),
+            Ok(None)
+        );
+        assert_eq!(
+            tf.chainstate.get_tx_inclusion_proof(prev_block_id.unwrap(), unknown_tx_id),
+            Ok(None)
+        );
+    });
+}
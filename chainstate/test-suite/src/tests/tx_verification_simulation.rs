@@ -31,10 +31,17 @@ fn simulation(
         let mut tf = TestFramework::builder(&mut rng)
             .with_chainstate_config(chainstate::ChainstateConfig {
                 tx_index_enabled: tx_index_enabled.into(),
+                address_index_enabled: Default::default(),
+                spent_index_enabled: Default::default(),
+                block_filters_enabled: Default::default(),
+                headers_only: Default::default(),
                 max_db_commit_attempts: Default::default(),
                 max_orphan_blocks: Default::default(),
                 min_max_bootstrap_import_buffer_sizes: Default::default(),
                 max_tip_age: Default::default(),
+                prune_mode: Default::default(),
+                assumed_valid_block: Default::default(),
+                reindex_chainstate: Default::default(),
             })
             .with_tx_verification_strategy(TxVerificationStrategy::Randomized(seed))
             .build();
@@ -0,0 +1,90 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate_test_framework::TestFramework;
+use common::{
+    chain::GenBlock,
+    primitives::{Amount, Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+use utxo::UtxoSetHash;
+
+// The reported info must match the utxo set that is actually stored
+fn check_utxo_set_info(tf: &TestFramework) {
+    let info = tf.chainstate.get_utxo_set_info().unwrap();
+    let utxos = tf.storage.read_utxo_set().unwrap();
+
+    assert_eq!(info.best_block_id, tf.best_block_id());
+    assert_eq!(info.utxo_count, utxos.len() as u64);
+    let coin_amount = utxos
+        .values()
+        .filter_map(|utxo| utxo.output().value().coin_amount())
+        .sum::<Option<Amount>>()
+        .unwrap();
+    assert_eq!(info.coin_amount, coin_amount);
+    assert!(info.token_amounts.is_empty());
+    assert_eq!(info.set_hash, UtxoSetHash::from_utxos(&utxos).digest());
+}
+
+// The hash is updated incrementally as blocks are connected and disconnected
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn hash_follows_reorgs(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        check_utxo_set_info(&tf);
+
+        let a_tip = tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        check_utxo_set_info(&tf);
+        let a_hash = tf.chainstate.get_utxo_set_info().unwrap().set_hash;
+
+        // reorg to the b branch
+        tf.create_chain(&genesis_id, 4, &mut rng).unwrap();
+        check_utxo_set_info(&tf);
+        assert_ne!(tf.chainstate.get_utxo_set_info().unwrap().set_hash, a_hash);
+
+        // and back to the a branch
+        tf.create_chain(&a_tip, 2, &mut rng).unwrap();
+        check_utxo_set_info(&tf);
+    });
+}
+
+// Nodes that processed the same blocks report the same hash
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn same_hash_on_different_nodes(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        tf1.create_chain(&genesis_id, 5, &mut rng).unwrap();
+
+        let mut tf2 = TestFramework::builder(&mut rng).build();
+        for height in 1..=5 {
+            let block = tf1.block(*tf1.index_at(height).block_id());
+            tf2.process_block(block, chainstate::BlockSource::Peer).unwrap();
+        }
+
+        assert_eq!(
+            tf1.chainstate.get_utxo_set_info().unwrap(),
+            tf2.chainstate.get_utxo_set_info().unwrap()
+        );
+    });
+}
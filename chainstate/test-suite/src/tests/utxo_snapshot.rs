@@ -0,0 +1,284 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{
+    collections::BTreeMap,
+    io::{BufReader, BufWriter, Read, Write},
+};
+
+use chainstate::{
+    BlockError, BlockSource, ChainstateConfig, ChainstateError, InitializationError,
+    UtxoSnapshotError,
+};
+use chainstate_storage::{BlockchainStorageRead, TransactionRw, Transactional};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{config::Builder as ChainConfigBuilder, Block, ChainConfig, GenBlock},
+    primitives::{BlockHeight, Id, Idable, H256},
+};
+use crypto::random::{CryptoRng, Rng};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+use utxo::UtxosStorageWrite;
+
+fn export_snapshot(tf: &TestFramework) -> (Vec<u8>, H256) {
+    let mut data = Vec::new();
+    let writer: BufWriter<Box<dyn Write + Send>> = BufWriter::new(Box::new(&mut data));
+    let hash = tf.chainstate.export_utxo_snapshot_stream(writer).unwrap();
+    (data, hash)
+}
+
+fn import_snapshot(tf: &mut TestFramework, data: &[u8]) -> Result<(), ChainstateError> {
+    let reader: BufReader<Box<dyn Read + Send>> = BufReader::new(Box::new(data));
+    tf.chainstate.import_utxo_snapshot_stream(reader)
+}
+
+fn snapshot_chain_config(pinned: BTreeMap<Id<Block>, H256>) -> ChainConfig {
+    ChainConfigBuilder::test_chain().utxo_snapshot_hashes(pinned).build()
+}
+
+fn new_node(rng: &mut (impl Rng + CryptoRng), chain_config: ChainConfig) -> TestFramework {
+    TestFramework::builder(rng)
+        .with_chain_config(chain_config)
+        .with_chainstate_config(ChainstateConfig::new().with_whether_tx_index_enabled(false))
+        .build()
+}
+
+fn restart_node(
+    rng: &mut (impl Rng + CryptoRng),
+    chain_config: ChainConfig,
+    storage: TestStore,
+) -> Result<TestFramework, ChainstateError> {
+    TestFramework::builder(rng)
+        .with_chain_config(chain_config)
+        .with_chainstate_config(ChainstateConfig::new().with_whether_tx_index_enabled(false))
+        .with_storage(storage)
+        .try_build()
+}
+
+/// The history is validated on a separate thread, wait for it to get somewhere
+fn wait_for(mut condition: impl FnMut() -> bool) {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(60);
+    while !condition() {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "timed out waiting for the history validation"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+}
+
+fn history_heights(tf: &TestFramework) -> Vec<u64> {
+    tf.chainstate
+        .get_utxo_snapshot_history_headers(usize::MAX)
+        .unwrap()
+        .iter()
+        .map(|header| {
+            let block_index = tf.block_index(&header.get_id().into());
+            block_index.block_height().into()
+        })
+        .collect()
+}
+
+// A snapshot taken on one node brings another node to the same tip, and the history blocks
+// received afterwards are checked against it
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn export_and_import(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = new_node(&mut rng, snapshot_chain_config(BTreeMap::new()));
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        tf1.create_chain(&genesis_id, 5, &mut rng).unwrap();
+        let tip_id = tf1.best_block_id();
+
+        let (data, hash) = export_snapshot(&tf1);
+
+        let tip_block_id = tf1.index_at(5).block_id().to_owned();
+        assert_eq!(Id::<GenBlock>::from(tip_block_id), tip_id);
+        let pinned = BTreeMap::from([(tip_block_id, hash)]);
+        let mut tf2 = new_node(&mut rng, snapshot_chain_config(pinned));
+        import_snapshot(&mut tf2, &data).unwrap();
+
+        assert_eq!(tf2.best_block_id(), tip_id);
+        assert_eq!(
+            tf2.storage.read_utxo_set().unwrap(),
+            tf1.storage.read_utxo_set().unwrap()
+        );
+        assert_eq!(
+            tf2.chainstate.get_pruned_height().unwrap(),
+            Some(BlockHeight::new(5))
+        );
+        assert_eq!(export_snapshot(&tf2).1, hash);
+        assert_eq!(
+            tf2.chainstate.get_utxo_set_info().unwrap(),
+            tf1.chainstate.get_utxo_set_info().unwrap()
+        );
+
+        for height in 1..=5 {
+            assert_eq!(
+                tf2.storage.get_utxo_snapshot_block().unwrap(),
+                Some(tip_block_id)
+            );
+            let block = tf1.block(*tf1.index_at(height).block_id());
+            assert!(tf2.process_block(block, chainstate::BlockSource::Peer).unwrap().is_none());
+        }
+        wait_for(|| tf2.storage.get_utxo_snapshot_block().unwrap().is_none());
+
+        tf1.create_chain(&tip_id, 1, &mut rng).unwrap();
+        let new_block = tf1.block(*tf1.index_at(6).block_id());
+        tf2.process_block(new_block, chainstate::BlockSource::Peer).unwrap();
+        assert_eq!(tf2.best_block_id(), tf1.best_block_id());
+    });
+}
+
+// Only snapshots whose hash is pinned in the chain config are accepted
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn reject_unpinned_snapshot(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = new_node(&mut rng, snapshot_chain_config(BTreeMap::new()));
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        tf1.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        let tip_block_id = tf1.index_at(3).block_id().to_owned();
+        let (data, hash) = export_snapshot(&tf1);
+
+        let mut tf2 = new_node(&mut rng, snapshot_chain_config(BTreeMap::new()));
+        assert_eq!(
+            import_snapshot(&mut tf2, &data),
+            Err(ChainstateError::UtxoSnapshotError(
+                UtxoSnapshotError::HashNotPinned(tip_block_id)
+            ))
+        );
+
+        let wrong_hash = H256::from_low_u64_be(rng.gen());
+        let pinned = BTreeMap::from([(tip_block_id, wrong_hash)]);
+        let mut tf3 = new_node(&mut rng, snapshot_chain_config(pinned));
+        assert_eq!(
+            import_snapshot(&mut tf3, &data),
+            Err(ChainstateError::UtxoSnapshotError(
+                UtxoSnapshotError::PinnedHashMismatch(tip_block_id, hash, wrong_hash)
+            ))
+        );
+        assert_eq!(
+            tf3.best_block_id(),
+            Id::<GenBlock>::from(tf3.genesis().get_id())
+        );
+    });
+}
+
+// The validation state is saved every 1000 blocks and on shutdown, a restarted node continues
+// from there
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn history_validation_resumes_after_restart(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = new_node(&mut rng, snapshot_chain_config(BTreeMap::new()));
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        tf1.create_chain(&genesis_id, 1003, &mut rng).unwrap();
+        let tip_block_id = tf1.index_at(1003).block_id().to_owned();
+        let (data, hash) = export_snapshot(&tf1);
+
+        let chain_config = snapshot_chain_config(BTreeMap::from([(tip_block_id, hash)]));
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf2 = restart_node(&mut rng, chain_config.clone(), storage.clone()).unwrap();
+        import_snapshot(&mut tf2, &data).unwrap();
+        assert_eq!(history_heights(&tf2), (1..=1003).collect::<Vec<_>>());
+
+        for height in 1..=1001 {
+            let block = tf1.block(*tf1.index_at(height).block_id());
+            tf2.process_block(block, BlockSource::Peer).unwrap();
+        }
+        assert_eq!(history_heights(&tf2), vec![1002, 1003]);
+        // The first 1000 blocks are saved while the validation is running
+        wait_for(|| !storage.get_utxo_snapshot_validation_entries().unwrap().is_empty());
+        drop(tf2);
+
+        let mut tf2 = restart_node(&mut rng, chain_config, storage.clone()).unwrap();
+        assert_eq!(history_heights(&tf2), vec![1002, 1003]);
+        for height in 1002..=1003 {
+            let block = tf1.block(*tf1.index_at(height).block_id());
+            tf2.process_block(block, BlockSource::Peer).unwrap();
+        }
+        wait_for(|| storage.get_utxo_snapshot_block().unwrap().is_none());
+        assert!(history_heights(&tf2).is_empty());
+        assert!(storage.get_utxo_snapshot_validation_entries().unwrap().is_empty());
+    });
+}
+
+// A history that doesn't lead to the snapshot stops the processing of blocks for good
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn invalid_history_halts_node(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf1 = new_node(&mut rng, snapshot_chain_config(BTreeMap::new()));
+        let genesis_id: Id<GenBlock> = tf1.genesis().get_id().into();
+        tf1.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        let tip_id = tf1.best_block_id();
+        let tip_block_id = tf1.index_at(3).block_id().to_owned();
+
+        // The snapshot is missing an output that the blocks create
+        let mut db_tx = tf1.storage.transaction_rw(None).unwrap();
+        let (outpoint, utxo) = db_tx.get_utxo_set().unwrap().into_iter().next().unwrap();
+        db_tx.del_utxo(&outpoint).unwrap();
+        db_tx.commit().unwrap();
+        let (data, hash) = export_snapshot(&tf1);
+        let mut db_tx = tf1.storage.transaction_rw(None).unwrap();
+        db_tx.set_utxo(&outpoint, utxo).unwrap();
+        db_tx.commit().unwrap();
+
+        let chain_config = snapshot_chain_config(BTreeMap::from([(tip_block_id, hash)]));
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf2 = restart_node(&mut rng, chain_config.clone(), storage.clone()).unwrap();
+        import_snapshot(&mut tf2, &data).unwrap();
+
+        for height in 1..=2 {
+            let block = tf1.block(*tf1.index_at(height).block_id());
+            tf2.process_block(block, BlockSource::Peer).unwrap();
+        }
+        let block = tf1.block(*tf1.index_at(3).block_id());
+        tf2.process_block(block, BlockSource::Peer).unwrap();
+        wait_for(|| storage.get_invalid_utxo_snapshot_block().unwrap().is_some());
+        assert_eq!(
+            storage.get_invalid_utxo_snapshot_block().unwrap(),
+            Some(tip_block_id)
+        );
+
+        tf1.create_chain(&tip_id, 1, &mut rng).unwrap();
+        let new_block = tf1.block(*tf1.index_at(4).block_id());
+        assert_eq!(
+            tf2.process_block(new_block, BlockSource::Peer).unwrap_err(),
+            ChainstateError::ProcessBlockError(BlockError::UtxoSnapshotHistoryInvalid(
+                tip_block_id
+            ))
+        );
+        assert_eq!(tf2.best_block_id(), tip_id);
+        drop(tf2);
+
+        assert_eq!(
+            restart_node(&mut rng, chain_config, storage).err(),
+            Some(ChainstateError::FailedToInitializeChainstate(
+                InitializationError::UtxoSnapshotHistoryInvalid(tip_block_id)
+            ))
+        );
+    });
+}
@@ -0,0 +1,133 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate::{BlockError, ChainInconsistency, ChainstateError, MAX_VERIFY_CHAIN_DEPTH};
+use chainstate_storage::{BlockchainStorageWrite, TransactionRw, Transactional};
+use chainstate_test_framework::{TestFramework, TestStore};
+use common::{
+    chain::{Block, GenBlock, OutPoint},
+    primitives::{BlockHeight, Id, Idable},
+};
+use rstest::rstest;
+use test_utils::random::{make_seedable_rng, Seed};
+use utxo::{UtxoSource, UtxosStorageRead, UtxosStorageWrite};
+
+fn main_chain_block_id(tf: &TestFramework, height: u64) -> Id<Block> {
+    tf.block_id(height)
+        .classify(&tf.chainstate.get_chain_config())
+        .chain_block_id()
+        .unwrap()
+}
+
+// A consistent chain passes the verification, which doesn't change the storage
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn verify_consistent_chain(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng).with_storage(storage.clone()).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 3, &mut rng).unwrap();
+        tf.create_chain(&genesis_id, 6, &mut rng).unwrap();
+
+        let expected = storage.dump_raw();
+        for depth in [1, 4, 6, 100] {
+            assert_eq!(tf.chainstate.verify_chain(depth), Ok(None));
+            assert_eq!(storage.dump_raw(), expected);
+        }
+    });
+}
+
+// A utxo that differs from the one created by its block is reported
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn verify_chain_with_modified_utxo(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng).with_storage(storage.clone()).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+
+        // Modify an output created by the tip, the other ones are spent
+        let block = tf.block(main_chain_block_id(&tf, 5));
+        let tx_id = block.transactions()[0].transaction().get_id();
+        let outpoint = OutPoint::new(tx_id.into(), 0);
+        let mut db_tx = storage.transaction_rw(None).unwrap();
+        let mut utxo = db_tx.get_utxo(&outpoint).unwrap().unwrap();
+        utxo.set_height(UtxoSource::Blockchain(BlockHeight::new(1000)));
+        db_tx.set_utxo(&outpoint, utxo).unwrap();
+        db_tx.commit().unwrap();
+        let expected = storage.dump_raw();
+
+        assert_eq!(
+            tf.chainstate.verify_chain(1),
+            Ok(Some(ChainInconsistency::UtxoMismatch(outpoint)))
+        );
+        assert_eq!(storage.dump_raw(), expected);
+    });
+}
+
+// A main chain block without its data is reported
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn verify_chain_with_missing_block(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let storage = TestStore::new_empty().unwrap();
+        let mut tf = TestFramework::builder(&mut rng).with_storage(storage.clone()).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 5, &mut rng).unwrap();
+
+        let block_id = main_chain_block_id(&tf, 3);
+        let mut db_tx = storage.transaction_rw(None).unwrap();
+        db_tx.del_block(block_id).unwrap();
+        db_tx.commit().unwrap();
+
+        assert_eq!(tf.chainstate.verify_chain(2), Ok(None));
+        assert_eq!(
+            tf.chainstate.verify_chain(5),
+            Ok(Some(ChainInconsistency::BlockMissing(block_id)))
+        );
+    });
+}
+
+// The depth of the verification is limited, as the rebuilt state is kept in memory
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn verify_chain_depth_limit(#[case] seed: Seed) {
+    utils::concurrency::model(move || {
+        let mut rng = make_seedable_rng(seed);
+        let mut tf = TestFramework::builder(&mut rng).build();
+        let genesis_id: Id<GenBlock> = tf.genesis().get_id().into();
+        tf.create_chain(&genesis_id, 2, &mut rng).unwrap();
+
+        assert_eq!(tf.chainstate.verify_chain(MAX_VERIFY_CHAIN_DEPTH), Ok(None));
+        assert_eq!(
+            tf.chainstate.verify_chain(MAX_VERIFY_CHAIN_DEPTH + 1),
+            Err(ChainstateError::ProcessBlockError(
+                BlockError::VerifyChainDepthTooLarge(
+                    MAX_VERIFY_CHAIN_DEPTH + 1,
+                    MAX_VERIFY_CHAIN_DEPTH
+                )
+            ))
+        );
+    });
+}
@@ -0,0 +1,169 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Address index cache with enabled/disabled flag.
+
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use chainstate_types::{AddressIndexKey, AddressOutputInfo, AddressOutputSpend};
+
+use super::{
+    config::TransactionVerifierConfig,
+    error::{AddressIndexError, ConnectTransactionError},
+    storage::TransactionVerifierStorageError,
+    CachedOperation,
+};
+
+pub type CachedAddressIndexOp = CachedOperation<AddressOutputInfo>;
+
+pub type AddressIndexMap = BTreeMap<AddressIndexKey, CachedAddressIndexOp>;
+
+/// Changes to the outputs and spends recorded by destination, if address indexing is enabled
+pub struct AddressIndexCache {
+    enabled: bool,
+    data: AddressIndexMap,
+}
+
+impl AddressIndexCache {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            data: AddressIndexMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &TransactionVerifierConfig) -> Self {
+        Self::new(config.address_index_enabled)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn consume(self) -> AddressIndexMap {
+        self.data
+    }
+
+    pub fn get_from_cached(&self, key: &AddressIndexKey) -> Option<&CachedAddressIndexOp> {
+        self.data.get(key)
+    }
+
+    pub fn add_output(
+        &mut self,
+        key: AddressIndexKey,
+        info: AddressOutputInfo,
+    ) -> Result<(), AddressIndexError> {
+        match self.data.entry(key) {
+            Entry::Occupied(mut entry) => match entry.get() {
+                CachedOperation::Write(_) | CachedOperation::Read(_) => {
+                    return Err(AddressIndexError::OutputAlreadyPresent(
+                        entry.key().2.clone(),
+                    ));
+                }
+                // the output was disconnected and is now connected again
+                CachedOperation::Erase => {
+                    entry.insert(CachedOperation::Write(info));
+                }
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(CachedOperation::Write(info));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_output(&mut self, key: AddressIndexKey, info: AddressOutputInfo) {
+        // possible overwrite is ok
+        self.data.insert(key, CachedOperation::Write(info));
+    }
+
+    pub fn remove_output(&mut self, key: AddressIndexKey) {
+        // possible overwrite is ok
+        self.data.insert(key, CachedOperation::Erase);
+    }
+
+    pub fn spend_output<F>(
+        &mut self,
+        key: &AddressIndexKey,
+        spend: AddressOutputSpend,
+        fetcher_func: F,
+    ) -> Result<(), ConnectTransactionError>
+    where
+        F: Fn(
+            &AddressIndexKey,
+        ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError>,
+    {
+        let op = self.fetch_mut(key, fetcher_func)?;
+        let mut info = Self::info_from_op(key, op)?;
+        if info.is_spent() {
+            return Err(AddressIndexError::AlreadySpent(key.2.clone()).into());
+        }
+        info.set_spend(Some(spend));
+        *op = CachedOperation::Write(info);
+        Ok(())
+    }
+
+    pub fn unspend_output<F>(
+        &mut self,
+        key: &AddressIndexKey,
+        fetcher_func: F,
+    ) -> Result<(), ConnectTransactionError>
+    where
+        F: Fn(
+            &AddressIndexKey,
+        ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError>,
+    {
+        let op = self.fetch_mut(key, fetcher_func)?;
+        let mut info = Self::info_from_op(key, op)?;
+        if !info.is_spent() {
+            return Err(AddressIndexError::InvariantBrokenAlreadyUnspent(key.2.clone()).into());
+        }
+        info.set_spend(None);
+        *op = CachedOperation::Write(info);
+        Ok(())
+    }
+
+    fn fetch_mut<F>(
+        &mut self,
+        key: &AddressIndexKey,
+        fetcher_func: F,
+    ) -> Result<&mut CachedAddressIndexOp, ConnectTransactionError>
+    where
+        F: Fn(
+            &AddressIndexKey,
+        ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError>,
+    {
+        let op = match self.data.entry(key.clone()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // Maybe the output is in a previous block?
+                let info = fetcher_func(key)?
+                    .ok_or_else(|| AddressIndexError::MissingOutput(key.2.clone()))?;
+                entry.insert(CachedOperation::Read(info))
+            }
+        };
+        Ok(op)
+    }
+
+    fn info_from_op(
+        key: &AddressIndexKey,
+        op: &CachedAddressIndexOp,
+    ) -> Result<AddressOutputInfo, AddressIndexError> {
+        match op {
+            CachedOperation::Write(info) | CachedOperation::Read(info) => Ok(info.clone()),
+            CachedOperation::Erase => Err(AddressIndexError::OutputErased(key.2.clone())),
+        }
+    }
+}
@@ -15,13 +15,10 @@
 
 use std::collections::BTreeMap;
 
-use common::primitives::Amount;
+use common::{chain::tokens::CoinOrTokenId, primitives::Amount};
 use fallible_iterator::{FallibleIterator, IntoFallibleIterator};
 
-use super::{
-    error::{ConnectTransactionError, TokensError},
-    token_issuance_cache::CoinOrTokenId,
-};
+use super::error::{ConnectTransactionError, TokensError};
 
 /// A temporary type used to accumulate token type vs amount
 #[derive(Debug)]
@@ -16,11 +16,33 @@
 #[derive(Clone)]
 pub struct TransactionVerifierConfig {
     pub tx_index_enabled: bool,
+    /// Record the outputs and spends of the transactions from the chain by destination.
+    pub address_index_enabled: bool,
+    /// Record the inputs that spend outputs in the transactions from the chain.
+    pub spent_index_enabled: bool,
+    /// Don't verify the input signatures of the transactions from the chain.
+    /// Used for the blocks that are ancestors of the assumed valid block.
+    pub skip_signature_verification: bool,
 }
 
 impl TransactionVerifierConfig {
     pub fn new(tx_index_enabled: bool) -> Self {
-        Self { tx_index_enabled }
+        Self {
+            tx_index_enabled,
+            address_index_enabled: false,
+            spent_index_enabled: false,
+            skip_signature_verification: false,
+        }
+    }
+
+    pub fn with_address_index_enabled(mut self, address_index_enabled: bool) -> Self {
+        self.address_index_enabled = address_index_enabled;
+        self
+    }
+
+    pub fn with_spent_index_enabled(mut self, spent_index_enabled: bool) -> Self {
+        self.spent_index_enabled = spent_index_enabled;
+        self
     }
 
     /// If transaction index is enabled, the function f is called, otherwise Ok(None) is returned
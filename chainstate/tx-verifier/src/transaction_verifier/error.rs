@@ -19,7 +19,7 @@ use common::{
         block::{Block, GenBlock},
         signature::TransactionSigError,
         tokens::TokenId,
-        OutPointSourceId, SpendError, Spender, Transaction, TxMainChainIndexError,
+        OutPoint, OutPointSourceId, SpendError, Spender, Transaction, TxMainChainIndexError,
     },
     primitives::{Amount, BlockHeight, Id},
 };
@@ -55,8 +55,8 @@ pub enum ConnectTransactionError {
     AttemptToPrintMoney(Amount, Amount),
     #[error("Fee calculation failed (total inputs: `{0:?}` vs total outputs `{1:?}`")]
     TxFeeTotalCalcFailed(Amount, Amount),
-    #[error("Signature verification failed in transaction")]
-    SignatureVerificationFailed(#[from] TransactionSigError),
+    #[error("Signature verification failed for input {1} of {0:?}: {2}")]
+    SignatureVerificationFailed(OutPointSourceId, usize, TransactionSigError),
     #[error("Error while calculating block height; possibly an overflow")]
     BlockHeightArithmeticError,
     #[error("Error while calculating timestamps; possibly an overflow")]
@@ -79,6 +79,10 @@ pub enum ConnectTransactionError {
     TokensError(#[from] TokensError),
     #[error("Tx index error: {0}")]
     TxIndexError(#[from] TxIndexError),
+    #[error("Address index error: {0}")]
+    AddressIndexError(#[from] AddressIndexError),
+    #[error("Spent-output index error: {0}")]
+    SpentIndexError(#[from] SpentIndexError),
     #[error("Error from TransactionVerifierStorage: {0}")]
     TransactionVerifierError(#[from] TransactionVerifierStorageError),
     #[error("utxo BlockUndo error: {0}")]
@@ -158,6 +162,26 @@ impl From<TxMainChainIndexError> for TxIndexError {
     }
 }
 
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum AddressIndexError {
+    #[error("Output {0:?} is already present in the address index")]
+    OutputAlreadyPresent(OutPoint),
+    #[error("Output {0:?} is not found in the address index")]
+    MissingOutput(OutPoint),
+    #[error("Output {0:?} was erased from the address index in a previous step")]
+    OutputErased(OutPoint),
+    #[error("Output {0:?} is already spent in the address index")]
+    AlreadySpent(OutPoint),
+    #[error("Output {0:?} is already unspent in the address index (invariant broken)")]
+    InvariantBrokenAlreadyUnspent(OutPoint),
+}
+
+#[derive(Error, Debug, PartialEq, Eq, Clone)]
+pub enum SpentIndexError {
+    #[error("Output {0:?} is already spent in the spent-output index")]
+    AlreadySpent(OutPoint),
+}
+
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
 pub enum TokensError {
     #[error("Blockchain storage error: {0}")]
@@ -14,11 +14,14 @@
 // limitations under the License.
 
 use super::{
+    address_index_cache::CachedAddressIndexOp,
+    spent_index_cache::CachedSpentIndexOp,
     storage::{TransactionVerifierStorageError, TransactionVerifierStorageMut},
     token_issuance_cache::{CachedAuxDataOp, CachedTokenIndexOp, ConsumedTokenIssuanceCache},
     CachedInputsOperation, TransactionVerifierDelta,
 };
-use common::chain::OutPointSourceId;
+use chainstate_types::AddressIndexKey;
+use common::chain::{OutPoint, OutPointSourceId};
 
 fn flush_tx_indexes(
     storage: &mut impl TransactionVerifierStorageMut,
@@ -35,6 +38,32 @@ fn flush_tx_indexes(
     Ok(())
 }
 
+fn flush_address_index(
+    storage: &mut impl TransactionVerifierStorageMut,
+    key: AddressIndexKey,
+    op: CachedAddressIndexOp,
+) -> Result<(), TransactionVerifierStorageError> {
+    match op {
+        CachedAddressIndexOp::Write(ref info) => storage.set_address_index_entry(&key, info)?,
+        CachedAddressIndexOp::Read(_) => (),
+        CachedAddressIndexOp::Erase => storage.del_address_index_entry(&key)?,
+    }
+    Ok(())
+}
+
+fn flush_spent_index(
+    storage: &mut impl TransactionVerifierStorageMut,
+    outpoint: OutPoint,
+    op: CachedSpentIndexOp,
+) -> Result<(), TransactionVerifierStorageError> {
+    match op {
+        CachedSpentIndexOp::Write(ref info) => storage.set_output_spend_info(&outpoint, info)?,
+        CachedSpentIndexOp::Read(_) => (),
+        CachedSpentIndexOp::Erase => storage.del_output_spend_info(&outpoint)?,
+    }
+    Ok(())
+}
+
 fn flush_tokens(
     storage: &mut impl TransactionVerifierStorageMut,
     token_cache: &ConsumedTokenIssuanceCache,
@@ -81,6 +110,14 @@ pub fn flush_to_storage(
         flush_tx_indexes(storage, tx_id, tx_index_op)?;
     }
 
+    for (key, op) in consumed.address_index_cache {
+        flush_address_index(storage, key, op)?;
+    }
+
+    for (outpoint, op) in consumed.spent_index_cache {
+        flush_spent_index(storage, outpoint, op)?;
+    }
+
     flush_tokens(storage, &consumed.token_issuance_cache)?;
 
     // flush utxo set
@@ -16,7 +16,9 @@
 use std::collections::BTreeMap;
 
 use super::{
+    address_index_cache::CachedAddressIndexOp,
     cached_operation::CachedInputsOperation,
+    spent_index_cache::CachedSpentIndexOp,
     storage::{
         TransactionVerifierStorageError, TransactionVerifierStorageMut,
         TransactionVerifierStorageRef,
@@ -24,7 +26,9 @@ use super::{
     token_issuance_cache::{CachedAuxDataOp, CachedTokenIndexOp},
     TransactionSource, TransactionVerifier,
 };
-use chainstate_types::{storage_result, GenBlockIndex};
+use chainstate_types::{
+    storage_result, AddressIndexKey, AddressOutputInfo, GenBlockIndex, OutputSpendInfo,
+};
 use common::{
     chain::{
         tokens::{TokenAuxiliaryData, TokenId},
@@ -36,7 +40,9 @@ use pos_accounting::{
     AccountingBlockUndo, DelegationData, DelegationId, DeltaMergeUndo, FlushablePoSAccountingView,
     PoSAccountingDeltaData, PoSAccountingView, PoolData, PoolId,
 };
-use utxo::{ConsumedUtxoCache, FlushableUtxoView, UtxosBlockUndo, UtxosStorageRead, UtxosView};
+use utxo::{
+    ConsumedUtxoCache, FlushableUtxoView, UtxoSetHash, UtxosBlockUndo, UtxosStorageRead, UtxosView,
+};
 
 impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView>
     TransactionVerifierStorageRef for TransactionVerifier<C, S, U, A>
@@ -103,6 +109,34 @@ impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView>
             None => self.storage.get_accounting_undo(id),
         }
     }
+
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError> {
+        match self.address_index_cache.get_from_cached(key) {
+            Some(v) => match v {
+                CachedAddressIndexOp::Write(info) => Ok(Some(info.clone())),
+                CachedAddressIndexOp::Read(info) => Ok(Some(info.clone())),
+                CachedAddressIndexOp::Erase => Ok(None),
+            },
+            None => self.storage.get_address_index_entry(key),
+        }
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError> {
+        match self.spent_index_cache.get_from_cached(outpoint) {
+            Some(v) => match v {
+                CachedSpentIndexOp::Write(info) => Ok(Some(info.clone())),
+                CachedSpentIndexOp::Read(info) => Ok(Some(info.clone())),
+                CachedSpentIndexOp::Erase => Ok(None),
+            },
+            None => self.storage.get_output_spend_info(outpoint),
+        }
+    }
 }
 
 impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView> UtxosStorageRead
@@ -125,6 +159,15 @@ impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView> Ut
             None => self.storage.get_undo_data(id),
         }
     }
+
+    // The hashes are only computed when the changes reach the database, so the pending changes
+    // of the verifier are not reflected here
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<UtxoSetHash>, storage_result::Error> {
+        self.storage.get_utxo_set_hash(block_id)
+    }
 }
 
 impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView>
@@ -232,6 +275,56 @@ impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView>
             .del_undo_data(tx_source)
             .map_err(TransactionVerifierStorageError::AccountingBlockUndoError)
     }
+
+    fn set_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+        info: &AddressOutputInfo,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        ::utils::ensure!(
+            self.address_index_cache.enabled(),
+            TransactionVerifierStorageError::AddressIndexDisabled
+        );
+        self.address_index_cache.set_output(key.clone(), info.clone());
+        Ok(())
+    }
+
+    fn del_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        ::utils::ensure!(
+            self.address_index_cache.enabled(),
+            TransactionVerifierStorageError::AddressIndexDisabled
+        );
+        self.address_index_cache.remove_output(key.clone());
+        Ok(())
+    }
+
+    fn set_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+        info: &OutputSpendInfo,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        ::utils::ensure!(
+            self.spent_index_cache.enabled(),
+            TransactionVerifierStorageError::SpentIndexDisabled
+        );
+        self.spent_index_cache.set_spend(outpoint.clone(), info.clone());
+        Ok(())
+    }
+
+    fn del_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+    ) -> Result<(), TransactionVerifierStorageError> {
+        ::utils::ensure!(
+            self.spent_index_cache.enabled(),
+            TransactionVerifierStorageError::SpentIndexDisabled
+        );
+        self.spent_index_cache.remove_spend(outpoint.clone());
+        Ok(())
+    }
 }
 
 impl<C, S: TransactionVerifierStorageRef, U: UtxosView, A: PoSAccountingView> FlushableUtxoView
@@ -14,6 +14,7 @@
 // limitations under the License.
 
 mod accounting_undo_cache;
+mod address_index_cache;
 mod amounts_map;
 mod cached_operation;
 mod token_issuance_cache;
@@ -26,45 +27,55 @@ pub mod error;
 pub mod flush;
 pub mod hierarchy;
 mod optional_tx_index_cache;
+mod signature_check;
+mod spent_index_cache;
 pub mod storage;
+pub mod timelock_check;
 
 use std::collections::BTreeMap;
 
 use self::{
     accounting_undo_cache::{AccountingBlockUndoCache, AccountingBlockUndoEntry},
+    address_index_cache::{AddressIndexCache, AddressIndexMap},
     amounts_map::AmountsMap,
     cached_operation::CachedInputsOperation,
     config::TransactionVerifierConfig,
     error::{ConnectTransactionError, TokensError},
     optional_tx_index_cache::OptionalTxIndexCache,
+    signature_check::{verify_signatures_in_parallel, DeferredSignatureCheck},
+    spent_index_cache::{SpentIndexCache, SpentIndexMap},
     storage::TransactionVerifierStorageRef,
-    token_issuance_cache::{CoinOrTokenId, ConsumedTokenIssuanceCache, TokenIssuanceCache},
+    timelock_check::check_timelock,
+    token_issuance_cache::{ConsumedTokenIssuanceCache, TokenIssuanceCache},
     utils::{
         calculate_total_outputs, check_transferred_amount, get_input_token_id_and_amount,
         get_total_fee,
     },
     utxos_undo_cache::{UtxosBlockUndoCache, UtxosBlockUndoEntry},
 };
-use ::utils::{ensure, shallow_clone::ShallowClone};
+use ::utils::shallow_clone::ShallowClone;
 
-use chainstate_types::{block_index_ancestor_getter, BlockIndex, GenBlockIndex};
+use chainstate_types::{
+    block_index_ancestor_getter, AddressIndexKey, AddressOutputInfo, AddressOutputSpend,
+    BlockIndex, OutputSpendInfo,
+};
 use common::{
     amount_sum,
     chain::{
         block::{timestamp::BlockTimestamp, BlockRewardTransactable},
         signature::{verify_signature, Signable, Transactable},
         signed_transaction::SignedTransaction,
-        tokens::{get_tokens_issuance_count, OutputValue, TokenId},
-        Block, ChainConfig, GenBlock, OutPointSourceId, OutputPurpose, Transaction, TxInput,
-        TxMainChainIndex, TxOutput,
+        tokens::{get_tokens_issuance_count, CoinOrTokenId, OutputValue, TokenId},
+        Block, ChainConfig, GenBlock, OutPoint, OutPointSourceId, OutputPurpose, Spender,
+        Transaction, TxInput, TxMainChainIndex, TxOutput,
     },
-    primitives::{id::WithId, Amount, BlockDistance, BlockHeight, Id, Idable, H256},
+    primitives::{id::WithId, Amount, BlockHeight, Id, Idable, H256},
 };
 use pos_accounting::{
     PoSAccountingDelta, PoSAccountingDeltaData, PoSAccountingOperations, PoSAccountingUndo,
     PoSAccountingView,
 };
-use utxo::{ConsumedUtxoCache, Utxo, UtxosCache, UtxosDB, UtxosView};
+use utxo::{ConsumedUtxoCache, UtxosCache, UtxosDB, UtxosView};
 
 // TODO: We can move it to mod common, because in chain config we have `token_min_issuance_fee`
 //       that essentially belongs to this type, but return Amount
@@ -163,6 +174,8 @@ impl<'a> TransactionSourceForConnect<'a> {
 #[derive(Debug, Eq, PartialEq)]
 pub struct TransactionVerifierDelta {
     tx_index_cache: BTreeMap<OutPointSourceId, CachedInputsOperation>,
+    address_index_cache: AddressIndexMap,
+    spent_index_cache: SpentIndexMap,
     utxo_cache: ConsumedUtxoCache,
     utxo_block_undo: BTreeMap<TransactionSource, UtxosBlockUndoEntry>,
     token_issuance_cache: ConsumedTokenIssuanceCache,
@@ -177,6 +190,8 @@ pub struct TransactionVerifier<C, S, U, A> {
     best_block: Id<GenBlock>,
 
     tx_index_cache: OptionalTxIndexCache,
+    address_index_cache: AddressIndexCache,
+    spent_index_cache: SpentIndexCache,
     token_issuance_cache: TokenIssuanceCache,
 
     utxo_cache: UtxosCache<U>,
@@ -184,6 +199,10 @@ pub struct TransactionVerifier<C, S, U, A> {
 
     accounting_delta: PoSAccountingDelta<A>,
     accounting_block_undo: AccountingBlockUndoCache,
+
+    skip_signature_verification: bool,
+    // Signature checks of the block transactions, done in parallel once the block is connected
+    deferred_signature_checks: Option<Vec<DeferredSignatureCheck>>,
 }
 
 impl<C, S: TransactionVerifierStorageRef + ShallowClone> TransactionVerifier<C, S, UtxosDB<S>, S> {
@@ -200,11 +219,15 @@ impl<C, S: TransactionVerifierStorageRef + ShallowClone> TransactionVerifier<C,
             chain_config,
             best_block,
             tx_index_cache,
+            address_index_cache: AddressIndexCache::from_config(&verifier_config),
+            spent_index_cache: SpentIndexCache::from_config(&verifier_config),
             token_issuance_cache: TokenIssuanceCache::new(),
             utxo_cache,
             utxo_block_undo: UtxosBlockUndoCache::new(),
             accounting_delta,
             accounting_block_undo: AccountingBlockUndoCache::new(),
+            skip_signature_verification: verifier_config.skip_signature_verification,
+            deferred_signature_checks: None,
         }
     }
 }
@@ -232,11 +255,15 @@ where
             chain_config,
             best_block,
             tx_index_cache,
+            address_index_cache: AddressIndexCache::from_config(&verifier_config),
+            spent_index_cache: SpentIndexCache::from_config(&verifier_config),
             token_issuance_cache: TokenIssuanceCache::new(),
             utxo_cache: UtxosCache::new(utxos), // TODO: take utxos from handle
             utxo_block_undo: UtxosBlockUndoCache::new(),
             accounting_delta: PoSAccountingDelta::new(accounting),
             accounting_block_undo: AccountingBlockUndoCache::new(),
+            skip_signature_verification: verifier_config.skip_signature_verification,
+            deferred_signature_checks: None,
         }
     }
 }
@@ -255,19 +282,23 @@ where
             storage: self,
             chain_config: self.chain_config.as_ref(),
             tx_index_cache: OptionalTxIndexCache::new(self.tx_index_cache.enabled()),
+            address_index_cache: AddressIndexCache::new(self.address_index_cache.enabled()),
+            spent_index_cache: SpentIndexCache::new(self.spent_index_cache.enabled()),
             utxo_cache: UtxosCache::new(&self.utxo_cache),
             utxo_block_undo: UtxosBlockUndoCache::new(),
             token_issuance_cache: TokenIssuanceCache::new(),
             accounting_delta: PoSAccountingDelta::new(&self.accounting_delta),
             accounting_block_undo: AccountingBlockUndoCache::new(),
             best_block: self.best_block,
+            skip_signature_verification: self.skip_signature_verification,
+            deferred_signature_checks: None,
         }
     }
 
     fn amount_from_outpoint(
         &self,
         tx_id: OutPointSourceId,
-        utxo: Utxo,
+        output_value: &OutputValue,
     ) -> Result<(CoinOrTokenId, Amount), ConnectTransactionError> {
         match tx_id {
             OutPointSourceId::Transaction(tx_id) => {
@@ -278,12 +309,11 @@ where
                             .map_err(ConnectTransactionError::TransactionVerifierError)
                     };
                 let (key, amount) =
-                    get_input_token_id_and_amount(utxo.output().value(), issuance_token_id_getter)?;
+                    get_input_token_id_and_amount(output_value, issuance_token_id_getter)?;
                 Ok((key, amount))
             }
             OutPointSourceId::BlockReward(_) => {
-                let (key, amount) =
-                    get_input_token_id_and_amount(utxo.output().value(), || Ok(None))?;
+                let (key, amount) = get_input_token_id_and_amount(output_value, || Ok(None))?;
                 match key {
                     CoinOrTokenId::Coin => Ok((CoinOrTokenId::Coin, amount)),
                     CoinOrTokenId::TokenId(tid) => Ok((CoinOrTokenId::TokenId(tid), amount)),
@@ -301,7 +331,7 @@ where
                 .utxo_cache
                 .utxo(input.outpoint())
                 .ok_or(ConnectTransactionError::MissingOutputOrSpent)?;
-            self.amount_from_outpoint(input.outpoint().tx_id(), utxo)
+            self.amount_from_outpoint(input.outpoint().tx_id(), utxo.output().value())
         });
 
         let iter = fallible_iterator::convert(iter);
@@ -408,51 +438,11 @@ where
         Ok(())
     }
 
-    fn check_timelock(
+    fn verify_signatures<T: Transactable>(
         &self,
-        source_block_index: &GenBlockIndex,
-        output: &TxOutput,
-        spend_height: &BlockHeight,
-        spending_time: &BlockTimestamp,
+        source_id: OutPointSourceId,
+        tx: &T,
     ) -> Result<(), ConnectTransactionError> {
-        use common::chain::timelock::OutputTimeLock;
-
-        let timelock = match output.purpose() {
-            OutputPurpose::Transfer(_) => return Ok(()),
-            OutputPurpose::LockThenTransfer(_, tl) => tl,
-            OutputPurpose::StakePool(_) => return Ok(()),
-            OutputPurpose::Burn => return Ok(()),
-        };
-
-        let source_block_height = source_block_index.block_height();
-        let source_block_time = source_block_index.block_timestamp();
-
-        let past_lock = match timelock {
-            OutputTimeLock::UntilHeight(h) => spend_height >= h,
-            OutputTimeLock::UntilTime(t) => spending_time >= t,
-            OutputTimeLock::ForBlockCount(d) => {
-                let d: i64 = (*d)
-                    .try_into()
-                    .map_err(|_| ConnectTransactionError::BlockHeightArithmeticError)?;
-                let d = BlockDistance::from(d);
-                *spend_height
-                    >= (source_block_height + d)
-                        .ok_or(ConnectTransactionError::BlockHeightArithmeticError)?
-            }
-            OutputTimeLock::ForSeconds(dt) => {
-                *spending_time
-                    >= source_block_time
-                        .add_int_seconds(*dt)
-                        .ok_or(ConnectTransactionError::BlockTimestampArithmeticError)?
-            }
-        };
-
-        ensure!(past_lock, ConnectTransactionError::TimeLockViolation);
-
-        Ok(())
-    }
-
-    fn verify_signatures<T: Transactable>(&self, tx: &T) -> Result<(), ConnectTransactionError> {
         let inputs = match tx.inputs() {
             Some(ins) => ins,
             None => return Ok(()),
@@ -468,8 +458,13 @@ where
             // TODO: see if a different treatment should be done for different output purposes
             // TODO: ensure that signature verification is tested in the test-suite, they seem to be tested only internally
             match utxo.output().purpose().destination() {
-                Some(d) => verify_signature(d, tx, input_idx)
-                    .map_err(ConnectTransactionError::SignatureVerificationFailed)?,
+                Some(d) => verify_signature(d, tx, input_idx).map_err(|err| {
+                    ConnectTransactionError::SignatureVerificationFailed(
+                        source_id.clone(),
+                        input_idx,
+                        err,
+                    )
+                })?,
                 None => return Err(ConnectTransactionError::AttemptToSpendBurnedAmount),
             }
         }
@@ -477,6 +472,56 @@ where
         Ok(())
     }
 
+    fn verify_or_defer_signatures(
+        &mut self,
+        tx: &SignedTransaction,
+    ) -> Result<(), ConnectTransactionError> {
+        let checks = match self.deferred_signature_checks.as_mut() {
+            Some(checks) => checks,
+            None => return self.verify_signatures(tx.transaction().get_id().into(), tx),
+        };
+
+        let tx_id = tx.transaction().get_id();
+        for (input_num, input) in tx.inputs().iter().enumerate() {
+            let utxo = self
+                .utxo_cache
+                .utxo(input.outpoint())
+                .ok_or(ConnectTransactionError::MissingOutputOrSpent)?;
+            let destination = utxo
+                .output()
+                .purpose()
+                .destination()
+                .ok_or(ConnectTransactionError::AttemptToSpendBurnedAmount)?;
+            checks.push(DeferredSignatureCheck::new(
+                tx_id,
+                input_num,
+                destination.clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Postpone the signature checks of the transactions from the chain until
+    /// `verify_deferred_signatures` is called, so that the whole block is checked in parallel
+    pub fn defer_signature_checks(&mut self) {
+        self.deferred_signature_checks.get_or_insert_with(Vec::new);
+    }
+
+    /// Do the postponed signature checks of the transactions of the given block
+    pub fn verify_deferred_signatures(
+        &mut self,
+        block: &WithId<Block>,
+    ) -> Result<(), ConnectTransactionError> {
+        match self.deferred_signature_checks.as_mut() {
+            Some(checks) => {
+                let checks = std::mem::take(checks);
+                verify_signatures_in_parallel(block, &checks)
+            }
+            None => Ok(()),
+        }
+    }
+
     fn check_timelocks<T: Transactable>(
         &self,
         tx_source: &TransactionSourceForConnect,
@@ -531,8 +576,9 @@ where
                     )
                 })?;
 
-                self.check_timelock(
-                    &source_block_index,
+                check_timelock(
+                    &source_block_index.block_height(),
+                    &source_block_index.block_timestamp(),
                     utxo.output(),
                     &tx_source.expected_block_height(),
                     spending_time,
@@ -617,6 +663,104 @@ where
         })
     }
 
+    /// The address index key of an output and its amount, or None if the output has no destination
+    fn address_index_key(
+        &self,
+        outpoint: OutPoint,
+        output: &TxOutput,
+    ) -> Result<Option<(AddressIndexKey, Amount)>, ConnectTransactionError> {
+        let destination = match output.purpose().destination() {
+            Some(destination) => destination.clone(),
+            None => return Ok(None),
+        };
+        let (coin_or_token_id, amount) =
+            self.amount_from_outpoint(outpoint.tx_id(), output.value())?;
+        Ok(Some(((destination, coin_or_token_id, outpoint), amount)))
+    }
+
+    // Must be called while the inputs are still in the utxo set
+    fn connect_address_index(
+        &mut self,
+        source_id: OutPointSourceId,
+        inputs: &[TxInput],
+        outputs: &[TxOutput],
+        spender: Spender,
+        block_height: BlockHeight,
+    ) -> Result<(), ConnectTransactionError> {
+        for input in inputs {
+            let utxo = self
+                .utxo_cache
+                .utxo(input.outpoint())
+                .ok_or(ConnectTransactionError::MissingOutputOrSpent)?;
+            if let Some((key, _)) =
+                self.address_index_key(input.outpoint().clone(), utxo.output())?
+            {
+                let spend = AddressOutputSpend::new(spender.clone(), block_height);
+                self.address_index_cache
+                    .spend_output(&key, spend, |key| self.storage.get_address_index_entry(key))?;
+            }
+        }
+
+        for (idx, output) in outputs.iter().enumerate() {
+            let outpoint = OutPoint::new(source_id.clone(), idx as u32);
+            if let Some((key, amount)) = self.address_index_key(outpoint, output)? {
+                self.address_index_cache
+                    .add_output(key, AddressOutputInfo::new(amount, block_height))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // Must be called once the inputs are restored in the utxo set
+    fn disconnect_address_index(
+        &mut self,
+        source_id: OutPointSourceId,
+        inputs: &[TxInput],
+        outputs: &[TxOutput],
+    ) -> Result<(), ConnectTransactionError> {
+        for (idx, output) in outputs.iter().enumerate() {
+            let outpoint = OutPoint::new(source_id.clone(), idx as u32);
+            if let Some((key, _)) = self.address_index_key(outpoint, output)? {
+                self.address_index_cache.remove_output(key);
+            }
+        }
+
+        for input in inputs {
+            let utxo = self
+                .utxo_cache
+                .utxo(input.outpoint())
+                .ok_or(ConnectTransactionError::MissingOutputOrSpent)?;
+            if let Some((key, _)) =
+                self.address_index_key(input.outpoint().clone(), utxo.output())?
+            {
+                self.address_index_cache
+                    .unspend_output(&key, |key| self.storage.get_address_index_entry(key))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn connect_spent_index(
+        &mut self,
+        inputs: &[TxInput],
+        spender: Spender,
+        block_id: Id<Block>,
+    ) -> Result<(), ConnectTransactionError> {
+        for (idx, input) in inputs.iter().enumerate() {
+            let info = OutputSpendInfo::new(spender.clone(), idx as u32, block_id);
+            self.spent_index_cache.add_spend(input.outpoint().clone(), info)?;
+        }
+        Ok(())
+    }
+
+    fn disconnect_spent_index(&mut self, inputs: &[TxInput]) {
+        for input in inputs {
+            self.spent_index_cache.remove_spend(input.outpoint().clone());
+        }
+    }
+
     pub fn connect_transaction(
         &mut self,
         tx_source: &TransactionSourceForConnect,
@@ -641,11 +785,34 @@ where
         // check timelocks of the outputs and make sure there's no premature spending
         self.check_timelocks(tx_source, tx, median_time_past)?;
 
-        // verify input signatures
-        self.verify_signatures(tx)?;
+        // verify input signatures, unless the block is known to be valid
+        match tx_source {
+            TransactionSourceForConnect::Chain { new_block_index: _ } => {
+                if !self.skip_signature_verification {
+                    self.verify_or_defer_signatures(tx)?;
+                }
+            }
+            TransactionSourceForConnect::Mempool { current_best: _ } => {
+                self.verify_signatures(tx.transaction().get_id().into(), tx)?;
+            }
+        }
 
         self.connect_pos_accounting_outputs(tx_source.into(), tx.transaction())?;
 
+        // update address index only for txs from main chain
+        if let TransactionSourceForConnect::Chain { new_block_index } = tx_source {
+            if self.address_index_cache.enabled() {
+                let tx_id = tx.transaction().get_id();
+                self.connect_address_index(
+                    tx_id.into(),
+                    tx.inputs(),
+                    tx.outputs(),
+                    Spender::RegularInput(tx_id),
+                    new_block_index.block_height(),
+                )?;
+            }
+        }
+
         // spend utxos
         let tx_undo = self
             .utxo_cache
@@ -658,7 +825,7 @@ where
             .insert_tx_undo(tx.transaction().get_id(), tx_undo)?;
 
         match tx_source {
-            TransactionSourceForConnect::Chain { new_block_index: _ } => {
+            TransactionSourceForConnect::Chain { new_block_index } => {
                 // update tx index only for txs from main chain
                 if let Some(tx_index_cache) = self.tx_index_cache.as_mut() {
                     // pre-cache all inputs
@@ -670,6 +837,14 @@ where
                     tx_index_cache
                         .spend_tx_index_inputs(tx.inputs(), tx.transaction().get_id().into())?;
                 }
+
+                if self.spent_index_cache.enabled() {
+                    self.connect_spent_index(
+                        tx.inputs(),
+                        Spender::RegularInput(tx.transaction().get_id()),
+                        *new_block_index.block_id(),
+                    )?;
+                }
             }
             TransactionSourceForConnect::Mempool { current_best: _ } => { /* do nothing */ }
         };
@@ -691,12 +866,25 @@ where
                 })?;
             }
 
-            // verify input signatures
-            self.verify_signatures(&reward_transactable)?;
+            // verify input signatures, unless the block is known to be valid
+            if !self.skip_signature_verification {
+                let block_id: Id<GenBlock> = (*block_index.block_id()).into();
+                self.verify_signatures(block_id.into(), &reward_transactable)?;
+            }
         }
 
         let block_id = *block_index.block_id();
 
+        if self.address_index_cache.enabled() {
+            self.connect_address_index(
+                block_id.into(),
+                reward_transactable.inputs().unwrap_or_default(),
+                reward_transactable.outputs().unwrap_or_default(),
+                Spender::BlockInput(block_id.into()),
+                block_index.block_height(),
+            )?;
+        }
+
         // spend inputs of the block reward
         // if block reward has no inputs then only outputs will be added to the utxo set
         let reward_undo = self
@@ -722,6 +910,13 @@ where
             tx_index_cache.spend_tx_index_inputs(inputs, block_id.into())?;
         }
 
+        if let (Some(inputs), true) = (
+            reward_transactable.inputs(),
+            self.spent_index_cache.enabled(),
+        ) {
+            self.connect_spent_index(inputs, Spender::BlockInput(block_id.into()), block_id)?;
+        }
+
         Ok(())
     }
 
@@ -833,6 +1028,20 @@ where
 
         self.utxo_cache.disconnect_transaction(tx.transaction(), tx_undo)?;
 
+        // update address index only for txs from main chain
+        if let (TransactionSource::Chain(_), true) = (tx_source, self.address_index_cache.enabled())
+        {
+            self.disconnect_address_index(
+                tx.transaction().get_id().into(),
+                tx.inputs(),
+                tx.outputs(),
+            )?;
+        }
+
+        if let (TransactionSource::Chain(_), true) = (tx_source, self.spent_index_cache.enabled()) {
+            self.disconnect_spent_index(tx.inputs());
+        }
+
         // pre-cache token ids before removing them
         self.token_issuance_cache
             .precache_token_issuance(|id| self.storage.get_token_aux_data(id), tx.transaction())?;
@@ -874,6 +1083,21 @@ where
                     reward_undo,
                 )?;
 
+                if self.address_index_cache.enabled() {
+                    self.disconnect_address_index(
+                        block.get_id().into(),
+                        reward_transactable.inputs().unwrap_or_default(),
+                        reward_transactable.outputs().unwrap_or_default(),
+                    )?;
+                }
+
+                if let (Some(inputs), true) = (
+                    reward_transactable.inputs(),
+                    self.spent_index_cache.enabled(),
+                ) {
+                    self.disconnect_spent_index(inputs);
+                }
+
                 let tx_index_fetcher =
                     |tx_id: &OutPointSourceId| self.storage.get_mainchain_tx_index(tx_id);
 
@@ -899,6 +1123,8 @@ where
     pub fn consume(self) -> Result<TransactionVerifierDelta, ConnectTransactionError> {
         Ok(TransactionVerifierDelta {
             tx_index_cache: self.tx_index_cache.take_always().consume(),
+            address_index_cache: self.address_index_cache.consume(),
+            spent_index_cache: self.spent_index_cache.consume(),
             utxo_cache: self.utxo_cache.consume(),
             utxo_block_undo: self.utxo_block_undo.consume(),
             token_issuance_cache: self.token_issuance_cache.consume(),
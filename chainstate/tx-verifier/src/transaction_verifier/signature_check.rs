@@ -0,0 +1,74 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use common::{
+    chain::{
+        signature::verify_signature, signed_transaction::SignedTransaction, Block, Destination,
+        Transaction,
+    },
+    primitives::{Id, Idable},
+};
+use rayon::prelude::*;
+
+use super::error::ConnectTransactionError;
+
+/// A signature check of a transaction input, postponed until the whole block is connected
+pub struct DeferredSignatureCheck {
+    tx_id: Id<Transaction>,
+    input_num: usize,
+    destination: Destination,
+}
+
+impl DeferredSignatureCheck {
+    pub fn new(tx_id: Id<Transaction>, input_num: usize, destination: Destination) -> Self {
+        Self {
+            tx_id,
+            input_num,
+            destination,
+        }
+    }
+}
+
+/// Verify the signatures of the block transactions on the worker pool.
+///
+/// The checks are independent, because the spent outputs are already known. If several checks
+/// fail, the error of the first one in the block order is returned.
+pub fn verify_signatures_in_parallel(
+    block: &Block,
+    checks: &[DeferredSignatureCheck],
+) -> Result<(), ConnectTransactionError> {
+    let txs: BTreeMap<Id<Transaction>, &SignedTransaction> =
+        block.transactions().iter().map(|tx| (tx.transaction().get_id(), tx)).collect();
+
+    let failure = checks.par_iter().find_map_first(|check| {
+        let tx = txs
+            .get(&check.tx_id)
+            .expect("Deferred signature checks must come from the same block");
+        verify_signature(&check.destination, *tx, check.input_num).err().map(|err| {
+            ConnectTransactionError::SignatureVerificationFailed(
+                check.tx_id.into(),
+                check.input_num,
+                err,
+            )
+        })
+    });
+
+    match failure {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
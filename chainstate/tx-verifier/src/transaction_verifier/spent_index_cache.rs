@@ -0,0 +1,90 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spent-output index cache with enabled/disabled flag.
+
+use std::collections::{btree_map::Entry, BTreeMap};
+
+use chainstate_types::OutputSpendInfo;
+use common::chain::OutPoint;
+
+use super::{config::TransactionVerifierConfig, error::SpentIndexError, CachedOperation};
+
+pub type CachedSpentIndexOp = CachedOperation<OutputSpendInfo>;
+
+pub type SpentIndexMap = BTreeMap<OutPoint, CachedSpentIndexOp>;
+
+/// Changes to the inputs recorded as spending each output, if spent-output indexing is enabled
+pub struct SpentIndexCache {
+    enabled: bool,
+    data: SpentIndexMap,
+}
+
+impl SpentIndexCache {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            data: SpentIndexMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &TransactionVerifierConfig) -> Self {
+        Self::new(config.spent_index_enabled)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn consume(self) -> SpentIndexMap {
+        self.data
+    }
+
+    pub fn get_from_cached(&self, outpoint: &OutPoint) -> Option<&CachedSpentIndexOp> {
+        self.data.get(outpoint)
+    }
+
+    pub fn add_spend(
+        &mut self,
+        outpoint: OutPoint,
+        info: OutputSpendInfo,
+    ) -> Result<(), SpentIndexError> {
+        match self.data.entry(outpoint) {
+            Entry::Occupied(mut entry) => match entry.get() {
+                CachedOperation::Write(_) | CachedOperation::Read(_) => {
+                    return Err(SpentIndexError::AlreadySpent(entry.key().clone()));
+                }
+                // the spending was disconnected and is now connected again
+                CachedOperation::Erase => {
+                    entry.insert(CachedOperation::Write(info));
+                }
+            },
+            Entry::Vacant(entry) => {
+                entry.insert(CachedOperation::Write(info));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_spend(&mut self, outpoint: OutPoint, info: OutputSpendInfo) {
+        // possible overwrite is ok
+        self.data.insert(outpoint, CachedOperation::Write(info));
+    }
+
+    pub fn remove_spend(&mut self, outpoint: OutPoint) {
+        // possible overwrite is ok
+        self.data.insert(outpoint, CachedOperation::Erase);
+    }
+}
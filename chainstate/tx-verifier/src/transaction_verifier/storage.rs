@@ -15,11 +15,13 @@
 
 use std::ops::Deref;
 
-use chainstate_types::{storage_result, GenBlockIndex};
+use chainstate_types::{
+    storage_result, AddressIndexKey, AddressOutputInfo, GenBlockIndex, OutputSpendInfo,
+};
 use common::{
     chain::{
         tokens::{TokenAuxiliaryData, TokenId},
-        Block, GenBlock, OutPointSourceId, Transaction, TxMainChainIndex,
+        Block, GenBlock, OutPoint, OutPointSourceId, Transaction, TxMainChainIndex,
     },
     primitives::Id,
 };
@@ -52,6 +54,10 @@ pub enum TransactionVerifierStorageError {
     UtxoBlockUndoError(#[from] utxo::UtxosBlockUndoError),
     #[error("Transaction index has been disabled")]
     TransactionIndexDisabled,
+    #[error("Address index has been disabled")]
+    AddressIndexDisabled,
+    #[error("Spent-output index has been disabled")]
+    SpentIndexDisabled,
     #[error("PoS accounting error: {0}")]
     PoSAccountingError(#[from] pos_accounting::Error),
     #[error("Accounting BlockUndo error: {0}")]
@@ -90,6 +96,16 @@ pub trait TransactionVerifierStorageRef: UtxosStorageRead + PoSAccountingView {
         &self,
         id: Id<Block>,
     ) -> Result<Option<AccountingBlockUndo>, TransactionVerifierStorageError>;
+
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError>;
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError>;
 }
 
 pub trait TransactionVerifierStorageMut:
@@ -149,6 +165,28 @@ pub trait TransactionVerifierStorageMut:
         &mut self,
         tx_source: TransactionSource,
     ) -> Result<(), TransactionVerifierStorageError>;
+
+    fn set_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+        info: &AddressOutputInfo,
+    ) -> Result<(), TransactionVerifierStorageError>;
+
+    fn del_address_index_entry(
+        &mut self,
+        key: &AddressIndexKey,
+    ) -> Result<(), TransactionVerifierStorageError>;
+
+    fn set_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+        info: &OutputSpendInfo,
+    ) -> Result<(), TransactionVerifierStorageError>;
+
+    fn del_output_spend_info(
+        &mut self,
+        outpoint: &OutPoint,
+    ) -> Result<(), TransactionVerifierStorageError>;
 }
 
 impl<T: Deref> TransactionVerifierStorageRef for T
@@ -189,4 +227,18 @@ where
     ) -> Result<Option<AccountingBlockUndo>, TransactionVerifierStorageError> {
         self.deref().get_accounting_undo(id)
     }
+
+    fn get_address_index_entry(
+        &self,
+        key: &AddressIndexKey,
+    ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError> {
+        self.deref().get_address_index_entry(key)
+    }
+
+    fn get_output_spend_info(
+        &self,
+        outpoint: &OutPoint,
+    ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError> {
+        self.deref().get_output_spend_info(outpoint)
+    }
 }
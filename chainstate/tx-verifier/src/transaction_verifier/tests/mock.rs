@@ -20,7 +20,9 @@ use crate::transaction_verifier::TransactionSource;
 use super::storage::{
     TransactionVerifierStorageError, TransactionVerifierStorageMut, TransactionVerifierStorageRef,
 };
-use chainstate_types::{storage_result, GenBlockIndex};
+use chainstate_types::{
+    storage_result, AddressIndexKey, AddressOutputInfo, GenBlockIndex, OutputSpendInfo,
+};
 use common::{
     chain::{
         tokens::{TokenAuxiliaryData, TokenId},
@@ -62,6 +64,16 @@ mockall::mock! {
             &self,
             id: Id<Block>,
         ) -> Result<Option<pos_accounting::AccountingBlockUndo>, TransactionVerifierStorageError>;
+
+        fn get_address_index_entry(
+            &self,
+            key: &AddressIndexKey,
+        ) -> Result<Option<AddressOutputInfo>, TransactionVerifierStorageError>;
+
+        fn get_output_spend_info(
+            &self,
+            outpoint: &OutPoint,
+        ) -> Result<Option<OutputSpendInfo>, TransactionVerifierStorageError>;
     }
 
     impl TransactionVerifierStorageMut for Store {
@@ -111,12 +123,35 @@ mockall::mock! {
             &mut self,
             tx_source: TransactionSource,
         ) -> Result<(), TransactionVerifierStorageError>;
+
+        fn set_address_index_entry(
+            &mut self,
+            key: &AddressIndexKey,
+            info: &AddressOutputInfo,
+        ) -> Result<(), TransactionVerifierStorageError>;
+
+        fn del_address_index_entry(
+            &mut self,
+            key: &AddressIndexKey,
+        ) -> Result<(), TransactionVerifierStorageError>;
+
+        fn set_output_spend_info(
+            &mut self,
+            outpoint: &OutPoint,
+            info: &OutputSpendInfo,
+        ) -> Result<(), TransactionVerifierStorageError>;
+
+        fn del_output_spend_info(
+            &mut self,
+            outpoint: &OutPoint,
+        ) -> Result<(), TransactionVerifierStorageError>;
     }
 
     impl UtxosStorageRead for Store {
         fn get_utxo(&self, outpoint: &OutPoint) -> Result<Option<Utxo>, storage_result::Error>;
         fn get_best_block_for_utxos(&self) -> Result<Option<Id<GenBlock>>,storage_result::Error>;
         fn get_undo_data(&self, id: Id<Block>) -> Result<Option<utxo::UtxosBlockUndo>, storage_result::Error>;
+        fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> Result<Option<utxo::UtxoSetHash>, storage_result::Error>;
     }
 
     impl FlushableUtxoView for Store {
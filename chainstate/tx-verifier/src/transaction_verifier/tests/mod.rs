@@ -26,6 +26,7 @@ use crypto::{
     key::{KeyKind, PrivateKey},
     random::{CryptoRng, Rng},
 };
+use utxo::Utxo;
 
 fn create_utxo(rng: &mut (impl Rng + CryptoRng), value: UnsignedIntType) -> (OutPoint, Utxo) {
     let outpoint = OutPoint::new(
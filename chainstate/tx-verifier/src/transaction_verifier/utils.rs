@@ -17,7 +17,7 @@ use std::collections::BTreeMap;
 
 use common::{
     chain::{
-        tokens::{token_id, OutputValue, TokenData, TokenId},
+        tokens::{token_id, CoinOrTokenId, OutputValue, TokenData, TokenId},
         Transaction, TxOutput,
     },
     primitives::Amount,
@@ -27,7 +27,6 @@ use fallible_iterator::FallibleIterator;
 use super::{
     amounts_map::AmountsMap,
     error::{ConnectTransactionError, TokensError},
-    token_issuance_cache::CoinOrTokenId,
     Fee,
 };
 
@@ -0,0 +1,96 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common::{
+    chain::{tokens::CoinOrTokenId, Destination, OutPoint, Spender},
+    primitives::{Amount, BlockHeight},
+};
+use serialization::{Decode, Encode};
+
+/// Key of the address index: the destination an output is sent to, the coin or token it holds
+/// and the output itself, so that all the entries of a destination (or of a destination and a
+/// token) are stored next to each other
+pub type AddressIndexKey = (Destination, CoinOrTokenId, OutPoint);
+
+/// The spending of an output recorded in the address index
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AddressOutputSpend {
+    spender: Spender,
+    block_height: BlockHeight,
+}
+
+impl AddressOutputSpend {
+    pub fn new(spender: Spender, block_height: BlockHeight) -> Self {
+        Self {
+            spender,
+            block_height,
+        }
+    }
+
+    pub fn spender(&self) -> &Spender {
+        &self.spender
+    }
+
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+}
+
+/// An output recorded in the address index, along with the input that spent it, if any
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct AddressOutputInfo {
+    amount: Amount,
+    block_height: BlockHeight,
+    spend: Option<AddressOutputSpend>,
+}
+
+impl AddressOutputInfo {
+    pub fn new(amount: Amount, block_height: BlockHeight) -> Self {
+        Self {
+            amount,
+            block_height,
+            spend: None,
+        }
+    }
+
+    pub fn amount(&self) -> Amount {
+        self.amount
+    }
+
+    /// Height of the block that created the output
+    pub fn block_height(&self) -> BlockHeight {
+        self.block_height
+    }
+
+    pub fn spend(&self) -> Option<&AddressOutputSpend> {
+        self.spend.as_ref()
+    }
+
+    pub fn is_spent(&self) -> bool {
+        self.spend.is_some()
+    }
+
+    pub fn set_spend(&mut self, spend: Option<AddressOutputSpend>) {
+        self.spend = spend;
+    }
+}
+
+/// An output of a destination along with its spending, as returned by the address history query
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddressHistoryEntry {
+    pub coin_or_token_id: CoinOrTokenId,
+    pub outpoint: OutPoint,
+    pub info: AddressOutputInfo,
+}
@@ -20,7 +20,7 @@ use common::primitives::{BlockHeight, Id, Idable};
 use common::Uint256;
 use serialization::{Decode, Encode};
 
-use crate::GenBlockIndex;
+use crate::{BlockStatus, GenBlockIndex};
 
 #[derive(Debug, Clone, Encode, Decode)]
 pub struct BlockIndex {
@@ -30,24 +30,25 @@ pub struct BlockIndex {
     chain_trust: Uint256,
     height: BlockHeight,
     time_max: BlockTimestamp,
+    status: BlockStatus,
 }
 
 impl BlockIndex {
     pub fn new(
-        block: &Block,
+        block_header: &BlockHeader,
         chain_trust: Uint256,
         some_ancestor: Id<GenBlock>,
         height: BlockHeight,
         time_max: BlockTimestamp,
     ) -> Self {
-        // We have to use the whole block because we are not able to take block_hash from the header
         Self {
-            block_header: block.header().clone(),
-            block_id: block.get_id(),
+            block_header: block_header.clone(),
+            block_id: block_header.get_id(),
             some_ancestor,
             chain_trust,
             height,
             time_max,
+            status: BlockStatus::new(),
         }
     }
 
@@ -79,6 +80,14 @@ impl BlockIndex {
         &self.block_header
     }
 
+    pub fn status(&self) -> BlockStatus {
+        self.status
+    }
+
+    pub fn set_status(&mut self, status: BlockStatus) {
+        self.status = status
+    }
+
     pub fn some_ancestor(&self) -> &Id<GenBlock> {
         &self.some_ancestor
     }
@@ -0,0 +1,125 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use serialization::{Decode, Encode};
+
+/// Validation status of a block, stored as a set of flags in the block index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct BlockStatus(u32);
+
+impl BlockStatus {
+    /// The header (and the context-free checks of the block body) passed validation
+    const VALID_HEADER: u32 = 1 << 0;
+    /// The transactions were successfully connected at least once
+    const VALID_TRANSACTIONS: u32 = 1 << 1;
+    /// The block itself failed validation or was invalidated manually
+    const FAILED: u32 = 1 << 2;
+    /// One of the ancestors of the block is invalid
+    const FAILED_CHILD: u32 = 1 << 3;
+
+    const FAILED_MASK: u32 = Self::FAILED | Self::FAILED_CHILD;
+
+    /// Status of a block whose header has just been checked and that was never connected
+    pub fn new() -> Self {
+        Self(Self::VALID_HEADER)
+    }
+
+    pub fn has_valid_header(&self) -> bool {
+        self.0 & Self::VALID_HEADER != 0
+    }
+
+    pub fn has_valid_transactions(&self) -> bool {
+        self.0 & Self::VALID_TRANSACTIONS != 0
+    }
+
+    /// Returns true if either the block or one of its ancestors is invalid
+    pub fn is_failed(&self) -> bool {
+        self.0 & Self::FAILED_MASK != 0
+    }
+
+    /// Returns true if the block itself is invalid
+    pub fn is_failed_itself(&self) -> bool {
+        self.0 & Self::FAILED != 0
+    }
+
+    /// Returns true if the block is valid by itself, but one of its ancestors is invalid
+    pub fn is_failed_child(&self) -> bool {
+        self.0 & Self::FAILED_CHILD != 0
+    }
+
+    pub fn set_valid_transactions(&mut self) {
+        self.0 |= Self::VALID_TRANSACTIONS
+    }
+
+    pub fn set_failed(&mut self) {
+        self.0 |= Self::FAILED
+    }
+
+    pub fn set_failed_child(&mut self) {
+        self.0 |= Self::FAILED_CHILD
+    }
+
+    /// Clear the failure flags, keeping the validation progress as is
+    pub fn reset_failure_flags(&mut self) {
+        self.0 &= !Self::FAILED_MASK
+    }
+}
+
+impl Default for BlockStatus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags() {
+        let mut status = BlockStatus::new();
+        assert!(status.has_valid_header());
+        assert!(!status.has_valid_transactions());
+        assert!(!status.is_failed());
+
+        status.set_valid_transactions();
+        assert!(status.has_valid_transactions());
+        assert!(!status.is_failed());
+
+        status.set_failed_child();
+        assert!(status.is_failed());
+        assert!(status.is_failed_child());
+        assert!(!status.is_failed_itself());
+
+        status.set_failed();
+        assert!(status.is_failed_itself());
+
+        status.reset_failure_flags();
+        assert!(!status.is_failed());
+        assert!(status.has_valid_header());
+        assert!(status.has_valid_transactions());
+    }
+
+    #[test]
+    fn encoding_roundtrip() {
+        let mut status = BlockStatus::new();
+        status.set_failed();
+        let encoded = status.encode();
+        assert_eq!(
+            BlockStatus::decode(&mut encoded.as_slice()).unwrap(),
+            status
+        );
+    }
+}
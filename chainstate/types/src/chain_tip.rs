@@ -0,0 +1,56 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common::{
+    chain::GenBlock,
+    primitives::{BlockDistance, BlockHeight, Id},
+    Uint256,
+};
+
+/// Status of the branch that ends at a chain tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainTipStatus {
+    /// The tip of the main chain
+    Active,
+    /// A fork whose blocks were all connected successfully at some point
+    ValidFork,
+    /// A fork whose blocks were checked, but never connected
+    HeadersOnly,
+    /// A fork that contains an invalid block
+    Invalid,
+}
+
+impl std::fmt::Display for ChainTipStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            ChainTipStatus::Active => "active",
+            ChainTipStatus::ValidFork => "valid-fork",
+            ChainTipStatus::HeadersOnly => "headers-only",
+            ChainTipStatus::Invalid => "invalid",
+        };
+        s.fmt(f)
+    }
+}
+
+/// A leaf of the block tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainTip {
+    pub block_id: Id<GenBlock>,
+    pub height: BlockHeight,
+    /// Number of blocks between the tip and the point where its branch forks off the main chain
+    pub branch_length: BlockDistance,
+    pub chain_trust: Uint256,
+    pub status: ChainTipStatus,
+}
@@ -50,6 +50,18 @@ pub enum PropertyQueryError {
     GenesisHeaderRequested,
     #[error("Tried getting value of a token outpoint")]
     ExpectedCoinOutpointAndFoundToken,
+    #[error("Utxo set hash not found for block {0}")]
+    UtxoSetHashNotFound(Id<GenBlock>),
+    #[error("Total amount of the utxo set overflowed")]
+    UtxoSetAmountOverflow,
+    #[error("Address index is disabled")]
+    AddressIndexDisabled,
+    #[error("Balance of the address overflowed")]
+    AddressBalanceOverflow,
+    #[error("Spent-output index is disabled")]
+    SpentIndexDisabled,
+    #[error("Block filters are disabled")]
+    BlockFiltersDisabled,
 }
 
 #[derive(Error, Debug, PartialEq, Eq, Clone)]
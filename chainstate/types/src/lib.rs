@@ -16,16 +16,32 @@
 pub mod storage_result;
 
 pub use crate::{
-    ancestor::block_index_ancestor_getter, ancestor::gen_block_index_getter,
-    block_index::BlockIndex, block_index_handle::BlockIndexHandle, error::GetAncestorError,
-    error::PropertyQueryError, gen_block_index::GenBlockIndex, height_skip::get_skip_height,
+    address_index::{AddressHistoryEntry, AddressIndexKey, AddressOutputInfo, AddressOutputSpend},
+    ancestor::block_index_ancestor_getter,
+    ancestor::gen_block_index_getter,
+    block_index::BlockIndex,
+    block_index_handle::BlockIndexHandle,
+    block_status::BlockStatus,
+    chain_tip::ChainTip,
+    chain_tip::ChainTipStatus,
+    error::GetAncestorError,
+    error::PropertyQueryError,
+    gen_block_index::GenBlockIndex,
+    height_skip::get_skip_height,
     locator::Locator,
+    spent_index::OutputSpendInfo,
+    utxo_set_info::UtxoSetInfo,
 };
 
+mod address_index;
 mod ancestor;
 mod block_index;
 mod block_index_handle;
+mod block_status;
+mod chain_tip;
 mod error;
 mod gen_block_index;
 mod height_skip;
 mod locator;
+mod spent_index;
+mod utxo_set_info;
@@ -0,0 +1,52 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common::{
+    chain::{Block, Spender},
+    primitives::Id,
+};
+use serialization::{Decode, Encode};
+
+/// The input that spent an output, recorded in the spent-output index
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct OutputSpendInfo {
+    spender: Spender,
+    input_index: u32,
+    block_id: Id<Block>,
+}
+
+impl OutputSpendInfo {
+    pub fn new(spender: Spender, input_index: u32, block_id: Id<Block>) -> Self {
+        Self {
+            spender,
+            input_index,
+            block_id,
+        }
+    }
+
+    /// The transaction, or the block for block reward inputs, that spent the output
+    pub fn spender(&self) -> &Spender {
+        &self.spender
+    }
+
+    pub fn input_index(&self) -> u32 {
+        self.input_index
+    }
+
+    /// The block that contains the spending input
+    pub fn block_id(&self) -> Id<Block> {
+        self.block_id
+    }
+}
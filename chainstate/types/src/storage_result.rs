@@ -18,6 +18,8 @@
 pub enum Error {
     #[error("Storage error: {0}")]
     Storage(storage::error::Recoverable),
+    #[error("The database has storage version {0}, but only versions up to {1} are supported")]
+    UnsupportedStorageVersion(u32, u32),
 }
 
 impl From<storage::Error> for Error {
@@ -0,0 +1,33 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+
+use common::{
+    chain::{tokens::TokenId, GenBlock},
+    primitives::{Amount, BlockHeight, Id, H256},
+};
+
+/// Summary of the utxo set at the tip of the main chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UtxoSetInfo {
+    pub best_block_id: Id<GenBlock>,
+    pub best_block_height: BlockHeight,
+    pub utxo_count: u64,
+    pub coin_amount: Amount,
+    pub token_amounts: BTreeMap<TokenId, Amount>,
+    /// Rolling hash of the utxo set, equal on nodes that have the same utxos
+    pub set_hash: H256,
+}
@@ -0,0 +1,348 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compact block filters.
+//!
+//! A block filter is a Golomb-coded set (GCS) built over the output destinations and the spent
+//! outpoints of a block. Light clients can test whether a block may be relevant to them without
+//! downloading it. False positives are possible, false negatives are not.
+//!
+//! Filters are chained through filter headers, where each header commits to the filter of its
+//! block and to the header of the previous block, so that a client can check the filters served
+//! by a peer against the filter headers obtained from others.
+
+use std::collections::BTreeSet;
+
+use crypto::hash::StreamHasher;
+use serialization::{Decode, Encode};
+
+use crate::{
+    chain::{signature::Signable, Block},
+    primitives::{
+        id::{hash_encoded_to, DefaultHashAlgoStream},
+        Id, Idable, H256,
+    },
+};
+
+/// Number of bits of the remainder in the Golomb-Rice coding
+const FILTER_P: u8 = 19;
+
+/// Inverse of the false positive rate
+const FILTER_M: u64 = 784931;
+
+/// A Golomb-coded set of the items relevant to a block
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct BlockFilter {
+    element_count: u32,
+    data: Vec<u8>,
+}
+
+impl BlockFilter {
+    /// Builds a filter over the given items; the items are keyed by the block id
+    pub fn new<'a>(block_id: &Id<Block>, items: impl IntoIterator<Item = &'a [u8]>) -> Self {
+        let items: BTreeSet<&[u8]> = items.into_iter().collect();
+        let element_count = items.len() as u64;
+        let range = element_count * FILTER_M;
+
+        let mut values: Vec<u64> =
+            items.into_iter().map(|item| hash_to_range(block_id, item, range)).collect();
+        values.sort_unstable();
+
+        let mut writer = BitWriter::default();
+        let mut last = 0;
+        for value in values {
+            writer.write_golomb_rice(value - last);
+            last = value;
+        }
+
+        Self {
+            element_count: element_count as u32,
+            data: writer.finish(),
+        }
+    }
+
+    /// Builds the filter of a block over its output destinations and spent outpoints
+    pub fn from_block(block: &Block) -> Self {
+        let reward = block.block_reward_transactable();
+        let outputs = block
+            .transactions()
+            .iter()
+            .flat_map(|tx| tx.outputs().iter())
+            .chain(reward.outputs().unwrap_or_default().iter());
+        let inputs = block
+            .transactions()
+            .iter()
+            .flat_map(|tx| tx.inputs().iter())
+            .chain(reward.inputs().unwrap_or_default().iter());
+
+        let items: Vec<Vec<u8>> = outputs
+            .filter_map(|output| output.purpose().destination())
+            .map(Encode::encode)
+            .chain(inputs.map(|input| input.outpoint().encode()))
+            .collect();
+
+        Self::new(&block.get_id(), items.iter().map(Vec::as_slice))
+    }
+
+    /// Number of items in the filter
+    pub fn element_count(&self) -> u32 {
+        self.element_count
+    }
+
+    /// Golomb-Rice coded deltas of the hashed items
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns true if any of the given items may be in the filter
+    pub fn matches_any<'a>(
+        &self,
+        block_id: &Id<Block>,
+        items: impl IntoIterator<Item = &'a [u8]>,
+    ) -> bool {
+        let range = self.element_count as u64 * FILTER_M;
+        let mut queries: Vec<u64> =
+            items.into_iter().map(|item| hash_to_range(block_id, item, range)).collect();
+        if queries.is_empty() || self.element_count == 0 {
+            return false;
+        }
+        queries.sort_unstable();
+
+        let mut reader = BitReader::new(&self.data);
+        let mut queries = queries.into_iter().peekable();
+        let mut value = 0;
+        for _ in 0..self.element_count {
+            let delta = match reader.read_golomb_rice() {
+                Some(delta) => delta,
+                None => return false,
+            };
+            value += delta;
+            while queries.next_if(|query| *query < value).is_some() {}
+            match queries.peek() {
+                Some(query) if *query == value => return true,
+                Some(_) => {}
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Hash of the filter, committed to by the filter header
+    pub fn hash(&self) -> H256 {
+        let mut hasher = DefaultHashAlgoStream::new();
+        hash_encoded_to(self, &mut hasher);
+        hasher.finalize().into()
+    }
+}
+
+/// Commitment to the filter of a block and to all the filters before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub struct BlockFilterHeader(H256);
+
+impl BlockFilterHeader {
+    /// The header preceding the filter of the first block after genesis
+    pub fn zero() -> Self {
+        Self(H256::zero())
+    }
+
+    /// Computes the header of a filter given the header of the previous block
+    pub fn new(filter: &BlockFilter, prev_header: &BlockFilterHeader) -> Self {
+        let mut hasher = DefaultHashAlgoStream::new();
+        hasher.write(filter.hash().as_bytes());
+        hasher.write(prev_header.0.as_bytes());
+        Self(hasher.finalize().into())
+    }
+
+    pub fn hash(&self) -> &H256 {
+        &self.0
+    }
+}
+
+/// Maps an item uniformly to `[0, range)`, keyed by the block id
+fn hash_to_range(block_id: &Id<Block>, item: &[u8], range: u64) -> u64 {
+    let mut hasher = DefaultHashAlgoStream::new();
+    hasher.write(block_id.get().as_bytes());
+    hasher.write(item);
+    let hash: H256 = hasher.finalize().into();
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&hash.as_bytes()[..8]);
+    ((u64::from_le_bytes(bytes) as u128 * range as u128) >> 64) as u64
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_count: usize,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.bit_count == self.bytes.len() * 8 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let last = self.bytes.last_mut().expect("pushed above");
+            *last |= 0x80 >> (self.bit_count % 8);
+        }
+        self.bit_count += 1;
+    }
+
+    fn write_golomb_rice(&mut self, value: u64) {
+        for _ in 0..(value >> FILTER_P) {
+            self.write_bit(true);
+        }
+        self.write_bit(false);
+        for i in (0..FILTER_P).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn finish(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.position / 8)?;
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    fn read_golomb_rice(&mut self) -> Option<u64> {
+        let mut quotient = 0u64;
+        while self.read_bit()? {
+            quotient += 1;
+        }
+        let mut remainder = 0u64;
+        for _ in 0..FILTER_P {
+            remainder = (remainder << 1) | self.read_bit()? as u64;
+        }
+        Some((quotient << FILTER_P) | remainder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::random::{Rng, SliceRandom};
+    use rstest::rstest;
+    use test_utils::random::{make_seedable_rng, Seed};
+
+    fn random_items(rng: &mut impl Rng, count: usize) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|_| {
+                let len = rng.gen_range(1..64);
+                (0..len).map(|_| rng.gen::<u8>()).collect()
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn members_always_match(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let block_id = Id::new(H256::random_using(&mut rng));
+        let count = rng.gen_range(1..500);
+        let items = random_items(&mut rng, count);
+        let filter = BlockFilter::new(&block_id, items.iter().map(Vec::as_slice));
+
+        let decoded = BlockFilter::decode(&mut filter.encode().as_slice()).unwrap();
+        assert_eq!(decoded, filter);
+
+        for item in &items {
+            assert!(filter.matches_any(&block_id, [item.as_slice()]));
+        }
+        let item = items.choose(&mut rng).unwrap();
+        let others = random_items(&mut rng, 10);
+        assert!(filter.matches_any(
+            &block_id,
+            others.iter().map(Vec::as_slice).chain([item.as_slice()])
+        ));
+    }
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn non_members_rarely_match(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let block_id = Id::new(H256::random_using(&mut rng));
+        let items = random_items(&mut rng, 100);
+        let filter = BlockFilter::new(&block_id, items.iter().map(Vec::as_slice));
+
+        // Random items are 65 bytes long so they can't collide with the members
+        let others: Vec<Vec<u8>> =
+            (0..1000).map(|_| (0..65).map(|_| rng.gen::<u8>()).collect()).collect();
+        let false_positives = others
+            .iter()
+            .filter(|item| filter.matches_any(&block_id, [item.as_slice()]))
+            .count();
+        assert!(false_positives <= 1);
+
+        // The same items keyed by another block id hash differently
+        let other_block_id = Id::new(H256::random_using(&mut rng));
+        assert_ne!(
+            BlockFilter::new(&other_block_id, items.iter().map(Vec::as_slice)),
+            filter
+        );
+    }
+
+    #[test]
+    fn empty_filter() {
+        let block_id = Id::new(H256::zero());
+        let filter = BlockFilter::new(&block_id, []);
+        assert_eq!(filter.element_count(), 0);
+        assert!(filter.data().is_empty());
+        assert!(!filter.matches_any(&block_id, [[1u8, 2, 3].as_slice()]));
+    }
+
+    #[test]
+    fn duplicates_are_counted_once() {
+        let block_id = Id::new(H256::zero());
+        let filter = BlockFilter::new(&block_id, [[1u8].as_slice(), &[1], &[2]]);
+        assert_eq!(filter.element_count(), 2);
+    }
+
+    #[test]
+    fn header_chain() {
+        let block_id = Id::new(H256::zero());
+        let filter1 = BlockFilter::new(&block_id, [[1u8].as_slice()]);
+        let filter2 = BlockFilter::new(&block_id, [[2u8].as_slice()]);
+
+        let header1 = BlockFilterHeader::new(&filter1, &BlockFilterHeader::zero());
+        let header2 = BlockFilterHeader::new(&filter2, &header1);
+        assert_ne!(header1, BlockFilterHeader::zero());
+        assert_ne!(header2, header1);
+        assert_ne!(
+            BlockFilterHeader::new(&filter2, &BlockFilterHeader::zero()),
+            header2
+        );
+        assert_eq!(
+            BlockFilterHeader::new(&filter1, &BlockFilterHeader::zero()),
+            header1
+        );
+    }
+}
@@ -47,6 +47,10 @@ impl BlockHeader {
         self.timestamp
     }
 
+    pub fn tx_merkle_root(&self) -> H256 {
+        self.tx_merkle_root
+    }
+
     pub fn header_size(&self) -> usize {
         self.encoded_size()
     }
@@ -22,10 +22,12 @@ pub use crate::chain::{
     GenBlock,
 };
 
+pub mod block_filter;
 pub mod block_header;
 pub mod block_size;
 pub mod consensus_data;
 pub mod timestamp;
+pub mod tx_inclusion_proof;
 
 mod block_reward;
 mod block_v1;
@@ -50,10 +52,15 @@ use crate::{
 
 use super::signed_transaction::SignedTransaction;
 
+const TX_ID_HASHER: fn(&SignedTransaction) -> H256 =
+    |tx: &SignedTransaction| tx.transaction().get_id().get();
+
 pub fn calculate_tx_merkle_root(body: &BlockBody) -> Result<H256, merkle::MerkleTreeFormError> {
-    const TX_HASHER: fn(&SignedTransaction) -> H256 =
-        |tx: &SignedTransaction| tx.transaction().get_id().get();
-    calculate_generic_merkle_root(&TX_HASHER, body)
+    calculate_generic_merkle_root(&TX_ID_HASHER, body)
+}
+
+fn tx_merkle_leaves(body: &BlockBody) -> Vec<H256> {
+    generic_merkle_leaves(&TX_ID_HASHER, body)
 }
 
 pub fn calculate_witness_merkle_root(
@@ -68,21 +75,28 @@ fn calculate_generic_merkle_root(
     tx_hasher: &fn(&SignedTransaction) -> H256,
     body: &BlockBody,
 ) -> Result<H256, merkle::MerkleTreeFormError> {
-    let rewards_hash = id::hash_encoded(&body.reward);
-
     if body.transactions.is_empty() {
         // using bitcoin's way, blocks that only have the coinbase (or a single tx in general)
         // use their coinbase as the merkleroot
-        return Ok(rewards_hash);
+        return Ok(id::hash_encoded(&body.reward));
     }
 
-    let hashes: Vec<H256> = iter::once(rewards_hash)
-        .chain(body.transactions.iter().map(tx_hasher))
-        .collect();
+    let hashes = generic_merkle_leaves(tx_hasher, body);
     let t = merkle::merkletree_from_vec(&hashes)?;
     Ok(t.root())
 }
 
+/// The leaves of the merkle tree of a block: the hash of the reward followed by the hashes of
+/// the transactions
+fn generic_merkle_leaves(
+    tx_hasher: &fn(&SignedTransaction) -> H256,
+    body: &BlockBody,
+) -> Vec<H256> {
+    iter::once(id::hash_encoded(&body.reward))
+        .chain(body.transactions.iter().map(tx_hasher))
+        .collect()
+}
+
 #[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
 pub enum BlockCreationError {
     #[error("Merkle tree calculation error: {0}")]
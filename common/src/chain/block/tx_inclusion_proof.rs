@@ -0,0 +1,231 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proofs that a transaction is included in a block.
+//!
+//! A proof carries the merkle path from the transaction id to the transaction merkle root of the
+//! block header, so it can be checked by anyone who trusts the header, without the block itself.
+//! The number of transactions in the block is part of the proof; it fixes the depth of the tree
+//! and the positions of the transactions in it, so the path can't be made to prove the block
+//! reward, a padding leaf or an internal node of the tree.
+
+use serialization::{Decode, Encode};
+
+use crate::{
+    chain::{block::BlockHeader, Block, SignedTransaction, Transaction},
+    primitives::{
+        merkle::{self, MerkleProof},
+        Id, Idable, H256,
+    },
+};
+
+use super::tx_merkle_leaves;
+
+/// Proof that a transaction is in a block
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct TxInclusionProof {
+    block_id: Id<Block>,
+    tx_count: u32,
+    merkle_proof: MerkleProof,
+}
+
+impl TxInclusionProof {
+    /// Makes the proof for a transaction of the block, returns None if the transaction is not in it
+    pub fn from_block(block: &Block, tx_id: &Id<Transaction>) -> Option<Self> {
+        let tx_index =
+            block.transactions().iter().position(|tx| tx.transaction().get_id() == *tx_id)?;
+
+        // The block reward comes first in the tree, so there are at least two leaves
+        let leaves = tx_merkle_leaves(block.body());
+        let merkle_proof = merkle::merkle_proof_from_vec(&leaves, tx_index + 1)
+            .expect("The transaction index is in range");
+
+        Some(Self {
+            block_id: block.get_id(),
+            tx_count: u32::try_from(block.transactions().len())
+                .expect("The transaction count of a block fits in u32"),
+            merkle_proof,
+        })
+    }
+
+    pub fn block_id(&self) -> &Id<Block> {
+        &self.block_id
+    }
+
+    /// The number of transactions in the block, not counting the block reward
+    pub fn tx_count(&self) -> u32 {
+        self.tx_count
+    }
+
+    pub fn merkle_proof(&self) -> &MerkleProof {
+        &self.merkle_proof
+    }
+
+    /// Checks that the transaction is in the tree with the given transaction merkle root
+    pub fn verify(&self, tx: &SignedTransaction, tx_merkle_root: &H256) -> bool {
+        // The block reward is the first leaf and the transactions follow it; the tree is padded
+        // up to the next power of two, which gives its depth
+        let leaf_count = u64::from(self.tx_count) + 1;
+        let depth = leaf_count.next_power_of_two().trailing_zeros() as usize;
+        let leaf_index = self.merkle_proof.leaf_index();
+
+        (1..=self.tx_count).contains(&leaf_index)
+            && self.merkle_proof.siblings().len() == depth
+            && self.merkle_proof.root_from_leaf(tx.transaction().get_id().get())
+                == Some(*tx_merkle_root)
+    }
+
+    /// Checks that the header is the one of the proven block, and the proof against it
+    pub fn verify_with_header(&self, tx: &SignedTransaction, header: &BlockHeader) -> bool {
+        header.block_id() == self.block_id && self.verify(tx, &header.tx_merkle_root())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::{
+        block::timestamp::BlockTimestamp, signature::inputsig::InputWitness, OutPointSourceId,
+        SignedTransaction, TxInput,
+    };
+    use crypto::random::Rng;
+    use rstest::rstest;
+    use test_utils::random::{make_seedable_rng, Seed};
+
+    fn make_block(rng: &mut impl Rng, tx_count: usize) -> Block {
+        let transactions = (0..tx_count)
+            .map(|_| {
+                let inputs = vec![TxInput::new(
+                    OutPointSourceId::Transaction(H256::random_using(rng).into()),
+                    0,
+                )];
+                SignedTransaction::new(
+                    Transaction::new(0, inputs, Vec::new(), 0).unwrap(),
+                    vec![InputWitness::NoSignature(None)],
+                )
+                .unwrap()
+            })
+            .collect();
+        Block::new_with_no_consensus(
+            transactions,
+            Id::new(H256::random_using(rng)),
+            BlockTimestamp::from_int_seconds(rng.gen()),
+        )
+        .unwrap()
+    }
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn proofs_of_all_transactions(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let tx_count = rng.gen_range(1..40);
+        let block = make_block(&mut rng, tx_count);
+
+        for tx in block.transactions() {
+            let tx_id = tx.transaction().get_id();
+            let proof = TxInclusionProof::from_block(&block, &tx_id).unwrap();
+
+            let decoded = TxInclusionProof::decode(&mut proof.encode().as_slice()).unwrap();
+            assert_eq!(decoded, proof);
+
+            assert_eq!(proof.block_id(), &block.get_id());
+            assert_eq!(proof.tx_count() as usize, tx_count);
+            assert!(proof.verify(tx, &block.merkle_root()));
+            assert!(proof.verify_with_header(tx, block.header()));
+        }
+
+        let other_tx_id = Id::new(H256::random_using(&mut rng));
+        assert_eq!(TxInclusionProof::from_block(&block, &other_tx_id), None);
+    }
+
+    fn with_merkle_proof(
+        proof: &TxInclusionProof,
+        leaf_index: u32,
+        siblings: &[H256],
+    ) -> TxInclusionProof {
+        TxInclusionProof {
+            merkle_proof: MerkleProof::decode(
+                &mut (leaf_index, siblings.to_vec()).encode().as_slice(),
+            )
+            .unwrap(),
+            ..proof.clone()
+        }
+    }
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn bad_proofs_are_rejected(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let tx_count = rng.gen_range(2..40);
+        let block = make_block(&mut rng, tx_count);
+        let other_block = make_block(&mut rng, tx_count);
+
+        let tx_index = rng.gen_range(0..tx_count);
+        let tx = &block.transactions()[tx_index];
+        let proof = TxInclusionProof::from_block(&block, &tx.transaction().get_id()).unwrap();
+        assert!(proof.verify(tx, &block.merkle_root()));
+
+        assert!(!proof.verify(tx, &other_block.merkle_root()));
+        assert!(!proof.verify_with_header(tx, other_block.header()));
+
+        // Another transaction of the block
+        let other_tx = &block.transactions()[(tx_index + 1) % tx_count];
+        assert!(!proof.verify(other_tx, &block.merkle_root()));
+
+        // The block reward is not a transaction
+        let leaves = tx_merkle_leaves(block.body());
+        let reward_proof = merkle::merkle_proof_from_vec(&leaves, 0).unwrap();
+        let reward_proof = with_merkle_proof(&proof, 0, reward_proof.siblings());
+        assert!(!reward_proof.verify(tx, &block.merkle_root()));
+
+        // The index has to be one of a transaction, not of a padding leaf
+        let siblings = proof.merkle_proof().siblings();
+        let padding_index = tx_count as u32 + 1;
+        if padding_index < 1 << siblings.len() {
+            let padding_proof = with_merkle_proof(&proof, padding_index, siblings);
+            assert!(!padding_proof.verify(tx, &block.merkle_root()));
+        }
+
+        // The depth of the tree follows from the transaction count, an internal node is not a leaf
+        let internal_node_proof = with_merkle_proof(
+            &proof,
+            proof.merkle_proof().leaf_index() / 2,
+            &siblings[1..],
+        );
+        assert!(!internal_node_proof.verify(tx, &block.merkle_root()));
+
+        // The transaction count has to match the tree
+        let wrong_count_proof = TxInclusionProof {
+            tx_count: tx_count as u32 * 2,
+            ..proof.clone()
+        };
+        assert!(!wrong_count_proof.verify(tx, &block.merkle_root()));
+        let zero_count_proof = TxInclusionProof {
+            tx_count: 0,
+            ..proof.clone()
+        };
+        assert!(!zero_count_proof.verify(tx, &block.merkle_root()));
+
+        // An index that only differs above the tree depth
+        let forged_index_proof = with_merkle_proof(
+            &proof,
+            proof.merkle_proof().leaf_index() + (1 << siblings.len()),
+            siblings,
+        );
+        assert!(!forged_index_proof.verify(tx, &block.merkle_root()));
+    }
+}
@@ -17,9 +17,9 @@ use super::emission_schedule::{self, *};
 use super::{create_mainnet_genesis, create_unit_test_genesis, ChainConfig, ChainType};
 
 use crate::chain::{
-    ConsensusUpgrade, Destination, Genesis, Mlt, NetUpgrades, PoWChainConfig, UpgradeVersion,
+    Block, ConsensusUpgrade, Destination, Genesis, Mlt, NetUpgrades, PoWChainConfig, UpgradeVersion,
 };
-use crate::primitives::{id::WithId, semver::SemVer, BlockHeight};
+use crate::primitives::{id::WithId, semver::SemVer, BlockHeight, Id, H256};
 use crate::primitives::{Amount, BlockDistance};
 
 use std::collections::BTreeMap;
@@ -58,6 +58,15 @@ impl ChainType {
             ChainType::Signet => NetUpgrades::unit_tests(),
         }
     }
+
+    fn default_assumed_valid_block(&self) -> Option<(BlockHeight, Id<Block>)> {
+        match self {
+            // TODO: set once the network has blocks deep enough to be assumed valid
+            ChainType::Mainnet | ChainType::Testnet => None,
+            // Test networks are short lived, all the signatures are checked
+            ChainType::Regtest | ChainType::Signet => None,
+        }
+    }
 }
 
 // Builder support types
@@ -107,6 +116,8 @@ pub struct Builder {
     token_min_hash_len: usize,
     token_max_hash_len: usize,
     empty_consensus_reward_maturity_distance: BlockDistance,
+    assumed_valid_block: Option<(BlockHeight, Id<Block>)>,
+    utxo_snapshot_hashes: BTreeMap<Id<Block>, H256>,
 }
 
 impl Builder {
@@ -117,7 +128,7 @@ impl Builder {
             address_prefix: chain_type.default_address_prefix().to_string(),
             coin_decimals: Mlt::DECIMALS,
             magic_bytes: chain_type.default_magic_bytes(),
-            version: SemVer::new(0, 1, 0),
+            version: SemVer::new(0, 2, 0),
             max_block_header_size: super::MAX_BLOCK_HEADER_SIZE,
             max_block_size_with_standard_txs: super::MAX_BLOCK_TXS_SIZE,
             max_block_size_with_smart_contracts: super::MAX_BLOCK_CONTRACTS_SIZE,
@@ -135,6 +146,8 @@ impl Builder {
             token_min_hash_len: super::TOKEN_MIN_HASH_LEN,
             token_max_hash_len: super::TOKEN_MAX_HASH_LEN,
             empty_consensus_reward_maturity_distance: BlockDistance::new(0),
+            assumed_valid_block: chain_type.default_assumed_valid_block(),
+            utxo_snapshot_hashes: BTreeMap::new(),
         }
     }
 
@@ -170,6 +183,8 @@ impl Builder {
             token_min_hash_len,
             token_max_hash_len,
             empty_consensus_reward_maturity_distance,
+            assumed_valid_block,
+            utxo_snapshot_hashes,
         } = self;
 
         let emission_schedule = match emission_schedule {
@@ -213,6 +228,8 @@ impl Builder {
             token_max_description_len,
             token_min_hash_len,
             token_max_hash_len,
+            assumed_valid_block,
+            utxo_snapshot_hashes,
         }
     }
 }
@@ -241,6 +258,8 @@ impl Builder {
     builder_method!(max_block_size_with_smart_contracts: usize);
     builder_method!(net_upgrades: NetUpgrades<UpgradeVersion>);
     builder_method!(empty_consensus_reward_maturity_distance: BlockDistance);
+    builder_method!(assumed_valid_block: Option<(BlockHeight, Id<Block>)>);
+    builder_method!(utxo_snapshot_hashes: BTreeMap<Id<Block>, H256>);
 
     /// Set the genesis block to be the unit test version
     pub fn genesis_unittest(mut self, premine_destination: Destination) -> Self {
@@ -29,7 +29,7 @@ use crate::chain::{Block, GenBlock, Genesis};
 use crate::chain::{PoWChainConfig, UpgradeVersion};
 use crate::primitives::id::{Id, Idable, WithId};
 use crate::primitives::semver::SemVer;
-use crate::primitives::{Amount, BlockDistance, BlockHeight};
+use crate::primitives::{Amount, BlockDistance, BlockHeight, H256};
 use std::collections::BTreeMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -90,6 +90,8 @@ pub struct ChainConfig {
     token_min_hash_len: usize,
     token_max_hash_len: usize,
     empty_consensus_reward_maturity_distance: BlockDistance,
+    assumed_valid_block: Option<(BlockHeight, Id<Block>)>,
+    utxo_snapshot_hashes: BTreeMap<Id<Block>, H256>,
 }
 
 impl ChainConfig {
@@ -193,6 +195,17 @@ impl ChainConfig {
         self.token_max_hash_len
     }
 
+    /// The signatures of the ancestors of this block, given with its height, are not checked
+    /// during the initial sync
+    pub fn assumed_valid_block(&self) -> Option<(BlockHeight, Id<Block>)> {
+        self.assumed_valid_block
+    }
+
+    /// The content hashes of the utxo snapshots that can be loaded, by their block ids
+    pub fn utxo_snapshot_hashes(&self) -> &BTreeMap<Id<Block>, H256> {
+        &self.utxo_snapshot_hashes
+    }
+
     pub fn empty_consensus_reward_maturity_distance(&self) -> BlockDistance {
         self.empty_consensus_reward_maturity_distance
     }
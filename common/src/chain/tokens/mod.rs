@@ -29,6 +29,15 @@ pub use tokens_utils::*;
 
 use super::{Block, Transaction};
 
+/// The kind of value held by an output: coins or a given token
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Encode, Decode)]
+pub enum CoinOrTokenId {
+    #[codec(index = 0)]
+    Coin,
+    #[codec(index = 1)]
+    TokenId(TokenId),
+}
+
 /// The data that is created when a token is issued to track it (and to update it with ACL commands)
 #[derive(Debug, Clone, Encode, Decode, Eq, PartialEq)]
 pub struct TokenAuxiliaryData {
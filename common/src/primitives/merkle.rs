@@ -21,6 +21,7 @@ use merkletree::hash::Hashable;
 use merkletree::merkle::Element;
 use merkletree::merkle::MerkleTree;
 use merkletree::store::VecStore;
+use serialization::{Decode, Encode};
 
 /// This is the hashing algorithm implementation
 /// used by MerkleTree; it basically contains
@@ -41,6 +42,8 @@ fn next_pow2(n: usize) -> usize {
 pub enum MerkleTreeFormError {
     #[error("Merkle tree input too small: {0}")]
     TooSmall(usize),
+    #[error("Merkle tree leaf index {0} out of range for {1} leaves")]
+    LeafIndexOutOfRange(usize, usize),
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -96,6 +99,86 @@ pub fn merkletree_from_vec(
     Ok(tree)
 }
 
+/// The sibling hashes on the path from a leaf to the root of a merkle tree, which are enough to
+/// recalculate the root from the leaf
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct MerkleProof {
+    leaf_index: u32,
+    siblings: Vec<H256>,
+}
+
+impl MerkleProof {
+    pub fn leaf_index(&self) -> u32 {
+        self.leaf_index
+    }
+
+    /// Sibling hashes, starting from the leaf level
+    pub fn siblings(&self) -> &[H256] {
+        &self.siblings
+    }
+
+    /// Calculate the root of the tree that the proof was made for, given the leaf it proves.
+    /// Returns None if the leaf index doesn't fit in a tree of the depth of the proof, as the
+    /// extra bits would make the same path prove other positions.
+    pub fn root_from_leaf(&self, leaf: H256) -> Option<H256> {
+        let depth = u32::try_from(self.siblings.len()).unwrap_or(u32::MAX);
+        if self.leaf_index.checked_shr(depth).unwrap_or(0) != 0 {
+            return None;
+        }
+
+        let mut index = self.leaf_index;
+        let mut node = leaf;
+        for sibling in &self.siblings {
+            node = if index & 1 == 0 {
+                hash_node(&node, sibling)
+            } else {
+                hash_node(sibling, &node)
+            };
+            index /= 2;
+        }
+        Some(node)
+    }
+}
+
+fn hash_node(left: &H256, right: &H256) -> H256 {
+    let mut hasher = DefaultHashAlgoStream::new();
+    hasher.write(left);
+    hasher.write(right);
+    hasher.finalize().into()
+}
+
+/// Given a set of leaf hashes, calculate the proof for the leaf at the given index in the tree
+/// that merkletree_from_vec makes of them
+pub fn merkle_proof_from_vec(
+    elements: &[H256],
+    leaf_index: usize,
+) -> Result<MerkleProof, MerkleTreeFormError> {
+    if elements.len() < 2 {
+        return Err(MerkleTreeFormError::TooSmall(elements.len()));
+    }
+    if leaf_index >= elements.len() {
+        return Err(MerkleTreeFormError::LeafIndexOutOfRange(
+            leaf_index,
+            elements.len(),
+        ));
+    }
+
+    let mut level: Vec<H256> =
+        elements.iter().copied().chain(merkletree_get_pad_data(elements)).collect();
+    let mut index = leaf_index;
+    let mut siblings = Vec::new();
+    while level.len() > 1 {
+        siblings.push(level[index ^ 1]);
+        level = level.chunks(2).map(|pair| hash_node(&pair[0], &pair[1])).collect();
+        index /= 2;
+    }
+
+    Ok(MerkleProof {
+        leaf_index: leaf_index as u32,
+        siblings,
+    })
+}
+
 impl Default for BlockchainHashAlgorithm {
     fn default() -> BlockchainHashAlgorithm {
         BlockchainHashAlgorithm(DefaultHashAlgoStream::new())
@@ -410,6 +493,61 @@ mod tests {
         assert_eq!(t.root(), res.into());
     }
 
+    #[test]
+    fn merkle_proofs_match_tree_root() {
+        for leaf_count in 2..20 {
+            let leaves: Vec<H256> =
+                (0..leaf_count).map(|i| default_hash(H256::from_low_u64_be(i))).collect();
+            let root = merkletree_from_vec(&leaves).unwrap().root();
+
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof_from_vec(&leaves, index).unwrap();
+                assert_eq!(proof.leaf_index(), index as u32);
+                assert_eq!(
+                    proof.siblings().len(),
+                    next_pow2(leaves.len()).trailing_zeros() as usize
+                );
+                assert_eq!(proof.root_from_leaf(*leaf), Some(root));
+                assert_ne!(proof.root_from_leaf(H256::zero()), Some(root));
+            }
+        }
+    }
+
+    #[test]
+    fn merkle_proof_index_out_of_depth() {
+        let leaves: Vec<H256> = (0..5).map(|i| default_hash(H256::from_low_u64_be(i))).collect();
+        let proof = merkle_proof_from_vec(&leaves, 0).unwrap();
+        let depth = proof.siblings().len();
+
+        // The bits above the depth of the tree are not used by the path, so they must be zero
+        for leaf_index in [1 << depth, (1 << depth) | 1, u32::MAX] {
+            let forged = MerkleProof {
+                leaf_index,
+                ..proof.clone()
+            };
+            assert_eq!(forged.root_from_leaf(leaves[0]), None);
+        }
+
+        // A proof deeper than the bits of the index doesn't restrict the index
+        let long_proof = MerkleProof {
+            leaf_index: u32::MAX,
+            siblings: vec![H256::zero(); 40],
+        };
+        assert!(long_proof.root_from_leaf(H256::zero()).is_some());
+    }
+
+    #[test]
+    fn merkle_proof_bad_input() {
+        assert_eq!(
+            merkle_proof_from_vec(&[H256::zero()], 0).unwrap_err(),
+            MerkleTreeFormError::TooSmall(1)
+        );
+        assert_eq!(
+            merkle_proof_from_vec(&[H256::zero(), H256::zero()], 2).unwrap_err(),
+            MerkleTreeFormError::LeafIndexOutOfRange(2, 2)
+        );
+    }
+
     #[test]
     fn next_pow2_tests() {
         assert_eq!(next_pow2(0), 1);
@@ -0,0 +1,156 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! LtHash16, a hash of a multiset that can be updated incrementally.
+//!
+//! Every element is expanded into a vector of 16-bit lanes, and the state is the lane-wise sum
+//! (modulo 2^16) of the vectors of the inserted elements minus those of the removed ones. So the
+//! digest doesn't depend on the order of the updates, states built separately can be combined,
+//! and the same multiset always has the same state.
+//! See "Securing Update Propagation with Homomorphic Hashing" by Lewi, Kim, Maykov and Weis.
+
+use generic_array::typenum::U32;
+use serialization::{Decode, Encode};
+
+use super::{Blake2b, Blake2b32Stream, StreamHasher};
+
+const NUM_LANES: usize = 1024;
+const LANES_PER_CHUNK: usize = 32;
+
+/// Expand the data into the lanes
+fn data_to_lanes(data: &[u8]) -> [u16; NUM_LANES] {
+    let mut lanes = [0; NUM_LANES];
+    for (chunk_idx, chunk) in lanes.chunks_mut(LANES_PER_CHUNK).enumerate() {
+        let mut chunk_data = Vec::with_capacity(data.len() + 2);
+        chunk_data.extend_from_slice(&(chunk_idx as u16).to_le_bytes());
+        chunk_data.extend_from_slice(data);
+        let chunk_hash = super::hash::<Blake2b, _>(chunk_data);
+        for (lane, bytes) in chunk.iter_mut().zip(chunk_hash.chunks_exact(2)) {
+            *lane = u16::from_le_bytes([bytes[0], bytes[1]]);
+        }
+    }
+    lanes
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct LtHash16 {
+    lanes: [u16; NUM_LANES],
+}
+
+impl LtHash16 {
+    /// The hash of the empty set
+    pub fn new() -> Self {
+        Self {
+            lanes: [0; NUM_LANES],
+        }
+    }
+
+    pub fn insert(&mut self, data: &[u8]) {
+        let lanes = data_to_lanes(data);
+        self.lanes
+            .iter_mut()
+            .zip(lanes.iter())
+            .for_each(|(a, b)| *a = a.wrapping_add(*b));
+    }
+
+    pub fn remove(&mut self, data: &[u8]) {
+        let lanes = data_to_lanes(data);
+        self.lanes
+            .iter_mut()
+            .zip(lanes.iter())
+            .for_each(|(a, b)| *a = a.wrapping_sub(*b));
+    }
+
+    /// Add the changes of the other state to this one
+    pub fn combine(&mut self, other: &LtHash16) {
+        self.lanes
+            .iter_mut()
+            .zip(other.lanes.iter())
+            .for_each(|(a, b)| *a = a.wrapping_add(*b));
+    }
+
+    /// The digest of the multiset
+    pub fn finalize(&self) -> generic_array::GenericArray<u8, U32> {
+        let mut hasher = Blake2b32Stream::new();
+        self.lanes.iter().for_each(|lane| {
+            hasher.write(lane.to_le_bytes());
+        });
+        hasher.finalize()
+    }
+}
+
+impl Default for LtHash16 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::{Rng, SliceRandom};
+    use rstest::rstest;
+    use test_utils::random::{make_seedable_rng, Seed};
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn order_independent(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let mut elements: Vec<[u8; 8]> = (0..10).map(|_| rng.gen()).collect();
+
+        let mut hash1 = LtHash16::new();
+        elements.iter().for_each(|e| hash1.insert(e));
+        elements.shuffle(&mut rng);
+        let mut hash2 = LtHash16::new();
+        elements.iter().for_each(|e| hash2.insert(e));
+        assert_eq!(hash1, hash2);
+
+        let mut hash3 = LtHash16::new();
+        elements[..5].iter().for_each(|e| hash3.insert(e));
+        let mut delta = LtHash16::new();
+        elements[5..].iter().for_each(|e| delta.insert(e));
+        hash3.combine(&delta);
+        assert_eq!(hash1, hash3);
+
+        let decoded = LtHash16::decode(&mut hash3.encode().as_slice()).unwrap();
+        assert_eq!(decoded.finalize(), hash1.finalize());
+    }
+
+    #[rstest]
+    #[trace]
+    #[case(Seed::from_entropy())]
+    fn remove_undoes_insert(#[case] seed: Seed) {
+        let mut rng = make_seedable_rng(seed);
+        let element1: [u8; 8] = rng.gen();
+        let element2: [u8; 8] = rng.gen();
+
+        let mut hash = LtHash16::new();
+        hash.insert(&element1);
+        let with_one = hash.clone();
+
+        hash.insert(&element2);
+        assert_ne!(hash.finalize(), with_one.finalize());
+
+        hash.remove(&element2);
+        assert_eq!(hash, with_one);
+
+        // removing before inserting gives the same state
+        let mut delta = LtHash16::new();
+        delta.remove(&element1);
+        hash.combine(&delta);
+        assert_eq!(hash, LtHash16::new());
+    }
+}
@@ -14,10 +14,13 @@
 // limitations under the License.
 
 mod internal;
+mod lthash;
 
 use generic_array::{sequence::Split, typenum, ArrayLength, GenericArray};
 use internal::InternalStreamHasher;
 
+pub use lthash::LtHash16;
+
 pub trait Hasher {
     type OutputSize: ArrayLength<u8>;
 
@@ -13,13 +13,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use chainstate::ChainstateError;
+use chainstate::{ban_score::BanScore, ChainstateError};
 use subsystem::subsystem::CallError;
 use thiserror::Error;
 
+use common::chain::signature::TransactionSigError;
 use common::chain::transaction::Transaction;
 use common::chain::OutPoint;
 use common::primitives::amount::Amount;
+use common::primitives::BlockHeight;
 use common::primitives::Id;
 use common::primitives::H256;
 
@@ -37,6 +39,26 @@ pub enum Error {
     RecvError,
 }
 
+/// Error reading or writing the mempool dump or the fee estimation statistics
+#[derive(Debug, Error)]
+pub enum DumpError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid data: {0}")]
+    Decode(#[from] serialization::Error),
+    #[error("Unsupported format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Fee estimates saved at height {saved_height}, {age_secs} seconds ago, are outdated")]
+    OutdatedFeeEstimates {
+        saved_height: BlockHeight,
+        age_secs: u64,
+    },
+    #[error("Chainstate error")]
+    ChainstateError(#[from] ChainstateError),
+    #[error("Subsystem call error")]
+    CallError(#[from] CallError),
+}
+
 #[derive(Debug, Error)]
 pub enum TxValidationError {
     #[error("Transaction has no inputs.")]
@@ -50,6 +72,18 @@ pub enum TxValidationError {
         outpoint: OutPoint,
         spending_tx_id: Id<Transaction>,
     },
+    #[error("Transaction spends a timelocked output: {outpoint:?}")]
+    SpendsTimelockedOutput {
+        outpoint: OutPoint,
+        spending_tx_id: Id<Transaction>,
+    },
+    #[error("Transaction spends a burned output: {outpoint:?}")]
+    SpendsBurnedOutput {
+        outpoint: OutPoint,
+        spending_tx_id: Id<Transaction>,
+    },
+    #[error("Signature verification failed: {0}")]
+    SignatureVerificationFailed(#[from] TransactionSigError),
     #[error("Transaction exceeds the maximum block size.")]
     ExceedsMaxBlockSize,
     #[error("Transaction already exists in the mempool.")]
@@ -102,3 +136,54 @@ pub enum TxValidationError {
     #[error("Internal Error.")]
     InternalError,
 }
+
+impl BanScore for Error {
+    fn ban_score(&self) -> u32 {
+        match self {
+            Error::MempoolFull => 0,
+            Error::TxValidationError(err) => err.ban_score(),
+            Error::SubsystemFailure => 0,
+            Error::SendError => 0,
+            Error::RecvError => 0,
+        }
+    }
+}
+
+// Only transactions that can never be valid are penalized. The others may be rejected because of
+// the local mempool state, which the peer can't know about.
+impl BanScore for TxValidationError {
+    fn ban_score(&self) -> u32 {
+        match self {
+            TxValidationError::NoInputs => 100,
+            TxValidationError::NoOutputs => 100,
+            TxValidationError::DuplicateInputs => 100,
+            TxValidationError::OutPointNotFound { .. } => 0,
+            TxValidationError::SpendsTimelockedOutput { .. } => 0,
+            TxValidationError::SpendsBurnedOutput { .. } => 100,
+            TxValidationError::SignatureVerificationFailed(_) => 100,
+            TxValidationError::ExceedsMaxBlockSize => 100,
+            TxValidationError::TransactionAlreadyInMempool => 0,
+            TxValidationError::ConflictWithIrreplaceableTransaction => 0,
+            TxValidationError::InputValuesOverflow => 100,
+            TxValidationError::OutputValuesOverflow => 100,
+            TxValidationError::InputsBelowOutputs => 100,
+            TxValidationError::ReplacementFeeLowerThanOriginal { .. } => 0,
+            TxValidationError::TooManyPotentialReplacements => 0,
+            TxValidationError::SpendsNewUnconfirmedOutput => 0,
+            TxValidationError::ConflictsFeeOverflow => 0,
+            TxValidationError::TransactionFeeLowerThanConflictsWithDescendants => 0,
+            TxValidationError::AdditionalFeesUnderflow => 0,
+            TxValidationError::InsufficientFeesToRelay { .. } => 0,
+            TxValidationError::InsufficientFeesToRelayRBF => 0,
+            TxValidationError::RollingFeeThresholdNotMet { .. } => 0,
+            TxValidationError::AncestorFeeOverflow => 0,
+            TxValidationError::AncestorFeeUpdateOverflow => 0,
+            TxValidationError::FeeOverflow => 0,
+            TxValidationError::GetParentError => 0,
+            TxValidationError::DescendantOfExpiredTransaction => 0,
+            TxValidationError::ChainstateError(err) => err.ban_score(),
+            TxValidationError::CallError(_) => 0,
+            TxValidationError::InternalError => 0,
+        }
+    }
+}
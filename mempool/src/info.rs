@@ -0,0 +1,57 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Summaries of the mempool state, as reported over RPC
+
+use common::{
+    chain::Transaction,
+    primitives::{Amount, Id},
+};
+
+/// Overall state of the mempool
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MempoolInfo {
+    /// Number of transactions in the mempool
+    pub tx_count: usize,
+    /// Sum of the encoded sizes of the transactions in the mempool
+    pub total_size: usize,
+    /// Estimated memory usage of the mempool
+    pub memory_usage: usize,
+    /// Memory usage above which the mempool starts evicting transactions
+    pub max_size: usize,
+    /// Minimum fee rate, per 1000 bytes, a transaction has to pay to get into the mempool
+    pub min_fee_rate_per_kb: Amount,
+}
+
+/// A transaction in the mempool along with the stats of its in-mempool ancestors and descendants.
+/// The ancestor and descendant stats include the transaction itself.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TxEntryInfo {
+    pub tx_id: Id<Transaction>,
+    pub fee: Amount,
+    pub size: usize,
+    /// Time the transaction entered the mempool, in seconds
+    pub creation_time: u64,
+    /// In-mempool transactions this transaction spends outputs of
+    pub parents: Vec<Id<Transaction>>,
+    /// In-mempool transactions spending outputs of this transaction
+    pub children: Vec<Id<Transaction>>,
+    pub ancestor_count: usize,
+    pub ancestor_size: usize,
+    pub ancestor_fees: Amount,
+    pub descendant_count: usize,
+    pub descendant_size: usize,
+    pub descendant_fees: Amount,
+}
@@ -13,10 +13,15 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{error::Error, tx_accumulator::TransactionAccumulator, MempoolEvent};
+use crate::{
+    error::Error,
+    info::{MempoolInfo, TxEntryInfo},
+    tx_accumulator::TransactionAccumulator,
+    MempoolEvent,
+};
 use common::{
     chain::{SignedTransaction, Transaction},
-    primitives::Id,
+    primitives::{Amount, Id},
 };
 use std::sync::Arc;
 use subsystem::{CallRequest, ShutdownRequest};
@@ -29,6 +34,21 @@ pub trait MempoolInterface: Send + Sync {
     // Returns `true` if the mempool contains a transaction with the given id, `false` otherwise.
     async fn contains_transaction(&self, tx: &Id<Transaction>) -> Result<bool, Error>;
 
+    // Returns the transaction with the given id, if it is in the mempool.
+    async fn transaction(&self, id: &Id<Transaction>) -> Result<Option<SignedTransaction>, Error>;
+
+    async fn get_all_transaction_ids(&self) -> Result<Vec<Id<Transaction>>, Error>;
+
+    // Returns the ancestor and descendant stats of the transaction with the given id, if it is in
+    // the mempool.
+    async fn entry_info(&self, id: &Id<Transaction>) -> Result<Option<TxEntryInfo>, Error>;
+
+    async fn info(&self) -> Result<MempoolInfo, Error>;
+
+    // Returns the fee rate per kB a transaction needs to pay to be confirmed within the given
+    // number of blocks, if there is enough data to estimate it.
+    async fn estimate_fee_rate(&self, in_blocks: usize) -> Result<Option<Amount>, Error>;
+
     async fn collect_txs(
         &self,
         tx_accumulator: Box<dyn TransactionAccumulator + Send>,
@@ -14,22 +14,28 @@
 // limitations under the License.
 
 use crate::{
-    error::Error, pool::Mempool, tx_accumulator::TransactionAccumulator, GetMemoryUsage,
-    MempoolEvent, MempoolInterface, MempoolSubsystemInterface,
+    error::{DumpError, Error},
+    info::{MempoolInfo, TxEntryInfo},
+    pool::Mempool,
+    tx_accumulator::TransactionAccumulator,
+    GetMemoryUsage, MempoolEvent, MempoolInterface, MempoolSubsystemInterface,
 };
 use chainstate::chainstate_interface::ChainstateInterface;
 use common::{
     chain::{ChainConfig, SignedTransaction, Transaction},
-    primitives::Id,
+    primitives::{Amount, Id},
     time_getter::TimeGetter,
 };
-use std::sync::Arc;
+use logging::log;
+use std::{path::PathBuf, sync::Arc};
 use subsystem::{CallRequest, ShutdownRequest};
 use tokio::sync::mpsc;
 use utils::tap_error_log::LogError;
 
 struct MempoolInterfaceImpl<M> {
     pool: Mempool<M>,
+    dump_file: Option<PathBuf>,
+    fee_estimates_file: Option<PathBuf>,
 }
 
 impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolInterfaceImpl<M> {
@@ -38,6 +44,8 @@ impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolInterfaceImpl<M> {
         chainstate_handle: subsystem::Handle<Box<dyn ChainstateInterface>>,
         time_getter: TimeGetter,
         memory_usage_estimator: M,
+        dump_file: Option<PathBuf>,
+        fee_estimates_file: Option<PathBuf>,
     ) -> Self {
         let pool = Mempool::new(
             chain_config,
@@ -45,7 +53,42 @@ impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolInterfaceImpl<M> {
             time_getter,
             memory_usage_estimator,
         );
-        Self { pool }
+        Self {
+            pool,
+            dump_file,
+            fee_estimates_file,
+        }
+    }
+
+    async fn load_dump(&mut self) {
+        if let Some(dump_file) = &self.dump_file {
+            match self.pool.load_dump(dump_file).await {
+                Ok(count) => log::info!("Restored {count} transactions from the mempool dump"),
+                Err(e) => log::error!("Failed to load the mempool dump: {e}"),
+            }
+        }
+        if let Some(fee_estimates_file) = &self.fee_estimates_file {
+            match self.pool.load_fee_estimates(fee_estimates_file).await {
+                Ok(true) => log::info!("Restored the fee estimates"),
+                Ok(false) => {}
+                Err(e @ DumpError::OutdatedFeeEstimates { .. }) => log::info!("{e}, not restored"),
+                Err(e) => log::error!("Failed to load the fee estimates: {e}"),
+            }
+        }
+    }
+
+    fn write_dump(&self) {
+        if let Some(dump_file) = &self.dump_file {
+            match self.pool.write_dump(dump_file) {
+                Ok(count) => log::info!("Saved {count} transactions to the mempool dump"),
+                Err(e) => log::error!("Failed to write the mempool dump: {e}"),
+            }
+        }
+        if let Some(fee_estimates_file) = &self.fee_estimates_file {
+            if let Err(e) = self.pool.write_fee_estimates(fee_estimates_file) {
+                log::error!("Failed to write the fee estimates: {e}");
+            }
+        }
     }
 
     pub async fn subscribe_to_chainstate_events(
@@ -79,13 +122,15 @@ impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolSubsystemInterface
             .await
             .log_err()
             .expect("chainstate event subscription");
+        self.load_dump().await;
         loop {
             tokio::select! {
                 () = shut_rq.recv() => break,
                 call = call_rq.recv() => call(&mut self).await,
-                Some(evt) = chainstate_events_rx.recv() => self.pool.process_chainstate_event(evt),
+                Some(evt) = chainstate_events_rx.recv() => self.pool.process_chainstate_event(evt).await,
             }
         }
+        self.write_dump();
     }
 }
 
@@ -103,6 +148,26 @@ impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolInterface for MempoolInte
         Ok(self.pool.contains_transaction(tx_id))
     }
 
+    async fn transaction(&self, id: &Id<Transaction>) -> Result<Option<SignedTransaction>, Error> {
+        Ok(self.pool.get_transaction(id))
+    }
+
+    async fn get_all_transaction_ids(&self) -> Result<Vec<Id<Transaction>>, Error> {
+        Ok(self.pool.get_all_transaction_ids())
+    }
+
+    async fn entry_info(&self, id: &Id<Transaction>) -> Result<Option<TxEntryInfo>, Error> {
+        Ok(self.pool.get_entry_info(id))
+    }
+
+    async fn info(&self) -> Result<MempoolInfo, Error> {
+        Ok(self.pool.info())
+    }
+
+    async fn estimate_fee_rate(&self, in_blocks: usize) -> Result<Option<Amount>, Error> {
+        Ok(self.pool.estimate_fee_rate(in_blocks))
+    }
+
     async fn collect_txs(
         &self,
         tx_accumulator: Box<dyn TransactionAccumulator + Send>,
@@ -120,11 +185,16 @@ impl<M: GetMemoryUsage + Sync + Send + 'static> MempoolInterface for MempoolInte
 }
 
 /// Mempool constructor
+///
+/// If `dump_file` is given, the mempool entries are saved into it on shutdown and restored from
+/// it on start-up. The same goes for the fee estimation statistics and `fee_estimates_file`.
 pub fn make_mempool<M>(
     chain_config: Arc<ChainConfig>,
     chainstate_handle: subsystem::Handle<Box<dyn ChainstateInterface>>,
     time_getter: TimeGetter,
     memory_usage_estimator: M,
+    dump_file: Option<PathBuf>,
+    fee_estimates_file: Option<PathBuf>,
 ) -> impl MempoolSubsystemInterface
 where
     M: GetMemoryUsage + 'static + Send + Sync,
@@ -134,5 +204,7 @@ where
         chainstate_handle,
         time_getter,
         memory_usage_estimator,
+        dump_file,
+        fee_estimates_file,
     )
 }
@@ -16,7 +16,7 @@
 #![deny(clippy::clone_on_ref_ptr)]
 
 use common::{
-    chain::Block,
+    chain::{Block, Transaction},
     primitives::{BlockHeight, Id},
 };
 pub use interface::{
@@ -31,14 +31,23 @@ pub use crate::get_memory_usage::SystemUsageEstimator;
 mod config;
 pub mod error;
 mod get_memory_usage;
+pub mod info;
 mod interface;
 mod pool;
 pub mod rpc;
 pub mod tx_accumulator;
 
+/// Name of the file in the data directory where the mempool is saved on shutdown
+pub const MEMPOOL_DUMP_FILE: &str = "mempool.dat";
+
+/// Name of the file in the data directory where the fee estimation statistics are saved
+pub const FEE_ESTIMATES_FILE: &str = "fee_estimates.dat";
+
 #[derive(Debug, Clone)]
 pub enum MempoolEvent {
     NewTip(Id<Block>, BlockHeight),
+    /// A transaction was accepted into the mempool
+    TransactionAdded(Id<Transaction>),
 }
 
 pub type MempoolHandle = subsystem::Handle<dyn MempoolInterface>;
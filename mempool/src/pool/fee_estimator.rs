@@ -0,0 +1,513 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fee rate estimation based on how long the transactions seen by the mempool took to confirm
+//!
+//! The transactions are sorted into buckets of exponentially growing fee rates. When a tracked
+//! transaction is confirmed, its bucket records the number of blocks it took. The counts decay
+//! with every block, so recent blocks weigh more. The estimate for a target is the lowest fee
+//! rate for which enough transactions were confirmed within the target.
+
+use std::{collections::BTreeMap, path::Path};
+
+use common::{
+    chain::Transaction,
+    primitives::{amount::Amount, BlockHeight, Id},
+};
+use serialization::{Decode, DecodeAll, Encode};
+
+use super::feerate::{FeeRate, INCREMENTAL_RELAY_FEE_RATE};
+use crate::{config::Time, error::DumpError};
+
+/// Version of the fee estimates file format, to be bumped on incompatible changes
+const FEE_ESTIMATES_FORMAT_VERSION: u32 = 2;
+
+/// Saved statistics older than this are discarded, the fees may have changed a lot since
+const MAX_STATS_AGE: Time = Time::from_secs(60 * 60 * 60);
+
+/// Saved statistics are discarded if the chain has grown by more blocks than this since
+const MAX_STATS_BLOCKS_BEHIND: u64 = 1000;
+
+/// The highest number of blocks for which an estimate can be requested
+pub const MAX_CONFIRMATION_TARGET: usize = 48;
+
+/// Fee rate of the highest bucket, in atoms per kB
+const MAX_BUCKET_FEE_RATE: u128 = 1_000_000_000_000_000;
+
+/// Every bucket starts at a fee rate 10% higher than the previous one
+const BUCKET_SPACING_PERCENT: u128 = 110;
+
+/// Weight of a single transaction in the statistics. The weights are integers to allow the
+/// statistics to be SCALE-encoded, so a transaction counts as this many units.
+const TX_WEIGHT: u64 = 1_000_000;
+
+/// The statistics are multiplied by this fraction with every block, a half-life of ~350 blocks
+const DECAY_NUMERATOR: u64 = 998;
+const DECAY_DENOMINATOR: u64 = 1000;
+
+/// The number of transactions a range of buckets needs to provide an estimate
+const MIN_TXS_IN_RANGE: u64 = 5;
+
+/// The share of transactions in a range of buckets that must have been confirmed within the
+/// target for the range to be considered good enough
+const SUCCESS_PERCENT: u64 = 85;
+
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+struct BucketStats {
+    /// Weight of the transactions confirmed within `n + 1` blocks, at index `n`
+    confirmed_within: Vec<u64>,
+    /// Weight of all the transactions with a known outcome, i.e. confirmed or not confirmed
+    /// within [MAX_CONFIRMATION_TARGET] blocks
+    total: u64,
+}
+
+impl BucketStats {
+    fn new() -> Self {
+        Self {
+            confirmed_within: vec![0; MAX_CONFIRMATION_TARGET],
+            total: 0,
+        }
+    }
+
+    fn decay(&mut self) {
+        let decay = |weight: &mut u64| *weight = *weight * DECAY_NUMERATOR / DECAY_DENOMINATOR;
+        self.confirmed_within.iter_mut().for_each(decay);
+        decay(&mut self.total);
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TrackedTx {
+    bucket: usize,
+    /// The height of the tip when the transaction entered the mempool
+    entry_height: BlockHeight,
+}
+
+pub struct FeeEstimator {
+    /// The lowest fee rate of each bucket, in ascending order
+    bucket_fee_rates: Vec<FeeRate>,
+    buckets: Vec<BucketStats>,
+    /// The transactions in the mempool waiting to be confirmed
+    tracked: BTreeMap<Id<Transaction>, TrackedTx>,
+    /// The height of the tip, unknown until a block is connected
+    tip_height: Option<BlockHeight>,
+}
+
+impl FeeEstimator {
+    pub fn new() -> Self {
+        let bucket_fee_rates: Vec<_> =
+            std::iter::successors(Some(INCREMENTAL_RELAY_FEE_RATE.atoms_per_kb()), |rate| {
+                Some(rate * BUCKET_SPACING_PERCENT / 100)
+            })
+            .take_while(|rate| *rate <= MAX_BUCKET_FEE_RATE)
+            .map(|rate| FeeRate::new(Amount::from_atoms(rate)))
+            .collect();
+        let buckets = vec![BucketStats::new(); bucket_fee_rates.len()];
+        Self {
+            bucket_fee_rates,
+            buckets,
+            tracked: BTreeMap::new(),
+            tip_height: None,
+        }
+    }
+
+    fn bucket_index(&self, fee_rate: FeeRate) -> usize {
+        self.bucket_fee_rates
+            .partition_point(|rate| *rate <= fee_rate)
+            .saturating_sub(1)
+    }
+
+    fn blocks_since(&self, entry_height: BlockHeight) -> u64 {
+        self.tip_height.map_or(0, |tip_height| {
+            u64::from(tip_height).saturating_sub(u64::from(entry_height))
+        })
+    }
+
+    /// Start tracking a transaction that just entered the mempool. The transactions that enter
+    /// before the first block is seen are not tracked, because the number of blocks they take to
+    /// confirm is unknown.
+    pub fn tx_added(&mut self, tx_id: Id<Transaction>, fee_rate: FeeRate) {
+        if let Some(entry_height) = self.tip_height {
+            let bucket = self.bucket_index(fee_rate);
+            self.tracked.insert(
+                tx_id,
+                TrackedTx {
+                    bucket,
+                    entry_height,
+                },
+            );
+        }
+    }
+
+    /// Record the confirmation of the tracked transactions included in the block.
+    ///
+    /// The transactions tracked for longer than [MAX_CONFIRMATION_TARGET] blocks are recorded as
+    /// not confirmed and no longer tracked.
+    pub fn block_connected<'a>(
+        &mut self,
+        block_height: BlockHeight,
+        tx_ids: impl IntoIterator<Item = &'a Id<Transaction>>,
+    ) {
+        self.tip_height = Some(block_height);
+        self.buckets.iter_mut().for_each(BucketStats::decay);
+
+        for tx_id in tx_ids {
+            if let Some(tracked) = self.tracked.remove(tx_id) {
+                let blocks = self.blocks_since(tracked.entry_height);
+                let stats = &mut self.buckets[tracked.bucket];
+                stats.total += TX_WEIGHT;
+                stats
+                    .confirmed_within
+                    .iter_mut()
+                    .skip((blocks as usize).saturating_sub(1))
+                    .for_each(|weight| *weight += TX_WEIGHT);
+            }
+        }
+
+        let expired: Vec<_> = self
+            .tracked
+            .iter()
+            .filter(|(_, tracked)| {
+                self.blocks_since(tracked.entry_height) > MAX_CONFIRMATION_TARGET as u64
+            })
+            .map(|(tx_id, _)| *tx_id)
+            .collect();
+        for tx_id in expired {
+            let tracked = self.tracked.remove(&tx_id).expect("tracked tx exists");
+            self.buckets[tracked.bucket].total += TX_WEIGHT;
+        }
+    }
+
+    /// Follow the tip without recording anything, for the blocks that say nothing about how long
+    /// the transactions take to confirm, e.g. the ones received during the initial block
+    /// download. The tracked transactions are forgotten.
+    pub fn block_skipped(&mut self, block_height: BlockHeight) {
+        self.tip_height = Some(block_height);
+        self.tracked.clear();
+    }
+
+    pub fn block_disconnected(&mut self, block_height: BlockHeight) {
+        self.tip_height = block_height.prev_height();
+    }
+
+    /// Stop tracking the transactions no longer in the mempool that were not confirmed, e.g.
+    /// evicted or replaced ones. They are not counted, as their removal says nothing about the
+    /// time they would have taken to confirm.
+    pub fn retain_tracked(&mut self, mut in_mempool: impl FnMut(&Id<Transaction>) -> bool) {
+        self.tracked.retain(|tx_id, _| in_mempool(tx_id));
+    }
+
+    /// Estimate the fee rate needed for a transaction to be confirmed within the given number of
+    /// blocks, clamped to `1..=MAX_CONFIRMATION_TARGET`. Returns `None` if there isn't enough
+    /// data.
+    ///
+    /// The buckets are grouped into ranges, starting from the highest fee rates, until each range
+    /// has enough transactions. The estimate is the upper bound of the lowest range of buckets
+    /// such that it and all the ranges above it had enough transactions confirmed in time. The
+    /// transactions still in the mempool for longer than the target count as not confirmed.
+    pub fn estimate_fee_rate(&self, target_blocks: usize) -> Option<FeeRate> {
+        let target_blocks = target_blocks.clamp(1, MAX_CONFIRMATION_TARGET);
+
+        let mut pending = vec![0; self.buckets.len()];
+        for tracked in self.tracked.values() {
+            if self.blocks_since(tracked.entry_height) >= target_blocks as u64 {
+                pending[tracked.bucket] += TX_WEIGHT;
+            }
+        }
+
+        let mut best_bucket = None;
+        let (mut confirmed, mut total) = (0, 0);
+        for (bucket, stats) in self.buckets.iter().enumerate().rev() {
+            confirmed += stats.confirmed_within[target_blocks - 1];
+            total += stats.total + pending[bucket];
+            if total >= MIN_TXS_IN_RANGE * TX_WEIGHT {
+                if confirmed * 100 < total * SUCCESS_PERCENT {
+                    break;
+                }
+                best_bucket = Some(bucket);
+                confirmed = 0;
+                total = 0;
+            }
+        }
+
+        best_bucket.map(|bucket| {
+            self.bucket_fee_rates
+                .get(bucket + 1)
+                .copied()
+                .unwrap_or(self.bucket_fee_rates[bucket])
+        })
+    }
+
+    /// Write the statistics into a file, the tracked transactions are not saved. Nothing is
+    /// written if no block was seen, as the statistics can't be tied to a height then.
+    ///
+    /// Like the mempool dump, the file consists of the format version followed by the
+    /// SCALE-encoded height of the tip, the time in seconds and the statistics.
+    pub fn write_stats(&self, path: &Path, now: Time) -> Result<(), DumpError> {
+        let tip_height = match self.tip_height {
+            Some(tip_height) => tip_height,
+            None => return Ok(()),
+        };
+        let mut data = FEE_ESTIMATES_FORMAT_VERSION.encode();
+        tip_height.encode_to(&mut data);
+        now.as_secs().encode_to(&mut data);
+        self.buckets.encode_to(&mut data);
+        let tmp_path = path.with_extension("new");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Restore the statistics written by [FeeEstimator::write_stats], returning whether the file
+    /// existed. The statistics written too long ago, or too many blocks below the current tip, are
+    /// rejected.
+    pub fn load_stats(
+        &mut self,
+        path: &Path,
+        now: Time,
+        tip_height: BlockHeight,
+    ) -> Result<bool, DumpError> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = &data[..];
+        let version = u32::decode(&mut data)?;
+        utils::ensure!(
+            version == FEE_ESTIMATES_FORMAT_VERSION,
+            DumpError::UnsupportedVersion(version)
+        );
+        let saved_height = BlockHeight::decode(&mut data)?;
+        let saved_at = Time::from_secs(u64::decode(&mut data)?);
+        let buckets = Vec::<BucketStats>::decode_all(&mut data)?;
+
+        let age = now.saturating_sub(saved_at);
+        let blocks_behind = u64::from(tip_height).checked_sub(u64::from(saved_height));
+        utils::ensure!(
+            age <= MAX_STATS_AGE
+                && blocks_behind.is_some_and(|blocks| blocks <= MAX_STATS_BLOCKS_BEHIND),
+            DumpError::OutdatedFeeEstimates {
+                saved_height,
+                age_secs: age.as_secs(),
+            }
+        );
+        utils::ensure!(
+            buckets.len() == self.buckets.len()
+                && buckets
+                    .iter()
+                    .all(|stats| stats.confirmed_within.len() == MAX_CONFIRMATION_TARGET),
+            DumpError::Decode("Unexpected number of fee estimation buckets".into())
+        );
+        self.buckets = buckets;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::primitives::H256;
+
+    fn fee_rate(atoms_per_kb: u128) -> FeeRate {
+        FeeRate::new(Amount::from_atoms(atoms_per_kb))
+    }
+
+    fn tx_id(n: u64) -> Id<Transaction> {
+        Id::new(H256::from_low_u64_be(n))
+    }
+
+    // Add `count` transactions with the given fee rate, confirmed after `blocks` blocks
+    fn confirm_txs(
+        estimator: &mut FeeEstimator,
+        first_id: u64,
+        count: u64,
+        rate: FeeRate,
+        blocks: u64,
+    ) {
+        let ids: Vec<_> = (first_id..first_id + count).map(tx_id).collect();
+        for id in &ids {
+            estimator.tx_added(*id, rate);
+        }
+        let tip = u64::from(estimator.tip_height.expect("tip known"));
+        for height in tip + 1..tip + blocks {
+            estimator.block_connected(BlockHeight::new(height), []);
+        }
+        estimator.block_connected(BlockHeight::new(tip + blocks), &ids);
+    }
+
+    #[test]
+    fn no_data() {
+        let mut estimator = FeeEstimator::new();
+        assert_eq!(estimator.estimate_fee_rate(1), None);
+
+        // Not tracked before the first block
+        estimator.tx_added(tx_id(1), fee_rate(5000));
+        assert!(estimator.tracked.is_empty());
+        estimator.block_connected(BlockHeight::new(1), &[tx_id(1)]);
+        assert_eq!(estimator.estimate_fee_rate(1), None);
+    }
+
+    #[test]
+    fn bucket_boundaries() {
+        let estimator = FeeEstimator::new();
+        assert_eq!(estimator.bucket_fee_rates[0], INCREMENTAL_RELAY_FEE_RATE);
+        assert!(estimator.bucket_fee_rates.windows(2).all(|rates| rates[0] < rates[1]));
+        assert_eq!(estimator.bucket_index(fee_rate(0)), 0);
+        assert_eq!(estimator.bucket_index(fee_rate(1099)), 0);
+        assert_eq!(estimator.bucket_index(fee_rate(1100)), 1);
+        assert_eq!(
+            estimator.bucket_index(fee_rate(u128::MAX)),
+            estimator.bucket_fee_rates.len() - 1
+        );
+    }
+
+    #[test]
+    fn estimates_by_target() {
+        let mut estimator = FeeEstimator::new();
+        estimator.block_connected(BlockHeight::new(1), []);
+
+        // High fee transactions are confirmed in the next block, low fee ones take 5 blocks
+        for round in 0..4 {
+            confirm_txs(&mut estimator, round * 100, 10, fee_rate(100_000), 1);
+            confirm_txs(&mut estimator, round * 100 + 50, 10, fee_rate(2_000), 5);
+        }
+
+        let high = estimator.estimate_fee_rate(1).expect("estimate");
+        assert!(high > fee_rate(100_000) && high <= fee_rate(110_000));
+        let low = estimator.estimate_fee_rate(5).expect("estimate");
+        assert!(low > fee_rate(2_000) && low <= fee_rate(2_200));
+        assert_eq!(
+            estimator.estimate_fee_rate(MAX_CONFIRMATION_TARGET),
+            Some(low)
+        );
+        assert_eq!(estimator.estimate_fee_rate(0), Some(high));
+    }
+
+    #[test]
+    fn pending_txs_count_as_failures() {
+        let mut estimator = FeeEstimator::new();
+        estimator.block_connected(BlockHeight::new(1), []);
+        confirm_txs(&mut estimator, 0, 10, fee_rate(2_000), 1);
+        assert!(estimator.estimate_fee_rate(1).is_some());
+
+        // More transactions with the same fee rate are stuck in the mempool
+        for id in 100..110 {
+            estimator.tx_added(tx_id(id), fee_rate(2_000));
+        }
+        estimator.block_connected(BlockHeight::new(3), []);
+        assert_eq!(estimator.estimate_fee_rate(1), None);
+
+        // They are forgotten when evicted
+        estimator.retain_tracked(|_| false);
+        assert!(estimator.estimate_fee_rate(1).is_some());
+    }
+
+    #[test]
+    fn unconfirmed_txs_recorded() {
+        let mut estimator = FeeEstimator::new();
+        estimator.block_connected(BlockHeight::new(1), []);
+        estimator.tx_added(tx_id(1), fee_rate(2_000));
+        for height in 2..MAX_CONFIRMATION_TARGET as u64 + 3 {
+            estimator.block_connected(BlockHeight::new(height), []);
+        }
+        assert!(estimator.tracked.is_empty());
+        let stats = &estimator.buckets[estimator.bucket_index(fee_rate(2_000))];
+        assert!(stats.total > 0);
+        assert!(stats.confirmed_within.iter().all(|weight| *weight == 0));
+    }
+
+    #[test]
+    fn skipped_blocks_not_recorded() {
+        let mut estimator = FeeEstimator::new();
+        estimator.block_connected(BlockHeight::new(1), []);
+        confirm_txs(&mut estimator, 0, 10, fee_rate(2_000), 1);
+        let buckets = estimator.buckets.clone();
+
+        // The statistics don't change and the tracked transactions are forgotten
+        estimator.tx_added(tx_id(100), fee_rate(2_000));
+        estimator.block_skipped(BlockHeight::new(3));
+        assert_eq!(estimator.tip_height, Some(BlockHeight::new(3)));
+        assert!(estimator.tracked.is_empty());
+        estimator.block_connected(BlockHeight::new(4), &[tx_id(100)]);
+        let bucket = estimator.bucket_index(fee_rate(2_000));
+        assert_eq!(
+            estimator.buckets[bucket].total,
+            buckets[bucket].total * DECAY_NUMERATOR / DECAY_DENOMINATOR
+        );
+    }
+
+    #[test]
+    fn stats_persisted() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(crate::FEE_ESTIMATES_FILE);
+
+        let now = Time::from_secs(1_000_000);
+        let height = BlockHeight::new(3);
+
+        // Nothing is written before the first block
+        let mut estimator = FeeEstimator::new();
+        estimator.write_stats(&path, now).unwrap();
+        assert!(!estimator.load_stats(&path, now, height).unwrap());
+        estimator.block_connected(BlockHeight::new(1), []);
+        confirm_txs(&mut estimator, 0, 10, fee_rate(50_000), 2);
+        estimator.write_stats(&path, now).unwrap();
+
+        let mut restored = FeeEstimator::new();
+        assert!(restored.load_stats(&path, now, height).unwrap());
+        assert_eq!(restored.buckets, estimator.buckets);
+        assert_eq!(
+            restored.estimate_fee_rate(2),
+            estimator.estimate_fee_rate(2)
+        );
+
+        std::fs::write(&path, 1u32.encode()).unwrap();
+        assert!(matches!(
+            restored.load_stats(&path, now, height),
+            Err(DumpError::UnsupportedVersion(1))
+        ));
+    }
+
+    #[test]
+    fn outdated_stats_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(crate::FEE_ESTIMATES_FILE);
+        let now = Time::from_secs(1_000_000);
+        let height = BlockHeight::new(3);
+
+        let mut estimator = FeeEstimator::new();
+        estimator.block_connected(BlockHeight::new(1), []);
+        confirm_txs(&mut estimator, 0, 10, fee_rate(50_000), 2);
+        estimator.write_stats(&path, now).unwrap();
+
+        let mut restored = FeeEstimator::new();
+        assert!(restored.load_stats(&path, now + MAX_STATS_AGE, height).unwrap());
+        let blocks_behind = BlockHeight::new(3 + MAX_STATS_BLOCKS_BEHIND);
+        assert!(restored.load_stats(&path, now, blocks_behind).unwrap());
+
+        for (load_time, tip_height) in [
+            (now + MAX_STATS_AGE + Time::from_secs(1), height),
+            (now, blocks_behind.next_height()),
+            (now, BlockHeight::new(2)),
+        ] {
+            let mut restored = FeeEstimator::new();
+            assert!(matches!(
+                restored.load_stats(&path, load_time, tip_height),
+                Err(DumpError::OutdatedFeeEstimates { .. })
+            ));
+            assert_eq!(restored.buckets, FeeEstimator::new().buckets);
+        }
+    }
+}
@@ -17,21 +17,28 @@ use parking_lot::RwLock;
 use std::{collections::BTreeSet, num::NonZeroUsize, sync::Arc, time::Duration};
 
 use chainstate::chainstate_interface::ChainstateInterface;
+use chainstate::{ChainstateError, PropertyQueryError};
 use common::{
-    chain::{Block, ChainConfig, SignedTransaction, Transaction},
+    chain::{
+        signature::verify_signature, Block, ChainConfig, GenBlock, SignedTransaction, Transaction,
+        TxOutput,
+    },
     primitives::{amount::Amount, BlockHeight, Id, Idable},
     time_getter::TimeGetter,
 };
 use logging::log;
 use serialization::Encode;
 use utils::{ensure, eventhandler::EventsController};
+use utxo::UtxoSource;
 
 use crate::{
     error::{Error, TxValidationError},
     get_memory_usage::GetMemoryUsage,
+    info::{MempoolInfo, TxEntryInfo},
     tx_accumulator::TransactionAccumulator,
     MempoolEvent,
 };
+use fee_estimator::FeeEstimator;
 use feerate::{FeeRate, INCREMENTAL_RELAY_FEE_RATE, INCREMENTAL_RELAY_THRESHOLD};
 use rolling_fee_rate::RollingFeeRate;
 use spends_unconfirmed::SpendsUnconfirmed;
@@ -41,7 +48,9 @@ use tx_with_fee::TxWithFee;
 
 use crate::config::*;
 
+mod fee_estimator;
 mod feerate;
+mod persist;
 mod rolling_fee_rate;
 mod spends_unconfirmed;
 mod store;
@@ -64,6 +73,10 @@ pub struct Mempool<M> {
     clock: TimeGetter,
     memory_usage_estimator: M,
     events_controller: EventsController<MempoolEvent>,
+    /// Blocks disconnected from the main chain, whose transactions are added back to the
+    /// mempool once the new tip is known
+    disconnected_blocks: Vec<Arc<Block>>,
+    fee_estimator: FeeEstimator,
 }
 
 impl<M> std::fmt::Debug for Mempool<M>
@@ -105,6 +118,8 @@ where
             clock,
             memory_usage_estimator,
             events_controller: Default::default(),
+            disconnected_blocks: Vec::new(),
+            fee_estimator: FeeEstimator::new(),
         }
     }
 
@@ -187,6 +202,7 @@ where
     async fn create_entry(
         &self,
         tx: SignedTransaction,
+        creation_time: Time,
     ) -> Result<TxMempoolEntry, TxValidationError> {
         // Genesis transaction has no parent, hence the first filter_map
         let parents = tx
@@ -205,8 +221,7 @@ where
             .collect();
 
         let fee = self.try_get_fee(&tx).await?;
-        let time = self.clock.get_time();
-        TxMempoolEntry::new(tx, fee, parents, ancestors, time)
+        TxMempoolEntry::new(tx, fee, parents, ancestors, creation_time)
     }
 }
 
@@ -288,6 +303,10 @@ where
 
         self.verify_inputs_available(tx.tx()).await?;
 
+        self.verify_signatures(tx.tx()).await?;
+
+        self.verify_timelocks(tx.tx()).await?;
+
         self.pays_minimum_relay_fees(&tx)?;
 
         self.pays_minimum_mempool_fee(&tx)?;
@@ -322,6 +341,126 @@ where
             )
     }
 
+    /// The outputs spent by the transaction that are created by the transactions in the mempool
+    fn unconfirmed_outputs(&self, tx: &SignedTransaction) -> Vec<Option<TxOutput>> {
+        tx.transaction()
+            .inputs()
+            .iter()
+            .map(|input| {
+                let outpoint = input.outpoint();
+                let entry = self.store.get_entry(outpoint.tx_id().get_tx_id()?)?;
+                entry
+                    .tx()
+                    .transaction()
+                    .outputs()
+                    .get(outpoint.output_index() as usize)
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Check the signatures of the inputs against the destinations of the spent outputs, so that
+    /// a transaction spending the outputs of someone else is neither relayed nor replaces the
+    /// transactions it conflicts with
+    async fn verify_signatures(&self, tx: &SignedTransaction) -> Result<(), TxValidationError> {
+        let unconfirmed_outputs = self.unconfirmed_outputs(tx);
+        let tx_clone = tx.clone();
+        let spent_outputs = self
+            .chainstate_handle
+            .call(move |this| {
+                let inputs = tx_clone.transaction().inputs().iter().zip(unconfirmed_outputs);
+                inputs
+                    .map(|(input, unconfirmed_output)| match unconfirmed_output {
+                        Some(output) => Ok(Some(output)),
+                        None => Ok(this.utxo(input.outpoint())?.map(|utxo| utxo.output().clone())),
+                    })
+                    .collect::<Result<Vec<_>, ChainstateError>>()
+            })
+            .await??;
+
+        for (input_num, (input, output)) in
+            tx.transaction().inputs().iter().zip(spent_outputs).enumerate()
+        {
+            // Missing inputs are reported by `verify_inputs_available`
+            let output = match output {
+                Some(output) => output,
+                None => continue,
+            };
+            let destination = output.purpose().destination().ok_or_else(|| {
+                TxValidationError::SpendsBurnedOutput {
+                    outpoint: input.outpoint().clone(),
+                    spending_tx_id: tx.transaction().get_id(),
+                }
+            })?;
+            verify_signature(destination, tx, input_num)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the outputs spent by the transaction are no longer timelocked in the next
+    /// block. Block rewards are timelocked too, so this also checks their maturity.
+    async fn verify_timelocks(&self, tx: &SignedTransaction) -> Result<(), TxValidationError> {
+        // The outputs of the transactions in the mempool can at best be confirmed in the same
+        // block as the spending transaction
+        let unconfirmed_outputs = self.unconfirmed_outputs(tx);
+        let tx_clone = tx.clone();
+
+        self.chainstate_handle
+            .call(move |this| {
+                let best_block = this.get_best_block_index()?;
+                let spend_height = best_block.block_height().next_height();
+                let spending_time = this.calculate_median_time_past(&best_block.block_id())?;
+
+                let inputs = tx_clone.transaction().inputs().iter().zip(unconfirmed_outputs);
+                for (input, unconfirmed_output) in inputs {
+                    let (output, source_height, source_time) = match this.utxo(input.outpoint())? {
+                        Some(utxo) => match utxo.source() {
+                            UtxoSource::Blockchain(height) => {
+                                let block_id = this
+                                    .get_block_id_from_height(height)?
+                                    .ok_or(PropertyQueryError::BlockForHeightNotFound(*height))
+                                    .map_err(ChainstateError::from)?;
+                                let block_index = this
+                                    .get_gen_block_index(&block_id)?
+                                    .ok_or(PropertyQueryError::BlockIndexAtHeightNotFound(*height))
+                                    .map_err(ChainstateError::from)?;
+                                (
+                                    utxo.output().clone(),
+                                    *height,
+                                    block_index.block_timestamp(),
+                                )
+                            }
+                            UtxoSource::Mempool => {
+                                (utxo.output().clone(), spend_height, spending_time)
+                            }
+                        },
+                        None => match unconfirmed_output {
+                            Some(output) => (output, spend_height, spending_time),
+                            // Missing inputs are reported by `verify_inputs_available`
+                            None => continue,
+                        },
+                    };
+
+                    ensure!(
+                        chainstate::check_timelock(
+                            &source_height,
+                            &source_time,
+                            &output,
+                            &spend_height,
+                            &spending_time
+                        )
+                        .is_ok(),
+                        TxValidationError::SpendsTimelockedOutput {
+                            outpoint: input.outpoint().clone(),
+                            spending_tx_id: tx_clone.transaction().get_id(),
+                        }
+                    );
+                }
+                Ok(())
+            })
+            .await?
+    }
+
     fn pays_minimum_mempool_fee(&self, tx: &TxWithFee) -> Result<(), TxValidationError> {
         let tx_fee = tx.fee();
         let minimum_fee = self.get_update_minimum_mempool_fee(tx.tx())?;
@@ -533,8 +672,12 @@ impl<M> Mempool<M>
 where
     M: GetMemoryUsage + Send + Sync,
 {
-    async fn finalize_tx(&mut self, tx: SignedTransaction) -> Result<(), Error> {
-        let entry = self.create_entry(tx).await?;
+    async fn finalize_tx(
+        &mut self,
+        tx: SignedTransaction,
+        creation_time: Time,
+    ) -> Result<(), Error> {
+        let entry = self.create_entry(tx, creation_time).await?;
         let id = entry.tx_id();
         self.store.add_tx(entry)?;
         self.remove_expired_transactions();
@@ -630,9 +773,88 @@ where
     M: GetMemoryUsage + Send + Sync,
 {
     pub async fn add_transaction(&mut self, tx: SignedTransaction) -> Result<(), Error> {
+        let tx_id = tx.transaction().get_id();
+        let creation_time = self.clock.get_time();
+        let track_fee_rate = !self.is_initial_block_download().await?;
+        self.add_transaction_created_at(tx, creation_time).await?;
+
+        // Only the transactions seen for the first time say how long it takes to confirm them
+        if track_fee_rate {
+            self.track_fee_rate(tx_id)?;
+        }
+        Ok(())
+    }
+
+    /// The fee estimator ignores the initial block download, the blocks are not mined in real
+    /// time then
+    async fn is_initial_block_download(&self) -> Result<bool, TxValidationError> {
+        let ibd = self.chainstate_handle.call(|this| this.is_initial_block_download()).await??;
+        Ok(ibd)
+    }
+
+    async fn best_block_id(&self) -> Result<Id<GenBlock>, TxValidationError> {
+        let best_block_id = self.chainstate_handle.call(|this| this.get_best_block_id()).await??;
+        Ok(best_block_id)
+    }
+
+    /// Whether the block is the tip of the chainstate, a failure to tell is logged and treated
+    /// as it isn't
+    async fn is_best_block(&self, block_id: Id<GenBlock>) -> bool {
+        match self.best_block_id().await {
+            Ok(best_block_id) => best_block_id == block_id,
+            Err(e) => {
+                log::error!("mempool: Failed to read the best block: {e}");
+                false
+            }
+        }
+    }
+
+    /// Whether the fee estimator records what happens to the transactions now, a failure to tell
+    /// is logged and treated as the initial block download
+    async fn tracks_fee_rates(&self) -> bool {
+        match self.is_initial_block_download().await {
+            Ok(ibd) => !ibd,
+            Err(e) => {
+                log::error!("mempool: Failed to check the initial block download: {e}");
+                false
+            }
+        }
+    }
+
+    /// Start tracking the confirmation of a mempool entry by the fee estimator, at the fee rate
+    /// the transaction pays
+    fn track_fee_rate(&mut self, tx_id: Id<Transaction>) -> Result<(), TxValidationError> {
+        let entry = self.store.get_entry(&tx_id).expect("added entry");
+        let fee_rate = FeeRate::from_total_tx_fee(
+            entry.fee(),
+            NonZeroUsize::new(entry.size()).expect("transaction cannot have zero size"),
+        )?;
+        self.fee_estimator.tx_added(tx_id, fee_rate);
+        Ok(())
+    }
+
+    /// Add a transaction that entered the mempool at the given time, e.g. one restored from a
+    /// mempool dump
+    async fn add_transaction_created_at(
+        &mut self,
+        tx: SignedTransaction,
+        creation_time: Time,
+    ) -> Result<(), Error> {
+        let tx_id = tx.transaction().get_id();
+        self.insert_transaction(tx, creation_time).await?;
+        self.events_controller.broadcast(MempoolEvent::TransactionAdded(tx_id));
+        Ok(())
+    }
+
+    /// Validate and add a transaction without announcing it
+    async fn insert_transaction(
+        &mut self,
+        tx: SignedTransaction,
+        creation_time: Time,
+    ) -> Result<(), Error> {
         let conflicts = self.validate_transaction(&tx).await?;
         self.store.drop_conflicts(conflicts);
-        self.finalize_tx(tx).await?;
+        self.finalize_tx(tx, creation_time).await?;
         self.store.assert_valid();
         Ok(())
     }
@@ -680,29 +902,163 @@ where
         self.store.txs_by_id.contains_key(tx_id)
     }
 
+    pub fn get_transaction(&self, tx_id: &Id<Transaction>) -> Option<SignedTransaction> {
+        self.store.get_entry(tx_id).map(|entry| entry.tx().clone())
+    }
+
+    pub fn get_all_transaction_ids(&self) -> Vec<Id<Transaction>> {
+        self.store.txs_by_id.keys().copied().collect()
+    }
+
+    pub fn get_entry_info(&self, tx_id: &Id<Transaction>) -> Option<TxEntryInfo> {
+        self.store.get_entry(tx_id).map(|entry| entry.info())
+    }
+
+    pub fn info(&self) -> MempoolInfo {
+        MempoolInfo {
+            tx_count: self.store.txs_by_id.len(),
+            total_size: self.store.txs_by_id.values().map(|entry| entry.size()).sum(),
+            memory_usage: self.get_memory_usage(),
+            max_size: self.max_size,
+            min_fee_rate_per_kb: Amount::from_atoms(self.get_update_min_fee_rate().atoms_per_kb()),
+        }
+    }
+
+    /// Estimate the fee rate per kB needed for a transaction to be confirmed within the given
+    /// number of blocks. The estimate is never below the minimum fee rate the mempool accepts.
+    pub fn estimate_fee_rate(&self, in_blocks: usize) -> Option<Amount> {
+        self.fee_estimator.estimate_fee_rate(in_blocks).map(|fee_rate| {
+            let fee_rate = std::cmp::max(fee_rate, self.get_update_min_fee_rate());
+            Amount::from_atoms(fee_rate.atoms_per_kb())
+        })
+    }
+
     pub fn subscribe_to_events(&mut self, handler: Arc<dyn Fn(MempoolEvent) + Send + Sync>) {
         self.events_controller.subscribe_to_events(handler)
     }
 
-    pub fn process_chainstate_event(&mut self, evt: chainstate::ChainstateEvent) {
-        log::info!("mempool: Processing chainstate event {evt:?}");
+    pub async fn process_chainstate_event(&mut self, evt: chainstate::ChainstateEvent) {
         match evt {
             chainstate::ChainstateEvent::NewTip(block_id, block_height) => {
-                self.new_tip_set(block_id, block_height);
+                log::info!("mempool: Processing new tip {block_id} at height {block_height}");
+                self.new_tip_set(block_id, block_height).await;
+            }
+            chainstate::ChainstateEvent::BlockConnected(block, block_height) => {
+                log::debug!(
+                    "mempool: Processing connected block {} at height {block_height}",
+                    block.get_id()
+                );
+                if !self.disconnected_blocks.is_empty() {
+                    self.reorg_done().await;
+                }
+                let track_fee_rates = self.tracks_fee_rates().await;
+                self.block_connected(&block, block_height, track_fee_rates);
+            }
+            chainstate::ChainstateEvent::BlockDisconnected(block, block_height) => {
+                log::debug!(
+                    "mempool: Processing disconnected block {} at height {block_height}",
+                    block.get_id()
+                );
+                self.fee_estimator.block_disconnected(block_height);
+                let prev_block_id = block.prev_block_id();
+                self.disconnected_blocks.push(block);
+                // No other event follows if the tip has moved back to the parent of the block,
+                // e.g. when the only block on top of genesis is invalidated
+                if self.is_best_block(prev_block_id).await {
+                    self.reorg_done().await;
+                }
+            }
+        }
+    }
+
+    /// Remove the transactions confirmed by the block, and the transactions that conflict with
+    /// them, together with their descendants. The confirmations are recorded by the fee
+    /// estimator if `track_fee_rates` is set.
+    fn block_connected(&mut self, block: &Block, block_height: BlockHeight, track_fee_rates: bool) {
+        if track_fee_rates {
+            let tx_ids: Vec<_> =
+                block.transactions().iter().map(|tx| tx.transaction().get_id()).collect();
+            self.fee_estimator.block_connected(block_height, &tx_ids);
+        } else {
+            self.fee_estimator.block_skipped(block_height);
+        }
+
+        for tx in block.transactions() {
+            let tx_id = tx.transaction().get_id();
+            self.store.remove_tx(&tx_id, MempoolRemovalReason::Block);
+            for input in tx.transaction().inputs() {
+                if let Some(conflict) = self.store.find_conflicting_tx(input.outpoint()) {
+                    log::debug!("Dropping tx {conflict} which conflicts with confirmed tx {tx_id}");
+                    self.store.drop_tx_and_descendants(conflict, MempoolRemovalReason::Block);
+                }
             }
         }
+        self.store.assert_valid();
+        self.fee_estimator
+            .retain_tracked(|tx_id| self.store.txs_by_id.contains_key(tx_id));
     }
 
-    pub fn new_tip_set(&mut self, block_id: Id<Block>, block_height: BlockHeight) {
+    pub async fn new_tip_set(&mut self, block_id: Id<Block>, block_height: BlockHeight) {
+        if !self.disconnected_blocks.is_empty() {
+            self.reorg_done().await;
+        }
+
         log::info!(
             "new tip with block_id {:?} and block_height {:?}",
             block_id,
             block_height
         );
-        // TODO(Roy) handle the new tip
         let mut rolling_fee_rate = self.rolling_fee_rate.write();
         (*rolling_fee_rate).set_block_since_last_rolling_fee_bump(true);
     }
+
+    /// Add the transactions of the disconnected blocks back and validate the existing entries
+    /// again. The inputs of the entries may be gone, and the timelocks, including the maturity
+    /// of block rewards, are checked against the new tip.
+    ///
+    /// The transactions of the disconnected blocks are not announced again, the peers have seen
+    /// them already. The fee estimator tracks them again at the fee rate they pay.
+    async fn reorg_done(&mut self) {
+        let disconnected_blocks = std::mem::take(&mut self.disconnected_blocks);
+        let entries = self.entries_parents_first().into_iter().cloned().collect::<Vec<_>>();
+        self.store = MempoolStore::new();
+        let track_fee_rates = self.tracks_fee_rates().await;
+
+        // The blocks were disconnected from the tip down
+        for block in disconnected_blocks.iter().rev() {
+            for tx in block.transactions() {
+                let tx_id = tx.transaction().get_id();
+                let creation_time = self.clock.get_time();
+                let result = match self.insert_transaction(tx.clone(), creation_time).await {
+                    Ok(()) if track_fee_rates => self.track_fee_rate(tx_id).map_err(Error::from),
+                    result => result,
+                };
+                if let Err(e) = result {
+                    log::debug!(
+                        "Transaction {tx_id} from a disconnected block not added back: {e}"
+                    );
+                }
+            }
+        }
+
+        for entry in entries {
+            let tx_id = entry.tx_id();
+            if let Err(e) = self.insert_transaction(entry.tx().clone(), entry.creation_time()).await
+            {
+                log::debug!("Transaction {tx_id} removed from the mempool after a reorg: {e}");
+            }
+        }
+        self.fee_estimator
+            .retain_tracked(|tx_id| self.store.txs_by_id.contains_key(tx_id));
+    }
+
+    /// The mempool entries ordered so that every entry comes after its parents
+    fn entries_parents_first(&self) -> Vec<&TxMempoolEntry> {
+        let mut entries: Vec<_> = self.store.txs_by_id.values().collect();
+        // An entry always has more ancestors than any of its parents
+        entries.sort_by_key(|entry| entry.count_with_ancestors());
+        entries
+    }
 }
 
 fn has_duplicate_entry<T>(iter: T) -> bool
@@ -0,0 +1,121 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Saving the mempool entries and the fee estimation statistics to files on shutdown and
+//! restoring them on start-up
+
+use std::path::Path;
+
+use common::{
+    chain::SignedTransaction,
+    primitives::{amount::Amount, Idable},
+};
+use logging::log;
+use serialization::{Decode, DecodeAll, Encode};
+
+use super::Mempool;
+use crate::{config::Time, error::DumpError, get_memory_usage::GetMemoryUsage};
+
+/// Version of the mempool dump format, to be bumped on incompatible changes
+const DUMP_FORMAT_VERSION: u32 = 1;
+
+/// A mempool entry as stored in the dump
+#[derive(Encode, Decode)]
+struct DumpedEntry {
+    transaction: SignedTransaction,
+    /// The time the transaction entered the mempool, in seconds
+    creation_time: u64,
+    fee: Amount,
+}
+
+impl<M> Mempool<M>
+where
+    M: GetMemoryUsage + Send + Sync,
+{
+    /// Write the mempool entries into a file, returning the number of entries written.
+    ///
+    /// The dump consists of the format version followed by the SCALE-encoded entries, ordered so
+    /// that parents come before their children. The dump is written next to the target file and
+    /// then renamed, so an interrupted write doesn't leave a truncated dump behind.
+    pub fn write_dump(&self, path: &Path) -> Result<usize, DumpError> {
+        let dump: Vec<DumpedEntry> = self
+            .entries_parents_first()
+            .into_iter()
+            .map(|entry| DumpedEntry {
+                transaction: entry.tx().clone(),
+                creation_time: entry.creation_time().as_secs(),
+                fee: entry.fee(),
+            })
+            .collect();
+
+        let mut data = DUMP_FORMAT_VERSION.encode();
+        dump.encode_to(&mut data);
+        let tmp_path = path.with_extension("new");
+        std::fs::write(&tmp_path, data)?;
+        std::fs::rename(&tmp_path, path)?;
+        Ok(dump.len())
+    }
+
+    /// Restore the entries written by [Mempool::write_dump], returning the number of entries
+    /// added back to the mempool.
+    ///
+    /// The transactions go through the full validation again and keep their original entry time.
+    /// The entries older than the mempool expiry time and the ones that are no longer valid are
+    /// skipped. A missing dump file is not an error.
+    pub async fn load_dump(&mut self, path: &Path) -> Result<usize, DumpError> {
+        let data = match std::fs::read(path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let mut data = &data[..];
+        let version = u32::decode(&mut data)?;
+        utils::ensure!(
+            version == DUMP_FORMAT_VERSION,
+            DumpError::UnsupportedVersion(version)
+        );
+        let dump = Vec::<DumpedEntry>::decode_all(&mut data)?;
+
+        let now = self.clock.get_time();
+        let mut restored = 0;
+        for entry in dump {
+            let tx_id = entry.transaction.transaction().get_id();
+            let creation_time = Time::from_secs(entry.creation_time);
+            if now.saturating_sub(creation_time) > self.max_tx_age {
+                log::debug!("Skipping expired transaction {tx_id} from the mempool dump");
+                continue;
+            }
+            match self.add_transaction_created_at(entry.transaction, creation_time).await {
+                Ok(()) => restored += 1,
+                Err(e) => log::debug!(
+                    "Transaction {tx_id} with fee {:?} from the mempool dump rejected: {e}",
+                    entry.fee
+                ),
+            }
+        }
+        Ok(restored)
+    }
+
+    pub fn write_fee_estimates(&self, path: &Path) -> Result<(), DumpError> {
+        self.fee_estimator.write_stats(path, self.clock.get_time())
+    }
+
+    /// Restore the fee estimation statistics, returning whether the file existed. The statistics
+    /// saved too long ago or too far below the current tip are rejected.
+    pub async fn load_fee_estimates(&mut self, path: &Path) -> Result<bool, DumpError> {
+        let tip_height = self.chainstate_handle.call(|this| this.get_best_block_height()).await??;
+        self.fee_estimator.load_stats(path, self.clock.get_time(), tip_height)
+    }
+}
@@ -27,7 +27,10 @@ use serialization::Encode;
 use utils::newtype;
 
 use super::Time;
-use crate::error::{Error, TxValidationError};
+use crate::{
+    error::{Error, TxValidationError},
+    info::TxEntryInfo,
+};
 
 newtype! {
     #[derive(Debug)]
@@ -492,6 +495,10 @@ impl TxMempoolEntry {
         self.fee
     }
 
+    pub fn count_with_ancestors(&self) -> usize {
+        self.count_with_ancestors
+    }
+
     pub fn count_with_descendants(&self) -> usize {
         self.count_with_descendants
     }
@@ -549,6 +556,23 @@ impl TxMempoolEntry {
         self.creation_time
     }
 
+    pub fn info(&self) -> TxEntryInfo {
+        TxEntryInfo {
+            tx_id: self.tx_id(),
+            fee: self.fee,
+            size: self.size(),
+            creation_time: self.creation_time.as_secs(),
+            parents: self.parents.iter().copied().collect(),
+            children: self.children.iter().copied().collect(),
+            ancestor_count: self.count_with_ancestors,
+            ancestor_size: self.size_with_ancestors,
+            ancestor_fees: self.fees_with_ancestors,
+            descendant_count: self.count_with_descendants,
+            descendant_size: self.size_with_descendants,
+            descendant_fees: self.fees_with_descendants,
+        }
+    }
+
     fn unconfirmed_parents(&self) -> impl Iterator<Item = &Id<Transaction>> {
         self.parents.iter()
     }
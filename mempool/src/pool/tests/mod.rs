@@ -29,13 +29,17 @@ use common::{
     chain::{
         block::{timestamp::BlockTimestamp, Block, BlockReward, ConsensusData},
         config::ChainConfig,
-        signature::inputsig::InputWitness,
+        signature::{
+            inputsig::{InputWitness, StandardInputSignature},
+            sighashtype::SigHashType,
+        },
         tokens::OutputValue,
         transaction::{Destination, TxInput, TxOutput},
         OutPointSourceId, OutputPurpose, Transaction,
     },
     primitives::{Id, H256},
 };
+use crypto::key::{KeyKind, PrivateKey};
 use rstest::rstest;
 use std::sync::{
     atomic::{AtomicU64, Ordering},
@@ -44,6 +48,8 @@ use std::sync::{
 use test_utils::random::{make_seedable_rng, Seed};
 
 mod expiry;
+mod persistence;
+mod reorg;
 mod replacement;
 mod utils;
 
@@ -898,6 +904,78 @@ async fn spends_new_unconfirmed(#[case] seed: Seed) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn invalid_signature(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+
+    let (private_key, public_key) = PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+    let (wrong_private_key, wrong_public_key) =
+        PrivateKey::new_from_rng(&mut rng, KeyKind::RistrettoSchnorr);
+    let destination = Destination::PublicKey(public_key);
+
+    let parent = TransactionBuilder::new()
+        .add_input(
+            TxInput::new(OutPointSourceId::BlockReward(genesis.get_id().into()), 0),
+            empty_witness(&mut rng),
+        )
+        .add_output(TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(999_999_999_000)),
+            OutputPurpose::Transfer(destination.clone()),
+        ))
+        .build();
+    let outpoint_source_id = OutPointSourceId::Transaction(parent.transaction().get_id());
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+    mempool.add_transaction(parent).await?;
+
+    let child = Transaction::new(
+        0,
+        vec![TxInput::new(outpoint_source_id, 0)],
+        vec![TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(999_999_000_000)),
+            OutputPurpose::Transfer(anyonecanspend_address()),
+        )],
+        0,
+    )?;
+    let sign_with = |key: &PrivateKey, key_destination: Destination| {
+        StandardInputSignature::produce_signature_for_input(
+            key,
+            SigHashType::try_from(SigHashType::ALL).unwrap(),
+            key_destination,
+            &child,
+            0,
+        )
+        .map(InputWitness::Standard)
+    };
+
+    // Neither a missing nor a forged signature is accepted
+    let forged_witness = sign_with(&wrong_private_key, Destination::PublicKey(wrong_public_key))?;
+    for witness in [InputWitness::NoSignature(None), forged_witness] {
+        let tx = SignedTransaction::new(child.clone(), vec![witness])?;
+        assert!(matches!(
+            mempool.add_transaction(tx).await,
+            Err(Error::TxValidationError(
+                TxValidationError::SignatureVerificationFailed(_)
+            ))
+        ));
+    }
+    mempool.store.assert_valid();
+
+    let tx = SignedTransaction::new(
+        child.clone(),
+        vec![sign_with(&private_key, destination.clone())?],
+    )?;
+    let tx_id = tx.transaction().get_id();
+    mempool.add_transaction(tx).await?;
+    assert!(mempool.contains_transaction(&tx_id));
+    mempool.store.assert_valid();
+    Ok(())
+}
+
 #[rstest]
 #[trace]
 #[case(Seed::from_entropy())]
@@ -1074,7 +1152,7 @@ async fn rolling_fee(#[case] seed: Seed) -> anyhow::Result<()> {
         .chainstate_handle
         .call_mut(|this| this.process_block(block, BlockSource::Local))
         .await??;
-    mempool.new_tip_set(Id::new(H256::zero()), BlockHeight::new(1));
+    mempool.new_tip_set(Id::new(H256::zero()), BlockHeight::new(1)).await;
     // Because the rolling fee is only updated when we attempt to add a tx to the mempool
     // we need to submit a "dummy" tx to trigger these updates.
 
@@ -1690,3 +1768,89 @@ async fn collect_transactions(#[case] seed: Seed) -> anyhow::Result<()> {
     assert_eq!(collected_txs.len(), 0);
     Ok(())
 }
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn entry_info(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+
+    let parent = TransactionBuilder::new()
+        .add_input(
+            TxInput::new(OutPointSourceId::BlockReward(genesis.get_id().into()), 0),
+            empty_witness(&mut rng),
+        )
+        .add_output(TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(10_000)),
+            OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+        ))
+        .build();
+    let parent_id = parent.transaction().get_id();
+
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+    assert_eq!(mempool.info().tx_count, 0);
+    mempool.add_transaction(parent.clone()).await?;
+
+    let child_fee = Amount::from_atoms(get_relay_fee_from_tx_size(TX_SPEND_INPUT_SIZE));
+    let child = tx_spend_input(
+        &mempool,
+        TxInput::new(OutPointSourceId::Transaction(parent_id), 0),
+        InputWitness::NoSignature(Some(DUMMY_WITNESS_MSG.to_vec())),
+        child_fee,
+        0,
+        0,
+    )
+    .await?;
+    let child_id = child.transaction().get_id();
+    mempool.add_transaction(child.clone()).await?;
+
+    assert_eq!(mempool.get_transaction(&parent_id), Some(parent.clone()));
+    assert_eq!(mempool.get_transaction(&child_id), Some(child.clone()));
+    let mut all_ids = mempool.get_all_transaction_ids();
+    all_ids.sort();
+    let mut expected_ids = vec![parent_id, child_id];
+    expected_ids.sort();
+    assert_eq!(all_ids, expected_ids);
+
+    let parent_fee = mempool.store.get_entry(&parent_id).expect("parent").fee();
+    let total_fees = (parent_fee + child_fee).expect("fee overflow");
+    let total_size = parent.encoded_size() + child.encoded_size();
+
+    let info = mempool.info();
+    assert_eq!(info.tx_count, 2);
+    assert_eq!(info.total_size, total_size);
+
+    let parent_info = mempool.get_entry_info(&parent_id).expect("parent info");
+    assert_eq!(parent_info.fee, parent_fee);
+    assert_eq!(parent_info.size, parent.encoded_size());
+    assert!(parent_info.parents.is_empty());
+    assert_eq!(parent_info.children, vec![child_id]);
+    assert_eq!(parent_info.ancestor_count, 1);
+    assert_eq!(parent_info.ancestor_size, parent.encoded_size());
+    assert_eq!(parent_info.ancestor_fees, parent_fee);
+    assert_eq!(parent_info.descendant_count, 2);
+    assert_eq!(parent_info.descendant_size, total_size);
+    assert_eq!(parent_info.descendant_fees, total_fees);
+
+    let child_info = mempool.get_entry_info(&child_id).expect("child info");
+    assert_eq!(child_info.fee, child_fee);
+    assert_eq!(child_info.parents, vec![parent_id]);
+    assert!(child_info.children.is_empty());
+    assert_eq!(child_info.ancestor_count, 2);
+    assert_eq!(child_info.ancestor_size, total_size);
+    assert_eq!(child_info.ancestor_fees, total_fees);
+    assert_eq!(child_info.descendant_count, 1);
+    assert_eq!(child_info.descendant_size, child.encoded_size());
+    assert_eq!(child_info.descendant_fees, child_fee);
+
+    mempool.store.remove_tx(&child_id, MempoolRemovalReason::Block);
+    assert_eq!(mempool.get_entry_info(&child_id), None);
+    let parent_info = mempool.get_entry_info(&parent_id).expect("parent info");
+    assert!(parent_info.children.is_empty());
+    assert_eq!(parent_info.descendant_count, 1);
+    assert_eq!(mempool.info().tx_count, 1);
+    Ok(())
+}
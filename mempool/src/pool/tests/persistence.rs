@@ -0,0 +1,172 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common::chain::tokens::OutputValue;
+use test_utils::random::{CryptoRng, Rng};
+
+use super::*;
+use crate::{error::DumpError, SystemUsageEstimator};
+
+fn mock_clock(mock_time: &Arc<AtomicU64>) -> TimeGetter {
+    let mock_time = Arc::clone(mock_time);
+    TimeGetter::new(Arc::new(move || {
+        Duration::from_secs(mock_time.load(Ordering::SeqCst))
+    }))
+}
+
+// Make a mempool holding a parent transaction and its child, sharing the chainstate with the
+// mempools the dump is loaded into
+async fn mempool_with_parent_and_child(
+    rng: &mut (impl Rng + CryptoRng),
+    clock: TimeGetter,
+) -> anyhow::Result<(
+    Mempool<SystemUsageEstimator>,
+    Id<Transaction>,
+    Id<Transaction>,
+)> {
+    let tf = TestFramework::builder(rng).build();
+    let genesis = tf.genesis();
+    let parent = TransactionBuilder::new()
+        .add_input(
+            TxInput::new(OutPointSourceId::BlockReward(genesis.get_id().into()), 0),
+            empty_witness(rng),
+        )
+        .add_output(TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(10_000)),
+            OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+        ))
+        .build();
+    let parent_id = parent.transaction().get_id();
+
+    let chainstate = tf.chainstate();
+    let mut mempool = Mempool::new(
+        chainstate.get_chain_config(),
+        start_chainstate(chainstate).await,
+        clock,
+        SystemUsageEstimator {},
+    );
+    mempool.add_transaction(parent).await?;
+
+    let child = tx_spend_input(
+        &mempool,
+        TxInput::new(OutPointSourceId::Transaction(parent_id), 0),
+        InputWitness::NoSignature(Some(DUMMY_WITNESS_MSG.to_vec())),
+        None,
+        0,
+        0,
+    )
+    .await?;
+    let child_id = child.transaction().get_id();
+    mempool.add_transaction(child).await?;
+
+    Ok((mempool, parent_id, child_id))
+}
+
+fn empty_mempool_like(
+    mempool: &Mempool<SystemUsageEstimator>,
+    clock: TimeGetter,
+) -> Mempool<SystemUsageEstimator> {
+    Mempool::new(
+        Arc::clone(&mempool.chain_config),
+        mempool.chainstate_handle.clone(),
+        clock,
+        SystemUsageEstimator {},
+    )
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn dump_and_restore(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let mock_time = Arc::new(AtomicU64::new(1_000));
+    let (mempool, parent_id, child_id) =
+        mempool_with_parent_and_child(&mut rng, mock_clock(&mock_time)).await?;
+
+    let dir = tempfile::TempDir::new()?;
+    let dump_file = dir.path().join(crate::MEMPOOL_DUMP_FILE);
+    assert_eq!(mempool.write_dump(&dump_file)?, 2);
+
+    mock_time.store(2_000, Ordering::SeqCst);
+    let mut restored = empty_mempool_like(&mempool, mock_clock(&mock_time));
+    assert_eq!(restored.load_dump(&dump_file).await?, 2);
+
+    for tx_id in [parent_id, child_id] {
+        let entry = mempool.store.get_entry(&tx_id).expect("original entry");
+        let restored_entry = restored.store.get_entry(&tx_id).expect("restored entry");
+        assert_eq!(restored_entry.tx(), entry.tx());
+        assert_eq!(restored_entry.fee(), entry.fee());
+        assert_eq!(restored_entry.creation_time(), Duration::from_secs(1_000));
+    }
+    restored.store.assert_valid();
+    Ok(())
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn expired_entries_not_restored(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let mock_time = Arc::new(AtomicU64::new(0));
+    let (mempool, parent_id, child_id) =
+        mempool_with_parent_and_child(&mut rng, mock_clock(&mock_time)).await?;
+
+    let dir = tempfile::TempDir::new()?;
+    let dump_file = dir.path().join(crate::MEMPOOL_DUMP_FILE);
+    mempool.write_dump(&dump_file)?;
+
+    mock_time.store(DEFAULT_MEMPOOL_EXPIRY.as_secs() + 1, Ordering::SeqCst);
+    let mut restored = empty_mempool_like(&mempool, mock_clock(&mock_time));
+    assert_eq!(restored.load_dump(&dump_file).await?, 0);
+    assert!(!restored.contains_transaction(&parent_id));
+    assert!(!restored.contains_transaction(&child_id));
+
+    // Entries within the expiry time are restored
+    mock_time.store(DEFAULT_MEMPOOL_EXPIRY.as_secs(), Ordering::SeqCst);
+    let mut restored = empty_mempool_like(&mempool, mock_clock(&mock_time));
+    assert_eq!(restored.load_dump(&dump_file).await?, 2);
+    assert!(restored.contains_transaction(&parent_id));
+    assert!(restored.contains_transaction(&child_id));
+    Ok(())
+}
+
+#[tokio::test]
+async fn missing_or_invalid_dump() -> anyhow::Result<()> {
+    let mut mempool = setup().await;
+    let dir = tempfile::TempDir::new()?;
+    let dump_file = dir.path().join(crate::MEMPOOL_DUMP_FILE);
+
+    // No dump is fine, e.g. on the first start
+    assert_eq!(mempool.load_dump(&dump_file).await?, 0);
+
+    // The dump of an empty mempool
+    assert_eq!(mempool.write_dump(&dump_file)?, 0);
+    assert_eq!(mempool.load_dump(&dump_file).await?, 0);
+
+    std::fs::write(&dump_file, 2u32.encode())?;
+    assert!(matches!(
+        mempool.load_dump(&dump_file).await,
+        Err(DumpError::UnsupportedVersion(2))
+    ));
+
+    std::fs::write(&dump_file, [1, 0, 0, 0, 0xff])?;
+    assert!(matches!(
+        mempool.load_dump(&dump_file).await,
+        Err(DumpError::Decode(_))
+    ));
+    Ok(())
+}
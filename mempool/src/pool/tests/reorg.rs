@@ -0,0 +1,308 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use chainstate::ChainstateEvent;
+use common::chain::{timelock::OutputTimeLock, tokens::OutputValue, GenBlock};
+
+use super::*;
+use test_utils::random::Rng;
+
+fn make_block(
+    prev_block_id: Id<GenBlock>,
+    transactions: Vec<SignedTransaction>,
+    timestamp: BlockTimestamp,
+) -> anyhow::Result<Arc<Block>> {
+    let block = Block::new(
+        transactions,
+        prev_block_id,
+        timestamp,
+        ConsensusData::None,
+        BlockReward::new(vec![]),
+    )
+    .map_err(|_| anyhow::Error::msg("block creation error"))?;
+    Ok(Arc::new(block))
+}
+
+async fn process_block(
+    mempool: &Mempool<SystemUsageEstimator>,
+    block: &Arc<Block>,
+) -> anyhow::Result<()> {
+    let block = Block::clone(block);
+    mempool
+        .chainstate_handle
+        .call_mut(|this| this.process_block(block, BlockSource::Local))
+        .await??;
+    Ok(())
+}
+
+fn output(value: u128, purpose: OutputPurpose) -> TxOutput {
+    TxOutput::new(OutputValue::Coin(Amount::from_atoms(value)), purpose)
+}
+
+fn transfer() -> OutputPurpose {
+    OutputPurpose::Transfer(Destination::AnyoneCanSpend)
+}
+
+// A transaction spending the genesis output, with the given outputs
+fn spend_genesis(
+    tf: &TestFramework,
+    rng: &mut impl Rng,
+    outputs: Vec<TxOutput>,
+) -> SignedTransaction {
+    let genesis_id = tf.genesis().get_id();
+    outputs
+        .into_iter()
+        .fold(
+            TransactionBuilder::new().add_input(
+                TxInput::new(OutPointSourceId::BlockReward(genesis_id.into()), 0),
+                empty_witness(rng),
+            ),
+            |builder, output| builder.add_output(output),
+        )
+        .build()
+}
+
+async fn spend(
+    mempool: &Mempool<SystemUsageEstimator>,
+    tx_id: Id<Transaction>,
+    output_index: u32,
+    fee: impl Into<Option<Amount>>,
+) -> anyhow::Result<SignedTransaction> {
+    tx_spend_input(
+        mempool,
+        TxInput::new(OutPointSourceId::Transaction(tx_id), output_index),
+        InputWitness::NoSignature(Some(DUMMY_WITNESS_MSG.to_vec())),
+        fee,
+        0,
+        0,
+    )
+    .await
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn confirmed_transactions_removed(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+
+    let parent = spend_genesis(
+        &tf,
+        &mut rng,
+        vec![output(10_000, transfer()), output(10_000, transfer())],
+    );
+    let parent_id = parent.transaction().get_id();
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+    mempool.add_transaction(parent.clone()).await?;
+
+    let child_0 = spend(&mempool, parent_id, 0, None).await?;
+    let child_0_id = child_0.transaction().get_id();
+    mempool.add_transaction(child_0).await?;
+    let child_1 = spend(&mempool, parent_id, 1, None).await?;
+    let child_1_id = child_1.transaction().get_id();
+    mempool.add_transaction(child_1).await?;
+    let grandchild = spend(&mempool, child_1_id, 0, None).await?;
+    let grandchild_id = grandchild.transaction().get_id();
+    mempool.add_transaction(grandchild).await?;
+
+    // The block confirms the parent and a transaction that conflicts with the second child
+    let conflict = spend(&mempool, parent_id, 1, Amount::from_atoms(1_000)).await?;
+    let block = make_block(
+        genesis.get_id().into(),
+        vec![parent, conflict],
+        genesis.timestamp(),
+    )?;
+    process_block(&mempool, &block).await?;
+    mempool
+        .process_chainstate_event(ChainstateEvent::BlockConnected(
+            Arc::clone(&block),
+            BlockHeight::new(1),
+        ))
+        .await;
+
+    assert!(!mempool.contains_transaction(&parent_id));
+    assert!(mempool.contains_transaction(&child_0_id));
+    assert!(!mempool.contains_transaction(&child_1_id));
+    assert!(!mempool.contains_transaction(&grandchild_id));
+    let child_0_info = mempool.get_entry_info(&child_0_id).expect("child info");
+    assert!(child_0_info.parents.is_empty());
+    assert_eq!(child_0_info.ancestor_count, 1);
+    mempool.store.assert_valid();
+    Ok(())
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn disconnected_transactions_added_back(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+    let genesis_time = genesis.timestamp();
+
+    let parent = spend_genesis(&tf, &mut rng, vec![output(10_000, transfer())]);
+    let parent_id = parent.transaction().get_id();
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+
+    let a1 = make_block(genesis.get_id().into(), vec![parent], genesis_time)?;
+    process_block(&mempool, &a1).await?;
+    mempool
+        .process_chainstate_event(ChainstateEvent::BlockConnected(
+            Arc::clone(&a1),
+            BlockHeight::new(1),
+        ))
+        .await;
+    mempool
+        .process_chainstate_event(ChainstateEvent::NewTip(a1.get_id(), BlockHeight::new(1)))
+        .await;
+
+    let child = spend(&mempool, parent_id, 0, None).await?;
+    let child_id = child.transaction().get_id();
+    mempool.add_transaction(child).await?;
+    assert!(mempool.get_entry_info(&child_id).expect("child").parents.is_empty());
+
+    // A longer chain without the parent replaces the block
+    let later = genesis_time.add_int_seconds(1).expect("timestamp");
+    let b1 = make_block(genesis.get_id().into(), vec![], later)?;
+    let b2 = make_block(b1.get_id().into(), vec![], later)?;
+    process_block(&mempool, &b1).await?;
+    process_block(&mempool, &b2).await?;
+    for event in [
+        ChainstateEvent::BlockDisconnected(a1, BlockHeight::new(1)),
+        ChainstateEvent::BlockConnected(Arc::clone(&b1), BlockHeight::new(1)),
+        ChainstateEvent::BlockConnected(Arc::clone(&b2), BlockHeight::new(2)),
+        ChainstateEvent::NewTip(b2.get_id(), BlockHeight::new(2)),
+    ] {
+        mempool.process_chainstate_event(event).await;
+    }
+
+    assert!(mempool.contains_transaction(&parent_id));
+    let child_info = mempool.get_entry_info(&child_id).expect("child");
+    assert_eq!(child_info.parents, vec![parent_id]);
+    assert_eq!(child_info.ancestor_count, 2);
+    mempool.store.assert_valid();
+    Ok(())
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn timelocks_checked_again_after_reorg(#[case] seed: Seed) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+    let genesis_time = genesis.timestamp();
+
+    let locked = OutputPurpose::LockThenTransfer(
+        Destination::AnyoneCanSpend,
+        OutputTimeLock::ForBlockCount(2),
+    );
+    let parent = spend_genesis(
+        &tf,
+        &mut rng,
+        vec![output(10_000, locked), output(10_000, transfer())],
+    );
+    let parent_id = parent.transaction().get_id();
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+
+    let a1 = make_block(genesis.get_id().into(), vec![parent.clone()], genesis_time)?;
+    process_block(&mempool, &a1).await?;
+
+    // The output created at height 1 can be spent from height 3
+    let child = spend(&mempool, parent_id, 0, None).await?;
+    let child_id = child.transaction().get_id();
+    assert!(matches!(
+        mempool.add_transaction(child.clone()).await,
+        Err(Error::TxValidationError(
+            TxValidationError::SpendsTimelockedOutput { .. }
+        ))
+    ));
+
+    // Empty blocks have no undo data, so give the block a transaction to be able to disconnect it
+    let unlocked_spend = spend(&mempool, parent_id, 1, None).await?;
+    let a2 = make_block(a1.get_id().into(), vec![unlocked_spend], genesis_time)?;
+    process_block(&mempool, &a2).await?;
+    mempool.add_transaction(child).await?;
+
+    // In the new chain, the parent is confirmed at height 3, so the child can't be in the next
+    // block anymore
+    let later = genesis_time.add_int_seconds(1).expect("timestamp");
+    let b1 = make_block(genesis.get_id().into(), vec![], later)?;
+    let b2 = make_block(b1.get_id().into(), vec![], later)?;
+    let b3 = make_block(b2.get_id().into(), vec![parent], later)?;
+    for block in [&b1, &b2, &b3] {
+        process_block(&mempool, block).await?;
+    }
+    for event in [
+        ChainstateEvent::BlockDisconnected(a2, BlockHeight::new(2)),
+        ChainstateEvent::BlockDisconnected(a1, BlockHeight::new(1)),
+        ChainstateEvent::BlockConnected(Arc::clone(&b1), BlockHeight::new(1)),
+        ChainstateEvent::BlockConnected(Arc::clone(&b2), BlockHeight::new(2)),
+        ChainstateEvent::BlockConnected(Arc::clone(&b3), BlockHeight::new(3)),
+        ChainstateEvent::NewTip(b3.get_id(), BlockHeight::new(3)),
+    ] {
+        mempool.process_chainstate_event(event).await;
+    }
+
+    assert!(!mempool.contains_transaction(&parent_id));
+    assert!(!mempool.contains_transaction(&child_id));
+    mempool.store.assert_valid();
+    Ok(())
+}
+
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+#[tokio::test]
+async fn disconnected_transactions_added_back_without_new_tip(
+    #[case] seed: Seed,
+) -> anyhow::Result<()> {
+    let mut rng = make_seedable_rng(seed);
+    let tf = TestFramework::builder(&mut rng).build();
+    let genesis = tf.genesis();
+
+    let parent = spend_genesis(&tf, &mut rng, vec![output(10_000, transfer())]);
+    let parent_id = parent.transaction().get_id();
+    let mut mempool = setup_with_chainstate(tf.chainstate()).await;
+
+    let a1 = make_block(genesis.get_id().into(), vec![parent], genesis.timestamp())?;
+    process_block(&mempool, &a1).await?;
+    mempool
+        .process_chainstate_event(ChainstateEvent::BlockConnected(
+            Arc::clone(&a1),
+            BlockHeight::new(1),
+        ))
+        .await;
+    assert!(!mempool.contains_transaction(&parent_id));
+
+    // Invalidating the only block on top of genesis leaves no new tip to announce
+    let a1_id = a1.get_id();
+    mempool
+        .chainstate_handle
+        .call_mut(move |this| this.invalidate_block(&a1_id))
+        .await??;
+    mempool
+        .process_chainstate_event(ChainstateEvent::BlockDisconnected(a1, BlockHeight::new(1)))
+        .await;
+
+    assert!(mempool.contains_transaction(&parent_id));
+    assert!(mempool.disconnected_blocks.is_empty());
+    mempool.store.assert_valid();
+    Ok(())
+}
@@ -15,14 +15,218 @@
 
 //! Mempool subsystem RPC handler
 
+use common::{
+    chain::{SignedTransaction, Transaction},
+    primitives::{Amount, Id},
+};
+use serialization::{DecodeAll, Encode};
+use subsystem::subsystem::CallError;
+
+use crate::{
+    error::Error,
+    info::{MempoolInfo, TxEntryInfo},
+};
+
 #[rpc::rpc(server, namespace = "mempool")]
 trait MempoolRpc {
-    #[method(name = "dummy")]
-    fn dummy(&self) -> rpc::Result<String>;
+    /// Submit a hex-encoded signed transaction to the mempool
+    #[method(name = "submit_transaction")]
+    async fn submit_transaction(&self, tx_hex: String) -> rpc::Result<()>;
+
+    /// Check whether the mempool contains the transaction with the given id
+    #[method(name = "contains_transaction")]
+    async fn contains_transaction(&self, tx_id: Id<Transaction>) -> rpc::Result<bool>;
+
+    /// Returns the hex-encoded signed transaction with the given id, if it is in the mempool
+    #[method(name = "get_transaction")]
+    async fn get_transaction(&self, tx_id: Id<Transaction>) -> rpc::Result<Option<String>>;
+
+    /// Get the ids of all the transactions in the mempool
+    #[method(name = "get_all_transaction_ids")]
+    async fn get_all_transaction_ids(&self) -> rpc::Result<Vec<Id<Transaction>>>;
+
+    /// Get the number and size of the transactions in the mempool, its memory usage and the
+    /// minimum fee rate it currently accepts
+    #[method(name = "info")]
+    async fn info(&self) -> rpc::Result<MempoolInfo>;
+
+    /// Get the fee, size and the ancestor and descendant stats of a transaction in the mempool
+    #[method(name = "entry_info")]
+    async fn entry_info(&self, tx_id: Id<Transaction>) -> rpc::Result<Option<TxEntryInfo>>;
+
+    /// Estimate the fee rate per kB a transaction needs to pay to be confirmed within the given
+    /// number of blocks, up to 48. Returns null until enough transactions have been confirmed.
+    #[method(name = "estimate_fee_rate")]
+    async fn estimate_fee_rate(&self, in_blocks: usize) -> rpc::Result<Option<Amount>>;
 }
 
+#[async_trait::async_trait]
 impl MempoolRpcServer for super::MempoolHandle {
-    fn dummy(&self) -> rpc::Result<String> {
-        Ok("dummy".to_string())
+    async fn submit_transaction(&self, tx_hex: String) -> rpc::Result<()> {
+        let tx_data = hex::decode(tx_hex).map_err(rpc::Error::to_call_error)?;
+        let tx =
+            SignedTransaction::decode_all(&mut &tx_data[..]).map_err(rpc::Error::to_call_error)?;
+        handle_error(self.call_async_mut(|this| this.add_transaction(tx)).await)
+    }
+
+    async fn contains_transaction(&self, tx_id: Id<Transaction>) -> rpc::Result<bool> {
+        handle_error(
+            self.call_async(move |this| {
+                Box::pin(async move { this.contains_transaction(&tx_id).await })
+            })
+            .await,
+        )
+    }
+
+    async fn get_transaction(&self, tx_id: Id<Transaction>) -> rpc::Result<Option<String>> {
+        let tx = handle_error(
+            self.call_async(move |this| Box::pin(async move { this.transaction(&tx_id).await }))
+                .await,
+        )?;
+        Ok(tx.map(|tx| hex::encode(tx.encode())))
+    }
+
+    async fn get_all_transaction_ids(&self) -> rpc::Result<Vec<Id<Transaction>>> {
+        handle_error(self.call_async(|this| this.get_all_transaction_ids()).await)
+    }
+
+    async fn info(&self) -> rpc::Result<MempoolInfo> {
+        handle_error(self.call_async(|this| this.info()).await)
+    }
+
+    async fn entry_info(&self, tx_id: Id<Transaction>) -> rpc::Result<Option<TxEntryInfo>> {
+        handle_error(
+            self.call_async(move |this| Box::pin(async move { this.entry_info(&tx_id).await }))
+                .await,
+        )
+    }
+
+    async fn estimate_fee_rate(&self, in_blocks: usize) -> rpc::Result<Option<Amount>> {
+        handle_error(self.call_async(move |this| this.estimate_fee_rate(in_blocks)).await)
+    }
+}
+
+fn handle_error<T>(e: Result<Result<T, Error>, CallError>) -> rpc::Result<T> {
+    e.map_err(rpc::Error::to_call_error)?.map_err(rpc::Error::to_call_error)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::MempoolSubsystemInterface;
+    use chainstate::{make_chainstate, ChainstateConfig, DefaultTransactionVerificationStrategy};
+    use common::{
+        chain::{
+            signature::inputsig::InputWitness, tokens::OutputValue, Destination, OutputPurpose,
+            TxInput, TxOutput,
+        },
+        primitives::{Idable, H256},
+    };
+    use serde_json::Value;
+    use std::{future::Future, sync::Arc};
+
+    async fn with_mempool<F: 'static + Send + Future<Output = ()>>(
+        proc: impl 'static + Send + FnOnce(crate::MempoolHandle) -> F,
+    ) {
+        let storage = chainstate_storage::inmemory::Store::new_empty().unwrap();
+        let chain_config = Arc::new(common::chain::config::create_unit_test_config());
+        let mut man = subsystem::Manager::new("rpctest");
+        let chainstate = man.add_subsystem(
+            "chainstate",
+            make_chainstate(
+                Arc::clone(&chain_config),
+                ChainstateConfig::new(),
+                storage,
+                DefaultTransactionVerificationStrategy::new(),
+                None,
+                Default::default(),
+            )
+            .unwrap(),
+        );
+        let mempool = crate::make_mempool(
+            chain_config,
+            chainstate,
+            Default::default(),
+            crate::SystemUsageEstimator {},
+            None,
+            None,
+        );
+        let mempool = man.add_subsystem_with_custom_eventloop("mempool", move |call, shutdn| {
+            mempool.run(call, shutdn)
+        });
+        let _ = man.add_subsystem_with_custom_eventloop(
+            "test",
+            move |_: subsystem::subsystem::CallRequest<()>, _| proc(mempool),
+        );
+        man.main().await;
+    }
+
+    #[tokio::test]
+    async fn rpc_requests() {
+        with_mempool(|handle| async {
+            let rpc = handle.into_rpc();
+
+            let res: rpc::Result<MempoolInfo> = rpc.call("mempool_info", [(); 0]).await;
+            let info = res.unwrap();
+            assert_eq!(info.tx_count, 0);
+            assert_eq!(info.total_size, 0);
+            assert_eq!(info.min_fee_rate_per_kb, Amount::ZERO);
+
+            let res: rpc::Result<Vec<Id<Transaction>>> =
+                rpc.call("mempool_get_all_transaction_ids", [(); 0]).await;
+            assert_eq!(res.unwrap(), vec![]);
+
+            let tx_id = Id::<Transaction>::new(H256::zero());
+            let res: rpc::Result<Value> = rpc.call("mempool_contains_transaction", [tx_id]).await;
+            assert!(matches!(res, Ok(Value::Bool(false))));
+            let res: rpc::Result<Value> = rpc.call("mempool_get_transaction", [tx_id]).await;
+            assert!(matches!(res, Ok(Value::Null)));
+            let res: rpc::Result<Value> = rpc.call("mempool_entry_info", [tx_id]).await;
+            assert!(matches!(res, Ok(Value::Null)));
+            let res: rpc::Result<Value> = rpc.call("mempool_estimate_fee_rate", [1]).await;
+            assert!(matches!(res, Ok(Value::Null)));
+
+            let res: rpc::Result<Value> = rpc.call("mempool_submit_transaction", ["not hex"]).await;
+            assert!(res.is_err());
+
+            // A transaction spending the genesis output
+            let chain_config = common::chain::config::create_unit_test_config();
+            let genesis_value = chain_config.genesis_block().utxos()[0]
+                .value()
+                .coin_amount()
+                .expect("genesis pays coins");
+            let tx = Transaction::new(
+                0,
+                vec![TxInput::new(chain_config.genesis_block_id().into(), 0)],
+                vec![TxOutput::new(
+                    OutputValue::Coin((genesis_value - Amount::from_atoms(10_000)).unwrap()),
+                    OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+                )],
+                0,
+            )
+            .unwrap();
+            let tx = SignedTransaction::new(tx, vec![InputWitness::NoSignature(None)]).unwrap();
+            let tx_id = tx.transaction().get_id();
+            let tx_hex = hex::encode(tx.encode());
+
+            // Trailing bytes are rejected
+            let res: rpc::Result<Value> =
+                rpc.call("mempool_submit_transaction", [format!("{tx_hex}00")]).await;
+            assert!(res.is_err());
+
+            let res: rpc::Result<()> = rpc.call("mempool_submit_transaction", [&tx_hex]).await;
+            res.unwrap();
+            let res: rpc::Result<bool> = rpc.call("mempool_contains_transaction", [tx_id]).await;
+            assert!(res.unwrap());
+            let res: rpc::Result<Option<String>> =
+                rpc.call("mempool_get_transaction", [tx_id]).await;
+            assert_eq!(res.unwrap(), Some(tx_hex));
+            let res: rpc::Result<TxEntryInfo> = rpc.call("mempool_entry_info", [tx_id]).await;
+            let entry_info = res.unwrap();
+            assert_eq!(entry_info.tx_id, tx_id);
+            assert_eq!(entry_info.fee, Amount::from_atoms(10_000));
+            assert_eq!(entry_info.size, tx.encoded_size());
+        })
+        .await
     }
 }
@@ -0,0 +1,172 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backups of the node databases
+//!
+//! A backup is a directory holding a copy of the chainstate database and of the peer database.
+//! In the native format, the databases are stored the same way as in the data directory. In the
+//! portable format, each database is a dump of its raw contents, see [storage::raw::write_dump].
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Context, Result};
+
+use chainstate::ChainstateHandle;
+use chainstate_launcher::StorageBackendConfig;
+use p2p::peer_manager::peerdb::storage_impl::PeerDbStorageImpl;
+use storage::{schema::Schema, BackupFormat, Storage};
+use storage_lmdb::Lmdb;
+
+/// Subdirectory under `datadir` where the LMDB peer database is placed
+pub const SUBDIRECTORY_PEERDB_LMDB: &str = "peerdb-lmdb";
+
+/// Name of the chainstate dump in a portable backup
+const CHAINSTATE_DUMP: &str = "chainstate.dump";
+
+/// Name of the peer database dump in a portable backup
+const PEERDB_DUMP: &str = "peerdb.dump";
+
+fn lmdb_backend(path: PathBuf) -> Lmdb {
+    Lmdb::new(
+        path,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+}
+
+/// Backups are only supported for the databases kept on disk by LMDB
+fn check_storage_backend(storage_backend: &StorageBackendConfig) -> Result<()> {
+    match storage_backend {
+        StorageBackendConfig::Lmdb => Ok(()),
+        StorageBackendConfig::InMemory => {
+            bail!("Backups are not supported with the in-memory storage backend")
+        }
+    }
+}
+
+/// Write a backup of the node databases into a new directory
+pub async fn write_backup(
+    chainstate: &ChainstateHandle,
+    storage_backend: &StorageBackendConfig,
+    peerdb: &PeerDbStorageImpl<Lmdb>,
+    backup_dir: &Path,
+    format: BackupFormat,
+) -> Result<()> {
+    check_storage_backend(storage_backend)?;
+    if backup_dir.exists() {
+        bail!("Backup directory {} already exists", backup_dir.display());
+    }
+    std::fs::create_dir_all(backup_dir).with_context(|| {
+        format!(
+            "Failed to create the backup directory {}",
+            backup_dir.display()
+        )
+    })?;
+
+    let (chainstate_path, peerdb_path) = match format {
+        BackupFormat::Native => (
+            backup_dir.join(chainstate_launcher::SUBDIRECTORY_LMDB),
+            backup_dir.join(SUBDIRECTORY_PEERDB_LMDB),
+        ),
+        BackupFormat::Portable => (
+            backup_dir.join(CHAINSTATE_DUMP),
+            backup_dir.join(PEERDB_DUMP),
+        ),
+    };
+
+    // The databases are copied on a blocking task, the chainstate only hands out the storage
+    let chainstate_backup = chainstate.call(|this| this.storage_backup_handle()).await?;
+    let peerdb = peerdb.clone();
+    tokio::task::spawn_blocking(move || {
+        chainstate_backup
+            .backup(&chainstate_path, format)
+            .context("Chainstate backup failed")?;
+        peerdb.backup(&peerdb_path, format).context("Peer database backup failed")
+    })
+    .await
+    .context("Backup task failed")?
+}
+
+/// Restore one database from a backup into its location in the data directory, in whichever
+/// format the backup was written. The entries are copied as they are read, not all at once.
+fn restore_database<Sch: Schema>(
+    backup_dir: &Path,
+    target: PathBuf,
+    native_name: &str,
+    dump_name: &str,
+) -> Result<()> {
+    let dump_path = backup_dir.join(dump_name);
+    let native_path = backup_dir.join(native_name);
+
+    let storage = Storage::<_, Sch>::new(lmdb_backend(target.clone()))
+        .with_context(|| format!("Failed to open {}", target.display()))?;
+
+    if dump_path.is_file() {
+        let file = std::fs::File::open(&dump_path)
+            .with_context(|| format!("Failed to open {}", dump_path.display()))?;
+        storage.restore_dump(&mut std::io::BufReader::new(file)).with_context(|| {
+            format!(
+                "Failed to restore {} into {}",
+                dump_path.display(),
+                target.display()
+            )
+        })
+    } else if native_path.is_dir() {
+        let backup = Storage::<_, Sch>::new(lmdb_backend(native_path.clone()))
+            .with_context(|| format!("Failed to open {}", native_path.display()))?;
+        storage.restore_from(&backup).with_context(|| {
+            format!(
+                "Failed to restore {} into {}",
+                native_path.display(),
+                target.display()
+            )
+        })
+    } else {
+        Err(anyhow!(
+            "Neither {} nor {} found in the backup",
+            dump_name,
+            native_name
+        ))
+    }
+}
+
+/// Restore the node databases from a backup directory written by [write_backup]. The databases
+/// in the data directory have to be empty.
+pub fn restore_backup(
+    datadir: &Path,
+    storage_backend: &StorageBackendConfig,
+    backup_dir: &Path,
+) -> Result<()> {
+    check_storage_backend(storage_backend)?;
+
+    restore_database::<chainstate_storage::schema::Schema>(
+        backup_dir,
+        datadir.join(chainstate_launcher::SUBDIRECTORY_LMDB),
+        chainstate_launcher::SUBDIRECTORY_LMDB,
+        CHAINSTATE_DUMP,
+    )
+    .context("Failed to restore the chainstate")?;
+
+    restore_database::<p2p::peer_manager::peerdb::storage_impl::Schema>(
+        backup_dir,
+        datadir.join(SUBDIRECTORY_PEERDB_LMDB),
+        SUBDIRECTORY_PEERDB_LMDB,
+        PEERDB_DUMP,
+    )
+    .context("Failed to restore the peer database")?;
+
+    Ok(())
+}
@@ -17,7 +17,8 @@ use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
-use chainstate::ChainstateConfig;
+use chainstate::{ChainstateConfig, PruneMode, MIN_BLOCKS_TO_KEEP_WHEN_PRUNING};
+use common::primitives::{BlockHeight, Id, H256};
 
 /// The chainstate subsystem configuration.
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -31,11 +32,33 @@ pub struct ChainstateConfigFile {
     pub min_max_bootstrap_import_buffer_sizes: Option<(usize, usize)>,
     /// Maintain a full transaction index.
     pub tx_index_enabled: Option<bool>,
+    /// Maintain an index of the outputs and spends by destination.
+    pub address_index_enabled: Option<bool>,
+    /// Maintain an index of the inputs that spend each output.
+    pub spent_index_enabled: Option<bool>,
+    /// Build compact filters and filter headers for main chain blocks.
+    pub block_filters_enabled: Option<bool>,
     /// A maximum tip age in seconds.
     ///
     /// The initial block download is finished if the difference between the current time and the
     /// tip time is less than this value.
     pub max_tip_age: Option<u64>,
+    /// Prune old blocks, keeping the given number of blocks below the tip.
+    pub prune_depth: Option<u64>,
+    /// Prune old blocks, keeping the total size of the stored blocks under the given number of
+    /// megabytes. Ignored if `prune_depth` is set.
+    pub prune_size_mb: Option<u64>,
+    /// Overrides the assumed valid block of the chain.
+    ///
+    /// The signatures of the ancestors of this block are not verified.
+    pub assumed_valid_block: Option<H256>,
+    /// The height of the assumed valid block, which is ignored without it.
+    pub assumed_valid_block_height: Option<u64>,
+    /// Rebuild the utxo set and the other data derived from the blocks on startup.
+    ///
+    /// This is a one-off run mode, so it's never stored in the config file.
+    #[serde(skip)]
+    pub reindex_chainstate: Option<bool>,
 }
 
 impl From<ChainstateConfigFile> for ChainstateConfig {
@@ -45,7 +68,26 @@ impl From<ChainstateConfigFile> for ChainstateConfig {
             max_orphan_blocks: c.max_orphan_blocks.into(),
             min_max_bootstrap_import_buffer_sizes: c.min_max_bootstrap_import_buffer_sizes.into(),
             tx_index_enabled: c.tx_index_enabled.into(),
+            address_index_enabled: c.address_index_enabled.into(),
+            spent_index_enabled: c.spent_index_enabled.into(),
+            block_filters_enabled: c.block_filters_enabled.into(),
+            headers_only: Default::default(),
             max_tip_age: c.max_tip_age.map(Duration::from_secs).into(),
+            prune_mode: prune_mode(c.prune_depth, c.prune_size_mb).into(),
+            assumed_valid_block: c
+                .assumed_valid_block_height
+                .map(BlockHeight::new)
+                .zip(c.assumed_valid_block.map(Id::new))
+                .into(),
+            reindex_chainstate: c.reindex_chainstate.into(),
         }
     }
 }
+
+fn prune_mode(prune_depth: Option<u64>, prune_size_mb: Option<u64>) -> Option<PruneMode> {
+    match (prune_depth, prune_size_mb) {
+        (Some(depth), _) => Some(PruneMode::Depth(depth.max(MIN_BLOCKS_TO_KEEP_WHEN_PRUNING))),
+        (None, Some(size_mb)) => Some(PruneMode::Size(size_mb.saturating_mul(1024 * 1024))),
+        (None, None) => None,
+    }
+}
@@ -110,21 +110,47 @@ fn chainstate_config(
         max_orphan_blocks,
         min_max_bootstrap_import_buffer_sizes,
         tx_index_enabled,
+        address_index_enabled,
+        spent_index_enabled,
+        block_filters_enabled,
         max_tip_age,
+        prune_depth,
+        prune_size_mb,
+        assumed_valid_block,
+        assumed_valid_block_height,
+        reindex_chainstate,
     } = chainstate_config;
 
     let storage_backend = options.storage_backend.clone().unwrap_or(storage_backend);
     let max_db_commit_attempts = options.max_db_commit_attempts.or(max_db_commit_attempts);
     let max_orphan_blocks = options.max_orphan_blocks.or(max_orphan_blocks);
     let tx_index_enabled = options.tx_index_enabled.or(tx_index_enabled);
+    let address_index_enabled = options.address_index_enabled.or(address_index_enabled);
+    let spent_index_enabled = options.spent_index_enabled.or(spent_index_enabled);
+    let block_filters_enabled = options.block_filters_enabled.or(block_filters_enabled);
     let max_tip_age = options.max_tip_age.or(max_tip_age);
+    let prune_depth = options.prune_depth.or(prune_depth);
+    let prune_size_mb = options.prune_size_mb.or(prune_size_mb);
+    let (assumed_valid_block, assumed_valid_block_height) = match options.assumed_valid_block {
+        Some(block_id) => (Some(block_id), options.assumed_valid_block_height),
+        None => (assumed_valid_block, assumed_valid_block_height),
+    };
+    let reindex_chainstate = options.reindex_chainstate.then_some(true).or(reindex_chainstate);
 
     let chainstate_config = ChainstateConfigFile {
         max_db_commit_attempts,
         max_orphan_blocks,
         min_max_bootstrap_import_buffer_sizes,
         tx_index_enabled,
+        address_index_enabled,
+        spent_index_enabled,
+        block_filters_enabled,
         max_tip_age,
+        prune_depth,
+        prune_size_mb,
+        assumed_valid_block,
+        assumed_valid_block_height,
+        reindex_chainstate,
     };
     ChainstateLauncherConfigFile {
         storage_backend,
@@ -28,6 +28,9 @@ pub enum NodeTypeConfigFile {
     /// A node that only download blocks, but ignores transactions.
     #[serde(rename = "blocks-only-node", alias = "blocks")]
     BlocksOnlyNode,
+    /// A node that only syncs and validates block headers.
+    #[serde(rename = "light-node", alias = "light")]
+    LightNode,
 }
 
 impl From<NodeTypeConfigFile> for NodeType {
@@ -35,6 +38,7 @@ impl From<NodeTypeConfigFile> for NodeType {
         match t {
             NodeTypeConfigFile::FullNode => Self::Full,
             NodeTypeConfigFile::BlocksOnlyNode => Self::BlocksOnly,
+            NodeTypeConfigFile::LightNode => Self::Light,
         }
     }
 }
@@ -83,6 +87,7 @@ impl From<P2pConfigFile> for P2pConfig {
             ping_check_period: c.ping_check_period.map(Duration::from_secs).into(),
             ping_timeout: c.ping_timeout.map(Duration::from_secs).into(),
             node_type: c.node_type.map(Into::into).into(),
+            block_serving: Default::default(),
             allow_discover_private_ips: Default::default(),
         }
     }
@@ -15,6 +15,7 @@
 
 //! Top-level node runner as a library
 
+mod backup;
 mod config_files;
 mod options;
 mod regtest_options;
@@ -19,6 +19,7 @@ use std::{ffi::OsString, fs, net::SocketAddr, num::NonZeroU64, path::PathBuf};
 
 use anyhow::{Context, Result};
 use clap::{Args, Parser, Subcommand};
+use common::primitives::H256;
 use directories::UserDirs;
 
 use crate::{
@@ -79,6 +80,46 @@ pub struct RunOptions {
     #[clap(long)]
     pub tx_index_enabled: Option<bool>,
 
+    /// Maintain an index of the outputs and spends by destination.
+    #[clap(long)]
+    pub address_index_enabled: Option<bool>,
+
+    /// Maintain an index of the inputs that spend each output.
+    #[clap(long)]
+    pub spent_index_enabled: Option<bool>,
+
+    /// Build compact filters and filter headers for main chain blocks.
+    #[clap(long)]
+    pub block_filters_enabled: Option<bool>,
+
+    /// Prune old blocks, keeping the given number of blocks below the tip.
+    #[clap(long, conflicts_with = "prune_size_mb")]
+    pub prune_depth: Option<u64>,
+
+    /// Prune old blocks, keeping the total size of the stored blocks under the given number of
+    /// megabytes.
+    #[clap(long)]
+    pub prune_size_mb: Option<u64>,
+
+    /// Skip the signature verification of the ancestors of the given block (hex encoded id)
+    /// instead of the default one of the chain.
+    #[clap(long, value_name = "BLOCK_ID", requires = "assumed_valid_block_height")]
+    pub assumed_valid_block: Option<H256>,
+
+    /// The height of the block given with `--assumed-valid-block`.
+    #[clap(long, value_name = "HEIGHT", requires = "assumed_valid_block")]
+    pub assumed_valid_block_height: Option<u64>,
+
+    /// Drop the utxo set and the other data derived from the blocks, then rebuild it by
+    /// reconnecting the stored blocks of the main chain.
+    #[clap(long)]
+    pub reindex_chainstate: bool,
+
+    /// Restore the chainstate and peer databases from a backup directory written by the
+    /// `node_backup` RPC before starting. The databases in the data directory must be empty.
+    #[clap(long, value_name = "DIR")]
+    pub restore_backup: Option<PathBuf>,
+
     /// Address to bind P2P to.
     #[clap(long, value_name = "ADDR")]
     pub p2p_addr: Option<Vec<String>>,
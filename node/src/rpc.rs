@@ -15,6 +15,13 @@
 
 //! Node RPC methods
 
+use std::path::PathBuf;
+
+use chainstate::ChainstateHandle;
+use chainstate_launcher::StorageBackendConfig;
+use p2p::peer_manager::peerdb::storage_impl::PeerDbStorageImpl;
+use storage::BackupFormat;
+use storage_lmdb::Lmdb;
 use subsystem::manager::ShutdownTrigger;
 
 #[rpc::rpc(server, namespace = "node")]
@@ -26,18 +33,37 @@ trait NodeRpc {
     /// Get node software version
     #[method(name = "version")]
     fn version(&self) -> rpc::Result<String>;
+
+    /// Write a consistent backup of the chainstate and peer databases into a new directory,
+    /// while the node keeps running. The format is either `native` or `portable`.
+    #[method(name = "backup")]
+    async fn backup(&self, backup_dir: PathBuf, format: BackupFormat) -> rpc::Result<()>;
 }
 
 struct NodeRpc {
     shutdown_trigger: ShutdownTrigger,
+    chainstate: ChainstateHandle,
+    storage_backend: StorageBackendConfig,
+    peerdb: PeerDbStorageImpl<Lmdb>,
 }
 
 impl NodeRpc {
-    fn new(shutdown_trigger: ShutdownTrigger) -> Self {
-        Self { shutdown_trigger }
+    fn new(
+        shutdown_trigger: ShutdownTrigger,
+        chainstate: ChainstateHandle,
+        storage_backend: StorageBackendConfig,
+        peerdb: PeerDbStorageImpl<Lmdb>,
+    ) -> Self {
+        Self {
+            shutdown_trigger,
+            chainstate,
+            storage_backend,
+            peerdb,
+        }
     }
 }
 
+#[async_trait::async_trait]
 impl NodeRpcServer for NodeRpc {
     fn shutdown(&self) -> rpc::Result<()> {
         self.shutdown_trigger.initiate();
@@ -47,8 +73,27 @@ impl NodeRpcServer for NodeRpc {
     fn version(&self) -> rpc::Result<String> {
         Ok(env!("CARGO_PKG_VERSION").into())
     }
+
+    async fn backup(&self, backup_dir: PathBuf, format: BackupFormat) -> rpc::Result<()> {
+        crate::backup::write_backup(
+            &self.chainstate,
+            &self.storage_backend,
+            &self.peerdb,
+            &backup_dir,
+            format,
+        )
+        .await
+        .map_err(|e| rpc::Error::Custom(format!("{e:#}")))
+    }
 }
 
-pub fn init(shutdown_trigger: ShutdownTrigger) -> rpc::Methods {
-    NodeRpc::new(shutdown_trigger).into_rpc().into()
+pub fn init(
+    shutdown_trigger: ShutdownTrigger,
+    chainstate: ChainstateHandle,
+    storage_backend: StorageBackendConfig,
+    peerdb: PeerDbStorageImpl<Lmdb>,
+) -> rpc::Methods {
+    NodeRpc::new(shutdown_trigger, chainstate, storage_backend, peerdb)
+        .into_rpc()
+        .into()
 }
@@ -34,7 +34,7 @@ use mempool::{rpc::MempoolRpcServer, MempoolSubsystemInterface};
 use p2p::{peer_manager::peerdb::storage_impl::PeerDbStorageImpl, rpc::P2pRpcServer};
 
 use crate::{
-    config_files::NodeConfigFile,
+    config_files::{NodeConfigFile, NodeTypeConfigFile},
     options::{Command, Options, RunOptions},
     regtest_options::ChainConfigOptions,
 };
@@ -52,10 +52,20 @@ pub async fn initialize(
     manager.install_signal_handlers();
 
     // Chainstate subsystem
+    let light_node = matches!(
+        node_config.p2p.node_type,
+        Some(NodeTypeConfigFile::LightNode)
+    );
+    let mut chainstate_config: chainstate_launcher::ChainstateLauncherConfig =
+        node_config.chainstate.into();
+    // Light nodes only keep the headers
+    chainstate_config.chainstate_config.headers_only = light_node.into();
+    let pruning_enabled = chainstate_config.chainstate_config.prune_mode.is_enabled();
+    let storage_backend = chainstate_config.storage_backend.clone();
     let chainstate = chainstate_launcher::make_chainstate(
         &node_config.datadir,
         Arc::clone(&chain_config),
-        node_config.chainstate.into(),
+        chainstate_config,
     )?;
     let chainstate = manager.add_subsystem("chainstate", chainstate);
 
@@ -65,6 +75,8 @@ pub async fn initialize(
         chainstate.clone(),
         Default::default(),
         mempool::SystemUsageEstimator {},
+        Some(node_config.datadir.join(mempool::MEMPOOL_DUMP_FILE)),
+        Some(node_config.datadir.join(mempool::FEE_ESTIMATES_FILE)),
     );
     let mempool = manager.add_subsystem_with_custom_eventloop("mempool", move |call, shutdn| {
         mempool.run(call, shutdn)
@@ -73,20 +85,27 @@ pub async fn initialize(
     // P2P subsystem
     // TODO: Replace Lmdb with Sqlite backend when it's ready
     let peerdb_storage = PeerDbStorageImpl::new(storage_lmdb::Lmdb::new(
-        node_config.datadir.join("peerdb-lmdb"),
+        node_config.datadir.join(crate::backup::SUBDIRECTORY_PEERDB_LMDB),
         Default::default(),
         Default::default(),
         Default::default(),
     ))?;
+    let mut p2p_config: p2p::config::P2pConfig = node_config.p2p.into();
+    if light_node {
+        p2p_config.block_serving = p2p::net::types::BlockServing::None.into();
+    } else if pruning_enabled {
+        // Old blocks are not available, so let the peers know
+        p2p_config.block_serving = p2p::net::types::BlockServing::Limited.into();
+    }
     let p2p = manager.add_subsystem(
         "p2p",
         p2p::make_p2p(
             Arc::clone(&chain_config),
-            Arc::new(node_config.p2p.into()),
+            Arc::new(p2p_config),
             chainstate.clone(),
             mempool.clone(),
             Default::default(),
-            peerdb_storage,
+            peerdb_storage.clone(),
         )
         .await
         .expect("The p2p subsystem initialization failed"),
@@ -110,7 +129,12 @@ pub async fn initialize(
         let _rpc = manager.add_subsystem(
             "rpc",
             rpc::Builder::new(node_config.rpc.into())
-                .register(crate::rpc::init(manager.make_shutdown_trigger()))
+                .register(crate::rpc::init(
+                    manager.make_shutdown_trigger(),
+                    chainstate.clone(),
+                    storage_backend,
+                    peerdb_storage,
+                ))
                 .register(chainstate.clone().into_rpc())
                 .register(mempool.into_rpc())
                 .register(p2p.clone().into_rpc())
@@ -176,6 +200,14 @@ async fn start(
     let node_config = NodeConfigFile::read(config_path, datadir_path_opt, run_options)
         .context("Failed to initialize config")?;
     log::info!("Starting with the following config:\n {node_config:#?}");
+    if let Some(backup_dir) = &run_options.restore_backup {
+        log::info!("Restoring the databases from the backup in {backup_dir:?}");
+        crate::backup::restore_backup(
+            &node_config.datadir,
+            &node_config.chainstate.storage_backend.clone().into(),
+            backup_dir,
+        )?;
+    }
     let manager = initialize(chain_config, node_config).await?;
     manager.main().await;
     Ok(())
@@ -97,6 +97,7 @@ fn read_config_override_values() {
     let backend_type = StorageBackendConfigFile::InMemory;
     let node_type = NodeTypeConfigFile::FullNode;
     let max_tip_age = 1000;
+    let prune_depth = 500;
 
     let options = RunOptions {
         storage_backend: Some(backend_type.clone()),
@@ -104,6 +105,15 @@ fn read_config_override_values() {
         max_db_commit_attempts: Some(max_db_commit_attempts),
         max_orphan_blocks: Some(max_orphan_blocks),
         tx_index_enabled: Some(false),
+        address_index_enabled: Some(true),
+        spent_index_enabled: Some(true),
+        block_filters_enabled: Some(true),
+        prune_depth: Some(prune_depth),
+        prune_size_mb: None,
+        assumed_valid_block: None,
+        assumed_valid_block_height: None,
+        reindex_chainstate: false,
+        restore_backup: None,
         p2p_addr: Some(vec![p2p_addr.to_owned()]),
         p2p_add_node: Some(vec![p2p_add_node.to_owned()]),
         p2p_ban_threshold: Some(p2p_ban_threshold),
@@ -133,10 +143,26 @@ fn read_config_override_values() {
         config.chainstate.chainstate_config.tx_index_enabled,
         Some(false)
     );
+    assert_eq!(
+        config.chainstate.chainstate_config.address_index_enabled,
+        Some(true)
+    );
+    assert_eq!(
+        config.chainstate.chainstate_config.spent_index_enabled,
+        Some(true)
+    );
+    assert_eq!(
+        config.chainstate.chainstate_config.block_filters_enabled,
+        Some(true)
+    );
     assert_eq!(
         config.chainstate.chainstate_config.max_tip_age,
         Some(max_tip_age)
     );
+    assert_eq!(
+        config.chainstate.chainstate_config.prune_depth,
+        Some(prune_depth)
+    );
 
     assert_eq!(config.p2p.bind_addresses, Some(vec!(p2p_addr.to_owned())));
     assert_eq!(config.p2p.added_nodes, Some(vec!(p2p_add_node.to_owned())));
@@ -207,6 +233,15 @@ fn default_run_options() -> RunOptions {
         max_db_commit_attempts: None,
         max_orphan_blocks: None,
         tx_index_enabled: None,
+        address_index_enabled: None,
+        spent_index_enabled: None,
+        block_filters_enabled: None,
+        prune_depth: None,
+        prune_size_mb: None,
+        assumed_valid_block: None,
+        assumed_valid_block_height: None,
+        reindex_chainstate: false,
+        restore_backup: None,
         p2p_addr: None,
         p2p_add_node: None,
         p2p_ban_threshold: None,
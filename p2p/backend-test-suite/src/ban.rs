@@ -44,6 +44,7 @@ where
     let chain_config = Arc::new(common::chain::config::create_unit_test_config());
     let p2p_config = Arc::new(P2pConfig::default());
     let handle = p2p_test_utils::start_chainstate(Arc::clone(&chain_config)).await;
+    let mempool = p2p_test_utils::start_mempool(Arc::clone(&chain_config), handle.clone()).await;
 
     let (mut conn1, sync1) = N::start(
         T::make_transport(),
@@ -59,6 +60,7 @@ where
         Arc::clone(&p2p_config),
         sync1,
         handle.clone(),
+        mempool,
         rx_sync,
         tx_peer_manager,
     );
@@ -79,7 +81,7 @@ where
     let blocks = p2p_test_utils::create_n_blocks(Arc::clone(&chain_config), best_block, 3);
 
     tokio::spawn(async move {
-        sync1.register_peer(peer_info2.peer_id).await.unwrap();
+        sync1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await.unwrap();
         sync1.run().await
     });
 
@@ -124,6 +126,7 @@ where
     let (tx_peer_manager, mut rx_peer_manager) = mpsc::unbounded_channel();
     let chain_config = Arc::new(common::chain::config::create_unit_test_config());
     let handle = p2p_test_utils::start_chainstate(Arc::clone(&chain_config)).await;
+    let mempool = p2p_test_utils::start_mempool(Arc::clone(&chain_config), handle.clone()).await;
 
     let (mut conn1, sync1) = N::start(
         T::make_transport(),
@@ -148,6 +151,7 @@ where
         Arc::new(P2pConfig::default()),
         sync1,
         handle.clone(),
+        mempool,
         rx_p2p_sync,
         tx_peer_manager,
     );
@@ -161,9 +165,10 @@ where
     // register `conn2` to the `SyncManager`, process a block response
     // and verify the `PeerManager` is notified of the protocol violation
     let remote_id = peer_info2.peer_id;
+    let block_serving = peer_info2.block_serving;
 
     tokio::spawn(async move {
-        sync1.register_peer(remote_id).await.unwrap();
+        sync1.register_peer(remote_id, block_serving).await.unwrap();
         let res = sync1.process_block_response(remote_id, vec![blocks[2].clone()]).await;
         sync1.handle_error(remote_id, res).await.unwrap();
     });
@@ -136,6 +136,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: NodeType::Inactive.into(),
+        block_serving: Default::default(),
         allow_discover_private_ips: Default::default(),
     });
     let (mut conn1, mut sync1) = N::start(
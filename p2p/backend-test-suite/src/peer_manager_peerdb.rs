@@ -47,6 +47,7 @@ where
             ping_check_period: Default::default(),
             ping_timeout: Default::default(),
             node_type: Default::default(),
+            block_serving: Default::default(),
             allow_discover_private_ips: Default::default(),
         }),
         time_getter.get_time_getter(),
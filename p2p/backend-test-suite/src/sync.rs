@@ -82,7 +82,10 @@ where
 
     // connect the two managers together so that they can exchange messages
     let (_address, _peer_info1, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
 
     // ensure that only a header request is received from the remote and
     // as the nodes are tracking the same chain, no further messages are exchanged
@@ -136,7 +139,10 @@ where
 
     // add peer to the hashmap of known peers and send getheaders request to them
     let (_address, _peer_info1, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..9 {
@@ -228,8 +234,14 @@ where
 
     // add peer to the hashmap of known peers and send getheaders request to them
     let (_address, peer_info, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info.peer_id, peer_info.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..14 {
@@ -350,8 +362,14 @@ where
 
     // add peer to the hashmap of known peers and send getheaders request to them
     let (_address, peer_info, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info.peer_id, peer_info.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..24 {
@@ -490,8 +508,14 @@ where
 
     // add peer to the hashmap of known peers and send getheaders request to them
     let (_address, peer_info, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info.peer_id, peer_info.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..20 {
@@ -642,10 +666,22 @@ where
     let (_address, peer_info12, peer_info21) = connect_services::<N>(&mut conn1, &mut conn2).await;
     let (_address, peer_info13, peer_info31) = connect_services::<N>(&mut conn1, &mut conn3).await;
 
-    assert_eq!(mgr1.register_peer(peer_info21.peer_id).await, Ok(()));
-    assert_eq!(mgr1.register_peer(peer_info31.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info12.peer_id).await, Ok(()));
-    assert_eq!(mgr3.register_peer(peer_info13.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info21.peer_id, peer_info21.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr1.register_peer(peer_info31.peer_id, peer_info31.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info12.peer_id, peer_info12.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr3.register_peer(peer_info13.peer_id, peer_info13.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..18 {
@@ -773,10 +809,22 @@ where
     let (_address, peer_info12, peer_info21) = connect_services::<N>(&mut conn1, &mut conn2).await;
     let (_address, peer_info13, peer_info31) = connect_services::<N>(&mut conn1, &mut conn3).await;
 
-    assert_eq!(mgr1.register_peer(peer_info21.peer_id).await, Ok(()));
-    assert_eq!(mgr1.register_peer(peer_info31.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info12.peer_id).await, Ok(()));
-    assert_eq!(mgr3.register_peer(peer_info13.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info21.peer_id, peer_info21.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr1.register_peer(peer_info31.peer_id, peer_info31.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info12.peer_id, peer_info12.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr3.register_peer(peer_info13.peer_id, peer_info13.block_serving).await,
+        Ok(())
+    );
 
     let (tx, mut rx) = mpsc::unbounded_channel();
     let handle = tokio::spawn(async move {
@@ -908,10 +956,22 @@ where
     let (_address, peer_info12, peer_info21) = connect_services::<N>(&mut conn1, &mut conn2).await;
     let (_address, peer_info13, peer_info31) = connect_services::<N>(&mut conn1, &mut conn3).await;
 
-    assert_eq!(mgr1.register_peer(peer_info21.peer_id).await, Ok(()));
-    assert_eq!(mgr1.register_peer(peer_info31.peer_id).await, Ok(()));
-    assert_eq!(mgr2.register_peer(peer_info12.peer_id).await, Ok(()));
-    assert_eq!(mgr3.register_peer(peer_info13.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info21.peer_id, peer_info21.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr1.register_peer(peer_info31.peer_id, peer_info31.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr2.register_peer(peer_info12.peer_id, peer_info12.block_serving).await,
+        Ok(())
+    );
+    assert_eq!(
+        mgr3.register_peer(peer_info13.peer_id, peer_info13.block_serving).await,
+        Ok(())
+    );
 
     let (tx, mut rx) = mpsc::unbounded_channel();
     let mut gethdr_received = HashSet::new();
@@ -1032,7 +1092,10 @@ where
     .await;
 
     let (_address, _peer_info1, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
 
     // ensure that only a header request is received from the remote and
     // as the nodes are tracking the same chain, no further messages are exchanged
@@ -1047,7 +1110,7 @@ where
     assert!(same_tip(&mgr1_handle, &mgr2_handle).await);
     assert!(!mgr1_handle.call(|c| c.is_initial_block_download()).await.unwrap().unwrap());
 
-    mgr1.unregister_peer(peer_info2.peer_id);
+    mgr1.unregister_peer(peer_info2.peer_id).await.unwrap();
     assert_eq!(conn1.disconnect(peer_info2.peer_id), Ok(()));
 
     let event = get_connectivity_event::<N>(&mut conn2).await;
@@ -1065,7 +1128,10 @@ where
     p2p_test_utils::import_blocks(&mgr2_handle, blocks.clone()).await;
 
     let (_address, _peer_info1, peer_info2) = connect_services::<N>(&mut conn1, &mut conn2).await;
-    assert_eq!(mgr1.register_peer(peer_info2.peer_id).await, Ok(()));
+    assert_eq!(
+        mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await,
+        Ok(())
+    );
 
     let handle = tokio::spawn(async move {
         for _ in 0..9 {
@@ -1158,6 +1224,8 @@ where
     )
     .await
     .unwrap();
+    let mempool =
+        p2p_test_utils::start_mempool(Arc::clone(&chain_config), chainstate.clone()).await;
 
     (
         BlockSyncManager::<T>::new(
@@ -1165,6 +1233,7 @@ where
             p2p_config,
             sync,
             chainstate,
+            mempool,
             rx_p2p_sync,
             tx_peer_manager,
         ),
@@ -1280,6 +1349,22 @@ where
             SyncRequest::BlockListRequest(request) => {
                 mgr.process_block_request(peer_id, request_id, request.into_block_ids()).await?;
             }
+            SyncRequest::BlockFilterListRequest(request) => {
+                mgr.process_block_filter_request(peer_id, request_id, request.into_block_ids())
+                    .await?;
+            }
+            SyncRequest::BlockFilterHeaderListRequest(request) => {
+                mgr.process_block_filter_header_request(
+                    peer_id,
+                    request_id,
+                    request.into_block_ids(),
+                )
+                .await?;
+            }
+            SyncRequest::TransactionRequest(request) => {
+                mgr.process_transaction_request(peer_id, request_id, request.into_tx_id())
+                    .await?;
+            }
         },
         SyncingEvent::Response {
             peer_id,
@@ -1292,6 +1377,12 @@ where
             SyncResponse::BlockListResponse(response) => {
                 mgr.process_block_response(peer_id, response.into_blocks()).await?;
             }
+            SyncResponse::TransactionResponse(response) => {
+                let (tx_id, transaction) = response.into_parts();
+                mgr.process_transaction_response(peer_id, tx_id, transaction).await?;
+            }
+            SyncResponse::BlockFilterListResponse(_)
+            | SyncResponse::BlockFilterHeaderListResponse(_) => {}
         },
         SyncingEvent::Announcement {
             peer_id,
@@ -35,6 +35,7 @@ use common::{
     primitives::{time, Amount, Id, Idable},
 };
 use crypto::random::SliceRandom;
+use mempool::{MempoolHandle, MempoolSubsystemInterface};
 
 pub type ChainstateHandle = subsystem::Handle<Box<dyn ChainstateInterface + 'static>>;
 
@@ -184,6 +185,26 @@ pub async fn start_chainstate(
     handle
 }
 
+pub async fn start_mempool(
+    chain_config: Arc<ChainConfig>,
+    chainstate_handle: ChainstateHandle,
+) -> MempoolHandle {
+    let mut man = subsystem::Manager::new("TODO");
+    let mempool = mempool::make_mempool(
+        chain_config,
+        chainstate_handle,
+        Default::default(),
+        mempool::SystemUsageEstimator {},
+        None,
+        None,
+    );
+    let handle = man.add_subsystem_with_custom_eventloop("mempool", move |call, shutdn| {
+        mempool.run(call, shutdn)
+    });
+    tokio::spawn(async move { man.main().await });
+    handle
+}
+
 pub fn create_block(config: Arc<ChainConfig>, parent: TestBlockInfo) -> Block {
     produce_test_block(&config, parent)
 }
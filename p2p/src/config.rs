@@ -17,7 +17,7 @@ use std::{collections::BTreeSet, time::Duration};
 
 use utils::make_config_setting;
 
-use crate::net::types::PubSubTopic;
+use crate::net::types::{BlockServing, PubSubTopic};
 
 pub const DEFAULT_BIND_PORT: u16 = 3031;
 
@@ -30,6 +30,7 @@ make_config_setting!(
     [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect()
 );
 make_config_setting!(NodeTypeSetting, NodeType, NodeType::Full);
+make_config_setting!(BlockServingSetting, BlockServing, BlockServing::Full);
 make_config_setting!(AllowDiscoverPrivateIps, bool, false);
 make_config_setting!(PingCheckPeriod, Duration, Duration::from_secs(60));
 make_config_setting!(PingTimeout, Duration, Duration::from_secs(150));
@@ -41,6 +42,9 @@ pub enum NodeType {
     Full,
     /// A node that only download blocks, but ignores transactions.
     BlocksOnly,
+    /// A node that only syncs and validates block headers, without keeping the block data and
+    /// the utxo set.
+    Light,
     /// A node that doesn't subscribe to any events.
     ///
     /// This node type isn't useful outside of the tests.
@@ -53,7 +57,7 @@ impl From<NodeType> for BTreeSet<PubSubTopic> {
             NodeType::Full => {
                 [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect()
             }
-            NodeType::BlocksOnly => [PubSubTopic::Blocks].into_iter().collect(),
+            NodeType::BlocksOnly | NodeType::Light => [PubSubTopic::Blocks].into_iter().collect(),
             NodeType::Inactive => BTreeSet::new(),
         }
     }
@@ -78,6 +82,8 @@ pub struct P2pConfig {
     pub ping_timeout: PingTimeout,
     /// A node type.
     pub node_type: NodeTypeSetting,
+    /// Which blocks the node advertises as available to its peers.
+    pub block_serving: BlockServingSetting,
     /// Allow announcing and discovering local and private IPs. Should be used for testing only.
     pub allow_discover_private_ips: AllowDiscoverPrivateIps,
 }
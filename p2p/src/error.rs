@@ -101,6 +101,8 @@ pub enum P2pError {
     ConversionError(ConversionError),
     #[error("Noise protocol handshake error")]
     NoiseHandshakeError(String),
+    #[error("Mempool error: `{0}`")]
+    MempoolError(String),
     #[error("Other: `{0}`")]
     Other(&'static str),
 }
@@ -141,6 +143,12 @@ impl From<chainstate::ChainstateError> for P2pError {
     }
 }
 
+impl From<mempool::error::Error> for P2pError {
+    fn from(e: mempool::error::Error) -> P2pError {
+        P2pError::MempoolError(e.to_string())
+    }
+}
+
 impl BanScore for P2pError {
     fn ban_score(&self) -> u32 {
         match self {
@@ -155,6 +163,8 @@ impl BanScore for P2pError {
             P2pError::ConversionError(err) => err.ban_score(),
             // Could be a noise protocol violation but also a network error, do not ban peer
             P2pError::NoiseHandshakeError(_) => 0,
+            // Transactions rejected by the mempool are scored separately
+            P2pError::MempoolError(_) => 0,
             P2pError::Other(_) => 0,
         }
     }
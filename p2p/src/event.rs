@@ -13,9 +13,13 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use common::chain::block::Block;
+use common::chain::{block::Block, Destination};
 
-use crate::{interface::types::ConnectedPeer, net::NetworkingService, utils::oneshot_nofail};
+use crate::{
+    interface::types::{ConnectedPeer, RelevantTransaction},
+    net::{types::BlockServing, NetworkingService},
+    utils::oneshot_nofail,
+};
 
 #[derive(Debug)]
 pub enum PeerManagerEvent<T: NetworkingService> {
@@ -46,9 +50,16 @@ pub enum SyncEvent {
 
 #[derive(Debug)]
 pub enum SyncControlEvent<T: NetworkingService> {
-    /// Peer connected
-    Connected(T::PeerId),
+    /// Peer connected, with the blocks it is able to send
+    Connected(T::PeerId, BlockServing),
 
     /// Peer disconnected
     Disconnected(T::PeerId),
+
+    /// Look for the transactions of the destination in the blocks received from now on, light
+    /// nodes only
+    WatchDestination(Destination, oneshot_nofail::Sender<crate::Result<()>>),
+
+    /// Get the transactions found for the watched destinations
+    GetRelevantTransactions(oneshot_nofail::Sender<Vec<RelevantTransaction>>),
 }
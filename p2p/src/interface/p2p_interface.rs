@@ -13,7 +13,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use super::types::ConnectedPeer;
+use common::chain::Destination;
+
+use super::types::{ConnectedPeer, RelevantTransaction};
 
 #[async_trait::async_trait]
 pub trait P2pInterface: Send + Sync {
@@ -26,4 +28,9 @@ pub trait P2pInterface: Send + Sync {
     async fn get_bind_addresses(&self) -> crate::Result<Vec<String>>;
 
     async fn get_connected_peers(&self) -> crate::Result<Vec<ConnectedPeer>>;
+
+    /// Have a light node look for the transactions of the destination in the new blocks
+    async fn watch_destination(&mut self, destination: Destination) -> crate::Result<()>;
+
+    async fn get_relevant_transactions(&self) -> crate::Result<Vec<RelevantTransaction>>;
 }
@@ -13,15 +13,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common::chain::Destination;
+
 use crate::{
     error::{ConversionError, P2pError},
-    event::PeerManagerEvent,
+    event::{PeerManagerEvent, SyncControlEvent},
     net::NetworkingService,
     utils::oneshot_nofail,
     P2p,
 };
 
-use super::{p2p_interface::P2pInterface, types::ConnectedPeer};
+use super::{
+    p2p_interface::P2pInterface,
+    types::{ConnectedPeer, RelevantTransaction},
+};
 
 #[async_trait::async_trait]
 impl<T> P2pInterface for P2p<T>
@@ -74,4 +79,20 @@ where
             .map_err(P2pError::from)?;
         rx.await.map_err(P2pError::from)
     }
+
+    async fn watch_destination(&mut self, destination: Destination) -> crate::Result<()> {
+        let (tx, rx) = oneshot_nofail::channel();
+        self.tx_sync_control
+            .send(SyncControlEvent::WatchDestination(destination, tx))
+            .map_err(P2pError::from)?;
+        rx.await.map_err(P2pError::from)?
+    }
+
+    async fn get_relevant_transactions(&self) -> crate::Result<Vec<RelevantTransaction>> {
+        let (tx, rx) = oneshot_nofail::channel();
+        self.tx_sync_control
+            .send(SyncControlEvent::GetRelevantTransactions(tx))
+            .map_err(P2pError::from)?;
+        rx.await.map_err(P2pError::from)
+    }
 }
@@ -15,7 +15,12 @@
 
 use std::ops::{Deref, DerefMut};
 
-use super::{p2p_interface::P2pInterface, types::ConnectedPeer};
+use common::chain::Destination;
+
+use super::{
+    p2p_interface::P2pInterface,
+    types::{ConnectedPeer, RelevantTransaction},
+};
 
 #[async_trait::async_trait]
 impl<T: Deref<Target = dyn P2pInterface> + DerefMut<Target = dyn P2pInterface> + Send + Sync>
@@ -40,4 +45,12 @@ impl<T: Deref<Target = dyn P2pInterface> + DerefMut<Target = dyn P2pInterface> +
     async fn get_connected_peers(&self) -> crate::Result<Vec<ConnectedPeer>> {
         self.deref().get_connected_peers().await
     }
+
+    async fn watch_destination(&mut self, destination: Destination) -> crate::Result<()> {
+        self.deref_mut().watch_destination(destination).await
+    }
+
+    async fn get_relevant_transactions(&self) -> crate::Result<Vec<RelevantTransaction>> {
+        self.deref().get_relevant_transactions().await
+    }
 }
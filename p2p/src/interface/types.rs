@@ -13,6 +13,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use common::chain::{block::tx_inclusion_proof::TxInclusionProof, SignedTransaction};
+
 #[derive(Debug, serde::Serialize)]
 pub struct ConnectedPeer {
     // TODO: Replace String with actual type, once libp2p removed
@@ -25,3 +27,11 @@ pub struct ConnectedPeer {
 
     pub ban_score: u32,
 }
+
+/// A transaction found by a light node to send to or spend from the watched destinations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RelevantTransaction {
+    pub transaction: SignedTransaction,
+
+    pub inclusion_proof: TxInclusionProof,
+}
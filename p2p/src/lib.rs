@@ -42,7 +42,7 @@ use logging::log;
 use crate::{
     config::P2pConfig,
     error::{ConversionError, P2pError},
-    event::{PeerManagerEvent, SyncEvent},
+    event::{PeerManagerEvent, SyncControlEvent, SyncEvent},
     net::{
         default_backend::{
             transport::{NoiseEncryptionAdapter, NoiseTcpTransport},
@@ -62,6 +62,9 @@ struct P2p<T: NetworkingService> {
 
     /// TX channel for sending syncing/pubsub events
     pub _tx_sync: mpsc::UnboundedSender<SyncEvent>,
+
+    /// A sender for the sync manager control events.
+    pub tx_sync_control: mpsc::UnboundedSender<SyncControlEvent<T>>,
 }
 
 impl<T> P2p<T>
@@ -78,7 +81,7 @@ where
         chain_config: Arc<ChainConfig>,
         p2p_config: Arc<P2pConfig>,
         chainstate_handle: subsystem::Handle<Box<dyn chainstate_interface::ChainstateInterface>>,
-        _mempool_handle: mempool::MempoolHandle,
+        mempool_handle: mempool::MempoolHandle,
         time_getter: TimeGetter,
         peerdb_storage: S,
     ) -> crate::Result<Self> {
@@ -117,7 +120,7 @@ where
             Arc::clone(&p2p_config),
             conn,
             rx_peer_manager,
-            tx_p2p_sync,
+            tx_p2p_sync.clone(),
             time_getter,
             peerdb_storage,
         )?;
@@ -136,6 +139,7 @@ where
                     p2p_config,
                     sync,
                     chainstate_handle,
+                    mempool_handle,
                     rx_p2p_sync,
                     tx_peer_manager,
                 )
@@ -148,6 +152,7 @@ where
         Ok(Self {
             tx_peer_manager,
             _tx_sync,
+            tx_sync_control: tx_p2p_sync,
         })
     }
 }
@@ -15,7 +15,13 @@
 
 use chainstate::Locator;
 use common::{
-    chain::block::{Block, BlockHeader},
+    chain::{
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            Block, BlockHeader,
+        },
+        SignedTransaction, Transaction,
+    },
     primitives::Id,
 };
 use serialization::{Decode, Encode};
@@ -60,6 +66,64 @@ impl BlockListRequest {
     }
 }
 
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct BlockFilterListRequest {
+    block_ids: Vec<Id<Block>>,
+}
+
+impl BlockFilterListRequest {
+    pub fn new(block_ids: Vec<Id<Block>>) -> Self {
+        Self { block_ids }
+    }
+
+    pub fn block_ids(&self) -> &[Id<Block>] {
+        &self.block_ids
+    }
+
+    pub fn into_block_ids(self) -> Vec<Id<Block>> {
+        self.block_ids
+    }
+}
+
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct BlockFilterHeaderListRequest {
+    block_ids: Vec<Id<Block>>,
+}
+
+impl BlockFilterHeaderListRequest {
+    pub fn new(block_ids: Vec<Id<Block>>) -> Self {
+        Self { block_ids }
+    }
+
+    pub fn block_ids(&self) -> &[Id<Block>] {
+        &self.block_ids
+    }
+
+    pub fn into_block_ids(self) -> Vec<Id<Block>> {
+        self.block_ids
+    }
+}
+
+/// Request for a transaction that the peer announced
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TransactionRequest {
+    tx_id: Id<Transaction>,
+}
+
+impl TransactionRequest {
+    pub fn new(tx_id: Id<Transaction>) -> Self {
+        Self { tx_id }
+    }
+
+    pub fn tx_id(&self) -> &Id<Transaction> {
+        &self.tx_id
+    }
+
+    pub fn into_tx_id(self) -> Id<Transaction> {
+        self.tx_id
+    }
+}
+
 #[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
 pub struct AddrListRequest {}
 
@@ -85,12 +149,21 @@ pub enum Request {
     AnnounceAddrRequest(AnnounceAddrRequest),
     #[codec(index = 4)]
     PingRequest(PingRequest),
+    #[codec(index = 5)]
+    BlockFilterListRequest(BlockFilterListRequest),
+    #[codec(index = 6)]
+    BlockFilterHeaderListRequest(BlockFilterHeaderListRequest),
+    #[codec(index = 7)]
+    TransactionRequest(TransactionRequest),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SyncRequest {
     HeaderListRequest(HeaderListRequest),
     BlockListRequest(BlockListRequest),
+    BlockFilterListRequest(BlockFilterListRequest),
+    BlockFilterHeaderListRequest(BlockFilterHeaderListRequest),
+    TransactionRequest(TransactionRequest),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -138,6 +211,71 @@ impl BlockListResponse {
     }
 }
 
+/// The filters of the requested blocks that are in the main chain
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct BlockFilterListResponse {
+    filters: Vec<(Id<Block>, BlockFilter)>,
+}
+
+impl BlockFilterListResponse {
+    pub fn new(filters: Vec<(Id<Block>, BlockFilter)>) -> Self {
+        Self { filters }
+    }
+
+    pub fn filters(&self) -> &[(Id<Block>, BlockFilter)] {
+        &self.filters
+    }
+
+    pub fn into_filters(self) -> Vec<(Id<Block>, BlockFilter)> {
+        self.filters
+    }
+}
+
+/// The filter headers of the requested blocks that are in the main chain
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct BlockFilterHeaderListResponse {
+    filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+}
+
+impl BlockFilterHeaderListResponse {
+    pub fn new(filter_headers: Vec<(Id<Block>, BlockFilterHeader)>) -> Self {
+        Self { filter_headers }
+    }
+
+    pub fn filter_headers(&self) -> &[(Id<Block>, BlockFilterHeader)] {
+        &self.filter_headers
+    }
+
+    pub fn into_filter_headers(self) -> Vec<(Id<Block>, BlockFilterHeader)> {
+        self.filter_headers
+    }
+}
+
+/// The requested transaction, `None` if it isn't in the mempool of the peer anymore
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct TransactionResponse {
+    tx_id: Id<Transaction>,
+    transaction: Option<SignedTransaction>,
+}
+
+impl TransactionResponse {
+    pub fn new(tx_id: Id<Transaction>, transaction: Option<SignedTransaction>) -> Self {
+        Self { tx_id, transaction }
+    }
+
+    pub fn tx_id(&self) -> &Id<Transaction> {
+        &self.tx_id
+    }
+
+    pub fn transaction(&self) -> Option<&SignedTransaction> {
+        self.transaction.as_ref()
+    }
+
+    pub fn into_parts(self) -> (Id<Transaction>, Option<SignedTransaction>) {
+        (self.tx_id, self.transaction)
+    }
+}
+
 #[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
 pub struct AnnounceAddrResponse {}
 
@@ -163,12 +301,21 @@ pub enum Response {
     AnnounceAddrResponse(AnnounceAddrResponse),
     #[codec(index = 4)]
     PingResponse(PingResponse),
+    #[codec(index = 5)]
+    BlockFilterListResponse(BlockFilterListResponse),
+    #[codec(index = 6)]
+    BlockFilterHeaderListResponse(BlockFilterHeaderListResponse),
+    #[codec(index = 7)]
+    TransactionResponse(TransactionResponse),
 }
 
 #[derive(Debug, Clone)]
 pub enum SyncResponse {
     HeaderListResponse(HeaderListResponse),
     BlockListResponse(BlockListResponse),
+    BlockFilterListResponse(BlockFilterListResponse),
+    BlockFilterHeaderListResponse(BlockFilterHeaderListResponse),
+    TransactionResponse(TransactionResponse),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -182,6 +329,10 @@ pub enum PeerManagerResponse {
 pub enum Announcement {
     #[codec(index = 0)]
     Block(Block),
+    /// Id of a transaction that entered the mempool of the peer, the transaction itself has to be
+    /// requested with a `TransactionRequest`
+    #[codec(index = 1)]
+    Transaction(Id<Transaction>),
 }
 
 impl From<PeerManagerRequest> for Request {
@@ -213,6 +364,13 @@ impl From<SyncRequest> for Request {
         match request {
             SyncRequest::HeaderListRequest(request) => Request::HeaderListRequest(request),
             SyncRequest::BlockListRequest(request) => Request::BlockListRequest(request),
+            SyncRequest::BlockFilterListRequest(request) => {
+                Request::BlockFilterListRequest(request)
+            }
+            SyncRequest::BlockFilterHeaderListRequest(request) => {
+                Request::BlockFilterHeaderListRequest(request)
+            }
+            SyncRequest::TransactionRequest(request) => Request::TransactionRequest(request),
         }
     }
 }
@@ -222,6 +380,13 @@ impl From<SyncResponse> for Response {
         match response {
             SyncResponse::HeaderListResponse(response) => Response::HeaderListResponse(response),
             SyncResponse::BlockListResponse(response) => Response::BlockListResponse(response),
+            SyncResponse::BlockFilterListResponse(response) => {
+                Response::BlockFilterListResponse(response)
+            }
+            SyncResponse::BlockFilterHeaderListResponse(response) => {
+                Response::BlockFilterHeaderListResponse(response)
+            }
+            SyncResponse::TransactionResponse(response) => Response::TransactionResponse(response),
         }
     }
 }
@@ -249,6 +249,32 @@ where
         Ok(())
     }
 
+    /// Sends the announcement to a single peer.
+    ///
+    /// The announcement is dropped if the peer didn't subscribe to the related topic.
+    fn send_announcement(
+        &mut self,
+        peer_id: PeerId,
+        topic: PubSubTopic,
+        message: Vec<u8>,
+    ) -> crate::Result<()> {
+        let announcement = message::Announcement::decode(&mut &message[..])?;
+
+        let peer = self
+            .peers
+            .get(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?;
+        if peer.subscriptions.contains(&topic) {
+            peer.tx
+                .send(Event::SendMessage(Box::new(Message::Announcement {
+                    announcement,
+                })))
+                .map_err(P2pError::from)?;
+        }
+
+        Ok(())
+    }
+
     /// Handle incoming request
     fn handle_incoming_request(
         &mut self,
@@ -277,6 +303,30 @@ where
                     request: SyncRequest::BlockListRequest(request),
                 })
                 .map_err(P2pError::from),
+            message::Request::BlockFilterListRequest(request) => self
+                .sync_tx
+                .send(SyncingEvent::Request {
+                    peer_id,
+                    request_id,
+                    request: SyncRequest::BlockFilterListRequest(request),
+                })
+                .map_err(P2pError::from),
+            message::Request::BlockFilterHeaderListRequest(request) => self
+                .sync_tx
+                .send(SyncingEvent::Request {
+                    peer_id,
+                    request_id,
+                    request: SyncRequest::BlockFilterHeaderListRequest(request),
+                })
+                .map_err(P2pError::from),
+            message::Request::TransactionRequest(request) => self
+                .sync_tx
+                .send(SyncingEvent::Request {
+                    peer_id,
+                    request_id,
+                    request: SyncRequest::TransactionRequest(request),
+                })
+                .map_err(P2pError::from),
             message::Request::AddrListRequest(request) => self
                 .conn_tx
                 .send(ConnectivityEvent::Request {
@@ -330,6 +380,30 @@ where
                     response: SyncResponse::BlockListResponse(response),
                 })
                 .map_err(P2pError::from),
+            message::Response::BlockFilterListResponse(response) => self
+                .sync_tx
+                .send(SyncingEvent::Response {
+                    peer_id,
+                    request_id,
+                    response: SyncResponse::BlockFilterListResponse(response),
+                })
+                .map_err(P2pError::from),
+            message::Response::BlockFilterHeaderListResponse(response) => self
+                .sync_tx
+                .send(SyncingEvent::Response {
+                    peer_id,
+                    request_id,
+                    response: SyncResponse::BlockFilterHeaderListResponse(response),
+                })
+                .map_err(P2pError::from),
+            message::Response::TransactionResponse(response) => self
+                .sync_tx
+                .send(SyncingEvent::Response {
+                    peer_id,
+                    request_id,
+                    response: SyncResponse::TransactionResponse(response),
+                })
+                .map_err(P2pError::from),
             message::Response::AddrListResponse(response) => self
                 .conn_tx
                 .send(ConnectivityEvent::Response {
@@ -516,6 +590,7 @@ where
                 network,
                 version,
                 subscriptions,
+                block_serving,
                 receiver_address,
                 handshake_nonce,
             } => {
@@ -544,6 +619,7 @@ where
                                     version,
                                     agent: None,
                                     subscriptions: subscriptions.clone(),
+                                    block_serving,
                                 },
                                 receiver_address,
                             })
@@ -559,6 +635,7 @@ where
                                     version,
                                     agent: None,
                                     subscriptions: subscriptions.clone(),
+                                    block_serving,
                                 },
                                 receiver_address,
                             })
@@ -681,6 +758,20 @@ where
                 })
             }
             .boxed(),
+            Command::SendAnnouncement {
+                peer_id,
+                topic,
+                message,
+            } => async move {
+                boxed_cb(move |this| {
+                    let res = this.send_announcement(peer_id, topic, message);
+                    if let Err(e) = res {
+                        log::debug!("Failed to send announcement to peer {peer_id}: {e}")
+                    }
+                    Ok(())
+                })
+            }
+            .boxed(),
         };
 
         self.command_queue.push(backend_task);
@@ -293,23 +293,27 @@ where
     }
 
     fn make_announcement(&mut self, announcement: message::Announcement) -> crate::Result<()> {
-        let message = announcement.encode();
-        if message.len() > ANNOUNCEMENT_MAX_SIZE {
-            return Err(P2pError::PublishError(PublishError::MessageTooLarge(
-                message.len(),
-                ANNOUNCEMENT_MAX_SIZE,
-            )));
-        }
-
-        let topic = match &announcement {
-            message::Announcement::Block(_) => PubSubTopic::Blocks,
-        };
-
+        let (topic, message) = encode_announcement(&announcement)?;
         self.cmd_tx
             .send(types::Command::AnnounceData { topic, message })
             .map_err(P2pError::from)
     }
 
+    fn send_announcement(
+        &mut self,
+        peer_id: S::PeerId,
+        announcement: message::Announcement,
+    ) -> crate::Result<()> {
+        let (topic, message) = encode_announcement(&announcement)?;
+        self.cmd_tx
+            .send(types::Command::SendAnnouncement {
+                peer_id,
+                topic,
+                message,
+            })
+            .map_err(P2pError::from)
+    }
+
     async fn poll_next(&mut self) -> crate::Result<SyncingEvent<S>> {
         match self.sync_rx.recv().await.ok_or(P2pError::ChannelClosed)? {
             types::SyncingEvent::Request {
@@ -341,6 +345,26 @@ where
     }
 }
 
+/// Encodes the announcement and returns it along with the topic it belongs to
+fn encode_announcement(
+    announcement: &message::Announcement,
+) -> crate::Result<(PubSubTopic, Vec<u8>)> {
+    let message = announcement.encode();
+    if message.len() > ANNOUNCEMENT_MAX_SIZE {
+        return Err(P2pError::PublishError(PublishError::MessageTooLarge(
+            message.len(),
+            ANNOUNCEMENT_MAX_SIZE,
+        )));
+    }
+
+    let topic = match announcement {
+        message::Announcement::Block(_) => PubSubTopic::Blocks,
+        message::Announcement::Transaction(_) => PubSubTopic::Transactions,
+    };
+
+    Ok((topic, message))
+}
+
 #[cfg(test)]
 mod tests {
     use super::{transport::NoiseTcpTransport, *};
@@ -390,7 +414,7 @@ mod tests {
         {
             assert_eq!(address, conn2.local_addresses()[0]);
             assert_eq!(&peer_info.network, config.magic_bytes());
-            assert_eq!(peer_info.version, SemVer::new(0, 1, 0));
+            assert_eq!(peer_info.version, SemVer::new(0, 2, 0));
             assert_eq!(peer_info.agent, None);
             assert_eq!(
                 peer_info.subscriptions,
@@ -454,7 +478,7 @@ mod tests {
                 assert_eq!(peer_info.network, *config.magic_bytes());
                 assert_eq!(
                     peer_info.version,
-                    common::primitives::semver::SemVer::new(0, 1, 0),
+                    common::primitives::semver::SemVer::new(0, 2, 0),
                 );
                 assert_eq!(peer_info.agent, None);
             }
@@ -591,7 +615,7 @@ mod tests {
         {
             assert_eq!(address, conn2.local_addresses()[0]);
             assert_eq!(&peer_info.network, config.magic_bytes());
-            assert_eq!(peer_info.version, SemVer::new(0, 1, 0));
+            assert_eq!(peer_info.version, SemVer::new(0, 2, 0));
             assert_eq!(peer_info.agent, None);
             assert_eq!(
                 peer_info.subscriptions,
@@ -28,7 +28,7 @@ use crate::{
             transport::TransportSocket,
             types::{self, Event, PeerEvent, PeerId},
         },
-        types::Role,
+        types::{BlockServing, Role},
     },
     types::peer_address::PeerAddress,
 };
@@ -109,15 +109,47 @@ where
     async fn handshake(&mut self) -> crate::Result<()> {
         match self.peer_role {
             PeerRole::Inbound => {
-                let Ok(types::Message::Handshake(types::HandshakeMessage::Hello {
+                let (
                     version,
                     network,
                     subscriptions,
+                    block_serving,
                     receiver_address,
                     handshake_nonce,
-                })) = self.socket.recv().await
-                else {
-                    return Err(P2pError::ProtocolError(ProtocolError::InvalidMessage));
+                    legacy,
+                ) = match self.socket.recv().await {
+                    Ok(types::Message::Handshake(types::HandshakeMessage::Hello {
+                        version,
+                        network,
+                        subscriptions,
+                        block_serving,
+                        receiver_address,
+                        handshake_nonce,
+                    })) => (
+                        version,
+                        network,
+                        subscriptions,
+                        block_serving,
+                        receiver_address,
+                        handshake_nonce,
+                        false,
+                    ),
+                    Ok(types::Message::Handshake(types::HandshakeMessage::LegacyHello {
+                        version,
+                        network,
+                        subscriptions,
+                        receiver_address,
+                        handshake_nonce,
+                    })) if version < types::BLOCK_SERVING_PROTOCOL_VERSION => (
+                        version,
+                        network,
+                        subscriptions,
+                        BlockServing::Full,
+                        receiver_address,
+                        handshake_nonce,
+                        true,
+                    ),
+                    _ => return Err(P2pError::ProtocolError(ProtocolError::InvalidMessage)),
                 };
 
                 // Send PeerInfoReceived before sending handshake to remote peer!
@@ -130,22 +162,31 @@ where
                             network,
                             version,
                             subscriptions,
+                            block_serving,
                             receiver_address,
                             handshake_nonce,
                         },
                     ))
                     .map_err(P2pError::from)?;
 
-                self.socket
-                    .send(types::Message::Handshake(
-                        types::HandshakeMessage::HelloAck {
-                            version: *self.chain_config.version(),
-                            network: *self.chain_config.magic_bytes(),
-                            subscriptions: (*self.p2p_config.node_type.as_ref()).into(),
-                            receiver_address: self.receiver_address.clone(),
-                        },
-                    ))
-                    .await?;
+                // Reply in the format the remote peer understands
+                let hello_ack = if legacy {
+                    types::HandshakeMessage::LegacyHelloAck {
+                        version: *self.chain_config.version(),
+                        network: *self.chain_config.magic_bytes(),
+                        subscriptions: (*self.p2p_config.node_type.as_ref()).into(),
+                        receiver_address: self.receiver_address.clone(),
+                    }
+                } else {
+                    types::HandshakeMessage::HelloAck {
+                        version: *self.chain_config.version(),
+                        network: *self.chain_config.magic_bytes(),
+                        subscriptions: (*self.p2p_config.node_type.as_ref()).into(),
+                        block_serving: *self.p2p_config.block_serving,
+                        receiver_address: self.receiver_address.clone(),
+                    }
+                };
+                self.socket.send(types::Message::Handshake(hello_ack)).await?;
             }
             PeerRole::Outbound { handshake_nonce } => {
                 self.socket
@@ -153,20 +194,43 @@ where
                         version: *self.chain_config.version(),
                         network: *self.chain_config.magic_bytes(),
                         subscriptions: (*self.p2p_config.node_type.as_ref()).into(),
+                        block_serving: *self.p2p_config.block_serving,
                         receiver_address: self.receiver_address.clone(),
                         handshake_nonce,
                     }))
                     .await?;
 
-                let Ok(types::Message::Handshake(types::HandshakeMessage::HelloAck {
-                    version,
-                    network,
-                    subscriptions,
-                    receiver_address,
-                })) = self.socket.recv().await
-                else {
-                    return Err(P2pError::ProtocolError(ProtocolError::InvalidMessage));
-                };
+                let (version, network, subscriptions, block_serving, receiver_address) =
+                    match self.socket.recv().await {
+                        Ok(types::Message::Handshake(types::HandshakeMessage::HelloAck {
+                            version,
+                            network,
+                            subscriptions,
+                            block_serving,
+                            receiver_address,
+                        })) => (
+                            version,
+                            network,
+                            subscriptions,
+                            block_serving,
+                            receiver_address,
+                        ),
+                        Ok(types::Message::Handshake(
+                            types::HandshakeMessage::LegacyHelloAck {
+                                version,
+                                network,
+                                subscriptions,
+                                receiver_address,
+                            },
+                        )) if version < types::BLOCK_SERVING_PROTOCOL_VERSION => (
+                            version,
+                            network,
+                            subscriptions,
+                            BlockServing::Full,
+                            receiver_address,
+                        ),
+                        _ => return Err(P2pError::ProtocolError(ProtocolError::InvalidMessage)),
+                    };
 
                 self.tx
                     .send((
@@ -175,6 +239,7 @@ where
                             network,
                             version,
                             subscriptions,
+                            block_serving,
                             receiver_address,
                             handshake_nonce,
                         },
@@ -256,6 +321,7 @@ mod tests {
         },
     };
     use chainstate::Locator;
+    use common::primitives::semver::SemVer;
     use futures::FutureExt;
 
     async fn handshake_inbound<A, T>()
@@ -295,6 +361,7 @@ mod tests {
                 subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                     .into_iter()
                     .collect(),
+                block_serving: BlockServing::Full,
                 receiver_address: None,
                 handshake_nonce: 123,
             }))
@@ -310,6 +377,7 @@ mod tests {
                 subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                     .into_iter()
                     .collect(),
+                block_serving: BlockServing::Full,
                 receiver_address: None,
                 handshake_nonce: 123,
             }
@@ -331,6 +399,86 @@ mod tests {
         handshake_inbound::<TestTransportNoise, NoiseTcpTransport>().await;
     }
 
+    async fn handshake_inbound_legacy<A, T>()
+    where
+        A: TestTransportMaker<Transport = T, Address = T::Address>,
+        T: TransportSocket,
+    {
+        let (socket1, socket2) = get_two_connected_sockets::<A, T>().await;
+        let chain_config = Arc::new(common::chain::config::create_mainnet());
+        let p2p_config = Arc::new(P2pConfig::default());
+        let (tx1, mut rx1) = mpsc::unbounded_channel();
+        let (_tx2, rx2) = mpsc::unbounded_channel();
+        let peer_id2 = PeerId::new();
+        let legacy_version = SemVer::new(0, 1, 0);
+
+        let mut peer = Peer::<T>::new(
+            peer_id2,
+            PeerRole::Inbound,
+            Arc::clone(&chain_config),
+            p2p_config,
+            socket1,
+            None,
+            tx1,
+            rx2,
+        );
+
+        let handle = tokio::spawn(async move {
+            peer.handshake().await.unwrap();
+            peer
+        });
+
+        let mut socket2 = BufferedTranscoder::new(socket2);
+        assert!(socket2
+            .send(types::Message::Handshake(
+                types::HandshakeMessage::LegacyHello {
+                    version: legacy_version,
+                    network: *chain_config.magic_bytes(),
+                    subscriptions: [PubSubTopic::Blocks].into_iter().collect(),
+                    receiver_address: None,
+                    handshake_nonce: 123,
+                }
+            ))
+            .await
+            .is_ok());
+
+        // The legacy peer gets an acknowledgement it is able to decode
+        assert!(matches!(
+            socket2.recv().await,
+            Ok(types::Message::Handshake(
+                types::HandshakeMessage::LegacyHelloAck { .. }
+            ))
+        ));
+
+        let _peer = handle.await.unwrap();
+        assert_eq!(
+            rx1.try_recv().unwrap().1,
+            types::PeerEvent::PeerInfoReceived {
+                network: *chain_config.magic_bytes(),
+                version: legacy_version,
+                subscriptions: [PubSubTopic::Blocks].into_iter().collect(),
+                block_serving: BlockServing::Full,
+                receiver_address: None,
+                handshake_nonce: 123,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn handshake_inbound_legacy_tcp() {
+        handshake_inbound_legacy::<TestTransportTcp, TcpTransportSocket>().await;
+    }
+
+    #[tokio::test]
+    async fn handshake_inbound_legacy_channels() {
+        handshake_inbound_legacy::<TestTransportChannel, MpscChannelTransport>().await;
+    }
+
+    #[tokio::test]
+    async fn handshake_inbound_legacy_noise() {
+        handshake_inbound_legacy::<TestTransportNoise, NoiseTcpTransport>().await;
+    }
+
     async fn handshake_outbound<A, T>()
     where
         A: TestTransportMaker<Transport = T, Address = T::Address>,
@@ -369,6 +517,7 @@ mod tests {
                     subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                         .into_iter()
                         .collect(),
+                    block_serving: BlockServing::Full,
                     receiver_address: None,
                 }
             ))
@@ -386,6 +535,7 @@ mod tests {
                     subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                         .into_iter()
                         .collect(),
+                    block_serving: BlockServing::Full,
                     receiver_address: None,
                     handshake_nonce: 1,
                 }
@@ -442,6 +592,7 @@ mod tests {
                 subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                     .into_iter()
                     .collect(),
+                block_serving: BlockServing::Full,
                 receiver_address: None,
                 handshake_nonce: 123,
             }))
@@ -28,7 +28,7 @@ use crate::{
     net::{
         self,
         default_backend::transport::TransportSocket,
-        types::{PeerInfo, PubSubTopic},
+        types::{BlockServing, PeerInfo, PubSubTopic},
     },
     types::peer_address::PeerAddress,
 };
@@ -55,6 +55,12 @@ pub enum Command<T: TransportSocket> {
         topic: PubSubTopic,
         message: Vec<u8>,
     },
+    /// Send an announcement to a single peer
+    SendAnnouncement {
+        peer_id: PeerId,
+        topic: PubSubTopic,
+        message: Vec<u8>,
+    },
 }
 
 pub enum SyncingEvent {
@@ -175,6 +181,7 @@ pub enum PeerEvent {
         network: [u8; 4],
         version: SemVer,
         subscriptions: BTreeSet<PubSubTopic>,
+        block_serving: BlockServing,
         receiver_address: Option<PeerAddress>,
 
         /// For outbound connections that is what we sent.
@@ -196,14 +203,42 @@ pub enum Event {
     SendMessage(Box<Message>),
 }
 
+/// First protocol version whose handshake carries the block serving mode
+pub const BLOCK_SERVING_PROTOCOL_VERSION: SemVer = SemVer::new(0, 2, 0);
+
 // TODO: Decide what to do about protocol upgrades.
 // For example adding new address type to PeerAddress might break handshakes with older nodes.
 #[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
 pub enum HandshakeMessage {
+    /// Handshake sent by nodes older than `BLOCK_SERVING_PROTOCOL_VERSION`.
+    /// Such nodes keep every block, so they are treated as serving full blocks.
+    #[codec(index = 0)]
+    LegacyHello {
+        version: SemVer,
+        network: [u8; 4],
+        subscriptions: BTreeSet<PubSubTopic>,
+
+        /// Socket address of the remote peer as seen by this node (addr_you in bitcoin)
+        receiver_address: Option<PeerAddress>,
+
+        /// Random nonce that is only used to detect and drop self-connects
+        handshake_nonce: HandshakeNonce,
+    },
+    #[codec(index = 1)]
+    LegacyHelloAck {
+        version: SemVer,
+        network: [u8; 4],
+        subscriptions: BTreeSet<PubSubTopic>,
+
+        /// Socket address of the remote peer as seen by this node (addr_you in bitcoin)
+        receiver_address: Option<PeerAddress>,
+    },
+    #[codec(index = 2)]
     Hello {
         version: SemVer,
         network: [u8; 4],
         subscriptions: BTreeSet<PubSubTopic>,
+        block_serving: BlockServing,
 
         /// Socket address of the remote peer as seen by this node (addr_you in bitcoin)
         receiver_address: Option<PeerAddress>,
@@ -211,10 +246,12 @@ pub enum HandshakeMessage {
         /// Random nonce that is only used to detect and drop self-connects
         handshake_nonce: HandshakeNonce,
     },
+    #[codec(index = 3)]
     HelloAck {
         version: SemVer,
         network: [u8; 4],
         subscriptions: BTreeSet<PubSubTopic>,
+        block_serving: BlockServing,
 
         /// Socket address of the remote peer as seen by this node (addr_you in bitcoin)
         receiver_address: Option<PeerAddress>,
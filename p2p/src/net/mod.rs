@@ -182,6 +182,17 @@ where
     /// Publishes an announcement on the network.
     fn make_announcement(&mut self, announcement: Announcement) -> crate::Result<()>;
 
+    /// Sends an announcement to a single peer, if it subscribed to the related topic.
+    ///
+    /// # Arguments
+    /// * `peer_id` - Unique ID of the peer the announcement is sent to
+    /// * `announcement` - Announcement to be sent
+    fn send_announcement(
+        &mut self,
+        peer_id: T::PeerId,
+        announcement: Announcement,
+    ) -> crate::Result<()>;
+
     /// Poll syncing-related event from the networking service
     async fn poll_next(&mut self) -> crate::Result<types::SyncingEvent<T>>;
 }
@@ -54,6 +54,9 @@ pub struct PeerInfo<P> {
 
     /// The announcements list that a peer interested is.
     pub subscriptions: BTreeSet<PubSubTopic>,
+
+    /// Which blocks the peer is able to send
+    pub block_serving: BlockServing,
 }
 
 impl<P: Debug> Display for PeerInfo<P> {
@@ -67,6 +70,7 @@ impl<P: Debug> Display for PeerInfo<P> {
             "--> User agent: {}",
             self.agent.as_ref().unwrap_or(&"No user agent".to_string())
         )?;
+        writeln!(f, "--> Block serving: {:?}", self.block_serving)?;
 
         Ok(())
     }
@@ -187,3 +191,16 @@ pub enum PubSubTopic {
     /// Blocks
     Blocks,
 }
+
+/// Which blocks a node is able to send to its peers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum BlockServing {
+    /// The whole block history is available
+    Full,
+
+    /// Old blocks are pruned, only the blocks close to the tip are available
+    Limited,
+
+    /// No blocks are available, only the headers are kept
+    None,
+}
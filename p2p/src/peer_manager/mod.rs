@@ -77,6 +77,9 @@ const MAX_ADDRESS_COUNT: usize = 1000;
 /// To how many peers re-send received announced address
 const ANNOUNCED_RESEND_COUNT: usize = 2;
 
+/// Oldest software version the node can still talk to
+const MIN_SUPPORTED_PROTOCOL_VERSION: SemVer = SemVer::new(0, 1, 0);
+
 pub struct PeerManager<T, S>
 where
     T: NetworkingService,
@@ -155,10 +158,12 @@ where
 
     /// Verify software version compatibility
     ///
-    /// Make sure that local and remote peer have the same software version
+    /// Make sure that the remote peer has the same major version as the local one
+    /// and is not older than the oldest supported protocol version.
+    /// The handshake takes care of the differences between the supported versions.
     fn validate_version(&self, version: &SemVer) -> bool {
-        // TODO: handle upgrades of versions
-        version == self.chain_config.version()
+        version.major == self.chain_config.version().major
+            && *version >= MIN_SUPPORTED_PROTOCOL_VERSION
     }
 
     fn is_peer_address_valid(&self, address: &PeerAddress) -> bool {
@@ -253,6 +258,7 @@ where
         receiver_address: Option<PeerAddress>,
     ) -> crate::Result<()> {
         let peer_id = info.peer_id;
+        let block_serving = info.block_serving;
 
         ensure!(
             info.network == *self.chain_config.magic_bytes(),
@@ -308,7 +314,9 @@ where
 
         self.peerdb.peer_connected(address);
 
-        self.tx_sync.send(SyncControlEvent::Connected(peer_id)).map_err(P2pError::from)
+        self.tx_sync
+            .send(SyncControlEvent::Connected(peer_id, block_serving))
+            .map_err(P2pError::from)
     }
 
     /// Validate inbound peer connection
@@ -57,6 +57,24 @@ impl<B: storage::Backend> PeerDbStorageImpl<B> {
         let store = storage::Storage::<_, Schema>::new(storage)?;
         Ok(Self(store))
     }
+
+    /// Write a consistent backup of the peer database to given path, which must not exist yet
+    pub fn backup(
+        &self,
+        path: &std::path::Path,
+        format: storage::BackupFormat,
+    ) -> crate::Result<()> {
+        self.0.backup(path, format).map_err(Into::into)
+    }
+}
+
+impl<B: storage::Backend> Clone for PeerDbStorageImpl<B>
+where
+    B::Impl: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
 }
 
 impl<'st, B: storage::Backend> PeerDbStorageWrite for PeerDbStoreTxRw<'st, B> {
@@ -35,7 +35,7 @@ use crate::{
             types::PeerId,
             DefaultNetworkingService,
         },
-        types::PubSubTopic,
+        types::{BlockServing, PubSubTopic},
         AsBannableAddress, ConnectivityService, NetworkingService,
     },
     peer_manager::tests::make_peer_manager,
@@ -229,6 +229,7 @@ where
             version: SemVer::new(0, 1, 0),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -245,6 +246,7 @@ where
             version: SemVer::new(1, 1, 1),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -262,6 +264,7 @@ where
             version: SemVer::new(0, 1, 0),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -321,6 +324,7 @@ where
             version: SemVer::new(0, 1, 0),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -336,6 +340,7 @@ where
             version: SemVer::new(1, 1, 1),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -352,6 +357,7 @@ where
             version: SemVer::new(0, 1, 0),
             agent: None,
             subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions].into_iter().collect(),
+            block_serving: BlockServing::Full,
         },
         None,
     );
@@ -43,7 +43,7 @@ use crate::{
             types::PeerId,
             DefaultNetworkingService,
         },
-        types::{PeerInfo, PubSubTopic},
+        types::{BlockServing, PeerInfo, PubSubTopic},
         ConnectivityService, NetworkingService,
     },
     peer_manager::{self, tests::make_peer_manager},
@@ -460,6 +460,7 @@ async fn inbound_connection_too_many_peers_tcp() {
                     subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                         .into_iter()
                         .collect(),
+                    block_serving: BlockServing::Full,
                 },
             )
         })
@@ -487,6 +488,7 @@ async fn inbound_connection_too_many_peers_channels() {
                     subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                         .into_iter()
                         .collect(),
+                    block_serving: BlockServing::Full,
                 },
             )
         })
@@ -514,6 +516,7 @@ async fn inbound_connection_too_many_peers_noise() {
                     subscriptions: [PubSubTopic::Blocks, PubSubTopic::Transactions]
                         .into_iter()
                         .collect(),
+                    block_serving: BlockServing::Full,
                 },
             )
         })
@@ -686,6 +689,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: Default::default(),
+        block_serving: Default::default(),
         allow_discover_private_ips: Default::default(),
     });
     let tx1 = run_peer_manager::<T>(
@@ -713,6 +717,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: Default::default(),
+        block_serving: Default::default(),
         allow_discover_private_ips: Default::default(),
     });
     let tx1 = run_peer_manager::<T>(
@@ -780,6 +785,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: Default::default(),
+        block_serving: Default::default(),
         allow_discover_private_ips: true.into(),
     });
     let tx1 = run_peer_manager::<T>(
@@ -808,6 +814,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: Default::default(),
+        block_serving: Default::default(),
         allow_discover_private_ips: true.into(),
     });
     let tx2 = run_peer_manager::<T>(
@@ -829,6 +836,7 @@ where
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
         node_type: Default::default(),
+        block_serving: Default::default(),
         allow_discover_private_ips: true.into(),
     });
     let tx3 = run_peer_manager::<T>(
@@ -27,7 +27,7 @@ use crate::{
             types::{Command, ConnectivityEvent, PeerId},
             ConnectivityHandle, DefaultNetworkingService,
         },
-        types::PeerInfo,
+        types::{BlockServing, PeerInfo},
     },
     peer_manager::PeerManager,
     testing_utils::{peerdb_inmemory_store, P2pTestTimeGetter},
@@ -79,6 +79,7 @@ async fn ping_timeout() {
                 version: *chain_config.version(),
                 agent: None,
                 subscriptions: Default::default(),
+                block_serving: BlockServing::Full,
             },
             receiver_address: None,
         })
@@ -13,9 +13,37 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::{error::P2pError, interface::types::ConnectedPeer};
+use crate::{
+    error::P2pError,
+    interface::types::{ConnectedPeer, RelevantTransaction},
+};
+use common::{
+    chain::{Block, Destination},
+    primitives::Id,
+};
+use serialization::{Decode, Encode};
 use subsystem::subsystem::CallError;
 
+/// A transaction found by a light node, as reported by the `get_relevant_transactions` call
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RpcRelevantTransaction {
+    pub block_id: Id<Block>,
+    /// Hex encoded signed transaction
+    pub transaction: String,
+    /// Hex encoded proof of the inclusion of the transaction in the block
+    pub inclusion_proof: String,
+}
+
+impl From<RelevantTransaction> for RpcRelevantTransaction {
+    fn from(relevant_tx: RelevantTransaction) -> Self {
+        Self {
+            block_id: *relevant_tx.inclusion_proof.block_id(),
+            transaction: hex::encode(relevant_tx.transaction.encode()),
+            inclusion_proof: hex::encode(relevant_tx.inclusion_proof.encode()),
+        }
+    }
+}
+
 #[rpc::rpc(server, namespace = "p2p")]
 trait P2pRpc {
     /// Connect to remote node
@@ -37,6 +65,15 @@ trait P2pRpc {
     /// Get details of connected peers
     #[method(name = "get_connected_peers")]
     async fn get_connected_peers(&self) -> rpc::Result<Vec<ConnectedPeer>>;
+
+    /// Have a light node look for the transactions sending to the hex encoded destination, or
+    /// spending what was sent to it, in the blocks received from now on
+    #[method(name = "watch_destination")]
+    async fn watch_destination(&self, destination_hex: String) -> rpc::Result<()>;
+
+    /// Get the transactions found by a light node for the watched destinations
+    #[method(name = "get_relevant_transactions")]
+    async fn get_relevant_transactions(&self) -> rpc::Result<Vec<RpcRelevantTransaction>>;
 }
 
 #[async_trait::async_trait]
@@ -65,6 +102,21 @@ impl P2pRpcServer for super::P2pHandle {
         let res = self.call_async(|this| Box::pin(this.get_connected_peers())).await;
         handle_error(res)
     }
+
+    async fn watch_destination(&self, destination_hex: String) -> rpc::Result<()> {
+        let destination_data = hex::decode(destination_hex).map_err(rpc::Error::to_call_error)?;
+        let destination =
+            Destination::decode(&mut &destination_data[..]).map_err(rpc::Error::to_call_error)?;
+        let res = self
+            .call_async_mut(move |this| Box::pin(this.watch_destination(destination)))
+            .await;
+        handle_error(res)
+    }
+
+    async fn get_relevant_transactions(&self) -> rpc::Result<Vec<RpcRelevantTransaction>> {
+        let res = self.call_async(|this| Box::pin(this.get_relevant_transactions())).await;
+        Ok(handle_error(res)?.into_iter().map(RpcRelevantTransaction::from).collect())
+    }
 }
 
 fn handle_error<T>(e: Result<Result<T, P2pError>, CallError>) -> rpc::Result<T> {
@@ -0,0 +1,611 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Scanning of the blocks for the transactions relevant to a light node.
+//!
+//! A light node asks its peers for the filters of the blocks whose headers it receives, and
+//! downloads only the blocks whose filters match the watched destinations or the outputs sent to
+//! them. The relevant transactions of these blocks are kept with a proof of their inclusion, which
+//! can be checked against the block header alone.
+//!
+//! The blocks of a peer are scanned in chain order, so that the outputs found in a block are
+//! watched by the time the filters of the following blocks are checked. A block that the peer has
+//! no filter for is downloaded, as it can't be ruled out.
+//!
+//! The filters are checked against the filter headers, which are requested from several peers.
+//! A filter is only used if the peers that sent the headers of its block and of the previous block
+//! agree on them; otherwise the block is downloaded.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use common::{
+    chain::{
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            tx_inclusion_proof::TxInclusionProof,
+        },
+        Block, Destination, OutPoint, OutPointSourceId, TxOutput,
+    },
+    primitives::{Id, Idable},
+};
+use logging::log;
+use serialization::Encode;
+use utils::ensure;
+
+use crate::{
+    error::{P2pError, ProtocolError},
+    interface::types::RelevantTransaction,
+};
+
+use super::BLOCK_FILTER_LIMIT;
+
+/// The encoded destinations and outpoints that a light node looks for in the block filters
+#[derive(Default)]
+pub struct WatchedItems {
+    items: BTreeSet<Vec<u8>>,
+}
+
+impl WatchedItems {
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn add_destination(&mut self, destination: &Destination) {
+        self.items.insert(destination.encode());
+    }
+
+    fn add_outpoint(&mut self, outpoint: &OutPoint) {
+        self.items.insert(outpoint.encode());
+    }
+
+    fn contains_outpoint(&self, outpoint: &OutPoint) -> bool {
+        self.items.contains(&outpoint.encode())
+    }
+
+    fn is_watched_output(&self, output: &TxOutput) -> bool {
+        match output.purpose().destination() {
+            Some(destination) => self.items.contains(&destination.encode()),
+            None => false,
+        }
+    }
+
+    /// Returns true if the filter of the block may contain any of the watched items
+    pub fn matches(&self, block_id: &Id<Block>, filter: &BlockFilter) -> bool {
+        filter.matches_any(block_id, self.items.iter().map(Vec::as_slice))
+    }
+
+    /// Returns the transactions of the block that send to the watched destinations or spend the
+    /// watched outputs; the outputs sent to the watched destinations are watched from now on
+    pub fn scan_block(&mut self, block: &Block) -> Vec<RelevantTransaction> {
+        let block_id = block.get_id();
+
+        // The block reward can't be proven to be in the block, but its outputs can be spent later
+        let reward_source = OutPointSourceId::BlockReward(block_id.into());
+        for (index, output) in block.block_reward().outputs().iter().enumerate() {
+            if self.is_watched_output(output) {
+                self.add_outpoint(&OutPoint::new(reward_source.clone(), index as u32));
+            }
+        }
+
+        let mut relevant = Vec::new();
+        for tx in block.transactions() {
+            let tx_id = tx.transaction().get_id();
+            let spends_watched =
+                tx.inputs().iter().any(|input| self.contains_outpoint(input.outpoint()));
+
+            let mut sends_to_watched = false;
+            for (index, output) in tx.outputs().iter().enumerate() {
+                if self.is_watched_output(output) {
+                    sends_to_watched = true;
+                    self.add_outpoint(&OutPoint::new(tx_id.into(), index as u32));
+                }
+            }
+
+            if spends_watched || sends_to_watched {
+                let inclusion_proof = TxInclusionProof::from_block(block, &tx_id)
+                    .expect("The transaction is in the block");
+                relevant.push(RelevantTransaction {
+                    transaction: tx.clone(),
+                    inclusion_proof,
+                });
+            }
+        }
+        relevant
+    }
+}
+
+/// Progress of a light node in scanning the blocks whose headers were received from a peer
+#[derive(Default)]
+pub struct FilterScan {
+    /// Blocks whose filters are still to be requested, in chain order, with their previous blocks
+    /// unless it's the genesis
+    pending: VecDeque<(Id<Block>, Option<Id<Block>>)>,
+
+    /// Blocks of the filter request sent to the peer whose filters are not checked yet
+    requested_filters: Vec<(Id<Block>, Option<Id<Block>>)>,
+
+    /// Filters received for the requested blocks, kept until the filter headers are received
+    received_filters: Option<Vec<Option<BlockFilter>>>,
+
+    /// Filter headers received from several peers to check the requested filters
+    filter_headers: FilterHeaders,
+
+    /// Blocks whose filters were checked and that are still to be scanned, with `None` if the
+    /// filter of the block is missing or can't be checked
+    received: VecDeque<(Id<Block>, Option<BlockFilter>)>,
+
+    /// Block requested from the peer because its filter matched
+    requested_block: Option<Id<Block>>,
+}
+
+/// The blocks whose filters are requested from the scanned peer, and the blocks whose filter
+/// headers are requested from several peers to check these filters
+#[derive(Debug, PartialEq, Eq)]
+pub struct FilterRequest {
+    pub filter_block_ids: Vec<Id<Block>>,
+    pub filter_header_block_ids: Vec<Id<Block>>,
+}
+
+/// The filter headers received from several peers for the blocks of a filter request
+#[derive(Default)]
+struct FilterHeaders {
+    /// The header of each block the peers agree on, `None` if they don't
+    headers: BTreeMap<Id<Block>, Option<BlockFilterHeader>>,
+
+    /// Blocks whose filter headers were also sent by the scanned peer
+    sent_by_scanned_peer: BTreeSet<Id<Block>>,
+
+    /// Number of filter header responses still expected
+    awaited_responses: usize,
+}
+
+impl FilterHeaders {
+    fn add(
+        &mut self,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+        from_scanned_peer: bool,
+    ) {
+        for (block_id, header) in filter_headers {
+            if from_scanned_peer {
+                self.sent_by_scanned_peer.insert(block_id);
+            }
+            self.headers
+                .entry(block_id)
+                .and_modify(|agreed| {
+                    if *agreed != Some(header) {
+                        *agreed = None
+                    }
+                })
+                .or_insert(Some(header));
+        }
+    }
+
+    /// The agreed filter header of the block, the genesis is preceded by the zero header
+    fn get(&self, block_id: Option<&Id<Block>>) -> Option<BlockFilterHeader> {
+        match block_id {
+            Some(block_id) => self.headers.get(block_id).copied().flatten(),
+            None => Some(BlockFilterHeader::zero()),
+        }
+    }
+
+    fn sent_by_scanned_peer(&self, block_id: Option<&Id<Block>>) -> bool {
+        match block_id {
+            Some(block_id) => self.sent_by_scanned_peer.contains(block_id),
+            None => true,
+        }
+    }
+}
+
+/// Match a response to the blocks of a request, fails unless the response is for the requested
+/// blocks in the requested order; the blocks the response skips are `None`
+pub fn match_response<V>(
+    requested: impl IntoIterator<Item = Id<Block>>,
+    response: Vec<(Id<Block>, V)>,
+) -> crate::Result<Vec<Option<V>>> {
+    let mut response = response.into_iter().peekable();
+    let matched = requested
+        .into_iter()
+        .map(|block_id| response.next_if(|(id, _)| *id == block_id).map(|(_, value)| value))
+        .collect();
+    ensure!(
+        response.next().is_none(),
+        P2pError::ProtocolError(ProtocolError::InvalidMessage),
+    );
+    Ok(matched)
+}
+
+impl FilterScan {
+    /// Queue new blocks for scanning, with their previous blocks unless it's the genesis
+    pub fn add_blocks(&mut self, blocks: impl IntoIterator<Item = (Id<Block>, Option<Id<Block>>)>) {
+        self.pending.extend(blocks);
+    }
+
+    /// Take the next blocks to request the filters of, unless a filter request is outstanding.
+    ///
+    /// The filter headers of the blocks and of their previous blocks are requested too, and fit in
+    /// a single request.
+    pub fn next_filter_request(&mut self) -> Option<FilterRequest> {
+        if !self.requested_filters.is_empty() || self.pending.is_empty() {
+            return None;
+        }
+
+        let count = std::cmp::min(self.pending.len(), BLOCK_FILTER_LIMIT / 2);
+        self.requested_filters = self.pending.drain(..count).collect();
+        self.filter_headers = FilterHeaders::default();
+
+        let mut filter_header_block_ids = Vec::new();
+        let mut added = BTreeSet::new();
+        for (block_id, prev_block_id) in &self.requested_filters {
+            for id in prev_block_id.iter().chain(std::iter::once(block_id)) {
+                if added.insert(*id) {
+                    filter_header_block_ids.push(*id);
+                }
+            }
+        }
+        Some(FilterRequest {
+            filter_block_ids: self.requested_filters.iter().map(|(id, _)| *id).collect(),
+            filter_header_block_ids,
+        })
+    }
+
+    /// Set how many peers the filter headers of the last filter request were requested from
+    pub fn expect_filter_headers(&mut self, response_count: usize) {
+        self.filter_headers.awaited_responses = response_count;
+    }
+
+    /// Record the response to a filter request, fails unless the filters are for the requested
+    /// blocks in the requested order
+    pub fn register_filter_response(
+        &mut self,
+        filters: Vec<(Id<Block>, BlockFilter)>,
+    ) -> crate::Result<()> {
+        ensure!(
+            !self.requested_filters.is_empty() && self.received_filters.is_none(),
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+
+        let block_ids = self.requested_filters.iter().map(|(id, _)| *id);
+        self.received_filters = Some(match_response(block_ids, filters)?);
+        self.check_filters()
+    }
+
+    /// Record the filter headers sent by a peer for the last filter request, already matched to
+    /// the request; fails if the scanned peer sent a filter that contradicts its own headers
+    pub fn register_filter_header_response(
+        &mut self,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+        from_scanned_peer: bool,
+    ) -> crate::Result<()> {
+        if self.filter_headers.awaited_responses == 0 {
+            return Ok(());
+        }
+        self.filter_headers.awaited_responses -= 1;
+        self.filter_headers.add(filter_headers, from_scanned_peer);
+        self.check_filters()
+    }
+
+    /// Once the filters and all the filter headers are received, keep the filters that match the
+    /// filter headers the peers agree on. The other blocks can't be ruled out.
+    fn check_filters(&mut self) -> crate::Result<()> {
+        if self.filter_headers.awaited_responses > 0 {
+            return Ok(());
+        }
+        let filters = match self.received_filters.take() {
+            Some(filters) => filters,
+            None => return Ok(()),
+        };
+        let filter_headers = std::mem::take(&mut self.filter_headers);
+        let requested = std::mem::take(&mut self.requested_filters);
+
+        for ((block_id, prev_block_id), filter) in requested.into_iter().zip(filters) {
+            let headers = (
+                filter_headers.get(prev_block_id.as_ref()),
+                filter_headers.get(Some(&block_id)),
+            );
+            let filter = match (filter, headers) {
+                (Some(filter), (Some(prev_header), Some(header))) => {
+                    if BlockFilterHeader::new(&filter, &prev_header) == header {
+                        Some(filter)
+                    } else {
+                        ensure!(
+                            !filter_headers.sent_by_scanned_peer(prev_block_id.as_ref())
+                                || !filter_headers.sent_by_scanned_peer(Some(&block_id)),
+                            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+                        );
+                        log::debug!("the filter of block {block_id} doesn't match its header");
+                        None
+                    }
+                }
+                (_, _) => None,
+            };
+            self.received.push_back((block_id, filter));
+        }
+        Ok(())
+    }
+
+    /// Skip the blocks whose filters don't match the watched items and take the next block to
+    /// download, unless a block request is outstanding
+    pub fn next_block_request(&mut self, watched_items: &WatchedItems) -> Option<Id<Block>> {
+        if self.requested_block.is_some() {
+            return None;
+        }
+
+        while let Some((block_id, filter)) = self.received.pop_front() {
+            let matches = match filter {
+                Some(filter) => watched_items.matches(&block_id, &filter),
+                None => true,
+            };
+            if matches {
+                self.requested_block = Some(block_id);
+                return Some(block_id);
+            }
+        }
+        None
+    }
+
+    /// Record the response to a block request, with `None` if the peer doesn't have the block;
+    /// fails if another block was sent
+    pub fn register_block_response(&mut self, block_id: Option<&Id<Block>>) -> crate::Result<()> {
+        let requested = self
+            .requested_block
+            .take()
+            .ok_or(P2pError::ProtocolError(ProtocolError::InvalidMessage))?;
+        if let Some(block_id) = block_id {
+            ensure!(
+                *block_id == requested,
+                P2pError::ProtocolError(ProtocolError::InvalidMessage),
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        chain::{
+            block::timestamp::BlockTimestamp, signature::inputsig::InputWitness,
+            tokens::OutputValue, OutputPurpose, SignedTransaction, Transaction, TxInput,
+        },
+        primitives::{Amount, H256},
+    };
+
+    fn block_id(n: u64) -> Id<Block> {
+        Id::new(H256::from_low_u64_be(n))
+    }
+
+    fn outpoint_source(n: u64) -> OutPointSourceId {
+        OutPointSourceId::Transaction(Id::new(H256::from_low_u64_be(n)))
+    }
+
+    fn destination(n: u64) -> Destination {
+        Destination::ScriptHash(Id::new(H256::from_low_u64_be(n)))
+    }
+
+    fn transaction(inputs: Vec<TxInput>, destination: Destination) -> SignedTransaction {
+        let witnesses = inputs.iter().map(|_| InputWitness::NoSignature(None)).collect();
+        let outputs = vec![TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(1)),
+            OutputPurpose::Transfer(destination),
+        )];
+        SignedTransaction::new(Transaction::new(0, inputs, outputs, 0).unwrap(), witnesses).unwrap()
+    }
+
+    fn block(prev_block_id: Id<Block>, transactions: Vec<SignedTransaction>) -> Block {
+        Block::new_with_no_consensus(
+            transactions,
+            prev_block_id.into(),
+            BlockTimestamp::from_int_seconds(1),
+        )
+        .unwrap()
+    }
+
+    /// A chain of blocks whose first block follows the genesis
+    fn chain(len: u64) -> Vec<(Id<Block>, Option<Id<Block>>)> {
+        (0..len).map(|n| (block_id(n), n.checked_sub(1).map(block_id))).collect()
+    }
+
+    /// The filter headers of a chain of filters whose first block follows the genesis
+    fn filter_headers(filters: &[(Id<Block>, BlockFilter)]) -> Vec<(Id<Block>, BlockFilterHeader)> {
+        let mut prev_header = BlockFilterHeader::zero();
+        filters
+            .iter()
+            .map(|(block_id, filter)| {
+                prev_header = BlockFilterHeader::new(filter, &prev_header);
+                (*block_id, prev_header)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn filter_responses() {
+        let mut scan = FilterScan::default();
+        let filter = BlockFilter::new(&block_id(1), []);
+        let batch = BLOCK_FILTER_LIMIT as u64 / 2;
+
+        // Nothing was requested
+        assert!(scan.register_filter_response(vec![]).is_err());
+
+        scan.add_blocks(chain(batch + 3));
+        let request = scan.next_filter_request().unwrap();
+        assert_eq!(request.filter_block_ids.len(), batch as usize);
+        // The first block follows the genesis, which has no filter header to request
+        assert_eq!(request.filter_header_block_ids, request.filter_block_ids);
+        assert_eq!(scan.next_filter_request(), None);
+        scan.expect_filter_headers(1);
+
+        // Out of order
+        assert!(scan
+            .register_filter_response(vec![
+                (block_id(2), filter.clone()),
+                (block_id(1), filter.clone())
+            ])
+            .is_err());
+        // Not requested
+        assert!(scan.register_filter_response(vec![(block_id(5000), filter.clone())]).is_err());
+
+        // Missing filters are allowed, the filters wait for the filter headers
+        scan.register_filter_response(vec![(block_id(1), filter.clone())]).unwrap();
+        assert!(scan.register_filter_response(vec![]).is_err());
+        assert!(scan.received.is_empty());
+
+        // Without the filter headers, the filters can't be checked
+        scan.register_filter_header_response(vec![], true).unwrap();
+        assert_eq!(scan.received.len(), batch as usize);
+        assert_eq!(scan.received[0], (block_id(0), None));
+        assert_eq!(scan.received[1], (block_id(1), None));
+
+        // The filter header of the block before the next ones is requested too
+        let request = scan.next_filter_request().unwrap();
+        assert_eq!(
+            request.filter_block_ids,
+            (batch..batch + 3).map(block_id).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            request.filter_header_block_ids,
+            (batch - 1..batch + 3).map(block_id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn filters_checked_against_headers() {
+        let filters: Vec<_> = (0..3)
+            .map(|n| {
+                (
+                    block_id(n),
+                    BlockFilter::new(&block_id(n), [[n as u8].as_slice()]),
+                )
+            })
+            .collect();
+        let headers = filter_headers(&filters);
+
+        // The peers disagree on the header of the last block, which is downloaded
+        let mut scan = FilterScan::default();
+        scan.add_blocks(chain(3));
+        scan.next_filter_request().unwrap();
+        scan.expect_filter_headers(3);
+        scan.register_filter_response(filters.clone()).unwrap();
+        scan.register_filter_header_response(headers.clone(), true).unwrap();
+        let mut other_headers = headers.clone();
+        other_headers[2].1 = BlockFilterHeader::zero();
+        scan.register_filter_header_response(other_headers, false).unwrap();
+        assert!(scan.received.is_empty());
+        scan.register_filter_header_response(headers[..1].to_vec(), false).unwrap();
+        assert_eq!(
+            Vec::from(scan.received.clone()),
+            vec![
+                (block_id(0), Some(filters[0].1.clone())),
+                (block_id(1), Some(filters[1].1.clone())),
+                (block_id(2), None),
+            ]
+        );
+
+        // A filter that doesn't match the headers of the other peers can't be ruled out
+        let mut scan = FilterScan::default();
+        scan.add_blocks(chain(2));
+        scan.next_filter_request().unwrap();
+        scan.expect_filter_headers(2);
+        scan.register_filter_response(vec![
+            filters[0].clone(),
+            (block_id(1), filters[2].1.clone()),
+        ])
+        .unwrap();
+        scan.register_filter_header_response(Vec::new(), true).unwrap();
+        scan.register_filter_header_response(headers[..2].to_vec(), false).unwrap();
+        assert_eq!(
+            Vec::from(scan.received.clone()),
+            vec![(block_id(0), Some(filters[0].1.clone())), (block_id(1), None),]
+        );
+
+        // The scanned peer sends a filter that contradicts its own headers
+        let mut scan = FilterScan::default();
+        scan.add_blocks(chain(2));
+        scan.next_filter_request().unwrap();
+        scan.expect_filter_headers(1);
+        scan.register_filter_response(vec![
+            filters[0].clone(),
+            (block_id(1), filters[2].1.clone()),
+        ])
+        .unwrap();
+        assert!(scan.register_filter_header_response(headers[..2].to_vec(), true).is_err());
+    }
+
+    #[test]
+    fn blocks_scanned_in_order() {
+        let mut watched_items = WatchedItems::default();
+        watched_items.add_destination(&destination(1));
+
+        let funding_tx = transaction(vec![TxInput::new(outpoint_source(1), 0)], destination(1));
+        let unrelated_tx = transaction(vec![TxInput::new(outpoint_source(2), 0)], destination(2));
+        let spending_tx = transaction(
+            vec![TxInput::new(funding_tx.transaction().get_id().into(), 0)],
+            destination(3),
+        );
+        let block0 = block(block_id(0), vec![unrelated_tx.clone(), funding_tx.clone()]);
+        let block1 = block(block0.get_id(), vec![unrelated_tx]);
+        let block2 = block(block1.get_id(), vec![spending_tx.clone()]);
+        let blocks = [block0, block1, block2];
+
+        let filters: Vec<_> = blocks
+            .iter()
+            .map(|block| (block.get_id(), BlockFilter::from_block(block)))
+            .collect();
+
+        // The first block follows the genesis
+        let mut scan = FilterScan::default();
+        scan.add_blocks(blocks.iter().scan(None, |prev_block_id, block| {
+            Some((block.get_id(), prev_block_id.replace(block.get_id())))
+        }));
+        scan.next_filter_request().unwrap();
+        scan.expect_filter_headers(1);
+        scan.register_filter_response(filters.clone()).unwrap();
+        scan.register_filter_header_response(filter_headers(&filters), true).unwrap();
+
+        // The spending block only matches once the funding output is watched
+        assert!(!watched_items.matches(&blocks[2].get_id(), &BlockFilter::from_block(&blocks[2])));
+
+        assert_eq!(
+            scan.next_block_request(&watched_items),
+            Some(blocks[0].get_id())
+        );
+        assert_eq!(scan.next_block_request(&watched_items), None);
+        assert!(scan.register_block_response(Some(&blocks[1].get_id())).is_err());
+        scan.requested_block = Some(blocks[0].get_id());
+        scan.register_block_response(Some(&blocks[0].get_id())).unwrap();
+
+        let relevant = watched_items.scan_block(&blocks[0]);
+        assert_eq!(relevant.len(), 1);
+        assert_eq!(relevant[0].transaction, funding_tx);
+        assert!(relevant[0]
+            .inclusion_proof
+            .verify_with_header(&relevant[0].transaction, blocks[0].header()));
+
+        assert_eq!(
+            scan.next_block_request(&watched_items),
+            Some(blocks[2].get_id())
+        );
+        scan.register_block_response(Some(&blocks[2].get_id())).unwrap();
+        let relevant = watched_items.scan_block(&blocks[2]);
+        assert_eq!(relevant.len(), 1);
+        assert_eq!(relevant[0].transaction, spending_tx);
+        assert!(relevant[0]
+            .inclusion_proof
+            .verify_with_header(&relevant[0].transaction, blocks[2].header()));
+
+        assert_eq!(scan.next_block_request(&watched_items), None);
+        assert!(scan.register_block_response(None).is_err());
+    }
+}
@@ -18,36 +18,77 @@
 
 pub mod peer;
 
+mod filter_scan;
 mod request;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    sync::Arc,
+    time::Duration,
+};
 
-use tokio::sync::mpsc;
+use tokio::{sync::mpsc, time::Instant};
 use void::Void;
 
-use chainstate::{ban_score::BanScore, chainstate_interface, BlockError, ChainstateError, Locator};
+use chainstate::{
+    ban_score::BanScore, chainstate_interface, BlockError, ChainstateError, Locator,
+    MIN_BLOCKS_TO_KEEP_WHEN_PRUNING,
+};
 use common::{
     chain::{
-        block::{Block, BlockHeader},
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            calculate_tx_merkle_root, calculate_witness_merkle_root, Block, BlockHeader,
+        },
         config::ChainConfig,
+        Destination, GenBlockId, SignedTransaction, Transaction,
     },
     primitives::{Id, Idable},
 };
 use logging::log;
+use mempool::{MempoolEvent, MempoolHandle};
 use utils::{ensure, tap_error_log::LogError};
 
 use crate::{
-    config::P2pConfig,
+    config::{NodeType, P2pConfig},
     error::{P2pError, PeerError, ProtocolError},
     event::{PeerManagerEvent, SyncControlEvent},
+    interface::types::RelevantTransaction,
     message::{self, Announcement, SyncRequest},
-    net::{types::SyncingEvent, NetworkingService, SyncingMessagingService},
+    net::{
+        types::{BlockServing, SyncingEvent},
+        NetworkingService, SyncingMessagingService,
+    },
     utils::oneshot_nofail,
 };
 
 // TODO: from config? global constant?
 const HEADER_LIMIT: usize = 2000;
 
+/// The maximum number of block filters or filter headers that can be requested at once
+const BLOCK_FILTER_LIMIT: usize = 1000;
+
+/// The number of peers the filter headers are requested from, including the peer whose filters
+/// they check
+const FILTER_HEADER_PEER_COUNT: usize = 3;
+
+/// The maximum number of relevant transactions kept by a light node, the oldest ones are forgotten
+const MAX_RELEVANT_TRANSACTIONS: usize = 10_000;
+
+/// The maximum number of transactions requested from a peer and not received yet
+const MAX_REQUESTED_TRANSACTIONS: usize = 1000;
+
+/// How long to wait for the response to a transaction request before requesting the
+/// transaction from another peer that announced it
+const TRANSACTION_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a late response to a timed out transaction request is dropped without penalizing
+/// the peer
+const LATE_TRANSACTION_RESPONSE_PERIOD: Duration = Duration::from_secs(60);
+
+/// How often the transaction requests are checked for the timeout
+const TRANSACTION_REQUEST_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
 // TODO: add more tests
 // TODO: cache locator and invalidate it when `NewTip` event is received
 
@@ -64,7 +105,7 @@ pub struct BlockSyncManager<T: NetworkingService> {
     chain_config: Arc<ChainConfig>,
 
     /// The p2p configuration.
-    _p2p_config: Arc<P2pConfig>,
+    p2p_config: Arc<P2pConfig>,
 
     /// Handle for sending/receiving syncing events
     peer_sync_handle: T::SyncingMessagingHandle,
@@ -80,6 +121,20 @@ pub struct BlockSyncManager<T: NetworkingService> {
 
     /// Subsystem handle to Chainstate
     chainstate_handle: subsystem::Handle<Box<dyn chainstate_interface::ChainstateInterface>>,
+
+    /// Subsystem handle to Mempool
+    mempool_handle: MempoolHandle,
+
+    /// Items looked up in the block filters by a light node
+    watched_items: filter_scan::WatchedItems,
+
+    /// Transactions found by a light node for the watched items in the blocks of the main chain,
+    /// oldest first
+    relevant_transactions: VecDeque<RelevantTransaction>,
+
+    /// The other peers that announced a transaction requested from some peer, in the order of
+    /// the announcements. The transaction is requested from them if the request fails.
+    transaction_announcers: BTreeMap<Id<Transaction>, VecDeque<T::PeerId>>,
 }
 
 /// Syncing manager
@@ -95,20 +150,51 @@ where
         p2p_config: Arc<P2pConfig>,
         handle: T::SyncingMessagingHandle,
         chainstate_handle: subsystem::Handle<Box<dyn chainstate_interface::ChainstateInterface>>,
+        mempool_handle: MempoolHandle,
         rx_sync: mpsc::UnboundedReceiver<SyncControlEvent<T>>,
         tx_peer_manager: mpsc::UnboundedSender<PeerManagerEvent<T>>,
     ) -> Self {
         Self {
             chain_config,
-            _p2p_config: p2p_config,
+            p2p_config,
             peer_sync_handle: handle,
             rx_sync,
             tx_peer_manager,
             chainstate_handle,
+            mempool_handle,
             peers: Default::default(),
+            watched_items: Default::default(),
+            relevant_transactions: VecDeque::new(),
+            transaction_announcers: BTreeMap::new(),
         }
     }
 
+    /// Light nodes only sync the headers and don't have any blocks to serve
+    fn is_light_node(&self) -> bool {
+        matches!(*self.p2p_config.node_type, NodeType::Light)
+    }
+
+    /// Only full nodes exchange transactions, the other node types ignore them
+    fn relays_transactions(&self) -> bool {
+        matches!(*self.p2p_config.node_type, NodeType::Full)
+    }
+
+    /// Look for the transactions sending to the destination, or spending what was sent to it, in
+    /// the blocks whose headers are received from now on
+    pub fn watch_destination(&mut self, destination: &Destination) -> crate::Result<()> {
+        ensure!(
+            self.is_light_node(),
+            P2pError::Other("Only light nodes watch destinations"),
+        );
+        self.watched_items.add_destination(destination);
+        Ok(())
+    }
+
+    /// Transactions found for the watched destinations, with the proofs of their inclusion
+    pub fn relevant_transactions(&self) -> Vec<RelevantTransaction> {
+        self.relevant_transactions.iter().cloned().collect()
+    }
+
     /// Get mutable reference to the handle
     pub fn handle_mut(&mut self) -> &mut T::SyncingMessagingHandle {
         // TODO: get rid of this function as it's used only in tests; perhaps a better way to do this is by
@@ -118,7 +204,11 @@ where
     }
 
     /// Register peer to the `SyncManager`
-    pub async fn register_peer(&mut self, peer_id: T::PeerId) -> crate::Result<()> {
+    pub async fn register_peer(
+        &mut self,
+        peer_id: T::PeerId,
+        block_serving: BlockServing,
+    ) -> crate::Result<()> {
         ensure!(
             !self.peers.contains_key(&peer_id),
             P2pError::PeerError(PeerError::PeerAlreadyExists),
@@ -133,14 +223,34 @@ where
         .map(|_| {
             self.peers.insert(
                 peer_id,
-                peer::PeerContext::new_with_locator(peer_id, locator),
+                peer::PeerContext::new_with_locator(peer_id, block_serving, locator),
             );
         })
     }
 
     /// Unregister peer from the `SyncManager`
-    pub fn unregister_peer(&mut self, peer_id: T::PeerId) {
-        self.peers.remove(&peer_id);
+    ///
+    /// The transactions requested from the peer are requested from the other peers that
+    /// announced them.
+    pub async fn unregister_peer(&mut self, peer_id: T::PeerId) -> crate::Result<()> {
+        let (requested_transactions, scanned_peers) = match self.peers.remove(&peer_id) {
+            Some(mut peer) => (
+                peer.take_transaction_requests(),
+                peer.take_filter_header_requests(),
+            ),
+            None => (Vec::new(), Vec::new()),
+        };
+        for announcers in self.transaction_announcers.values_mut() {
+            announcers.retain(|announcer| *announcer != peer_id);
+        }
+        for tx_id in requested_transactions {
+            self.retry_transaction_request(tx_id).await?;
+        }
+        // The filter headers the peer won't send are missing
+        for scanned_peer in scanned_peers {
+            self.add_filter_headers(scanned_peer, Vec::new(), false).await?;
+        }
+        Ok(())
     }
 
     /// Process header request
@@ -152,6 +262,12 @@ where
     ) -> crate::Result<()> {
         log::debug!("send header response to peer {peer_id}, request_id: {request_id:?}");
 
+        // The peer would ask for the blocks of the headers, so a light node reports that it has
+        // nothing newer
+        if self.is_light_node() {
+            return self.send_header_response(request_id, Vec::new());
+        }
+
         // TODO: check if remote has already asked for these headers?
         let headers = self.chainstate_handle.call(move |this| this.get_headers(locator)).await??;
         self.send_header_response(request_id, headers)
@@ -183,13 +299,100 @@ where
         match block_result {
             Ok(Some(block)) => self.send_block_response(request_id, vec![block]),
             Ok(None) => {
-                // TODO: check if remote has already asked for these headers?
-                Err(P2pError::ProtocolError(ProtocolError::InvalidMessage))
+                // The block is unknown or has been pruned, which isn't the fault of the peer
+                log::debug!("block {block_id} requested by peer {peer_id} is not available");
+                self.send_block_response(request_id, Vec::new())
             }
             Err(err) => Err(P2pError::ChainstateError(err)),
         }
     }
 
+    /// Process block filter request
+    ///
+    /// The blocks that are not in the main chain are skipped.
+    pub async fn process_block_filter_request(
+        &mut self,
+        peer_id: T::PeerId,
+        request_id: T::PeerRequestId,
+        block_ids: Vec<Id<Block>>,
+    ) -> crate::Result<()> {
+        ensure!(
+            block_ids.len() <= BLOCK_FILTER_LIMIT,
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+        log::debug!("send block filter response to peer {peer_id}, request_id: {request_id:?}");
+
+        let filters = self
+            .chainstate_handle
+            .call(move |this| {
+                block_ids
+                    .into_iter()
+                    .filter_map(|id| this.get_block_filter(id).transpose().map(|f| Ok((id, f?))))
+                    .collect::<Result<Vec<_>, ChainstateError>>()
+            })
+            .await??;
+        self.send_block_filter_response(request_id, filters)
+    }
+
+    /// Process block filter header request
+    ///
+    /// The blocks that are not in the main chain are skipped.
+    pub async fn process_block_filter_header_request(
+        &mut self,
+        peer_id: T::PeerId,
+        request_id: T::PeerRequestId,
+        block_ids: Vec<Id<Block>>,
+    ) -> crate::Result<()> {
+        ensure!(
+            block_ids.len() <= BLOCK_FILTER_LIMIT,
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+        log::debug!(
+            "send block filter header response to peer {peer_id}, request_id: {request_id:?}"
+        );
+
+        let filter_headers = self
+            .chainstate_handle
+            .call(move |this| {
+                block_ids
+                    .into_iter()
+                    .filter_map(|id| {
+                        this.get_block_filter_header(id).transpose().map(|h| Ok((id, h?)))
+                    })
+                    .collect::<Result<Vec<_>, ChainstateError>>()
+            })
+            .await??;
+        self.send_block_filter_header_response(request_id, filter_headers)
+    }
+
+    /// Process transaction request
+    ///
+    /// The response is empty if the transaction isn't in the mempool.
+    pub async fn process_transaction_request(
+        &mut self,
+        peer_id: T::PeerId,
+        request_id: T::PeerRequestId,
+        tx_id: Id<Transaction>,
+    ) -> crate::Result<()> {
+        log::debug!("send transaction response to peer {peer_id}, request_id: {request_id:?}");
+
+        let transaction = if self.relays_transactions() {
+            self.mempool_handle
+                .call_async(move |this| Box::pin(async move { this.transaction(&tx_id).await }))
+                .await??
+        } else {
+            None
+        };
+
+        if transaction.is_some() {
+            self.peers
+                .get_mut(&peer_id)
+                .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+                .add_known_transaction(tx_id);
+        }
+        self.send_transaction_response(request_id, tx_id, transaction)
+    }
+
     /// Validate incoming header response
     async fn validate_header_response(
         &mut self,
@@ -201,6 +404,7 @@ where
             P2pError::ProtocolError(ProtocolError::InvalidMessage),
         );
 
+        let downloads_blocks = !self.is_light_node();
         let peer = self
             .peers
             .get_mut(peer_id)
@@ -239,6 +443,14 @@ where
             .call(|this| this.filter_already_existing_blocks(headers))
             .await?
         {
+            Ok(headers) if downloads_blocks && !peer_has_blocks(peer.block_serving(), &headers) => {
+                log::debug!(
+                    "peer {peer_id} doesn't keep the {} blocks it announced",
+                    headers.len()
+                );
+                peer.register_header_response(&[]);
+                Ok(None)
+            }
             Ok(headers) => {
                 peer.register_header_response(&headers);
                 Ok(peer.get_header_for_download())
@@ -254,18 +466,324 @@ where
         headers: Vec<BlockHeader>,
     ) -> crate::Result<()> {
         match self.validate_header_response(&peer_id, headers).await {
+            Ok(Some(header)) if self.is_light_node() => {
+                self.process_light_node_headers(peer_id, header).await
+            }
             Ok(Some(header)) => self.send_block_request(peer_id, header.get_id()),
             Ok(None) => {
                 self.peers
                     .get_mut(&peer_id)
                     .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
                     .set_state(peer::PeerSyncState::Idle);
-                Ok(())
+                self.request_utxo_snapshot_history(peer_id).await
             }
             Err(err) => Err(err),
         }
     }
 
+    /// Once the peer has no new blocks to send, download from it the blocks below a loaded utxo
+    /// snapshot, which are needed to validate the history of the snapshot
+    async fn request_utxo_snapshot_history(&mut self, peer_id: T::PeerId) -> crate::Result<()> {
+        let block_serving = self
+            .peers
+            .get(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .block_serving();
+        // Pruned peers don't keep the old blocks
+        if self.is_light_node() || !matches!(block_serving, BlockServing::Full) {
+            return Ok(());
+        }
+
+        let headers = self
+            .chainstate_handle
+            .call(|this| this.get_utxo_snapshot_history_headers(HEADER_LIMIT))
+            .await??;
+        let peer = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?;
+        peer.register_header_response(&headers);
+        match peer.get_header_for_download() {
+            Some(header) => self.send_block_request(peer_id, header.get_id()),
+            None => Ok(()),
+        }
+    }
+
+    /// Add the new headers of a header response to the chainstate of a light node and ask the
+    /// peer for more headers
+    async fn process_light_node_headers(
+        &mut self,
+        peer_id: T::PeerId,
+        first_header: BlockHeader,
+    ) -> crate::Result<()> {
+        let peer = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?;
+        let headers: Vec<_> = std::iter::once(first_header)
+            .chain(std::iter::from_fn(|| peer.get_header_for_download()))
+            .collect();
+
+        let mut new_blocks = Vec::new();
+        let mut new_tip = false;
+        for header in headers {
+            let block = self.scanned_block(&header);
+            match self.chainstate_handle.call_mut(move |this| this.process_header(header)).await? {
+                Ok(tip) => {
+                    new_blocks.push(block);
+                    new_tip |= tip.is_some();
+                }
+                Err(ChainstateError::ProcessBlockError(BlockError::BlockAlreadyExists(_id))) => {}
+                Err(err) => return Err(P2pError::ChainstateError(err)),
+            }
+        }
+        if new_tip {
+            self.drop_reorged_relevant_transactions().await?;
+        }
+        self.scan_blocks(peer_id, new_blocks)?;
+
+        let locator = self.chainstate_handle.call(|this| this.get_locator()).await??;
+        self.send_header_request(peer_id, locator)
+    }
+
+    /// The block of a header with its previous block, as scanned by a light node
+    fn scanned_block(&self, header: &BlockHeader) -> (Id<Block>, Option<Id<Block>>) {
+        let prev_block_id = match header.prev_block_id().classify(&self.chain_config) {
+            GenBlockId::Genesis(_) => None,
+            GenBlockId::Block(prev_block_id) => Some(prev_block_id),
+        };
+        (header.get_id(), prev_block_id)
+    }
+
+    /// Have a light node look for the watched items in the filters of new blocks served by the
+    /// peer, given with their previous blocks
+    fn scan_blocks(
+        &mut self,
+        peer_id: T::PeerId,
+        blocks: Vec<(Id<Block>, Option<Id<Block>>)>,
+    ) -> crate::Result<()> {
+        if self.watched_items.is_empty() || blocks.is_empty() {
+            return Ok(());
+        }
+
+        self.peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .filter_scan_mut()
+            .add_blocks(blocks);
+        self.request_block_filters(peer_id)
+    }
+
+    /// Request the next filters to scan from the peer, unless a request is outstanding. Their
+    /// filter headers are requested from the peer and from other peers to check them.
+    fn request_block_filters(&mut self, peer_id: T::PeerId) -> crate::Result<()> {
+        let request = match self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .filter_scan_mut()
+            .next_filter_request()
+        {
+            Some(request) => request,
+            None => return Ok(()),
+        };
+
+        let header_peers: Vec<_> = std::iter::once(peer_id)
+            .chain(self.peers.keys().copied().filter(|id| *id != peer_id))
+            .take(FILTER_HEADER_PEER_COUNT)
+            .collect();
+        for header_peer in &header_peers {
+            self.peers
+                .get_mut(header_peer)
+                .expect("the peer exists")
+                .register_filter_header_request(peer_id, request.filter_header_block_ids.clone());
+            let header_request =
+                self.make_block_filter_header_request(request.filter_header_block_ids.clone());
+            self.send_request(*header_peer, header_request)?;
+        }
+        self.peers
+            .get_mut(&peer_id)
+            .expect("the peer exists")
+            .filter_scan_mut()
+            .expect_filter_headers(header_peers.len());
+
+        let filter_request = self.make_block_filter_request(request.filter_block_ids);
+        self.send_request(peer_id, filter_request)
+    }
+
+    /// Request the next block whose filter matches the watched items from the peer, unless a
+    /// request is outstanding
+    fn request_relevant_block(&mut self, peer_id: T::PeerId) -> crate::Result<()> {
+        let block_id = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .filter_scan_mut()
+            .next_block_request(&self.watched_items);
+        match block_id {
+            Some(block_id) => {
+                let request = self.make_block_request(vec![block_id]);
+                self.send_request(peer_id, request)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Process the block filters requested by a light node
+    pub fn process_block_filter_response(
+        &mut self,
+        peer_id: T::PeerId,
+        filters: Vec<(Id<Block>, BlockFilter)>,
+    ) -> crate::Result<()> {
+        self.peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .filter_scan_mut()
+            .register_filter_response(filters)?;
+        self.request_block_filters(peer_id)?;
+        self.request_relevant_block(peer_id)
+    }
+
+    /// Process the filter headers requested by a light node to check the filters of a peer
+    pub async fn process_block_filter_header_response(
+        &mut self,
+        peer_id: T::PeerId,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+    ) -> crate::Result<()> {
+        let scanned_peer = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .register_filter_header_response(&filter_headers)?;
+        self.add_filter_headers(scanned_peer, filter_headers, scanned_peer == peer_id)
+            .await
+    }
+
+    /// Add the filter headers received from a peer to the filter scan of the scanned peer, and
+    /// continue the scan if its filters are checked. The scanned peer is penalized if its filters
+    /// contradict its own headers.
+    async fn add_filter_headers(
+        &mut self,
+        scanned_peer: T::PeerId,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+        from_scanned_peer: bool,
+    ) -> crate::Result<()> {
+        let result = match self.peers.get_mut(&scanned_peer) {
+            Some(peer) => peer
+                .filter_scan_mut()
+                .register_filter_header_response(filter_headers, from_scanned_peer),
+            // The scanned peer disconnected in the meantime
+            None => return Ok(()),
+        };
+        let result = result
+            .and_then(|()| self.request_block_filters(scanned_peer))
+            .and_then(|()| self.request_relevant_block(scanned_peer));
+        self.handle_error(scanned_peer, result).await
+    }
+
+    /// Keep the relevant transactions found by a light node in a block of the main chain,
+    /// forgetting the oldest ones over the limit
+    async fn add_relevant_transactions(
+        &mut self,
+        block_id: Id<Block>,
+        relevant_transactions: Vec<RelevantTransaction>,
+    ) -> crate::Result<()> {
+        if relevant_transactions.is_empty() {
+            return Ok(());
+        }
+        let in_main_chain = self
+            .chainstate_handle
+            .call(move |this| this.is_block_in_main_chain(&block_id))
+            .await??;
+        if !in_main_chain {
+            log::debug!("block {block_id} left the main chain, its transactions are not kept");
+            return Ok(());
+        }
+
+        self.relevant_transactions.extend(relevant_transactions);
+        let excess = self.relevant_transactions.len().saturating_sub(MAX_RELEVANT_TRANSACTIONS);
+        self.relevant_transactions.drain(..excess);
+        Ok(())
+    }
+
+    /// Forget the relevant transactions of the blocks that left the main chain of a light node
+    async fn drop_reorged_relevant_transactions(&mut self) -> crate::Result<()> {
+        if self.relevant_transactions.is_empty() {
+            return Ok(());
+        }
+
+        let block_ids: BTreeSet<Id<Block>> = self
+            .relevant_transactions
+            .iter()
+            .map(|tx| *tx.inclusion_proof.block_id())
+            .collect();
+        let reorged_block_ids = self
+            .chainstate_handle
+            .call(move |this| {
+                block_ids
+                    .into_iter()
+                    .filter_map(|id| match this.is_block_in_main_chain(&id) {
+                        Ok(true) => None,
+                        Ok(false) => Some(Ok(id)),
+                        Err(err) => Some(Err(err)),
+                    })
+                    .collect::<Result<BTreeSet<_>, ChainstateError>>()
+            })
+            .await??;
+        if !reorged_block_ids.is_empty() {
+            log::debug!(
+                "dropping the relevant transactions of {} blocks that left the main chain",
+                reorged_block_ids.len()
+            );
+            self.relevant_transactions
+                .retain(|tx| !reorged_block_ids.contains(tx.inclusion_proof.block_id()));
+        }
+        Ok(())
+    }
+
+    /// Process a block requested by a light node because its filter matched, and keep its
+    /// transactions relevant to the watched items
+    async fn process_relevant_block_response(
+        &mut self,
+        peer_id: T::PeerId,
+        blocks: Vec<Block>,
+    ) -> crate::Result<()> {
+        ensure!(
+            blocks.len() <= 1,
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+        let block = blocks.into_iter().next();
+        self.peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .filter_scan_mut()
+            .register_block_response(block.as_ref().map(Idable::get_id).as_ref())?;
+
+        match block {
+            Some(block) => {
+                // The header was validated when it was received, so the body only has to match it
+                ensure!(
+                    calculate_tx_merkle_root(block.body()).ok() == Some(block.merkle_root())
+                        && calculate_witness_merkle_root(block.body()).ok()
+                            == Some(block.witness_merkle_root()),
+                    P2pError::ProtocolError(ProtocolError::InvalidMessage),
+                );
+                let relevant_transactions = self.watched_items.scan_block(&block);
+                log::debug!(
+                    "found {} relevant transactions in block {}",
+                    relevant_transactions.len(),
+                    block.get_id(),
+                );
+                self.add_relevant_transactions(block.get_id(), relevant_transactions).await?;
+            }
+            None => {
+                log::debug!("peer {peer_id} doesn't have the requested block, it isn't scanned")
+            }
+        }
+
+        self.request_relevant_block(peer_id)
+    }
+
     /// Validate incoming block response
     async fn validate_block_response(
         &mut self,
@@ -308,6 +826,21 @@ where
         peer_id: T::PeerId,
         blocks: Vec<Block>,
     ) -> crate::Result<()> {
+        // Light nodes only download the blocks that may have relevant transactions
+        if self.is_light_node() {
+            return self.process_relevant_block_response(peer_id, blocks).await;
+        }
+
+        // An empty response means that the peer doesn't have the requested block anymore
+        if blocks.is_empty() {
+            log::debug!("peer {peer_id} doesn't have the requested block");
+            return self
+                .peers
+                .get_mut(&peer_id)
+                .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+                .register_block_not_available();
+        }
+
         // TODO: remove the limitation of sending only one block, and allow sending multiple blocks (up to a cap)
         ensure!(
             blocks.len() == 1,
@@ -325,6 +858,50 @@ where
         }
     }
 
+    /// Process transaction response
+    ///
+    /// The received transaction is added to the mempool; the peer is penalized if the transaction
+    /// can never be valid.
+    pub async fn process_transaction_response(
+        &mut self,
+        peer_id: T::PeerId,
+        tx_id: Id<Transaction>,
+        transaction: Option<SignedTransaction>,
+    ) -> crate::Result<()> {
+        let requested = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .register_transaction_response(&tx_id)?;
+        if !requested {
+            log::debug!("late response for transaction {tx_id} from peer {peer_id} dropped");
+            return Ok(());
+        }
+
+        // The transaction left the mempool of the peer in the meantime
+        let transaction = match transaction {
+            Some(transaction) => transaction,
+            None => return self.retry_transaction_request(tx_id).await,
+        };
+        self.transaction_announcers.remove(&tx_id);
+        ensure!(
+            transaction.transaction().get_id() == tx_id,
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+
+        let result = self
+            .mempool_handle
+            .call_async_mut(move |this| this.add_transaction(transaction))
+            .await?;
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                log::debug!("Transaction {tx_id} from peer {peer_id} rejected: {err}");
+                self.adjust_peer_score(peer_id, err.ban_score()).await
+            }
+        }
+    }
+
     pub async fn process_response(
         &mut self,
         peer_id: T::PeerId,
@@ -350,6 +927,35 @@ where
                 let result = self.process_block_response(peer_id, response.into_blocks()).await;
                 self.handle_error(peer_id, result).await?;
             }
+            // Only light nodes request filters, to find the blocks with relevant transactions
+            message::SyncResponse::BlockFilterListResponse(response) => {
+                log::debug!(
+                    "process block filter response (id {request_id:?}) from peer {peer_id}, # of filters: {}",
+                    response.filters().len(),
+                );
+
+                let result = self.process_block_filter_response(peer_id, response.into_filters());
+                self.handle_error(peer_id, result).await?;
+            }
+            // Light nodes request filter headers from several peers to check the filters
+            message::SyncResponse::BlockFilterHeaderListResponse(response) => {
+                log::debug!(
+                    "process block filter header response (id {request_id:?}) from peer {peer_id}, # of filter headers: {}",
+                    response.filter_headers().len(),
+                );
+
+                let result = self
+                    .process_block_filter_header_response(peer_id, response.into_filter_headers())
+                    .await;
+                self.handle_error(peer_id, result).await?;
+            }
+            message::SyncResponse::TransactionResponse(response) => {
+                log::debug!("process transaction response (id {request_id:?}) from peer {peer_id}");
+
+                let (tx_id, transaction) = response.into_parts();
+                let result = self.process_transaction_response(peer_id, tx_id, transaction).await;
+                self.handle_error(peer_id, result).await?;
+            }
         }
 
         Ok(())
@@ -365,7 +971,139 @@ where
         // blocks again, and again, wasting their bandwidth.
         match announcement {
             Announcement::Block(block) => self.process_block_announcement(peer_id, block).await,
+            Announcement::Transaction(tx_id) => {
+                let result = self.process_transaction_announcement(peer_id, tx_id).await;
+                self.handle_error(peer_id, result).await
+            }
+        }
+    }
+
+    /// Request an announced transaction from the peer, unless it is already in the mempool. If
+    /// the transaction is already requested from another peer, the announcing peer is remembered
+    /// to request the transaction from if that request fails.
+    async fn process_transaction_announcement(
+        &mut self,
+        peer_id: T::PeerId,
+        tx_id: Id<Transaction>,
+    ) -> crate::Result<()> {
+        if !self.relays_transactions() {
+            return Ok(());
+        }
+
+        let peer = self
+            .peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?;
+        peer.add_known_transaction(tx_id);
+        if self.peers.values().any(|peer| peer.has_requested_transaction(&tx_id)) {
+            if !self.peers[&peer_id].has_requested_transaction(&tx_id) {
+                let announcers = self.transaction_announcers.entry(tx_id).or_default();
+                if !announcers.contains(&peer_id) {
+                    announcers.push_back(peer_id);
+                }
+            }
+            return Ok(());
+        }
+        if self.peers[&peer_id].requested_transaction_count() >= MAX_REQUESTED_TRANSACTIONS {
+            log::debug!("too many transactions requested from peer {peer_id}, ignore {tx_id}");
+            return Ok(());
+        }
+
+        let in_mempool = self
+            .mempool_handle
+            .call_async(move |this| {
+                Box::pin(async move { this.contains_transaction(&tx_id).await })
+            })
+            .await??;
+        if in_mempool {
+            return Ok(());
         }
+
+        self.send_transaction_request(peer_id, tx_id)
+    }
+
+    /// Request the transaction from the next peer that announced it, after the request to another
+    /// peer failed. Peers that have too many transactions requested are skipped.
+    async fn retry_transaction_request(&mut self, tx_id: Id<Transaction>) -> crate::Result<()> {
+        let in_mempool = self
+            .mempool_handle
+            .call_async(move |this| {
+                Box::pin(async move { this.contains_transaction(&tx_id).await })
+            })
+            .await??;
+        if in_mempool {
+            self.transaction_announcers.remove(&tx_id);
+            return Ok(());
+        }
+
+        let mut announcers = self.transaction_announcers.remove(&tx_id).unwrap_or_default();
+        while let Some(peer_id) = announcers.pop_front() {
+            let available = self.peers.get(&peer_id).is_some_and(|peer| {
+                peer.requested_transaction_count() < MAX_REQUESTED_TRANSACTIONS
+            });
+            if available {
+                log::debug!("request transaction {tx_id} again from peer {peer_id}");
+                if !announcers.is_empty() {
+                    self.transaction_announcers.insert(tx_id, announcers);
+                }
+                return self.send_transaction_request(peer_id, tx_id);
+            }
+        }
+        log::debug!("no other peer to request transaction {tx_id} from");
+        Ok(())
+    }
+
+    /// Request the transactions again from other peers if the responses are overdue
+    pub async fn handle_transaction_request_timeouts(&mut self, now: Instant) -> crate::Result<()> {
+        let requested_before = match now.checked_sub(TRANSACTION_REQUEST_TIMEOUT) {
+            Some(time) => time,
+            None => return Ok(()),
+        };
+        let forget_before = requested_before.checked_sub(LATE_TRANSACTION_RESPONSE_PERIOD);
+        let mut expired = Vec::new();
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if let Some(forget_before) = forget_before {
+                peer.forget_expired_transaction_requests_sent_before(forget_before);
+            }
+            for tx_id in peer.take_transaction_requests_sent_before(requested_before) {
+                log::debug!("transaction request for {tx_id} to peer {peer_id} timed out");
+                expired.push(tx_id);
+            }
+        }
+        for tx_id in expired {
+            self.retry_transaction_request(tx_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Announce a transaction accepted into the mempool to the peers that don't know about it
+    fn relay_transaction(&mut self, tx_id: Id<Transaction>) {
+        if !self.relays_transactions() {
+            return;
+        }
+
+        for (peer_id, peer) in self.peers.iter_mut() {
+            if peer.knows_transaction(&tx_id) {
+                continue;
+            }
+            peer.add_known_transaction(tx_id);
+            let _ = self
+                .peer_sync_handle
+                .send_announcement(*peer_id, Announcement::Transaction(tx_id))
+                .log_err();
+        }
+    }
+
+    async fn adjust_peer_score(&mut self, peer_id: T::PeerId, score: u32) -> crate::Result<()> {
+        if score > 0 {
+            let (tx, rx) = oneshot_nofail::channel();
+            self.tx_peer_manager
+                .send(PeerManagerEvent::AdjustPeerScore(peer_id, score, tx))
+                .map_err(P2pError::from)?;
+            let _ = rx.await.map_err(P2pError::from)?;
+        }
+
+        Ok(())
     }
 
     // TODO: refactor this
@@ -441,6 +1179,9 @@ where
         log::info!("Starting SyncManager");
 
         let mut block_rx = self.subscribe_to_chainstate_events().await?;
+        let mut tx_rx = self.subscribe_to_mempool_events().await?;
+        let mut transaction_request_check =
+            tokio::time::interval(TRANSACTION_REQUEST_CHECK_INTERVAL);
 
         loop {
             tokio::select! {
@@ -472,6 +1213,39 @@ where
                             ).await;
                             self.handle_error(peer_id, result).await?;
                         }
+                        message::SyncRequest::BlockFilterListRequest(request) => {
+                            log::debug!("process block filter request (id {request_id:?}) from peer {peer_id}");
+                            log::trace!("requested block ids: {:#?}", request.block_ids());
+
+                            let result = self.process_block_filter_request(
+                                peer_id,
+                                request_id,
+                                request.into_block_ids(),
+                            ).await;
+                            self.handle_error(peer_id, result).await?;
+                        }
+                        message::SyncRequest::BlockFilterHeaderListRequest(request) => {
+                            log::debug!("process block filter header request (id {request_id:?}) from peer {peer_id}");
+                            log::trace!("requested block ids: {:#?}", request.block_ids());
+
+                            let result = self.process_block_filter_header_request(
+                                peer_id,
+                                request_id,
+                                request.into_block_ids(),
+                            ).await;
+                            self.handle_error(peer_id, result).await?;
+                        }
+                        message::SyncRequest::TransactionRequest(request) => {
+                            log::debug!("process transaction request (id {request_id:?}) from peer {peer_id}");
+                            log::trace!("requested transaction id: {}", request.tx_id());
+
+                            let result = self.process_transaction_request(
+                                peer_id,
+                                request_id,
+                                request.into_tx_id(),
+                            ).await;
+                            self.handle_error(peer_id, result).await?;
+                        }
                     },
                     SyncingEvent::Response {
                         peer_id,
@@ -485,18 +1259,34 @@ where
                     }
                 },
                 event = self.rx_sync.recv() => match event.ok_or(P2pError::ChannelClosed)? {
-                    SyncControlEvent::Connected(peer_id) => {
+                    SyncControlEvent::Connected(peer_id, block_serving) => {
                         log::debug!("register peer {peer_id} to sync manager");
-                        let result = self.register_peer(peer_id).await;
+                        let result = self.register_peer(peer_id, block_serving).await;
                         self.handle_error(peer_id, result).await?;
                     }
                     SyncControlEvent::Disconnected(peer_id) => {
                         log::debug!("unregister peer {peer_id} from sync manager");
-                        self.unregister_peer(peer_id)
+                        self.unregister_peer(peer_id).await?;
+                    }
+                    SyncControlEvent::WatchDestination(destination, response) => {
+                        response.send(self.watch_destination(&destination));
                     }
+                    SyncControlEvent::GetRelevantTransactions(response) => {
+                        response.send(self.relevant_transactions());
+                    }
+                },
+                tx_id = tx_rx.recv() => {
+                    self.relay_transaction(tx_id.ok_or(P2pError::ChannelClosed)?);
+                },
+                now = transaction_request_check.tick() => {
+                    self.handle_transaction_request_timeouts(now).await?;
                 },
                 block_id = block_rx.recv(), if !self.chainstate_handle.call(|c| c.is_initial_block_download()).await?? => {
                     let block_id = block_id.ok_or(P2pError::ChannelClosed)?;
+                    // Light nodes have no blocks to announce
+                    if self.is_light_node() {
+                        continue;
+                    }
 
                     match self.chainstate_handle.call(move |this| this.get_block(block_id)).await?? {
                         Some(block) => {
@@ -523,6 +1313,8 @@ where
                             log::error!("PubSubMessageHandler closed: {e:?}")
                         }
                     }
+                    chainstate::ChainstateEvent::BlockConnected(_, _)
+                    | chainstate::ChainstateEvent::BlockDisconnected(_, _) => {}
                 },
             );
 
@@ -534,11 +1326,38 @@ where
         Ok(rx)
     }
 
+    /// Returns a receiver for the ids of the transactions accepted into the mempool.
+    async fn subscribe_to_mempool_events(
+        &mut self,
+    ) -> crate::Result<mpsc::UnboundedReceiver<Id<Transaction>>> {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let subscribe_func = Arc::new(move |mempool_event: MempoolEvent| match mempool_event {
+            MempoolEvent::NewTip(_, _) => {}
+            MempoolEvent::TransactionAdded(tx_id) => {
+                if let Err(e) = tx.send(tx_id) {
+                    log::error!("Transaction relay handler closed: {e:?}")
+                }
+            }
+        });
+
+        self.mempool_handle
+            .call_async_mut(|this| this.subscribe_to_events(subscribe_func))
+            .await
+            .map_err(|_| P2pError::SubsystemFailure)??;
+
+        Ok(rx)
+    }
+
     async fn process_block_announcement(
         &mut self,
         peer_id: T::PeerId,
         block: Block,
     ) -> crate::Result<()> {
+        if self.is_light_node() {
+            return self.process_light_node_announcement(peer_id, block.header().clone()).await;
+        }
+
         let result = match self
             .chainstate_handle
             .call(move |this| this.preliminary_block_check(block))
@@ -554,12 +1373,7 @@ where
 
         let score = match result {
             Ok(_) => 0,
-            Err(e) => match e {
-                ChainstateError::FailedToInitializeChainstate(_) => 0,
-                ChainstateError::ProcessBlockError(err) => err.ban_score(),
-                ChainstateError::FailedToReadProperty(_) => 0,
-                ChainstateError::BootstrapError(_) => 0,
-            },
+            Err(e) => e.ban_score(),
         };
 
         if score > 0 {
@@ -573,6 +1387,54 @@ where
 
         Ok(())
     }
+    /// Only the header of an announced block is used by a light node; if it doesn't connect to
+    /// the known headers, the missing ones are requested from the peer
+    async fn process_light_node_announcement(
+        &mut self,
+        peer_id: T::PeerId,
+        header: BlockHeader,
+    ) -> crate::Result<()> {
+        let prev_block_id = *header.prev_block_id();
+        let prev_known = self
+            .chainstate_handle
+            .call(move |this| this.get_gen_block_index(&prev_block_id))
+            .await??
+            .is_some();
+        if !prev_known {
+            let locator = self.chainstate_handle.call(|this| this.get_locator()).await??;
+            return self.send_header_request(peer_id, locator);
+        }
+
+        let block = self.scanned_block(&header);
+        let result =
+            match self.chainstate_handle.call_mut(move |this| this.process_header(header)).await? {
+                Ok(tip) => {
+                    if tip.is_some() {
+                        self.drop_reorged_relevant_transactions().await?;
+                    }
+                    self.scan_blocks(peer_id, vec![block])
+                }
+                Err(ChainstateError::ProcessBlockError(BlockError::BlockAlreadyExists(_id))) => {
+                    Ok(())
+                }
+                Err(err) => Err(P2pError::ChainstateError(err)),
+            };
+        self.handle_error(peer_id, result).await
+    }
+}
+
+/// Check whether a peer can send the blocks of the given headers, which are the unknown blocks of
+/// its last header response.
+///
+/// A pruned peer keeps at least `MIN_BLOCKS_TO_KEEP_WHEN_PRUNING` blocks below its tip, and its tip
+/// is not below the last announced header. The blocks have to be downloaded in order, so none of
+/// them are requested if the first one may be outside of that window.
+fn peer_has_blocks(block_serving: BlockServing, headers: &[BlockHeader]) -> bool {
+    match block_serving {
+        BlockServing::Full => true,
+        BlockServing::Limited => headers.len() as u64 <= MIN_BLOCKS_TO_KEEP_WHEN_PRUNING,
+        BlockServing::None => headers.is_empty(),
+    }
 }
 
 #[cfg(test)]
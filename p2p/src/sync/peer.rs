@@ -15,16 +15,24 @@
 
 use crate::{
     error::{P2pError, ProtocolError},
-    net::NetworkingService,
+    net::{types::BlockServing, NetworkingService},
+    sync::filter_scan::{match_response, FilterScan},
 };
 use chainstate::Locator;
 use common::{
-    chain::block::{Block, BlockHeader},
+    chain::{
+        block::{block_filter::BlockFilterHeader, Block, BlockHeader},
+        Transaction,
+    },
     primitives::{Id, Idable},
 };
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use tokio::time::Instant;
 use utils::ensure;
 
+/// The maximum number of transaction ids remembered as known by a peer
+const MAX_KNOWN_TRANSACTIONS: usize = 5000;
+
 /// State of the peer
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PeerSyncState {
@@ -52,28 +60,93 @@ where
     /// State of the peer
     state: PeerSyncState,
 
+    /// Which blocks the peer is able to send, as told in the handshake
+    block_serving: BlockServing,
+
     /// List of block headers indicating which blocks
     /// still need to be downloaded from the remote peer
     work: VecDeque<BlockHeader>,
+
+    /// Transactions the peer is known to have, because it announced them or because they were
+    /// announced or sent to it
+    known_transactions: KnownTransactions,
+
+    /// Transactions requested from the peer and not received yet, with the time of the request
+    requested_transactions: BTreeMap<Id<Transaction>, Instant>,
+
+    /// Transaction requests that timed out recently, with the time of the request. Late responses
+    /// to them are dropped without penalizing the peer.
+    expired_transaction_requests: BTreeMap<Id<Transaction>, Instant>,
+
+    /// Scanning of the block filters served by the peer, light nodes only
+    filter_scan: FilterScan,
+
+    /// Filter header requests sent to the peer, in order, with the peers whose filters they check
+    filter_header_requests: VecDeque<(T::PeerId, Vec<Id<Block>>)>,
+}
+
+/// A set of transaction ids that forgets the oldest ids once it is full
+#[derive(Default)]
+struct KnownTransactions {
+    ids: BTreeSet<Id<Transaction>>,
+    insertion_order: VecDeque<Id<Transaction>>,
+}
+
+impl KnownTransactions {
+    fn contains(&self, tx_id: &Id<Transaction>) -> bool {
+        self.ids.contains(tx_id)
+    }
+
+    fn insert(&mut self, tx_id: Id<Transaction>) {
+        if !self.ids.insert(tx_id) {
+            return;
+        }
+        self.insertion_order.push_back(tx_id);
+        if self.insertion_order.len() > MAX_KNOWN_TRANSACTIONS {
+            let oldest = self.insertion_order.pop_front().expect("the queue is not empty");
+            self.ids.remove(&oldest);
+        }
+    }
 }
 
 impl<T: NetworkingService> PeerContext<T> {
-    pub fn new(_peer_id: T::PeerId) -> Self {
+    pub fn new(_peer_id: T::PeerId, block_serving: BlockServing) -> Self {
         Self {
             _peer_id,
             state: PeerSyncState::Unknown,
+            block_serving,
             work: VecDeque::new(),
+            known_transactions: Default::default(),
+            requested_transactions: BTreeMap::new(),
+            expired_transaction_requests: BTreeMap::new(),
+            filter_scan: Default::default(),
+            filter_header_requests: VecDeque::new(),
         }
     }
 
-    pub fn new_with_locator(_peer_id: T::PeerId, locator: Locator) -> Self {
+    pub fn new_with_locator(
+        _peer_id: T::PeerId,
+        block_serving: BlockServing,
+        locator: Locator,
+    ) -> Self {
         Self {
             _peer_id,
             state: PeerSyncState::UploadingHeaders(locator),
+            block_serving,
             work: VecDeque::new(),
+            known_transactions: Default::default(),
+            requested_transactions: BTreeMap::new(),
+            expired_transaction_requests: BTreeMap::new(),
+            filter_scan: Default::default(),
+            filter_header_requests: VecDeque::new(),
         }
     }
 
+    /// Which blocks the peer is able to send
+    pub fn block_serving(&self) -> BlockServing {
+        self.block_serving
+    }
+
     pub fn register_header_response(&mut self, headers: &[BlockHeader]) {
         self.state = PeerSyncState::Idle;
         self.work = VecDeque::from(headers.to_vec());
@@ -102,6 +175,18 @@ impl<T: NetworkingService> PeerContext<T> {
         }
     }
 
+    /// Record that the peer doesn't have the requested block, the remaining blocks it announced
+    /// aren't downloaded from it
+    pub fn register_block_not_available(&mut self) -> crate::Result<()> {
+        ensure!(
+            matches!(self.state, PeerSyncState::UploadingBlocks(_)),
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+        self.state = PeerSyncState::Idle;
+        self.work.clear();
+        Ok(())
+    }
+
     fn get_next_block(&mut self) -> Option<BlockHeader> {
         self.work.pop_front()
     }
@@ -115,6 +200,113 @@ impl<T: NetworkingService> PeerContext<T> {
     pub fn state(&self) -> &PeerSyncState {
         &self.state
     }
+
+    /// Get the scanning progress of the block filters served by the peer
+    pub fn filter_scan_mut(&mut self) -> &mut FilterScan {
+        &mut self.filter_scan
+    }
+
+    /// Record a filter header request sent to the peer to check the filters of the scanned peer
+    pub fn register_filter_header_request(
+        &mut self,
+        scanned_peer: T::PeerId,
+        block_ids: Vec<Id<Block>>,
+    ) {
+        self.filter_header_requests.push_back((scanned_peer, block_ids));
+    }
+
+    /// Record the response to the oldest filter header request and return the scanned peer it was
+    /// sent for; fails unless the headers are for the requested blocks in the requested order
+    pub fn register_filter_header_response(
+        &mut self,
+        filter_headers: &[(Id<Block>, BlockFilterHeader)],
+    ) -> crate::Result<T::PeerId> {
+        let (scanned_peer, block_ids) = self
+            .filter_header_requests
+            .pop_front()
+            .ok_or(P2pError::ProtocolError(ProtocolError::InvalidMessage))?;
+        let response = filter_headers.iter().map(|(id, _)| (*id, ())).collect();
+        match_response(block_ids, response)?;
+        Ok(scanned_peer)
+    }
+
+    /// Forget the filter header requests and return the scanned peers they were sent for
+    pub fn take_filter_header_requests(&mut self) -> Vec<T::PeerId> {
+        std::mem::take(&mut self.filter_header_requests)
+            .into_iter()
+            .map(|(scanned_peer, _)| scanned_peer)
+            .collect()
+    }
+
+    /// Check whether the peer is known to have the transaction
+    pub fn knows_transaction(&self, tx_id: &Id<Transaction>) -> bool {
+        self.known_transactions.contains(tx_id)
+    }
+
+    /// Remember that the peer has the transaction, so it isn't announced to it
+    pub fn add_known_transaction(&mut self, tx_id: Id<Transaction>) {
+        self.known_transactions.insert(tx_id)
+    }
+
+    /// Check whether the transaction was requested from the peer and not received yet
+    pub fn has_requested_transaction(&self, tx_id: &Id<Transaction>) -> bool {
+        self.requested_transactions.contains_key(tx_id)
+    }
+
+    /// Number of transactions requested from the peer and not received yet
+    pub fn requested_transaction_count(&self) -> usize {
+        self.requested_transactions.len()
+    }
+
+    /// Record a transaction request sent to the peer at given time
+    pub fn register_transaction_request(&mut self, tx_id: Id<Transaction>, now: Instant) {
+        self.requested_transactions.insert(tx_id, now);
+    }
+
+    /// Record the response to a transaction request. Returns false for a late response to a
+    /// request that timed out recently, fails if the transaction wasn't requested.
+    pub fn register_transaction_response(
+        &mut self,
+        tx_id: &Id<Transaction>,
+    ) -> crate::Result<bool> {
+        if self.requested_transactions.remove(tx_id).is_some() {
+            return Ok(true);
+        }
+        ensure!(
+            self.expired_transaction_requests.remove(tx_id).is_some(),
+            P2pError::ProtocolError(ProtocolError::InvalidMessage),
+        );
+        Ok(false)
+    }
+
+    /// Expire the transaction requests sent before given time and return their transactions.
+    /// A late response to such a request is accepted until the request is forgotten.
+    pub fn take_transaction_requests_sent_before(&mut self, time: Instant) -> Vec<Id<Transaction>> {
+        let expired: Vec<_> = self
+            .requested_transactions
+            .iter()
+            .filter(|(_, requested_at)| **requested_at < time)
+            .map(|(tx_id, _)| *tx_id)
+            .collect();
+        for tx_id in &expired {
+            if let Some(requested_at) = self.requested_transactions.remove(tx_id) {
+                self.expired_transaction_requests.insert(*tx_id, requested_at);
+            }
+        }
+        expired
+    }
+
+    /// Forget the expired transaction requests sent before given time, a late response to them
+    /// is treated as unrequested
+    pub fn forget_expired_transaction_requests_sent_before(&mut self, time: Instant) {
+        self.expired_transaction_requests
+            .retain(|_, requested_at| *requested_at >= time);
+    }
+
+    /// Forget all the transaction requests and return their transactions
+    pub fn take_transaction_requests(&mut self) -> Vec<Id<Transaction>> {
+        std::mem::take(&mut self.requested_transactions).into_keys().collect()
+    }
 }
 
 #[cfg(test)]
@@ -128,7 +320,10 @@ mod tests {
     };
 
     fn new_peersyncstate() -> PeerContext<DefaultNetworkingService<TcpTransportSocket>> {
-        PeerContext::<DefaultNetworkingService<TcpTransportSocket>>::new(types::PeerId::new())
+        PeerContext::<DefaultNetworkingService<TcpTransportSocket>>::new(
+            types::PeerId::new(),
+            BlockServing::Full,
+        )
     }
 
     #[test]
@@ -155,4 +350,68 @@ mod tests {
         peer.set_state(PeerSyncState::UploadingBlocks(header.get_id()));
         assert_eq!(peer.state, PeerSyncState::UploadingBlocks(header.get_id()));
     }
+
+    #[test]
+    fn known_transactions_limit() {
+        let mut peer = new_peersyncstate();
+        let tx_id = |i: u64| Id::new(common::primitives::H256::from_low_u64_be(i));
+
+        for i in 0..MAX_KNOWN_TRANSACTIONS as u64 {
+            peer.add_known_transaction(tx_id(i));
+        }
+        assert!(peer.knows_transaction(&tx_id(0)));
+
+        // Adding a known transaction again doesn't change the order
+        peer.add_known_transaction(tx_id(0));
+        peer.add_known_transaction(tx_id(MAX_KNOWN_TRANSACTIONS as u64));
+        assert!(!peer.knows_transaction(&tx_id(0)));
+        assert!(peer.knows_transaction(&tx_id(1)));
+        assert!(peer.knows_transaction(&tx_id(MAX_KNOWN_TRANSACTIONS as u64)));
+    }
+
+    #[test]
+    fn transaction_requests() {
+        let mut peer = new_peersyncstate();
+        let tx_id = Id::new(common::primitives::H256([0x01; 32]));
+
+        assert!(peer.register_transaction_response(&tx_id).is_err());
+        peer.register_transaction_request(tx_id, Instant::now());
+        assert!(peer.has_requested_transaction(&tx_id));
+        assert_eq!(peer.requested_transaction_count(), 1);
+        assert!(peer.register_transaction_response(&tx_id).unwrap());
+        assert!(!peer.has_requested_transaction(&tx_id));
+        assert!(peer.register_transaction_response(&tx_id).is_err());
+    }
+
+    #[test]
+    fn transaction_requests_expire() {
+        let mut peer = new_peersyncstate();
+        let tx_id = |i: u64| Id::new(common::primitives::H256::from_low_u64_be(i));
+        let start = Instant::now();
+        let second = std::time::Duration::from_secs(1);
+
+        peer.register_transaction_request(tx_id(1), start);
+        peer.register_transaction_request(tx_id(2), start + second);
+        assert_eq!(peer.take_transaction_requests_sent_before(start), vec![]);
+        assert_eq!(
+            peer.take_transaction_requests_sent_before(start + second),
+            vec![tx_id(1)]
+        );
+        assert!(!peer.has_requested_transaction(&tx_id(1)));
+        assert!(peer.has_requested_transaction(&tx_id(2)));
+
+        // A late response is accepted once, but not after the expired request is forgotten
+        peer.register_transaction_request(tx_id(3), start);
+        assert_eq!(
+            peer.take_transaction_requests_sent_before(start + second),
+            vec![tx_id(3)]
+        );
+        assert!(!peer.register_transaction_response(&tx_id(1)).unwrap());
+        assert!(peer.register_transaction_response(&tx_id(1)).is_err());
+        peer.forget_expired_transaction_requests_sent_before(start + second);
+        assert!(peer.register_transaction_response(&tx_id(3)).is_err());
+
+        assert_eq!(peer.take_transaction_requests(), vec![tx_id(2)]);
+        assert_eq!(peer.requested_transaction_count(), 0);
+    }
 }
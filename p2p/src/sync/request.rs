@@ -17,7 +17,13 @@
 
 use chainstate::Locator;
 use common::{
-    chain::{block::BlockHeader, Block},
+    chain::{
+        block::{
+            block_filter::{BlockFilter, BlockFilterHeader},
+            BlockHeader,
+        },
+        Block, SignedTransaction, Transaction,
+    },
     primitives::Id,
 };
 use logging::log;
@@ -47,6 +53,23 @@ where
         SyncRequest::HeaderListRequest(message::HeaderListRequest::new(locator))
     }
 
+    /// Creates a block filters request message.
+    pub fn make_block_filter_request(&self, block_ids: Vec<Id<Block>>) -> SyncRequest {
+        SyncRequest::BlockFilterListRequest(message::BlockFilterListRequest::new(block_ids))
+    }
+
+    /// Creates a block filter headers request message.
+    pub fn make_block_filter_header_request(&self, block_ids: Vec<Id<Block>>) -> SyncRequest {
+        SyncRequest::BlockFilterHeaderListRequest(message::BlockFilterHeaderListRequest::new(
+            block_ids,
+        ))
+    }
+
+    /// Creates a transaction request message.
+    pub fn make_transaction_request(&self, tx_id: Id<Transaction>) -> SyncRequest {
+        SyncRequest::TransactionRequest(message::TransactionRequest::new(tx_id))
+    }
+
     /// Make header response
     ///
     /// # Arguments
@@ -63,6 +86,43 @@ where
         SyncResponse::BlockListResponse(message::BlockListResponse::new(blocks))
     }
 
+    /// Make block filter response
+    ///
+    /// # Arguments
+    /// * `filters` - the filters of the requested blocks that are in the main chain
+    pub fn make_block_filter_response(
+        &self,
+        filters: Vec<(Id<Block>, BlockFilter)>,
+    ) -> SyncResponse {
+        SyncResponse::BlockFilterListResponse(message::BlockFilterListResponse::new(filters))
+    }
+
+    /// Make block filter header response
+    ///
+    /// # Arguments
+    /// * `filter_headers` - the filter headers of the requested blocks that are in the main chain
+    pub fn make_block_filter_header_response(
+        &self,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+    ) -> SyncResponse {
+        SyncResponse::BlockFilterHeaderListResponse(message::BlockFilterHeaderListResponse::new(
+            filter_headers,
+        ))
+    }
+
+    /// Make transaction response
+    ///
+    /// # Arguments
+    /// * `tx_id` - the id of the requested transaction
+    /// * `transaction` - the requested transaction, `None` if it isn't in the mempool
+    pub fn make_transaction_response(
+        &self,
+        tx_id: Id<Transaction>,
+        transaction: Option<SignedTransaction>,
+    ) -> SyncResponse {
+        SyncResponse::TransactionResponse(message::TransactionResponse::new(tx_id, transaction))
+    }
+
     /// Sends a request to the given peer.
     pub fn send_request(&mut self, peer_id: T::PeerId, request: SyncRequest) -> crate::Result<()> {
         self.peer_sync_handle.send_request(peer_id, request).map(|_| ())
@@ -132,6 +192,33 @@ where
         Ok(())
     }
 
+    /// Send transaction request to remote peer and start tracking it
+    ///
+    /// # Arguments
+    /// * `peer_id` - peer ID of the remote node
+    /// * `tx_id` - ID of the transaction that is requested
+    pub fn send_transaction_request(
+        &mut self,
+        peer_id: T::PeerId,
+        tx_id: Id<Transaction>,
+    ) -> crate::Result<()> {
+        ensure!(
+            self.peers.contains_key(&peer_id),
+            P2pError::PeerError(PeerError::PeerDoesntExist),
+        );
+
+        log::trace!("send transaction request to {peer_id}, transaction id {tx_id}");
+
+        let wanted_transaction = self.make_transaction_request(tx_id);
+        self.send_request(peer_id, wanted_transaction)?;
+
+        self.peers
+            .get_mut(&peer_id)
+            .ok_or(P2pError::PeerError(PeerError::PeerDoesntExist))?
+            .register_transaction_request(tx_id, tokio::time::Instant::now());
+        Ok(())
+    }
+
     /// Send header response to remote peer
     ///
     /// The header request that is removed from remote peer contains
@@ -171,4 +258,54 @@ where
         let message = self.make_block_response(blocks);
         self.peer_sync_handle.send_response(request_id, message)
     }
+
+    /// Send block filter response to remote peer
+    ///
+    /// # Arguments
+    /// * `request_id` - ID of the request that this is a response to
+    /// * `filters` - filters that the remote requested
+    pub fn send_block_filter_response(
+        &mut self,
+        request_id: T::PeerRequestId,
+        filters: Vec<(Id<Block>, BlockFilter)>,
+    ) -> crate::Result<()> {
+        log::trace!("send block filter response, request id {request_id:?}");
+
+        let message = self.make_block_filter_response(filters);
+        self.peer_sync_handle.send_response(request_id, message)
+    }
+
+    /// Send block filter header response to remote peer
+    ///
+    /// # Arguments
+    /// * `request_id` - ID of the request that this is a response to
+    /// * `filter_headers` - filter headers that the remote requested
+    pub fn send_block_filter_header_response(
+        &mut self,
+        request_id: T::PeerRequestId,
+        filter_headers: Vec<(Id<Block>, BlockFilterHeader)>,
+    ) -> crate::Result<()> {
+        log::trace!("send block filter header response, request id {request_id:?}");
+
+        let message = self.make_block_filter_header_response(filter_headers);
+        self.peer_sync_handle.send_response(request_id, message)
+    }
+
+    /// Send transaction response to remote peer
+    ///
+    /// # Arguments
+    /// * `request_id` - ID of the request that this is a response to
+    /// * `tx_id` - ID of the transaction that the remote requested
+    /// * `transaction` - the transaction, `None` if it isn't in the mempool
+    pub fn send_transaction_response(
+        &mut self,
+        request_id: T::PeerRequestId,
+        tx_id: Id<Transaction>,
+        transaction: Option<SignedTransaction>,
+    ) -> crate::Result<()> {
+        log::trace!("send transaction response, request id {request_id:?}");
+
+        let message = self.make_transaction_response(tx_id, transaction);
+        self.peer_sync_handle.send_response(request_id, message)
+    }
 }
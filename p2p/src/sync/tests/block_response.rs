@@ -306,3 +306,64 @@ async fn invalid_block_noise() {
     invalid_block::<TestTransportNoise, PeerId, DefaultNetworkingService<NoiseTcpTransport>>()
         .await;
 }
+
+// the peer doesn't have the requested block
+async fn block_not_available<A, P, T>()
+where
+    A: TestTransportMaker<Transport = T::Transport, Address = T::Address>,
+    P: MakeTestPeerId<PeerId = T::PeerId>,
+    T: NetworkingService + 'static,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T>,
+{
+    let addr = A::make_address();
+    let peer_id = P::new();
+
+    let config = Arc::new(common::chain::config::create_unit_test_config());
+    let (mut mgr, _conn, _sync, _pm) = make_sync_manager::<T>(A::make_transport(), addr).await;
+    register_peer(&mut mgr, peer_id).await;
+
+    // An empty response is only expected for a block request
+    assert_eq!(
+        mgr.process_block_response(peer_id, vec![]).await,
+        Err(P2pError::ProtocolError(ProtocolError::InvalidMessage)),
+    );
+
+    let blocks = p2p_test_utils::create_n_blocks(
+        Arc::clone(&config),
+        TestBlockInfo::from_genesis(config.genesis_block()),
+        2,
+    );
+    let peer = mgr.peers.get_mut(&peer_id).unwrap();
+    peer.register_header_response(&[blocks[1].header().clone()]);
+    peer.set_state(peer::PeerSyncState::UploadingBlocks(blocks[0].get_id()));
+
+    // The remaining blocks aren't requested from the peer, which isn't penalized
+    assert_eq!(mgr.process_block_response(peer_id, vec![]).await, Ok(()));
+    let peer = mgr.peers.get_mut(&peer_id).unwrap();
+    assert_eq!(peer.state(), &peer::PeerSyncState::Idle);
+    assert_eq!(peer.get_header_for_download(), None);
+}
+
+#[tokio::test]
+async fn block_not_available_tcp() {
+    block_not_available::<TestTransportTcp, PeerId, DefaultNetworkingService<TcpTransportSocket>>()
+        .await;
+}
+
+#[tokio::test]
+async fn block_not_available_channels() {
+    block_not_available::<
+        TestTransportChannel,
+        PeerId,
+        DefaultNetworkingService<MpscChannelTransport>,
+    >()
+    .await;
+}
+
+#[tokio::test]
+async fn block_not_available_noise() {
+    block_not_available::<TestTransportNoise, PeerId, DefaultNetworkingService<NoiseTcpTransport>>(
+    )
+    .await;
+}
@@ -24,6 +24,7 @@ use crate::{
         types::PeerId,
         DefaultNetworkingService,
     },
+    net::types::BlockServing,
     sync::tests::{make_sync_manager, register_peer, MakeTestPeerId},
     ConnectivityService, NetworkingService, SyncingMessagingService,
 };
@@ -45,7 +46,7 @@ where
 
     assert_eq!(mgr.peers.len(), 1);
     assert_eq!(
-        mgr.register_peer(peer_id).await,
+        mgr.register_peer(peer_id, BlockServing::Full).await,
         Err(P2pError::PeerError(PeerError::PeerAlreadyExists))
     );
 }
@@ -90,10 +91,10 @@ where
     assert_eq!(mgr.peers.len(), 1);
 
     // no peer with this id exist, nothing happens
-    mgr.unregister_peer(peer_id2);
+    mgr.unregister_peer(peer_id2).await.unwrap();
     assert_eq!(mgr.peers.len(), 1);
 
-    mgr.unregister_peer(peer_id1);
+    mgr.unregister_peer(peer_id1).await.unwrap();
     assert!(mgr.peers.is_empty());
 }
 
@@ -24,10 +24,13 @@ use p2p_test_utils::TestBlockInfo;
 
 use crate::{
     error::{P2pError, PeerError, ProtocolError},
-    net::default_backend::{
-        transport::{MpscChannelTransport, NoiseTcpTransport, TcpTransportSocket},
-        types::PeerId,
-        DefaultNetworkingService,
+    net::{
+        default_backend::{
+            transport::{MpscChannelTransport, NoiseTcpTransport, TcpTransportSocket},
+            types::PeerId,
+            DefaultNetworkingService,
+        },
+        types::BlockServing,
     },
     sync::{
         peer,
@@ -384,3 +387,69 @@ async fn peer_doesnt_exist_noise() {
     peer_doesnt_exist::<TestTransportNoise, PeerId, DefaultNetworkingService<NoiseTcpTransport>>()
         .await;
 }
+
+// a pruned peer isn't asked for blocks that may be below its window
+async fn pruned_peer<A, P, T>()
+where
+    A: TestTransportMaker<Transport = T::Transport, Address = T::Address>,
+    P: MakeTestPeerId<PeerId = T::PeerId>,
+    T: NetworkingService + 'static,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T>,
+{
+    let addr = A::make_address();
+    let peer_id = P::new();
+
+    let config = Arc::new(common::chain::config::create_unit_test_config());
+    let (mut mgr, _conn, _sync, _pm) = make_sync_manager::<T>(A::make_transport(), addr).await;
+    let locator = mgr.chainstate_handle.call(|this| this.get_locator()).await.unwrap().unwrap();
+    mgr.peers.insert(
+        peer_id,
+        peer::PeerContext::new_with_locator(peer_id, BlockServing::Limited, locator.clone()),
+    );
+
+    let headers = p2p_test_utils::create_n_blocks(
+        Arc::clone(&config),
+        TestBlockInfo::from_genesis(config.genesis_block()),
+        chainstate::MIN_BLOCKS_TO_KEEP_WHEN_PRUNING as usize + 1,
+    )
+    .iter()
+    .map(|b| b.header().clone())
+    .collect::<Vec<_>>();
+
+    assert_eq!(
+        mgr.validate_header_response(&peer_id, headers.clone()).await,
+        Ok(None),
+    );
+    assert_eq!(
+        mgr.peers.get(&peer_id).unwrap().state(),
+        &peer::PeerSyncState::Idle
+    );
+
+    // The blocks close enough to the last header are downloaded
+    mgr.peers
+        .get_mut(&peer_id)
+        .unwrap()
+        .set_state(peer::PeerSyncState::UploadingHeaders(locator));
+    assert_eq!(
+        mgr.validate_header_response(&peer_id, headers[..headers.len() - 1].to_vec())
+            .await,
+        Ok(Some(headers[0].clone())),
+    );
+}
+
+#[tokio::test]
+async fn pruned_peer_tcp() {
+    pruned_peer::<TestTransportTcp, PeerId, DefaultNetworkingService<TcpTransportSocket>>().await;
+}
+
+#[tokio::test]
+async fn pruned_peer_channels() {
+    pruned_peer::<TestTransportChannel, PeerId, DefaultNetworkingService<MpscChannelTransport>>()
+        .await;
+}
+
+#[tokio::test]
+async fn pruned_peer_noise() {
+    pruned_peer::<TestTransportNoise, PeerId, DefaultNetworkingService<NoiseTcpTransport>>().await;
+}
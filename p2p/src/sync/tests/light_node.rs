@@ -0,0 +1,204 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use tokio::time::timeout;
+
+use chainstate::ChainstateConfig;
+use common::{
+    chain::{
+        block::tx_inclusion_proof::TxInclusionProof, config::create_unit_test_config, Block,
+        Destination,
+    },
+    primitives::Idable,
+};
+use p2p_test_utils::TestBlockInfo;
+
+use crate::{
+    config::NodeType,
+    event::SyncControlEvent,
+    interface::types::RelevantTransaction,
+    net::default_backend::{
+        transport::{MpscChannelTransport, TcpTransportSocket},
+        types::PeerId,
+        DefaultNetworkingService,
+    },
+    sync::{tests::make_sync_manager_with_config, MAX_RELEVANT_TRANSACTIONS},
+    testing_utils::{connect_services, TestTransportChannel, TestTransportMaker, TestTransportTcp},
+    utils::oneshot_nofail,
+    ConnectivityService, NetworkingService, SyncingMessagingService,
+};
+
+// A light node syncs the headers from a full node, fetches the block filters and downloads the
+// blocks that send to the watched destination, keeping the transactions with their proofs
+async fn relevant_transactions_found<A, T>()
+where
+    A: TestTransportMaker<Transport = T::Transport, Address = T::Address>,
+    T: NetworkingService + Debug + 'static,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T> + Sync,
+    T::PeerRequestId: 'static,
+    T::PeerId: 'static,
+{
+    let (mut full_mgr, mut full_conn, _full_sync, _full_pm) = make_sync_manager_with_config::<T>(
+        A::make_transport(),
+        A::make_address(),
+        ChainstateConfig::new().with_whether_block_filters_enabled(true),
+        NodeType::Full,
+    )
+    .await;
+    let (mut light_mgr, mut light_conn, light_sync, _light_pm) =
+        make_sync_manager_with_config::<T>(
+            A::make_transport(),
+            A::make_address(),
+            ChainstateConfig::new().with_headers_only(true),
+            NodeType::Light,
+        )
+        .await;
+
+    // Each of the test blocks has a transaction that sends to anyone
+    let chain_config = Arc::new(create_unit_test_config());
+    let blocks = p2p_test_utils::create_n_blocks(
+        Arc::clone(&chain_config),
+        TestBlockInfo::from_genesis(chain_config.genesis_block()),
+        3,
+    );
+    p2p_test_utils::import_blocks(&full_mgr.chainstate_handle, blocks.clone()).await;
+    light_mgr.watch_destination(&Destination::AnyoneCanSpend).unwrap();
+    assert!(full_mgr.watch_destination(&Destination::AnyoneCanSpend).is_err());
+
+    let (_address, full_info, light_info) =
+        connect_services::<T>(&mut full_conn, &mut light_conn).await;
+    full_mgr
+        .register_peer(light_info.peer_id, light_info.block_serving)
+        .await
+        .unwrap();
+    light_mgr
+        .register_peer(full_info.peer_id, full_info.block_serving)
+        .await
+        .unwrap();
+    tokio::spawn(async move { full_mgr.run().await });
+    tokio::spawn(async move { light_mgr.run().await });
+
+    let relevant_transactions = timeout(Duration::from_secs(15), async {
+        loop {
+            let (tx, rx) = oneshot_nofail::channel();
+            light_sync.send(SyncControlEvent::GetRelevantTransactions(tx)).unwrap();
+            let relevant_transactions = rx.await.unwrap();
+            if relevant_transactions.len() == blocks.len() {
+                break relevant_transactions;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the relevant transactions were not found in time");
+
+    for (block, relevant_tx) in blocks.iter().zip(relevant_transactions) {
+        assert_eq!(&relevant_tx.transaction, &block.transactions()[0]);
+        assert_eq!(relevant_tx.inclusion_proof.block_id(), &block.get_id());
+        assert!(relevant_tx
+            .inclusion_proof
+            .verify_with_header(&relevant_tx.transaction, block.header()));
+    }
+}
+
+#[tokio::test]
+async fn relevant_transactions_found_tcp() {
+    relevant_transactions_found::<TestTransportTcp, DefaultNetworkingService<TcpTransportSocket>>()
+        .await;
+}
+
+#[tokio::test]
+async fn relevant_transactions_found_channels() {
+    relevant_transactions_found::<
+        TestTransportChannel,
+        DefaultNetworkingService<MpscChannelTransport>,
+    >()
+    .await;
+}
+
+// A light node forgets the relevant transactions of the blocks that leave the main chain, and the
+// oldest ones over the limit
+#[tokio::test]
+async fn relevant_transactions_reorged_and_bounded() {
+    type T = DefaultNetworkingService<MpscChannelTransport>;
+
+    let (mut light_mgr, _light_conn, _light_sync, _light_pm) = make_sync_manager_with_config::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+        ChainstateConfig::new().with_headers_only(true),
+        NodeType::Light,
+    )
+    .await;
+    let peer_id = PeerId::new();
+
+    let chain_config = Arc::new(create_unit_test_config());
+    let genesis = TestBlockInfo::from_genesis(chain_config.genesis_block());
+    let block = p2p_test_utils::create_block(Arc::clone(&chain_config), genesis.clone());
+    let fork = p2p_test_utils::create_n_blocks(Arc::clone(&chain_config), genesis, 2);
+    let relevant_transaction = |block: &Block| {
+        let transaction = block.transactions()[0].clone();
+        let inclusion_proof =
+            TxInclusionProof::from_block(block, &transaction.transaction().get_id()).unwrap();
+        RelevantTransaction {
+            transaction,
+            inclusion_proof,
+        }
+    };
+
+    light_mgr
+        .process_light_node_announcement(peer_id, block.header().clone())
+        .await
+        .unwrap();
+    light_mgr
+        .add_relevant_transactions(block.get_id(), vec![relevant_transaction(&block)])
+        .await
+        .unwrap();
+    assert_eq!(light_mgr.relevant_transactions().len(), 1);
+
+    // The fork becomes the main chain
+    for fork_block in &fork {
+        light_mgr
+            .process_light_node_announcement(peer_id, fork_block.header().clone())
+            .await
+            .unwrap();
+    }
+    assert!(light_mgr.relevant_transactions().is_empty());
+
+    // The transactions of a block outside of the main chain are not kept
+    light_mgr
+        .add_relevant_transactions(block.get_id(), vec![relevant_transaction(&block)])
+        .await
+        .unwrap();
+    assert!(light_mgr.relevant_transactions().is_empty());
+
+    let transactions = vec![relevant_transaction(&fork[0]); MAX_RELEVANT_TRANSACTIONS];
+    light_mgr
+        .add_relevant_transactions(fork[0].get_id(), transactions)
+        .await
+        .unwrap();
+    light_mgr
+        .add_relevant_transactions(fork[1].get_id(), vec![relevant_transaction(&fork[1])])
+        .await
+        .unwrap();
+    let relevant_transactions = light_mgr.relevant_transactions();
+    assert_eq!(relevant_transactions.len(), MAX_RELEVANT_TRANSACTIONS);
+    assert_eq!(
+        relevant_transactions.last().unwrap().inclusion_proof.block_id(),
+        &fork[1].get_id()
+    );
+}
@@ -16,7 +16,9 @@
 mod block_response;
 mod connection;
 mod header_response;
+mod light_node;
 mod request_response;
+mod transaction_relay;
 
 use std::sync::Arc;
 
@@ -27,7 +29,7 @@ use chainstate::{make_chainstate, ChainstateConfig, DefaultTransactionVerificati
 use crate::{
     config::{NodeType, P2pConfig},
     event::{PeerManagerEvent, SyncControlEvent},
-    net::{default_backend::types::PeerId, ConnectivityService},
+    net::{default_backend::types::PeerId, types::BlockServing, ConnectivityService},
     sync::{peer, BlockSyncManager},
     NetworkingService, SyncingMessagingService,
 };
@@ -41,6 +43,28 @@ async fn make_sync_manager<T>(
     mpsc::UnboundedSender<SyncControlEvent<T>>,
     mpsc::UnboundedReceiver<PeerManagerEvent<T>>,
 )
+where
+    T: NetworkingService,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T>,
+    T::PeerRequestId: 'static,
+    T::PeerId: 'static,
+{
+    make_sync_manager_with_config::<T>(transport, addr, ChainstateConfig::new(), NodeType::Full)
+        .await
+}
+
+async fn make_sync_manager_with_config<T>(
+    transport: T::Transport,
+    addr: T::Address,
+    chainstate_config: ChainstateConfig,
+    node_type: NodeType,
+) -> (
+    BlockSyncManager<T>,
+    T::ConnectivityHandle,
+    mpsc::UnboundedSender<SyncControlEvent<T>>,
+    mpsc::UnboundedReceiver<PeerManagerEvent<T>>,
+)
 where
     T: NetworkingService,
     T::ConnectivityHandle: ConnectivityService<T>,
@@ -52,7 +76,6 @@ where
     let (tx_pm, rx_pm) = mpsc::unbounded_channel();
     let storage = chainstate_storage::inmemory::Store::new_empty().unwrap();
     let chain_config = Arc::new(common::chain::config::create_unit_test_config());
-    let chainstate_config = ChainstateConfig::new();
     let mut man = subsystem::Manager::new("TODO");
     let handle = man.add_subsystem(
         "chainstate",
@@ -69,6 +92,7 @@ where
     tokio::spawn(async move { man.main().await });
 
     let chain_config = Arc::new(common::chain::config::create_unit_test_config());
+    let mempool = p2p_test_utils::start_mempool(Arc::clone(&chain_config), handle.clone()).await;
     let p2p_config = Arc::new(P2pConfig {
         bind_addresses: vec!["/ip6/::1/tcp/3031".to_owned()],
         added_nodes: Vec::new(),
@@ -77,7 +101,8 @@ where
         outbound_connection_timeout: Default::default(),
         ping_check_period: Default::default(),
         ping_timeout: Default::default(),
-        node_type: NodeType::Full.into(),
+        node_type: node_type.into(),
+        block_serving: Default::default(),
         allow_discover_private_ips: Default::default(),
     });
     let (conn, sync) = T::start(
@@ -90,7 +115,15 @@ where
     .unwrap();
 
     (
-        BlockSyncManager::<T>::new(chain_config, p2p_config, sync, handle, rx_p2p_sync, tx_pm),
+        BlockSyncManager::<T>::new(
+            chain_config,
+            p2p_config,
+            sync,
+            handle,
+            mempool,
+            rx_p2p_sync,
+            tx_pm,
+        ),
         conn,
         tx_p2p_sync,
         rx_pm,
@@ -106,7 +139,7 @@ where
 
     mgr.peers.insert(
         peer_id,
-        peer::PeerContext::new_with_locator(peer_id, locator),
+        peer::PeerContext::new_with_locator(peer_id, BlockServing::Full, locator),
     );
 }
 
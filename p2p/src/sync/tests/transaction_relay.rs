@@ -0,0 +1,361 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::{fmt::Debug, time::Duration};
+
+use tokio::time::{timeout, Instant};
+
+use common::{
+    chain::{
+        config::ChainConfig, signature::inputsig::InputWitness, tokens::OutputValue, Destination,
+        OutPointSourceId, OutputPurpose, SignedTransaction, Transaction, TxInput, TxOutput,
+    },
+    primitives::{Amount, Id, Idable},
+};
+
+use crate::{
+    event::PeerManagerEvent,
+    message::{Announcement, SyncRequest, SyncResponse, TransactionResponse},
+    net::{
+        default_backend::{
+            transport::{MpscChannelTransport, TcpTransportSocket},
+            DefaultNetworkingService,
+        },
+        types::SyncingEvent,
+    },
+    sync::{tests::make_sync_manager, TRANSACTION_REQUEST_TIMEOUT},
+    testing_utils::{connect_services, TestTransportChannel, TestTransportMaker, TestTransportTcp},
+    ConnectivityService, NetworkingService, SyncingMessagingService,
+};
+
+/// A transaction that spends the first genesis output and that the mempool accepts
+fn spend_genesis(chain_config: &ChainConfig) -> SignedTransaction {
+    let genesis = chain_config.genesis_block();
+    let value = match genesis.utxos()[0].value() {
+        OutputValue::Coin(coin) => *coin,
+        OutputValue::Token(_) => panic!("genesis output is not a coin"),
+    };
+    let fee = Amount::from_atoms(100_000);
+    let transaction = Transaction::new(
+        0,
+        vec![TxInput::new(OutPointSourceId::BlockReward(genesis.get_id().into()), 0)],
+        vec![TxOutput::new(
+            OutputValue::Coin((value - fee).unwrap()),
+            OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+        )],
+        0,
+    )
+    .unwrap();
+    SignedTransaction::new(transaction, vec![InputWitness::NoSignature(None)]).unwrap()
+}
+
+/// A transaction without inputs, which can never be valid
+fn invalid_transaction() -> SignedTransaction {
+    let transaction = Transaction::new(
+        0,
+        vec![],
+        vec![TxOutput::new(
+            OutputValue::Coin(Amount::from_atoms(1)),
+            OutputPurpose::Transfer(Destination::AnyoneCanSpend),
+        )],
+        0,
+    )
+    .unwrap();
+    SignedTransaction::new(transaction, vec![]).unwrap()
+}
+
+// A transaction added to the mempool of one node is announced, requested and added to the
+// mempool of the other node
+async fn transaction_relayed<A, T>()
+where
+    A: TestTransportMaker<Transport = T::Transport, Address = T::Address>,
+    T: NetworkingService + Debug + 'static,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T> + Sync,
+    T::PeerRequestId: 'static,
+    T::PeerId: 'static,
+{
+    let (mut mgr1, mut conn1, _sync1, _pm1) =
+        make_sync_manager::<T>(A::make_transport(), A::make_address()).await;
+    let (mut mgr2, mut conn2, _sync2, _pm2) =
+        make_sync_manager::<T>(A::make_transport(), A::make_address()).await;
+
+    let (_address, peer_info1, peer_info2) = connect_services::<T>(&mut conn1, &mut conn2).await;
+    mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await.unwrap();
+    mgr2.register_peer(peer_info1.peer_id, peer_info1.block_serving).await.unwrap();
+
+    let mempool1 = mgr1.mempool_handle.clone();
+    let mempool2 = mgr2.mempool_handle.clone();
+    tokio::spawn(async move { mgr1.run().await });
+    tokio::spawn(async move { mgr2.run().await });
+    // Let the sync managers subscribe to the mempool events
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let tx = spend_genesis(&common::chain::config::create_unit_test_config());
+    let tx_id = tx.transaction().get_id();
+    mempool1
+        .call_async_mut(move |this| this.add_transaction(tx))
+        .await
+        .unwrap()
+        .unwrap();
+
+    timeout(Duration::from_secs(15), async {
+        loop {
+            let relayed = mempool2
+                .call_async(move |this| {
+                    Box::pin(async move { this.contains_transaction(&tx_id).await })
+                })
+                .await
+                .unwrap()
+                .unwrap();
+            if relayed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .expect("the transaction was not relayed in time");
+}
+
+#[tokio::test]
+async fn transaction_relayed_tcp() {
+    transaction_relayed::<TestTransportTcp, DefaultNetworkingService<TcpTransportSocket>>().await;
+}
+
+#[tokio::test]
+async fn transaction_relayed_channels() {
+    transaction_relayed::<TestTransportChannel, DefaultNetworkingService<MpscChannelTransport>>()
+        .await;
+}
+
+// A peer sending a transaction that can never be valid is penalized
+async fn invalid_transaction_penalized<A, T>()
+where
+    A: TestTransportMaker<Transport = T::Transport, Address = T::Address>,
+    T: NetworkingService + Debug + 'static,
+    T::ConnectivityHandle: ConnectivityService<T>,
+    T::SyncingMessagingHandle: SyncingMessagingService<T> + Sync,
+    T::PeerRequestId: 'static,
+    T::PeerId: 'static,
+{
+    let (mut mgr1, mut conn1, _sync1, mut pm1) =
+        make_sync_manager::<T>(A::make_transport(), A::make_address()).await;
+    let (mut mgr2, mut conn2, _sync2, _pm2) =
+        make_sync_manager::<T>(A::make_transport(), A::make_address()).await;
+
+    let (_address, _peer_info1, peer_info2) = connect_services::<T>(&mut conn1, &mut conn2).await;
+    mgr1.register_peer(peer_info2.peer_id, peer_info2.block_serving).await.unwrap();
+    tokio::spawn(async move { mgr1.run().await });
+
+    let tx = invalid_transaction();
+    let tx_id = tx.transaction().get_id();
+    mgr2.peer_sync_handle
+        .make_announcement(Announcement::Transaction(tx_id))
+        .unwrap();
+
+    // Serve the transaction once it's requested
+    let request_id = loop {
+        match timeout(Duration::from_secs(15), mgr2.peer_sync_handle.poll_next())
+            .await
+            .expect("the transaction was not requested in time")
+            .unwrap()
+        {
+            SyncingEvent::Request {
+                peer_id: _,
+                request_id,
+                request: SyncRequest::TransactionRequest(request),
+            } => {
+                assert_eq!(request.tx_id(), &tx_id);
+                break request_id;
+            }
+            SyncingEvent::Request { .. } => {}
+            event => panic!("unexpected event: {event:?}"),
+        }
+    };
+    mgr2.peer_sync_handle
+        .send_response(
+            request_id,
+            SyncResponse::TransactionResponse(TransactionResponse::new(tx_id, Some(tx))),
+        )
+        .unwrap();
+
+    match timeout(Duration::from_secs(15), pm1.recv()).await {
+        Ok(Some(PeerManagerEvent::AdjustPeerScore(peer_id, score, _))) => {
+            assert_eq!(peer_id, peer_info2.peer_id);
+            assert_eq!(score, 100);
+        }
+        event => panic!("unexpected event: {event:?}"),
+    }
+}
+
+#[tokio::test]
+async fn invalid_transaction_penalized_tcp() {
+    invalid_transaction_penalized::<TestTransportTcp, DefaultNetworkingService<TcpTransportSocket>>(
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn invalid_transaction_penalized_channels() {
+    invalid_transaction_penalized::<
+        TestTransportChannel,
+        DefaultNetworkingService<MpscChannelTransport>,
+    >()
+    .await;
+}
+
+// The peer that announced a transaction knows it and doesn't get it announced back
+#[tokio::test]
+async fn known_transactions_not_announced() {
+    type T = DefaultNetworkingService<MpscChannelTransport>;
+
+    let (mut mgr1, mut conn1, _sync1, _pm1) = make_sync_manager::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+    )
+    .await;
+    let (mut mgr2, mut conn2, _sync2, _pm2) = make_sync_manager::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+    )
+    .await;
+    let (_address, _peer_info1, peer_info2) = connect_services::<T>(&mut conn1, &mut conn2).await;
+    super::register_peer(&mut mgr1, peer_info2.peer_id).await;
+
+    let tx_id: Id<Transaction> = invalid_transaction().transaction().get_id();
+    mgr1.process_announcement(peer_info2.peer_id, Announcement::Transaction(tx_id))
+        .await
+        .unwrap();
+    let peer = &mgr1.peers[&peer_info2.peer_id];
+    assert!(peer.knows_transaction(&tx_id));
+    assert!(peer.has_requested_transaction(&tx_id));
+
+    // Announcing the transaction again doesn't request it twice
+    mgr1.process_announcement(peer_info2.peer_id, Announcement::Transaction(tx_id))
+        .await
+        .unwrap();
+    assert_eq!(
+        mgr1.peers[&peer_info2.peer_id].requested_transaction_count(),
+        1
+    );
+
+    // Nothing is sent to the peer that knows the transaction, the request is the only message
+    mgr1.relay_transaction(tx_id);
+    let other_tx_id = spend_genesis(&common::chain::config::create_unit_test_config())
+        .transaction()
+        .get_id();
+    mgr1.relay_transaction(other_tx_id);
+    assert!(mgr1.peers[&peer_info2.peer_id].knows_transaction(&other_tx_id));
+
+    let mut events = Vec::new();
+    while let Ok(event) = timeout(
+        Duration::from_millis(500),
+        mgr2.peer_sync_handle.poll_next(),
+    )
+    .await
+    {
+        events.push(event.unwrap());
+    }
+    let tx_requests = events
+        .iter()
+        .filter(|event| {
+            matches!(
+                event,
+                SyncingEvent::Request {
+                    request: SyncRequest::TransactionRequest(_),
+                    ..
+                }
+            )
+        })
+        .count();
+    let announcements = events
+        .iter()
+        .filter_map(|event| match event {
+            SyncingEvent::Announcement {
+                announcement: Announcement::Transaction(id),
+                ..
+            } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(tx_requests, 1);
+    assert_eq!(announcements, vec![other_tx_id]);
+}
+
+// A transaction whose request times out or whose peer disconnects is requested from the next
+// peer that announced it
+#[tokio::test]
+async fn failed_transaction_request_retried() {
+    type T = DefaultNetworkingService<MpscChannelTransport>;
+
+    let (mut mgr1, mut conn1, _sync1, _pm1) = make_sync_manager::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+    )
+    .await;
+    let (_mgr2, mut conn2, _sync2, _pm2) = make_sync_manager::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+    )
+    .await;
+    let (_mgr3, mut conn3, _sync3, _pm3) = make_sync_manager::<T>(
+        TestTransportChannel::make_transport(),
+        TestTransportChannel::make_address(),
+    )
+    .await;
+    let (_address, _peer_info1, peer_info2) = connect_services::<T>(&mut conn1, &mut conn2).await;
+    let (_address, _peer_info1, peer_info3) = connect_services::<T>(&mut conn1, &mut conn3).await;
+    let (peer2, peer3) = (peer_info2.peer_id, peer_info3.peer_id);
+    super::register_peer(&mut mgr1, peer2).await;
+    super::register_peer(&mut mgr1, peer3).await;
+
+    // The transaction is requested only from the first peer that announced it
+    let tx_id: Id<Transaction> = invalid_transaction().transaction().get_id();
+    for peer_id in [peer2, peer3] {
+        mgr1.process_announcement(peer_id, Announcement::Transaction(tx_id))
+            .await
+            .unwrap();
+    }
+    assert!(mgr1.peers[&peer2].has_requested_transaction(&tx_id));
+    assert!(!mgr1.peers[&peer3].has_requested_transaction(&tx_id));
+
+    // Nothing changes before the timeout
+    let now = Instant::now();
+    mgr1.handle_transaction_request_timeouts(now).await.unwrap();
+    assert!(mgr1.peers[&peer2].has_requested_transaction(&tx_id));
+
+    // After the timeout the transaction is requested from the other peer, a late response
+    // from the first peer is dropped, another one is unexpected
+    mgr1.handle_transaction_request_timeouts(
+        now + TRANSACTION_REQUEST_TIMEOUT + Duration::from_secs(1),
+    )
+    .await
+    .unwrap();
+    assert!(!mgr1.peers[&peer2].has_requested_transaction(&tx_id));
+    assert!(mgr1.peers[&peer3].has_requested_transaction(&tx_id));
+    assert!(mgr1.process_transaction_response(peer2, tx_id, None).await.is_ok());
+    assert!(mgr1.peers[&peer3].has_requested_transaction(&tx_id));
+    assert!(mgr1.process_transaction_response(peer2, tx_id, None).await.is_err());
+
+    // The first peer announces the transaction again and gets the request once the other peer
+    // disconnects
+    mgr1.process_announcement(peer2, Announcement::Transaction(tx_id))
+        .await
+        .unwrap();
+    assert!(!mgr1.peers[&peer2].has_requested_transaction(&tx_id));
+    mgr1.unregister_peer(peer3).await.unwrap();
+    assert!(mgr1.peers[&peer2].has_requested_transaction(&tx_id));
+}
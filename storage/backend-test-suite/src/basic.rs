@@ -249,11 +249,127 @@ fn put_and_iterate_delete_some<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>)
     drop(dbtx);
 }
 
+// Check iteration over a range of keys in both directions
+fn check_range<Tx: ReadOps>(dbtx: &Tx, range: KeyRange, expected: &[(&str, &str)]) {
+    let expected: Vec<_> = expected
+        .iter()
+        .map(|(x, y)| (Data::from(x.to_string()), Data::from(y.to_string())))
+        .collect();
+
+    let entries: Vec<_> =
+        dbtx.range_iter(IDX.0, range.clone(), Direction::Forward).unwrap().collect();
+    assert_eq!(entries, expected, "range={range:?}");
+
+    let entries: Vec<_> =
+        dbtx.range_iter(IDX.0, range.clone(), Direction::Reverse).unwrap().collect();
+    let expected_rev: Vec<_> = expected.into_iter().rev().collect();
+    assert_eq!(entries, expected_rev, "range={range:?}");
+}
+
+fn put_and_iterate_over_ranges<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    use Bound::{Excluded, Included, Unbounded};
+    let key = |k: &str| Data::from(k);
+
+    let store = backend_fn().open(desc(1)).expect("db open to succeed");
+
+    // Populate the database with some values
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"ac".to_vec(), b"2".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"b".to_vec(), b"4".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"aa".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"bb".to_vec(), b"5".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"ab".to_vec(), b"1".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"aca".to_vec(), b"3".to_vec()).unwrap();
+    dbtx.commit().expect("commit to succeed");
+
+    let dbtx = store.transaction_ro().unwrap();
+    let all = [("aa", "0"), ("ab", "1"), ("ac", "2"), ("aca", "3"), ("b", "4"), ("bb", "5")];
+    check_range(&dbtx, (Unbounded, Unbounded), &all);
+    check_range(&dbtx, (Included(key("a")), Unbounded), &all);
+    check_range(&dbtx, (Unbounded, Included(key("ab"))), &all[..2]);
+    check_range(&dbtx, (Unbounded, Excluded(key("ab"))), &all[..1]);
+    check_range(&dbtx, (Included(key("ab")), Included(key("b"))), &all[1..5]);
+    check_range(&dbtx, (Excluded(key("ab")), Excluded(key("b"))), &all[2..4]);
+    check_range(
+        &dbtx,
+        (Included(key("ab0")), Excluded(key("acb"))),
+        &all[2..4],
+    );
+    check_range(&dbtx, (Excluded(key("bb")), Unbounded), &[]);
+    check_range(&dbtx, (Included(key("c")), Unbounded), &[]);
+    check_range(&dbtx, (Unbounded, Excluded(key("aa"))), &[]);
+    check_range(&dbtx, (Included(key("b")), Included(key("ab"))), &[]);
+    check_range(&dbtx, (Excluded(key("b")), Excluded(key("b"))), &[]);
+    drop(dbtx);
+
+    // Modify the database and check the uncommitted changes are taken into account
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.del(IDX.0, b"ab").unwrap();
+    dbtx.put(IDX.0, b"ad".to_vec(), b"6".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"b".to_vec(), b"7".to_vec()).unwrap();
+    let all = [("aa", "0"), ("ac", "2"), ("aca", "3"), ("ad", "6"), ("b", "7"), ("bb", "5")];
+    check_range(&dbtx, (Unbounded, Unbounded), &all);
+    check_range(&dbtx, (Included(key("ab")), Included(key("b"))), &all[1..5]);
+    check_range(&dbtx, (Excluded(key("aca")), Unbounded), &all[3..]);
+    dbtx.commit().expect("commit to succeed");
+
+    let dbtx = store.transaction_ro().unwrap();
+    check_range(&dbtx, (Unbounded, Unbounded), &all);
+    drop(dbtx);
+}
+
+fn interleaved_partial_iteration<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    let store = backend_fn().open(desc(1)).expect("db open to succeed");
+
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    for i in 0u8..10 {
+        dbtx.put(IDX.0, vec![b'a', i], vec![i]).unwrap();
+        dbtx.put(IDX.0, vec![b'b', i], vec![i]).unwrap();
+    }
+    dbtx.commit().expect("commit to succeed");
+
+    // Several iterators over the same map can be alive at the same time
+    let dbtx = store.transaction_ro().unwrap();
+    let mut iter_a = dbtx.prefix_iter(IDX.0, vec![b'a']).unwrap();
+    let mut iter_b = dbtx.prefix_iter(IDX.0, vec![b'b']).unwrap();
+    let mut iter_all = dbtx.prefix_iter(IDX.0, vec![]).unwrap();
+    for i in 0u8..10 {
+        assert_eq!(iter_a.next(), Some((vec![b'a', i], vec![i])));
+        assert_eq!(iter_b.next(), Some((vec![b'b', i], vec![i])));
+        assert_eq!(iter_all.next(), Some((vec![b'a', i], vec![i])));
+    }
+    assert_eq!(iter_a.next(), None);
+    assert_eq!(iter_b.next(), None);
+    assert_eq!(iter_all.next(), Some((vec![b'b', 0], vec![0])));
+    drop((iter_a, iter_b, iter_all));
+    drop(dbtx);
+
+    // Dropping a partially consumed iterator does not affect subsequent operations
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    let first: Vec<_> = dbtx.prefix_iter(IDX.0, vec![b'a']).unwrap().take(3).collect();
+    assert_eq!(first.len(), 3);
+    for (key, _) in first {
+        dbtx.del(IDX.0, &key).unwrap();
+    }
+    assert_eq!(dbtx.prefix_iter(IDX.0, vec![b'a']).unwrap().count(), 7);
+    dbtx.commit().expect("commit to succeed");
+
+    let dbtx = store.transaction_ro().unwrap();
+    assert_eq!(
+        dbtx.prefix_iter(IDX.0, vec![b'a']).unwrap().next(),
+        Some((vec![b'a', 3], vec![3]))
+    );
+    assert_eq!(dbtx.prefix_iter(IDX.0, vec![]).unwrap().count(), 17);
+    drop(dbtx);
+}
+
 tests![
+    interleaved_partial_iteration,
     put_and_abort,
     put_and_commit,
     put_and_iterate_delete_some,
     put_and_iterate_over_prefixes,
+    put_and_iterate_over_ranges,
     put_iterator_count_matches,
     put_twice_then_commit_read_last,
     put_two_under_different_keys,
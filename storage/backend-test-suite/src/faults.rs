@@ -0,0 +1,149 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Behavior of the backend in presence of failures, injected using the failing backend wrapper
+
+use crate::prelude::*;
+use storage_failing::{CommitFault, Failing, FaultInjector};
+
+/// Open the backend wrapped in the fault injecting backend
+fn open_failing<B: Backend, F: BackendFn<B>>(
+    backend_fn: &F,
+    num_dbs: usize,
+) -> (FaultInjector, <Failing<B> as Backend>::Impl) {
+    let faults = FaultInjector::new();
+    let store = Failing::new(backend_fn(), faults.clone())
+        .open(desc(num_dbs))
+        .expect("db open to succeed");
+    (faults, store)
+}
+
+fn commit_failure_leaves_db_untouched<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    let (faults, store) = open_failing(&*backend_fn, 2);
+
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"foo".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.commit().unwrap();
+
+    // Modify both maps and fail the commit
+    faults.push_commit_fault(CommitFault::Fail);
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"foo".to_vec(), b"1".to_vec()).unwrap();
+    dbtx.put(IDX.1, b"bar".to_vec(), b"2".to_vec()).unwrap();
+    assert!(dbtx.commit().is_err());
+    assert_eq!(faults.pending_commit_faults(), 0);
+    assert_eq!(faults.commit_count(), 2);
+
+    let dbtx = store.transaction_ro().unwrap();
+    assert_eq!(
+        dbtx.get(IDX.0, b"foo").unwrap().as_deref(),
+        Some(b"0".as_ref())
+    );
+    assert_eq!(dbtx.get(IDX.1, b"bar"), Ok(None));
+    drop(dbtx);
+
+    // Retrying the same transaction succeeds once the fault is gone
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"foo".to_vec(), b"1".to_vec()).unwrap();
+    dbtx.put(IDX.1, b"bar".to_vec(), b"2".to_vec()).unwrap();
+    dbtx.commit().unwrap();
+
+    let dbtx = store.transaction_ro().unwrap();
+    assert_eq!(
+        dbtx.get(IDX.0, b"foo").unwrap().as_deref(),
+        Some(b"1".as_ref())
+    );
+    assert_eq!(
+        dbtx.get(IDX.1, b"bar").unwrap().as_deref(),
+        Some(b"2".as_ref())
+    );
+    drop(dbtx);
+}
+
+fn crash_persists_only_first_writes<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    let (faults, store) = open_failing(&*backend_fn, 1);
+
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"a".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"b".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.commit().unwrap();
+
+    faults.push_commit_fault(CommitFault::Pass);
+    faults.push_commit_fault(CommitFault::Crash {
+        persisted_writes: 2,
+    });
+
+    // The first commit is not affected
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"b".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.commit().unwrap();
+
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.del(IDX.0, b"a").unwrap();
+    dbtx.put(IDX.0, b"c".to_vec(), b"1".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"b".to_vec(), b"1".to_vec()).unwrap();
+    dbtx.put(IDX.0, b"d".to_vec(), b"1".to_vec()).unwrap();
+    assert!(dbtx.commit().is_err());
+
+    // Only the deletion of "a" and the insertion of "c" made it to the database
+    let expected = [(b"b".to_vec(), b"0".to_vec()), (b"c".to_vec(), b"1".to_vec())];
+    assert_eq!(
+        Model::from_db(&store, IDX.0),
+        expected.into_iter().collect()
+    );
+}
+
+fn nth_operation_fails<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    let (faults, store) = open_failing(&*backend_fn, 1);
+
+    // Starting the transaction is the first operation, the put is the third one
+    faults.fail_nth_operation(3);
+    let mut dbtx = store.transaction_rw(None).unwrap();
+    dbtx.put(IDX.0, b"a".to_vec(), b"0".to_vec()).unwrap();
+    assert!(dbtx.put(IDX.0, b"b".to_vec(), b"0".to_vec()).is_err());
+    dbtx.put(IDX.0, b"c".to_vec(), b"0".to_vec()).unwrap();
+    dbtx.commit().unwrap();
+    assert_eq!(faults.operation_count(), 5);
+
+    // The failed write has no effect
+    let expected = [(b"a".to_vec(), b"0".to_vec()), (b"c".to_vec(), b"0".to_vec())];
+    assert_eq!(
+        Model::from_db(&store, IDX.0),
+        expected.into_iter().collect()
+    );
+
+    // Failing to start a transaction
+    faults.fail_nth_operation(1);
+    assert!(store.transaction_ro().is_err());
+    let dbtx = store.transaction_ro().unwrap();
+    assert_eq!(
+        dbtx.get(IDX.0, b"a").unwrap().as_deref(),
+        Some(b"0".as_ref())
+    );
+    drop(dbtx);
+
+    // Failing to create an iterator
+    faults.fail_nth_operation(2);
+    let dbtx = store.transaction_ro().unwrap();
+    assert!(dbtx.prefix_iter(IDX.0, Data::new()).is_err());
+    assert_eq!(dbtx.prefix_iter(IDX.0, Data::new()).unwrap().count(), 2);
+    drop(dbtx);
+}
+
+tests![
+    commit_failure_leaves_db_untouched,
+    crash_persists_only_first_writes,
+    nth_operation_fails,
+];
@@ -25,6 +25,7 @@ pub mod model;
 // Test modules
 mod basic;
 mod concurrent;
+mod faults;
 
 #[cfg(not(loom))]
 mod property;
@@ -44,6 +45,7 @@ fn tests<B: 'static + Backend, F: BackendFn<B>>(backend_fn: F) -> Vec<libtest_mi
     std::iter::empty()
         .chain(basic::tests(Arc::clone(&backend_fn)))
         .chain(concurrent::tests(Arc::clone(&backend_fn)))
+        .chain(faults::tests(Arc::clone(&backend_fn)))
         .chain(property::tests(backend_fn))
         .collect()
 }
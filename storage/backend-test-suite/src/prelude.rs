@@ -17,13 +17,14 @@
 pub use crate::model::{ApplyActions, Model, WriteAction};
 pub use storage_core::{
     backend::{
-        Backend, Data, PrefixIter, ReadOps, TransactionalRo, TransactionalRw, TxRo, TxRw, WriteOps,
+        Backend, Data, Direction, KeyRange, PrefixIter, RangeIter, ReadOps, TransactionalRo,
+        TransactionalRw, TxRo, TxRw, WriteOps,
     },
     info::{self, DbDesc, DbIndex, MapDesc},
 };
 pub use utils::{sync, thread};
 
-pub use std::{mem::drop, sync::Arc};
+pub use std::{mem::drop, ops::Bound, sync::Arc};
 
 /// A function to construct a backend
 pub trait BackendFn<B: Backend>: 'static + Fn() -> B + Send + Sync {}
@@ -17,6 +17,7 @@
 
 use crate::prelude::*;
 use proptest::prelude::Strategy;
+use std::ops::RangeBounds;
 
 /// Proptest generators
 mod gen {
@@ -53,6 +54,15 @@ mod gen {
         ]
     }
 
+    pub fn bound(key_cardinality: u32) -> impl Strategy<Value = std::ops::Bound<Data>> {
+        use std::ops::Bound;
+        prop_oneof![
+            Just(Bound::Unbounded),
+            key(key_cardinality).prop_map(Bound::Included),
+            key(key_cardinality).prop_map(Bound::Excluded),
+        ]
+    }
+
     pub fn actions(
         key_cardinality: u32,
         count: impl Into<proptest::collection::SizeRange>,
@@ -436,6 +446,55 @@ fn prefix_iteration<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
     )
 }
 
+fn range_iteration<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
+    using_proptest(
+        file!(),
+        backend_fn,
+        (
+            gen::actions(100, 0..20),
+            gen::actions(100, 0..20),
+            gen::bound(100),
+            gen::bound(100),
+        ),
+        |backend, (committed, uncommitted, start, end)| {
+            let range: KeyRange = (start, end);
+            let check = |dbtx: &dyn Fn(Direction) -> Vec<(Data, Data)>, model: &Model| {
+                let expected: Vec<_> =
+                    model.clone().into_iter().filter(|(k, _)| range.contains(k)).collect();
+                assert_eq!(dbtx(Direction::Forward), expected);
+                let expected_rev: Vec<_> = expected.into_iter().rev().collect();
+                assert_eq!(dbtx(Direction::Reverse), expected_rev);
+            };
+
+            // Open storage
+            let store = backend.open(desc(1)).expect("db open to succeed");
+
+            // Populate the database
+            let mut dbtx = store.transaction_rw(None).unwrap();
+            dbtx.apply_actions(IDX.0, committed.iter().cloned());
+            dbtx.commit().unwrap();
+            let mut model = Model::from_actions(committed);
+
+            // Check iteration over committed data
+            let dbtx = store.transaction_ro().unwrap();
+            check(
+                &|dir| dbtx.range_iter(IDX.0, range.clone(), dir).unwrap().collect(),
+                &model,
+            );
+            drop(dbtx);
+
+            // Check iteration in a transaction with uncommitted changes
+            let mut dbtx = store.transaction_rw(None).unwrap();
+            dbtx.apply_actions(IDX.0, uncommitted.iter().cloned());
+            model.apply_actions(uncommitted.into_iter());
+            check(
+                &|dir| dbtx.range_iter(IDX.0, range.clone(), dir).unwrap().collect(),
+                &model,
+            );
+        },
+    )
+}
+
 fn post_commit_consistency<B: Backend, F: BackendFn<B>>(backend_fn: Arc<F>) {
     using_proptest(
         file!(),
@@ -468,4 +527,5 @@ tests![
     overwrite_and_abort,
     post_commit_consistency,
     prefix_iteration,
+    range_iteration,
 ];
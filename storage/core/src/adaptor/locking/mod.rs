@@ -17,6 +17,7 @@
 //! read/write operations, giving a full-featured (albeit not necessarily efficient) backend.
 
 mod prefix_iter_rw;
+mod range_iter_rw;
 
 use crate::{
     adaptor::{Construct, CoreOps},
@@ -24,7 +25,7 @@ use crate::{
     info::{DbDesc, DbIndex},
     Data,
 };
-use backend::{PrefixIter, ReadOps, WriteOps};
+use backend::{Direction, KeyRange, PrefixIter, RangeIter, ReadOps, WriteOps};
 
 use std::{borrow::Cow, collections::BTreeMap};
 use utils::sync;
@@ -46,6 +47,19 @@ impl<'tx, 'i, T: PrefixIter<'i>> PrefixIter<'i> for TxRo<'tx, T> {
     }
 }
 
+impl<'tx, 'i, T: RangeIter<'i>> RangeIter<'i> for TxRo<'tx, T> {
+    type Iterator = T::Iterator;
+
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> crate::Result<Self::Iterator> {
+        self.0.range_iter(idx, range, direction)
+    }
+}
+
 impl<'tx, T: ReadOps> backend::TxRo for TxRo<'tx, T> {}
 
 // Tracker for database changes
@@ -81,6 +95,19 @@ impl<'tx, 'i, T: PrefixIter<'i>> PrefixIter<'i> for TxRw<'tx, T> {
     }
 }
 
+impl<'tx, 'i, T: RangeIter<'i>> RangeIter<'i> for TxRw<'tx, T> {
+    type Iterator = range_iter_rw::Iter<'i, T>;
+
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> crate::Result<Self::Iterator> {
+        range_iter_rw::iter(self, idx, range, direction)
+    }
+}
+
 impl<'tx, T> WriteOps for TxRw<'tx, T> {
     fn put(&mut self, idx: DbIndex, key: Data, val: Data) -> crate::Result<()> {
         self.update(idx, key, Some(val))
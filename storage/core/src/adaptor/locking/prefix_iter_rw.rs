@@ -20,22 +20,23 @@ use itertools::EitherOrBoth;
 
 // The prefix iterator type for mutable transaction is a fairly complicated type. Here, we
 // introduce a bunch of type aliases to simplify its definition a bit.
-type DataPair = (Data, Data);
-type DataPairRef<'a> = (&'a [u8], &'a Option<Data>);
-type KeyCompareFn = fn(&DataPair, &DataPairRef<'_>) -> std::cmp::Ordering;
-type ItemMergeFn = fn(itertools::EitherOrBoth<DataPair, DataPairRef<'_>>) -> Option<DataPair>;
+pub(super) type DataPair = (Data, Data);
+pub(super) type DataPairRef<'a> = (&'a [u8], &'a Option<Data>);
+pub(super) type KeyCompareFn = fn(&DataPair, &DataPairRef<'_>) -> std::cmp::Ordering;
+pub(super) type ItemMergeFn =
+    fn(itertools::EitherOrBoth<DataPair, DataPairRef<'_>>) -> Option<DataPair>;
 type DbIter<'i, T> = <T as PrefixIter<'i>>::Iterator;
 type DeltaIter<'i> = crate::util::PrefixIter<'i, Option<Data>>;
 type JoinIter<'i, T> = itertools::MergeJoinBy<DbIter<'i, T>, DeltaIter<'i>, KeyCompareFn>;
 pub type Iter<'i, T> = std::iter::FilterMap<JoinIter<'i, T>, ItemMergeFn>;
 
 /// Function to compare key-value entries by the key
-fn comparator((a, _): &DataPair, (b, _): &DataPairRef<'_>) -> std::cmp::Ordering {
+pub(super) fn comparator((a, _): &DataPair, (b, _): &DataPairRef<'_>) -> std::cmp::Ordering {
     a.as_slice().cmp(b)
 }
 
 /// How to merge the items if the keys collide
-fn merger(item: EitherOrBoth<DataPair, DataPairRef<'_>>) -> Option<(Data, Data)> {
+pub(super) fn merger(item: EitherOrBoth<DataPair, DataPairRef<'_>>) -> Option<(Data, Data)> {
     match item {
         // Item only in original db, just present it
         EitherOrBoth::Left(l) => Some(l),
@@ -0,0 +1,51 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Internal functions and types used in the implementation of range iterator for RW transactions
+
+use super::prefix_iter_rw::{comparator, merger, DataPair, DataPairRef, ItemMergeFn, KeyCompareFn};
+use super::{DbIndex, RangeIter, TxRw};
+use crate::backend::{Direction, KeyRange};
+
+// Same structure as the prefix iterator, see the prefix_iter_rw module
+type DbIter<'i, T> = <T as RangeIter<'i>>::Iterator;
+type DeltaIter<'i> = crate::util::RangeIter<'i, Option<crate::Data>>;
+type JoinIter<'i, T> = itertools::MergeJoinBy<DbIter<'i, T>, DeltaIter<'i>, KeyCompareFn>;
+pub type Iter<'i, T> = std::iter::FilterMap<JoinIter<'i, T>, ItemMergeFn>;
+
+/// Function to compare key-value entries by the key in the reverse order
+fn comparator_rev(a: &DataPair, b: &DataPairRef<'_>) -> std::cmp::Ordering {
+    comparator(a, b).reverse()
+}
+
+/// Create the iterator
+pub fn iter<'tx, 'i, 'm: 'i, T: RangeIter<'i>>(
+    tx: &'m TxRw<'tx, T>,
+    idx: DbIndex,
+    range: KeyRange,
+    direction: Direction,
+) -> crate::Result<Iter<'i, T>> {
+    // Initialize the iterator over the underlying db and the deltas
+    let db_iter = tx.db.range_iter(idx, range.clone(), direction)?;
+    let delta_iter = crate::util::RangeIter::new(&tx.deltas[idx.get()], range, direction);
+
+    // Both iterators yield keys in the same order, merge them accordingly
+    let cmp = match direction {
+        Direction::Forward => comparator as KeyCompareFn,
+        Direction::Reverse => comparator_rev as KeyCompareFn,
+    };
+    let iter = itertools::merge_join_by(db_iter, delta_iter, cmp).filter_map(merger as ItemMergeFn);
+    Ok(iter)
+}
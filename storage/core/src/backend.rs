@@ -15,7 +15,7 @@
 
 //! Low-level interface implemented by storage backends.
 
-use std::borrow::Cow;
+use std::{borrow::Cow, ops::Bound, path::Path};
 
 use utils::shallow_clone::ShallowClone;
 
@@ -33,8 +33,34 @@ pub trait PrefixIter<'i> {
     fn prefix_iter<'m: 'i>(&'m self, idx: DbIndex, prefix: Data) -> crate::Result<Self::Iterator>;
 }
 
+/// Range of keys given by a start and an end bound
+pub type KeyRange = (Bound<Data>, Bound<Data>);
+
+/// Order in which the entries are visited by an iterator
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the smallest key to the largest one
+    Forward,
+    /// From the largest key to the smallest one
+    Reverse,
+}
+
+/// Types providing capability of iterating over keys in given range
+pub trait RangeIter<'i> {
+    /// The iterator type
+    type Iterator: 'i + Iterator<Item = (Data, Data)>;
+
+    /// Get iterator over key-value pairs where the key falls within given range
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> crate::Result<Self::Iterator>;
+}
+
 /// Read-only database operations
-pub trait ReadOps: for<'i> PrefixIter<'i> {
+pub trait ReadOps: for<'i> PrefixIter<'i> + for<'i> RangeIter<'i> {
     /// Get value associated with given key.
     fn get(&self, idx: DbIndex, key: &[u8]) -> crate::Result<Option<Cow<[u8]>>>;
 }
@@ -87,6 +113,18 @@ where
     Self: for<'tx> TransactionalRw<'tx>,
     Self: ShallowClone,
 {
+    /// Write a consistent snapshot of the database into a new database at given path, in the
+    /// native format of the backend. The live database can still be used in the meantime.
+    ///
+    /// Backends without a native on-disk format do not support this.
+    fn backup_native(&self, path: &Path) -> crate::Result<()> {
+        let _ = path;
+        Err(crate::error::Recoverable::Io(
+            std::io::ErrorKind::Unsupported,
+            "Native backups are not supported by the storage backend".to_string(),
+        )
+        .into())
+    }
 }
 
 /// Storage backend type. Used to set up storage.
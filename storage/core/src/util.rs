@@ -15,8 +15,11 @@
 
 //! Utilities for implementing storage backends
 
-use crate::Data;
-use std::collections::BTreeMap;
+use crate::{
+    backend::{Direction, KeyRange},
+    Data,
+};
+use std::{collections::BTreeMap, ops::Bound};
 
 /// Iterator over entries of a [BTreeMap] with keys starting with given prefix
 pub struct PrefixIter<'m, T> {
@@ -40,3 +43,56 @@ impl<'m, T> Iterator for PrefixIter<'m, T> {
             .and_then(|(k, v)| k.starts_with(&self.prefix[..]).then(|| (k.as_ref(), v)))
     }
 }
+
+/// Check whether given key falls within the range
+pub fn range_contains(range: &KeyRange, key: &[u8]) -> bool {
+    let after_start = match &range.0 {
+        Bound::Included(start) => key >= start.as_slice(),
+        Bound::Excluded(start) => key > start.as_slice(),
+        Bound::Unbounded => true,
+    };
+    let before_end = match &range.1 {
+        Bound::Included(end) => key <= end.as_slice(),
+        Bound::Excluded(end) => key < end.as_slice(),
+        Bound::Unbounded => true,
+    };
+    after_start && before_end
+}
+
+/// Check whether the range cannot contain any keys
+pub fn range_is_empty(range: &KeyRange) -> bool {
+    match range {
+        (Bound::Included(start), Bound::Included(end)) => start > end,
+        (Bound::Included(start), Bound::Excluded(end))
+        | (Bound::Excluded(start), Bound::Included(end))
+        | (Bound::Excluded(start), Bound::Excluded(end)) => start >= end,
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => false,
+    }
+}
+
+/// Iterator over entries of a [BTreeMap] with keys in given range
+pub struct RangeIter<'m, T> {
+    inner: Option<std::collections::btree_map::Range<'m, Data, T>>,
+    direction: Direction,
+}
+
+impl<'m, T> RangeIter<'m, T> {
+    pub fn new(map: &'m BTreeMap<Data, T>, range: KeyRange, direction: Direction) -> Self {
+        // BTreeMap::range panics on ranges with start past the end, so handle those up front
+        let inner = (!range_is_empty(&range)).then(|| map.range(range));
+        Self { inner, direction }
+    }
+}
+
+impl<'m, T> Iterator for RangeIter<'m, T> {
+    type Item = (&'m [u8], &'m T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let inner = self.inner.as_mut()?;
+        let (k, v) = match self.direction {
+            Direction::Forward => inner.next(),
+            Direction::Reverse => inner.next_back(),
+        }?;
+        Some((k.as_ref(), v))
+    }
+}
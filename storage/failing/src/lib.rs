@@ -0,0 +1,340 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Storage backend wrapper that injects faults into the operations of the wrapped backend
+//!
+//! Faults are controlled at run time by a [FaultInjector] handle shared between the backend
+//! and the test. It can make commits fail, emulate a crash in the middle of a commit after which
+//! only a part of the transaction has been persisted, or make an arbitrary operation fail.
+//! Without any faults injected, the wrapper behaves exactly like the underlying backend.
+
+use std::{borrow::Cow, collections::VecDeque};
+
+use storage_core::{
+    backend::{self, Direction, KeyRange, PrefixIter, RangeIter, ReadOps, WriteOps},
+    error::Recoverable,
+    Data, DbDesc, DbIndex,
+};
+use utils::sync::{Arc, Mutex, MutexGuard};
+
+/// Fault injected into a commit
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommitFault {
+    /// The commit goes through normally. Useful to inject faults into one of the later commits.
+    Pass,
+
+    /// The commit fails, none of the changes are persisted
+    Fail,
+
+    /// Crash in the middle of a commit. Only the given number of the first write operations of
+    /// the transaction are persisted, the rest is lost and the commit returns an error.
+    Crash { persisted_writes: usize },
+}
+
+#[derive(Default)]
+struct Faults {
+    /// Faults to be applied to the subsequent commits
+    commit_faults: VecDeque<CommitFault>,
+
+    /// Number of the operation that should fail
+    failing_operation: Option<u64>,
+
+    /// Number of operations performed so far
+    operation_count: u64,
+
+    /// Number of commits attempted so far
+    commit_count: u64,
+}
+
+/// Handle used to control the faults injected into the storage
+#[derive(Clone, Default)]
+pub struct FaultInjector(Arc<Mutex<Faults>>);
+
+impl FaultInjector {
+    /// New fault injector with no faults scheduled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Faults> {
+        self.0.lock().expect("fault injector lock to be alive")
+    }
+
+    /// Schedule a commit fault. Faults are applied to the subsequent commits in the order in which
+    /// they have been scheduled, one fault per commit.
+    pub fn push_commit_fault(&self, fault: CommitFault) {
+        self.lock().commit_faults.push_back(fault);
+    }
+
+    /// Make the `n`-th operation from now on fail, `n` starting from 1. Operations include
+    /// starting a transaction, reads, writes, creating an iterator and commits.
+    pub fn fail_nth_operation(&self, n: u64) {
+        assert!(n > 0, "operations are numbered from 1");
+        let mut faults = self.lock();
+        faults.failing_operation = Some(faults.operation_count + n);
+    }
+
+    /// Number of operations performed so far
+    pub fn operation_count(&self) -> u64 {
+        self.lock().operation_count
+    }
+
+    /// Number of commits attempted so far, including the failed ones
+    pub fn commit_count(&self) -> u64 {
+        self.lock().commit_count
+    }
+
+    /// Number of commit faults that have not been applied yet
+    pub fn pending_commit_faults(&self) -> usize {
+        self.lock().commit_faults.len()
+    }
+
+    /// Remove all the scheduled faults
+    pub fn clear(&self) {
+        let mut faults = self.lock();
+        faults.commit_faults.clear();
+        faults.failing_operation = None;
+    }
+
+    /// Register an operation, failing if it is the one scheduled to fail
+    fn operation(&self) -> storage_core::Result<()> {
+        let mut faults = self.lock();
+        faults.operation_count += 1;
+        if faults.failing_operation == Some(faults.operation_count) {
+            faults.failing_operation = None;
+            return Err(Recoverable::TemporarilyUnavailable.into());
+        }
+        Ok(())
+    }
+
+    fn take_commit_fault(&self) -> Option<CommitFault> {
+        let mut faults = self.lock();
+        faults.commit_count += 1;
+        faults.commit_faults.pop_front()
+    }
+}
+
+/// Read-only transaction
+pub struct TxRo<'tx, T> {
+    inner: T,
+    faults: &'tx FaultInjector,
+}
+
+impl<T: ReadOps> ReadOps for TxRo<'_, T> {
+    fn get(&self, idx: DbIndex, key: &[u8]) -> storage_core::Result<Option<Cow<[u8]>>> {
+        self.faults.operation()?;
+        self.inner.get(idx, key)
+    }
+}
+
+impl<'i, T: PrefixIter<'i>> PrefixIter<'i> for TxRo<'_, T> {
+    type Iterator = T::Iterator;
+
+    fn prefix_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        prefix: Data,
+    ) -> storage_core::Result<Self::Iterator> {
+        self.faults.operation()?;
+        self.inner.prefix_iter(idx, prefix)
+    }
+}
+
+impl<'i, T: RangeIter<'i>> RangeIter<'i> for TxRo<'_, T> {
+    type Iterator = T::Iterator;
+
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> storage_core::Result<Self::Iterator> {
+        self.faults.operation()?;
+        self.inner.range_iter(idx, range, direction)
+    }
+}
+
+impl<T: backend::TxRo> backend::TxRo for TxRo<'_, T> {}
+
+/// Read-write transaction
+///
+/// Keeps track of the writes performed so a crash in the middle of a commit can be emulated.
+pub struct TxRw<'tx, B: backend::TransactionalRw<'tx>> {
+    inner: B::TxRw,
+    backend: &'tx FailingImpl<B>,
+    writes: Vec<(DbIndex, Data, Option<Data>)>,
+}
+
+impl<'tx, B: backend::TransactionalRw<'tx>> ReadOps for TxRw<'tx, B> {
+    fn get(&self, idx: DbIndex, key: &[u8]) -> storage_core::Result<Option<Cow<[u8]>>> {
+        self.backend.faults.operation()?;
+        self.inner.get(idx, key)
+    }
+}
+
+impl<'tx, 'i, B: backend::TransactionalRw<'tx>> PrefixIter<'i> for TxRw<'tx, B> {
+    type Iterator = <B::TxRw as PrefixIter<'i>>::Iterator;
+
+    fn prefix_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        prefix: Data,
+    ) -> storage_core::Result<Self::Iterator> {
+        self.backend.faults.operation()?;
+        self.inner.prefix_iter(idx, prefix)
+    }
+}
+
+impl<'tx, 'i, B: backend::TransactionalRw<'tx>> RangeIter<'i> for TxRw<'tx, B> {
+    type Iterator = <B::TxRw as RangeIter<'i>>::Iterator;
+
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> storage_core::Result<Self::Iterator> {
+        self.backend.faults.operation()?;
+        self.inner.range_iter(idx, range, direction)
+    }
+}
+
+impl<'tx, B: backend::TransactionalRw<'tx>> WriteOps for TxRw<'tx, B> {
+    fn put(&mut self, idx: DbIndex, key: Data, val: Data) -> storage_core::Result<()> {
+        self.backend.faults.operation()?;
+        self.inner.put(idx, key.clone(), val.clone())?;
+        self.writes.push((idx, key, Some(val)));
+        Ok(())
+    }
+
+    fn del(&mut self, idx: DbIndex, key: &[u8]) -> storage_core::Result<()> {
+        self.backend.faults.operation()?;
+        self.inner.del(idx, key)?;
+        self.writes.push((idx, key.to_vec(), None));
+        Ok(())
+    }
+}
+
+impl<'tx, B: backend::TransactionalRw<'tx>> backend::TxRw for TxRw<'tx, B> {
+    fn commit(self) -> storage_core::Result<()> {
+        let faults = &self.backend.faults;
+        faults.operation()?;
+
+        match faults.take_commit_fault() {
+            None | Some(CommitFault::Pass) => self.inner.commit(),
+            Some(CommitFault::Fail) => Err(Recoverable::TransactionFailed.into()),
+            Some(CommitFault::Crash { persisted_writes }) => {
+                // Throw away the original transaction and only persist the first few writes
+                let Self {
+                    inner,
+                    backend,
+                    writes,
+                } = self;
+                drop(inner);
+
+                let mut dbtx = backend.inner.transaction_rw(None)?;
+                for (idx, key, val) in writes.into_iter().take(persisted_writes) {
+                    match val {
+                        Some(val) => dbtx.put(idx, key, val)?,
+                        None => dbtx.del(idx, &key)?,
+                    }
+                }
+                dbtx.commit()?;
+
+                Err(Recoverable::Io(
+                    std::io::ErrorKind::Interrupted,
+                    "Injected crash during commit".to_string(),
+                )
+                .into())
+            }
+        }
+    }
+}
+
+/// Backend implementation with fault injection
+pub struct FailingImpl<B> {
+    inner: B,
+    faults: FaultInjector,
+}
+
+impl<B: Clone> Clone for FailingImpl<B> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            faults: self.faults.clone(),
+        }
+    }
+}
+
+impl<B: utils::shallow_clone::ShallowClone> utils::shallow_clone::ShallowClone for FailingImpl<B> {}
+
+impl<'tx, B: backend::TransactionalRo<'tx>> backend::TransactionalRo<'tx> for FailingImpl<B> {
+    type TxRo = TxRo<'tx, B::TxRo>;
+
+    fn transaction_ro<'st: 'tx>(&'st self) -> storage_core::Result<Self::TxRo> {
+        self.faults.operation()?;
+        Ok(TxRo {
+            inner: self.inner.transaction_ro()?,
+            faults: &self.faults,
+        })
+    }
+}
+
+impl<'tx, B: 'tx + backend::TransactionalRw<'tx>> backend::TransactionalRw<'tx> for FailingImpl<B> {
+    type TxRw = TxRw<'tx, B>;
+
+    fn transaction_rw<'st: 'tx>(
+        &'st self,
+        size: Option<usize>,
+    ) -> storage_core::Result<Self::TxRw> {
+        self.faults.operation()?;
+        Ok(TxRw {
+            inner: self.inner.transaction_rw(size)?,
+            backend: self,
+            writes: Vec::new(),
+        })
+    }
+}
+
+impl<B: backend::BackendImpl> backend::BackendImpl for FailingImpl<B> {
+    fn backup_native(&self, path: &std::path::Path) -> storage_core::Result<()> {
+        self.faults.operation()?;
+        self.inner.backup_native(path)
+    }
+}
+
+/// Storage backend wrapper injecting faults into the operations of the inner backend
+pub struct Failing<B> {
+    inner: B,
+    faults: FaultInjector,
+}
+
+impl<B: backend::Backend> Failing<B> {
+    /// Wrap given backend, injecting faults controlled by given fault injector
+    pub fn new(inner: B, faults: FaultInjector) -> Self {
+        Self { inner, faults }
+    }
+}
+
+impl<B: backend::Backend> backend::Backend for Failing<B> {
+    type Impl = FailingImpl<B::Impl>;
+
+    fn open(self, desc: DbDesc) -> storage_core::Result<Self::Impl> {
+        Ok(FailingImpl {
+            inner: self.inner.open(desc)?,
+            faults: self.faults,
+        })
+    }
+}
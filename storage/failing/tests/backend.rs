@@ -0,0 +1,24 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use storage_failing::{Failing, FaultInjector};
+use storage_inmemory::InMemory;
+
+fn main() {
+    // With no faults injected, the wrapper has to pass the whole backend test suite
+    let result =
+        storage_backend_test_suite::main(|| Failing::new(InMemory::new(), FaultInjector::new()));
+    result.exit()
+}
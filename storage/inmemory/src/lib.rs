@@ -15,10 +15,28 @@
 
 use storage_core::{adaptor, backend, util, Data, DbDesc, DbIndex};
 
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
 
 type Map = BTreeMap<Data, Data>;
 
+/// The entries changed since the journal was last taken, `None` for removed entries
+pub type Journal = BTreeMap<(DbIndex, Data), Option<Data>>;
+
+/// Handle to the journal of a journaled in-memory database
+#[derive(Clone)]
+pub struct JournalHandle(Arc<Mutex<Journal>>);
+
+impl JournalHandle {
+    /// Take the changes recorded so far, the journal starts over empty
+    pub fn take(&self) -> Journal {
+        std::mem::take(&mut *self.0.lock().expect("lock to be alive"))
+    }
+}
+
 pub struct PrefixIter<'i>(storage_core::util::PrefixIter<'i, Data>);
 
 impl<'i> Iterator for PrefixIter<'i> {
@@ -29,11 +47,33 @@ impl<'i> Iterator for PrefixIter<'i> {
     }
 }
 
-pub struct StorageMaps(Vec<Map>);
+pub struct RangeIter<'i>(storage_core::util::RangeIter<'i, Data>);
+
+impl<'i> Iterator for RangeIter<'i> {
+    type Item = (Data, Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(k, v)| (k.to_vec(), v.clone()))
+    }
+}
+
+pub struct StorageMaps {
+    maps: Vec<Map>,
+    journal: Option<JournalHandle>,
+}
+
+impl StorageMaps {
+    fn record(&self, idx: DbIndex, key: &[u8], val: Option<&Data>) {
+        if let Some(journal) = &self.journal {
+            let mut journal = journal.0.lock().expect("lock to be alive");
+            journal.insert((idx, key.to_vec()), val.cloned());
+        }
+    }
+}
 
 impl backend::ReadOps for StorageMaps {
     fn get(&self, idx: DbIndex, key: &[u8]) -> storage_core::Result<Option<Cow<[u8]>>> {
-        Ok(self.0[idx.get()].get(key).map(|p| p.into()))
+        Ok(self.maps[idx.get()].get(key).map(|p| p.into()))
     }
 }
 
@@ -46,29 +86,51 @@ impl<'i> backend::PrefixIter<'i> for StorageMaps {
         prefix: Data,
     ) -> storage_core::Result<Self::Iterator> {
         Ok(PrefixIter(util::PrefixIter::new(
-            &self.0[idx.get()],
+            &self.maps[idx.get()],
             prefix,
         )))
     }
 }
 
+impl<'i> backend::RangeIter<'i> for StorageMaps {
+    type Iterator = RangeIter<'i>;
+
+    fn range_iter<'m: 'i>(
+        &'m self,
+        idx: DbIndex,
+        range: backend::KeyRange,
+        direction: backend::Direction,
+    ) -> storage_core::Result<Self::Iterator> {
+        Ok(RangeIter(util::RangeIter::new(
+            &self.maps[idx.get()],
+            range,
+            direction,
+        )))
+    }
+}
+
 impl backend::WriteOps for StorageMaps {
     fn put(&mut self, idx: DbIndex, key: Data, val: Data) -> storage_core::Result<()> {
-        let _ = self.0[idx.get()].insert(key, val);
+        self.record(idx, &key, Some(&val));
+        let _ = self.maps[idx.get()].insert(key, val);
         Ok(())
     }
 
     fn del(&mut self, idx: DbIndex, key: &[u8]) -> storage_core::Result<()> {
-        let _ = self.0[idx.get()].remove(key);
+        self.record(idx, key, None);
+        let _ = self.maps[idx.get()].remove(key);
         Ok(())
     }
 }
 
 impl adaptor::Construct for StorageMaps {
-    type From = ();
+    type From = Option<JournalHandle>;
 
-    fn construct(_: (), desc: DbDesc) -> storage_core::Result<Self> {
-        Ok(Self(vec![Map::new(); desc.len()]))
+    fn construct(journal: Option<JournalHandle>, desc: DbDesc) -> storage_core::Result<Self> {
+        Ok(Self {
+            maps: vec![Map::new(); desc.len()],
+            journal,
+        })
     }
 }
 
@@ -86,7 +148,14 @@ impl backend::Backend for InMemory {
 impl InMemory {
     /// Create a new in-memory storage backend
     pub fn new() -> Self {
-        Self(adaptor::Locking::new(()))
+        Self(adaptor::Locking::new(None))
+    }
+
+    /// Create a new in-memory storage backend which records the changed entries in a journal,
+    /// so that the changes can be saved elsewhere as they are made
+    pub fn new_journaled() -> (Self, JournalHandle) {
+        let journal = JournalHandle(Arc::new(Mutex::new(Journal::new())));
+        (Self(adaptor::Locking::new(Some(journal.clone()))), journal)
     }
 }
 
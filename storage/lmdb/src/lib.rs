@@ -19,15 +19,20 @@ pub mod memsize;
 pub mod resize_callback;
 
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::{borrow::Cow, path::PathBuf};
+use std::{
+    borrow::Cow,
+    ffi::{c_char, c_int, c_uint, c_void, CString},
+    ops::Bound,
+    path::{Path, PathBuf},
+};
 
 use initial_map_size::InitialMapSize;
 use lmdb::Cursor;
 use resize_callback::MapResizeCallback;
 use storage_core::{
-    backend::{self, TransactionalRo, TransactionalRw},
+    backend::{self, Direction, KeyRange, TransactionalRo, TransactionalRw},
     info::{DbDesc, MapDesc},
-    Data, DbIndex,
+    util, Data, DbIndex,
 };
 use utils::sync::Arc;
 
@@ -70,6 +75,112 @@ impl<'tx, C: lmdb::Cursor<'tx>> Iterator for PrefixIter<'tx, C> {
     }
 }
 
+/// Cursor operations used by the range iterator
+mod cursor_op {
+    pub use lmdb_sys::{
+        MDB_FIRST as FIRST, MDB_LAST as LAST, MDB_NEXT as NEXT, MDB_PREV as PREV,
+        MDB_SET_RANGE as SET_RANGE,
+    };
+}
+
+/// LMDB iterator over entries with keys in given range
+pub struct RangeIter<'tx> {
+    /// Underlying cursor
+    cursor: lmdb::RoCursor<'tx>,
+
+    /// Range to iterate over
+    range: KeyRange,
+
+    /// Iteration direction
+    direction: Direction,
+
+    /// Whether the cursor has been positioned at the first item yet
+    started: bool,
+
+    /// Whether the iteration has finished
+    done: bool,
+}
+
+impl<'tx> RangeIter<'tx> {
+    fn new(cursor: lmdb::RoCursor<'tx>, range: KeyRange, direction: Direction) -> Self {
+        RangeIter {
+            cursor,
+            range,
+            direction,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Perform given cursor operation, returning `None` if there is no such entry
+    fn cursor_get(&self, key: Option<&[u8]>, op: u32) -> Option<(&'tx [u8], &'tx [u8])> {
+        match self.cursor.get(key, None, op) {
+            Ok((k, v)) => Some((k.expect("cursor operation to return a key"), v)),
+            Err(lmdb::Error::NotFound) => None,
+            Err(e) => panic!("iteration to proceed: {e}"),
+        }
+    }
+
+    /// Position the cursor at the first entry in the iteration order
+    fn first(&self) -> Option<(&'tx [u8], &'tx [u8])> {
+        match self.direction {
+            Direction::Forward => match &self.range.0 {
+                Bound::Unbounded => self.cursor_get(None, cursor_op::FIRST),
+                Bound::Included(start) => self.cursor_get(Some(start), cursor_op::SET_RANGE),
+                Bound::Excluded(start) => {
+                    match self.cursor_get(Some(start), cursor_op::SET_RANGE) {
+                        Some((k, _)) if k == start.as_slice() => {
+                            self.cursor_get(None, cursor_op::NEXT)
+                        }
+                        item => item,
+                    }
+                }
+            },
+            Direction::Reverse => match &self.range.1 {
+                Bound::Unbounded => self.cursor_get(None, cursor_op::LAST),
+                Bound::Included(end) => match self.cursor_get(Some(end), cursor_op::SET_RANGE) {
+                    None => self.cursor_get(None, cursor_op::LAST),
+                    Some((k, v)) if k == end.as_slice() => Some((k, v)),
+                    Some(_) => self.cursor_get(None, cursor_op::PREV),
+                },
+                Bound::Excluded(end) => match self.cursor_get(Some(end), cursor_op::SET_RANGE) {
+                    None => self.cursor_get(None, cursor_op::LAST),
+                    Some(_) => self.cursor_get(None, cursor_op::PREV),
+                },
+            },
+        }
+    }
+}
+
+impl<'tx> Iterator for RangeIter<'tx> {
+    type Item = (Data, Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        let item = if self.started {
+            let op = match self.direction {
+                Direction::Forward => cursor_op::NEXT,
+                Direction::Reverse => cursor_op::PREV,
+            };
+            self.cursor_get(None, op)
+        } else {
+            self.started = true;
+            self.first()
+        };
+
+        match item {
+            Some((k, v)) if util::range_contains(&self.range, k) => Some((k.to_vec(), v.to_vec())),
+            _ => {
+                self.done = true;
+                None
+            }
+        }
+    }
+}
+
 pub struct DbTx<'m, Tx> {
     tx: Tx,
     backend: &'m LmdbImpl,
@@ -97,6 +208,21 @@ impl<'s, 'i, Tx: lmdb::Transaction> backend::PrefixIter<'i> for DbTx<'s, Tx> {
     }
 }
 
+impl<'s, 'i, Tx: lmdb::Transaction> backend::RangeIter<'i> for DbTx<'s, Tx> {
+    type Iterator = RangeIter<'i>;
+
+    fn range_iter<'t: 'i>(
+        &'t self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> storage_core::Result<Self::Iterator> {
+        let cursor =
+            self.tx.open_ro_cursor(self.backend.dbs[idx]).or_else(error::process_with_err)?;
+        Ok(RangeIter::new(cursor, range, direction))
+    }
+}
+
 impl<Tx: lmdb::Transaction> backend::ReadOps for DbTx<'_, Tx> {
     fn get(&self, idx: DbIndex, key: &[u8]) -> storage_core::Result<Option<Cow<[u8]>>> {
         self.tx
@@ -217,7 +343,37 @@ impl<'tx> TransactionalRw<'tx> for LmdbImpl {
 }
 
 impl utils::shallow_clone::ShallowClone for LmdbImpl {}
-impl backend::BackendImpl for LmdbImpl {}
+impl backend::BackendImpl for LmdbImpl {
+    fn backup_native(&self, path: &Path) -> storage_core::Result<()> {
+        // The copy is written into a new directory, as a data file that LMDB opens directly
+        std::fs::create_dir(path).map_err(error::process_io_error)?;
+        let path = path.to_str().and_then(|path| CString::new(path).ok()).ok_or_else(|| {
+            storage_core::error::Recoverable::Io(
+                std::io::ErrorKind::InvalidInput,
+                "The backup path is not a valid unicode string without zero bytes".to_string(),
+            )
+        })?;
+
+        // SAFETY: The environment is kept open by the handle for the duration of the call and the
+        // path is a valid null-terminated string. LMDB copies the data from a read-only
+        // transaction of its own, so the copy is consistent while the database is in use.
+        let ret =
+            unsafe { mdb_env_copy2(self.env.env() as *mut c_void, path.as_ptr(), MDB_CP_COMPACT) };
+        match ret {
+            0 => Ok(()),
+            err => error::process_with_err(lmdb::Error::from_err_code(err)),
+        }
+    }
+}
+
+/// Omit the free pages when copying the environment
+const MDB_CP_COMPACT: c_uint = 0x01;
+
+extern "C" {
+    /// Copy an LMDB environment to given directory, provided by the LMDB library linked in by the
+    /// `lmdb` crate
+    fn mdb_env_copy2(env: *mut c_void, path: *const c_char, flags: c_uint) -> c_int;
+}
 
 pub struct Lmdb {
     path: PathBuf,
@@ -0,0 +1,119 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Prefix iteration benchmarks, comparing the SQLite backend against LMDB
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use storage_core::{
+    backend::{BackendImpl, PrefixIter, TransactionalRw, TxRw, WriteOps},
+    info::MapDesc,
+    Backend, Data, DbDesc, DbIndex,
+};
+use storage_lmdb::{memsize::MemSize, Lmdb};
+use storage_sqlite::Sqlite;
+
+/// Number of distinct key prefixes in the generated dataset
+const NUM_PREFIXES: u8 = 16;
+/// Number of entries under each prefix
+const ENTRIES_PER_PREFIX: u32 = 20_000;
+/// Number of entries written in a single transaction while populating the database
+const ENTRIES_PER_TX: u32 = 10_000;
+
+const IDX: DbIndex = DbIndex::new(0);
+
+fn desc() -> DbDesc {
+    std::iter::once(MapDesc::new("bench_map".to_string())).collect()
+}
+
+fn make_key(prefix: u8, num: u32) -> Data {
+    std::iter::once(prefix).chain(num.to_be_bytes()).collect()
+}
+
+fn make_value(prefix: u8, num: u32) -> Data {
+    let seed = num.wrapping_mul(2654435761) ^ u32::from(prefix);
+    seed.to_le_bytes().iter().copied().cycle().take(64).collect()
+}
+
+/// Open the backend and fill it with the generated dataset
+fn populate<B: Backend>(backend: B) -> B::Impl {
+    let store = backend.open(desc()).expect("db open to succeed");
+
+    for prefix in 0..NUM_PREFIXES {
+        for chunk_start in (0..ENTRIES_PER_PREFIX).step_by(ENTRIES_PER_TX as usize) {
+            let mut dbtx = store.transaction_rw(None).unwrap();
+            for num in chunk_start..(chunk_start + ENTRIES_PER_TX).min(ENTRIES_PER_PREFIX) {
+                dbtx.put(IDX, make_key(prefix, num), make_value(prefix, num)).unwrap();
+            }
+            dbtx.commit().unwrap();
+        }
+    }
+
+    store
+}
+
+fn bench_backend<B: BackendImpl>(c: &mut Criterion, name: &str, store: &B) {
+    let mut group = c.benchmark_group("prefix_iter");
+    group.sample_size(10);
+
+    // Scan the whole database
+    group.bench_function(BenchmarkId::new("full_scan", name), |b| {
+        b.iter(|| {
+            let dbtx = store.transaction_ro().unwrap();
+            let count = dbtx.prefix_iter(IDX, Data::new()).unwrap().count();
+            assert_eq!(count, NUM_PREFIXES as usize * ENTRIES_PER_PREFIX as usize);
+        })
+    });
+
+    // Scan entries under a single prefix in the middle of the key space
+    group.bench_function(BenchmarkId::new("single_prefix", name), |b| {
+        b.iter(|| {
+            let dbtx = store.transaction_ro().unwrap();
+            let count = dbtx.prefix_iter(IDX, vec![NUM_PREFIXES / 2]).unwrap().count();
+            assert_eq!(count, ENTRIES_PER_PREFIX as usize);
+        })
+    });
+
+    // Only look at the first few entries under a prefix
+    group.bench_function(BenchmarkId::new("first_entries", name), |b| {
+        b.iter(|| {
+            let dbtx = store.transaction_ro().unwrap();
+            let count = dbtx.prefix_iter(IDX, vec![NUM_PREFIXES / 2]).unwrap().take(10).count();
+            assert_eq!(count, 10);
+        })
+    });
+
+    group.finish();
+}
+
+fn prefix_iter_benches(c: &mut Criterion) {
+    let data_dir = tempdir::TempDir::new("prefix_iter_bench").unwrap();
+
+    let sqlite = populate(Sqlite::new(data_dir.path().join("database.sqlite")));
+    bench_backend(c, "sqlite", &sqlite);
+
+    let lmdb_dir = data_dir.path().join("lmdb");
+    std::fs::create_dir_all(&lmdb_dir).unwrap();
+    // Map the whole dataset up front to avoid resizes while populating
+    let lmdb = populate(Lmdb::new(
+        lmdb_dir,
+        MemSize::from_bytes(1 << 30).into(),
+        Default::default(),
+        Default::default(),
+    ));
+    bench_backend(c, "lmdb", &lmdb);
+}
+
+criterion_group!(benches, prefix_iter_benches);
+criterion_main!(benches);
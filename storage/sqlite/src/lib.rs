@@ -18,38 +18,81 @@ extern crate core;
 mod error;
 mod queries;
 
-use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use rusqlite::{params_from_iter, Connection, OpenFlags, OptionalExtension};
 use std::borrow::Cow;
 use std::cmp::max;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Mutex, MutexGuard};
 
-use crate::queries::SqliteQueries;
+use crate::queries::{db_table_name, range_iter_query, SqliteQueries};
 use error::process_sqlite_error;
 use storage_core::{
-    backend::{self, TransactionalRo, TransactionalRw},
+    backend::{self, Direction, KeyRange, TransactionalRo, TransactionalRw},
     info::DbDesc,
     Data, DbIndex,
 };
 use utils::shallow_clone::ShallowClone;
 use utils::sync::Arc;
 
+/// Sqlite iterator over the rows returned by a key-value query
+///
+/// Rows are pulled lazily from the prepared statement, which is kept alive by the iterator.
+#[ouroboros::self_referencing]
+pub struct RowIter<'tx> {
+    /// The statement producing the rows
+    stmt: rusqlite::CachedStatement<'tx>,
+
+    /// Rows returned by the statement
+    #[borrows(mut stmt)]
+    #[not_covariant]
+    rows: rusqlite::Rows<'this>,
+}
+
+impl<'tx> RowIter<'tx> {
+    /// Run the query with given parameters
+    fn query(
+        stmt: rusqlite::CachedStatement<'tx>,
+        params: impl rusqlite::Params,
+    ) -> storage_core::Result<Self> {
+        RowIter::try_new(stmt, |stmt| {
+            stmt.query(params).map_err(process_sqlite_error)
+        })
+    }
+
+    /// Fetch the next key-value pair
+    fn next_entry(&mut self) -> rusqlite::Result<Option<(Data, Data)>> {
+        self.with_rows_mut(|rows| {
+            rows.next()?
+                .map(|row| Ok((row.get::<usize, Vec<u8>>(0)?, row.get::<usize, Vec<u8>>(1)?)))
+                .transpose()
+        })
+    }
+}
+
+impl<'tx> Iterator for RowIter<'tx> {
+    type Item = (Data, Data);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_entry().expect("iteration to proceed")
+    }
+}
+
 /// Sqlite iterator over entries with given key prefix
-pub struct PrefixIter {
-    /// Underlying iterator
-    iter: std::vec::IntoIter<(Vec<u8>, Vec<u8>)>,
+pub struct PrefixIter<'tx> {
+    /// Underlying iterator over all entries starting at the prefix
+    iter: RowIter<'tx>,
 
     /// Prefix to iterate over
     prefix: Data,
 }
 
-impl PrefixIter {
-    fn new(iter: std::vec::IntoIter<(Vec<u8>, Vec<u8>)>, prefix: Data) -> Self {
+impl<'tx> PrefixIter<'tx> {
+    fn new(iter: RowIter<'tx>, prefix: Data) -> Self {
         PrefixIter { iter, prefix }
     }
 }
 
-impl Iterator for PrefixIter {
+impl<'tx> Iterator for PrefixIter<'tx> {
     type Item = (Data, Data);
 
     fn next(&mut self) -> Option<Self::Item> {
@@ -102,34 +145,38 @@ impl Drop for DbTx<'_> {
 }
 
 impl<'s, 'i> backend::PrefixIter<'i> for DbTx<'s> {
-    type Iterator = PrefixIter;
+    type Iterator = PrefixIter<'i>;
 
     fn prefix_iter<'t: 'i>(
         &'t self,
         idx: DbIndex,
         prefix: Data,
     ) -> storage_core::Result<Self::Iterator> {
-        // TODO check if prefix.is_empty()
-        // TODO Perform the filtering in the SQL query itself
-        let mut stmt = self
+        let stmt = self
             .connection
             .prepare_cached(self.queries[idx].prefix_iter_query.as_str())
             .map_err(process_sqlite_error)?;
 
-        let mut rows = stmt.query(()).map_err(process_sqlite_error)?;
+        // Start at the prefix, the iterator stops at the first key without it
+        let iter = RowIter::query(stmt, (prefix.as_slice(),))?;
 
-        // TODO Move the statement/rows in to the PrefixIter
-        let mut kv = Vec::new();
-        while let Some(row) = rows.next().map_err(process_sqlite_error)? {
-            let key = row.get::<usize, Vec<u8>>(0).map_err(process_sqlite_error)?;
-            if key.starts_with(&prefix) {
-                let value = row.get::<usize, Vec<u8>>(1).map_err(process_sqlite_error)?;
-                kv.push((key, value));
-            }
-        }
-        let kv_iter = kv.into_iter();
+        Ok(PrefixIter::new(iter, prefix))
+    }
+}
+
+impl<'s, 'i> backend::RangeIter<'i> for DbTx<'s> {
+    type Iterator = RowIter<'i>;
+
+    fn range_iter<'t: 'i>(
+        &'t self,
+        idx: DbIndex,
+        range: KeyRange,
+        direction: Direction,
+    ) -> storage_core::Result<Self::Iterator> {
+        let (query, params) = range_iter_query(&db_table_name(idx.get()), &range, direction);
+        let stmt = self.connection.prepare_cached(&query).map_err(process_sqlite_error)?;
 
-        Ok(PrefixIter::new(kv_iter, prefix))
+        RowIter::query(stmt, params_from_iter(params))
     }
 }
 
@@ -224,7 +271,25 @@ impl<'tx> TransactionalRw<'tx> for SqliteImpl {
 
 impl ShallowClone for SqliteImpl {}
 
-impl backend::BackendImpl for SqliteImpl {}
+impl backend::BackendImpl for SqliteImpl {
+    fn backup_native(&self, path: &Path) -> storage_core::Result<()> {
+        let path = path.to_str().ok_or_else(|| {
+            storage_core::error::Recoverable::Io(
+                std::io::ErrorKind::InvalidInput,
+                "The backup path is not valid unicode".to_string(),
+            )
+        })?;
+
+        // VACUUM INTO writes a consistent, compacted copy of the database to a new file
+        let connection = self
+            .0
+            .connection
+            .lock()
+            .map_err(|e| storage_core::error::Fatal::InternalError(e.to_string()))?;
+        connection.execute("VACUUM INTO ?", [path]).map_err(process_sqlite_error)?;
+        Ok(())
+    }
+}
 
 #[derive(Eq, PartialEq, Clone, Debug)]
 pub struct Sqlite {
@@ -13,7 +13,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use storage_core::{DbDesc, DbIndex};
+use std::ops::Bound;
+
+use storage_core::{
+    backend::{Direction, KeyRange},
+    DbDesc, DbIndex,
+};
 
 /// Return the prefix of the database key/value tables
 #[inline]
@@ -27,6 +32,43 @@ pub fn create_table_query(table_name: &String) -> String {
     format!("CREATE TABLE {table_name}(key BLOB PRIMARY KEY NOT NULL, value BLOB NOT NULL)")
 }
 
+/// Returns an SQL query to iterate over keys in given range, together with the query parameters
+pub fn range_iter_query(
+    table_name: &str,
+    range: &KeyRange,
+    direction: Direction,
+) -> (String, Vec<Vec<u8>>) {
+    let mut conditions = Vec::new();
+    let mut params = Vec::new();
+    let mut add_condition =
+        |bound: &Bound<Vec<u8>>, inclusive_op: &str, exclusive_op: &str| match bound {
+            Bound::Included(key) => {
+                conditions.push(format!("key {inclusive_op} ?"));
+                params.push(key.clone());
+            }
+            Bound::Excluded(key) => {
+                conditions.push(format!("key {exclusive_op} ?"));
+                params.push(key.clone());
+            }
+            Bound::Unbounded => (),
+        };
+    add_condition(&range.0, ">=", ">");
+    add_condition(&range.1, "<=", "<");
+
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+    let order = match direction {
+        Direction::Forward => "ASC",
+        Direction::Reverse => "DESC",
+    };
+
+    let query = format!("SELECT key, value FROM {table_name}{where_clause} ORDER BY key {order}");
+    (query, params)
+}
+
 /// SQL queries that are customized per an individual key/value database
 #[derive(Eq, PartialEq, Debug, Clone)]
 pub struct SqliteQuery {
@@ -50,7 +92,9 @@ impl SqliteQueries {
         let queries = (0..desc.len())
             .map(|i| SqliteQuery {
                 get_query: format!("SELECT value FROM db_{i} WHERE key = ?"),
-                prefix_iter_query: format!("SELECT key, value FROM db_{i} ORDER BY key"),
+                prefix_iter_query: format!(
+                    "SELECT key, value FROM db_{i} WHERE key >= ? ORDER BY key"
+                ),
                 put_query: format!("INSERT or REPLACE into db_{i} values(?, ?)"),
                 delete_query: format!("DELETE FROM db_{i} WHERE key = ?"),
             })
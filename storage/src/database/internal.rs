@@ -15,12 +15,15 @@
 
 //! Internal database implementation utils
 
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    ops::{Bound, RangeBounds},
+};
 
 use crate::schema;
-use serialization::{encoded::Encoded, EncodeLike};
+use serialization::{encoded::Encoded, Encode, EncodeLike};
 use storage_core::{
-    backend::{self, PrefixIter, ReadOps},
+    backend::{self, Direction, KeyRange, PrefixIter, RangeIter, ReadOps},
     Backend, DbIndex,
 };
 
@@ -48,6 +51,15 @@ pub fn get<DbMap: schema::DbMap, Tx: ReadOps, K: EncodeLike<DbMap::Key>>(
     key.using_encoded(|key| dbtx.get(idx, key).map(|x| x.map(Encoded::from_bytes_unchecked)))
 }
 
+/// Get the raw value bytes from the database backend, not checked to be a valid encoding
+pub fn get_raw<'tx, Tx: ReadOps>(
+    dbtx: &'tx Tx,
+    idx: DbIndex,
+    key: &[u8],
+) -> crate::Result<Option<Cow<'tx, [u8]>>> {
+    dbtx.get(idx, key)
+}
+
 /// Iterator over DB map entries
 pub trait EntryIterator<DbMap: schema::DbMap>:
     Iterator<Item = (DbMap::Key, Encoded<Vec<u8>, DbMap::Value>)>
@@ -73,3 +85,44 @@ pub fn prefix_iter<'tx, DbMap: schema::DbMap, Tx: PrefixIter<'tx>>(
         })
     })
 }
+
+pub fn prefix_iter_keys<'tx, DbMap: schema::DbMap, Tx: PrefixIter<'tx>>(
+    dbtx: &'tx Tx,
+    idx: DbIndex,
+    prefix: Vec<u8>,
+) -> crate::Result<impl 'tx + Iterator<Item = DbMap::Key>> {
+    dbtx.prefix_iter(idx, prefix)
+        .map(|iter| iter.map(|(k, _v)| Encoded::<_, DbMap::Key>::from_bytes_unchecked(k).decode()))
+}
+
+/// Encode both bounds of a key range
+pub fn encode_range<K: Encode>(range: impl RangeBounds<K>) -> KeyRange {
+    fn encode_bound<K: Encode>(bound: Bound<&K>) -> Bound<Vec<u8>> {
+        match bound {
+            Bound::Included(key) => Bound::Included(key.encode()),
+            Bound::Excluded(key) => Bound::Excluded(key.encode()),
+            Bound::Unbounded => Bound::Unbounded,
+        }
+    }
+
+    (
+        encode_bound(range.start_bound()),
+        encode_bound(range.end_bound()),
+    )
+}
+
+pub fn range_iter<'tx, DbMap: schema::DbMap, Tx: RangeIter<'tx>>(
+    dbtx: &'tx Tx,
+    idx: DbIndex,
+    range: KeyRange,
+    direction: Direction,
+) -> crate::Result<impl 'tx + EntryIterator<DbMap>> {
+    dbtx.range_iter(idx, range, direction).map(|iter| {
+        iter.map(|(k, v)| {
+            (
+                Encoded::from_bytes_unchecked(k).decode(),
+                Encoded::from_bytes_unchecked(v),
+            )
+        })
+    })
+}
@@ -18,13 +18,29 @@
 mod internal;
 pub mod raw;
 
-use std::borrow::Cow;
+use std::{borrow::Cow, io::Write, ops::RangeBounds, path::Path};
 
 use internal::{EntryIterator, TxImpl};
 
 use crate::schema::{self, Schema};
 use serialization::{encoded::Encoded, Encode, EncodeLike};
-use storage_core::{backend, Backend, DbIndex};
+use storage_core::{
+    backend::{self, Direction},
+    Backend, DbIndex,
+};
+
+/// Format of a database backup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupFormat {
+    /// Database in the native format of the storage backend, it can be opened by the backend
+    /// directly. Not supported by all the backends.
+    Native,
+
+    /// Dump of the raw database contents, independent of the storage backend.
+    /// See [raw::write_dump].
+    Portable,
+}
 
 /// The main storage type
 pub struct Storage<B: Backend, Sch> {
@@ -63,6 +79,46 @@ impl<B: Backend, Sch: Schema> Storage<B, Sch> {
         raw::dump_storage(self)
     }
 
+    /// Write raw contents into the database, which has to be empty
+    pub fn restore_raw(&self, contents: &raw::StorageContents<Sch>) -> crate::Result<()> {
+        raw::restore_storage(self, contents)
+    }
+
+    /// Restore the database from a portable dump, see [raw::restore_dump]
+    pub fn restore_dump(&self, reader: &mut impl std::io::Read) -> crate::Result<()> {
+        raw::restore_dump(self, reader)
+    }
+
+    /// Restore the database from another one with the same schema, see [raw::copy_storage]
+    pub fn restore_from<S: Backend>(&self, source: &Storage<S, Sch>) -> crate::Result<()> {
+        raw::copy_storage(self, source)
+    }
+
+    /// Write a consistent backup of the database to given path, which must not exist yet.
+    ///
+    /// The backup is taken from a read-only transaction, so the database can be used while the
+    /// backup is in progress.
+    pub fn backup(&self, path: &Path, format: BackupFormat) -> crate::Result<()> {
+        utils::ensure!(
+            !path.exists(),
+            storage_core::error::Recoverable::Io(
+                std::io::ErrorKind::AlreadyExists,
+                format!("Backup path {} already exists", path.display()),
+            ),
+        );
+
+        match format {
+            BackupFormat::Native => backend::BackendImpl::backup_native(&self.backend, path),
+            BackupFormat::Portable => {
+                let dbtx = self.transaction_ro()?;
+                let file = std::fs::File::create(path).map_err(raw::io_error)?;
+                let mut writer = std::io::BufWriter::new(file);
+                raw::stream_dump(&dbtx, &mut writer)?;
+                writer.flush().map_err(raw::io_error)
+            }
+        }
+    }
+
     /// Start a read-only transaction
     pub fn transaction_ro<'tx, 'st: 'tx>(&'st self) -> crate::Result<TransactionRo<'tx, B, Sch>> {
         Ok(TransactionRo {
@@ -169,6 +225,14 @@ where
         internal::get::<DbMap, _, _>(self.dbtx, self.idx, key)
     }
 
+    /// Get the raw bytes of the value associated with given key.
+    ///
+    /// The bytes are not checked to be a valid encoding of the value type. This is useful to
+    /// convert values stored in an older format.
+    pub fn get_raw<K: EncodeLike<DbMap::Key>>(&self, key: K) -> crate::Result<Option<Cow<[u8]>>> {
+        key.using_encoded(|key| internal::get_raw(self.dbtx, self.idx, key))
+    }
+
     /// Iterator over entries with key starting with given prefix
     pub fn prefix_iter<Pfx>(&self, prefix: &Pfx) -> crate::Result<impl '_ + EntryIterator<DbMap>>
     where
@@ -178,6 +242,18 @@ where
         internal::prefix_iter(self.dbtx, self.idx, prefix.encode())
     }
 
+    /// Iterator over keys starting with given prefix, the values are not looked at
+    pub fn prefix_iter_keys<Pfx>(
+        &self,
+        prefix: &Pfx,
+    ) -> crate::Result<impl '_ + Iterator<Item = DbMap::Key>>
+    where
+        Pfx: Encode,
+        DbMap::Key: HasPrefix<Pfx>,
+    {
+        internal::prefix_iter_keys::<DbMap, _>(self.dbtx, self.idx, prefix.encode())
+    }
+
     /// Iterator over decoded entries with key starting with given prefix
     pub fn prefix_iter_decoded<Pfx>(
         &self,
@@ -190,6 +266,29 @@ where
         internal::prefix_iter::<DbMap, <Tx as TxImpl>::Impl>(self.dbtx, self.idx, prefix.encode())
             .map(|item| item.map(|(k, v)| (k, v.decode())))
     }
+
+    /// Iterator over entries with key in given range, visited in given direction.
+    ///
+    /// Keys are compared by their encoding, which may differ from the ordering of the key type.
+    pub fn range_iter<K: EncodeLike<DbMap::Key>>(
+        &self,
+        range: impl RangeBounds<K>,
+        direction: Direction,
+    ) -> crate::Result<impl '_ + EntryIterator<DbMap>> {
+        let range = internal::encode_range(range);
+        internal::range_iter(self.dbtx, self.idx, range, direction)
+    }
+
+    /// Iterator over decoded entries with key in given range, visited in given direction
+    pub fn range_iter_decoded<K: EncodeLike<DbMap::Key>>(
+        &self,
+        range: impl RangeBounds<K>,
+        direction: Direction,
+    ) -> crate::Result<impl '_ + Iterator<Item = (DbMap::Key, DbMap::Value)>> {
+        let range = internal::encode_range(range);
+        internal::range_iter::<DbMap, <Tx as TxImpl>::Impl>(self.dbtx, self.idx, range, direction)
+            .map(|item| item.map(|(k, v)| (k, v.decode())))
+    }
 }
 
 /// Represents a mutable view of a key-value map
@@ -231,6 +330,18 @@ where
     {
         internal::prefix_iter(self.dbtx, self.idx, prefix.encode())
     }
+
+    /// Iterator over entries with key in given range, visited in given direction.
+    ///
+    /// Keys are compared by their encoding, which may differ from the ordering of the key type.
+    pub fn range_iter<K: EncodeLike<DbMap::Key>>(
+        &self,
+        range: impl RangeBounds<K>,
+        direction: Direction,
+    ) -> crate::Result<impl '_ + EntryIterator<DbMap>> {
+        let range = internal::encode_range(range);
+        internal::range_iter(self.dbtx, self.idx, range, direction)
+    }
 }
 
 impl<Tx: TxImpl, DbMap: schema::DbMap> MapMut<'_, Tx, DbMap>
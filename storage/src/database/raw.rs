@@ -17,10 +17,14 @@
 
 use crate::{
     schema::{self, HasDbMap, Schema},
-    Backend, Storage,
+    Backend, Storage, TransactionRo,
 };
+use serialization::{Compact, Decode, Encode};
 use std::collections::BTreeMap;
-use storage_core::backend::PrefixIter;
+use storage_core::{
+    backend::{PrefixIter, WriteOps},
+    error::Recoverable,
+};
 
 pub use storage_core::Data;
 
@@ -138,6 +142,248 @@ pub fn dump_storage<B: Backend, Sch: Schema>(
         .collect::<crate::Result<StorageContents<Sch>>>()
 }
 
+/// Magic bytes at the start of a portable dump
+const DUMP_MAGIC: [u8; 8] = *b"MLSTDUMP";
+
+/// Version of the portable dump format
+const DUMP_FORMAT_VERSION: u32 = 1;
+
+/// Turn an I/O error into a recoverable storage error
+pub(crate) fn io_error(err: std::io::Error) -> crate::Error {
+    Recoverable::Io(err.kind(), err.to_string()).into()
+}
+
+fn invalid_dump(msg: impl Into<String>) -> crate::Error {
+    Recoverable::Io(std::io::ErrorKind::InvalidData, msg.into()).into()
+}
+
+/// Write raw storage contents in the portable dump format.
+///
+/// The dump consists of magic bytes, the format version and the SCALE-encoded contents of each
+/// key-value map together with the map name.
+pub fn write_dump<Sch: Schema>(
+    contents: &StorageContents<Sch>,
+    writer: &mut impl std::io::Write,
+) -> crate::Result<()> {
+    write_dump_header(contents.len(), writer)?;
+    for (idx, map) in contents {
+        write_map_header(&idx.name(), map.len(), writer)?;
+        for (key, val) in map {
+            write_entry(key, val, writer)?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the contents of the database seen by given transaction in the portable dump format,
+/// the same as [write_dump] does. The entries are written as they are read from the database,
+/// each map is read twice to get the number of its entries first.
+pub fn stream_dump<B: Backend, Sch: Schema>(
+    dbtx: &TransactionRo<'_, B, Sch>,
+    writer: &mut impl std::io::Write,
+) -> crate::Result<()> {
+    write_dump_header(Sch::desc_iter().count(), writer)?;
+    for (idx, desc) in Sch::desc_iter().enumerate() {
+        let idx = storage_core::DbIndex::new(idx);
+        let num_entries = dbtx.dbtx.prefix_iter(idx, Vec::new())?.count();
+        write_map_header(&desc.name, num_entries, writer)?;
+        for (key, val) in dbtx.dbtx.prefix_iter(idx, Vec::new())? {
+            write_entry(&key, &val, writer)?;
+        }
+    }
+    Ok(())
+}
+
+// After the magic bytes and the version, the dump is the encoding of the key-value maps with their
+// entries, `Vec<(String, Vec<(Data, Data)>)>`. The maps are identified by their names, so a dump
+// can be loaded even if the order of the maps in the schema changes. It's written and read piece
+// by piece, so that it doesn't have to be held in memory
+
+fn write_dump_header(num_maps: usize, writer: &mut impl std::io::Write) -> crate::Result<()> {
+    writer.write_all(&DUMP_MAGIC).map_err(io_error)?;
+    writer.write_all(&DUMP_FORMAT_VERSION.encode()).map_err(io_error)?;
+    writer.write_all(&Compact(num_maps as u32).encode()).map_err(io_error)
+}
+
+fn write_map_header(
+    name: &str,
+    num_entries: usize,
+    writer: &mut impl std::io::Write,
+) -> crate::Result<()> {
+    writer.write_all(&name.encode()).map_err(io_error)?;
+    writer.write_all(&Compact(num_entries as u32).encode()).map_err(io_error)
+}
+
+fn write_entry(key: &Data, val: &Data, writer: &mut impl std::io::Write) -> crate::Result<()> {
+    writer.write_all(&key.encode()).map_err(io_error)?;
+    writer.write_all(&val.encode()).map_err(io_error)
+}
+
+/// Decoder input reading from an [std::io::Read]
+struct ReaderInput<R>(R);
+
+impl<R: std::io::Read> serialization::Input for ReaderInput<R> {
+    fn remaining_len(&mut self) -> Result<Option<usize>, serialization::Error> {
+        Ok(None)
+    }
+
+    fn read(&mut self, into: &mut [u8]) -> Result<(), serialization::Error> {
+        self.0.read_exact(into).map_err(|_| "Failed to read the storage dump".into())
+    }
+}
+
+/// Decode a portable dump written by [write_dump] entry by entry, passing each entry to `f`
+fn decode_dump<Sch: Schema>(
+    reader: &mut impl std::io::Read,
+    mut f: impl FnMut(DbIndex<Sch>, Data, Data) -> crate::Result<()>,
+) -> crate::Result<()> {
+    let mut input = ReaderInput(reader);
+    let decode_error = |e: serialization::Error| invalid_dump(e.to_string());
+
+    let magic = <[u8; 8]>::decode(&mut input).map_err(decode_error)?;
+    utils::ensure!(
+        magic == DUMP_MAGIC,
+        invalid_dump("Not a portable storage dump"),
+    );
+    let version = u32::decode(&mut input).map_err(decode_error)?;
+    utils::ensure!(
+        version == DUMP_FORMAT_VERSION,
+        invalid_dump(format!("Unsupported storage dump version {version}")),
+    );
+
+    let num_maps = Compact::<u32>::decode(&mut input).map_err(decode_error)?.0;
+    for _ in 0..num_maps {
+        let name = String::decode(&mut input).map_err(decode_error)?;
+        let idx = DbIndex::from_name(&name)
+            .ok_or_else(|| invalid_dump(format!("Unknown key-value map {name}")))?;
+        let num_entries = Compact::<u32>::decode(&mut input).map_err(decode_error)?.0;
+        for _ in 0..num_entries {
+            let key = Data::decode(&mut input).map_err(decode_error)?;
+            let val = Data::decode(&mut input).map_err(decode_error)?;
+            f(idx, key, val)?;
+        }
+    }
+
+    let mut rest = [0u8; 1];
+    let trailing = input.0.read(&mut rest).map_err(io_error)?;
+    utils::ensure!(
+        trailing == 0,
+        invalid_dump("Unexpected data after the end of the storage dump"),
+    );
+    Ok(())
+}
+
+/// Read raw storage contents written by [write_dump]
+pub fn read_dump<Sch: Schema>(
+    reader: &mut impl std::io::Read,
+) -> crate::Result<StorageContents<Sch>> {
+    let mut contents: StorageContents<Sch> = Sch::desc_iter()
+        .enumerate()
+        .map(|(idx, _)| (DbIndex::from_usize_unchecked(idx), MapContents::new()))
+        .collect();
+    decode_dump::<Sch>(reader, |idx, key, val| {
+        contents.entry(idx).or_default().insert(key, val);
+        Ok(())
+    })?;
+    Ok(contents)
+}
+
+/// How many bytes of entries are written in one transaction when restoring a database
+const RESTORE_BATCH_SIZE: usize = 64 * 1024 * 1024;
+
+/// Writes entries into a storage, committing a transaction each time the buffered entries
+/// reach [RESTORE_BATCH_SIZE] bytes, so the whole database doesn't have to be held in memory
+struct BatchWriter<'st, B: Backend, Sch> {
+    storage: &'st Storage<B, Sch>,
+    batch: Vec<(storage_core::DbIndex, Data, Data)>,
+    batch_size: usize,
+}
+
+impl<'st, B: Backend, Sch: Schema> BatchWriter<'st, B, Sch> {
+    /// Start writing into the storage, which has to be empty
+    fn new(storage: &'st Storage<B, Sch>) -> crate::Result<Self> {
+        let dbtx = storage.transaction_ro()?;
+        for (idx, _dbinfo) in Sch::desc_iter().enumerate() {
+            let idx = storage_core::DbIndex::new(idx);
+            let is_empty = dbtx.dbtx.prefix_iter(idx, Vec::new())?.next().is_none();
+            utils::ensure!(
+                is_empty,
+                Recoverable::Io(
+                    std::io::ErrorKind::AlreadyExists,
+                    "Cannot restore into a non-empty database".to_string(),
+                ),
+            );
+        }
+
+        Ok(Self {
+            storage,
+            batch: Vec::new(),
+            batch_size: 0,
+        })
+    }
+
+    fn put(&mut self, idx: storage_core::DbIndex, key: Data, val: Data) -> crate::Result<()> {
+        self.batch_size += key.len() + val.len();
+        self.batch.push((idx, key, val));
+        if self.batch_size >= RESTORE_BATCH_SIZE {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> crate::Result<()> {
+        let mut dbtx = self.storage.transaction_rw(None)?;
+        for (idx, key, val) in self.batch.drain(..) {
+            dbtx.dbtx.put(idx, key, val)?;
+        }
+        self.batch_size = 0;
+        dbtx.commit()
+    }
+}
+
+/// Write raw contents into a storage, which has to be empty
+pub fn restore_storage<B: Backend, Sch: Schema>(
+    storage: &Storage<B, Sch>,
+    contents: &StorageContents<Sch>,
+) -> crate::Result<()> {
+    let mut writer = BatchWriter::new(storage)?;
+    for (idx, map) in contents {
+        for (key, val) in map {
+            writer.put(idx.idx, key.clone(), val.clone())?;
+        }
+    }
+    writer.flush()
+}
+
+/// Write the contents of a portable dump into a storage, which has to be empty. The entries are
+/// written as they are read, in transactions of limited size; if the restore is interrupted, the
+/// partially written database has to be removed before trying again.
+pub fn restore_dump<B: Backend, Sch: Schema>(
+    storage: &Storage<B, Sch>,
+    reader: &mut impl std::io::Read,
+) -> crate::Result<()> {
+    let mut writer = BatchWriter::new(storage)?;
+    decode_dump::<Sch>(reader, |idx, key, val| writer.put(idx.idx, key, val))?;
+    writer.flush()
+}
+
+/// Copy the contents of one storage into another, which has to be empty. The entries are written
+/// as they are read from the source, in transactions of limited size, as [restore_dump] does.
+pub fn copy_storage<B: Backend, S: Backend, Sch: Schema>(
+    storage: &Storage<B, Sch>,
+    source: &Storage<S, Sch>,
+) -> crate::Result<()> {
+    let mut writer = BatchWriter::new(storage)?;
+    let dbtx = source.transaction_ro()?;
+    for (idx, _dbinfo) in Sch::desc_iter().enumerate() {
+        let idx = storage_core::DbIndex::new(idx);
+        for (key, val) in dbtx.dbtx.prefix_iter(idx, Vec::new())? {
+            writer.put(idx, key, val)?;
+        }
+    }
+    writer.flush()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -211,4 +457,47 @@ mod test {
             }
         })
     }
+
+    #[test]
+    fn portable_dump() {
+        utils::concurrency::model(|| {
+            let storage = Storage::<_, TestSchema>::new(InMemory::new()).unwrap();
+            let mut dbtx = storage.transaction_rw(None).unwrap();
+            dbtx.get_mut::<Db0, _>().put(42, 1337).unwrap();
+            dbtx.get_mut::<Db0, _>().put(43, 1338).unwrap();
+            dbtx.get_mut::<Db1, _>().put(21, vec![1, 2, 3, 4]).unwrap();
+            dbtx.commit().unwrap();
+            let contents = storage.dump_raw().unwrap();
+
+            let mut dump = Vec::new();
+            write_dump(&contents, &mut dump).unwrap();
+            assert_eq!(
+                read_dump::<TestSchema>(&mut dump.as_slice()),
+                Ok(contents.clone())
+            );
+
+            // Restore into a new database
+            let restored = Storage::<_, TestSchema>::new(InMemory::new()).unwrap();
+            restored.restore_raw(&read_dump(&mut dump.as_slice()).unwrap()).unwrap();
+            assert_eq!(restored.dump_raw(), Ok(contents.clone()));
+
+            // Restoring into a database with contents is refused
+            assert!(restored.restore_raw(&contents).is_err());
+            assert_eq!(restored.dump_raw(), Ok(contents.clone()));
+
+            // The dump is restored entry by entry as well
+            let restored = Storage::<_, TestSchema>::new(InMemory::new()).unwrap();
+            restored.restore_dump(&mut dump.as_slice()).unwrap();
+            assert_eq!(restored.dump_raw(), Ok(contents));
+            assert!(restored.restore_dump(&mut dump.as_slice()).is_err());
+
+            // Corrupted dumps are rejected
+            assert!(read_dump::<TestSchema>(&mut &dump[1..]).is_err());
+            assert!(read_dump::<TestSchema>(&mut &dump[..dump.len() - 1]).is_err());
+            let trailing = [dump.as_slice(), &[0]].concat();
+            assert!(read_dump::<TestSchema>(&mut trailing.as_slice()).is_err());
+            let restored = Storage::<_, TestSchema>::new(InMemory::new()).unwrap();
+            assert!(restored.restore_dump(&mut &dump[..dump.len() - 1]).is_err());
+        })
+    }
 }
@@ -38,6 +38,13 @@
 //! type `H256` representing the transaction ID. The result is an iterator over all
 //! `(Outpoint, Utxo)` pairs that belong to given transaction.
 //!
+//! # Range iteration
+//!
+//! Entries with keys in given range can be visited in either direction using
+//! `map.range_iter(range, direction)`. Keys are compared by their encoding, so for keys with
+//! a non order-preserving encoding (e.g. little-endian integers), the range is in terms of the
+//! byte representation rather than the key values.
+//!
 //! # Example
 //!
 //! ```
@@ -96,7 +103,7 @@ mod database;
 pub mod schema;
 
 // Re-export user-facing items from core
-pub use storage_core::{error, Backend, Error, Result};
+pub use storage_core::{backend::Direction, error, Backend, Error, Result};
 
 // Re-export the interface types
 pub use database::*;
@@ -120,3 +120,69 @@ fn prefix_iteration() {
         dbtx.close();
     });
 }
+
+#[test]
+fn range_iteration() {
+    utils::concurrency::model(|| {
+        let store = Storage::<_, Compound>::new(inmemory::InMemory::new()).unwrap();
+
+        let test_values = [
+            ((String::from("foo"), 12), 0),
+            ((String::from("foo"), 1), 1),
+            ((String::from("foo"), 2), 2),
+            ((String::from("bar"), 42), 3),
+            ((String::from("bar"), 43), 4),
+            ((String::from("hello"), 1337), 6),
+        ];
+
+        // Populate the database
+        let mut dbtx = store.transaction_rw(None).unwrap();
+        let mut map = dbtx.get_mut::<Map2, _>();
+        for (key, val) in &test_values {
+            map.put(key, val).unwrap();
+        }
+        dbtx.commit().unwrap();
+
+        let range = (String::from("bar"), 43)..=(String::from("foo"), 2);
+        let expected = vec![
+            ((String::from("bar"), 43), 4),
+            ((String::from("foo"), 1), 1),
+            ((String::from("foo"), 2), 2),
+        ];
+
+        // Iterate over the range forward
+        let dbtx = store.transaction_ro().unwrap();
+        let items: Vec<_> = dbtx
+            .get::<Map2, _>()
+            .range_iter(range.clone(), Direction::Forward)
+            .unwrap()
+            .map(|(k, v)| (k, v.decode()))
+            .collect();
+        assert_eq!(items, expected);
+        dbtx.close();
+
+        // Iterate over the range in reverse, decoded
+        let dbtx = store.transaction_ro().unwrap();
+        let items: Vec<_> = dbtx
+            .get::<Map2, _>()
+            .range_iter_decoded(range, Direction::Reverse)
+            .unwrap()
+            .collect();
+        let expected_rev: Vec<_> = expected.into_iter().rev().collect();
+        assert_eq!(items, expected_rev);
+        dbtx.close();
+
+        // Iterate over everything from the last "foo" entry, including uncommitted changes
+        let mut dbtx = store.transaction_rw(None).unwrap();
+        let mut map = dbtx.get_mut::<Map2, _>();
+        map.del((String::from("hello"), 1337)).unwrap();
+        map.put((String::from("world"), 0), 7).unwrap();
+        let items: Vec<_> = map
+            .range_iter((String::from("foo"), 12).., Direction::Forward)
+            .unwrap()
+            .map(|(k, v)| (k, v.decode()))
+            .collect();
+        let expected = vec![((String::from("foo"), 12), 0), ((String::from("world"), 0), 7)];
+        assert_eq!(items, expected);
+    });
+}
@@ -0,0 +1,113 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Backups of databases using the persistent storage backends
+
+use std::path::Path;
+
+use storage::{BackupFormat, Storage};
+use storage_core::Backend;
+use storage_lmdb::Lmdb;
+use storage_sqlite::Sqlite;
+use tempdir::TempDir;
+
+storage::decl_schema! {
+    Schema {
+        Map1: Map<u32, String>,
+        Map2: Map<String, u64>,
+    }
+}
+
+fn populate<B: Backend>(storage: &Storage<B, Schema>, num_entries: u32) {
+    let mut dbtx = storage.transaction_rw(None).unwrap();
+    for i in 0..num_entries {
+        dbtx.get_mut::<Map1, _>().put(i, format!("value {i}")).unwrap();
+        dbtx.get_mut::<Map2, _>().put(format!("key {i}"), u64::from(i) * 3).unwrap();
+    }
+    dbtx.commit().unwrap();
+}
+
+fn check_backups<B: Backend, R: Backend>(backend: B, reopen: impl Fn(&Path) -> R) {
+    let dir = TempDir::new("storage-backup").unwrap();
+    let storage = Storage::<_, Schema>::new(backend).unwrap();
+    populate(&storage, 500);
+    let contents = storage.dump_raw().unwrap();
+
+    // Native backup, opened by the same backend
+    let native_path = dir.path().join("native");
+    storage.backup(&native_path, BackupFormat::Native).unwrap();
+    let backup = Storage::<_, Schema>::new(reopen(&native_path)).unwrap();
+    assert_eq!(backup.dump_raw(), Ok(contents.clone()));
+
+    // The native backup copied into a new database
+    let copied = Storage::<_, Schema>::new(reopen(&dir.path().join("copied"))).unwrap();
+    copied.restore_from(&backup).unwrap();
+    assert_eq!(copied.dump_raw(), Ok(contents.clone()));
+
+    // Portable backup, restored into a new database
+    let portable_path = dir.path().join("portable.dump");
+    storage.backup(&portable_path, BackupFormat::Portable).unwrap();
+    let file = std::fs::File::open(&portable_path).unwrap();
+    let dump = storage::raw::read_dump(&mut std::io::BufReader::new(file)).unwrap();
+    assert_eq!(dump, contents);
+    let restored = Storage::<_, Schema>::new(reopen(&dir.path().join("restored"))).unwrap();
+    let file = std::fs::File::open(&portable_path).unwrap();
+    restored.restore_dump(&mut std::io::BufReader::new(file)).unwrap();
+    assert_eq!(restored.dump_raw(), Ok(contents.clone()));
+
+    // Restoring into a database with contents is refused
+    assert!(restored.restore_from(&backup).is_err());
+
+    // Existing backups are not overwritten
+    populate(&storage, 600);
+    assert!(storage.backup(&native_path, BackupFormat::Native).is_err());
+    assert!(storage.backup(&portable_path, BackupFormat::Portable).is_err());
+    assert_eq!(backup.dump_raw(), Ok(contents));
+}
+
+#[test]
+fn lmdb_backups() {
+    let dir = TempDir::new("lmdb").unwrap();
+    let reopen = |path: &Path| {
+        Lmdb::new(
+            path.to_path_buf(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+    };
+    check_backups(reopen(&dir.path().join("db")), reopen);
+}
+
+#[test]
+fn sqlite_backups() {
+    let dir = TempDir::new("sqlite").unwrap();
+    let reopen = |path: &Path| Sqlite::new(path.to_path_buf());
+    check_backups(reopen(&dir.path().join("db.sqlite")), reopen);
+}
+
+#[test]
+fn inmemory_has_no_native_format() {
+    let dir = TempDir::new("inmemory").unwrap();
+    let storage = Storage::<_, Schema>::new(storage_inmemory::InMemory::new()).unwrap();
+    populate(&storage, 10);
+    let result = storage.backup(&dir.path().join("native"), BackupFormat::Native);
+    assert!(matches!(
+        result,
+        Err(storage::Error::Recoverable(
+            storage::error::Recoverable::Io(std::io::ErrorKind::Unsupported, _)
+        ))
+    ));
+}
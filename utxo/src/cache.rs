@@ -16,7 +16,7 @@
 use crate::{
     utxo_entry::{IsDirty, IsFresh, UtxoEntry},
     {
-        Error, FlushableUtxoView, Utxo, UtxoSource, UtxosBlockRewardUndo, UtxosTxUndo,
+        Error, FlushableUtxoView, Utxo, UtxoSetHash, UtxoSource, UtxosBlockRewardUndo, UtxosTxUndo,
         UtxosTxUndoWithSources, UtxosView,
     },
 };
@@ -38,6 +38,7 @@ use std::{
 pub struct ConsumedUtxoCache {
     pub(crate) container: BTreeMap<OutPoint, UtxoEntry>,
     pub(crate) best_block: Id<GenBlock>,
+    pub(crate) set_hash_delta: UtxoSetHash,
 }
 
 impl<T> UtxosView for T
@@ -67,6 +68,8 @@ pub struct UtxosCache<P> {
     current_block_hash: Id<GenBlock>,
     // pub(crate) visibility is required for tests that are in a different mod
     pub(crate) utxos: BTreeMap<OutPoint, UtxoEntry>,
+    // the changes made to the utxo set of the parent, in terms of its rolling hash
+    set_hash_delta: UtxoSetHash,
     // TODO: calculate memory usage (mintlayer/mintlayer-core#354)
     #[allow(dead_code)]
     memory_usage: usize,
@@ -103,6 +106,7 @@ impl<P: UtxosView> UtxosCache<P> {
             parent,
             current_block_hash,
             utxos: BTreeMap::new(),
+            set_hash_delta: UtxoSetHash::new(),
             memory_usage: 0,
         }
     }
@@ -265,6 +269,12 @@ impl<P: UtxosView> UtxosCache<P> {
         // TODO: update the memory usage
         // self.memory_usage should be deducted based on this current entry.
 
+        if possible_overwrite {
+            if let Some(replaced_utxo) = self.utxo(outpoint) {
+                self.set_hash_delta.remove_utxo(outpoint, &replaced_utxo);
+            }
+        }
+
         let is_fresh = match self.utxos.get(outpoint) {
             None => {
                 // An insert can be done. This utxo doesn't exist yet, so it's fresh.
@@ -295,6 +305,8 @@ impl<P: UtxosView> UtxosCache<P> {
             }
         };
 
+        self.set_hash_delta.add_utxo(outpoint, &utxo);
+
         // create a new entry
         let new_entry = UtxoEntry::new(Some(utxo), IsFresh::from(is_fresh), IsDirty::Yes);
 
@@ -323,7 +335,9 @@ impl<P: UtxosView> UtxosCache<P> {
             self.utxos.insert(outpoint.clone(), new_entry);
         }
 
-        entry.take_utxo().ok_or_else(|| Error::UtxoAlreadySpent(outpoint.tx_id()))
+        let utxo = entry.take_utxo().ok_or_else(|| Error::UtxoAlreadySpent(outpoint.tx_id()))?;
+        self.set_hash_delta.remove_utxo(outpoint, &utxo);
+        Ok(utxo)
     }
 
     /// Checks whether utxo exists in the cache
@@ -332,6 +346,7 @@ impl<P: UtxosView> UtxosCache<P> {
     }
 
     /// Returns a mutable reference of the utxo, given the outpoint.
+    /// Changes made through the reference are not reflected in the utxo set hash.
     pub fn get_mut_utxo(&mut self, outpoint: &OutPoint) -> Option<&mut Utxo> {
         let entry = self.fetch_utxo_entry(outpoint)?;
         let utxo = entry.utxo()?;
@@ -366,6 +381,7 @@ impl<P: UtxosView> UtxosCache<P> {
         ConsumedUtxoCache {
             container: self.utxos,
             best_block: self.current_block_hash,
+            set_hash_delta: self.set_hash_delta,
         }
     }
 }
@@ -462,6 +478,7 @@ impl<P> FlushableUtxoView for UtxosCache<P> {
         }
 
         self.current_block_hash = utxo_entries.best_block;
+        self.set_hash_delta.combine(&utxo_entries.set_hash_delta);
         Ok(())
     }
 }
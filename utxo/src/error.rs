@@ -40,4 +40,6 @@ pub enum Error {
     InvalidBlockRewardOutputType(Id<GenBlock>),
     #[error("Database error: `{0}`")]
     DBError(#[from] storage_result::Error),
+    #[error("Database error: the utxo set hash at the best block `{0}` is missing")]
+    MissingUtxoSetHash(Id<GenBlock>),
 }
@@ -15,6 +15,7 @@
 
 mod cache;
 mod error;
+mod set_hash;
 mod storage;
 mod undo;
 mod utxo;
@@ -24,6 +25,7 @@ mod view;
 pub use crate::{
     cache::{ConsumedUtxoCache, UtxosCache},
     error::Error,
+    set_hash::UtxoSetHash,
     storage::{UtxosDB, UtxosStorageRead, UtxosStorageWrite},
     undo::{
         UtxosBlockRewardUndo, UtxosBlockUndo, UtxosBlockUndoError, UtxosTxUndo,
@@ -0,0 +1,58 @@
+// Copyright (c) 2023 RBB S.r.l
+// opensource@mintlayer.org
+// SPDX-License-Identifier: MIT
+// Licensed under the MIT License;
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// https://github.com/mintlayer/mintlayer-core/blob/master/LICENSE
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use common::{chain::OutPoint, primitives::H256};
+use crypto::hash::LtHash16;
+use serialization::{Decode, Encode};
+
+use crate::Utxo;
+
+/// Rolling hash of a utxo set, or of the changes made to it.
+///
+/// The hash doesn't depend on the order in which the utxos are added and removed, so it can be
+/// updated as the set changes and compared between nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct UtxoSetHash(LtHash16);
+
+impl UtxoSetHash {
+    /// The hash of the empty utxo set
+    pub fn new() -> Self {
+        Self(LtHash16::new())
+    }
+
+    pub fn from_utxos<'a>(utxos: impl IntoIterator<Item = (&'a OutPoint, &'a Utxo)>) -> Self {
+        let mut hash = Self::new();
+        utxos.into_iter().for_each(|(outpoint, utxo)| hash.add_utxo(outpoint, utxo));
+        hash
+    }
+
+    pub fn add_utxo(&mut self, outpoint: &OutPoint, utxo: &Utxo) {
+        self.0.insert(&(outpoint, utxo).encode());
+    }
+
+    pub fn remove_utxo(&mut self, outpoint: &OutPoint, utxo: &Utxo) {
+        self.0.remove(&(outpoint, utxo).encode());
+    }
+
+    /// Apply the changes tracked by the other hash to this one
+    pub fn combine(&mut self, other: &UtxoSetHash) {
+        self.0.combine(&other.0)
+    }
+
+    /// The final value of the hash
+    pub fn digest(&self) -> H256 {
+        self.0.finalize().into()
+    }
+}
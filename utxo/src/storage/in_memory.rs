@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use super::{UtxosStorageRead, UtxosStorageWrite};
-use crate::{Utxo, UtxosBlockUndo, UtxosView};
+use crate::{Utxo, UtxoSetHash, UtxosBlockUndo, UtxosView};
 use chainstate_types::storage_result::Error;
 use common::{
     chain::{Block, GenBlock, OutPoint},
@@ -26,14 +26,17 @@ use std::collections::BTreeMap;
 pub struct UtxosDBInMemoryImpl {
     store: BTreeMap<OutPoint, Utxo>,
     undo_store: BTreeMap<Id<Block>, UtxosBlockUndo>,
+    set_hash_store: BTreeMap<Id<GenBlock>, UtxoSetHash>,
     best_block_id: Id<GenBlock>,
 }
 
 impl UtxosDBInMemoryImpl {
     pub fn new(best_block: Id<GenBlock>, initial_utxos: BTreeMap<OutPoint, Utxo>) -> Self {
+        let set_hash = UtxoSetHash::from_utxos(&initial_utxos);
         Self {
             store: initial_utxos,
             undo_store: BTreeMap::new(),
+            set_hash_store: BTreeMap::from([(best_block, set_hash)]),
             best_block_id: best_block,
         }
     }
@@ -53,6 +56,10 @@ impl UtxosStorageRead for UtxosDBInMemoryImpl {
     fn get_best_block_for_utxos(&self) -> Result<Option<Id<GenBlock>>, Error> {
         Ok(Some(self.best_block_id))
     }
+
+    fn get_utxo_set_hash(&self, block_id: &Id<GenBlock>) -> Result<Option<UtxoSetHash>, Error> {
+        Ok(self.set_hash_store.get(block_id).cloned())
+    }
 }
 
 impl UtxosStorageWrite for UtxosDBInMemoryImpl {
@@ -78,6 +85,20 @@ impl UtxosStorageWrite for UtxosDBInMemoryImpl {
         self.undo_store.remove(&id);
         Ok(())
     }
+
+    fn set_utxo_set_hash(
+        &mut self,
+        block_id: &Id<GenBlock>,
+        hash: &UtxoSetHash,
+    ) -> Result<(), Error> {
+        self.set_hash_store.insert(*block_id, hash.clone());
+        Ok(())
+    }
+
+    fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> Result<(), Error> {
+        self.set_hash_store.remove(block_id);
+        Ok(())
+    }
 }
 
 impl UtxosView for UtxosDBInMemoryImpl {
@@ -16,7 +16,7 @@
 mod rw_impls;
 mod view_impls;
 
-use crate::{FlushableUtxoView, Utxo, UtxosBlockUndo, UtxosCache};
+use crate::{FlushableUtxoView, Utxo, UtxoSetHash, UtxosBlockUndo, UtxosCache};
 use chainstate_types::storage_result;
 use common::{
     chain::{Block, ChainConfig, GenBlock, OutPoint},
@@ -29,6 +29,10 @@ pub trait UtxosStorageRead {
     fn get_best_block_for_utxos(&self) -> Result<Option<Id<GenBlock>>, storage_result::Error>;
     fn get_undo_data(&self, id: Id<Block>)
         -> Result<Option<UtxosBlockUndo>, storage_result::Error>;
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<UtxoSetHash>, storage_result::Error>;
 }
 
 pub trait UtxosStorageWrite: UtxosStorageRead {
@@ -46,6 +50,13 @@ pub trait UtxosStorageWrite: UtxosStorageRead {
         undo: &UtxosBlockUndo,
     ) -> Result<(), storage_result::Error>;
     fn del_undo_data(&mut self, id: Id<Block>) -> Result<(), storage_result::Error>;
+
+    fn set_utxo_set_hash(
+        &mut self,
+        block_id: &Id<GenBlock>,
+        hash: &UtxoSetHash,
+    ) -> Result<(), storage_result::Error>;
+    fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> Result<(), storage_result::Error>;
 }
 
 #[must_use]
@@ -72,8 +83,11 @@ impl<S: UtxosStorageWrite> UtxosDB<S> {
 
         let mut utxos_db = Self(store);
 
-        // before deriving cache, there has to be a best block
+        // before deriving cache, there has to be a best block with the hash of the empty set
         utxos_db.set_best_block_for_utxos(&genesis_id).expect("Setting genesis failed");
+        utxos_db
+            .set_utxo_set_hash(&genesis_id, &UtxoSetHash::default())
+            .expect("Setting genesis utxo set hash failed");
 
         let mut utxos_cache = UtxosCache::new(&utxos_db);
 
@@ -114,6 +128,13 @@ where
     ) -> Result<Option<UtxosBlockUndo>, storage_result::Error> {
         self.deref().get_undo_data(id)
     }
+
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<UtxoSetHash>, storage_result::Error> {
+        self.deref().get_utxo_set_hash(block_id)
+    }
 }
 
 impl<T> UtxosStorageWrite for T
@@ -147,6 +168,18 @@ where
     fn del_undo_data(&mut self, id: Id<Block>) -> Result<(), storage_result::Error> {
         self.deref_mut().del_undo_data(id)
     }
+
+    fn set_utxo_set_hash(
+        &mut self,
+        block_id: &Id<GenBlock>,
+        hash: &UtxoSetHash,
+    ) -> Result<(), storage_result::Error> {
+        self.deref_mut().set_utxo_set_hash(block_id, hash)
+    }
+
+    fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> Result<(), storage_result::Error> {
+        self.deref_mut().del_utxo_set_hash(block_id)
+    }
 }
 
 #[cfg(test)]
@@ -14,7 +14,7 @@
 // limitations under the License.
 
 use super::{UtxosDB, UtxosStorageRead, UtxosStorageWrite};
-use crate::{Utxo, UtxosBlockUndo};
+use crate::{Utxo, UtxoSetHash, UtxosBlockUndo};
 use chainstate_types::storage_result::Error as StorageError;
 use common::{
     chain::{Block, GenBlock, OutPoint},
@@ -40,6 +40,18 @@ impl<S: UtxosStorageWrite> UtxosStorageWrite for UtxosDB<S> {
     fn del_undo_data(&mut self, id: Id<Block>) -> Result<(), StorageError> {
         self.0.del_undo_data(id)
     }
+
+    fn set_utxo_set_hash(
+        &mut self,
+        block_id: &Id<GenBlock>,
+        hash: &UtxoSetHash,
+    ) -> Result<(), StorageError> {
+        self.0.set_utxo_set_hash(block_id, hash)
+    }
+
+    fn del_utxo_set_hash(&mut self, block_id: &Id<GenBlock>) -> Result<(), StorageError> {
+        self.0.del_utxo_set_hash(block_id)
+    }
 }
 
 impl<S: UtxosStorageRead> UtxosStorageRead for UtxosDB<S> {
@@ -54,4 +66,11 @@ impl<S: UtxosStorageRead> UtxosStorageRead for UtxosDB<S> {
     fn get_undo_data(&self, id: Id<Block>) -> Result<Option<UtxosBlockUndo>, StorageError> {
         self.0.get_undo_data(id)
     }
+
+    fn get_utxo_set_hash(
+        &self,
+        block_id: &Id<GenBlock>,
+    ) -> Result<Option<UtxoSetHash>, StorageError> {
+        self.0.get_utxo_set_hash(block_id)
+    }
 }
@@ -87,23 +87,18 @@ fn initialize_db(
     tx_outputs_size: u32,
 ) -> (UtxosDBInMemoryImpl, Vec<OutPoint>) {
     let best_block_id: Id<GenBlock> = Id::new(H256::random_using(rng));
-    let mut db_interface = UtxosDBInMemoryImpl::new(best_block_id, Default::default());
 
     // let's populate the db with outputs.
     let tx_outputs = create_tx_outputs(rng, tx_outputs_size);
-
-    // collect outpoints for spending later
-    let outpoints = tx_outputs
+    let utxos = tx_outputs
         .into_iter()
         .enumerate()
-        .map(|(idx, output)| {
-            let (outpoint, utxo) = convert_to_utxo(rng, output, 0, idx);
-            // immediately add to the db
-            assert!(db_interface.set_utxo(&outpoint, utxo).is_ok());
+        .map(|(idx, output)| convert_to_utxo(rng, output, 0, idx))
+        .collect::<BTreeMap<_, _>>();
 
-            outpoint
-        })
-        .collect_vec();
+    // collect outpoints for spending later
+    let outpoints = utxos.keys().cloned().collect_vec();
+    let db_interface = UtxosDBInMemoryImpl::new(best_block_id, utxos);
 
     (db_interface, outpoints)
 }
@@ -137,6 +132,7 @@ fn utxo_and_undo_test(#[case] seed: Seed) {
     let expected_tx_inputs = create_tx_inputs(&mut rng, &outpoints);
     // create the UtxosDB.
     let mut db = UtxosDB::new(&mut db_impl);
+    let initial_set_hash = db.get_utxo_set_hash(&db.best_block_hash()).unwrap().unwrap();
 
     // let's check that each tx_input exists in the db. Secure the spent utxos.
     let spent_utxos = expected_tx_inputs
@@ -207,6 +203,17 @@ fn utxo_and_undo_test(#[case] seed: Seed) {
         assert_eq!(db.utxo(input.outpoint()), None);
     });
 
+    // check that the spent utxos are removed from the utxo set hash
+    {
+        let mut expected_set_hash = initial_set_hash.clone();
+        expected_tx_inputs.iter().zip(spent_utxos.iter()).for_each(|(input, utxo)| {
+            expected_set_hash.remove_utxo(input.outpoint(), utxo);
+        });
+        let set_hash = db.get_utxo_set_hash(&block.get_id().into()).unwrap().unwrap();
+        assert_eq!(set_hash.digest(), expected_set_hash.digest());
+        assert_ne!(set_hash.digest(), initial_set_hash.digest());
+    }
+
     // save the undo data to the db.
     {
         db.set_undo_data(block.get_id(), &block_undo).unwrap();
@@ -264,6 +271,10 @@ fn utxo_and_undo_test(#[case] seed: Seed) {
         // remove the block undo file
         db.del_undo_data(block.get_id()).unwrap();
         assert_eq!(db.get_undo_data(block.get_id()), Ok(None));
+
+        // the utxo set is back to its initial state
+        let set_hash = db.get_utxo_set_hash(&current_best_block_id).unwrap().unwrap();
+        assert_eq!(set_hash.digest(), initial_set_hash.digest());
     }
 
     // check that all the expected_tx_inputs exists, and the same utxo is saved.
@@ -324,6 +335,7 @@ fn test_batch_write(#[case] seed: Seed) {
     let utxos = ConsumedUtxoCache {
         container: utxos,
         best_block: new_best_block_hash,
+        set_hash_delta: Default::default(),
     };
 
     utxo_db.batch_write(utxos.clone()).unwrap();
@@ -345,6 +357,29 @@ fn test_batch_write(#[case] seed: Seed) {
     assert_eq!(utxo_db.best_block_hash(), new_best_block_hash);
 }
 
+#[rstest]
+#[trace]
+#[case(Seed::from_entropy())]
+fn batch_write_without_set_hash(#[case] seed: Seed) {
+    let mut rng = make_seedable_rng(seed);
+    let best_block_hash = Id::new(H256::random_using(&mut rng));
+
+    let mut db_interface = UtxosDBInMemoryImpl::new(best_block_hash, Default::default());
+    let mut utxo_db = UtxosDB::new(&mut db_interface);
+    utxo_db.del_utxo_set_hash(&best_block_hash).unwrap();
+
+    let utxos = ConsumedUtxoCache {
+        container: create_utxo_entries(&mut rng, 10),
+        best_block: Id::new(H256::random_using(&mut rng)),
+        set_hash_delta: Default::default(),
+    };
+
+    assert_eq!(
+        utxo_db.batch_write(utxos),
+        Err(MissingUtxoSetHash(best_block_hash))
+    );
+}
+
 #[rstest]
 #[trace]
 #[case(Seed::from_entropy())]
@@ -363,6 +398,7 @@ fn try_flush_non_dirty_utxo(#[case] seed: Seed) {
     let cache = ConsumedUtxoCache {
         container: map,
         best_block: Id::new(H256::random_using(&mut rng)),
+        set_hash_delta: Default::default(),
     };
 
     utxo_db.batch_write(cache).unwrap();
@@ -391,6 +427,7 @@ fn try_flush_spent_utxo(#[case] seed: Seed) {
     let cache = ConsumedUtxoCache {
         container: map,
         best_block: Id::new(H256::random_using(&mut rng)),
+        set_hash_delta: Default::default(),
     };
 
     utxo_db.batch_write(cache).unwrap();
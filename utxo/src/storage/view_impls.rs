@@ -55,6 +55,16 @@ impl<S: UtxosStorageWrite> FlushableUtxoView for UtxosDB<S> {
                 };
             }
         }
+
+        // the stored hash always corresponds to the utxo set at the current best block
+        let best_block = self.best_block_hash();
+        let mut set_hash = self
+            .0
+            .get_utxo_set_hash(&best_block)?
+            .ok_or(crate::Error::MissingUtxoSetHash(best_block))?;
+        set_hash.combine(&utxos.set_hash_delta);
+        self.0.set_utxo_set_hash(&utxos.best_block, &set_hash)?;
+
         self.0.set_best_block_for_utxos(&utxos.best_block)?;
         Ok(())
     }
@@ -217,6 +217,7 @@ fn check_write_utxo(
     let single_entry_cache = ConsumedUtxoCache {
         container: single_entry_map,
         best_block: Id::new(H256::random_using(rng)),
+        set_hash_delta: Default::default(),
     };
     let res = parent.batch_write(single_entry_cache);
     let entry = parent.utxos.get(key);